@@ -0,0 +1,40 @@
+//! Decode frames from a Beast binary server, such as `dump1090 --net` on port 30005
+//!
+//! ```text
+//! > cargo r --example beast_client -- [HOST:PORT]
+//! ```
+//!
+//! The messages are decoded with `adsb_deku::beast`, Mode A/C messages are skipped.
+
+use std::io::{self, Read};
+use std::net::TcpStream;
+
+use adsb_deku::beast::BeastDecoder;
+use adsb_deku::Frame;
+
+fn main() -> io::Result<()> {
+    let address = std::env::args().nth(1).unwrap_or_else(|| "localhost:30005".to_string());
+    let mut stream = TcpStream::connect(address)?;
+
+    let mut decoder = BeastDecoder::default();
+    let mut buf = [0; 4096];
+    loop {
+        let len = stream.read(&mut buf)?;
+        if len == 0 {
+            return Ok(());
+        }
+        for beast in decoder.decode(&buf[..len]) {
+            let Some(avr) = beast.ok().and_then(|beast| beast.to_avr()) else {
+                continue;
+            };
+            let bytes = avr.bytes();
+            match Frame::from_bytes(bytes) {
+                Ok(frame) => {
+                    let timestamp = avr.timestamp.unwrap_or(0);
+                    println!("@{timestamp:012x} {}\n{frame}", hex::encode(bytes));
+                }
+                Err(e) => eprintln!("{e}"),
+            }
+        }
+    }
+}
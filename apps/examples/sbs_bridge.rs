@@ -0,0 +1,68 @@
+//! Convert AVR lines from stdin into SBS-1 (BaseStation) `MSG` lines on stdout
+//!
+//! ```text
+//! > nc localhost 30002 | cargo r --example sbs_bridge | nc -lk 30003
+//! ```
+//!
+//! The messages are the ones of [`SbsMessage::from_frame`], as `1090 --sbs`, with UTC dates.
+//!
+//! Aircraft addresses given as arguments are not sent, or sent with a pseudonym address and
+//! without callsign with `--anonymize`:
+//!
+//! ```text
+//! > nc localhost 30002 | cargo r --example sbs_bridge -- --anonymize a1b2c3 | nc -lk 30003
+//! ```
+
+use std::io::{self, BufRead, Write};
+use std::time::SystemTime;
+
+use adsb_deku::{avr, Frame};
+use rsadsb_common::altitude::AltitudeSource;
+use rsadsb_common::privacy::{PrivacyAction, PrivacyFilter};
+use rsadsb_common::sbs::SbsMessage;
+use rsadsb_common::{timestamp, Airplanes};
+
+fn main() -> io::Result<()> {
+    let mut airplanes = Airplanes::new();
+    let privacy = privacy_filter()?;
+    let mut stdout = io::stdout().lock();
+
+    for line in io::stdin().lock().lines() {
+        let Ok(Some(avr)) = avr::parse_line(&line?) else {
+            continue;
+        };
+        let Ok(frame) = Frame::from_bytes(avr.bytes()) else {
+            continue;
+        };
+        airplanes.action(frame.clone());
+        let Some(message) = SbsMessage::from_frame(&frame, &airplanes, AltitudeSource::Baro)
+            .and_then(|message| privacy.apply_sbs(message))
+        else {
+            continue;
+        };
+
+        let (date, time) = timestamp::sbs(timestamp::utc(SystemTime::now()));
+        writeln!(stdout, "{}", message.to_line(Some((&date, &time))))?;
+    }
+
+    Ok(())
+}
+
+/// Filter of the addresses given as arguments
+fn privacy_filter() -> io::Result<PrivacyFilter> {
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    let action = if let Some(index) = args.iter().position(|arg| arg == "--anonymize") {
+        args.remove(index);
+        PrivacyAction::Anonymize
+    } else {
+        PrivacyAction::Drop
+    };
+    let mut filter = PrivacyFilter::new(action);
+    for arg in args {
+        let icao = arg.parse().map_err(|_| {
+            io::Error::new(io::ErrorKind::InvalidInput, format!("invalid ICAO address: {arg}"))
+        })?;
+        filter.block(icao);
+    }
+    Ok(filter)
+}
@@ -0,0 +1,34 @@
+//! Decode and display every frame of an AVR file
+//!
+//! ```text
+//! > cargo r --example simple_decode -- [AVR_FILE]
+//! ```
+//!
+//! Without `AVR_FILE`, the frames recorded for the `adsb_deku` tests are used.
+
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+
+use adsb_deku::{avr, Frame};
+
+/// Frames recorded for the `adsb_deku` tests
+const RECORDED: &str =
+    concat!(env!("CARGO_MANIFEST_DIR"), "/../libadsb_deku/tests/lax-messages.txt");
+
+fn main() -> io::Result<()> {
+    let path = std::env::args().nth(1).unwrap_or_else(|| RECORDED.to_string());
+    let reader = BufReader::new(File::open(path)?);
+
+    for line in reader.lines() {
+        // blank, comment and garbage lines are skipped
+        let Ok(Some(avr)) = avr::parse_line(&line?) else {
+            continue;
+        };
+        match Frame::from_bytes(avr.bytes()) {
+            Ok(frame) => println!("{frame}"),
+            Err(e) => eprintln!("{e}"),
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,58 @@
+//! Track aircraft from an AVR file with `rsadsb_common`, and display all tracked aircraft
+//!
+//! ```text
+//! > cargo r --example track_aircraft -- [AVR_FILE] [LAT] [LONG]
+//! ```
+//!
+//! Without `AVR_FILE`, the frames recorded for the `adsb_deku` tests are used. The receiver
+//! position `LAT` `LONG` is used for the distance of aircraft.
+
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+
+use adsb_deku::{avr, Frame};
+use rsadsb_common::Airplanes;
+
+/// Frames recorded for the `adsb_deku` tests
+const RECORDED: &str =
+    concat!(env!("CARGO_MANIFEST_DIR"), "/../libadsb_deku/tests/lax-messages.txt");
+
+fn main() -> io::Result<()> {
+    let mut args = std::env::args().skip(1);
+    let path = args.next().unwrap_or_else(|| RECORDED.to_string());
+    let lat_long = args.next().zip(args.next()).map(|(lat, long)| {
+        (lat.parse().expect("invalid latitude"), long.parse().expect("invalid longitude"))
+    });
+
+    let mut builder = Airplanes::builder();
+    if let Some(lat_long) = lat_long {
+        builder = builder.position(lat_long);
+    }
+    let mut airplanes = builder.build();
+
+    let reader = BufReader::new(File::open(path)?);
+    for line in reader.lines() {
+        let Ok(Some(avr)) = avr::parse_line(&line?) else {
+            continue;
+        };
+        if let Ok(frame) = Frame::from_bytes(avr.bytes()) {
+            airplanes.action(frame);
+        }
+    }
+
+    println!("{} aircraft", airplanes.len());
+    for (icao, state) in airplanes.iter() {
+        let callsign = state.callsign.as_deref().unwrap_or("");
+        let squawk = state.squawk.map(|squawk| format!("{squawk:04x}")).unwrap_or_default();
+        print!("[{icao}] {callsign:8} {squawk:4} messages: {:6}", state.num_messages);
+        if let Some(position) = state.coords.position {
+            print!(" position: ({:.4}, {:.4})", position.latitude, position.longitude);
+        }
+        if let Some(kilo_distance) = state.coords.kilo_distance {
+            print!(" distance: {kilo_distance:.1}km");
+        }
+        println!();
+    }
+
+    Ok(())
+}
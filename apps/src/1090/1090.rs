@@ -1,8 +1,21 @@
-use std::io::{BufRead, BufReader};
-use std::net::TcpStream;
+use std::io::{ErrorKind, Write};
+use std::path::PathBuf;
+use std::time::{Duration, Instant, SystemTime};
 
-use adsb_deku::Frame;
+use adsb_deku::{DfFilter, Frame, ICAO};
+use anyhow::{Context, Result};
 use clap::Parser;
+use rsadsb_apps::input::{parse_avr, LineReader, Source};
+use rsadsb_apps::privacy::PrivacyArgs;
+use rsadsb_apps::service::Service;
+use rsadsb_common::altitude::AltitudeSource;
+use rsadsb_common::privacy::PrivacyFilter;
+use rsadsb_common::sbs::SbsMessage;
+use rsadsb_common::{timestamp, Airplanes};
+use time::UtcOffset;
+
+/// Seconds without a frame before an aircraft of `--sbs` is removed
+const SBS_FILTER_TIME: u64 = 120;
 
 #[derive(Debug, Parser)]
 #[command(
@@ -18,6 +31,12 @@ struct Options {
     /// port of ADS-B demodulated bytes server
     #[arg(long, default_value = "30002")]
     port: u16,
+    /// Read from this Unix domain socket instead of `--host` and `--port`
+    #[arg(long, conflicts_with = "stdin")]
+    unix_socket: Option<PathBuf>,
+    /// Read from the standard input instead of `--host` and `--port`
+    #[arg(long)]
+    stdin: bool,
     /// Panic on adsb_deku::Frame::fmt::Display not implemented
     #[arg(long)]
     panic_display: bool,
@@ -27,42 +46,166 @@ struct Options {
     /// Display debug of adsb::Frame
     #[arg(long)]
     debug: bool,
+    /// Display the AVR line of each frame above it, such as `*8d40621d58c382d690c8ac2863a7;`
+    #[arg(long)]
+    show_raw: bool,
+    /// Only display frames from this aircraft address, such as `a1b2c3`
+    #[arg(long)]
+    icao: Option<ICAO>,
+    /// Only display frames with these Downlink Formats, such as `17,18` or `0,4,5,11,16-21`
+    #[arg(long, default_value = "0-31")]
+    accept_df: DfFilter,
+    /// Display the BaseStation (SBS-1) line of each frame instead, such as for Virtual Radar
+    /// Server
+    ///
+    /// The positions are decoded from the previous frames of the aircraft, and the frames of
+    /// aircraft not yet seen in a DF11/17 frame are skipped.
+    #[arg(long, conflicts_with_all = ["debug", "show_raw"])]
+    sbs: bool,
+    /// Altitude of the `--sbs` lines: baro, gnss or gnss-corrected
+    #[arg(long, default_value = "baro")]
+    sbs_altitude: AltitudeSource,
+    /// Display the barometric altitudes at or above this transition altitude (ft) as flight
+    /// levels, such as FL350
+    #[arg(long)]
+    transition_altitude: Option<i32>,
+    #[command(flatten)]
+    privacy: PrivacyArgs,
 }
 
-fn main() {
+impl Options {
+    /// Source of the lines, from `--unix-socket`, `--stdin`, or `--host` and `--port`
+    fn source(&self) -> Source {
+        if let Some(path) = &self.unix_socket {
+            Source::Unix(path.clone())
+        } else if self.stdin {
+            Source::Stdin
+        } else {
+            Source::Tcp { host: self.host.clone(), port: self.port }
+        }
+    }
+}
+
+/// Aircraft address of `frame`
+///
+/// This is the AA field for DF11/17/18, and the address recovered from the parity for the other
+/// DFs.
+fn frame_icao(frame: &Frame) -> ICAO {
+    frame.announced_icao().unwrap_or_else(|| ICAO::from(frame.crc))
+}
+
+/// Return true if `frame` can be displayed with the `--privacy-*` options
+///
+/// Since the bytes are displayed as is, the frames of filtered aircraft are never displayed, even
+/// with `--privacy-anonymize`. The frames that don't decode have no known address, and are only
+/// displayed without `--privacy-allow`.
+fn allows(privacy: Option<&PrivacyFilter>, frame: Option<&Frame>) -> bool {
+    privacy.map_or(true, |privacy| match frame {
+        Some(frame) => privacy.allows_frame(frame),
+        None => privacy.allows_unknown(),
+    })
+}
+
+/// Display `bytes` as hex, or as an AVR line with `--show-raw`
+fn print_raw(bytes: &[u8], show_raw: bool) {
+    if show_raw {
+        println!("*{};", hex::encode(bytes));
+    } else {
+        println!("{}", hex::encode(bytes));
+    }
+}
+
+/// BaseStation message of `bytes`, after updating `airplanes`
+fn sbs_message(
+    airplanes: &mut Airplanes,
+    bytes: &[u8],
+    altitude: AltitudeSource,
+) -> Option<SbsMessage> {
+    let frame = Frame::from_bytes(bytes).ok()?;
+    airplanes.action(frame.clone());
+    SbsMessage::from_frame(&frame, airplanes, altitude)
+}
+
+fn main() -> Result<()> {
     let options = Options::parse();
-    let stream = TcpStream::connect((options.host, options.port)).unwrap();
-    stream.set_read_timeout(Some(std::time::Duration::from_millis(50))).unwrap();
-    let mut reader = BufReader::new(stream);
-    let mut input = String::new();
+    let mut service = Service::new().context("unable to catch SIGTERM")?;
+    let source = options.source();
+    let reader = source
+        .open(Duration::from_millis(50))
+        .with_context(|| format!("unable to open: {source}"))?;
+    let mut reader = LineReader::new(reader);
+    let mut airplanes = Airplanes::new();
+    let mut last_prune = Instant::now();
+    let utc_offset = UtcOffset::current_local_offset().unwrap_or(UtcOffset::UTC);
+    let privacy = options.privacy.filter();
+    service.ready();
 
     loop {
-        input.clear();
-        if let Ok(len) = reader.read_line(&mut input) {
-            if len == 0 {
+        service.keep_alive();
+        if service.terminated() {
+            break;
+        }
+        let input = match reader.read_line() {
+            Ok(input) => input,
+            // the server or stdin is closed
+            Err(e) if e.kind() == ErrorKind::UnexpectedEof => break,
+            Err(e) => return Err(e).with_context(|| format!("unable to read: {source}")),
+        };
+        if let Some(input) = input {
+            // convert from avr line -> bytes
+            let Ok(Some(avr)) = parse_avr(&input, &options.accept_df) else {
+                continue;
+            };
+            let bytes = avr.bytes();
+            if options.sbs {
+                let message = sbs_message(&mut airplanes, bytes, options.sbs_altitude)
+                    .filter(|message| options.icao.map_or(true, |icao| icao == message.icao))
+                    .and_then(|message| match &privacy {
+                        Some(privacy) => privacy.apply_sbs(message),
+                        None => Some(message),
+                    });
+                if let Some(message) = message {
+                    let now = timestamp::local(SystemTime::now(), utc_offset);
+                    let (date, time) = timestamp::sbs(now);
+                    println!("{}", message.to_line(Some((&date, &time))));
+                }
+                if last_prune.elapsed() >= Duration::from_secs(1) {
+                    airplanes.prune(SBS_FILTER_TIME);
+                    last_prune = Instant::now();
+                }
                 continue;
             }
-            // convert from string hex -> bytes
-            let hex = &mut input.to_string()[1..len - 2].to_string();
-            println!("{}", hex.to_lowercase());
-            let bytes = if let Ok(bytes) = hex::decode(hex) {
-                bytes
-            } else {
+            let frame = Frame::from_bytes(bytes);
+            if !allows(privacy.as_ref(), frame.as_ref().ok()) {
                 continue;
-            };
+            }
+            // without `--icao`, also the frames that don't decode
+            if options.icao.is_none() {
+                print_raw(bytes, options.show_raw);
+            }
 
             // check for all 0's
             if bytes.iter().all(|&b| b == 0) {
                 continue;
             }
 
-            // decode
-            match Frame::from_bytes(&bytes) {
+            match frame {
                 Ok(frame) => {
+                    if let Some(icao) = options.icao {
+                        if frame_icao(&frame) != icao {
+                            continue;
+                        }
+                        print_raw(bytes, options.show_raw);
+                    }
                     if options.debug {
                         println!("{frame:#?}");
                     }
-                    println!("{frame}");
+                    match options.transition_altitude {
+                        Some(transition_altitude) => {
+                            println!("{}", frame.display_flight_levels(transition_altitude));
+                        }
+                        None => println!("{frame}"),
+                    }
                     assert!(
                         !((frame.to_string() == "") && options.panic_display),
                         "[E] fmt::Display not implemented"
@@ -72,7 +215,40 @@ fn main() {
                     assert!(!options.panic_decode, "[E] {e}");
                 }
             }
-            input.clear();
         }
     }
+
+    service.stopping();
+    std::io::stdout().flush().context("unable to write: stdout")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use rsadsb_common::privacy::PrivacyAction;
+
+    use super::*;
+
+    /// identification of 40621d
+    const IDENTIFICATION: [u8; 14] =
+        [0x8d, 0x40, 0x62, 0x1d, 0x58, 0xc3, 0x82, 0xd6, 0x90, 0xc8, 0xac, 0x28, 0x63, 0xa7];
+
+    #[test]
+    fn privacy() {
+        let frame = Frame::from_bytes(&IDENTIFICATION).unwrap();
+        assert!(allows(None, Some(&frame)));
+        assert!(allows(None, None));
+
+        let mut filter = PrivacyFilter::new(PrivacyAction::Anonymize);
+        filter.block(ICAO([0x40, 0x62, 0x1d]));
+        assert!(!allows(Some(&filter), Some(&frame)));
+        assert!(allows(Some(&filter), None));
+
+        let mut filter = PrivacyFilter::new(PrivacyAction::Drop);
+        filter.allow(ICAO([0xa0, 0x8f, 0x94]));
+        assert!(!allows(Some(&filter), Some(&frame)));
+        assert!(!allows(Some(&filter), None));
+        filter.allow(ICAO([0x40, 0x62, 0x1d]));
+        assert!(allows(Some(&filter), Some(&frame)));
+    }
 }
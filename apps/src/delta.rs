@@ -0,0 +1,268 @@
+//! Newline-delimited JSON events of the changes of [`Airplanes`], for piping into other processes
+//!
+//! A [`DeltaWriter`] compares the aircraft with the ones of its previous
+//! [`DeltaWriter::write`], and writes one line for each added, updated or removed aircraft. Only
+//! the changed fields are written, a field without a value anymore is `null`:
+//! ```json
+//! {"event":"added","icao":"a1b2c3","fields":{"altitude":37000,"callsign":"UAL123"}}
+//! {"event":"updated","icao":"a1b2c3","fields":{"altitude":36975,"lat":35.12,"long":-80.4}}
+//! {"event":"removed","icao":"a1b2c3"}
+//! ```
+//!
+//! The `altitude` is the one of [`DeltaWriter::with_altitude_source`], barometric by default.
+//!
+//! Such that a consumer is a few lines of shell or Python, without a HTTP or WebSocket server:
+//! ```text
+//! > some_app | jq -c 'select(.event == "added")'
+//! ```
+
+use std::borrow::Borrow;
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::{self, Write};
+
+use adsb_deku::ICAO;
+use rsadsb_common::altitude::AltitudeSource;
+use rsadsb_common::{AirplaneState, Airplanes};
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+
+/// Fields of an aircraft, by name
+type Fields = Map<String, Value>;
+
+/// Line of [`self`]
+#[derive(Serialize)]
+struct Event<'a> {
+    event: &'a str,
+    icao: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    fields: Option<Fields>,
+}
+
+/// Writer of the events of [`self`]
+#[derive(Debug)]
+pub struct DeltaWriter<W> {
+    writer: W,
+    /// Fields written for each aircraft
+    last: BTreeMap<ICAO, Fields>,
+    altitude_source: AltitudeSource,
+}
+
+impl<W: Write> DeltaWriter<W> {
+    /// Writer without any aircraft, the first [`Self::write`] adds all of them
+    pub fn new(writer: W) -> Self {
+        Self { writer, last: BTreeMap::new(), altitude_source: AltitudeSource::default() }
+    }
+
+    /// Write the `altitude` from `source`
+    #[must_use]
+    pub fn with_altitude_source(mut self, source: AltitudeSource) -> Self {
+        self.altitude_source = source;
+        self
+    }
+
+    /// Write the events since the previous call, and flush the writer
+    ///
+    /// Returns the amount of events written.
+    pub fn write(&mut self, airplanes: &Airplanes) -> io::Result<usize> {
+        self.write_states(airplanes.iter().map(|(icao, state)| (*icao, state)))
+    }
+
+    /// [`Self::write`] of the aircraft of `states`, such as from [`PrivacyFilter::states`]
+    ///
+    /// [`PrivacyFilter::states`]: rsadsb_common::privacy::PrivacyFilter::states
+    pub fn write_states<S: Borrow<AirplaneState>>(
+        &mut self,
+        states: impl IntoIterator<Item = (ICAO, S)>,
+    ) -> io::Result<usize> {
+        let mut events = 0;
+        let mut current = BTreeSet::new();
+        for (icao, state) in states {
+            current.insert(icao);
+            let fields = fields(state.borrow(), self.altitude_source);
+            match self.last.get(&icao) {
+                None => {
+                    let added = fields.clone().into_iter().filter(|(_, v)| !v.is_null()).collect();
+                    self.event("added", icao, Some(added))?;
+                }
+                Some(last) => {
+                    let changed: Fields = fields
+                        .iter()
+                        .filter(|(name, value)| last.get(*name) != Some(value))
+                        .map(|(name, value)| (name.clone(), value.clone()))
+                        .collect();
+                    if changed.is_empty() {
+                        continue;
+                    }
+                    self.event("updated", icao, Some(changed))?;
+                }
+            }
+            self.last.insert(icao, fields);
+            events += 1;
+        }
+
+        let removed: Vec<ICAO> =
+            self.last.keys().filter(|icao| !current.contains(*icao)).copied().collect();
+        for icao in removed {
+            self.event("removed", icao, None)?;
+            self.last.remove(&icao);
+            events += 1;
+        }
+
+        self.writer.flush()?;
+        Ok(events)
+    }
+
+    /// Inner writer
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    fn event(&mut self, event: &str, icao: ICAO, fields: Option<Fields>) -> io::Result<()> {
+        let event = Event { event, icao: icao.to_string(), fields };
+        serde_json::to_writer(&mut self.writer, &event)?;
+        self.writer.write_all(b"\n")
+    }
+}
+
+/// Fields of the events, `null` without a value
+fn fields(state: &AirplaneState, altitude_source: AltitudeSource) -> Fields {
+    let position = state.coords.position;
+    let value = json!({
+        "callsign": state.callsign,
+        "squawk": state.squawk.map(|squawk| format!("{squawk:04x}")),
+        "altitude": state.altitude(altitude_source),
+        "lat": position.map(|p| p.latitude),
+        "long": position.map(|p| p.longitude),
+        "track": state.heading.map(tenths),
+        "ground_speed": state.speed.map(tenths),
+        "vertical_rate": state.vert_speed,
+        "on_ground": state.on_ground,
+    });
+    match value {
+        Value::Object(fields) => fields,
+        _ => unreachable!(),
+    }
+}
+
+/// `f32` rounded to a tenth, without the noise of the conversion to `f64` in the JSON
+fn tenths(value: f32) -> f64 {
+    (f64::from(value) * 10.0).round() / 10.0
+}
+
+#[cfg(test)]
+mod tests {
+    use rsadsb_common::privacy::{PrivacyAction, PrivacyFilter};
+    use rsadsb_common::sbs::SbsMessage;
+
+    use super::*;
+
+    fn apply(airplanes: &mut Airplanes, line: &str) {
+        let message = SbsMessage::parse_line(line).unwrap().unwrap();
+        airplanes.apply_sbs(&message);
+    }
+
+    fn lines(writer: &mut DeltaWriter<Vec<u8>>, airplanes: &Airplanes) -> Vec<String> {
+        writer.write(airplanes).unwrap();
+        let lines = String::from_utf8(std::mem::take(&mut writer.writer)).unwrap();
+        lines.lines().map(str::to_string).collect()
+    }
+
+    #[test]
+    fn events() {
+        let mut airplanes = Airplanes::new();
+        let mut writer = DeltaWriter::new(vec![]);
+        assert_eq!(lines(&mut writer, &airplanes), Vec::<String>::new());
+
+        apply(&mut airplanes, "MSG,1,1,1,A1B2C3,1,,,,,UAL123,,,,,,,,,,,");
+        apply(&mut airplanes, "MSG,4,1,1,A1B2C3,1,,,,,,,451,270.3,,,,,,,,");
+        assert_eq!(
+            lines(&mut writer, &airplanes),
+            [
+                r#"{"event":"added","icao":"a1b2c3","fields":{"callsign":"UAL123","ground_speed":451.0,"track":270.3}}"#
+            ]
+        );
+
+        // only the changed fields
+        apply(&mut airplanes, "MSG,5,1,1,A1B2C3,1,,,,,UAL123,37000,,,,,,,,,,");
+        assert_eq!(
+            lines(&mut writer, &airplanes),
+            [r#"{"event":"updated","icao":"a1b2c3","fields":{"altitude":37000}}"#]
+        );
+        assert_eq!(lines(&mut writer, &airplanes), Vec::<String>::new());
+
+        assert_eq!(writer.write(&Airplanes::new()).unwrap(), 1);
+        assert_eq!(
+            String::from_utf8(writer.into_inner()).unwrap(),
+            "{\"event\":\"removed\",\"icao\":\"a1b2c3\"}\n"
+        );
+    }
+
+    #[test]
+    fn privacy() {
+        let mut airplanes = Airplanes::new();
+        apply(&mut airplanes, "MSG,1,1,1,A1B2C3,1,,,,,UAL123,,,,,,,,,,,");
+        apply(&mut airplanes, "MSG,1,1,1,A1B2C4,1,,,,,UAL124,,,,,,,,,,,");
+        let mut filter = PrivacyFilter::new(PrivacyAction::Anonymize).with_salt(0);
+        filter.block(ICAO([0xa1, 0xb2, 0xc3]));
+        let pseudonym = filter.pseudonym(ICAO([0xa1, 0xb2, 0xc3]));
+
+        let mut writer = DeltaWriter::new(vec![]);
+        writer.write_states(filter.states(&airplanes)).unwrap();
+        let lines = String::from_utf8(std::mem::take(&mut writer.writer)).unwrap();
+        assert_eq!(
+            lines.lines().collect::<Vec<_>>(),
+            [
+                format!(r#"{{"event":"added","icao":"{pseudonym}","fields":{{}}}}"#),
+                r#"{"event":"added","icao":"a1b2c4","fields":{"callsign":"UAL124"}}"#.to_string(),
+            ]
+        );
+
+        // sent out with a pseudonym, instead of the address
+        filter.block(ICAO([0xa1, 0xb2, 0xc4]));
+        let pseudonym = filter.pseudonym(ICAO([0xa1, 0xb2, 0xc4]));
+        writer.write_states(filter.states(&airplanes)).unwrap();
+        assert_eq!(
+            String::from_utf8(writer.into_inner()).unwrap(),
+            format!(
+                "{{\"event\":\"added\",\"icao\":\"{pseudonym}\",\"fields\":{{}}}}\n\
+                 {{\"event\":\"removed\",\"icao\":\"a1b2c4\"}}\n"
+            )
+        );
+    }
+
+    #[test]
+    fn altitude_source() {
+        let mut airplanes = Airplanes::new();
+        // barometric altitude of 38000 ft, then GNSS 550 ft above the barometric altitude
+        for frame in ["8d40621d58c382d690c8ac2863a7", "8d40621d994409940838175b284f"] {
+            let bytes = hex::decode(frame).unwrap();
+            airplanes.action(adsb_deku::Frame::from_bytes(&bytes).unwrap());
+        }
+
+        for (source, altitude) in
+            [(AltitudeSource::Baro, 38000), (AltitudeSource::GnssCorrected, 38550)]
+        {
+            let mut writer = DeltaWriter::new(vec![]).with_altitude_source(source);
+            let line: Value = serde_json::from_str(&lines(&mut writer, &airplanes)[0]).unwrap();
+            assert_eq!(line["fields"]["altitude"], altitude);
+        }
+    }
+
+    #[test]
+    fn squawk() {
+        let mut airplanes = Airplanes::new();
+        // airborne position, then DF5 squawking 7700
+        for frame in ["8d40621d58c382d690c8ac2863a7", "28000aaa0ac6d4"] {
+            let bytes = hex::decode(frame).unwrap();
+            airplanes.action(adsb_deku::Frame::from_bytes(&bytes).unwrap());
+        }
+        let mut writer = DeltaWriter::new(vec![]);
+        let line: Value = serde_json::from_str(&lines(&mut writer, &airplanes)[0]).unwrap();
+        assert_eq!(line["fields"]["squawk"], "7700");
+
+        // the same from a decimal SBS squawk
+        apply(&mut airplanes, "MSG,6,1,1,40621D,1,,,,,,,,,,,,0356,0,0,0,0");
+        let line: Value = serde_json::from_str(&lines(&mut writer, &airplanes)[0]).unwrap();
+        assert_eq!(line["fields"]["squawk"], "0356");
+    }
+}
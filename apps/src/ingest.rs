@@ -0,0 +1,465 @@
+//! Reading frames on a separate thread, decoupled from the rendering of the apps
+//!
+//! At high message rates, such as 3000+ frames/s of busy sites, reading and parsing each line
+//! between draws falls behind the input. An [`Ingest`] thread reads and parses the lines of a
+//! [`LineReader`] into a bounded channel, and the app takes all frames received since the last
+//! draw with [`Ingest::drain`].
+//!
+//! The lines are AVR frames, or SBS (BaseStation) messages with [`InputFormat::Sbs`]. Beast
+//! binary messages are read from a [`BeastReader`] with [`Ingest::spawn_beast`] instead.
+//!
+//! # Drop policy
+//!
+//! When the channel is full, because the app fell behind for longer than the channel capacity,
+//! the newest frames are dropped and counted in [`IngestStats::dropped`]. The input keeps being
+//! read, so the server doesn't disconnect a slow client and the delay of the frames in the
+//! channel stays bounded by the capacity. Frames already in the channel are kept, in order.
+
+use std::io::{self, BufRead, ErrorKind};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, SyncSender, TryRecvError, TrySendError};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::thread;
+
+use adsb_deku::avr::AvrFrame;
+use adsb_deku::{crc, DfFilter};
+use clap::ValueEnum;
+use rsadsb_common::sbs::SbsMessage;
+use tracing::debug;
+
+use crate::input::{parse_avr, BeastReader, LineReader};
+
+/// Default capacity of the channel, a few seconds of a busy site
+pub const DEFAULT_CAPACITY: usize = 8192;
+
+/// Format of the input read by [`Ingest`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum InputFormat {
+    /// AVR frames, such as from port 30002 of dump1090
+    #[default]
+    Avr,
+    /// SBS (BaseStation) `MSG` lines, such as from port 30003 of dump1090, without the frames
+    Sbs,
+    /// Beast binary messages, such as from port 30005 of dump1090 and readsb
+    Beast,
+}
+
+/// Line parsed by [`Ingest`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum Message {
+    Avr(AvrFrame),
+    Sbs(SbsMessage),
+}
+
+/// Counters of an [`Ingest`] thread, see [`Ingest::stats`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct IngestStats {
+    /// Frames sent to the channel
+    pub received: u64,
+    /// Frames dropped because the channel was full
+    pub dropped: u64,
+    /// Lines that aren't a valid AVR frame or SBS message, and unknown or truncated Beast messages
+    pub invalid: u64,
+    /// See [`LineReader::resyncs`] and [`BeastReader::resyncs`]
+    pub resyncs: u64,
+    /// AVR frames with a checked CRC (DF11/17/18), see [`crc_valid`]
+    pub crc_checked: u64,
+    /// `crc_checked` frames with an invalid CRC
+    pub crc_failures: u64,
+}
+
+/// State shared with the thread
+#[derive(Debug)]
+struct Shared {
+    received: AtomicU64,
+    dropped: AtomicU64,
+    invalid: AtomicU64,
+    resyncs: AtomicU64,
+    crc_checked: AtomicU64,
+    crc_failures: AtomicU64,
+    accept_df: Mutex<DfFilter>,
+    quiet: AtomicBool,
+}
+
+/// Options of [`Ingest::spawn`]
+#[derive(Debug, Clone)]
+pub struct IngestOptions {
+    /// Format of the lines, not used by [`Ingest::spawn_beast`]
+    pub format: InputFormat,
+    /// Frames with other Downlink Formats are dropped, see [`parse_avr`]. Not used for SBS
+    /// messages, which don't contain the frame.
+    pub accept_df: DfFilter,
+    /// Frames kept in the channel, see [`self`]
+    pub capacity: usize,
+    /// Don't log each line and invalid line
+    pub quiet: bool,
+}
+
+impl Default for IngestOptions {
+    fn default() -> Self {
+        Self {
+            format: InputFormat::Avr,
+            accept_df: DfFilter::all(),
+            capacity: DEFAULT_CAPACITY,
+            quiet: false,
+        }
+    }
+}
+
+/// Receiving side of the thread reading frames, see [`self`]
+#[derive(Debug)]
+pub struct Ingest {
+    receiver: Receiver<io::Result<Message>>,
+    shared: Arc<Shared>,
+}
+
+impl Ingest {
+    /// Read and parse the lines of `reader` from a new thread
+    ///
+    /// The thread stops when the input is closed, or when the [`Ingest`] is dropped and a frame is
+    /// received.
+    pub fn spawn<R>(reader: LineReader<R>, options: IngestOptions) -> Self
+    where
+        R: BufRead + Send + 'static,
+    {
+        let format = options.format;
+        Self::spawn_with(options, move |sender, shared| read_frames(reader, format, sender, shared))
+    }
+
+    /// Read the Beast messages of `reader` from a new thread, see [`Self::spawn`]
+    ///
+    /// The Mode A/C messages are dropped.
+    pub fn spawn_beast<R>(reader: BeastReader<R>, options: IngestOptions) -> Self
+    where
+        R: BufRead + Send + 'static,
+    {
+        Self::spawn_with(options, move |sender, shared| read_beast(reader, sender, shared))
+    }
+
+    fn spawn_with<F>(options: IngestOptions, body: F) -> Self
+    where
+        F: FnOnce(&SyncSender<io::Result<Message>>, &Shared) + Send + 'static,
+    {
+        let (sender, receiver) = mpsc::sync_channel(options.capacity);
+        let shared = Arc::new(Shared {
+            received: AtomicU64::new(0),
+            dropped: AtomicU64::new(0),
+            invalid: AtomicU64::new(0),
+            resyncs: AtomicU64::new(0),
+            crc_checked: AtomicU64::new(0),
+            crc_failures: AtomicU64::new(0),
+            accept_df: Mutex::new(options.accept_df),
+            quiet: AtomicBool::new(options.quiet),
+        });
+        let thread_shared = Arc::clone(&shared);
+        thread::spawn(move || body(&sender, &thread_shared));
+        Self { receiver, shared }
+    }
+
+    /// Change the `accept_df` and `quiet` of [`IngestOptions`], for the next lines read
+    pub fn set_filter(&self, accept_df: DfFilter, quiet: bool) {
+        *self.shared.accept_df.lock().unwrap_or_else(PoisonError::into_inner) = accept_df;
+        self.shared.quiet.store(quiet, Ordering::Relaxed);
+    }
+
+    /// Move the frames received since the last call to the end of `frames`
+    ///
+    /// Returns an `ErrorKind::UnexpectedEof` error when the input is closed, or the error of the
+    /// failed read, after all the frames read before were returned.
+    pub fn drain(&self, frames: &mut Vec<Message>) -> io::Result<()> {
+        loop {
+            match self.receiver.try_recv() {
+                Ok(Ok(frame)) => frames.push(frame),
+                Ok(Err(e)) => return Err(e),
+                Err(TryRecvError::Empty) => return Ok(()),
+                Err(TryRecvError::Disconnected) => return Err(ErrorKind::UnexpectedEof.into()),
+            }
+        }
+    }
+
+    #[must_use]
+    pub fn stats(&self) -> IngestStats {
+        IngestStats {
+            received: self.shared.received.load(Ordering::Relaxed),
+            dropped: self.shared.dropped.load(Ordering::Relaxed),
+            invalid: self.shared.invalid.load(Ordering::Relaxed),
+            resyncs: self.shared.resyncs.load(Ordering::Relaxed),
+            crc_checked: self.shared.crc_checked.load(Ordering::Relaxed),
+            crc_failures: self.shared.crc_failures.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Body of the [`Ingest`] thread
+fn read_frames<R: BufRead>(
+    mut reader: LineReader<R>,
+    format: InputFormat,
+    sender: &SyncSender<io::Result<Message>>,
+    shared: &Shared,
+) {
+    loop {
+        let line = match reader.next_line() {
+            Ok(Some(line)) => line,
+            // read timeout
+            Ok(None) => continue,
+            // closed or broken input, such as a reset connection, reading again would fail again
+            Err(e) => {
+                // blocks until the frames before were received, or the Ingest is dropped
+                let _ = sender.send(Err(e));
+                return;
+            }
+        };
+        let quiet = shared.quiet.load(Ordering::Relaxed);
+        let frame = match format {
+            InputFormat::Avr => {
+                if !quiet {
+                    debug!("avr: {}", line.trim_end());
+                }
+                let accept_df = *shared.accept_df.lock().unwrap_or_else(PoisonError::into_inner);
+                parse_avr(&line, &accept_df)
+                    .map(|frame| frame.map(Message::Avr))
+                    .map_err(|e| format!("invalid avr: {e}"))
+            }
+            InputFormat::Sbs => {
+                if !quiet {
+                    debug!("sbs: {}", line.trim_end());
+                }
+                SbsMessage::parse_line(&line)
+                    .map(|message| message.map(Message::Sbs))
+                    .map_err(|e| format!("invalid sbs: {e}"))
+            }
+            InputFormat::Beast => Err("beast messages are not lines".to_string()),
+        };
+        let frame = match frame {
+            Ok(Some(frame)) => frame,
+            Ok(None) => continue,
+            Err(e) => {
+                if !quiet {
+                    debug!("{e}");
+                }
+                shared.invalid.fetch_add(1, Ordering::Relaxed);
+                continue;
+            }
+        };
+        shared.resyncs.store(reader.resyncs(), Ordering::Relaxed);
+        if !send_frame(frame, sender, shared) {
+            return;
+        }
+    }
+}
+
+/// Body of the [`Ingest`] thread of [`Ingest::spawn_beast`]
+fn read_beast<R: BufRead>(
+    mut reader: BeastReader<R>,
+    sender: &SyncSender<io::Result<Message>>,
+    shared: &Shared,
+) {
+    loop {
+        let beast = match reader.next_frame() {
+            Ok(Some(beast)) => beast,
+            // read timeout
+            Ok(None) => continue,
+            // closed or broken input, such as a reset connection, reading again would fail again
+            Err(e) => {
+                // blocks until the frames before were received, or the Ingest is dropped
+                let _ = sender.send(Err(e));
+                return;
+            }
+        };
+        let quiet = shared.quiet.load(Ordering::Relaxed);
+        shared.resyncs.store(reader.resyncs(), Ordering::Relaxed);
+        let beast = match beast {
+            Ok(beast) => beast,
+            Err(e) => {
+                if !quiet {
+                    debug!("invalid beast: {e}");
+                }
+                shared.invalid.fetch_add(1, Ordering::Relaxed);
+                continue;
+            }
+        };
+        if !quiet {
+            debug!("beast: @{:012x} {:02x?}", beast.timestamp, beast.bytes());
+        }
+        let accept_df = *shared.accept_df.lock().unwrap_or_else(PoisonError::into_inner);
+        let Some(avr) = beast.to_avr().filter(|avr| accept_df.accepts(avr.bytes())) else {
+            continue;
+        };
+        if !send_frame(Message::Avr(avr), sender, shared) {
+            return;
+        }
+    }
+}
+
+/// Send `frame` to the [`Ingest`], counting its CRC check, `false` if the [`Ingest`] was dropped
+fn send_frame(frame: Message, sender: &SyncSender<io::Result<Message>>, shared: &Shared) -> bool {
+    if let Message::Avr(avr) = &frame {
+        if let Some(valid) = crc_valid(avr.bytes()) {
+            shared.crc_checked.fetch_add(1, Ordering::Relaxed);
+            if !valid {
+                shared.crc_failures.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    }
+    match sender.try_send(Ok(frame)) {
+        Ok(()) => shared.received.fetch_add(1, Ordering::Relaxed),
+        Err(TrySendError::Full(_)) => shared.dropped.fetch_add(1, Ordering::Relaxed),
+        Err(TrySendError::Disconnected(_)) => return false,
+    };
+    true
+}
+
+/// CRC of the frames where it isn't overlaid with the aircraft address, `None` for other DFs
+///
+/// The CRC of DF17/18 is 0, and the interrogator code (II or SI, and code label) overlaid on the
+/// CRC of DF11 is below 0x80.
+fn crc_valid(bytes: &[u8]) -> Option<bool> {
+    let crc = crc::modes_checksum(bytes, bytes.len() * 8).ok()?;
+    match bytes.first()? >> 3 {
+        17 | 18 => Some(crc == 0),
+        11 => Some(crc < 0x80),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+
+    const LINE: &str = "*8D40621D58C382D690C8AC2863A7;\n";
+
+    #[test]
+    fn drop_newest() {
+        let input = format!("garbage\n*zz;\n{}", LINE.repeat(10));
+        let reader = LineReader::new(Cursor::new(input.into_bytes()));
+        let options = IngestOptions { capacity: 4, ..IngestOptions::default() };
+        let ingest = Ingest::spawn(reader, options);
+
+        // the thread waits for the closed input to be received
+        while ingest.stats().received + ingest.stats().dropped < 10 {
+            thread::yield_now();
+        }
+        let mut frames = vec![];
+        let error = loop {
+            if let Err(e) = ingest.drain(&mut frames) {
+                break e;
+            }
+        };
+        assert_eq!(error.kind(), ErrorKind::UnexpectedEof);
+        assert_eq!(frames.len(), 4);
+        assert_eq!(
+            ingest.stats(),
+            IngestStats {
+                received: 4,
+                dropped: 6,
+                invalid: 1,
+                resyncs: 1,
+                crc_checked: 10,
+                crc_failures: 0
+            }
+        );
+    }
+
+    #[test]
+    fn accept_df() {
+        let input = format!("{LINE}*5dab3d17d4ba29;\n{LINE}");
+        let reader = LineReader::new(Cursor::new(input.into_bytes()));
+        let options = IngestOptions { accept_df: "17".parse().unwrap(), ..Default::default() };
+        let ingest = Ingest::spawn(reader, options);
+        let mut frames = vec![];
+        while ingest.drain(&mut frames).is_ok() {
+            thread::yield_now();
+        }
+        assert_eq!(frames.len(), 2);
+        let Message::Avr(frame) = &frames[0] else {
+            panic!("expected avr: {:?}", frames[0]);
+        };
+        assert_eq!(frame.bytes()[0], 0x8d);
+    }
+
+    #[test]
+    fn crc_failures() {
+        // DF17 with a flipped bit, DF11, DF4 without a checked CRC
+        let input =
+            format!("{LINE}*8D40621D58C382D690C8AC2863A6;\n*5dab3d17d4ba29;\n*200012b0d96e39;\n");
+        let reader = LineReader::new(Cursor::new(input.into_bytes()));
+        let ingest = Ingest::spawn(reader, IngestOptions::default());
+        let mut frames = vec![];
+        while ingest.drain(&mut frames).is_ok() {
+            thread::yield_now();
+        }
+        assert_eq!(frames.len(), 4);
+        assert_eq!(ingest.stats().crc_checked, 3);
+        assert_eq!(ingest.stats().crc_failures, 1);
+    }
+
+    #[test]
+    fn sbs() {
+        let input =
+            "MSG,1,1,1,A1B2C3,1,,,,,UAL123\nSTA,,1,1,A1B2C3,1\n*8D40621D58C382D690C8AC2863A7;\n";
+        let reader = LineReader::unaligned(Cursor::new(input.as_bytes().to_vec()));
+        let options = IngestOptions { format: InputFormat::Sbs, ..Default::default() };
+        let ingest = Ingest::spawn(reader, options);
+        let mut frames = vec![];
+        while ingest.drain(&mut frames).is_ok() {
+            thread::yield_now();
+        }
+        assert_eq!(frames.len(), 1);
+        let Message::Sbs(message) = &frames[0] else {
+            panic!("expected sbs: {:?}", frames[0]);
+        };
+        assert_eq!(message.callsign.as_deref(), Some("UAL123"));
+        assert_eq!(ingest.stats().invalid, 1);
+    }
+
+    #[test]
+    fn read_error() {
+        /// Input of a reset connection, failing every read
+        struct Reset;
+
+        impl io::Read for Reset {
+            fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+                Err(ErrorKind::ConnectionReset.into())
+            }
+        }
+
+        let reader = io::BufReader::new(io::Read::chain(Cursor::new(LINE), Reset));
+        let ingest = Ingest::spawn(LineReader::new(reader), IngestOptions::default());
+        let mut frames = vec![];
+        let error = loop {
+            if let Err(e) = ingest.drain(&mut frames) {
+                break e;
+            }
+            thread::yield_now();
+        };
+        assert_eq!(error.kind(), ErrorKind::ConnectionReset);
+        assert_eq!(frames.len(), 1);
+    }
+
+    #[test]
+    fn beast() {
+        // DF17 with an escaped timestamp, Mode A/C, unknown type, DF11 not accepted
+        let input = b"\x1a3\x00\x00\x00\x00\x00\x1a\x1a\x80\x8d\x40\x62\x1d\x58\xc3\x82\xd6\x90\xc8\xac\x28\x63\xa7\
+            \x1a1\x00\x00\x00\x00\x00\x00\x80\x0a\x00\
+            \x1a4\x00\
+            \x1a2\x00\x00\x00\x00\x00\x00\x80\x5d\xab\x3d\x17\xd4\xba\x29";
+        let reader = BeastReader::new(Cursor::new(input.to_vec()));
+        let options = IngestOptions { accept_df: "17".parse().unwrap(), ..Default::default() };
+        let ingest = Ingest::spawn_beast(reader, options);
+        let mut frames = vec![];
+        while ingest.drain(&mut frames).is_ok() {
+            thread::yield_now();
+        }
+        assert_eq!(frames.len(), 1);
+        let Message::Avr(frame) = &frames[0] else {
+            panic!("expected avr: {:?}", frames[0]);
+        };
+        assert_eq!(frame.timestamp, Some(0x1a));
+        assert_eq!(frame.bytes()[0], 0x8d);
+        assert_eq!(frame.meta.unwrap().signal_level, Some(0x80));
+        assert_eq!(ingest.stats().invalid, 1);
+        assert_eq!(ingest.stats().crc_checked, 1);
+    }
+}
@@ -0,0 +1,568 @@
+//! Reading demodulated lines or Beast messages of a TCP, Unix socket or standard input [`Source`],
+//! with a read timeout
+
+use std::borrow::Cow;
+use std::fmt;
+use std::io::{self, BufRead, BufReader, ErrorKind, Read};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::Duration;
+
+use adsb_deku::avr::{self, AvrError, AvrFrame};
+use adsb_deku::beast::{BeastDecoder, BeastError, BeastFrame};
+use adsb_deku::DfFilter;
+
+/// Longest partial line kept while waiting for a newline
+const MAX_LINE_LEN: usize = 1024;
+
+/// Timeout of each address of a TCP [`Source`]
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Reader of an opened [`Source`]
+pub type SourceReader = BufReader<Box<dyn Read + Send>>;
+
+/// Where the demodulated lines are read from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Source {
+    /// TCP server, such as port 30002 of dump1090
+    Tcp { host: String, port: u16 },
+    /// Unix domain socket of a server on the same machine
+    Unix(PathBuf),
+    /// Standard input, such as piped from a demodulator
+    Stdin,
+}
+
+impl Source {
+    /// Connect to the source, setting `read_timeout` on the sockets
+    ///
+    /// Standard input doesn't have a read timeout, it is read by a thread instead, see
+    /// [`ChannelReader`].
+    pub fn open(&self, read_timeout: Duration) -> io::Result<SourceReader> {
+        let reader: Box<dyn Read + Send> = match self {
+            Self::Tcp { host, port } => {
+                let mut last_error = io::Error::from(ErrorKind::AddrNotAvailable);
+                let mut stream = None;
+                for addr in (host.as_str(), *port).to_socket_addrs()? {
+                    match TcpStream::connect_timeout(&addr, CONNECT_TIMEOUT) {
+                        Ok(s) => {
+                            stream = Some(s);
+                            break;
+                        }
+                        Err(e) => last_error = e,
+                    }
+                }
+                let stream = stream.ok_or(last_error)?;
+                stream.set_read_timeout(Some(read_timeout))?;
+                Box::new(stream)
+            }
+            #[cfg(unix)]
+            Self::Unix(path) => {
+                let stream = std::os::unix::net::UnixStream::connect(path)?;
+                stream.set_read_timeout(Some(read_timeout))?;
+                Box::new(stream)
+            }
+            #[cfg(not(unix))]
+            Self::Unix(_) => return Err(ErrorKind::Unsupported.into()),
+            Self::Stdin => Box::new(ChannelReader::spawn(io::stdin(), read_timeout)),
+        };
+        Ok(BufReader::new(reader))
+    }
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Tcp { host, port } => write!(f, "{host}:{port}"),
+            Self::Unix(path) => write!(f, "{}", path.display()),
+            Self::Stdin => write!(f, "stdin"),
+        }
+    }
+}
+
+/// Reader of the bytes read by a thread from a blocking `reader`, such as standard input
+///
+/// Reads time out with `ErrorKind::WouldBlock` after `read_timeout` without data, as the sockets
+/// of [`Source::open`], such that the main loop keeps running while the input is idle.
+#[derive(Debug)]
+pub struct ChannelReader {
+    receiver: Receiver<io::Result<Vec<u8>>>,
+    read_timeout: Duration,
+    chunk: Vec<u8>,
+    /// bytes at the start of `chunk` already read
+    pos: usize,
+}
+
+impl ChannelReader {
+    /// Read `reader` on a new thread, until the end of the input or an error
+    pub fn spawn<R: Read + Send + 'static>(mut reader: R, read_timeout: Duration) -> Self {
+        let (sender, receiver) = mpsc::sync_channel(16);
+        thread::spawn(move || {
+            let mut buf = [0; 4096];
+            loop {
+                let chunk = match reader.read(&mut buf) {
+                    Ok(len) => Ok(buf[..len].to_vec()),
+                    Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                    Err(e) => Err(e),
+                };
+                // stop at the end of the input, on errors, or when the reader is dropped
+                let done = !matches!(&chunk, Ok(chunk) if !chunk.is_empty());
+                if sender.send(chunk).is_err() || done {
+                    break;
+                }
+            }
+        });
+        Self { receiver, read_timeout, chunk: Vec::new(), pos: 0 }
+    }
+}
+
+impl Read for ChannelReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.pos == self.chunk.len() {
+            self.chunk = match self.receiver.recv_timeout(self.read_timeout) {
+                Ok(chunk) => chunk?,
+                Err(RecvTimeoutError::Timeout) => return Err(ErrorKind::WouldBlock.into()),
+                // the thread stopped after the end of the input or an error
+                Err(RecvTimeoutError::Disconnected) => Vec::new(),
+            };
+            self.pos = 0;
+        }
+        let len = buf.len().min(self.chunk.len() - self.pos);
+        buf[..len].copy_from_slice(&self.chunk[self.pos..self.pos + len]);
+        self.pos += len;
+        Ok(len)
+    }
+}
+
+/// Line reader that treats read timeouts as "no data yet"
+///
+/// [`BufRead::read_line`] returns an error when a `set_read_timeout` timeout happens in the
+/// middle of a line, and the bytes already read are easily lost. This keeps the partial line
+/// and resumes it on the next call to [`Self::read_line`].
+///
+/// Lines are also realigned on the AVR messages (starting with `*` or `@`), such as after
+/// connecting in the middle of a line or when messages aren't separated by a newline, see
+/// [`Self::resyncs`].
+#[derive(Debug)]
+pub struct LineReader<R> {
+    reader: R,
+    line: Vec<u8>,
+    /// bytes at the start of `line` already returned, removed on the next read
+    consumed: usize,
+    resyncs: u64,
+    /// realign the lines on the AVR messages
+    align_avr: bool,
+    /// the reader is closed, `line` is the rest of the input
+    eof: bool,
+}
+
+impl<R: BufRead> LineReader<R> {
+    pub fn new(reader: R) -> Self {
+        Self { reader, line: Vec::new(), consumed: 0, resyncs: 0, align_avr: true, eof: false }
+    }
+
+    /// Line reader of lines that aren't AVR messages, such as SBS, which are never realigned
+    pub fn unaligned(reader: R) -> Self {
+        Self { align_avr: false, ..Self::new(reader) }
+    }
+
+    /// Read until the next newline, see [`Self::next_line`]
+    pub fn read_line(&mut self) -> io::Result<Option<String>> {
+        Ok(self.next_line()?.map(Cow::into_owned))
+    }
+
+    /// Read until the next newline or AVR message, without allocating for each line
+    ///
+    /// The line borrows the buffer of this reader, which is reused for the next line.
+    ///
+    /// Returns:
+    ///   `Ok(Some(line))`: a complete line, including the newline, a message followed by
+    ///                     another message on the same line, or the last line of the input
+    ///                     without a newline
+    ///   `Ok(None)`:       no complete line yet, the reader timed out
+    ///   `Err(e)`:         `ErrorKind::UnexpectedEof` after the last line when the reader is
+    ///                     closed, or other error
+    pub fn next_line(&mut self) -> io::Result<Option<Cow<'_, str>>> {
+        self.line.drain(..self.consumed);
+        self.consumed = 0;
+        loop {
+            if !self.eof && !self.line.contains(&b'\n') {
+                let bytes = match self.reader.fill_buf() {
+                    Ok(bytes) => bytes,
+                    Err(e) if is_timeout(&e) => return Ok(None),
+                    Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                    Err(e) => return Err(e),
+                };
+                if bytes.is_empty() {
+                    self.eof = true;
+                } else {
+                    let newline = bytes.iter().position(|&b| b == b'\n');
+                    let used = newline.map_or(bytes.len(), |pos| pos + 1);
+                    self.line.extend_from_slice(&bytes[..used]);
+                    self.reader.consume(used);
+                    if newline.is_none() {
+                        self.limit_pending();
+                    }
+                    continue;
+                }
+            }
+            if self.line.is_empty() {
+                return Err(ErrorKind::UnexpectedEof.into());
+            }
+            if let Some(len) = self.align() {
+                self.consumed = len;
+                return Ok(Some(String::from_utf8_lossy(&self.line[..len])));
+            }
+        }
+    }
+
+    /// Align the first complete line of `line` on an AVR message
+    ///
+    /// Return the length of the line or first message, `None` if the line was dropped
+    fn align(&mut self) -> Option<usize> {
+        // without a newline, the last line of the input
+        let mut end =
+            self.line.iter().position(|&b| b == b'\n').map_or(self.line.len(), |pos| pos + 1);
+        if !self.align_avr {
+            return Some(end);
+        }
+        match self.line[..end].iter().position(|b| !b.is_ascii_whitespace()) {
+            // blank line or comment
+            None => return Some(end),
+            Some(start)
+                if matches!(self.line[start], b'#') || is_message_start(self.line[start]) => {}
+            // joined in the middle of a message, skip to the next message
+            Some(_) => {
+                self.resyncs += 1;
+                let Some(next) = self.line[..end].iter().position(|&b| is_message_start(b)) else {
+                    self.line.drain(..end);
+                    return None;
+                };
+                self.line.drain(..next);
+                end -= next;
+            }
+        }
+
+        // messages without a newline in between
+        let line = &self.line[..end];
+        if let Some(separator) =
+            line.windows(2).position(|w| w[0] == b';' && is_message_start(w[1]))
+        {
+            self.resyncs += 1;
+            return Some(separator + 1);
+        }
+        Some(end)
+    }
+
+    /// Drop a partial line over [`MAX_LINE_LEN`], keeping the last message start
+    fn limit_pending(&mut self) {
+        if self.line.len() <= MAX_LINE_LEN {
+            return;
+        }
+        self.resyncs += 1;
+        let keep = self
+            .line
+            .iter()
+            .rposition(|&b| is_message_start(b))
+            .filter(|start| self.line.len() - start <= MAX_LINE_LEN)
+            .unwrap_or(self.line.len());
+        self.line.drain(..keep);
+    }
+
+    /// Amount of times the stream was realigned on an AVR message
+    ///
+    /// This counts lines not starting with a message (such as joining a stream in the middle of a
+    /// line), messages without a newline in between, and partial lines over 1024 bytes.
+    pub fn resyncs(&self) -> u64 {
+        self.resyncs
+    }
+
+    /// Bytes of the current partial line
+    pub fn pending(&self) -> &[u8] {
+        &self.line[self.consumed..]
+    }
+
+    pub fn get_ref(&self) -> &R {
+        &self.reader
+    }
+
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.reader
+    }
+
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+/// Beast message reader that treats read timeouts as "no data yet", see [`adsb_deku::beast`]
+///
+/// The bytes of a message interrupted by a timeout are kept in the [`BeastDecoder`], and the
+/// message is resumed on the next call to [`Self::next_frame`].
+#[derive(Debug)]
+pub struct BeastReader<R> {
+    reader: R,
+    decoder: BeastDecoder,
+}
+
+impl<R: BufRead> BeastReader<R> {
+    pub fn new(reader: R) -> Self {
+        Self { reader, decoder: BeastDecoder::default() }
+    }
+
+    /// Read until the end of the next message
+    ///
+    /// Returns:
+    ///   `Ok(Some(Ok(frame)))`: a complete message
+    ///   `Ok(Some(Err(e)))`:    an unknown or truncated message
+    ///   `Ok(None)`:            no complete message yet, the reader timed out
+    ///   `Err(e)`:              `ErrorKind::UnexpectedEof` when the reader is closed, or other
+    ///                          error
+    pub fn next_frame(&mut self) -> io::Result<Option<Result<BeastFrame, BeastError>>> {
+        loop {
+            let bytes = match self.reader.fill_buf() {
+                Ok([]) => return Err(ErrorKind::UnexpectedEof.into()),
+                Ok(bytes) => bytes,
+                Err(e) if is_timeout(&e) => return Ok(None),
+                Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            };
+            let mut used = 0;
+            let mut message = None;
+            for byte in bytes {
+                used += 1;
+                message = self.decoder.push(*byte);
+                if message.is_some() {
+                    break;
+                }
+            }
+            self.reader.consume(used);
+            if message.is_some() {
+                return Ok(message);
+            }
+        }
+    }
+
+    /// See [`BeastDecoder::resyncs`]
+    pub fn resyncs(&self) -> u64 {
+        self.decoder.resyncs()
+    }
+
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+/// Parse an AVR `line`, dropping frames with a Downlink Format not in `accept`
+///
+/// This is checked on the first byte, before the frame is decoded with
+/// [`adsb_deku::Frame::from_bytes`].
+///
+/// Returns:
+///   `Ok(Some(frame))`: accepted frame
+///   `Ok(None)`:        blank or comment line, or not accepted frame
+///   `Err(e)`:          garbage line
+pub fn parse_avr(line: &str, accept: &DfFilter) -> Result<Option<AvrFrame>, AvrError> {
+    Ok(avr::parse_line(line)?.filter(|avr| accept.accepts(avr.bytes())))
+}
+
+/// First character of an AVR message, with or without a timestamp
+const fn is_message_start(b: u8) -> bool {
+    matches!(b, b'*' | b'@')
+}
+
+/// `WouldBlock` on unix, `TimedOut` on windows
+fn is_timeout(e: &io::Error) -> bool {
+    matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::VecDeque;
+
+    use super::*;
+
+    /// Reader returning each chunk in a single read, `None` is a timeout
+    struct TimeoutReader(VecDeque<Option<&'static [u8]>>);
+
+    impl Read for TimeoutReader {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            match self.0.pop_front() {
+                Some(Some(chunk)) => {
+                    buf[..chunk.len()].copy_from_slice(chunk);
+                    Ok(chunk.len())
+                }
+                Some(None) => Err(ErrorKind::WouldBlock.into()),
+                None => Ok(0),
+            }
+        }
+    }
+
+    #[test]
+    fn timeout_mid_line() {
+        let chunks = VecDeque::from([
+            Some(&b"*8D40621D58C382"[..]),
+            None,
+            Some(&b"D690C8AC2863A7;\n*5dab3d"[..]),
+            None,
+            None,
+            Some(&b"17d4ba29;\n"[..]),
+        ]);
+        let mut reader = LineReader::new(BufReader::new(TimeoutReader(chunks)));
+
+        assert_eq!(reader.read_line().unwrap(), None);
+        assert_eq!(reader.pending(), b"*8D40621D58C382");
+        assert_eq!(reader.next_line().unwrap().unwrap(), "*8D40621D58C382D690C8AC2863A7;\n");
+        assert_eq!(reader.pending(), b"");
+        assert_eq!(reader.read_line().unwrap(), None);
+        assert_eq!(reader.read_line().unwrap(), None);
+        assert_eq!(reader.read_line().unwrap().unwrap(), "*5dab3d17d4ba29;\n");
+        assert_eq!(reader.read_line().unwrap_err().kind(), ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn accept_df() {
+        let accept: DfFilter = "17".parse().unwrap();
+        assert!(parse_avr("*8D40621D58C382D690C8AC2863A7;\n", &accept).unwrap().is_some());
+        assert!(parse_avr("*5dab3d17d4ba29;\n", &accept).unwrap().is_none());
+        assert!(parse_avr("*5dab3d17d4ba29;\n", &DfFilter::all()).unwrap().is_some());
+        assert!(parse_avr("garbage\n", &accept).is_err());
+    }
+
+    #[test]
+    fn resync() {
+        let chunks = VecDeque::from([
+            // joined in the middle of a message
+            Some(&b"C382D690C8AC2863A7;\n*5dab3d17d4ba29;\n"[..]),
+            // garbage line
+            Some(&b"garbage\n"[..]),
+            // messages without a newline in between
+            Some(&b"*8D40621D58C382D690C8AC2863A7;*5dab3d17d4ba29;\n"[..]),
+            // partial line that is too long, split over a timeout
+            Some(&[b'x'; 2000][..]),
+            None,
+            Some(&b"*5dab3d17d4ba29;\n"[..]),
+        ]);
+        let mut reader = LineReader::new(BufReader::new(TimeoutReader(chunks)));
+        let mut lines = vec![];
+        while let Ok(line) = reader.read_line() {
+            lines.push(line);
+        }
+        assert_eq!(
+            lines,
+            [
+                Some("*5dab3d17d4ba29;\n".to_string()),
+                Some("*8D40621D58C382D690C8AC2863A7;".to_string()),
+                Some("*5dab3d17d4ba29;\n".to_string()),
+                None,
+                Some("*5dab3d17d4ba29;\n".to_string()),
+            ]
+        );
+        for line in lines.into_iter().flatten() {
+            assert!(parse_avr(&line, &DfFilter::all()).unwrap().is_some());
+        }
+        assert_eq!(reader.resyncs(), 4);
+    }
+
+    #[test]
+    fn unaligned() {
+        let chunks =
+            VecDeque::from([Some(&b"MSG,3,1,1,A1B2C3,1,,,,,,35000\n\nMSG,4,1,"[..]), None]);
+        let mut reader = LineReader::unaligned(BufReader::new(TimeoutReader(chunks)));
+        assert_eq!(reader.read_line().unwrap().unwrap(), "MSG,3,1,1,A1B2C3,1,,,,,,35000\n");
+        assert_eq!(reader.read_line().unwrap().unwrap(), "\n");
+        assert_eq!(reader.read_line().unwrap(), None);
+        assert_eq!(reader.pending(), b"MSG,4,1,");
+        assert_eq!(reader.resyncs(), 0);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn unix_source() {
+        use std::io::Write;
+        use std::os::unix::net::UnixListener;
+
+        let path = std::env::temp_dir().join(format!("rsadsb-input-{}.sock", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let listener = UnixListener::bind(&path).unwrap();
+        let source = Source::Unix(path.clone());
+        assert_eq!(source.to_string(), path.display().to_string());
+
+        let mut reader = LineReader::new(source.open(Duration::from_millis(10)).unwrap());
+        let (mut server, _) = listener.accept().unwrap();
+        assert_eq!(reader.read_line().unwrap(), None);
+        server.write_all(b"*5dab3d17d4ba29;\n").unwrap();
+        drop(server);
+        assert_eq!(reader.read_line().unwrap().unwrap(), "*5dab3d17d4ba29;\n");
+        assert_eq!(reader.read_line().unwrap_err().kind(), ErrorKind::UnexpectedEof);
+        std::fs::remove_file(&path).unwrap();
+
+        let source = Source::Tcp { host: "localhost".to_string(), port: 30002 };
+        assert_eq!(source.to_string(), "localhost:30002");
+    }
+
+    #[test]
+    fn beast_timeout_mid_message() {
+        let chunks = VecDeque::from([
+            Some(&b"\x00\x1a2\x00\x00\x00"[..]),
+            None,
+            Some(&b"\x00\x00\x1a\x1a\x80\x5d\xab\x3d\x17\xd4\xba\x29\x1a4\x00"[..]),
+        ]);
+        let mut reader = BeastReader::new(BufReader::new(TimeoutReader(chunks)));
+        assert_eq!(reader.next_frame().unwrap(), None);
+        let beast = reader.next_frame().unwrap().unwrap().unwrap();
+        assert_eq!(beast.timestamp, 0x1a);
+        assert_eq!(beast.bytes(), [0x5d, 0xab, 0x3d, 0x17, 0xd4, 0xba, 0x29]);
+        assert_eq!(reader.next_frame().unwrap(), Some(Err(BeastError::UnknownType(b'4'))));
+        assert_eq!(reader.next_frame().unwrap_err().kind(), ErrorKind::UnexpectedEof);
+        assert_eq!(reader.resyncs(), 1);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn channel_timeout() {
+        let (mut input, output) = std::os::unix::net::UnixStream::pair().unwrap();
+        let mut reader = LineReader::new(BufReader::new(ChannelReader::spawn(
+            output,
+            Duration::from_millis(10),
+        )));
+        assert_eq!(reader.read_line().unwrap(), None);
+        std::io::Write::write_all(&mut input, b"*5dab3d").unwrap();
+        assert_eq!(reader.read_line().unwrap(), None);
+        std::io::Write::write_all(&mut input, b"17d4ba29;\n").unwrap();
+        drop(input);
+        assert_eq!(reader.read_line().unwrap().unwrap(), "*5dab3d17d4ba29;\n");
+        assert_eq!(reader.read_line().unwrap_err().kind(), ErrorKind::UnexpectedEof);
+        assert_eq!(reader.read_line().unwrap_err().kind(), ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn eof_mid_line() {
+        let chunks = VecDeque::from([Some(&b"*8D40621D58C382D690C8AC2863A7;\n*5dab3d"[..])]);
+        let mut reader = LineReader::new(BufReader::new(TimeoutReader(chunks)));
+        assert_eq!(reader.read_line().unwrap().unwrap(), "*8D40621D58C382D690C8AC2863A7;\n");
+        // the last line of a file without a newline at the end
+        assert_eq!(reader.read_line().unwrap().unwrap(), "*5dab3d");
+        assert_eq!(reader.read_line().unwrap_err().kind(), ErrorKind::UnexpectedEof);
+        assert_eq!(reader.read_line().unwrap_err().kind(), ErrorKind::UnexpectedEof);
+
+        let chunks = VecDeque::from([Some(&b"*5dab3d17d4ba29;"[..])]);
+        let mut reader = LineReader::new(BufReader::new(TimeoutReader(chunks)));
+        let line = reader.read_line().unwrap().unwrap();
+        assert!(parse_avr(&line, &DfFilter::all()).unwrap().is_some());
+        assert_eq!(reader.read_line().unwrap_err().kind(), ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn long_line_without_timeout() {
+        // a peer streaming without a newline, never timing out
+        let mut chunks = VecDeque::from(vec![Some(&[b'x'; 512][..]); 8]);
+        chunks.push_back(Some(&b"*5dab3d17d4ba29;\n"[..]));
+        let mut reader = LineReader::new(BufReader::with_capacity(512, TimeoutReader(chunks)));
+        assert_eq!(reader.read_line().unwrap().unwrap(), "*5dab3d17d4ba29;\n");
+        assert!(reader.line.capacity() <= 2 * MAX_LINE_LEN);
+        // twice over the limit, then joined in the middle of the line
+        assert_eq!(reader.resyncs(), 3);
+    }
+}
@@ -0,0 +1,8 @@
+//! Shared code of the `radar` and `1090` apps
+
+pub mod delta;
+pub mod ingest;
+pub mod input;
+pub mod logging;
+pub mod privacy;
+pub mod service;
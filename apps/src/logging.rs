@@ -0,0 +1,112 @@
+//! Destination and format of the `tracing` logs of the apps
+//!
+//! The logs are filtered with the `RUST_LOG` environment variable, and written to a [`LogSink`]:
+//! daily files, stderr, journald or nowhere, such as for read-only filesystems where the files
+//! can't be created. Text or JSON lines are selected with [`LogFormat`], journald always keeps
+//! the fields of the events.
+
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_subscriber::fmt::MakeWriter;
+use tracing_subscriber::EnvFilter;
+
+/// Destination of the logs
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum LogSink {
+    /// Daily files in the log folder
+    #[default]
+    File,
+    /// Standard error
+    Stderr,
+    /// systemd journal
+    Journald,
+    /// No logs
+    None,
+}
+
+/// Format of the logs of [`LogSink::File`] and [`LogSink::Stderr`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum LogFormat {
+    /// Human readable lines
+    #[default]
+    Text,
+    /// JSON lines, with the fields of each event
+    Json,
+}
+
+/// Install the global subscriber writing to `sink`
+///
+/// Files are named `<file_name>.<date>` in `folder`, created if it doesn't exist.
+///
+/// Returns the guard flushing the files, which must be kept until quitting.
+pub fn init(
+    sink: LogSink,
+    format: LogFormat,
+    folder: &str,
+    file_name: &str,
+) -> Result<Option<WorkerGuard>> {
+    let env = EnvFilter::from_default_env();
+    match sink {
+        LogSink::File => {
+            let file_appender = RollingFileAppender::builder()
+                .rotation(Rotation::DAILY)
+                .filename_prefix(file_name)
+                .build(folder)
+                .with_context(|| format!("unable to create log files in: {folder}"))?;
+            let (non_blocking, guard) = tracing_appender::non_blocking(file_appender);
+            install(env, format, non_blocking);
+            Ok(Some(guard))
+        }
+        LogSink::Stderr => {
+            install(env, format, std::io::stderr);
+            Ok(None)
+        }
+        #[cfg(unix)]
+        LogSink::Journald => {
+            use tracing_subscriber::layer::SubscriberExt;
+            use tracing_subscriber::util::SubscriberInitExt;
+
+            let journald = tracing_journald::layer().context("unable to connect to journald")?;
+            tracing_subscriber::registry().with(env).with(journald).init();
+            Ok(None)
+        }
+        #[cfg(not(unix))]
+        LogSink::Journald => anyhow::bail!("journald is only supported on unix"),
+        LogSink::None => Ok(None),
+    }
+}
+
+fn install<W>(env: EnvFilter, format: LogFormat, writer: W)
+where
+    W: for<'a> MakeWriter<'a> + Send + Sync + 'static,
+{
+    let builder = tracing_subscriber::fmt()
+        .with_env_filter(env)
+        .with_writer(writer)
+        .with_line_number(true)
+        .with_file(true);
+    match format {
+        LogFormat::Text => builder.with_ansi(true).init(),
+        LogFormat::Json => builder.json().init(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn file_error() {
+        // a folder that can't be created is an error instead of a panic
+        let file = std::env::temp_dir().join(format!("rsadsb-logging-{}", std::process::id()));
+        std::fs::write(&file, b"").unwrap();
+        let folder = file.join("logs");
+        let err = init(LogSink::File, LogFormat::Text, folder.to_str().unwrap(), "radar.log")
+            .unwrap_err();
+        assert!(err.to_string().starts_with("unable to create log files in:"));
+        assert!(init(LogSink::None, LogFormat::Json, "", "radar.log").unwrap().is_none());
+        std::fs::remove_file(&file).unwrap();
+    }
+}
@@ -0,0 +1,89 @@
+//! `--privacy-*` options of the apps, building the [`PrivacyFilter`] of what is sent out
+//!
+//! The filtered aircraft are still displayed locally, only the exports (such as the output of
+//! `1090`, or `--emit-deltas` and `--render` of `radar`) are filtered.
+
+use adsb_deku::ICAO;
+use clap::Args;
+use rsadsb_common::privacy::{PrivacyAction, PrivacyFilter};
+
+/// Options of the [`PrivacyFilter`], see [`self`]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Args)]
+pub struct PrivacyArgs {
+    /// Don't send out these aircraft addresses, such as `a1b2c3,a08f94`
+    #[arg(long, value_delimiter = ',')]
+    pub privacy_block: Vec<ICAO>,
+    /// Only send out these aircraft addresses, such as `a1b2c3,a08f94`
+    #[arg(long, value_delimiter = ',')]
+    pub privacy_allow: Vec<ICAO>,
+    /// Send out the filtered aircraft with a pseudonym address and without callsign and squawk,
+    /// instead of dropping them
+    #[arg(long)]
+    pub privacy_anonymize: bool,
+    /// Secret salt of the pseudonyms of `--privacy-anonymize`, such that they can't be reversed
+    /// and are the same between runs. Random if not set
+    #[arg(long, requires = "privacy_anonymize")]
+    pub privacy_salt: Option<u64>,
+}
+
+impl PrivacyArgs {
+    /// Filter of the options, `None` if all aircraft are sent out
+    pub fn filter(&self) -> Option<PrivacyFilter> {
+        let action =
+            if self.privacy_anonymize { PrivacyAction::Anonymize } else { PrivacyAction::Drop };
+        let mut filter = PrivacyFilter::new(action);
+        if let Some(salt) = self.privacy_salt {
+            filter = filter.with_salt(salt);
+        }
+        for icao in &self.privacy_block {
+            filter.block(*icao);
+        }
+        for icao in &self.privacy_allow {
+            filter.allow(*icao);
+        }
+        (!filter.is_empty()).then_some(filter)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+
+    use super::*;
+
+    #[derive(Debug, Parser)]
+    struct Options {
+        #[command(flatten)]
+        privacy: PrivacyArgs,
+    }
+
+    #[test]
+    fn filter() {
+        let options = Options::parse_from(["test"]);
+        assert_eq!(options.privacy.filter(), None);
+
+        let options = Options::parse_from(["test", "--privacy-block", "a1b2c3,40621d"]);
+        let filter = options.privacy.filter().unwrap();
+        assert_eq!(filter.action(), PrivacyAction::Drop);
+        assert!(filter.is_filtered(ICAO([0x40, 0x62, 0x1d])));
+        assert!(!filter.is_filtered(ICAO([0xa0, 0x8f, 0x94])));
+
+        let options =
+            Options::parse_from(["test", "--privacy-allow=a08f94", "--privacy-anonymize"]);
+        let filter = options.privacy.filter().unwrap();
+        assert_eq!(filter.action(), PrivacyAction::Anonymize);
+        assert!(filter.is_filtered(ICAO([0x40, 0x62, 0x1d])));
+        assert!(!filter.is_filtered(ICAO([0xa0, 0x8f, 0x94])));
+    }
+
+    #[test]
+    fn salt() {
+        let icao = ICAO([0x40, 0x62, 0x1d]);
+        let args = ["test", "--privacy-block=40621d", "--privacy-anonymize", "--privacy-salt=42"];
+        let filter = Options::parse_from(args).privacy.filter().unwrap();
+        let salted = PrivacyFilter::new(PrivacyAction::Anonymize).with_salt(42);
+        assert_eq!(filter.pseudonym(icao), salted.pseudonym(icao));
+
+        assert!(Options::try_parse_from(["test", "--privacy-salt=42"]).is_err());
+    }
+}
@@ -1,24 +1,112 @@
-use ratatui::layout::{Constraint, Rect};
+use adsb_deku::ICAO;
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
 use ratatui::style::{Color, Modifier, Style};
-use ratatui::widgets::{Block, Row, Table, TableState};
-use rsadsb_common::{AirplaneDetails, Airplanes};
+use ratatui::widgets::{Block, Paragraph, Row, Table};
+use rsadsb_common::altitude::AltitudeDisplay;
+use rsadsb_common::sort::{SortKey, SortOrder};
+use rsadsb_common::{AirplaneDetails, AirplaneState, Airplanes, EnhancedSurveillance, Trend};
 
-use crate::DEFAULT_PRECISION;
+use crate::keys::Action;
+use crate::tab::{handle_altitude_key, Tab, TabContext, MAP_TAB};
+use crate::{Settings, DEFAULT_PRECISION};
+
+/// Orders of the Airplanes tab, cycled with [`Action::Sort`], with the name shown in its title
+pub const SORTS: [(SortKey, SortOrder, &str); 6] = [
+    (SortKey::Icao, SortOrder::Ascending, "ICAO"),
+    (SortKey::Distance, SortOrder::Ascending, "distance"),
+    (SortKey::Altitude, SortOrder::Descending, "altitude"),
+    (SortKey::Speed, SortOrder::Descending, "speed"),
+    (SortKey::LastSeen, SortOrder::Ascending, "seen"),
+    (SortKey::Callsign, SortOrder::Ascending, "call sign"),
+];
+
+/// Airplanes tab, with the selection in [`Settings::airplanes_state`]
+pub struct AirplanesTab;
+
+impl Tab for AirplanesTab {
+    fn title(&self, adsb_airplanes: &Airplanes) -> String {
+        format!("Airplanes({})", adsb_airplanes.len())
+    }
+
+    fn render(&self, f: &mut ratatui::Frame, chunks: &[Rect], ctx: &TabContext<'_>) -> Rect {
+        build_tab_airplanes(f, chunks, ctx.adsb_airplanes, ctx.settings);
+        chunks[1]
+    }
+
+    fn handle_key(&mut self, action: Action, settings: &mut Settings, adsb_airplanes: &Airplanes) {
+        let visible = visible_keys(adsb_airplanes, settings).count();
+        let last = visible.saturating_sub(1);
+        match action {
+            Action::Up => {
+                let index = settings
+                    .airplanes_state
+                    .selected()
+                    .and_then(|selected| selected.min(last).checked_sub(1))
+                    .unwrap_or(0);
+                settings.airplanes_state.select(Some(index));
+            }
+            Action::Down => {
+                let index = settings.airplanes_state.selected().map_or(0, |selected| selected + 1);
+                settings.airplanes_state.select(Some(index.min(last)));
+            }
+            Action::Enter => {
+                let key = settings
+                    .airplanes_state
+                    .selected()
+                    .and_then(|selected| visible_keys(adsb_airplanes, settings).nth(selected));
+                if let Some(key) = key {
+                    let aircraft_details = adsb_airplanes.aircraft_details(key);
+                    if let Some(AirplaneDetails { position, .. }) = aircraft_details {
+                        settings.custom_lat = Some(position.latitude);
+                        settings.custom_long = Some(position.longitude);
+                        settings.tab_selection = MAP_TAB;
+                    }
+                }
+            }
+            Action::Sort => settings.airplanes_sort = (settings.airplanes_sort + 1) % SORTS.len(),
+            action => handle_altitude_key(action, settings),
+        }
+    }
+}
+
+/// Arrow of the climb or descent trend of `state`, with `--vert-rate-threshold` (ft/min)
+pub fn trend_arrow(state: &AirplaneState, threshold: u16) -> &'static str {
+    match state.trend(f32::from(threshold)) {
+        Some(Trend::Climb) => "↑",
+        Some(Trend::Descent) => "↓",
+        Some(Trend::Level) | None => "",
+    }
+}
+
+/// Airplanes shown in the Airplanes tab, within the altitude band and in the order of
+/// [`Settings::airplanes_sort`]
+pub fn visible_keys<'a>(
+    adsb_airplanes: &'a Airplanes,
+    settings: &'a Settings,
+) -> impl Iterator<Item = ICAO> + 'a {
+    let (by, order, _) = SORTS[settings.airplanes_sort];
+    adsb_airplanes.sorted(by, order).map(|(key, _)| key).filter(|key| {
+        let altitude = adsb_airplanes.aircraft_details(*key).map(|details| details.altitude);
+        settings.altitude_visible(altitude)
+    })
+}
 
 /// Render Airplanes tab for tui display
 pub fn build_tab_airplanes(
     f: &mut ratatui::Frame,
     chunks: &[Rect],
     adsb_airplanes: &Airplanes,
-    airplanes_state: &mut TableState,
+    settings: &Settings,
 ) {
+    let ehs = settings.opts.airplanes_ehs;
     let mut rows = vec![];
     // make a vec of all strings to get a total amount of airplanes with
     // position information
     let empty = "".to_string();
-    for key in adsb_airplanes.keys() {
-        let state = adsb_airplanes.get(*key).unwrap();
-        let aircraft_details = adsb_airplanes.aircraft_details(*key);
+    let keys: Vec<ICAO> = visible_keys(adsb_airplanes, settings).collect();
+    for &key in &keys {
+        let state = adsb_airplanes.get(key).unwrap();
+        let aircraft_details = adsb_airplanes.aircraft_details(key);
         let mut lat = empty.clone();
         let mut lon = empty.clone();
         let mut alt = empty.clone();
@@ -27,68 +115,183 @@ pub fn build_tab_airplanes(
             lat = format!("{:.DEFAULT_PRECISION$}", position.latitude);
             lon = format!("{:.DEFAULT_PRECISION$}", position.longitude);
             s_kilo_distance = format!("{kilo_distance:.DEFAULT_PRECISION$}");
-            alt = altitude.to_string();
+            alt = AltitudeDisplay::new(altitude, settings.opts.transition_altitude).to_string();
         }
 
         let heading =
             state.heading.map_or_else(|| "".to_string(), |heading| format!("{heading:>7.1}"));
 
-        rows.push(Row::new(vec![
+        let mut row = vec![
             format!("{key}"),
             state.callsign.as_ref().unwrap_or(&empty).clone(),
             lat,
             lon,
             heading,
-            format!("{alt:>8}"),
-            state.vert_speed.map_or_else(|| "".into(), |v| format!("{v:>6}")),
+            format!("{alt:>8} {}", trend_arrow(state, settings.opts.vert_rate_threshold)),
+            state.vert_speed.map_or_else(|| "".into(), |v| format!("{v:>+6}")),
             state.speed.map_or_else(|| "".into(), |v| format!("{v:>5.0}")),
             format!("{s_kilo_distance:>8}"),
             format!("{:>4}", state.num_messages),
-        ]));
+            format!("{:>4.0}", state.seen().as_secs_f32()),
+            state.seen_pos().map_or_else(|| "".into(), |v| format!("{:>8.0}", v.as_secs_f32())),
+            state.quality().map_or_else(|| "".into(), |v| format!("{:>4}", v.score)),
+        ];
+        if ehs {
+            let ehs = &state.ehs;
+            row.extend([
+                ehs.true_airspeed.map_or_else(|| "".into(), |v| format!("{v:>4}")),
+                ehs.indicated_airspeed.map_or_else(|| "".into(), |v| format!("{v:>4}")),
+                ehs.mach.map_or_else(|| "".into(), |v| format!("{v:>5.3}")),
+                ehs.magnetic_heading.map_or_else(|| "".into(), |v| format!("{v:>7.1}")),
+                ehs.roll_angle.map_or_else(|| "".into(), |v| format!("{v:>5.1}")),
+                state.estimate_wind().map_or_else(
+                    || "".into(),
+                    |wind| format!("{:03.0}/{:<3.0}", wind.direction, wind.speed),
+                ),
+                state.estimate_temperature().map_or_else(|| "".into(), |v| format!("{v:>5.0}")),
+            ]);
+        }
+        rows.push(Row::new(row));
     }
 
     let rows_len = rows.len();
 
     // check the length of selected airplanes
+    let mut airplanes_state = settings.airplanes_state.clone();
     if let Some(selected) = airplanes_state.selected() {
-        if selected > rows_len - 1 {
-            airplanes_state.select(Some(rows_len - 1));
+        if selected > rows_len.saturating_sub(1) {
+            airplanes_state.select(Some(rows_len.saturating_sub(1)));
         }
     }
 
     // draw table
-    let widths = &[
+    let mut widths = vec![
         Constraint::Length(6),
         Constraint::Length(9),
         Constraint::Length(7),
         Constraint::Length(7),
         Constraint::Length(7),
-        Constraint::Length(8),
+        Constraint::Length(10),
         Constraint::Length(6),
         Constraint::Length(5),
         Constraint::Length(8),
         Constraint::Length(6),
+        Constraint::Length(4),
+        Constraint::Length(8),
+        Constraint::Length(4),
+    ];
+    let mut header = vec![
+        "ICAO",
+        "Call sign",
+        "Lat",
+        "Long",
+        "Heading",
+        "Altitude",
+        "   FPM",
+        "Speed",
+        "Distance",
+        "Msgs",
+        "Seen",
+        "Seen Pos",
+        "Qual",
     ];
+    if ehs {
+        widths.extend([
+            Constraint::Length(4),
+            Constraint::Length(4),
+            Constraint::Length(5),
+            Constraint::Length(7),
+            Constraint::Length(5),
+            Constraint::Length(7),
+            Constraint::Length(5),
+        ]);
+        header.extend([" TAS", " IAS", " Mach", "Mag Hdg", " Roll", "Wind", " Temp"]);
+    }
     let table = Table::new(rows, widths)
         .style(Style::default().fg(Color::White))
-        .header(
-            Row::new(vec![
-                "ICAO",
-                "Call sign",
-                "Lat",
-                "Long",
-                "Heading",
-                "Altitude",
-                "   FPM",
-                "Speed",
-                "Distance",
-                "Msgs",
-            ])
-            .bottom_margin(1),
+        .header(Row::new(header).bottom_margin(1))
+        .block(
+            Block::bordered()
+                .title(format!("Airplanes({rows_len}) by {}", SORTS[settings.airplanes_sort].2)),
         )
-        .block(Block::bordered().title(format!("Airplanes({rows_len})")))
         .column_spacing(1)
         .highlight_style(Style::default().add_modifier(Modifier::BOLD))
         .highlight_symbol(">> ");
-    f.render_stateful_widget(table, chunks[1], &mut airplanes_state.clone());
+
+    // Comm-B panel of the selected aircraft, if any was received
+    let selected = airplanes_state.selected().and_then(|selected| keys.get(selected));
+    let ehs = selected.and_then(|key| Some((key, adsb_airplanes.get(*key)?.ehs)));
+    let Some((key, ehs)) = ehs.filter(|(_, ehs)| *ehs != EnhancedSurveillance::default()) else {
+        f.render_stateful_widget(table, chunks[1], &mut airplanes_state);
+        return;
+    };
+    let lines = ehs_lines(&ehs);
+    let vertical_chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(0), Constraint::Length(lines.len() as u16 + 2)])
+        .split(chunks[1]);
+    f.render_stateful_widget(table, vertical_chunks[0], &mut airplanes_state);
+    let paragraph =
+        Paragraph::new(lines.join("\n")).block(Block::bordered().title(format!("{key} Comm-B")));
+    f.render_widget(paragraph, vertical_chunks[1]);
+}
+
+/// Lines of the Comm-B panel, with the selected altitude (`BDS` 4,0), roll and track (`BDS` 5,0)
+/// and airspeeds (`BDS` 5,0 and 6,0), unknown values as `-`
+fn ehs_lines(ehs: &EnhancedSurveillance) -> [String; 3] {
+    fn value<T>(value: Option<T>, f: impl FnOnce(T) -> String) -> String {
+        value.map_or_else(|| "-".to_string(), f)
+    }
+    let side = |value: f32| match value {
+        value if value < 0.0 => " left",
+        value if value > 0.0 => " right",
+        _ => "",
+    };
+
+    [
+        format!(
+            "Selected altitude  MCP {}  FMS {}  QNH {}",
+            value(ehs.mcp_selected_altitude, |v| format!("{v} ft")),
+            value(ehs.fms_selected_altitude, |v| format!("{v} ft")),
+            value(ehs.barometric_pressure_setting, |v| format!("{v:.1} hPa")),
+        ),
+        format!(
+            "Roll {}  Track {}  Track rate {}",
+            value(ehs.roll_angle, |v| format!("{:.1}°{}", v.abs(), side(v))),
+            value(ehs.true_track, |v| format!("{v:.1}°")),
+            value(ehs.track_angle_rate, |v| format!("{:.2}°/s{}", v.abs(), side(v))),
+        ),
+        format!(
+            "IAS {}  TAS {}  Mach {}  Mag heading {}",
+            value(ehs.indicated_airspeed, |v| format!("{v} kt")),
+            value(ehs.true_airspeed, |v| format!("{v} kt")),
+            value(ehs.mach, |v| format!("{v:.3}")),
+            value(ehs.magnetic_heading, |v| format!("{v:.1}°")),
+        ),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn comm_b_panel() {
+        let ehs = EnhancedSurveillance {
+            mcp_selected_altitude: Some(35008),
+            barometric_pressure_setting: Some(1013.2),
+            roll_angle: Some(-12.3),
+            track_angle_rate: Some(0.5),
+            mach: Some(0.78),
+            ..EnhancedSurveillance::default()
+        };
+        assert_eq!(
+            ehs_lines(&ehs),
+            [
+                "Selected altitude  MCP 35008 ft  FMS -  QNH 1013.2 hPa",
+                "Roll 12.3° left  Track -  Track rate 0.50°/s right",
+                "IAS -  TAS -  Mach 0.780  Mag heading -",
+            ]
+        );
+    }
 }
@@ -1,5 +1,6 @@
 use std::fs::File;
 
+use rsadsb_common::geo;
 use serde::Deserialize;
 
 #[allow(dead_code)]
@@ -41,3 +42,141 @@ impl Airport {
         airports
     }
 }
+
+/// Feet to kilometers
+const FT_TO_KM: f64 = 0.000_304_8;
+
+/// Nautical miles to kilometers
+pub const NM_TO_KM: f64 = 1.852;
+
+/// (latitude, longitude) of a runway threshold
+type Threshold = (f64, f64);
+
+/// Row of the runways csv file from <https://ourairports.com/data/>
+///
+/// `le` is the low numbered end of the runway, and `he` the high numbered end.
+#[allow(dead_code)]
+#[derive(Clone, Debug, Deserialize)]
+pub struct Runway {
+    pub airport_ident: String,
+    pub length_ft: Option<f64>,
+    #[serde(deserialize_with = "deserialize_bool")]
+    pub closed: bool,
+    pub le_ident: String,
+    pub le_latitude_deg: Option<f64>,
+    pub le_longitude_deg: Option<f64>,
+    #[serde(rename = "le_heading_degT")]
+    pub le_heading_deg: Option<f64>,
+    pub he_ident: String,
+    pub he_latitude_deg: Option<f64>,
+    pub he_longitude_deg: Option<f64>,
+    #[serde(rename = "he_heading_degT")]
+    pub he_heading_deg: Option<f64>,
+}
+
+/// `closed` is `0` or `1`
+fn deserialize_bool<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<bool, D::Error> {
+    Ok(u8::deserialize(deserializer)? != 0)
+}
+
+impl Runway {
+    /// Open runways with known ends, only of `airports` if not empty
+    pub fn from_file(filename: &str, airports: &[Airport]) -> Vec<Self> {
+        let f = File::open(filename).unwrap();
+        Self::from_reader(f, airports)
+    }
+
+    fn from_reader(reader: impl std::io::Read, airports: &[Airport]) -> Vec<Self> {
+        let mut runways = vec![];
+        let mut rdr = csv::Reader::from_reader(reader);
+        for result in rdr.deserialize() {
+            let record: Self = result.unwrap();
+            if record.closed || record.ends().is_none() {
+                continue;
+            }
+            if airports.is_empty() || airports.iter().any(|a| a.icao == record.airport_ident) {
+                runways.push(record);
+            }
+        }
+        runways
+    }
+
+    /// Low and high numbered thresholds
+    ///
+    /// Without the position of one threshold, it is computed from the other threshold, its heading
+    /// and the length of the runway.
+    pub fn ends(&self) -> Option<(Threshold, Threshold)> {
+        let le = self.le_latitude_deg.zip(self.le_longitude_deg);
+        let he = self.he_latitude_deg.zip(self.he_longitude_deg);
+        let length = self.length_ft? * FT_TO_KM;
+        match (le, he) {
+            (Some(le), Some(he)) => Some((le, he)),
+            (Some(le), None) => Some((le, geo::destination(le, self.le_heading_deg?, length))),
+            (None, Some(he)) => Some((geo::destination(he, self.he_heading_deg?, length), he)),
+            (None, None) => None,
+        }
+    }
+
+    /// Extended centerlines of `nm` (nautical miles) from each threshold, along the approach of
+    /// the landing aircraft, as (threshold, end of the centerline)
+    pub fn centerlines(&self, nm: f64) -> Option<[(Threshold, (f64, f64)); 2]> {
+        let (le, he) = self.ends()?;
+        let km = nm * NM_TO_KM;
+        Some([
+            (le, geo::destination(le, geo::bearing(he, le), km)),
+            (he, geo::destination(he, geo::bearing(le, he), km)),
+        ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RUNWAYS: &str = "\
+id,airport_ref,airport_ident,length_ft,width_ft,surface,lighted,closed,le_ident,le_latitude_deg,le_longitude_deg,le_elevation_ft,le_heading_degT,le_displaced_threshold_ft,he_ident,he_latitude_deg,he_longitude_deg,he_elevation_ft,he_heading_degT,he_displaced_threshold_ft
+1,1,KCLT,10000,150,CON,1,0,18C,35.2396,-80.9431,739,180,,36C,35.2122,-80.9431,726,360,
+2,1,KCLT,7502,150,ASP,1,0,05,35.2006,-80.9527,,90,,23,,,,,
+3,1,KCLT,7000,150,ASP,1,1,01,35.2,-80.9,,10,,19,35.25,-80.9,,190,
+4,2,KJQF,5500,100,ASP,1,0,02,,,,,,20,,,,,
+";
+
+    #[test]
+    fn runways() {
+        let runways = Runway::from_reader(RUNWAYS.as_bytes(), &[]);
+        // closed, and without thresholds
+        assert_eq!(runways.len(), 2);
+
+        let (le, he) = runways[0].ends().unwrap();
+        assert_eq!((le, he), ((35.2396, -80.9431), (35.2122, -80.9431)));
+        // approaches from the north to 18C, and from the south to 36C
+        let [(start, north), (_, south)] = runways[0].centerlines(10.0).unwrap();
+        assert_eq!(start, le);
+        assert!((geo::distance(le, north) - 18.52).abs() < 0.01);
+        assert!(north.0 > le.0 && south.0 < he.0);
+        assert!((north.1 - le.1).abs() < 1e-6);
+
+        // from the heading and length
+        let (le, he) = runways[1].ends().unwrap();
+        assert!((geo::distance(le, he) - 7502.0 * FT_TO_KM).abs() < 0.001);
+        assert!((geo::bearing(le, he) - 90.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn runways_of_airports() {
+        let airport = |icao: &str| Airport {
+            icao: icao.to_string(),
+            iata: String::new(),
+            name: String::new(),
+            city: String::new(),
+            subd: String::new(),
+            country: String::new(),
+            elevation: 0.0,
+            lat: 0.0,
+            lon: 0.0,
+            tz: String::new(),
+        };
+        assert_eq!(Runway::from_reader(RUNWAYS.as_bytes(), &[airport("KCLT")]).len(), 2);
+        assert!(Runway::from_reader(RUNWAYS.as_bytes(), &[airport("KJFK")]).is_empty());
+    }
+}
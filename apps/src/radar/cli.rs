@@ -1,11 +1,25 @@
 use std::net::Ipv4Addr;
 use std::num::ParseFloatError;
+use std::path::PathBuf;
 use std::str::FromStr;
 
-use clap::Parser;
+use adsb_deku::DfFilter;
+use clap::{Parser, ValueEnum};
+use rsadsb_apps::ingest::InputFormat;
+use rsadsb_apps::input::Source;
+use rsadsb_apps::logging::{LogFormat, LogSink};
+use rsadsb_apps::privacy::PrivacyArgs;
+use rsadsb_common::altitude::AltitudeSource;
+use rsadsb_common::track::TrackFilter;
+use serde::Deserialize;
+
+use crate::keys::Keymap;
+use crate::label::LabelTemplate;
+use crate::projection::ProjectionKind;
+use crate::render::RenderSize;
 
 /// Parsing struct for the --locations clap parameter
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Deserialize)]
 pub struct Location {
     pub name: String,
     pub lat: f64,
@@ -25,6 +39,26 @@ impl FromStr for Location {
     }
 }
 
+/// Screen layout from `--layout`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum ScreenLayout {
+    /// A single tab
+    #[default]
+    Single,
+    /// Map tab on the left and the Airplanes table on the right
+    Split,
+}
+
+/// Marker of the aircraft on the Map from `--heading-style`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum HeadingStyle {
+    /// An arrow (↑ ↗ → ↘ ↓ ↙ ← ↖) rotated to the nearest 45° of the heading
+    #[default]
+    Arrow,
+    /// Two lines behind the aircraft, as wings angled toward the heading
+    Wings,
+}
+
 const AFTER_TEST: &str = r#"Environment Variables:
     RUST_LOG: See "https://docs.rs/tracing-subscriber/latest/tracing_subscriber/fmt/index.html#filtering-events-with-environment-variables"
 "#;
@@ -46,16 +80,43 @@ pub struct Opts {
     #[arg(long, default_value = "30002")]
     pub port: u16,
 
-    /// Antenna location latitude, this use for aircraft position algorithms.
+    /// Format of the ADS-B server, such as `sbs` with `--port 30003` for a BaseStation feed, or
+    /// `beast` with `--port 30005`
     ///
-    /// This is overwritten when using the `--gpsd` option.
+    /// SBS messages don't contain the frames, so `--accept-df`, `--dedup-window` and `--max-rate`
+    /// aren't used. `--replay` files are always AVR.
+    #[arg(long, value_enum, default_value_t)]
+    pub input_format: InputFormat,
+
+    /// Read from this Unix domain socket instead of `--host` and `--port`
+    #[arg(long, conflicts_with = "stdin")]
+    pub unix_socket: Option<PathBuf>,
+
+    /// Read from the standard input instead of `--host` and `--port`, such as piped from a
+    /// demodulator
     #[arg(long)]
+    pub stdin: bool,
+
+    /// Antenna location latitude, this use for aircraft position algorithms.
+    ///
+    /// This is overwritten when using the `--gpsd` or `--auto-position` option.
+    #[arg(
+        long,
+        required_unless_present = "auto_position",
+        default_value = "0",
+        hide_default_value = true
+    )]
     pub lat: f64,
 
     /// Antenna location longitude
     ///
-    /// This is overwritten when using the `--gpsd` option.
-    #[arg(long)]
+    /// This is overwritten when using the `--gpsd` or `--auto-position` option.
+    #[arg(
+        long,
+        required_unless_present = "auto_position",
+        default_value = "0",
+        hide_default_value = true
+    )]
     pub long: f64,
 
     /// Vector of location [(name, lat, long),..] to display on Map
@@ -78,10 +139,32 @@ pub struct Opts {
     #[arg(long)]
     pub disable_heading: bool,
 
+    /// Marker showing the heading of the aircraft on the Map, `arrow` reads better at small
+    /// terminal sizes
+    #[arg(long, value_enum, default_value_t)]
+    pub heading_style: HeadingStyle,
+
     /// Disable display of previous positions of aircraft on Map
     #[arg(long)]
     pub disable_track: bool,
 
+    /// Simplify the tracks on Map to lines, removing the previous positions within this distance
+    /// (m) of the line
+    #[arg(long, default_value = "0")]
+    pub track_tolerance: f64,
+
+    /// Smooth the tracks on Map to lines, cutting their corners this amount of times
+    #[arg(long, default_value = "0", value_parser = clap::value_parser!(u8).range(0..=4))]
+    pub track_smoothing: u8,
+
+    /// Disable moving and hiding of overlapping aircraft labels on Map
+    #[arg(long)]
+    pub disable_declutter: bool,
+
+    /// Disable display of surface vehicles and fixed obstructions (emitter category C) on Map
+    #[arg(long)]
+    pub disable_surface_vehicles: bool,
+
     /// Zoom level of Map and Coverage (-=zoom out/+=zoom in)
     #[arg(long, default_value = ".12")]
     pub scale: f64,
@@ -96,6 +179,21 @@ pub struct Opts {
     #[arg(long, default_value = "localhost")]
     pub gpsd_ip: String,
 
+    /// Estimate the antenna location from the positions of received aircraft, for an unknown
+    /// location.
+    ///
+    /// This overwrites the `--lat` and `--long`, once enough positions are received
+    #[arg(long, conflicts_with = "gpsd")]
+    pub auto_position: bool,
+
+    /// Seconds since the last message of an aircraft to draw it dimmed on Map
+    #[arg(long, default_value = "15")]
+    pub dim_age: u64,
+
+    /// Seconds since the last position of an aircraft to draw it as stale (hollow marker) on Map
+    #[arg(long, default_value = "30")]
+    pub stale_position: u64,
+
     /// Seconds since last message from airplane, triggers removal of airplane after time is up
     #[arg(long, default_value = "120")]
     pub filter_time: u64,
@@ -103,15 +201,42 @@ pub struct Opts {
     #[arg(long, default_value = "logs")]
     pub log_folder: String,
 
+    /// Destination of the logs, such as `journald` or `none` on read-only filesystems
+    ///
+    /// With `stderr`, redirect it away from the terminal, such as `2>radar.log`, it is rejected on
+    /// the terminal of the display.
+    #[arg(long, value_enum, default_value_t)]
+    pub log: LogSink,
+
+    /// Format of the `file` and `stderr` logs
+    #[arg(long, value_enum, default_value_t)]
+    pub log_format: LogFormat,
+
     /// Enable three tabs on left side of screen for zoom out/zoom in/and reset
     #[arg(long)]
     pub touchscreen: bool,
 
-    /// Limit parsing of ADS-B messages to `DF::ADSB(17)` num_messages
+    /// Screen layout, `split` shows the Airplanes table next to the Map in the Map tab
+    #[arg(long, value_enum, default_value_t = ScreenLayout::Single)]
+    pub layout: ScreenLayout,
+
+    /// Projection of the Map and Coverage tabs, `azimuthal` keeps the true range and bearing from
+    /// the receiver at high latitudes
+    #[arg(long, value_enum, default_value_t = ProjectionKind::Mercator)]
+    pub projection: ProjectionKind,
+
+    /// Only decode frames with these Downlink Formats, such as `17,18` or `0,4,5,11,16-21`
     ///
-    /// This can improve performance of just needing to read radar related messages
-    #[arg(long)]
-    pub limit_parsing: bool,
+    /// This is checked on the first byte of the frame, and can improve performance of just
+    /// needing to read radar related messages
+    #[arg(long, default_value = "0-31")]
+    pub accept_df: DfFilter,
+
+    /// Repair the CRC of DF17/18 frames by flipping up to this many of their least confident bits
+    ///
+    /// Only for the frames with the bit confidence of a demodulator frontend, 0 disables it
+    #[arg(long, default_value = "0", value_parser = clap::value_parser!(u8).range(0..=4))]
+    pub soft_repair: u8,
 
     /// Import downloaded csv file for FAA Airport from <https://github.com/mborsetti/airportsdata>
     #[arg(long)]
@@ -121,6 +246,14 @@ pub struct Opts {
     #[arg(long)]
     pub airports_tz_filter: Option<String>,
 
+    /// Import downloaded runways csv file from <https://ourairports.com/data/>, only the runways of --airports if set
+    #[arg(long)]
+    pub runways: Option<String>,
+
+    /// Length (NM) of the extended centerlines of --runways on Map, 0 to only draw the runways
+    #[arg(long, default_value = "10")]
+    pub centerline_length: f64,
+
     /// retry TCP connection to dump1090 instance if connecton is lost/disconnected
     #[arg(long)]
     pub retry_tcp: bool,
@@ -128,6 +261,178 @@ pub struct Opts {
     /// Control the max range of the receiver in km
     #[arg(long, default_value = "500")]
     pub max_range: f64,
+
+    /// Draw the last positions of each aircraft rejected by the range checks on the Map
+    ///
+    /// Positions out of --max-range are drawn as a red `x`, and positions too far from the
+    /// previous position of the aircraft as a yellow `x`, for diagnosing a misconfigured receiver
+    /// position or max range
+    #[arg(long)]
+    pub show_rejected_fixes: bool,
+
+    /// Display Enhanced Surveillance (Comm-B BDS 5,0 / 6,0) columns in the Airplanes tab
+    ///
+    /// This adds the true airspeed, indicated airspeed, mach, magnetic heading, roll angle, and the
+    /// estimated wind (direction/speed) and temperature (celsius)
+    #[arg(long)]
+    pub airplanes_ehs: bool,
+
+    /// TOML configuration file, overriding the command line options
+    ///
+    /// Keys have the same name as the options, such as `max_range = 300.0`. Reload at runtime with
+    /// the `r` key or SIGHUP.
+    #[arg(long)]
+    pub config: Option<String>,
+
+    /// Suppress duplicate frames (same bytes) received within this window in milliseconds
+    ///
+    /// Useful for receivers with multiple antennas or re-broadcasting feeds
+    #[arg(long)]
+    pub dedup_window: Option<u64>,
+
+    /// Don't log each received line and decoded frame, even at the debug and trace levels
+    ///
+    /// Reduces the CPU usage on constrained devices when logging with `RUST_LOG=debug`
+    #[arg(long)]
+    pub quiet_decode: bool,
+
+    /// Limit the amount of frames decoded per second, for constrained devices
+    ///
+    /// Position and velocity frames are always decoded, other frames over the limit are dropped
+    #[arg(long)]
+    pub max_rate: Option<u32>,
+
+    /// Frames kept between reading the ADS-B server and decoding them, newer frames are dropped
+    /// when full
+    ///
+    /// Frames are read from a separate thread, and all frames received are decoded before each
+    /// draw. Dropped frames are shown in the Stats tab.
+    #[arg(
+        long,
+        default_value = "8192",
+        value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..)
+    )]
+    pub input_queue: usize,
+
+    /// Maximum redraws per second
+    ///
+    /// The display is only redrawn when frames or input were received, and once per second
+    /// otherwise. Lower values reduce the CPU usage on low-power devices.
+    #[arg(long, default_value = "20", value_parser = clap::value_parser!(u32).range(1..=120))]
+    pub fps: u32,
+
+    /// Only display aircraft at or above this altitude (ft) on the Map and Airplanes tabs
+    ///
+    /// Change at runtime with `[` and `]`
+    #[arg(long)]
+    pub min_altitude: Option<i32>,
+
+    /// Only display aircraft at or below this altitude (ft) on the Map and Airplanes tabs
+    ///
+    /// Change at runtime with `{` and `}`
+    #[arg(long)]
+    pub max_altitude: Option<i32>,
+
+    /// Only collect the coverage of aircraft at or above this altitude (ft), in the Coverage tab and its export
+    ///
+    /// This avoids the skew of the nearby low traffic, such as around an airport. Aircraft without
+    /// a known altitude aren't collected with a floor.
+    #[arg(long)]
+    pub coverage_min_altitude: Option<i32>,
+
+    /// Smoothed vertical rate (ft/min) for the climb and descent arrows next to the altitude in
+    /// the Airplanes tab and the aircraft on the Map
+    #[arg(long, default_value = "300")]
+    pub vert_rate_threshold: u16,
+
+    /// Label of the aircraft on the Map, replacing the callsign and latitude/longitude
+    ///
+    /// Fields: {icao} {callsign} {alt} {alt_fl} {gs} {track} {vs} {squawk} {lat} {long} {dist}
+    /// {trend}, such as "{callsign} {alt_fl} {gs}". Fields without a value are left out.
+    #[arg(long)]
+    pub map_label: Option<LabelTemplate>,
+
+    /// Altitude of the `{alt}` of `--map-label`: baro, gnss or gnss-corrected (barometric altitude
+    /// corrected by the GNSS difference of the airborne velocities)
+    #[arg(long, default_value = "baro")]
+    pub label_altitude: AltitudeSource,
+
+    /// Transition altitude (ft), the altitudes at or above are displayed as flight levels (such as
+    /// FL350) and the QNH of the aircraft below are used for the consensus QNH in the Stats tab and
+    /// `--qnh-correction`
+    #[arg(long, default_value = "18000")]
+    pub transition_altitude: i32,
+
+    /// Correct the barometric altitudes below `--transition-altitude` to the true altitudes with
+    /// the consensus QNH, in the `{alt}` of `--map-label`
+    #[arg(long)]
+    pub qnh_correction: bool,
+
+    /// Keep daily statistics (unique aircraft, messages, range, busiest hour) in this JSON lines
+    /// file, displayed in the Stats tab
+    #[arg(long)]
+    pub stats_ledger: Option<String>,
+
+    /// Stream the changes of the aircraft as JSON lines to the clients of this Unix socket
+    ///
+    /// Each line is an `added`, `updated` or `removed` event of an aircraft with its changed
+    /// fields, such as read with `socat - UNIX-CONNECT:<path>`.
+    #[arg(long)]
+    pub emit_deltas: Option<String>,
+
+    /// Altitude of the `--emit-deltas` events: baro, gnss or gnss-corrected
+    #[arg(long, default_value = "baro")]
+    pub deltas_altitude: AltitudeSource,
+
+    /// Also draw the selected tab into this image file (binary PPM), for e-paper and framebuffer
+    /// displays
+    ///
+    /// The image is replaced every `--render-interval`, with the same layout as the terminal.
+    #[arg(long)]
+    pub render: Option<String>,
+
+    /// Size in pixels of the `--render` image, 6x8 pixels per character
+    #[arg(long, default_value = "800x480")]
+    pub render_size: RenderSize,
+
+    /// Seconds between the writes of the `--render` image
+    #[arg(long, default_value = "10", value_parser = clap::value_parser!(u64).range(1..))]
+    pub render_interval: u64,
+
+    /// Play a recorded AVR file instead of connecting to the ADS-B server
+    ///
+    /// Frames with a `@` timestamp are played at the pace they were recorded. Pause with Space,
+    /// step a single frame with `.`, and change the speed with `<` and `>`.
+    #[arg(long)]
+    pub replay: Option<String>,
+
+    /// Aircraft sent out by `--emit-deltas` and drawn by `--render`, all aircraft are still
+    /// displayed in the terminal
+    #[command(flatten)]
+    pub privacy: PrivacyArgs,
+
+    /// Key bindings, only set from the `[keys]` table of `--config`
+    #[arg(skip)]
+    pub keys: Keymap,
+}
+
+impl Opts {
+    /// Simplification and smoothing of the tracks on Map, from `--track-tolerance` and
+    /// `--track-smoothing`
+    pub fn track_filter(&self) -> TrackFilter {
+        TrackFilter { tolerance: self.track_tolerance / 1000.0, smoothing: self.track_smoothing }
+    }
+
+    /// Source of the lines, from `--unix-socket`, `--stdin`, or `--host` and `--port`
+    pub fn source(&self) -> Source {
+        if let Some(path) = &self.unix_socket {
+            Source::Unix(path.clone())
+        } else if self.stdin {
+            Source::Stdin
+        } else {
+            Source::Tcp { host: self.host.to_string(), port: self.port }
+        }
+    }
 }
 
 #[cfg(test)]
@@ -141,25 +446,68 @@ mod tests {
         let exp_opt = Opts {
             host: Ipv4Addr::LOCALHOST,
             port: 30002,
+            input_format: InputFormat::Avr,
+            unix_socket: None,
+            stdin: false,
             lat: 35.0,
             long: -80.0,
             locations: vec![],
             disable_lat_long: false,
             disable_callsign: false,
+            disable_declutter: false,
             scale: 0.12,
             gpsd: false,
             gpsd_ip: "localhost".to_string(),
+            auto_position: false,
             filter_time: 120,
+            dim_age: 15,
+            stale_position: 30,
             log_folder: "logs".to_string(),
+            log: LogSink::File,
+            log_format: LogFormat::Text,
             touchscreen: false,
-            limit_parsing: false,
+            layout: ScreenLayout::Single,
+            projection: ProjectionKind::Mercator,
+            accept_df: DfFilter::all(),
+            soft_repair: 0,
             airports: None,
             airports_tz_filter: None,
+            runways: None,
+            centerline_length: 10.0,
             disable_icao: false,
             disable_heading: false,
+            heading_style: HeadingStyle::Arrow,
             disable_track: false,
+            track_tolerance: 0.0,
+            track_smoothing: 0,
+            disable_surface_vehicles: false,
             retry_tcp: false,
             max_range: 500.0,
+            show_rejected_fixes: false,
+            airplanes_ehs: false,
+            dedup_window: None,
+            quiet_decode: false,
+            max_rate: None,
+            input_queue: 8192,
+            fps: 20,
+            config: None,
+            min_altitude: None,
+            max_altitude: None,
+            coverage_min_altitude: None,
+            vert_rate_threshold: 300,
+            map_label: None,
+            label_altitude: AltitudeSource::Baro,
+            transition_altitude: 18000,
+            qnh_correction: false,
+            stats_ledger: None,
+            emit_deltas: None,
+            deltas_altitude: AltitudeSource::Baro,
+            render: None,
+            render_size: RenderSize { width: 800, height: 480 },
+            render_interval: 10,
+            replay: None,
+            privacy: PrivacyArgs::default(),
+            keys: Keymap::default(),
         };
         assert_eq!(exp_opt, opt);
 
@@ -175,6 +523,9 @@ mod tests {
         let exp_opt = Opts {
             host: Ipv4Addr::LOCALHOST,
             port: 30002,
+            input_format: InputFormat::Avr,
+            unix_socket: None,
+            stdin: false,
             lat: 35.0,
             long: -80.0,
             locations: vec![
@@ -183,21 +534,84 @@ mod tests {
             ],
             disable_lat_long: false,
             disable_callsign: false,
+            disable_declutter: false,
             scale: 0.12,
             gpsd: false,
             gpsd_ip: "localhost".to_string(),
+            auto_position: false,
             filter_time: 120,
+            dim_age: 15,
+            stale_position: 30,
             log_folder: "logs".to_string(),
+            log: LogSink::File,
+            log_format: LogFormat::Text,
             touchscreen: false,
-            limit_parsing: false,
+            layout: ScreenLayout::Single,
+            projection: ProjectionKind::Mercator,
+            accept_df: DfFilter::all(),
+            soft_repair: 0,
             airports: None,
             airports_tz_filter: None,
+            runways: None,
+            centerline_length: 10.0,
             disable_icao: false,
             disable_heading: false,
+            heading_style: HeadingStyle::Arrow,
             disable_track: false,
+            track_tolerance: 0.0,
+            track_smoothing: 0,
+            disable_surface_vehicles: false,
             retry_tcp: false,
             max_range: 500.0,
+            show_rejected_fixes: false,
+            airplanes_ehs: false,
+            dedup_window: None,
+            quiet_decode: false,
+            max_rate: None,
+            input_queue: 8192,
+            fps: 20,
+            config: None,
+            min_altitude: None,
+            max_altitude: None,
+            coverage_min_altitude: None,
+            vert_rate_threshold: 300,
+            map_label: None,
+            label_altitude: AltitudeSource::Baro,
+            transition_altitude: 18000,
+            qnh_correction: false,
+            stats_ledger: None,
+            emit_deltas: None,
+            deltas_altitude: AltitudeSource::Baro,
+            render: None,
+            render_size: RenderSize { width: 800, height: 480 },
+            render_interval: 10,
+            replay: None,
+            privacy: PrivacyArgs::default(),
+            keys: Keymap::default(),
         };
         assert_eq!(exp_opt, opt);
+
+        // the location is only required without --auto-position
+        assert!(Opts::try_parse_from(["radar"]).is_err());
+        let opt = Opts::try_parse_from(["radar", "--auto-position"]).unwrap();
+        assert!(opt.auto_position);
+        assert!(Opts::try_parse_from(["radar", "--auto-position", "--gpsd"]).is_err());
+
+        // a queue of 0 would drop every frame
+        assert!(Opts::try_parse_from(["radar", "--auto-position", "--input-queue=0"]).is_err());
+    }
+
+    #[test]
+    fn test_source() {
+        let opt = Opts::try_parse_from(["radar", "--auto-position", "--port=30005"]).unwrap();
+        assert_eq!(opt.source(), Source::Tcp { host: "127.0.0.1".to_string(), port: 30005 });
+        let opt = Opts::try_parse_from(["radar", "--auto-position", "--stdin"]).unwrap();
+        assert_eq!(opt.source(), Source::Stdin);
+        let opt =
+            Opts::try_parse_from(["radar", "--auto-position", "--unix-socket=/run/adsb.sock"])
+                .unwrap();
+        assert_eq!(opt.source(), Source::Unix("/run/adsb.sock".into()));
+        assert!(Opts::try_parse_from(["radar", "--auto-position", "--stdin", "--unix-socket=a"])
+            .is_err());
     }
 }
@@ -0,0 +1,350 @@
+//! TOML configuration file from `--config`
+//!
+//! Every key is optional and has the same name and meaning as the command line option, such as:
+//! ```toml
+//! lat = 35.0
+//! long = -80.0
+//! max_range = 300.0
+//! disable_track = true
+//! locations = [{ name = "home", lat = 35.1, long = -80.1 }]
+//!
+//! [keys]
+//! quit = "x"
+//! ```
+//!
+//! The `[keys]` table, only in the configuration file, remaps the keys, see [`crate::keys`].
+//!
+//! Values from the configuration file override the command line options. The file is reloaded
+//! at runtime with the `r` key or `SIGHUP`.
+
+use std::fmt::Display;
+use std::path::Path;
+use std::str::FromStr;
+
+use adsb_deku::DfFilter;
+use anyhow::{bail, Context, Result};
+use clap::ValueEnum;
+use rsadsb_common::altitude::AltitudeSource;
+use serde::de::{self, Deserializer};
+use serde::Deserialize;
+
+use crate::cli::{HeadingStyle, Location, Opts, ScreenLayout};
+use crate::keys::{parse_key, Action, Keymap};
+use crate::label::LabelTemplate;
+use crate::projection::ProjectionKind;
+
+/// Declare [`Config`] and [`Config::apply`] from the keys, each overriding the option of [`Opts`]
+/// with the same name
+macro_rules! config {
+    ($($(#[$attr:meta])* $name:ident: $ty:ty,)*) => {
+        /// Options read from the configuration file, `None` if not set
+        #[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+        #[serde(default, deny_unknown_fields)]
+        pub struct Config {
+            $($(#[$attr])* pub $name: Option<$ty>,)*
+        }
+
+        impl Config {
+            /// Override `opts` with the values set in this configuration
+            pub fn apply(&self, opts: &mut Opts) {
+                $(if let Some(value) = &self.$name {
+                    opts.$name = value.clone().into();
+                })*
+            }
+        }
+    };
+}
+
+config! {
+    lat: f64,
+    long: f64,
+    locations: Vec<Location>,
+    disable_lat_long: bool,
+    disable_callsign: bool,
+    disable_icao: bool,
+    disable_heading: bool,
+    #[serde(deserialize_with = "value_enum")]
+    heading_style: HeadingStyle,
+    disable_track: bool,
+    track_tolerance: f64,
+    track_smoothing: u8,
+    disable_declutter: bool,
+    disable_surface_vehicles: bool,
+    scale: f64,
+    filter_time: u64,
+    dim_age: u64,
+    stale_position: u64,
+    #[serde(deserialize_with = "value_enum")]
+    layout: ScreenLayout,
+    #[serde(deserialize_with = "value_enum")]
+    projection: ProjectionKind,
+    #[serde(deserialize_with = "parsed")]
+    accept_df: DfFilter,
+    soft_repair: u8,
+    airports: String,
+    airports_tz_filter: String,
+    runways: String,
+    centerline_length: f64,
+    max_range: f64,
+    show_rejected_fixes: bool,
+    airplanes_ehs: bool,
+    dedup_window: u64,
+    max_rate: u32,
+    input_queue: usize,
+    fps: u32,
+    quiet_decode: bool,
+    min_altitude: i32,
+    max_altitude: i32,
+    coverage_min_altitude: i32,
+    vert_rate_threshold: u16,
+    #[serde(deserialize_with = "parsed")]
+    map_label: LabelTemplate,
+    #[serde(deserialize_with = "parsed")]
+    label_altitude: AltitudeSource,
+    transition_altitude: i32,
+    qnh_correction: bool,
+    #[serde(deserialize_with = "parsed")]
+    deltas_altitude: AltitudeSource,
+    /// `[keys]` table of `action = "key"`, over the default keys
+    #[serde(deserialize_with = "keys")]
+    keys: Keymap,
+}
+
+impl Config {
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let s = std::fs::read_to_string(path)
+            .with_context(|| format!("unable to read config: {}", path.display()))?;
+        s.parse().with_context(|| format!("invalid config: {}", path.display()))
+    }
+
+    /// Check the ranges of the values that aren't checked by their type
+    fn validate(&self) -> Result<()> {
+        if self.track_smoothing.is_some_and(|smoothing| smoothing > 4) {
+            bail!("track_smoothing: expected 0 to 4");
+        }
+        if self.soft_repair.is_some_and(|bits| bits > 4) {
+            bail!("soft_repair: expected 0 to 4");
+        }
+        if self.centerline_length.is_some_and(|length| length < 0.0) {
+            bail!("centerline_length: expected positive number");
+        }
+        if self.fps.is_some_and(|fps| !(1..=120).contains(&fps)) {
+            bail!("fps: expected 1 to 120");
+        }
+        if self.input_queue == Some(0) {
+            bail!("input_queue: expected positive number");
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Config {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let config: Self = toml_edit::de::from_str(s)?;
+        config.validate()?;
+        Ok(config)
+    }
+}
+
+/// Value of a `--<option>` with possible values, such as `layout = "split"`
+fn value_enum<'de, D: Deserializer<'de>, T: ValueEnum>(
+    deserializer: D,
+) -> Result<Option<T>, D::Error> {
+    let value = String::deserialize(deserializer)?;
+    T::from_str(&value, true).map(Some).map_err(|e| de::Error::custom(format!("{value}: {e}")))
+}
+
+/// Value parsed from a string, such as `accept_df = "17,18"`
+fn parsed<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: FromStr,
+    T::Err: Display,
+{
+    let value = String::deserialize(deserializer)?;
+    value.parse().map(Some).map_err(|e| de::Error::custom(format!("{value}: {e}")))
+}
+
+/// Table of `action = "key"`, over the default keys
+fn keys<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Keymap>, D::Error> {
+    let table = std::collections::BTreeMap::<String, String>::deserialize(deserializer)?;
+    let keymap = || -> Result<Keymap> {
+        let mut keymap = Keymap::default();
+        for (name, key) in &table {
+            let action: Action = name.parse().context("keys")?;
+            keymap.bind(action, parse_key(key).with_context(|| format!("keys.{name}"))?);
+        }
+        keymap.validate().context("keys")?;
+        Ok(keymap)
+    };
+    keymap().map(Some).map_err(|e| de::Error::custom(format!("{e:#}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+    use crossterm::event::KeyCode;
+    use rsadsb_common::track::TrackFilter;
+
+    use super::*;
+
+    #[test]
+    fn test_config() {
+        let config: Config = r#"
+            lat = 35
+            long = -80.5
+            disable_track = true
+            filter_time = 60
+            dim_age = 10
+            show_rejected_fixes = true
+            layout = "split"
+            projection = "azimuthal"
+            max_altitude = 10000
+            coverage_min_altitude = 5000
+            fps = 5
+            heading_style = "wings"
+            track_tolerance = 50
+            track_smoothing = 2
+            soft_repair = 3
+            airports = "airports.csv"
+            runways = "runways.csv"
+            centerline_length = 5
+            map_label = "{callsign} {alt_fl}"
+            label_altitude = "gnss-corrected"
+            transition_altitude = 6000
+            qnh_correction = true
+            locations = [{ name = "a", lat = 56.5, long = 57.2 }, { name = "b", lat = 1, long = 2 }]
+        "#
+        .parse()
+        .unwrap();
+        assert_eq!(
+            config,
+            Config {
+                lat: Some(35.0),
+                long: Some(-80.5),
+                disable_track: Some(true),
+                filter_time: Some(60),
+                dim_age: Some(10),
+                show_rejected_fixes: Some(true),
+                layout: Some(ScreenLayout::Split),
+                projection: Some(ProjectionKind::Azimuthal),
+                max_altitude: Some(10000),
+                coverage_min_altitude: Some(5000),
+                fps: Some(5),
+                heading_style: Some(HeadingStyle::Wings),
+                track_tolerance: Some(50.0),
+                track_smoothing: Some(2),
+                soft_repair: Some(3),
+                airports: Some("airports.csv".to_string()),
+                runways: Some("runways.csv".to_string()),
+                centerline_length: Some(5.0),
+                map_label: Some("{callsign} {alt_fl}".parse().unwrap()),
+                label_altitude: Some(AltitudeSource::GnssCorrected),
+                transition_altitude: Some(6000),
+                qnh_correction: Some(true),
+                locations: Some(vec![
+                    Location { name: "a".to_string(), lat: 56.5, long: 57.2 },
+                    Location { name: "b".to_string(), lat: 1.0, long: 2.0 },
+                ]),
+                ..Config::default()
+            }
+        );
+
+        let mut opts =
+            Opts::try_parse_from(["radar", "--lat=1.0", "--long=2.0", "--max-range=100"]).unwrap();
+        config.apply(&mut opts);
+        assert_eq!(opts.lat, 35.0);
+        assert_eq!(opts.long, -80.5);
+        assert!(opts.disable_track);
+        assert_eq!(opts.filter_time, 60);
+        assert_eq!(opts.dim_age, 10);
+        assert_eq!(opts.max_range, 100.0);
+        assert!(opts.show_rejected_fixes);
+        assert_eq!(opts.locations.len(), 2);
+        assert_eq!(opts.max_altitude, Some(10000));
+        assert_eq!(opts.coverage_min_altitude, Some(5000));
+        assert_eq!(opts.fps, 5);
+        assert_eq!(opts.heading_style, HeadingStyle::Wings);
+        assert_eq!(opts.track_filter(), TrackFilter { tolerance: 0.05, smoothing: 2 });
+        assert_eq!(opts.projection, ProjectionKind::Azimuthal);
+        assert_eq!(opts.soft_repair, 3);
+        assert_eq!(opts.runways.as_deref(), Some("runways.csv"));
+        assert_eq!(opts.centerline_length, 5.0);
+        assert_eq!(opts.map_label.unwrap().to_string(), "{callsign} {alt_fl}");
+        assert_eq!(opts.label_altitude, AltitudeSource::GnssCorrected);
+        assert_eq!(opts.deltas_altitude, AltitudeSource::Baro);
+        assert_eq!(opts.transition_altitude, 6000);
+        assert!(opts.qnh_correction);
+    }
+
+    #[test]
+    fn test_config_array_of_tables() {
+        let config: Config = r#"
+            [[locations]]
+            name = "a"
+            lat = 56.5
+            long = 57.2
+        "#
+        .parse()
+        .unwrap();
+        assert_eq!(
+            config.locations,
+            Some(vec![Location { name: "a".to_string(), lat: 56.5, long: 57.2 }])
+        );
+    }
+
+    #[test]
+    fn test_config_invalid() {
+        assert!("unknown = 1".parse::<Config>().is_err());
+        assert!("lat = \"35\"".parse::<Config>().is_err());
+        assert!("filter_time = -1".parse::<Config>().is_err());
+        assert!("layout = \"double\"".parse::<Config>().is_err());
+        assert!("projection = \"globe\"".parse::<Config>().is_err());
+        assert!("map_label = \"{unknown}\"".parse::<Config>().is_err());
+        assert!("fps = 0".parse::<Config>().is_err());
+        assert!("input_queue = 0".parse::<Config>().is_err());
+        assert!("deltas_altitude = \"geometric\"".parse::<Config>().is_err());
+        assert!("qnh_correction = \"yes\"".parse::<Config>().is_err());
+        assert!("show_rejected_fixes = 1".parse::<Config>().is_err());
+        assert!("heading_style = \"plane\"".parse::<Config>().is_err());
+        assert!("track_smoothing = 5".parse::<Config>().is_err());
+        assert!("soft_repair = 5".parse::<Config>().is_err());
+        assert!("centerline_length = -1".parse::<Config>().is_err());
+        assert!("locations = [{ name = \"a\" }]".parse::<Config>().is_err());
+    }
+
+    #[test]
+    fn test_config_keys() {
+        let config: Config = r#"
+            [keys]
+            quit = "x"
+            zoom_in = "="
+            next_tab = "space"
+            pause = "tab"
+        "#
+        .parse()
+        .unwrap();
+        let keys = config.keys.clone().unwrap();
+        assert_eq!(keys.action(KeyCode::Char('x')), Some(Action::Quit));
+        assert_eq!(keys.action(KeyCode::Char('=')), Some(Action::ZoomIn));
+        assert_eq!(keys.action(KeyCode::Char(' ')), Some(Action::NextTab));
+        assert_eq!(keys.action(KeyCode::Char('q')), None);
+        // unchanged
+        assert_eq!(keys.action(KeyCode::Char('-')), Some(Action::ZoomOut));
+
+        let mut opts = Opts::try_parse_from(["radar", "--lat=1.0", "--long=2.0"]).unwrap();
+        config.apply(&mut opts);
+        assert_eq!(opts.keys, keys);
+
+        // pause is still on Space
+        let err = "keys = { next_tab = \"space\" }".parse::<Config>().unwrap_err();
+        assert!(format!("{err:#}").ends_with("keys: next_tab and pause have the same key: Space\n"));
+        assert!("keys = { zoom = \"z\" }".parse::<Config>().is_err());
+        assert!("keys = { quit = \"f1\" }".parse::<Config>().is_err());
+        assert!("keys = { quit = 1 }".parse::<Config>().is_err());
+        assert!("keys = \"q\"".parse::<Config>().is_err());
+    }
+}
@@ -1,12 +1,62 @@
+use std::fs;
+use std::time::SystemTime;
+
 use adsb_deku::cpr::Position;
 use adsb_deku::ICAO;
+use anyhow::{Context, Result};
+use crossterm::event::MouseEvent;
 use ratatui::layout::Rect;
 use ratatui::style::Color;
 use ratatui::widgets::canvas::{Canvas, Points};
 use ratatui::widgets::Block;
-use rsadsb_common::Airplanes;
+use rsadsb_common::coverage::{altitude_band, band_label, RangeCoverage, ALTITUDE_BANDS};
+use rsadsb_common::{timestamp, Airplanes};
+
+use crate::keys::Action;
+use crate::polar::build_tab_polar;
+use crate::tab::{drag_map, handle_map_key, Tab, TabContext};
+use crate::{draw_locations, Settings, DEFAULT_PRECISION, MAX_PLOT_HIGH, MAX_PLOT_LOW};
+
+/// Coverage tab, or its polar view
+pub struct CoverageTab;
+
+impl Tab for CoverageTab {
+    fn title(&self, _: &Airplanes) -> String {
+        "Coverage".to_string()
+    }
+
+    fn render(&self, f: &mut ratatui::Frame, chunks: &[Rect], ctx: &TabContext<'_>) -> Rect {
+        if ctx.settings.coverage_polar {
+            build_tab_polar(f, chunks, ctx.polar, ctx.settings.opts.coverage_min_altitude);
+        } else {
+            build_tab_coverage(f, chunks, ctx.settings, ctx.coverage);
+        }
+        chunks[1]
+    }
+
+    fn handle_key(&mut self, action: Action, settings: &mut Settings, _: &Airplanes) {
+        match action {
+            Action::Polar => settings.coverage_polar ^= true,
+            Action::Export => settings.export_coverage = true,
+            Action::Band => settings.next_coverage_band(),
+            action => handle_map_key(action, settings),
+        }
+    }
 
-use crate::{draw_locations, Settings, MAX_PLOT_HIGH, MAX_PLOT_LOW};
+    fn handle_mouse(
+        &mut self,
+        mouse: MouseEvent,
+        settings: &mut Settings,
+        _: &Airplanes,
+        area: Rect,
+    ) {
+        drag_map(mouse, settings, area);
+    }
+
+    fn touchscreen(&self) -> bool {
+        true
+    }
+}
 
 /// Accuracy of latitude/longitude for Coverage is affected by this variable.
 ///
@@ -14,17 +64,67 @@ use crate::{draw_locations, Settings, MAX_PLOT_HIGH, MAX_PLOT_LOW};
 /// position for the sake of an usable heatmap
 const COVERAGE_MASK: f64 = 100.0;
 
-// Add to the coverage tab data structure `coverage_airplanes`.
+/// Positions seen in the Coverage tab, by band of `ALTITUDE_BANDS`
+///
+/// Each position is (lat, long, seen_number, icao), where `seen_number` counts the other aircraft
+/// seen at the same position.
+#[derive(Debug)]
+pub struct Coverage {
+    bands: [Vec<(f64, f64, u32, ICAO)>; ALTITUDE_BANDS.len()],
+}
+
+impl Default for Coverage {
+    fn default() -> Self {
+        Self { bands: std::array::from_fn(|_| vec![]) }
+    }
+}
+
+impl Coverage {
+    /// Positions of `band`, or of all bands
+    fn positions(&self, band: Option<usize>) -> impl Iterator<Item = &(f64, f64, u32, ICAO)> {
+        self.bands
+            .iter()
+            .enumerate()
+            .filter(move |(i, _)| band.map_or(true, |band| band == *i))
+            .flat_map(|(_, positions)| positions)
+    }
+}
+
+/// Return true if an aircraft at `altitude` is collected in the coverage, at or above the
+/// `min_altitude` of `--coverage-min-altitude`
+///
+/// Aircraft without a known altitude are only collected without a floor.
+pub fn above_coverage_floor(altitude: Option<i32>, min_altitude: Option<i32>) -> bool {
+    min_altitude.map_or(true, |floor| altitude.is_some_and(|altitude| altitude >= floor))
+}
+
+/// Title suffix of the Coverage tab for `--coverage-min-altitude`, if any
+pub fn coverage_floor_label(min_altitude: Option<i32>) -> Option<String> {
+    min_altitude.map(|floor| format!("(ALT >= {floor} ft)"))
+}
+
+// Add to the coverage tab data structure `coverage`, the aircraft under `min_altitude` are
+// skipped.
 //
 // Two events cause an addition:
-// 1: New plot from a lat/long position that didn't exist before
-// 2: New ICAO(plane) at a previously seen location
+// 1: New plot from a lat/long position that didn't exist before in the altitude band
+// 2: New ICAO(plane) at a previously seen location in the altitude band
 pub fn populate_coverage(
     adsb_airplanes: &Airplanes,
-    coverage_airplanes: &mut Vec<(f64, f64, u32, ICAO)>,
+    coverage: &mut Coverage,
+    min_altitude: Option<i32>,
 ) {
-    let all_position = adsb_airplanes.all_position();
-    for (all_icao, Position { latitude, longitude, .. }) in all_position {
+    for (all_icao, state) in adsb_airplanes.iter() {
+        let Some(Position { latitude, longitude, .. }) = state.coords.position else {
+            continue;
+        };
+        if !above_coverage_floor(state.coords.altitude(), min_altitude) {
+            continue;
+        }
+        // surface positions without an altitude are in the lowest band
+        let band = altitude_band(state.coords.altitude().unwrap_or(0));
+        let coverage_airplanes = &mut coverage.bands[band];
+        let all_icao = *all_icao;
         let latitude = (latitude * COVERAGE_MASK).round() / COVERAGE_MASK;
         let longitude = (longitude * COVERAGE_MASK).round() / COVERAGE_MASK;
 
@@ -61,15 +161,45 @@ pub fn populate_coverage(
     }
 }
 
+/// Write the max range by bearing and altitude band as an SVG polar diagram in the current
+/// directory, returning the path
+pub fn export_coverage(range_coverage: &RangeCoverage, settings: &Settings) -> Result<String> {
+    let now = timestamp::local(SystemTime::now(), settings.utc_offset);
+    let format = time::format_description::parse("[year][month][day]-[hour][minute][second]")?;
+    let path = format!("coverage-{}.svg", now.format(&format)?);
+    let mut title = format!(
+        "rsadsb coverage ({:.DEFAULT_PRECISION$}, {:.DEFAULT_PRECISION$}) {}",
+        settings.lat,
+        settings.long,
+        now.date()
+    );
+    if let Some(floor) = coverage_floor_label(settings.opts.coverage_min_altitude) {
+        title.push_str(&format!(" {floor}"));
+    }
+    fs::write(&path, range_coverage.to_svg(&title))
+        .with_context(|| format!("unable to write: {path}"))?;
+    Ok(path)
+}
+
 /// Render Coverage tab for tui display
 pub fn build_tab_coverage(
     f: &mut ratatui::Frame,
     chunks: &[Rect],
     settings: &Settings,
-    coverage_airplanes: &[(f64, f64, u32, ICAO)],
+    coverage: &Coverage,
 ) {
+    let mut title = "Coverage".to_string();
+    if let Some(band) = settings.coverage_band {
+        title.push_str(&format!(" ({})", band_label(band)));
+    }
+    if let Some(floor) = coverage_floor_label(settings.opts.coverage_min_altitude) {
+        title.push_str(&format!(" {floor}"));
+    }
+    if let Some(export) = &settings.coverage_export {
+        title.push_str(&format!(" - {export}"));
+    }
     let canvas = Canvas::default()
-        .block(Block::bordered().title("Coverage"))
+        .block(Block::bordered().title(title))
         .x_bounds([MAX_PLOT_LOW, MAX_PLOT_HIGH])
         .y_bounds([MAX_PLOT_LOW, MAX_PLOT_HIGH])
         .paint(|ctx| {
@@ -77,7 +207,7 @@ pub fn build_tab_coverage(
             draw_locations(ctx, settings);
 
             // draw ADSB tab airplanes
-            for (lat, long, seen_number, _) in coverage_airplanes.iter() {
+            for (lat, long, seen_number, _) in coverage.positions(settings.coverage_band) {
                 let (x, y) = settings.to_xy(*lat, *long);
 
                 let number: u32 = 100 + *seen_number * 50;
@@ -93,3 +223,43 @@ pub fn build_tab_coverage(
         });
     f.render_widget(canvas, chunks[1]);
 }
+
+#[cfg(test)]
+mod tests {
+    use adsb_deku::Frame;
+
+    use super::*;
+
+    #[test]
+    fn altitude_bands() {
+        let mut airplanes = Airplanes::new();
+        // even and odd airborne positions at 38000 ft
+        for frame in ["8d40621d58c382d690c8ac2863a7", "8d40621d58c386435cc412692ad6"] {
+            let bytes = hex::decode(frame).unwrap();
+            airplanes.action(Frame::from_bytes(&bytes).unwrap());
+        }
+        let mut coverage = Coverage::default();
+        populate_coverage(&airplanes, &mut coverage, None);
+        populate_coverage(&airplanes, &mut coverage, Some(38000));
+
+        assert_eq!(coverage.positions(None).count(), 1);
+        assert_eq!(coverage.positions(Some(3)).count(), 1);
+        assert_eq!(coverage.positions(Some(0)).count(), 0);
+        let (lat, long, seen_number, _) = coverage.positions(Some(3)).next().unwrap();
+        assert_eq!((*lat, *long, *seen_number), (52.27, 3.94, 0));
+
+        let mut coverage = Coverage::default();
+        populate_coverage(&airplanes, &mut coverage, Some(38100));
+        assert_eq!(coverage.positions(None).count(), 0);
+    }
+
+    #[test]
+    fn coverage_floor() {
+        assert!(above_coverage_floor(None, None));
+        assert!(above_coverage_floor(Some(500), None));
+        assert!(above_coverage_floor(Some(5000), Some(5000)));
+        assert!(!above_coverage_floor(Some(4975), Some(5000)));
+        assert!(!above_coverage_floor(None, Some(5000)));
+        assert_eq!(coverage_floor_label(Some(5000)).unwrap(), "(ALT >= 5000 ft)");
+    }
+}
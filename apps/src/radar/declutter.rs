@@ -0,0 +1,131 @@
+//! Placement of the aircraft labels on the Map, so they don't overlap
+//!
+//! Labels are placed in order of priority: the aircraft selected in the Airplanes tab first, then
+//! from the lowest altitude up. Each label is tried above, below, right and left of its aircraft,
+//! and hidden if it overlaps a placed label or aircraft in all of them.
+
+use std::cmp::Reverse;
+
+use ratatui::layout::Rect;
+use ratatui::widgets::Block;
+
+use crate::{MAX_PLOT_HIGH, MAX_PLOT_LOW};
+
+/// Canvas distance between an aircraft and the label above or below it
+const OFFSET: f64 = 20.0;
+
+/// Label of an aircraft at `x` and `y` on the Map canvas
+#[derive(Debug, Clone, PartialEq)]
+pub struct Label {
+    pub x: f64,
+    pub y: f64,
+    pub text: String,
+    /// selected in the Airplanes tab
+    pub selected: bool,
+    pub altitude: i32,
+}
+
+/// Cells used by a label or aircraft: row and columns `start..end`
+#[derive(Debug, Clone, Copy)]
+struct Cells {
+    row: u16,
+    start: u16,
+    end: u16,
+}
+
+impl Cells {
+    /// Overlapping or next to `other` on the same row
+    const fn collides(self, other: Self) -> bool {
+        self.row == other.row && self.start <= other.end && other.start <= self.end
+    }
+}
+
+/// Canvas of the Map drawn in `area`, to convert canvas coordinates to terminal cells
+struct Grid {
+    inner: Rect,
+}
+
+impl Grid {
+    /// Cells of `text` printed at `x` and `y`, `None` if outside of the canvas
+    fn cells(&self, x: f64, y: f64, text: &str) -> Option<Cells> {
+        let size = MAX_PLOT_HIGH - MAX_PLOT_LOW;
+        if !(MAX_PLOT_LOW..=MAX_PLOT_HIGH).contains(&x)
+            || !(MAX_PLOT_LOW..=MAX_PLOT_HIGH).contains(&y)
+        {
+            return None;
+        }
+        // same as `ratatui::widgets::canvas::Canvas` printing a label
+        let column =
+            ((x - MAX_PLOT_LOW) * f64::from(self.inner.width.saturating_sub(1)) / size) as u16;
+        let row =
+            ((MAX_PLOT_HIGH - y) * f64::from(self.inner.height.saturating_sub(1)) / size) as u16;
+        let len = text.chars().count() as u16;
+        Some(Cells { row, start: column, end: column.saturating_add(len) })
+    }
+
+    /// Canvas width of a cell
+    fn cell_width(&self) -> f64 {
+        (MAX_PLOT_HIGH - MAX_PLOT_LOW) / f64::from(self.inner.width.max(2) - 1)
+    }
+}
+
+/// Position of the `labels` that are shown on the Map drawn in `area`, as (x, y, text)
+pub fn declutter(mut labels: Vec<Label>, area: Rect) -> Vec<(f64, f64, String)> {
+    let grid = Grid { inner: Block::bordered().inner(area) };
+
+    // aircraft are never covered by labels
+    let mut used: Vec<Cells> =
+        labels.iter().filter_map(|label| grid.cells(label.x, label.y, " ")).collect();
+
+    labels.sort_by_key(|label| (Reverse(label.selected), label.altitude));
+
+    let mut placed = vec![];
+    for Label { x, y, text, .. } in labels {
+        let width = grid.cell_width();
+        let len = text.chars().count() as f64;
+        let candidates =
+            [(x, y + OFFSET), (x, y - OFFSET), (x + 2.0 * width, y), (x - (len + 1.0) * width, y)];
+        let position = candidates.into_iter().find_map(|(x, y)| {
+            let cells = grid.cells(x, y, &text)?;
+            (!used.iter().any(|used| used.collides(cells))).then_some((x, y, cells))
+        });
+        if let Some((x, y, cells)) = position {
+            used.push(cells);
+            placed.push((x, y, text));
+        }
+    }
+    placed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn label(x: f64, y: f64, text: &str, altitude: i32) -> Label {
+        Label { x, y, text: text.to_string(), selected: false, altitude }
+    }
+
+    #[test]
+    fn overlapping() {
+        let area = Rect::new(0, 0, 102, 52);
+
+        // apart
+        let placed =
+            declutter(vec![label(0.0, 0.0, "A", 1000), label(200.0, 0.0, "B", 1000)], area);
+        assert_eq!(placed, vec![(0.0, 20.0, "A".to_string()), (200.0, 20.0, "B".to_string())]);
+
+        // lowest altitude placed first, above the aircraft
+        let labels = vec![label(0.0, 0.0, "HIGH", 30000), label(0.0, 0.0, "LOW", 1000)];
+        let placed = declutter(labels, area);
+        assert_eq!(placed[0], (0.0, 20.0, "LOW".to_string()));
+        assert_eq!(placed[1], (0.0, -20.0, "HIGH".to_string()));
+
+        // selected before altitude, and hidden when there is no room left
+        let mut labels: Vec<_> = (0..5).map(|i| label(0.0, 0.0, &format!("AB{i}"), i)).collect();
+        labels[4].selected = true;
+        let placed = declutter(labels, area);
+        assert_eq!(placed.len(), 4);
+        assert_eq!(placed[0], (0.0, 20.0, "AB4".to_string()));
+        assert!(!placed.iter().any(|(_, _, text)| text == "AB3"));
+    }
+}
@@ -0,0 +1,123 @@
+//! Unix socket of `--emit-deltas`, streaming the [`DeltaWriter`] events to each connected client
+//!
+//! A new client first receives an `added` event for each current aircraft. Clients that don't
+//! read fast enough to keep up are disconnected, so they never slow down the display.
+
+use std::fs;
+use std::io::{BufWriter, ErrorKind};
+use std::os::unix::fs::FileTypeExt;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::PathBuf;
+
+use anyhow::{bail, Context, Result};
+use rsadsb_apps::delta::DeltaWriter;
+use rsadsb_common::altitude::AltitudeSource;
+use rsadsb_common::privacy::PrivacyFilter;
+use rsadsb_common::Airplanes;
+use tracing::{error, info};
+
+/// Listening socket and its clients, see [`self`]
+#[derive(Debug)]
+pub struct DeltaSocket {
+    path: PathBuf,
+    listener: UnixListener,
+    clients: Vec<DeltaWriter<BufWriter<UnixStream>>>,
+    altitude_source: AltitudeSource,
+}
+
+impl DeltaSocket {
+    /// Listen at `path`, replacing the socket of a previous run, the events having the altitude
+    /// from `altitude_source`
+    pub fn bind(path: impl Into<PathBuf>, altitude_source: AltitudeSource) -> Result<Self> {
+        let path = path.into();
+        if let Ok(metadata) = fs::symlink_metadata(&path) {
+            if !metadata.file_type().is_socket() {
+                bail!("not a socket: {}", path.display());
+            }
+            fs::remove_file(&path)?;
+        }
+        let listener = UnixListener::bind(&path)
+            .with_context(|| format!("unable to bind: {}", path.display()))?;
+        listener.set_nonblocking(true)?;
+        Ok(Self { path, listener, clients: vec![], altitude_source })
+    }
+
+    /// Accept the new clients, and write the events since the previous call to all clients
+    ///
+    /// The aircraft filtered by `privacy` are dropped or anonymized, see [`PrivacyFilter::states`].
+    pub fn publish(&mut self, airplanes: &Airplanes, privacy: Option<&PrivacyFilter>) {
+        loop {
+            match self.listener.accept() {
+                Ok((stream, _)) => {
+                    if let Err(e) = stream.set_nonblocking(true) {
+                        error!("emit deltas: {e}");
+                        continue;
+                    }
+                    info!("emit deltas: client connected");
+                    let writer = DeltaWriter::new(BufWriter::new(stream))
+                        .with_altitude_source(self.altitude_source);
+                    self.clients.push(writer);
+                }
+                Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+                Err(e) => {
+                    error!("emit deltas: {e}");
+                    break;
+                }
+            }
+        }
+
+        self.clients.retain_mut(|client| {
+            let written = match privacy {
+                Some(privacy) => client.write_states(privacy.states(airplanes)),
+                None => client.write(airplanes),
+            };
+            match written {
+                Ok(_) => true,
+                Err(e) => {
+                    info!("emit deltas: client disconnected: {e}");
+                    false
+                }
+            }
+        });
+    }
+}
+
+impl Drop for DeltaSocket {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{BufRead, BufReader};
+
+    use rsadsb_common::sbs::SbsMessage;
+
+    use super::*;
+
+    #[test]
+    fn clients() {
+        let path = std::env::temp_dir().join(format!("radar-deltas-{}.sock", std::process::id()));
+        let mut socket = DeltaSocket::bind(&path, AltitudeSource::Baro).unwrap();
+        let client = UnixStream::connect(&path).unwrap();
+
+        let mut airplanes = Airplanes::new();
+        let line = "MSG,5,1,1,A1B2C3,1,,,,,,37000,,,,,,,,,,";
+        airplanes.apply_sbs(&SbsMessage::parse_line(line).unwrap().unwrap());
+        socket.publish(&airplanes, None);
+
+        let mut line = String::new();
+        BufReader::new(client).read_line(&mut line).unwrap();
+        assert_eq!(
+            line,
+            "{\"event\":\"added\",\"icao\":\"a1b2c3\",\"fields\":{\"altitude\":37000}}\n"
+        );
+
+        // the socket of a previous run is replaced
+        std::mem::forget(socket);
+        let socket = DeltaSocket::bind(&path, AltitudeSource::Baro).unwrap();
+        drop(socket);
+        assert!(!path.exists());
+    }
+}
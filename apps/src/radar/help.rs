@@ -1,9 +1,32 @@
 use ratatui::layout::{Constraint, Direction, Layout, Rect};
 use ratatui::style::{Color, Style};
 use ratatui::widgets::{Block, Row, Table};
+use rsadsb_common::Airplanes;
+
+use crate::keys::{Action, Keymap};
+use crate::tab::{Tab, TabContext};
+
+/// Help tab, with the keys of each tab from `--config`
+pub struct HelpTab;
+
+impl Tab for HelpTab {
+    fn title(&self, _: &Airplanes) -> String {
+        "Help".to_string()
+    }
+
+    fn render(&self, f: &mut ratatui::Frame, chunks: &[Rect], ctx: &TabContext<'_>) -> Rect {
+        build_tab_help(f, chunks, &ctx.settings.opts.keys);
+        chunks[1]
+    }
+}
 
 /// Render Help tab for tui display
-pub fn build_tab_help(f: &mut ratatui::Frame, chunks: &[Rect]) {
+pub fn build_tab_help(f: &mut ratatui::Frame, chunks: &[Rect], keys: &Keymap) {
+    let row = |action, text: &str| Row::new(vec![keys.label(action), text.to_string()]);
+    let pair = |first, second, text: &str| {
+        Row::new(vec![format!("{} {}", keys.label(first), keys.label(second)), text.to_string()])
+    };
+
     let horizontal_chunks = Layout::default()
         .direction(Direction::Horizontal)
         .constraints([
@@ -17,9 +40,10 @@ pub fn build_tab_help(f: &mut ratatui::Frame, chunks: &[Rect]) {
         .direction(Direction::Vertical)
         .constraints([
             Constraint::Percentage(2),
-            Constraint::Percentage(40),
-            Constraint::Percentage(40),
-            Constraint::Percentage(10),
+            Constraint::Percentage(34),
+            Constraint::Percentage(26),
+            Constraint::Percentage(18),
+            Constraint::Percentage(18),
             Constraint::Percentage(2),
         ])
         .split(horizontal_chunks[1]);
@@ -31,13 +55,15 @@ pub fn build_tab_help(f: &mut ratatui::Frame, chunks: &[Rect]) {
         Row::new(vec!["F3", "Move to Airplanes screen"]),
         Row::new(vec!["F4", "Move to Stats screen"]),
         Row::new(vec!["F5", "Move to Help screen"]),
-        Row::new(vec!["l", "control --disable-lat-long"]),
-        Row::new(vec!["i", "control --disable-icao"]),
-        Row::new(vec!["h", "control --disable-heading"]),
-        Row::new(vec!["t", "control --disable-track"]),
-        Row::new(vec!["n", "toggle --disable-callsign"]),
-        Row::new(vec!["TAB", "Move to Next screen"]),
-        Row::new(vec!["q", "Quit this app"]),
+        row(Action::ToggleLatLong, "control --disable-lat-long"),
+        row(Action::ToggleIcao, "control --disable-icao"),
+        row(Action::ToggleHeading, "control --disable-heading"),
+        row(Action::ToggleTrack, "control --disable-track"),
+        row(Action::ToggleCallsign, "toggle --disable-callsign"),
+        row(Action::ToggleDeclutter, "toggle --disable-declutter"),
+        row(Action::Reload, "Reload --config"),
+        row(Action::NextTab, "Move to Next screen"),
+        row(Action::Quit, "Quit this app"),
         Row::new(vec!["ctrl+c", "Quit this app"]),
     ];
     let widths = &[Constraint::Percentage(10), Constraint::Percentage(90)];
@@ -50,13 +76,18 @@ pub fn build_tab_help(f: &mut ratatui::Frame, chunks: &[Rect]) {
 
     // Second help section
     let rows = vec![
-        Row::new(vec!["-", "Zoom out"]),
-        Row::new(vec!["+", "Zoom in"]),
-        Row::new(vec!["Up", "Move map up"]),
-        Row::new(vec!["Down", "Move map down"]),
-        Row::new(vec!["Left", "Move map left"]),
-        Row::new(vec!["Right", "Move map right"]),
-        Row::new(vec!["Enter", "Map position reset"]),
+        row(Action::ZoomOut, "Zoom out"),
+        row(Action::ZoomIn, "Zoom in"),
+        row(Action::Up, "Move map up"),
+        row(Action::Down, "Move map down"),
+        row(Action::Left, "Move map left"),
+        row(Action::Right, "Move map right"),
+        row(Action::Enter, "Map position reset"),
+        row(Action::Measure, "Toggle Map measure mode, click two points or aircraft"),
+        Row::new(vec!["Click", "Select the clicked aircraft on the Map, right click to clear"]),
+        row(Action::Polar, "Toggle Coverage polar view (bearing/elevation)"),
+        row(Action::Band, "Show the next Coverage altitude band, then all bands"),
+        row(Action::Export, "Export Coverage max range as an SVG polar diagram"),
     ];
     let table = Table::new(rows, widths)
         .style(Style::default().fg(Color::White))
@@ -67,9 +98,19 @@ pub fn build_tab_help(f: &mut ratatui::Frame, chunks: &[Rect]) {
 
     // Third help section
     let rows = [
-        Row::new(vec!["Up", "Move selection upward"]),
-        Row::new(vec!["Down", "Move selection downward"]),
-        Row::new(vec!["Enter", "Center Map tab on selected aircraft"]),
+        row(Action::Up, "Move selection upward"),
+        row(Action::Down, "Move selection downward"),
+        row(Action::Enter, "Center Map tab on selected aircraft"),
+        row(
+            Action::Sort,
+            "Sort by the next column: ICAO, distance, altitude, speed, seen, call sign",
+        ),
+        pair(Action::FloorDown, Action::FloorUp, "Lower/raise the altitude floor, also on Map"),
+        pair(
+            Action::CeilingDown,
+            Action::CeilingUp,
+            "Lower/raise the altitude ceiling, also on Map",
+        ),
     ];
     let table = Table::new(rows, widths)
         .style(Style::default().fg(Color::White))
@@ -77,4 +118,18 @@ pub fn build_tab_help(f: &mut ratatui::Frame, chunks: &[Rect]) {
         .column_spacing(1)
         .block(Block::bordered().title("Key Bindings - Airplanes"));
     f.render_widget(table, vertical_chunks[3]);
+
+    // Fourth help section
+    let rows = [
+        row(Action::Pause, "Pause or resume playback"),
+        row(Action::Step, "Pause and play the next frame"),
+        row(Action::Faster, "Double the playback speed"),
+        row(Action::Slower, "Halve the playback speed"),
+    ];
+    let table = Table::new(rows, widths)
+        .style(Style::default().fg(Color::White))
+        .header(Row::new(vec!["Key", "Action"]).bottom_margin(1))
+        .column_spacing(1)
+        .block(Block::bordered().title("Key Bindings - Replay (--replay)"));
+    f.render_widget(table, vertical_chunks[4]);
 }
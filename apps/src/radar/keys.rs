@@ -0,0 +1,280 @@
+//! Key bindings, remapped with the `[keys]` table of `--config`
+//!
+//! ```toml
+//! [keys]
+//! quit = "x"
+//! zoom_in = "="
+//! reload = "R"
+//! ```
+//!
+//! Keys are a single character, or one of the names of [`NAMED_KEYS`]. The function keys
+//! selecting the tabs and ctrl+c can't be remapped.
+
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+use anyhow::{anyhow, bail, Result};
+use crossterm::event::KeyCode;
+
+/// Names of the keys that aren't a single character, lowercase in the configuration file
+const NAMED_KEYS: [(&str, KeyCode); 14] = [
+    ("up", KeyCode::Up),
+    ("down", KeyCode::Down),
+    ("left", KeyCode::Left),
+    ("right", KeyCode::Right),
+    ("enter", KeyCode::Enter),
+    ("tab", KeyCode::Tab),
+    ("space", KeyCode::Char(' ')),
+    ("esc", KeyCode::Esc),
+    ("backspace", KeyCode::Backspace),
+    ("delete", KeyCode::Delete),
+    ("home", KeyCode::Home),
+    ("end", KeyCode::End),
+    ("pageup", KeyCode::PageUp),
+    ("pagedown", KeyCode::PageDown),
+];
+
+/// Action of a key, named as in the `[keys]` table
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Action {
+    Quit,
+    NextTab,
+    ToggleLatLong,
+    ToggleIcao,
+    ToggleHeading,
+    ToggleTrack,
+    ToggleCallsign,
+    ToggleDeclutter,
+    Reload,
+    Pause,
+    Step,
+    Faster,
+    Slower,
+    ZoomOut,
+    ZoomIn,
+    Up,
+    Down,
+    Left,
+    Right,
+    Enter,
+    Measure,
+    Polar,
+    Band,
+    Export,
+    FloorUp,
+    FloorDown,
+    CeilingUp,
+    CeilingDown,
+    Sort,
+}
+
+impl Action {
+    pub const ALL: [Self; 29] = [
+        Self::Quit,
+        Self::NextTab,
+        Self::ToggleLatLong,
+        Self::ToggleIcao,
+        Self::ToggleHeading,
+        Self::ToggleTrack,
+        Self::ToggleCallsign,
+        Self::ToggleDeclutter,
+        Self::Reload,
+        Self::Pause,
+        Self::Step,
+        Self::Faster,
+        Self::Slower,
+        Self::ZoomOut,
+        Self::ZoomIn,
+        Self::Up,
+        Self::Down,
+        Self::Left,
+        Self::Right,
+        Self::Enter,
+        Self::Measure,
+        Self::Polar,
+        Self::Band,
+        Self::Export,
+        Self::FloorUp,
+        Self::FloorDown,
+        Self::CeilingUp,
+        Self::CeilingDown,
+        Self::Sort,
+    ];
+
+    /// Name in the `[keys]` table
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Quit => "quit",
+            Self::NextTab => "next_tab",
+            Self::ToggleLatLong => "toggle_lat_long",
+            Self::ToggleIcao => "toggle_icao",
+            Self::ToggleHeading => "toggle_heading",
+            Self::ToggleTrack => "toggle_track",
+            Self::ToggleCallsign => "toggle_callsign",
+            Self::ToggleDeclutter => "toggle_declutter",
+            Self::Reload => "reload",
+            Self::Pause => "pause",
+            Self::Step => "step",
+            Self::Faster => "faster",
+            Self::Slower => "slower",
+            Self::ZoomOut => "zoom_out",
+            Self::ZoomIn => "zoom_in",
+            Self::Up => "up",
+            Self::Down => "down",
+            Self::Left => "left",
+            Self::Right => "right",
+            Self::Enter => "enter",
+            Self::Measure => "measure",
+            Self::Polar => "polar",
+            Self::Band => "band",
+            Self::Export => "export",
+            Self::FloorUp => "floor_up",
+            Self::FloorDown => "floor_down",
+            Self::CeilingUp => "ceiling_up",
+            Self::CeilingDown => "ceiling_down",
+            Self::Sort => "sort",
+        }
+    }
+
+    fn default_key(self) -> KeyCode {
+        match self {
+            Self::Quit => KeyCode::Char('q'),
+            Self::NextTab => KeyCode::Tab,
+            Self::ToggleLatLong => KeyCode::Char('l'),
+            Self::ToggleIcao => KeyCode::Char('i'),
+            Self::ToggleHeading => KeyCode::Char('h'),
+            Self::ToggleTrack => KeyCode::Char('t'),
+            Self::ToggleCallsign => KeyCode::Char('n'),
+            Self::ToggleDeclutter => KeyCode::Char('d'),
+            Self::Reload => KeyCode::Char('r'),
+            Self::Pause => KeyCode::Char(' '),
+            Self::Step => KeyCode::Char('.'),
+            Self::Faster => KeyCode::Char('>'),
+            Self::Slower => KeyCode::Char('<'),
+            Self::ZoomOut => KeyCode::Char('-'),
+            Self::ZoomIn => KeyCode::Char('+'),
+            Self::Up => KeyCode::Up,
+            Self::Down => KeyCode::Down,
+            Self::Left => KeyCode::Left,
+            Self::Right => KeyCode::Right,
+            Self::Enter => KeyCode::Enter,
+            Self::Measure => KeyCode::Char('m'),
+            Self::Polar => KeyCode::Char('p'),
+            Self::Band => KeyCode::Char('b'),
+            Self::Export => KeyCode::Char('e'),
+            Self::FloorUp => KeyCode::Char(']'),
+            Self::FloorDown => KeyCode::Char('['),
+            Self::CeilingUp => KeyCode::Char('}'),
+            Self::CeilingDown => KeyCode::Char('{'),
+            Self::Sort => KeyCode::Char('s'),
+        }
+    }
+}
+
+impl FromStr for Action {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|action| action.name() == s)
+            .ok_or_else(|| anyhow!("unknown action: {s}"))
+    }
+}
+
+/// Key of each [`Action`], see [`self`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Keymap {
+    keys: BTreeMap<Action, KeyCode>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Self {
+            keys: Action::ALL.into_iter().map(|action| (action, action.default_key())).collect(),
+        }
+    }
+}
+
+impl Keymap {
+    /// Action of `code`, if any
+    pub fn action(&self, code: KeyCode) -> Option<Action> {
+        self.keys.iter().find(|(_, key)| **key == code).map(|(action, _)| *action)
+    }
+
+    /// Key of `action`, as shown in the Help tab
+    pub fn label(&self, action: Action) -> String {
+        let code = self.keys[&action];
+        match code {
+            KeyCode::Char(' ') => "Space".to_string(),
+            KeyCode::Char(c) => c.to_string(),
+            code => format!("{code:?}"),
+        }
+    }
+
+    /// Bind `action` to `code`, replacing its key
+    pub fn bind(&mut self, action: Action, code: KeyCode) {
+        self.keys.insert(action, code);
+    }
+
+    /// Check that no key has two actions
+    pub fn validate(&self) -> Result<()> {
+        let mut actions = BTreeMap::new();
+        for (action, code) in &self.keys {
+            if let Some(other) = actions.insert(format!("{code:?}"), action) {
+                bail!(
+                    "{} and {} have the same key: {}",
+                    other.name(),
+                    action.name(),
+                    self.label(*action)
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Key from its name in the `[keys]` table, a single character or one of [`NAMED_KEYS`]
+pub fn parse_key(s: &str) -> Result<KeyCode> {
+    let mut chars = s.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        return Ok(KeyCode::Char(c));
+    }
+    let name = s.to_lowercase();
+    NAMED_KEYS
+        .iter()
+        .find(|(key, _)| *key == name)
+        .map(|(_, code)| *code)
+        .ok_or_else(|| anyhow!("unknown key: {s}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn remap() {
+        let mut keymap = Keymap::default();
+        assert_eq!(keymap.action(KeyCode::Char('q')), Some(Action::Quit));
+        assert_eq!(keymap.action(KeyCode::Char('x')), None);
+        assert_eq!(keymap.label(Action::Pause), "Space");
+        assert_eq!(keymap.label(Action::NextTab), "Tab");
+        keymap.validate().unwrap();
+
+        keymap.bind(Action::Quit, parse_key("x").unwrap());
+        assert_eq!(keymap.action(KeyCode::Char('x')), Some(Action::Quit));
+        assert_eq!(keymap.action(KeyCode::Char('q')), None);
+
+        // same key as zoom_in
+        keymap.bind(Action::Quit, parse_key("+").unwrap());
+        let err = keymap.validate().unwrap_err().to_string();
+        assert_eq!(err, "quit and zoom_in have the same key: +");
+
+        assert_eq!(parse_key("PageUp").unwrap(), KeyCode::PageUp);
+        assert_eq!(parse_key("space").unwrap(), KeyCode::Char(' '));
+        assert!(parse_key("f1").is_err());
+        assert!(parse_key("").is_err());
+        assert!("zoom".parse::<Action>().is_err());
+        assert_eq!("floor_up".parse::<Action>().unwrap(), Action::FloorUp);
+    }
+}
@@ -0,0 +1,235 @@
+//! Template of the aircraft labels on the Map, from `--map-label`
+//!
+//! Placeholders in braces are replaced with the values of the aircraft, such as
+//! `{callsign} {alt_fl} {gs}`. Placeholders without a value are left out.
+
+use std::fmt;
+use std::str::FromStr;
+
+use adsb_deku::ICAO;
+use anyhow::{anyhow, bail};
+use rsadsb_common::altitude::{AltitudeDisplay, AltitudeSource};
+use rsadsb_common::{AirplaneDetails, AirplaneState};
+
+use crate::DEFAULT_PRECISION;
+
+/// Values that can be used in a [`LabelTemplate`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+    /// ICAO address
+    Icao,
+    /// Callsign, or ICAO address if not yet received
+    Callsign,
+    /// Altitude (ft), from `--label-altitude` and `--qnh-correction`
+    Alt,
+    /// Flight level of the barometric altitude, such as `FL350`, or feet below
+    /// `--transition-altitude`, such as `3000 ft`
+    AltFl,
+    /// Ground speed (kt)
+    Gs,
+    /// Track or heading (degrees)
+    Track,
+    /// Vertical rate (ft/min)
+    Vs,
+    Squawk,
+    Lat,
+    Long,
+    /// Distance from the receiver (km)
+    Dist,
+    /// Climb or descent arrow, see `--vert-rate-threshold`
+    Trend,
+}
+
+impl Field {
+    const ALL: [(&'static str, Self); 12] = [
+        ("icao", Self::Icao),
+        ("callsign", Self::Callsign),
+        ("alt", Self::Alt),
+        ("alt_fl", Self::AltFl),
+        ("gs", Self::Gs),
+        ("track", Self::Track),
+        ("vs", Self::Vs),
+        ("squawk", Self::Squawk),
+        ("lat", Self::Lat),
+        ("long", Self::Long),
+        ("dist", Self::Dist),
+        ("trend", Self::Trend),
+    ];
+
+    fn name(self) -> &'static str {
+        Self::ALL.iter().find(|(_, field)| *field == self).map_or("", |(name, _)| name)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Part {
+    Text(String),
+    Field(Field),
+}
+
+/// Parsed `--map-label`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LabelTemplate(Vec<Part>);
+
+impl FromStr for LabelTemplate {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = vec![];
+        let mut rest = s;
+        while let Some(start) = rest.find('{') {
+            if start > 0 {
+                parts.push(Part::Text(rest[..start].to_string()));
+            }
+            let end = rest[start..].find('}').ok_or_else(|| anyhow!("unclosed '{{' in: {s}"))?;
+            let name = &rest[start + 1..start + end];
+            let Some((_, field)) = Field::ALL.iter().find(|(n, _)| *n == name) else {
+                let names: Vec<_> = Field::ALL.iter().map(|(n, _)| *n).collect();
+                bail!("unknown label field: {{{name}}}, expected one of: {}", names.join(", "));
+            };
+            parts.push(Part::Field(*field));
+            rest = &rest[start + end + 1..];
+        }
+        if !rest.is_empty() {
+            parts.push(Part::Text(rest.to_string()));
+        }
+        Ok(Self(parts))
+    }
+}
+
+impl fmt::Display for LabelTemplate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for part in &self.0 {
+            match part {
+                Part::Text(text) => write!(f, "{text}")?,
+                Part::Field(field) => write!(f, "{{{}}}", field.name())?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl LabelTemplate {
+    /// Label of `icao`, with `arrow` from [`crate::airplanes::trend_arrow`] and the `altitude` of
+    /// `{alt}`
+    ///
+    /// Whitespace left by placeholders without a value is removed.
+    pub fn render(
+        &self,
+        icao: ICAO,
+        state: &AirplaneState,
+        details: &AirplaneDetails,
+        arrow: &str,
+        altitude: Option<i32>,
+        transition_altitude: i32,
+    ) -> String {
+        let mut label = String::new();
+        for part in &self.0 {
+            match part {
+                Part::Text(text) => label.push_str(text),
+                Part::Field(field) => {
+                    label.push_str(&Self::value(
+                        *field,
+                        icao,
+                        state,
+                        details,
+                        arrow,
+                        altitude,
+                        transition_altitude,
+                    ));
+                }
+            }
+        }
+        label.split_whitespace().collect::<Vec<_>>().join(" ")
+    }
+
+    fn value(
+        field: Field,
+        icao: ICAO,
+        state: &AirplaneState,
+        details: &AirplaneDetails,
+        arrow: &str,
+        altitude: Option<i32>,
+        transition_altitude: i32,
+    ) -> String {
+        let position = &details.position;
+        match field {
+            Field::Icao => icao.to_string(),
+            Field::Callsign => state.callsign.clone().unwrap_or_else(|| icao.to_string()),
+            Field::Alt => altitude.map_or_else(String::new, |v| v.to_string()),
+            Field::AltFl => state.altitude(AltitudeSource::Baro).map_or_else(String::new, |v| {
+                AltitudeDisplay::new(v, transition_altitude).to_string()
+            }),
+            Field::Gs => state.speed.map_or_else(String::new, |v| format!("{v:.0}")),
+            Field::Track => details.heading.map_or_else(String::new, |v| format!("{v:03.0}")),
+            Field::Vs => state.vert_speed.map_or_else(String::new, |v| format!("{v:+}")),
+            Field::Squawk => state.squawk.map_or_else(String::new, |v| format!("{v:04x}")),
+            Field::Lat => format!("{:.DEFAULT_PRECISION$}", position.latitude),
+            Field::Long => format!("{:.DEFAULT_PRECISION$}", position.longitude),
+            Field::Dist => format!("{:.DEFAULT_PRECISION$}", details.kilo_distance),
+            Field::Trend => arrow.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use adsb_deku::Frame;
+    use rsadsb_common::Airplanes;
+
+    use super::*;
+
+    #[test]
+    fn parse() {
+        let template: LabelTemplate = "{callsign} {alt_fl}/{gs}".parse().unwrap();
+        assert_eq!(template.to_string(), "{callsign} {alt_fl}/{gs}");
+
+        assert!("{callsign".parse::<LabelTemplate>().is_err());
+        assert!("{unknown}".parse::<LabelTemplate>().is_err());
+    }
+
+    #[test]
+    fn render() {
+        let mut airplanes = Airplanes::builder().position((52.0, 4.0)).build();
+        // even and odd airborne position, and identification
+        for frame in [
+            "8d40621d58c382d690c8ac2863a7",
+            "8d40621d58c386435cc412692ad6",
+            "8d40621d202cc371c32ce0576098",
+        ] {
+            let bytes = hex::decode(frame).unwrap();
+            airplanes.action(Frame::from_bytes(&bytes).unwrap());
+        }
+        let icao = ICAO([0x40, 0x62, 0x1d]);
+        let state = airplanes.get(icao).unwrap();
+        let details = airplanes.aircraft_details(icao).unwrap();
+
+        let template: LabelTemplate = "{callsign} {alt_fl} {gs} {squawk}{trend}".parse().unwrap();
+        let baro = state.altitude(AltitudeSource::Baro);
+        assert_eq!(template.render(icao, state, &details, "", baro, 18000), "KLM1023 FL380");
+
+        // DF5 squawking 7700
+        let bytes = hex::decode("28000aaa0ac6d4").unwrap();
+        airplanes.action(Frame::from_bytes(&bytes).unwrap());
+        let state = airplanes.get(icao).unwrap();
+        assert_eq!(template.render(icao, state, &details, "", baro, 18000), "KLM1023 FL380 7700");
+
+        let template: LabelTemplate = "{icao} ({lat}, {long})".parse().unwrap();
+        assert_eq!(
+            template.render(icao, state, &details, "↑", baro, 18000),
+            "40621d (52.266, 3.939)"
+        );
+
+        // airborne velocity, GNSS 550 ft above the barometric altitude, and the flight level stays
+        // barometric
+        let bytes = hex::decode("8d40621d994409940838175b284f").unwrap();
+        airplanes.action(Frame::from_bytes(&bytes).unwrap());
+        let state = airplanes.get(icao).unwrap();
+        let template: LabelTemplate = "{alt} {alt_fl}".parse().unwrap();
+        let gnss = state.altitude(AltitudeSource::GnssCorrected);
+        assert_eq!(template.render(icao, state, &details, "", gnss, 18000), "38550 FL380");
+        assert_eq!(template.render(icao, state, &details, "", None, 18000), "FL380");
+        // below the transition altitude
+        assert_eq!(template.render(icao, state, &details, "", None, 40000), "38000 ft");
+    }
+}
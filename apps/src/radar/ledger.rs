@@ -0,0 +1,221 @@
+//! Daily statistics ledger from `--stats-ledger`
+//!
+//! Each line of the file is the JSON summary of one (local) day, such as:
+//! ```json
+//! {"date":"2024-09-02","unique_aircraft":412,"messages":1203345,"max_range":321.4,"hourly_messages":[0,0,...]}
+//! ```
+//!
+//! The current day is kept in memory and written with the previous days every
+//! [`SAVE_INTERVAL`] and when quitting. After a restart the current day is continued from the
+//! file, aircraft seen both before and after the restart are counted twice.
+
+use std::collections::BTreeSet;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use adsb_deku::ICAO;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use time::{Date, OffsetDateTime};
+use tracing::error;
+
+/// Time between writes of the ledger
+pub const SAVE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Statistics of a single day
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DayStats {
+    /// Local date, `YYYY-MM-DD`
+    pub date: String,
+    /// Amount of different aircraft addresses of ADS-B and TIS-B frames
+    pub unique_aircraft: u32,
+    /// Amount of decoded frames
+    pub messages: u64,
+    /// Farthest aircraft position (km)
+    pub max_range: Option<f64>,
+    /// Amount of decoded frames in each local hour
+    pub hourly_messages: [u64; 24],
+}
+
+impl DayStats {
+    fn new(date: String) -> Self {
+        Self { date, ..Self::default() }
+    }
+
+    /// Hour with the most decoded frames, and its amount of frames
+    pub fn busiest_hour(&self) -> Option<(usize, u64)> {
+        self.hourly_messages
+            .iter()
+            .copied()
+            .enumerate()
+            .filter(|(_, messages)| *messages > 0)
+            .max_by_key(|(_, messages)| *messages)
+    }
+}
+
+/// On-disk history of [`DayStats`]
+#[derive(Debug)]
+pub struct Ledger {
+    path: PathBuf,
+    /// Previous days, oldest first
+    days: Vec<DayStats>,
+    today: DayStats,
+    /// Date of `today`, compared with each frame instead of formatting its date
+    date: Date,
+    /// Aircraft seen today, since the ledger was opened
+    aircraft: BTreeSet<ICAO>,
+    saved: Instant,
+}
+
+impl Ledger {
+    /// Read the ledger at `path`, which is created on the first save if it doesn't exist
+    pub fn open(path: impl Into<PathBuf>, now: OffsetDateTime) -> Result<Self> {
+        let path = path.into();
+        let mut days: Vec<DayStats> = vec![];
+        match File::open(&path) {
+            Ok(file) => {
+                for line in BufReader::new(file).lines() {
+                    let line = line?;
+                    if line.trim().is_empty() {
+                        continue;
+                    }
+                    let day = serde_json::from_str(&line)
+                        .with_context(|| format!("invalid ledger: {}", path.display()))?;
+                    days.push(day);
+                }
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => (),
+            Err(e) => return Err(e).with_context(|| format!("unable to read: {}", path.display())),
+        }
+
+        let date = now.date();
+        let today = match days.last() {
+            Some(day) if day.date == date.to_string() => days.pop().unwrap_or_default(),
+            _ => DayStats::new(date.to_string()),
+        };
+        Ok(Self { path, days, today, date, aircraft: BTreeSet::new(), saved: Instant::now() })
+    }
+
+    /// Count a decoded frame received at `now`, from `icao` at `kilo_distance`
+    pub fn update(&mut self, now: OffsetDateTime, icao: Option<ICAO>, kilo_distance: Option<f64>) {
+        if now.date() != self.date {
+            self.date = now.date();
+            let today = std::mem::replace(&mut self.today, DayStats::new(self.date.to_string()));
+            self.days.push(today);
+            self.aircraft.clear();
+            self.save_or_log();
+        }
+
+        let today = &mut self.today;
+        today.messages += 1;
+        today.hourly_messages[usize::from(now.hour())] += 1;
+        if let Some(icao) = icao {
+            if self.aircraft.insert(icao) {
+                today.unique_aircraft += 1;
+            }
+        }
+        if let Some(kilo_distance) = kilo_distance {
+            if today.max_range.map_or(true, |max_range| kilo_distance > max_range) {
+                today.max_range = Some(kilo_distance);
+            }
+        }
+    }
+
+    /// Save if it wasn't saved within [`SAVE_INTERVAL`]
+    pub fn save_if_due(&mut self) {
+        if self.saved.elapsed() >= SAVE_INTERVAL {
+            self.save_or_log();
+        }
+    }
+
+    /// Write all days, replacing the file
+    pub fn save(&mut self) -> Result<()> {
+        self.saved = Instant::now();
+
+        // write to a temporary file first, so a crash never leaves a partial ledger
+        let tmp = self.path.with_extension("tmp");
+        let mut writer = BufWriter::new(
+            File::create(&tmp).with_context(|| format!("unable to write: {}", tmp.display()))?,
+        );
+        for day in self.days() {
+            serde_json::to_writer(&mut writer, day)?;
+            writeln!(writer)?;
+        }
+        writer.flush()?;
+        drop(writer);
+        std::fs::rename(&tmp, &self.path)
+            .with_context(|| format!("unable to write: {}", self.path.display()))
+    }
+
+    fn save_or_log(&mut self) {
+        if let Err(e) = self.save() {
+            error!("{e:?}");
+        }
+    }
+
+    /// All days including today, oldest first
+    pub fn days(&self) -> impl DoubleEndedIterator<Item = &DayStats> {
+        self.days.iter().chain(std::iter::once(&self.today))
+    }
+
+    /// Best unique aircraft, messages and range of all days
+    pub fn records(&self) -> (u32, u64, Option<f64>) {
+        self.days().fold((0, 0, None), |(aircraft, messages, range), day| {
+            let range = match (range, day.max_range) {
+                (Some(a), Some(b)) => Some(f64::max(a, b)),
+                (a, b) => a.or(b),
+            };
+            (aircraft.max(day.unique_aircraft), messages.max(day.messages), range)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ICAO_A: ICAO = ICAO([0xa0, 0x8f, 0x94]);
+    const ICAO_B: ICAO = ICAO([0x40, 0x62, 0x1d]);
+
+    fn at(unix: i64) -> OffsetDateTime {
+        OffsetDateTime::from_unix_timestamp(unix).unwrap()
+    }
+
+    #[test]
+    fn ledger() {
+        let path = std::env::temp_dir().join(format!("radar-ledger-{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        // 2024-09-02 17:05 UTC
+        let now = at(1_725_296_700);
+        let mut ledger = Ledger::open(&path, now).unwrap();
+        ledger.update(now, Some(ICAO_A), Some(100.0));
+        ledger.update(now, Some(ICAO_A), Some(250.5));
+        ledger.update(now, None, None);
+        ledger.update(at(1_725_300_000), Some(ICAO_B), Some(50.0));
+
+        // next day
+        let tomorrow = at(1_725_325_200);
+        ledger.update(tomorrow, Some(ICAO_A), None);
+        ledger.save().unwrap();
+
+        // continue tomorrow after a restart
+        let mut ledger = Ledger::open(&path, tomorrow).unwrap();
+        ledger.update(tomorrow, Some(ICAO_B), None);
+        let days: Vec<_> = ledger.days().collect();
+        assert_eq!(days.len(), 2);
+        assert_eq!(days[0].date, "2024-09-02");
+        assert_eq!(days[0].unique_aircraft, 2);
+        assert_eq!(days[0].messages, 4);
+        assert_eq!(days[0].max_range, Some(250.5));
+        assert_eq!(days[0].busiest_hour(), Some((17, 3)));
+        assert_eq!(days[1].date, "2024-09-03");
+        assert_eq!(days[1].unique_aircraft, 2);
+        assert_eq!(days[1].messages, 2);
+        assert_eq!(ledger.records(), (2, 4, Some(250.5)));
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
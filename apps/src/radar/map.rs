@@ -1,21 +1,124 @@
-use ratatui::layout::Rect;
+use std::time::Instant;
+
+use adsb_deku::{DownlinkRequest, ICAO};
+use crossterm::event::{MouseButton, MouseEvent, MouseEventKind};
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
 use ratatui::style::{Color, Style};
 use ratatui::text::Span;
-use ratatui::widgets::canvas::{Canvas, Line, Points};
-use ratatui::widgets::Block;
-use rsadsb_common::{AirplaneDetails, Airplanes};
+use ratatui::widgets::canvas::{Canvas, Context, Line, Points};
+use ratatui::widgets::{Block, Clear, Paragraph};
+use rsadsb_common::altitude::{AltitudeDisplay, AltitudeSource};
+use rsadsb_common::ground::GroundState;
+use rsadsb_common::qnh;
+use rsadsb_common::{AirplaneDetails, AirplaneState, Airplanes, RejectReason};
 
+use crate::airplanes::{build_tab_airplanes, trend_arrow, visible_keys};
+use crate::cli::{HeadingStyle, ScreenLayout};
+use crate::declutter::{declutter, Label};
+use crate::keys::Action;
+use crate::measure::{clicked_aircraft, clicked_lat_long, draw_measure, Measure};
+use crate::tab::{drag_map, handle_altitude_key, handle_map_key, Tab, TabContext};
 use crate::{draw_lines, draw_locations, Settings, DEFAULT_PRECISION, MAX_PLOT_HIGH, MAX_PLOT_LOW};
 
-/// Render Map tab for tui display
+/// Map tab, with the Airplanes table on the right with `--layout split`
+pub struct MapTab;
+
+impl Tab for MapTab {
+    fn title(&self, _: &Airplanes) -> String {
+        "Map".to_string()
+    }
+
+    fn render(&self, f: &mut ratatui::Frame, chunks: &[Rect], ctx: &TabContext<'_>) -> Rect {
+        let TabContext { settings, adsb_airplanes, .. } = ctx;
+        let selected = settings
+            .airplanes_state
+            .selected()
+            .and_then(|selected| visible_keys(adsb_airplanes, settings).nth(selected));
+        if settings.opts.layout == ScreenLayout::Split {
+            let split_chunks = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Percentage(55), Constraint::Percentage(45)])
+                .split(chunks[1]);
+            let map_chunks = [chunks[0], split_chunks[0]];
+            let airplanes_chunks = [chunks[0], split_chunks[1]];
+            build_tab_map(f, &map_chunks, settings, adsb_airplanes, selected, ctx.stats.qnh());
+            build_tab_airplanes(f, &airplanes_chunks, adsb_airplanes, settings);
+            split_chunks[0]
+        } else {
+            build_tab_map(f, chunks, settings, adsb_airplanes, selected, ctx.stats.qnh());
+            chunks[1]
+        }
+    }
+
+    fn handle_key(&mut self, action: Action, settings: &mut Settings, _: &Airplanes) {
+        if action == Action::Measure {
+            settings.measure =
+                if settings.measure.is_some() { None } else { Some(Measure::default()) };
+        }
+        handle_altitude_key(action, settings);
+        handle_map_key(action, settings);
+    }
+
+    fn handle_mouse(
+        &mut self,
+        mouse: MouseEvent,
+        settings: &mut Settings,
+        adsb_airplanes: &Airplanes,
+        area: Rect,
+    ) {
+        // the map doesn't move while measuring
+        if settings.measure.is_none() {
+            match mouse.kind {
+                MouseEventKind::Down(MouseButton::Left) => {
+                    let aircraft =
+                        clicked_aircraft(settings, adsb_airplanes, area, mouse.column, mouse.row);
+                    if let Some((key, _)) = aircraft {
+                        select_aircraft(settings, adsb_airplanes, key);
+                    }
+                }
+                MouseEventKind::Down(MouseButton::Right) => settings.airplanes_state.select(None),
+                _ => drag_map(mouse, settings, area),
+            }
+            return;
+        }
+        if mouse.kind == MouseEventKind::Down(MouseButton::Left) {
+            let lat_long =
+                clicked_lat_long(settings, adsb_airplanes, area, mouse.column, mouse.row);
+            if let (Some(measure), Some(lat_long)) = (&mut settings.measure, lat_long) {
+                measure.click(lat_long);
+            }
+        }
+    }
+
+    fn touchscreen(&self) -> bool {
+        true
+    }
+}
+
+/// Select `key` in the Airplanes tab, also selecting it on the Map
+fn select_aircraft(settings: &mut Settings, adsb_airplanes: &Airplanes, key: ICAO) {
+    let index = visible_keys(adsb_airplanes, settings).position(|visible| visible == key);
+    if index.is_some() {
+        settings.airplanes_state.select(index);
+    }
+}
+
+/// Render Map tab for tui display, with the label of the `selected` aircraft of the Airplanes tab
+/// placed first and its information in the top right corner, and the `qnh` of `--qnh-correction`
 pub fn build_tab_map(
     f: &mut ratatui::Frame,
     chunks: &[Rect],
     settings: &Settings,
     adsb_airplanes: &Airplanes,
+    selected: Option<ICAO>,
+    qnh: Option<f32>,
 ) {
+    let title = settings.measure.as_ref().map_or_else(
+        || "Map".to_string(),
+        |measure| format!("Map - {}", measure.title((settings.lat, settings.long))),
+    );
     let canvas = Canvas::default()
-        .block(Block::bordered().title("Map"))
+        .block(Block::bordered().title(title))
         .x_bounds([MAX_PLOT_LOW, MAX_PLOT_HIGH])
         .y_bounds([MAX_PLOT_LOW, MAX_PLOT_HIGH])
         .paint(|ctx| {
@@ -24,14 +127,55 @@ pub fn build_tab_map(
             // draw locations
             draw_locations(ctx, settings);
 
-            // draw ADSB tab airplanes
+            // draw measurement
+            if let Some(measure) = &settings.measure {
+                draw_measure(ctx, settings, measure);
+            }
+
+            // draw the positions rejected by the range checks, also of airplanes without a position
+            if settings.opts.show_rejected_fixes {
+                draw_rejected_fixes(ctx, settings, adsb_airplanes);
+            }
+
+            // draw ADSB tab airplanes, and collect their labels
+            let dim_age = settings.opts.dim_age as f64;
+            let stale_position = settings.opts.stale_position as f64;
+            let track_filter = settings.opts.track_filter();
+            let now = Instant::now();
+            let mut labels = vec![];
             for (key, value) in adsb_airplanes.iter() {
+                let surface_vehicle = value.category.is_some_and(|c| c.is_surface_vehicle());
+                let obstruction = value.category.is_some_and(|c| c.is_obstruction());
+                if settings.opts.disable_surface_vehicles && (surface_vehicle || obstruction) {
+                    continue;
+                }
+
                 let aircraft_details = adsb_airplanes.aircraft_details(*key);
-                if let Some(AirplaneDetails { position, heading, track, .. }) = aircraft_details {
+                if let Some(details) = aircraft_details {
+                    let AirplaneDetails { position, track, .. } = &details;
+                    if !settings.altitude_visible(Some(details.altitude)) {
+                        continue;
+                    }
+
                     let (x, y) = settings.to_xy(position.latitude, position.longitude);
 
-                    // draw previous positions ("track")
-                    if !settings.opts.disable_track {
+                    // draw previous positions ("track"), simplified and smoothed as lines up to
+                    // the aircraft with --track-tolerance and --track-smoothing
+                    if !settings.opts.disable_track && !track_filter.is_disabled() {
+                        if let Some(track) = track {
+                            let positions: Vec<_> = track
+                                .iter()
+                                .filter_map(|coor| coor.position)
+                                .chain([*position])
+                                .collect();
+                            let filtered = track_filter.apply(&positions);
+                            for pair in filtered.windows(2) {
+                                let (x1, y1) = settings.to_xy(pair[0].latitude, pair[0].longitude);
+                                let (x2, y2) = settings.to_xy(pair[1].latitude, pair[1].longitude);
+                                ctx.draw(&Line { x1, y1, x2, y2, color: Color::White });
+                            }
+                        }
+                    } else if !settings.opts.disable_track {
                         if let Some(track) = track {
                             for coor in track {
                                 if let Some(position) = coor.position {
@@ -45,10 +189,21 @@ pub fn build_tab_map(
                         }
                     }
 
+                    // fade the aircraft without recent messages
+                    let marker_color =
+                        if details.seen > dim_age { Color::DarkGray } else { Color::Blue };
+
+                    // turned since the last heading with the current turn rate
+                    let heading = if settings.opts.disable_heading {
+                        None
+                    } else {
+                        value.estimated_heading(now)
+                    };
+
                     // make wings for the angle directions facing toward the heading. This tried to
                     // account for the angles not showing up around the 90 degree mark, of which I
                     // add degrees of the angle before displaying
-                    if !settings.opts.disable_heading {
+                    if settings.opts.heading_style == HeadingStyle::Wings {
                         if let Some(heading) = heading {
                             const ANGLE: f32 = 20.0;
                             const LENGTH: f32 = 8.0;
@@ -78,7 +233,7 @@ pub fn build_tab_map(
                                 x2: x_2,
                                 y1: y_1,
                                 y2: y_2,
-                                color: Color::Blue,
+                                color: marker_color,
                             });
 
                             // repeat for the other side (addition, so just modding)
@@ -93,20 +248,27 @@ pub fn build_tab_map(
                                 x2: x_2,
                                 y1: y_1,
                                 y2: y_2,
-                                color: Color::Blue,
+                                color: marker_color,
                             });
                         }
                     }
 
+                    let arrow = trend_arrow(value, settings.opts.vert_rate_threshold);
                     let call_sign = if settings.opts.disable_callsign {
-                        format!("{key}").into_boxed_str()
+                        format!("{key}{arrow}").into_boxed_str()
                     } else if let Some(callsign) = &value.callsign {
-                        callsign.to_string().into_boxed_str()
+                        format!("{callsign}{arrow}").into_boxed_str()
                     } else {
-                        format!("{key}").into_boxed_str()
+                        format!("{key}{arrow}").into_boxed_str()
                     };
 
-                    let name = if settings.opts.disable_lat_long {
+                    let name = if let Some(map_label) = &settings.opts.map_label {
+                        let altitude = label_altitude(value, settings, qnh);
+                        let transition_altitude = settings.opts.transition_altitude;
+                        map_label
+                            .render(*key, value, &details, arrow, altitude, transition_altitude)
+                            .into_boxed_str()
+                    } else if settings.opts.disable_lat_long {
                         format!("{call_sign}").into_boxed_str()
                     } else {
                         format!(
@@ -117,18 +279,276 @@ pub fn build_tab_map(
                     };
 
                     if !settings.opts.disable_icao {
-                        // draw plane ICAO name
-                        ctx.print(
+                        labels.push(Label {
                             x,
-                            y + 20.0,
-                            Span::styled(name.to_string(), Style::default().fg(Color::White)),
-                        );
+                            y,
+                            text: name.to_string(),
+                            selected: selected == Some(*key),
+                            altitude: details.altitude,
+                        });
                     }
 
-                    // draw dot on actual lat/lon
-                    ctx.draw(&Points { coords: &[(x, y)], color: Color::Blue });
+                    // draw marker on actual lat/lon, ground vehicles and obstructions are kept
+                    // apart from (taxiing) aircraft, and parked aircraft don't point anywhere
+                    if surface_vehicle {
+                        ctx.print(x, y, Span::styled("■", Style::default().fg(Color::Yellow)));
+                    } else if obstruction {
+                        ctx.print(x, y, Span::styled("▲", Style::default().fg(Color::Red)));
+                    } else if details.seen_pos.is_some_and(|seen| seen > stale_position) {
+                        ctx.print(x, y, Span::styled("○", Style::default().fg(marker_color)));
+                    } else if details.ground_state == Some(GroundState::Parked) {
+                        ctx.print(x, y, Span::styled("•", Style::default().fg(marker_color)));
+                    } else if let (HeadingStyle::Arrow, Some(heading)) =
+                        (settings.opts.heading_style, heading)
+                    {
+                        let arrow = heading_arrow(heading);
+                        ctx.print(x, y, Span::styled(arrow, Style::default().fg(marker_color)));
+                    } else {
+                        ctx.draw(&Points { coords: &[(x, y)], color: marker_color });
+                    }
                 }
             }
+
+            // draw plane ICAO names
+            let labels = if settings.opts.disable_declutter {
+                labels.into_iter().map(|Label { x, y, text, .. }| (x, y + 20.0, text)).collect()
+            } else {
+                declutter(labels, chunks[1])
+            };
+            for (x, y, text) in labels {
+                ctx.print(x, y, Span::styled(text, Style::default().fg(Color::White)));
+            }
         });
     f.render_widget(canvas, chunks[1]);
+
+    // the closest, highest and fastest aircraft, then the information of the selected aircraft
+    // below them in the top right corner
+    let inner = Block::bordered().inner(chunks[1]);
+    let lines = records(adsb_airplanes, settings.opts.transition_altitude);
+    let y = if lines.is_empty() {
+        inner.y
+    } else {
+        draw_corner_box(f, inner, inner.y, "Records".to_string(), &lines).unwrap_or(inner.y)
+    };
+
+    let selected = selected.and_then(|key| {
+        let state = adsb_airplanes.get(key)?;
+        Some((key, state, adsb_airplanes.aircraft_details(key)?))
+    });
+    if let Some((key, state, details)) = selected {
+        let lines = selected_info(key, state, &details, settings);
+        let title = state.callsign.clone().unwrap_or_else(|| key.to_string());
+        draw_corner_box(f, inner, y, title, &lines);
+    }
+}
+
+/// Draw `lines` in a box on the right of `inner` from `y`, if it fits
+///
+/// Returns the row below the box
+/// Draw the last rejected positions of each aircraft, red when out of range and yellow after a
+/// jump from the previous position
+fn draw_rejected_fixes(ctx: &mut Context<'_>, settings: &Settings, adsb_airplanes: &Airplanes) {
+    for (_, state) in adsb_airplanes.iter() {
+        for fix in &state.rejected_fixes.recent {
+            let color = match fix.reason {
+                RejectReason::OutOfRange => Color::Red,
+                _ => Color::Yellow,
+            };
+            let (x, y) = settings.to_xy(fix.position.latitude, fix.position.longitude);
+            ctx.print(x, y, Span::styled("x", Style::default().fg(color)));
+        }
+    }
+}
+
+fn draw_corner_box(
+    f: &mut ratatui::Frame,
+    inner: Rect,
+    y: u16,
+    title: String,
+    lines: &[String],
+) -> Option<u16> {
+    let width = lines.iter().chain([&title]).map(|line| line.chars().count()).max();
+    let width = width.unwrap_or(0) as u16 + 4;
+    let height = lines.len() as u16 + 2;
+    if inner.width < width || inner.bottom() < y + height {
+        return None;
+    }
+    let area = Rect::new(inner.right() - width, y, width, height);
+    let paragraph = Paragraph::new(lines.join("\n")).block(Block::bordered().title(title));
+    f.render_widget(Clear, area);
+    f.render_widget(paragraph, area);
+    Some(area.bottom())
+}
+
+/// Altitude of the `{alt}` of `--map-label`, from `--label-altitude`
+///
+/// With `--qnh-correction`, the barometric altitudes below `--transition-altitude` are corrected
+/// to the true altitude with the `qnh`.
+fn label_altitude(state: &AirplaneState, settings: &Settings, qnh: Option<f32>) -> Option<i32> {
+    let source = settings.opts.label_altitude;
+    let altitude = state.altitude(source)?;
+    match qnh {
+        Some(qnh)
+            if settings.opts.qnh_correction
+                && source == AltitudeSource::Baro
+                && altitude < settings.opts.transition_altitude =>
+        {
+            Some(qnh::true_altitude(altitude, qnh))
+        }
+        _ => Some(altitude),
+    }
+}
+
+/// Closest airborne aircraft with its distance, altitude and bearing, and the highest and fastest
+/// aircraft, shown on the Map with the flight levels above `transition_altitude` (ft)
+fn records(adsb_airplanes: &Airplanes, transition_altitude: i32) -> Vec<String> {
+    let name = |icao: ICAO, state: &AirplaneState| {
+        state.callsign.clone().unwrap_or_else(|| icao.to_string())
+    };
+    let mut lines = vec![];
+    if let Some((icao, state)) = adsb_airplanes.closest() {
+        let coords = &state.coords;
+        let altitude = coords
+            .altitude()
+            .map(|altitude| format!(" {}", AltitudeDisplay::new(altitude, transition_altitude)));
+        lines.push(format!(
+            "Closest  {} {:.1} km {:.0}°{}",
+            name(icao, state),
+            coords.kilo_distance.unwrap_or_default(),
+            coords.bearing.unwrap_or_default(),
+            altitude.unwrap_or_default()
+        ));
+    }
+    if let Some((icao, state)) = adsb_airplanes.highest() {
+        let altitude =
+            AltitudeDisplay::new(state.coords.altitude().unwrap_or_default(), transition_altitude);
+        lines.push(format!("Highest  {} {altitude}", name(icao, state)));
+    }
+    if let Some((icao, state)) = adsb_airplanes.fastest() {
+        let speed = state.speed.unwrap_or_default();
+        lines.push(format!("Fastest  {} {speed:.0} kt", name(icao, state)));
+    }
+    lines
+}
+
+/// Arrow of the marker with `--heading-style arrow`, `heading` rounded to the nearest 45°
+fn heading_arrow(heading: f32) -> &'static str {
+    const ARROWS: [&str; 8] = ["↑", "↗", "→", "↘", "↓", "↙", "←", "↖"];
+    ARROWS[(heading.rem_euclid(360.0) / 45.0).round() as usize % ARROWS.len()]
+}
+
+/// Information of the selected aircraft `key` shown on the Map
+fn selected_info(
+    key: ICAO,
+    state: &AirplaneState,
+    details: &AirplaneDetails,
+    settings: &Settings,
+) -> Vec<String> {
+    let arrow = trend_arrow(state, settings.opts.vert_rate_threshold);
+    let altitude = AltitudeDisplay::new(details.altitude, settings.opts.transition_altitude);
+    let mut lines = vec![format!("ICAO  {key}"), format!("Alt   {altitude}{arrow}")];
+    if let Some(speed) = state.speed {
+        lines.push(format!("GS    {speed:.0} kt"));
+    }
+    if let Some(heading) = state.heading {
+        lines.push(format!("Trk   {heading:.1}°"));
+    }
+    if let Some(vert_speed) = state.vert_speed {
+        lines.push(format!("VS    {vert_speed:+} ft/min"));
+    }
+    if let Some(squawk) = state.squawk {
+        lines.push(format!("Sqk   {squawk:04x}"));
+    }
+    if let Some(ri) = state.reply_information {
+        lines.push(format!("RI    {ri}"));
+    }
+    if let Some(downlink) = state.downlink {
+        if downlink.request != DownlinkRequest::None {
+            lines.push(format!("DR    {}", downlink.request));
+        }
+        if let Some(iis) = downlink.interrogator {
+            lines.push(format!("IIS   {iis}"));
+        }
+    }
+    lines.push(format!(
+        "Dist  {:.DEFAULT_PRECISION$} km {:.0}°",
+        details.kilo_distance, details.bearing_deg
+    ));
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+    use rsadsb_common::sbs::SbsMessage;
+    use time::UtcOffset;
+
+    use super::*;
+    use crate::cli::Opts;
+
+    #[test]
+    fn test_heading_arrow() {
+        assert_eq!(heading_arrow(0.0), "↑");
+        assert_eq!(heading_arrow(22.4), "↑");
+        assert_eq!(heading_arrow(22.6), "↗");
+        assert_eq!(heading_arrow(270.0), "←");
+        assert_eq!(heading_arrow(350.0), "↑");
+        assert_eq!(heading_arrow(-90.0), "←");
+    }
+
+    #[test]
+    fn test_records() {
+        let mut airplanes = Airplanes::builder().position((35.0, -80.0)).build();
+        assert!(records(&airplanes, 18000).is_empty());
+        for line in [
+            "MSG,3,1,1,A1B2C3,1,,,,,,37000,,,35.1,-80.0,,,,,,",
+            "MSG,4,1,1,A1B2C3,1,,,,,,,450,270,,,0,,,,,",
+        ] {
+            airplanes.apply_sbs(&SbsMessage::parse_line(line).unwrap().unwrap());
+        }
+        assert_eq!(
+            records(&airplanes, 18000),
+            ["Closest  a1b2c3 11.1 km 0° FL370", "Highest  a1b2c3 FL370", "Fastest  a1b2c3 450 kt"]
+        );
+        assert_eq!(records(&airplanes, 40000)[1], "Highest  a1b2c3 37000 ft");
+    }
+
+    #[test]
+    fn test_selected_info() {
+        let mut airplanes = Airplanes::builder().position((52.0, 4.0)).build();
+        // even and odd airborne position, then DF5 squawking 7700
+        for frame in
+            ["8d40621d58c382d690c8ac2863a7", "8d40621d58c386435cc412692ad6", "28000aaa0ac6d4"]
+        {
+            let bytes = hex::decode(frame).unwrap();
+            airplanes.action(adsb_deku::Frame::from_bytes(&bytes).unwrap());
+        }
+        let icao = ICAO([0x40, 0x62, 0x1d]);
+        let state = airplanes.get(icao).unwrap();
+        let details = airplanes.aircraft_details(icao).unwrap();
+
+        let opts = Opts::try_parse_from(["radar", "--lat=52.0", "--long=4.0"]).unwrap();
+        let settings = Settings::new(opts, UtcOffset::UTC);
+        let lines = selected_info(icao, state, &details, &settings);
+        assert_eq!(lines[..2], ["ICAO  40621d", "Alt   FL380"]);
+        assert!(lines.contains(&"Sqk   7700".to_string()), "{lines:?}");
+    }
+
+    #[test]
+    fn test_label_altitude() {
+        let mut airplanes = Airplanes::new();
+        let line = "MSG,5,1,1,A1B2C3,1,,,,,,3000,,,,,,,,,,";
+        airplanes.apply_sbs(&SbsMessage::parse_line(line).unwrap().unwrap());
+        let state = airplanes.get(ICAO([0xa1, 0xb2, 0xc3])).unwrap();
+
+        let opts = Opts::try_parse_from(["radar", "--lat=35.0", "--long=-80.0"]).unwrap();
+        let mut settings = Settings::new(opts, UtcOffset::UTC);
+        assert_eq!(label_altitude(state, &settings, Some(1023.25)), Some(3000));
+        settings.opts.qnh_correction = true;
+        assert_eq!(label_altitude(state, &settings, Some(1023.25)), Some(3272));
+        assert_eq!(label_altitude(state, &settings, None), Some(3000));
+        // above the transition altitude
+        settings.opts.transition_altitude = 3000;
+        assert_eq!(label_altitude(state, &settings, Some(1023.25)), Some(3000));
+    }
 }
@@ -0,0 +1,193 @@
+//! Measure mode of the Map tab, and the clicks on the Map
+//!
+//! Clicking a point (or an aircraft) shows the distance and bearing from the receiver, clicking
+//! a second point shows the distance and bearing between the two points. Outside of measure mode,
+//! clicking an aircraft selects it, see [`clicked_aircraft`].
+
+use adsb_deku::ICAO;
+use ratatui::layout::Rect;
+use ratatui::style::{Color, Style};
+use ratatui::text::Span;
+use ratatui::widgets::canvas::{Context, Line};
+use ratatui::widgets::Block;
+use rsadsb_common::{geo, AirplaneDetails, Airplanes};
+
+use crate::{Settings, DEFAULT_PRECISION, MAX_PLOT_HIGH, MAX_PLOT_LOW};
+
+/// Distance on the Map canvas from an aircraft for a click to select the aircraft
+const SNAP_DISTANCE: f64 = 10.0;
+
+/// Points clicked in measure mode, (lat, long)
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Measure {
+    points: Vec<(f64, f64)>,
+}
+
+impl Measure {
+    /// Add a clicked point, starting a new measurement after two points
+    pub fn click(&mut self, lat_long: (f64, f64)) {
+        if self.points.len() == 2 {
+            self.points.clear();
+        }
+        self.points.push(lat_long);
+    }
+
+    /// Start and end (lat, long) of the measurement, from `receiver` until a second point is
+    /// clicked
+    pub fn line(&self, receiver: (f64, f64)) -> Option<((f64, f64), (f64, f64))> {
+        match self.points[..] {
+            [to] => Some((receiver, to)),
+            [from, to] => Some((from, to)),
+            _ => None,
+        }
+    }
+
+    /// Distance (km) and bearing (degrees) of the measurement
+    pub fn result(&self, receiver: (f64, f64)) -> Option<(f64, f64)> {
+        self.line(receiver).map(|(from, to)| (geo::distance(from, to), geo::bearing(from, to)))
+    }
+
+    /// Title of the Map block
+    pub fn title(&self, receiver: (f64, f64)) -> String {
+        match self.result(receiver) {
+            Some((distance, bearing)) => {
+                format!("Measure: {distance:.DEFAULT_PRECISION$} km, {bearing:.1}°")
+            }
+            None => "Measure: click a point or aircraft".to_string(),
+        }
+    }
+}
+
+/// (lat, long) of a mouse click at `column` and `row` on the Map drawn in `area`
+///
+/// Clicks close to an aircraft return the position of the aircraft.
+pub fn clicked_lat_long(
+    settings: &Settings,
+    adsb_airplanes: &Airplanes,
+    area: Rect,
+    column: u16,
+    row: u16,
+) -> Option<(f64, f64)> {
+    let (x, y) = clicked_xy(area, column, row)?;
+    let aircraft = nearest_aircraft(settings, adsb_airplanes, (x, y));
+    Some(aircraft.map_or_else(|| settings.to_lat_long(x, y), |(_, lat_long)| lat_long))
+}
+
+/// Aircraft and its (lat, long) closest to a mouse click at `column` and `row` on the Map drawn
+/// in `area`, `None` if no aircraft is close to the click
+pub fn clicked_aircraft(
+    settings: &Settings,
+    adsb_airplanes: &Airplanes,
+    area: Rect,
+    column: u16,
+    row: u16,
+) -> Option<(ICAO, (f64, f64))> {
+    let xy = clicked_xy(area, column, row)?;
+    nearest_aircraft(settings, adsb_airplanes, xy)
+}
+
+/// Middle of the cell at `column` and `row` on the Map drawn in `area`, in canvas coordinates
+fn clicked_xy(area: Rect, column: u16, row: u16) -> Option<(f64, f64)> {
+    let inner = Block::bordered().inner(area);
+    if !(inner.left()..inner.right()).contains(&column)
+        || !(inner.top()..inner.bottom()).contains(&row)
+    {
+        return None;
+    }
+
+    let size = MAX_PLOT_HIGH - MAX_PLOT_LOW;
+    let x = MAX_PLOT_LOW + (f64::from(column - inner.x) + 0.5) / f64::from(inner.width) * size;
+    let y = MAX_PLOT_HIGH - (f64::from(row - inner.y) + 0.5) / f64::from(inner.height) * size;
+    Some((x, y))
+}
+
+/// Aircraft shown on the Map closest to `(x, y)`, within [`SNAP_DISTANCE`]
+fn nearest_aircraft(
+    settings: &Settings,
+    adsb_airplanes: &Airplanes,
+    (x, y): (f64, f64),
+) -> Option<(ICAO, (f64, f64))> {
+    adsb_airplanes
+        .keys()
+        .filter_map(|key| adsb_airplanes.aircraft_details(*key).map(|details| (*key, details)))
+        .filter(|(_, details)| settings.altitude_visible(Some(details.altitude)))
+        .map(|(key, AirplaneDetails { position, .. })| {
+            let lat_long = (position.latitude, position.longitude);
+            let (a_x, a_y) = settings.to_xy(lat_long.0, lat_long.1);
+            (key, lat_long, (a_x - x).hypot(a_y - y))
+        })
+        .filter(|(_, _, distance)| *distance <= SNAP_DISTANCE)
+        .min_by(|(_, _, a), (_, _, b)| a.total_cmp(b))
+        .map(|(key, lat_long, _)| (key, lat_long))
+}
+
+/// Draw the measurement line and clicked points on the Map
+pub fn draw_measure(ctx: &mut Context<'_>, settings: &Settings, measure: &Measure) {
+    let Some((from, to)) = measure.line((settings.lat, settings.long)) else {
+        return;
+    };
+    let (x1, y1) = settings.to_xy(from.0, from.1);
+    let (x2, y2) = settings.to_xy(to.0, to.1);
+    ctx.draw(&Line { x1, y1, x2, y2, color: Color::Yellow });
+    for (x, y) in [(x1, y1), (x2, y2)] {
+        ctx.print(x, y, Span::styled("+", Style::default().fg(Color::Yellow)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+    use time::UtcOffset;
+
+    use super::*;
+    use crate::cli::Opts;
+
+    #[test]
+    fn measure() {
+        let receiver = (35.0, -80.0);
+        let mut measure = Measure::default();
+        assert_eq!(measure.result(receiver), None);
+
+        measure.click((36.0, -80.0));
+        let (distance, bearing) = measure.result(receiver).unwrap();
+        assert!((distance - 111.195).abs() < 1e-3);
+        assert!(bearing.abs() < 1e-9);
+
+        measure.click((36.0, -79.0));
+        assert_eq!(measure.line(receiver), Some(((36.0, -80.0), (36.0, -79.0))));
+
+        // start over
+        measure.click((34.0, -80.0));
+        let (_, bearing) = measure.result(receiver).unwrap();
+        assert!((bearing - 180.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn clicked() {
+        let opts = Opts::try_parse_from(["radar", "--lat=35.0", "--long=-80.0"]).unwrap();
+        let settings = Settings::new(opts, UtcOffset::UTC);
+        let airplanes = Airplanes::default();
+        let area = Rect::new(0, 0, 102, 52);
+
+        let (x, y) = settings.to_xy(36.0, -79.0);
+        let (lat, long) = settings.to_lat_long(x, y);
+        assert!((lat - 36.0).abs() < 1e-9);
+        assert!((long + 79.0).abs() < 1e-9);
+
+        // receiver in the middle of the map
+        let (lat, long) = clicked_lat_long(&settings, &airplanes, area, 51, 26).unwrap();
+        assert!((lat - 35.0).abs() < 0.1);
+        assert!((long + 80.0).abs() < 0.1);
+
+        // up and right
+        let (lat, long) = clicked_lat_long(&settings, &airplanes, area, 90, 5).unwrap();
+        assert!(lat > 35.0);
+        assert!(long > -80.0);
+
+        // border
+        assert_eq!(clicked_lat_long(&settings, &airplanes, area, 0, 26), None);
+
+        // no aircraft to select
+        assert_eq!(clicked_aircraft(&settings, &airplanes, area, 51, 26), None);
+    }
+}
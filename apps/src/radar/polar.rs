@@ -0,0 +1,116 @@
+use adsb_deku::ICAO;
+use ratatui::layout::Rect;
+use ratatui::style::Color;
+use ratatui::text::Span;
+use ratatui::widgets::canvas::{Canvas, Circle, Points};
+use ratatui::widgets::Block;
+use rsadsb_common::{geo, Airplanes};
+
+use crate::coverage::coverage_floor_label;
+
+/// Width of each bearing bin (degrees)
+const BEARING_STEP: f64 = 10.0;
+
+/// Amount of bearing bins, covering 0 to 360 degrees
+const BEARING_BINS: usize = 36;
+
+/// Height of each elevation bin (degrees)
+const ELEVATION_STEP: f64 = 0.5;
+
+/// Lowest elevation shown, lower elevations are counted in the first bin
+const ELEVATION_MIN: f64 = -1.0;
+
+/// Amount of elevation bins, higher elevations are counted in the last bin
+const ELEVATION_BINS: usize = 32;
+
+/// Message counts by bearing and elevation from the receiver, for antenna alignment
+#[derive(Debug)]
+pub struct Polar {
+    counts: [[u32; ELEVATION_BINS]; BEARING_BINS],
+}
+
+impl Default for Polar {
+    fn default() -> Self {
+        Self { counts: [[0; ELEVATION_BINS]; BEARING_BINS] }
+    }
+}
+
+impl Polar {
+    /// Count a message from `icao`, if the aircraft currently has a position
+    pub fn update(&mut self, airplanes: &Airplanes, icao: ICAO) {
+        // not `Airplanes::aircraft_details`, which clones the track, for every frame received
+        let Some(coords) = airplanes.get(icao).map(|state| &state.coords) else {
+            return;
+        };
+        if let (Some(_), Some(altitude), Some(kilo_distance)) =
+            (coords.position, coords.altitude(), coords.kilo_distance)
+        {
+            let elevation_deg = geo::elevation(kilo_distance, f64::from(altitude));
+            let bearing =
+                (coords.bearing.unwrap_or_default() / BEARING_STEP) as usize % BEARING_BINS;
+            let elevation = ((elevation_deg - ELEVATION_MIN) / ELEVATION_STEP)
+                .clamp(0.0, (ELEVATION_BINS - 1) as f64) as usize;
+            self.counts[bearing][elevation] += 1;
+        }
+    }
+
+    /// Radius within the polar plot of an elevation, the lowest elevation is the outer ring
+    fn radius(elevation: f64) -> f64 {
+        let range = ELEVATION_STEP * ELEVATION_BINS as f64;
+        1.0 - (elevation - ELEVATION_MIN) / range
+    }
+}
+
+/// Render Coverage tab polar view for tui display
+pub fn build_tab_polar(
+    f: &mut ratatui::Frame,
+    chunks: &[Rect],
+    polar: &Polar,
+    min_altitude: Option<i32>,
+) {
+    let max = polar.counts.iter().flatten().copied().max().unwrap_or(0).max(1);
+
+    let mut title = "Coverage - Polar (messages by bearing/elevation)".to_string();
+    if let Some(floor) = coverage_floor_label(min_altitude) {
+        title.push_str(&format!(" {floor}"));
+    }
+    let canvas = Canvas::default()
+        .block(Block::bordered().title(title))
+        .x_bounds([-1.1, 1.1])
+        .y_bounds([-1.1, 1.1])
+        .paint(|ctx| {
+            // elevation rings
+            for elevation in [0.0, 5.0, 10.0] {
+                let radius = Polar::radius(elevation);
+                ctx.draw(&Circle { x: 0.0, y: 0.0, radius, color: Color::DarkGray });
+                ctx.print(0.0, radius, Span::styled(format!("{elevation}°"), Color::DarkGray));
+            }
+            ctx.print(0.0, 1.05, "N");
+            ctx.print(1.05, 0.0, "E");
+            ctx.print(0.0, -1.05, "S");
+            ctx.print(-1.05, 0.0, "W");
+
+            ctx.layer();
+
+            // message counts
+            for (bearing, elevations) in polar.counts.iter().enumerate() {
+                let angle = (bearing as f64 + 0.5) * BEARING_STEP;
+                for (elevation, count) in elevations.iter().enumerate() {
+                    if *count == 0 {
+                        continue;
+                    }
+                    let elevation = ELEVATION_MIN + (elevation as f64 + 0.5) * ELEVATION_STEP;
+                    let radius = Polar::radius(elevation);
+                    let x = radius * angle.to_radians().sin();
+                    let y = radius * angle.to_radians().cos();
+
+                    let color_number = (100 + 155 * u64::from(*count) / u64::from(max)) as u8;
+                    ctx.draw(&Points {
+                        coords: &[(x, y)],
+                        color: Color::Rgb(color_number, color_number, color_number),
+                    });
+                }
+            }
+        });
+    f.render_widget(canvas, chunks[1]);
+}
@@ -0,0 +1,128 @@
+//! Projections of latitude and longitude onto the Map and Coverage tabs, from `--projection`
+
+use clap::ValueEnum;
+use rsadsb_common::geo;
+
+/// Kilometers in a degree of latitude
+const KM_PER_DEGREE: f64 = 111.195;
+
+/// Smallest cosine of the latitude of the receiver for the scale of [`Azimuthal`], near the poles
+const MIN_SCALE_COS: f64 = 0.1;
+
+/// Map projection from `--projection`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum ProjectionKind {
+    /// Web Mercator, which stretches ranges at high latitudes
+    #[default]
+    Mercator,
+    /// Azimuthal equidistant centered on the receiver, with the true range and bearing
+    Azimuthal,
+}
+
+/// Conversion between (latitude, longitude) and (x, y) on the canvas, with y to the north
+pub trait Projection {
+    fn project(&self, lat_long: (f64, f64)) -> (f64, f64);
+    fn unproject(&self, xy: (f64, f64)) -> (f64, f64);
+}
+
+/// Mercator of `scale` canvas units around the equator
+#[derive(Debug, Clone, Copy)]
+pub struct Mercator {
+    pub scale: f64,
+}
+
+impl Projection for Mercator {
+    fn project(&self, (lat, long): (f64, f64)) -> (f64, f64) {
+        let x = long * (self.scale / 360.0);
+        let lat_rad = lat.to_radians();
+        let merc_n = f64::ln(f64::tan((std::f64::consts::PI / 4.0) + (lat_rad / 2.0)));
+        let y = self.scale * merc_n / (2.0 * std::f64::consts::PI);
+        (x, y)
+    }
+
+    fn unproject(&self, (x, y): (f64, f64)) -> (f64, f64) {
+        let long = x * (360.0 / self.scale);
+        let merc_n = y * (2.0 * std::f64::consts::PI) / self.scale;
+        let lat = merc_n.sinh().atan().to_degrees();
+        (lat, long)
+    }
+}
+
+/// Azimuthal equidistant centered on the receiver, where the distance and bearing from the center
+/// are the great-circle distance and bearing
+#[derive(Debug, Clone, Copy)]
+pub struct Azimuthal {
+    /// (latitude, longitude)
+    center: (f64, f64),
+    /// canvas units per km
+    scale: f64,
+}
+
+impl Azimuthal {
+    /// Projection around `center`, with the same zoom as a [`Mercator`] of `scale` at the center
+    pub fn new(center: (f64, f64), scale: f64) -> Self {
+        let cos = center.0.to_radians().cos().max(MIN_SCALE_COS);
+        Self { center, scale: scale / (360.0 * KM_PER_DEGREE * cos) }
+    }
+}
+
+impl Projection for Azimuthal {
+    fn project(&self, lat_long: (f64, f64)) -> (f64, f64) {
+        let kilo_distance = geo::distance(self.center, lat_long) * self.scale;
+        let bearing = geo::bearing(self.center, lat_long).to_radians();
+        (kilo_distance * bearing.sin(), kilo_distance * bearing.cos())
+    }
+
+    fn unproject(&self, (x, y): (f64, f64)) -> (f64, f64) {
+        let kilo_distance = x.hypot(y) / self.scale;
+        let bearing = x.atan2(y).to_degrees();
+        geo::destination(self.center, bearing, kilo_distance)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TROMSO: (f64, f64) = (69.65, 18.96);
+
+    fn round_trip(projection: &dyn Projection) {
+        for lat_long in [TROMSO, (70.5, 25.0), (66.0, 14.5), (0.0, 0.0)] {
+            let (lat, long) = projection.unproject(projection.project(lat_long));
+            assert!((lat - lat_long.0).abs() < 1e-9 && (long - lat_long.1).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn inverse() {
+        round_trip(&Mercator { scale: 60_000.0 });
+        round_trip(&Azimuthal::new(TROMSO, 60_000.0));
+    }
+
+    #[test]
+    fn true_range() {
+        let mercator = Mercator { scale: 60_000.0 };
+        let azimuthal = Azimuthal::new(TROMSO, 60_000.0);
+        let length = |projection: &dyn Projection, bearing| {
+            let (x0, y0) = projection.project(TROMSO);
+            let (x, y) = projection.project(geo::destination(TROMSO, bearing, 300.0));
+            (x - x0).hypot(y - y0)
+        };
+
+        // same range in all directions
+        let north = length(&azimuthal, 0.0);
+        for bearing in [90.0, 180.0, 270.0] {
+            assert!((length(&azimuthal, bearing) - north).abs() < 1e-6);
+        }
+        // mercator stretches further north
+        assert!(length(&mercator, 0.0) > length(&mercator, 180.0) * 1.1);
+
+        // same zoom at the receiver
+        let near = |projection: &dyn Projection| {
+            let (_, y0) = projection.project(TROMSO);
+            let (_, y) = projection.project((TROMSO.0 + 0.01, TROMSO.1));
+            y - y0
+        };
+        assert!((near(&azimuthal) / near(&mercator) - 1.0).abs() < 1e-3);
+    }
+}
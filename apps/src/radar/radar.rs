@@ -3,30 +3,71 @@
 //! and testing your coverage.
 
 mod airport;
-use crate::airport::Airport;
+use crate::airport::{Airport, Runway};
 
 mod cli;
 use crate::cli::Opts;
 
+mod config;
+use crate::config::Config;
+
 mod coverage;
-use crate::coverage::{build_tab_coverage, populate_coverage};
+use crate::coverage::{above_coverage_floor, export_coverage, populate_coverage, Coverage};
+
+mod label;
+
+#[cfg(unix)]
+mod deltas;
+#[cfg(unix)]
+use crate::deltas::DeltaSocket;
+
+mod ledger;
+use crate::ledger::Ledger;
 
 mod map;
-use crate::map::build_tab_map;
+
+mod measure;
+use crate::measure::Measure;
 
 mod stats;
-use crate::stats::{build_tab_stats, Stats};
+use crate::stats::Stats;
+
+mod declutter;
 
 mod help;
-use crate::help::build_tab_help;
+
+mod keys;
+use crate::keys::Action;
+
+mod polar;
+use crate::polar::Polar;
+
+mod projection;
+use crate::projection::{Azimuthal, Mercator, Projection, ProjectionKind};
+
+mod render;
+use crate::render::Renderer;
+
+mod replay;
+use crate::replay::Replay;
+
+mod sources;
+use crate::sources::SourceHealth;
+
+mod tab;
+use crate::tab::{clicked_tab, TabBar, TabContext, MAP_TAB};
 
 mod airplanes;
-use std::io::{self, BufRead, BufReader, BufWriter};
-use std::net::{SocketAddr, TcpStream};
+use std::borrow::Cow;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, IsTerminal};
+use std::net::TcpStream;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
-use std::time::Duration;
+use std::time::{Duration, Instant, SystemTime};
 
-use adsb_deku::{Frame, ICAO};
+use adsb_deku::avr::AvrFrame;
+use adsb_deku::{Frame, DF};
 use anyhow::{Context, Result};
 use clap::Parser;
 use crossterm::event::{
@@ -44,16 +85,22 @@ use ratatui::text::Span;
 use ratatui::widgets::canvas::{Line, Points};
 use ratatui::widgets::{Block, Paragraph, TableState, Tabs};
 use ratatui::Terminal;
-use rsadsb_common::{AirplaneDetails, Airplanes};
+use rsadsb_apps::ingest::{Ingest, IngestOptions, InputFormat, Message};
+use rsadsb_apps::input::{parse_avr, BeastReader, LineReader, Source, SourceReader};
+use rsadsb_apps::logging::{self, LogSink};
+use rsadsb_apps::service::Service;
+use rsadsb_common::coverage::{RangeCoverage, ALTITUDE_BANDS};
+use rsadsb_common::dedup::Dedup;
+use rsadsb_common::governor::Governor;
+use rsadsb_common::locate::ReceiverLocator;
+use rsadsb_common::privacy::PrivacyFilter;
+use rsadsb_common::{timestamp, Airplanes};
 use time::UtcOffset;
-use tracing::{debug, error, info, trace};
-use tracing_subscriber::EnvFilter;
-
-use crate::airplanes::build_tab_airplanes;
+use tracing::{debug, error, info, trace, Level};
 
 /// Amount of zoom out from your original lat/long position
 const MAX_PLOT_HIGH: f64 = 400.0;
-const MAX_PLOT_LOW: f64 = MAX_PLOT_HIGH * -1.0;
+const MAX_PLOT_LOW: f64 = -MAX_PLOT_HIGH;
 
 mod scale {
     /// Diff between scale changes
@@ -63,54 +110,56 @@ mod scale {
     pub const DEFAULT: f64 = 500_000.0;
 }
 
+mod altitude {
+    /// Diff between altitude floor/ceiling changes (ft)
+    pub const CHANGE: i32 = 1000;
+
+    /// Altitude ceiling (ft) used when the ceiling is first lowered
+    pub const MAX: i32 = 45_000;
+}
+
 /// tui top bar margin
 const TUI_START_MARGIN: u16 = 1;
 
+/// Time between each estimate of the receiver location with `--auto-position`
+const AUTO_POSITION_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Interval of `--emit-deltas` without new frames
+#[cfg(unix)]
+const DELTAS_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Redraw interval without any change, for the ages (such as Seen) and clocks of the display
+const IDLE_REDRAW: Duration = Duration::from_secs(1);
+
 /// width of tui top bar
 const TUI_BAR_WIDTH: u16 = 3;
 
 /// default precision of latitude, longitude, and distance
 pub const DEFAULT_PRECISION: usize = 3;
 
-/// Available top row Tabs
-#[derive(Copy, Clone)]
-enum Tab {
-    Map,
-    Coverage,
-    Airplanes,
-    Stats,
-    Help,
-}
-
-impl Tab {
-    pub fn next_tab(self) -> Self {
-        match self {
-            Self::Map => Self::Coverage,
-            Self::Coverage => Self::Airplanes,
-            Self::Airplanes => Self::Stats,
-            Self::Stats => Self::Help,
-            Self::Help => Self::Map,
-        }
-    }
-}
-
 /// Enum representing any reason that the main event loop was exited
 enum QuitReason {
-    /// Tcp Disconnect from the dump1090 server. In the case of --retry-tcp, try to reconnect.
+    /// Disconnect from the dump1090 server, or end of the standard input. In the case of
+    /// --retry-tcp, try to reconnect to the server.
     TcpDisconnect,
     /// User used a tui method to exit the app, we do what the user wants
     UserRequested,
+    /// SIGTERM, such as stopping the systemd service
+    Terminated,
 }
 
 impl std::fmt::Display for QuitReason {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Self::TcpDisconnect => {
-                writeln!(f, "TCP connection aborted, quitting radar tui")?;
+                writeln!(f, "connection aborted, quitting radar tui")?;
             }
             Self::UserRequested => {
                 writeln!(f, "user requested quit")?;
             }
+            Self::Terminated => {
+                writeln!(f, "terminated")?;
+            }
         }
 
         Ok(())
@@ -123,8 +172,12 @@ pub struct Settings {
     opts: Opts,
     /// when Some(), imply quitting with msg
     quit: Option<QuitReason>,
-    /// mutable current map selection
-    tab_selection: Tab,
+    /// index of the selected tab in the `TabBar`
+    tab_selection: usize,
+    /// selected aircraft of the Airplanes tab, also highlighted on the Map
+    airplanes_state: TableState,
+    /// index in [`airplanes::SORTS`] of the order of the Airplanes tab
+    airplanes_sort: usize,
     /// current scale from operator
     scale: f64,
     /// current lat from operator
@@ -139,15 +192,33 @@ pub struct Settings {
     last_mouse_dragging: Option<(u16, u16)>,
     /// Parsed list of airport locations
     airports: Option<Vec<Airport>>,
+    /// Parsed list of runways
+    runways: Vec<Runway>,
     /// DateTime offset
     utc_offset: UtcOffset,
+    /// Show polar view (bearing/elevation) instead of the map in the Coverage tab
+    coverage_polar: bool,
+    /// altitude band shown in the Coverage tab, all bands if None
+    coverage_band: Option<usize>,
+    /// when true, export the range coverage as an SVG in the main loop
+    export_coverage: bool,
+    /// path or error of the last export of the range coverage
+    coverage_export: Option<String>,
+    /// when true, reload `--config` in the main loop
+    reload: bool,
+    /// playback of `--replay`, instead of reading from the dump1090 instance
+    replay: Option<Replay<BufReader<File>>>,
+    /// when Some, clicking the Map measures distance and bearing instead of moving it
+    measure: Option<Measure>,
 }
 
 impl Settings {
     fn new(opts: Opts, utc_offset: UtcOffset) -> Self {
         Self {
             quit: None,
-            tab_selection: Tab::Map,
+            tab_selection: MAP_TAB,
+            airplanes_state: TableState::default(),
+            airplanes_sort: 0,
             scale: opts.scale,
             lat: opts.lat,
             long: opts.long,
@@ -156,36 +227,50 @@ impl Settings {
             opts,
             last_mouse_dragging: None,
             airports: None,
+            runways: vec![],
             utc_offset,
+            coverage_polar: false,
+            coverage_band: None,
+            export_coverage: false,
+            coverage_export: None,
+            reload: false,
+            replay: None,
+            measure: None,
         }
     }
 
-    /// Convert new lat/long into mercator using current location from `Settings`
+    /// Convert lat/long into the `--projection` coordinates of the Map and Coverage tabs, with
+    /// the current location from `Settings` at (0, 0)
     fn to_xy(&self, latitude: f64, longitude: f64) -> (f64, f64) {
-        // TODO save before, it's kinda costly
-        let (local_x, local_y) = self.local_lat_lon();
-        let (x, y) = self.to_mercator(latitude, longitude);
-        let (x, y) = (x - local_x, y - local_y);
-        (x, y * -1.0)
+        self.with_projection(|projection| {
+            let (local_x, local_y) = projection.project(self.local_lat_lon());
+            let (x, y) = projection.project((latitude, longitude));
+            (x - local_x, y - local_y)
+        })
     }
 
-    /// Calculate mercator for local lat/long
+    /// Convert `to_xy` coordinates back into lat/long
+    fn to_lat_long(&self, x: f64, y: f64) -> (f64, f64) {
+        self.with_projection(|projection| {
+            let (local_x, local_y) = projection.project(self.local_lat_lon());
+            projection.unproject((x + local_x, y + local_y))
+        })
+    }
+
+    /// Current location, moved by the operator
     fn local_lat_lon(&self) -> (f64, f64) {
         let lat = self.custom_lat.map_or(self.lat, |lat| lat);
         let long = self.custom_long.map_or(self.long, |long| long);
-        self.to_mercator(lat, long)
+        (lat, long)
     }
 
-    /// Convert lat/long to mercator coordinates
-    fn to_mercator(&self, lat: f64, long: f64) -> (f64, f64) {
+    /// Call `f` with the `--projection` at the current scale, centered on the receiver
+    fn with_projection<T>(&self, f: impl FnOnce(&dyn Projection) -> T) -> T {
         let scale: f64 = self.scale * scale::DEFAULT;
-
-        let x = (long + 180.0) * (scale / 360.0);
-        let lat_rad = lat.to_radians();
-        let merc_n = f64::ln(f64::tan((std::f64::consts::PI / 4.0) + (lat_rad / 2.0)));
-        let y = (scale / 2.0) - (scale * merc_n / (2.0 * std::f64::consts::PI));
-
-        (x, y)
+        match self.opts.projection {
+            ProjectionKind::Mercator => f(&Mercator { scale }),
+            ProjectionKind::Azimuthal => f(&Azimuthal::new((self.lat, self.long), scale)),
+        }
     }
 
     fn scale_increase(&mut self) {
@@ -228,17 +313,120 @@ impl Settings {
         }
     }
 
+    fn floor_increase(&mut self) {
+        self.opts.min_altitude = Some(self.opts.min_altitude.unwrap_or(0) + altitude::CHANGE);
+    }
+
+    fn floor_decrease(&mut self) {
+        self.opts.min_altitude =
+            self.opts.min_altitude.map(|floor| floor - altitude::CHANGE).filter(|floor| *floor > 0);
+    }
+
+    fn ceiling_increase(&mut self) {
+        self.opts.max_altitude = self
+            .opts
+            .max_altitude
+            .map(|ceiling| ceiling + altitude::CHANGE)
+            .filter(|ceiling| *ceiling < altitude::MAX);
+    }
+
+    fn ceiling_decrease(&mut self) {
+        let ceiling = self.opts.max_altitude.unwrap_or(altitude::MAX) - altitude::CHANGE;
+        self.opts.max_altitude = Some(ceiling.max(0));
+    }
+
+    /// Return true if `altitude` is within `--min-altitude` and `--max-altitude`
+    ///
+    /// Aircraft without a known altitude are always shown.
+    fn altitude_visible(&self, altitude: Option<i32>) -> bool {
+        altitude.map_or(true, |altitude| {
+            self.opts.min_altitude.map_or(true, |floor| altitude >= floor)
+                && self.opts.max_altitude.map_or(true, |ceiling| altitude <= ceiling)
+        })
+    }
+
+    /// Current altitude band for the tui title, if any
+    fn altitude_band(&self) -> Option<String> {
+        match (self.opts.min_altitude, self.opts.max_altitude) {
+            (None, None) => None,
+            (Some(floor), None) => Some(format!("(ALT >= {floor} ft)")),
+            (None, Some(ceiling)) => Some(format!("(ALT <= {ceiling} ft)")),
+            (Some(floor), Some(ceiling)) => Some(format!("(ALT {floor}-{ceiling} ft)")),
+        }
+    }
+
+    /// Show the next altitude band in the Coverage tab, then all bands
+    fn next_coverage_band(&mut self) {
+        self.coverage_band = match self.coverage_band {
+            None => Some(0),
+            Some(band) if band + 1 < ALTITUDE_BANDS.len() => Some(band + 1),
+            Some(_) => None,
+        };
+    }
+
     fn reset(&mut self) {
         self.custom_lat = None;
         self.custom_long = None;
         self.scale = self.opts.scale;
     }
+
+    /// Parse airports from `--airports`, and their runways from `--runways`
+    fn load_airports(&mut self) {
+        let mut airports = vec![];
+        if let Some(airport) = &self.opts.airports {
+            airports = Airport::from_file(airport, &self.opts.airports_tz_filter);
+        }
+        self.runways = match &self.opts.runways {
+            Some(runways) => Runway::from_file(runways, &airports),
+            None => vec![],
+        };
+        self.airports = Some(airports);
+    }
+
+    /// Re-read `--config` and override `cli_opts` with it
+    ///
+    /// On error, the current options are kept.
+    fn reload_config(&mut self, cli_opts: &Opts) {
+        let Some(path) = &cli_opts.config else {
+            return;
+        };
+        let config = match Config::from_file(path) {
+            Ok(config) => config,
+            Err(e) => {
+                error!("{e:?}");
+                return;
+            }
+        };
+        info!("reloading config: {path}");
+
+        let mut opts = cli_opts.clone();
+        config.apply(&mut opts);
+        let reload_airports = (&opts.airports, &opts.airports_tz_filter, &opts.runways)
+            != (&self.opts.airports, &self.opts.airports_tz_filter, &self.opts.runways);
+
+        // with --gpsd or --auto-position, the location is updated at runtime
+        if !opts.gpsd && !opts.auto_position {
+            self.lat = opts.lat;
+            self.long = opts.long;
+        }
+        if opts.scale != self.opts.scale {
+            self.scale = opts.scale;
+        }
+        self.opts = opts;
+
+        if reload_airports {
+            self.load_airports();
+        }
+    }
 }
 
 /// Information generated by tui during runtime that is needed for `MouseEvents`
 #[derive(Default, Debug, Clone)]
 struct TuiInfo {
-    bottom_chunks: Option<Vec<Rect>>,
+    /// titles of the top bar
+    tab_titles: Vec<String>,
+    /// area of the selected tab receiving the mouse events, see [`tab::Tab::render`]
+    tab_area: Option<Rect>,
     touchscreen_buttons: Option<Vec<Rect>>,
 }
 
@@ -246,29 +434,31 @@ fn main() -> Result<()> {
     // grab the local offset from localtime_r while we are a single thread for safety
     let utc_offset = time::OffsetDateTime::now_local().unwrap().offset();
 
-    // Parse arguments
-    let opts = Opts::parse();
-
-    // Generate logs file and start logging
-    let file_appender = tracing_appender::rolling::daily(&opts.log_folder, "radar.log");
-    let (non_blocking, _guard) = tracing_appender::non_blocking(file_appender);
-    let env = EnvFilter::from_default_env();
-    tracing_subscriber::fmt()
-        .with_env_filter(env)
-        .with_ansi(true)
-        .with_writer(non_blocking)
-        .with_line_number(true)
-        .with_file(true)
-        .init();
+    // Parse arguments, overridden by the --config file
+    let cli_opts = Opts::parse();
+    let mut opts = cli_opts.clone();
+    if let Some(config) = &cli_opts.config {
+        Config::from_file(config)?.apply(&mut opts);
+    }
+
+    // Generate logs file and start logging, the logs on the terminal would corrupt the display
+    if opts.log == LogSink::Stderr && io::stderr().is_terminal() {
+        anyhow::bail!("--log stderr writes into the display, redirect stderr such as 2>radar.log");
+    }
+    let _guard = logging::init(opts.log, opts.log_format, &opts.log_folder, "radar.log")?;
 
     // print current version
     let version = env!("CARGO_PKG_VERSION");
     info!("starting rsadsb/radar-v{} with options: {:?}", version, opts);
 
     // empty containers
-    let mut input = String::new();
-    let mut coverage_airplanes: Vec<(f64, f64, u32, ICAO)> = Vec::new();
-    let mut adsb_airplanes = Airplanes::new();
+    let mut coverage = Coverage::default();
+    // with --auto-position, the location is unknown until estimated
+    let mut adsb_airplanes = if opts.auto_position {
+        Airplanes::new()
+    } else {
+        Airplanes::builder().position((opts.lat, opts.long)).max_range(opts.max_range).build()
+    };
 
     // setup tui params
     let mut stdout = io::stdout();
@@ -279,25 +469,35 @@ fn main() -> Result<()> {
     enable_raw_mode().unwrap();
 
     // setup tui variables
-    let mut airplanes_state = TableState::default();
-    let filter_time = opts.filter_time;
+    let mut tabs = TabBar::new();
 
     // create settings, dropping opts to prevent bad usage of variable
     let mut settings = Settings::new(opts.clone(), utc_offset);
 
-    // Setup non-blocking TcpStream, display a tui display saying as such and setup the quit
-    // if the user wants to quit
-    let socket = SocketAddr::from((opts.host, opts.port));
-    let mut tcp_reader = match init_tcp_reader(&mut terminal, &mut settings, socket)? {
-        Some(tcp_reader) => tcp_reader,
-        None => return Ok(()),
-    };
-
-    let mut airports = vec![];
-    if let Some(airport) = &settings.opts.airports {
-        airports = Airport::from_file(airport, &settings.opts.airports_tz_filter);
+    // Setup the TcpStream, Unix socket or stdin with a read timeout, display a tui display saying
+    // as such and setup the quit if the user wants to quit. With --replay, read from the recorded
+    // file instead.
+    let source = opts.source();
+    let mut service = Service::new()?;
+    let mut ingest = None;
+    if let Some(replay) = &opts.replay {
+        let file = File::open(replay).with_context(|| format!("unable to open: {replay}"))?;
+        settings.replay = Some(Replay::new(BufReader::new(file)));
+    } else {
+        match init_reader(&mut terminal, &mut settings, &mut service, &source)? {
+            Some(reader) => ingest = Some(spawn_ingest(reader, &settings.opts)),
+            None => return Ok(()),
+        }
     }
-    settings.airports = Some(airports);
+
+    service.ready();
+
+    settings.load_airports();
+
+    // reload --config on SIGHUP
+    let sighup = Arc::new(AtomicBool::new(false));
+    #[cfg(unix)]
+    signal_hook::flag::register(signal_hook::consts::SIGHUP, Arc::clone(&sighup))?;
 
     // This next group of functions and variables handle if `gpsd_ip` is set from the command
     // line.
@@ -318,20 +518,74 @@ fn main() -> Result<()> {
     }
 
     let mut stats = Stats::default();
+    if let Some(path) = &settings.opts.stats_ledger {
+        let now = timestamp::local(SystemTime::now(), utc_offset);
+        stats.ledger = Some(Ledger::open(path, now)?);
+    }
+    // health of the input, shown as a Source in the Stats tab
+    if ingest.is_some() {
+        stats.sources.push(SourceHealth::new(source.to_string(), Instant::now()));
+    }
+    #[cfg(unix)]
+    let mut deltas = settings
+        .opts
+        .emit_deltas
+        .as_ref()
+        .map(|path| DeltaSocket::bind(path, settings.opts.deltas_altitude))
+        .transpose()?;
+    #[cfg(not(unix))]
+    if settings.opts.emit_deltas.is_some() {
+        anyhow::bail!("--emit-deltas is only supported on unix");
+    }
+    let mut dedup = settings.opts.dedup_window.map(|ms| Dedup::new(Duration::from_millis(ms)));
+    let mut governor = settings.opts.max_rate.map(Governor::new);
+    let mut polar = Polar::default();
+    let mut range_coverage = RangeCoverage::new();
+    let mut frames = vec![];
+    let mut locator = settings.opts.auto_position.then(ReceiverLocator::new);
+    let mut last_located = Instant::now();
+    #[cfg(unix)]
+    let mut last_deltas = Instant::now();
+
+    // redraw when frames or input were received, at most --fps times per second
+    let mut changed = true;
+    let mut last_draw: Option<Instant> = None;
+    let mut tui_info = TuiInfo::default();
+    let privacy = settings.opts.privacy.filter();
+    let mut renderer = settings
+        .opts
+        .render
+        .as_ref()
+        .map(|path| {
+            let interval = Duration::from_secs(settings.opts.render_interval);
+            Renderer::new(path, settings.opts.render_size, interval)
+        })
+        .transpose()?;
 
     // Startup main loop
     info!("tui setup");
     loop {
+        // quit on SIGTERM, saving the ledger
+        service.keep_alive();
+        if service.terminated() {
+            settings.quit = Some(QuitReason::Terminated);
+        }
+
         // check if we need to bail this main event loop
         match settings.quit {
             Some(QuitReason::TcpDisconnect) => {
-                // if --retry-tcp has been used, try to generate a new tcp connection
-                if settings.opts.retry_tcp {
-                    tcp_reader = match init_tcp_reader(&mut terminal, &mut settings, socket)? {
+                // if --retry-tcp has been used, try to generate a new connection. The end of
+                // stdin is final.
+                if settings.opts.retry_tcp && source != Source::Stdin {
+                    ingest = match init_reader(&mut terminal, &mut settings, &mut service, &source)?
+                    {
                         // a new connection to a dump1090 instance has been found/set. use it
                         Some(tcp_reader) => {
                             settings.quit = None;
-                            tcp_reader
+                            if let Some(health) = stats.sources.first_mut() {
+                                health.reconnected();
+                            }
+                            Some(spawn_ingest(tcp_reader, &settings.opts))
                         }
                         // the settings.quit has been set within init_tcp_reader. This continues
                         // to the next loop, which checks for the settings.quit being set
@@ -342,110 +596,251 @@ fn main() -> Result<()> {
                     break;
                 }
             }
-            Some(QuitReason::UserRequested) => break,
+            Some(QuitReason::UserRequested | QuitReason::Terminated) => break,
             None => (),
         }
 
+        // export the range coverage from the `e` key
+        if std::mem::take(&mut settings.export_coverage) {
+            changed = true;
+            settings.coverage_export = Some(match export_coverage(&range_coverage, &settings) {
+                Ok(path) => {
+                    info!("exported coverage: {path}");
+                    format!("exported {path}")
+                }
+                Err(e) => {
+                    error!("{e:?}");
+                    format!("export failed: {e}")
+                }
+            });
+        }
+
+        // reload --config from the `r` key or SIGHUP, keeping airplanes and coverage
+        if std::mem::take(&mut settings.reload) | sighup.swap(false, Ordering::Relaxed) {
+            changed = true;
+            let dedup_window = settings.opts.dedup_window;
+            let max_rate = settings.opts.max_rate;
+            settings.reload_config(&cli_opts);
+            if settings.opts.dedup_window != dedup_window {
+                dedup = settings.opts.dedup_window.map(|ms| Dedup::new(Duration::from_millis(ms)));
+            }
+            if settings.opts.max_rate != max_rate {
+                governor = settings.opts.max_rate.map(Governor::new);
+            }
+            if adsb_airplanes.receiver_position().is_some() {
+                adsb_airplanes.set_receiver_position((settings.lat, settings.long));
+                adsb_airplanes.set_max_range(Some(settings.opts.max_range));
+            }
+            if let Some(ingest) = &ingest {
+                ingest.set_filter(settings.opts.accept_df, settings.opts.quiet_decode);
+            }
+        }
+
         // check the Mutex from the gpsd thread, update lat/long
-        if let Ok(lat_long) = gps_lat_long.lock() {
-            if let Some((lat, long)) = *lat_long {
+        if let Ok(mut lat_long) = gps_lat_long.lock() {
+            if let Some((lat, long)) = lat_long.take() {
                 settings.lat = lat;
                 settings.long = long;
+                adsb_airplanes.set_receiver_position((lat, long));
             }
         }
 
-        if let Ok(len) = tcp_reader.read_line(&mut input) {
-            // a length of 0 would indicate a broken pipe/input, quit program
-            if len == 0 {
-                settings.quit = Some(QuitReason::TcpDisconnect);
-                continue;
+        // take all frames received since the last draw
+        let quiet = settings.opts.quiet_decode;
+        match (&ingest, &mut settings.replay) {
+            (Some(ingest), _) => {
+                // a closed reader would indicate a broken pipe/input, quit program
+                if let Err(e) = ingest.drain(&mut frames) {
+                    if e.kind() != io::ErrorKind::UnexpectedEof {
+                        error!("{e}");
+                    }
+                    settings.quit = Some(QuitReason::TcpDisconnect);
+                }
             }
+            // in replay, play the lines that are already due before drawing
+            (None, Some(replay)) => loop {
+                let now = Instant::now();
+                match replay.next_line(now) {
+                    Ok(Some(line)) => {
+                        if !quiet {
+                            debug!("avr: {}", line.trim_end());
+                        }
+                        match parse_avr(&line, &settings.opts.accept_df) {
+                            Ok(Some(avr)) => frames.push(Message::Avr(avr)),
+                            Ok(None) => (),
+                            Err(e) => {
+                                if !quiet {
+                                    debug!("invalid avr: {e}");
+                                }
+                            }
+                        }
+                    }
+                    Ok(None) => (),
+                    Err(e) => error!("{e}"),
+                }
+                if replay.should_draw(now) {
+                    break;
+                }
+            },
+            (None, None) => (),
+        }
 
-            // convert from string hex -> bytes
-            let hex = &mut input.to_string()[1..len - 2].to_string();
-            debug!("bytes: {hex}");
-            let bytes = if let Ok(bytes) = hex::decode(hex) {
-                bytes
-            } else {
-                continue;
+        let received = !frames.is_empty();
+        changed |= received;
+        for message in frames.drain(..) {
+            let (icao, outcome) = match message {
+                Message::Avr(mut avr) => {
+                    if settings.opts.soft_repair > 0 {
+                        avr.soft_repair(usize::from(settings.opts.soft_repair));
+                    }
+                    if let Some(meta) = &avr.meta {
+                        stats.update_demod(meta);
+                    }
+                    let signal_level = avr.meta.as_ref().and_then(|meta| meta.signal_level);
+                    let Some(frame) =
+                        filter_frame(&avr, &settings, &mut dedup, &mut governor, &mut stats)
+                    else {
+                        continue;
+                    };
+                    let icao = match &frame.df {
+                        DF::ADSB(adsb) => Some(adsb.icao),
+                        DF::TisB { cf, .. } => Some(cf.aa),
+                        _ => None,
+                    };
+                    let outcome = adsb_airplanes.action(frame);
+                    // from the Beast messages, once the aircraft is tracked
+                    if let (Some(icao), Some(signal_level)) = (icao, signal_level) {
+                        adsb_airplanes.add_signal_level(icao, signal_level);
+                    }
+                    (icao, outcome)
+                }
+                Message::Sbs(message) => (Some(message.icao), adsb_airplanes.apply_sbs(&message)),
             };
 
-            // check for all 0's
-            if bytes.iter().all(|&b| b == 0) {
-                continue;
+            // update stats
+            stats.update(&adsb_airplanes, &outcome);
+            if let Some(ledger) = &mut stats.ledger {
+                let now = timestamp::local(SystemTime::now(), settings.utc_offset);
+                let kilo_distance = icao
+                    .and_then(|icao| adsb_airplanes.get(icao))
+                    .and_then(|state| state.coords.kilo_distance);
+                ledger.update(now, icao, kilo_distance);
+            }
+            if let Some(icao) = icao {
+                let altitude = adsb_airplanes.get(icao).and_then(|state| state.coords.altitude());
+                if above_coverage_floor(altitude, settings.opts.coverage_min_altitude) {
+                    polar.update(&adsb_airplanes, icao);
+                    range_coverage.update(&adsb_airplanes, icao);
+                }
+                if let Some(locator) = &mut locator {
+                    locator.update(&adsb_airplanes, icao);
+                }
             }
+        }
 
-            // decode
-            // first check if the option is selected that limits the parsing by first checking the
-            // first 5 bits if they are the known adsb header DF field
-            let df_adsb = if settings.opts.limit_parsing {
-                ((bytes[0] & 0b1111_1000) >> 3) == 17
-            } else {
-                true
-            };
-            if df_adsb {
-                // parse the entire DF frame
-                let frame = Frame::from_bytes(&bytes);
-                match frame {
-                    Ok(frame) => {
-                        debug!("ADS-B Frame: {frame}");
-                        let airplane_added = adsb_airplanes.action(
-                            frame,
-                            (settings.lat, settings.long),
-                            settings.opts.max_range,
-                        );
-                        // update stats
-                        stats.update(&adsb_airplanes, airplane_added);
-                    }
-                    Err(e) => error!("{e:?}"),
+        populate_coverage(&adsb_airplanes, &mut coverage, settings.opts.coverage_min_altitude);
+        stats.refresh(&adsb_airplanes);
+        stats.update_qnh(&adsb_airplanes, settings.opts.transition_altitude);
+        if let Some(ingest) = &ingest {
+            let ingest_stats = ingest.stats();
+            stats.update_ingest(ingest_stats);
+            if let Some(health) = stats.sources.first_mut() {
+                health.update(ingest_stats, Instant::now());
+            }
+        }
+
+        // move the receiver to the location estimated from the received positions
+        if let Some(locator) = &locator {
+            if last_located.elapsed() >= AUTO_POSITION_INTERVAL {
+                last_located = Instant::now();
+                let estimate = locator.estimate();
+                if let Some(estimate) = estimate {
+                    info!("auto position: {estimate:?}");
+                    (settings.lat, settings.long) = estimate.position;
+                    adsb_airplanes.set_receiver_position(estimate.position);
+                    adsb_airplanes.set_max_range(Some(settings.opts.max_range));
                 }
+                stats.update_auto_position(locator, estimate);
             }
         }
-        input.clear();
 
-        populate_coverage(&adsb_airplanes, &mut coverage_airplanes);
+        if let Some(ledger) = &mut stats.ledger {
+            ledger.save_if_due();
+        }
 
-        // remove airplanes that timed-out
-        adsb_airplanes.prune(filter_time);
+        // remove airplanes that timed-out, keeping the Airplanes and Stats frozen while paused
+        if !settings.replay.as_ref().is_some_and(Replay::is_paused) {
+            adsb_airplanes.prune(settings.opts.filter_time);
+        }
 
-        // draw crossterm tui display
-        let tui_info = draw(
-            version,
-            &mut terminal,
-            &adsb_airplanes,
-            &settings,
-            &coverage_airplanes,
-            &mut airplanes_state,
-            &stats,
-        );
+        // stream the changes to the clients of --emit-deltas, after new frames or regularly for
+        // the pruned aircraft and new clients
+        #[cfg(unix)]
+        if let Some(deltas) = &mut deltas {
+            if received || last_deltas.elapsed() >= DELTAS_INTERVAL {
+                deltas.publish(&adsb_airplanes, privacy.as_ref());
+                last_deltas = Instant::now();
+            }
+        }
+
+        // draw crossterm tui display, if something changed and a frame is due, or to update the
+        // ages and clocks of the display when idle
+        let frame_interval = Duration::from_secs(1) / settings.opts.fps;
+        let since_draw = last_draw.map_or(Duration::MAX, |last_draw| last_draw.elapsed());
+        let ctx = TabContext {
+            settings: &settings,
+            adsb_airplanes: &adsb_airplanes,
+            coverage: &coverage,
+            stats: &stats,
+            polar: &polar,
+        };
+        if (changed && since_draw >= frame_interval) || since_draw >= IDLE_REDRAW {
+            tui_info = draw(version, &mut terminal, &tabs, &ctx);
+            last_draw = Some(Instant::now());
+            changed = false;
+        }
+
+        // draw the same tab off-screen for --render
+        if let Some(renderer) = &mut renderer {
+            let now = Instant::now();
+            if renderer.is_due(now) {
+                let airplanes = exported(privacy.as_ref(), &adsb_airplanes);
+                let ctx = TabContext { adsb_airplanes: &airplanes, ..ctx };
+                draw(version, &mut renderer.terminal, &tabs, &ctx);
+                if let Err(e) = renderer.save(now) {
+                    error!("render: {e:?}");
+                }
+            }
+        }
 
         // handle crossterm events
         //
-        // Loop until all MouseEvents are read, if you don't do this it takes forever to read
-        // all the moved mouse signals and repeated keyboard events
-        loop {
-            if poll(Duration::from_millis(10))? {
-                match read()? {
-                    // handle keyboard events
-                    Event::Key(key_event) => {
-                        trace!("{:?}", key_event);
-                        handle_keyevent(
-                            key_event,
-                            &mut settings,
-                            &adsb_airplanes,
-                            &mut airplanes_state,
-                        );
-                    }
-                    // handle mouse events
-                    Event::Mouse(mouse_event) => {
-                        trace!("{:?}", mouse_event);
-                        handle_mouseevent(mouse_event, &mut settings, &tui_info);
-                    }
-                    _ => (),
+        // Wait for events until the next frame is due, then loop until all MouseEvents are read,
+        // if you don't do this it takes forever to read all the moved mouse signals and repeated
+        // keyboard events
+        let mut timeout =
+            if changed { frame_interval.saturating_sub(since_draw) } else { frame_interval };
+        while poll(timeout)? {
+            timeout = Duration::ZERO;
+            changed = true;
+            match read()? {
+                // handle keyboard events
+                Event::Key(key_event) => {
+                    trace!("{:?}", key_event);
+                    handle_keyevent(key_event, &mut settings, &adsb_airplanes, &mut tabs);
+                }
+                // handle mouse events
+                Event::Mouse(mouse_event) => {
+                    trace!("{:?}", mouse_event);
+                    handle_mouseevent(
+                        mouse_event,
+                        &mut settings,
+                        &adsb_airplanes,
+                        &mut tabs,
+                        &tui_info,
+                    );
                 }
-            } else {
-                // don't seen anything, don't read anymore
-                break;
+                _ => (),
             }
         }
     }
@@ -454,6 +849,7 @@ fn main() -> Result<()> {
     //
     // PANIC: this won't panic, because main loop will continue until this is Some
     let reason = settings.quit.unwrap();
+    service.stopping();
     terminal.clear()?;
     let mut stdout = io::stdout();
     crossterm::execute!(
@@ -463,6 +859,10 @@ fn main() -> Result<()> {
     )?;
     crossterm::terminal::disable_raw_mode()?;
     terminal.show_cursor()?;
+    // saved after restoring the terminal, such that an error is readable
+    if let Some(ledger) = &mut stats.ledger {
+        ledger.save()?;
+    }
     println!("radar quitting: {reason}");
     info!("quitting: {}", reason);
     Ok(())
@@ -471,17 +871,15 @@ fn main() -> Result<()> {
 /// Try and connect to a dump1090 instance while showing a tui display.
 ///
 /// Returns:
-///   `Ok(Some(tcp_reader))`: Success, new tcp connection wrapped in a `BufReader`
-///   `Ok(None)`:             User quit method has been used
-///   `Err()`:                Some other system error has occurred
-fn init_tcp_reader(
-    terminal: &mut Terminal<CrosstermBackend<std::io::Stdout>>,
+///   `Ok(Some(reader))`: Success, new connection
+///   `Ok(None)`:         User quit method has been used, or SIGTERM
+///   `Err()`:            Some other system error has occurred
+fn init_reader<B: Backend>(
+    terminal: &mut Terminal<B>,
     settings: &mut Settings,
-    socket: SocketAddr,
-) -> Result<Option<BufReader<TcpStream>>> {
-    let ip = socket.ip();
-    let port = socket.port();
-
+    service: &mut Service,
+    source: &Source,
+) -> Result<Option<SourceReader>> {
     // display a tui display
     terminal.draw(|f| {
         let chunks = Layout::default()
@@ -490,127 +888,197 @@ fn init_tcp_reader(
             .constraints([Constraint::Min(3), Constraint::Percentage(100)].as_ref())
             .split(f.area());
 
-        let paragraph = Paragraph::new(format!("radar: Waiting for connection to {ip}:{port}"))
+        let paragraph = Paragraph::new(format!("radar: Waiting for connection to {source}"))
             .alignment(Alignment::Left);
 
         f.render_widget(paragraph, chunks[0]);
     })?;
 
     loop {
+        service.keep_alive();
+        if service.terminated() {
+            settings.quit = Some(QuitReason::Terminated);
+            return Ok(None);
+        }
+
         // handle keyboard events
         if poll(Duration::from_millis(10))? {
             if let Ok(Event::Key(key_event)) = read() {
-                let modifiers = key_event.modifiers;
-                let code = key_event.code;
-                match code {
-                    KeyCode::Char('q') => {
-                        settings.quit = Some(QuitReason::UserRequested);
-                        return Ok(None);
-                    }
-                    KeyCode::Char('c') => {
-                        if modifiers == crossterm::event::KeyModifiers::CONTROL {
-                            settings.quit = Some(QuitReason::UserRequested);
-                            return Ok(None);
-                        }
-                    }
-                    // unknown key
-                    _ => (),
+                let ctrl_c = key_event.code == KeyCode::Char('c')
+                    && key_event.modifiers == crossterm::event::KeyModifiers::CONTROL;
+                if ctrl_c || settings.opts.keys.action(key_event.code) == Some(Action::Quit) {
+                    settings.quit = Some(QuitReason::UserRequested);
+                    return Ok(None);
                 }
             }
         }
 
         // try and connect to initial dump1090 instance
-        if let Ok(stream) = TcpStream::connect_timeout(&socket, Duration::from_secs(10)) {
-            stream.set_read_timeout(Some(std::time::Duration::from_millis(50))).unwrap();
-            return Ok(Some(BufReader::new(stream)));
+        if let Ok(reader) = source.open(Duration::from_millis(50)) {
+            return Ok(Some(reader));
         }
     }
 }
 
-/// Handle a `KeyEvent`
+/// Aircraft sent out by `--render`, filtered by `--privacy-*`
+fn exported<'a>(privacy: Option<&PrivacyFilter>, airplanes: &'a Airplanes) -> Cow<'a, Airplanes> {
+    match privacy {
+        Some(privacy) => Cow::Owned(privacy.apply(airplanes)),
+        None => Cow::Borrowed(airplanes),
+    }
+}
+
+/// Read and parse the frames of `reader` in `--input-format` from a new thread, see [`Ingest`]
+fn spawn_ingest(reader: SourceReader, opts: &Opts) -> Ingest {
+    let options = IngestOptions {
+        format: opts.input_format,
+        accept_df: opts.accept_df,
+        capacity: opts.input_queue,
+        quiet: opts.quiet_decode,
+    };
+    match opts.input_format {
+        InputFormat::Avr => Ingest::spawn(LineReader::new(reader), options),
+        InputFormat::Sbs => Ingest::spawn(LineReader::unaligned(reader), options),
+        InputFormat::Beast => Ingest::spawn_beast(BeastReader::new(reader), options),
+    }
+}
+
+/// Decode `avr`, dropping frames filtered by `--accept-df`, `--dedup-window` and `--max-rate`
+fn filter_frame(
+    avr: &AvrFrame,
+    settings: &Settings,
+    dedup: &mut Option<Dedup>,
+    governor: &mut Option<Governor>,
+    stats: &mut Stats,
+) -> Option<Frame> {
+    let quiet = settings.opts.quiet_decode;
+    let bytes = avr.bytes();
+
+    // drop frames read before a reload of --accept-df
+    if !settings.opts.accept_df.accepts(bytes) {
+        return None;
+    }
+
+    // drop duplicate frames
+    if let Some(dedup) = dedup {
+        let duplicate = dedup.is_duplicate(bytes);
+        stats.update_dedup(dedup);
+        if duplicate {
+            if !quiet {
+                trace!("duplicate: {bytes:02x?}");
+            }
+            return None;
+        }
+    }
+
+    // drop frames over --max-rate, keeping position and velocity frames
+    if let Some(governor) = governor {
+        let allowed = governor.allow(bytes);
+        stats.update_governor(governor);
+        if !allowed {
+            if !quiet {
+                trace!("throttled: {bytes:02x?}");
+            }
+            return None;
+        }
+    }
+
+    // check for all 0's
+    if bytes.iter().all(|&b| b == 0) {
+        return None;
+    }
+
+    // decode, parsing the entire DF frame
+    match Frame::from_bytes(bytes) {
+        Ok(frame) => {
+            // the multi-line Display of the frame is only formatted for debugging
+            if !quiet && tracing::enabled!(Level::DEBUG) {
+                debug!("ADS-B Frame: {frame}");
+            }
+            Some(frame)
+        }
+        Err(e) => {
+            error!("{e:?}");
+            None
+        }
+    }
+}
+
+/// Handle a `KeyEvent` with the `--config` key bindings, actions that aren't handled by all tabs
+/// are handled by the selected tab
 fn handle_keyevent(
     key_event: KeyEvent,
     settings: &mut Settings,
     adsb_airplanes: &Airplanes,
-    airplanes_state: &mut TableState,
+    tabs: &mut TabBar,
 ) {
-    let modifiers = key_event.modifiers;
-    let code = key_event.code;
-    let current_selection = settings.tab_selection;
-    match (code, current_selection) {
-        // All Tabs
-        (KeyCode::F(1), _) => settings.tab_selection = Tab::Map,
-        (KeyCode::F(2), _) => settings.tab_selection = Tab::Coverage,
-        (KeyCode::F(3), _) => settings.tab_selection = Tab::Airplanes,
-        (KeyCode::F(4), _) => settings.tab_selection = Tab::Stats,
-        (KeyCode::F(5), _) => settings.tab_selection = Tab::Help,
-        (KeyCode::Tab, _) => settings.tab_selection = settings.tab_selection.next_tab(),
-        (KeyCode::Char('q'), _) => settings.quit = Some(QuitReason::UserRequested),
-        (KeyCode::Char('c'), _) => {
-            if modifiers == crossterm::event::KeyModifiers::CONTROL {
-                settings.quit = Some(QuitReason::UserRequested);
+    // not remapped
+    match key_event.code {
+        KeyCode::F(n) if (1..=tabs.len()).contains(&usize::from(n)) => {
+            settings.tab_selection = usize::from(n) - 1;
+            return;
+        }
+        KeyCode::Char('c') if key_event.modifiers == crossterm::event::KeyModifiers::CONTROL => {
+            settings.quit = Some(QuitReason::UserRequested);
+            return;
+        }
+        _ => (),
+    }
+
+    let Some(action) = settings.opts.keys.action(key_event.code) else {
+        return;
+    };
+    match action {
+        Action::NextTab => settings.tab_selection = (settings.tab_selection + 1) % tabs.len(),
+        Action::Quit => settings.quit = Some(QuitReason::UserRequested),
+        Action::ToggleLatLong => settings.opts.disable_lat_long ^= true,
+        Action::ToggleIcao => settings.opts.disable_icao ^= true,
+        Action::ToggleHeading => settings.opts.disable_heading ^= true,
+        Action::ToggleTrack => settings.opts.disable_track ^= true,
+        Action::ToggleCallsign => settings.opts.disable_callsign ^= true,
+        Action::ToggleDeclutter => settings.opts.disable_declutter ^= true,
+        Action::Reload => settings.reload = true,
+        // Replay
+        Action::Pause => {
+            if let Some(replay) = &mut settings.replay {
+                replay.toggle_pause();
             }
         }
-        (KeyCode::Char('l'), _) => settings.opts.disable_lat_long ^= true,
-        (KeyCode::Char('i'), _) => settings.opts.disable_icao ^= true,
-        (KeyCode::Char('h'), _) => settings.opts.disable_heading ^= true,
-        (KeyCode::Char('t'), _) => settings.opts.disable_track ^= true,
-        (KeyCode::Char('n'), _) => settings.opts.disable_callsign ^= true,
-        // Map and Coverage
-        (KeyCode::Char('-'), Tab::Map | Tab::Coverage) => settings.scale_increase(),
-        (KeyCode::Char('+'), Tab::Map | Tab::Coverage) => settings.scale_decrease(),
-        (KeyCode::Up, Tab::Map | Tab::Coverage) => settings.lat_increase(),
-        (KeyCode::Down, Tab::Map | Tab::Coverage) => settings.lat_decrease(),
-        (KeyCode::Left, Tab::Map | Tab::Coverage) => settings.long_increase(),
-        (KeyCode::Right, Tab::Map | Tab::Coverage) => settings.long_decrease(),
-        (KeyCode::Enter, Tab::Map | Tab::Coverage) => settings.reset(),
-        // Airplanes
-        (KeyCode::Up, Tab::Airplanes) => {
-            let index = airplanes_state
-                .selected()
-                .and_then(|selected| selected.checked_sub(1))
-                .unwrap_or(0);
-            airplanes_state.select(Some(index));
-        }
-        (KeyCode::Down, Tab::Airplanes) => {
-            let index = airplanes_state.selected().map_or(0, |selected| selected + 1);
-            airplanes_state.select(Some(index));
-        }
-        (KeyCode::Enter, Tab::Airplanes) => {
-            if let Some(selected) = airplanes_state.selected() {
-                let key = adsb_airplanes.keys().nth(selected).unwrap();
-                let aircraft_details = adsb_airplanes.aircraft_details(*key);
-                if let Some(AirplaneDetails { position, .. }) = aircraft_details {
-                    settings.custom_lat = Some(position.latitude);
-                    settings.custom_long = Some(position.longitude);
-                    settings.tab_selection = Tab::Map;
-                }
+        Action::Step => {
+            if let Some(replay) = &mut settings.replay {
+                replay.step();
             }
         }
-        _ => (),
+        Action::Faster => {
+            if let Some(replay) = &mut settings.replay {
+                replay.faster();
+            }
+        }
+        Action::Slower => {
+            if let Some(replay) = &mut settings.replay {
+                replay.slower();
+            }
+        }
+        action => tabs.get_mut(settings.tab_selection).handle_key(action, settings, adsb_airplanes),
     }
 }
 
-/// Handle a `MouseEvent`
-fn handle_mouseevent(mouse_event: MouseEvent, settings: &mut Settings, tui_info: &TuiInfo) {
+/// Handle a `MouseEvent`, events below the top bar are handled by the selected tab
+fn handle_mouseevent(
+    mouse_event: MouseEvent,
+    settings: &mut Settings,
+    adsb_airplanes: &Airplanes,
+    tabs: &mut TabBar,
+    tui_info: &TuiInfo,
+) {
     match mouse_event.kind {
         MouseEventKind::Down(MouseButton::Left) => {
             // Tabs
-            match (mouse_event.column, mouse_event.row) {
-                (3..=6, TUI_START_MARGIN..=TUI_BAR_WIDTH) => settings.tab_selection = Tab::Map,
-                (8..=16, TUI_START_MARGIN..=TUI_BAR_WIDTH) => {
-                    settings.tab_selection = Tab::Coverage;
-                }
-                (20..=34, TUI_START_MARGIN..=TUI_BAR_WIDTH) => {
-                    settings.tab_selection = Tab::Airplanes;
-                }
-                (36..=42, TUI_START_MARGIN..=TUI_BAR_WIDTH) => {
-                    settings.tab_selection = Tab::Stats;
+            if (TUI_START_MARGIN..=TUI_BAR_WIDTH).contains(&mouse_event.row) {
+                if let Some(index) = clicked_tab(&tui_info.tab_titles, mouse_event.column) {
+                    settings.tab_selection = index;
                 }
-                (43..=48, TUI_START_MARGIN..=TUI_BAR_WIDTH) => {
-                    settings.tab_selection = Tab::Help;
-                }
-                _ => (),
+                return;
             }
             // left touchscreen (if enabled)
             if let Some(btr) = &tui_info.touchscreen_buttons {
@@ -626,79 +1094,56 @@ fn handle_mouseevent(mouse_event: MouseEvent, settings: &mut Settings, tui_info:
                     && (scale_i_start..=scale_i_end).contains(&mouse_event.row)
                 {
                     settings.scale_increase();
+                    return;
                 // zoom in
                 } else if (1..=10_u16).contains(&mouse_event.column)
                     && (scale_o_start..=scale_o_end).contains(&mouse_event.row)
                 {
                     settings.scale_decrease();
+                    return;
                 // reset
                 } else if (1..=10_u16).contains(&mouse_event.column)
                     && (reset_start..=reset_end).contains(&mouse_event.row)
                 {
                     settings.reset();
-                }
-            }
-        }
-        MouseEventKind::Drag(MouseButton::Left) => {
-            // check tab
-            match settings.tab_selection {
-                Tab::Map | Tab::Coverage => (),
-                Tab::Airplanes | Tab::Stats | Tab::Help => return,
-            }
-
-            // check bounds below tab selection
-            if mouse_event.row < TUI_BAR_WIDTH {
-                return;
-            }
-
-            // check bounds if in map view, ignoring touchscreen controls
-            if let Some(bottom_chunks) = &tui_info.bottom_chunks {
-                let minimum_left_bound = bottom_chunks[1].x;
-                if mouse_event.column < minimum_left_bound {
                     return;
                 }
             }
-
-            // if we have a previous mouse drag without a mouse lift, change the current position
-            if let Some((column, row)) = &settings.last_mouse_dragging {
-                let up =
-                    f64::from(i32::from(mouse_event.row).wrapping_sub(i32::from(*row))) * 0.020;
-                if let Some(lat) = &mut settings.custom_lat {
-                    *lat += up;
-                } else {
-                    settings.custom_lat = Some(settings.lat + up);
-                }
-
-                let left =
-                    f64::from(i32::from(mouse_event.column).wrapping_sub(i32::from(*column)))
-                        * 0.020;
-                if let Some(long) = &mut settings.custom_long {
-                    *long -= left;
-                } else {
-                    settings.custom_long = Some(settings.long - left);
-                }
-            }
-            settings.last_mouse_dragging = Some((mouse_event.column, mouse_event.row));
         }
         MouseEventKind::Up(_) => {
             settings.last_mouse_dragging = None;
+            return;
+        }
+        MouseEventKind::ScrollDown => {
+            settings.scale_increase();
+            return;
+        }
+        MouseEventKind::ScrollUp => {
+            settings.scale_decrease();
+            return;
         }
-        MouseEventKind::ScrollDown => settings.scale_increase(),
-        MouseEventKind::ScrollUp => settings.scale_decrease(),
         _ => (),
     }
+
+    // check bounds below tab selection
+    if mouse_event.row < TUI_BAR_WIDTH {
+        return;
+    }
+    if let Some(area) = tui_info.tab_area {
+        let tab = tabs.get_mut(settings.tab_selection);
+        tab.handle_mouse(mouse_event, settings, adsb_airplanes, area);
+    }
 }
 
-fn draw(
+/// Draw the tab bar and the selected tab, in the terminal or off-screen for `--render`
+fn draw<B: Backend>(
     version: &str,
-    terminal: &mut Terminal<CrosstermBackend<std::io::Stdout>>,
-    adsb_airplanes: &Airplanes,
-    settings: &Settings,
-    coverage_airplanes: &[(f64, f64, u32, ICAO)],
-    airplanes_state: &mut TableState,
-    stats: &Stats,
+    terminal: &mut Terminal<B>,
+    tabs: &TabBar,
+    ctx: &TabContext<'_>,
 ) -> TuiInfo {
     let mut tui_info = TuiInfo::default();
+    let settings = ctx.settings;
 
     // tui drawing
     terminal
@@ -711,8 +1156,7 @@ fn draw(
                 .split(f.area());
 
             // render tabs
-            let airplane_len = format!("Airplanes({})", adsb_airplanes.len());
-            let titles = vec!["Map", "Coverage", &airplane_len, "Stats", "Help"];
+            tui_info.tab_titles = tabs.titles(ctx.adsb_airplanes);
 
             let mut view_type = "";
 
@@ -726,30 +1170,25 @@ fn draw(
                 long
             });
 
-            let tab = Tabs::new(titles)
+            let altitude_band = settings.altitude_band().unwrap_or_default();
+            let replay = settings.replay.as_ref().map(|replay| format!("- {replay}")).unwrap_or_default();
+
+            let tab = Tabs::new(tui_info.tab_titles.clone())
                 .block(
                     Block::bordered()
                         .title(format!(
-                            "rsadsb/radar(v{version}) - ({lat:.DEFAULT_PRECISION$},{long:.DEFAULT_PRECISION$}) {view_type}"
+                            "rsadsb/radar(v{version}) - ({lat:.DEFAULT_PRECISION$},{long:.DEFAULT_PRECISION$}) {view_type} {altitude_band} {replay}"
                         ))
                 )
                 .style(Style::default().fg(Color::White))
                 .highlight_style(Style::default().fg(Color::Green))
-                .select(settings.tab_selection as usize)
+                .select(settings.tab_selection)
                 .divider(DOT);
 
             f.render_widget(tab, chunks[0]);
 
             // render everything under tab
-            tui_info = draw_bottom_chunks(
-                f,
-                &chunks,
-                settings,
-                adsb_airplanes,
-                coverage_airplanes,
-                airplanes_state,
-                stats,
-            );
+            draw_bottom_chunks(f, &chunks, tabs, ctx, &mut tui_info);
         })
         .unwrap();
 
@@ -759,17 +1198,15 @@ fn draw(
 fn draw_bottom_chunks(
     f: &mut ratatui::Frame,
     chunks: &[Rect],
-    settings: &Settings,
-    adsb_airplanes: &Airplanes,
-    coverage_airplanes: &[(f64, f64, u32, ICAO)],
-    airplanes_state: &mut TableState,
-    stats: &Stats,
-) -> TuiInfo {
-    let mut tui_info = TuiInfo::default();
+    tabs: &TabBar,
+    ctx: &TabContext<'_>,
+    tui_info: &mut TuiInfo,
+) {
+    let tab = tabs.get(ctx.settings.tab_selection);
 
-    // touchscreen is enabled when operator enabled and Map or Coverage.
-    let touchscreen_enable =
-        settings.opts.touchscreen && matches!(settings.tab_selection, Tab::Map | Tab::Coverage);
+    // touchscreen is enabled when operator enabled and supported by the tab, such as Map or
+    // Coverage
+    let touchscreen_enable = ctx.settings.opts.touchscreen && tab.touchscreen();
 
     // if --touchscreen was used, create 10 percent of the screen on the left for the three
     // required buttoms to appear
@@ -779,8 +1216,6 @@ fn draw_bottom_chunks(
         .constraints([Constraint::Min(left_size), Constraint::Percentage(100)].as_ref())
         .split(chunks[1]);
 
-    tui_info.bottom_chunks = Some(bottom_chunks.to_vec());
-
     // Optionally create the tui widgets for the touchscreen
     tui_info.touchscreen_buttons = if touchscreen_enable {
         let touchscreen_chunks = Layout::default()
@@ -807,15 +1242,7 @@ fn draw_bottom_chunks(
     };
 
     // render the bottom cavas depending on the chosen tab
-    match settings.tab_selection {
-        Tab::Map => build_tab_map(f, &bottom_chunks, settings, adsb_airplanes),
-        Tab::Coverage => build_tab_coverage(f, &bottom_chunks, settings, coverage_airplanes),
-        Tab::Airplanes => build_tab_airplanes(f, &bottom_chunks, adsb_airplanes, airplanes_state),
-        Tab::Stats => build_tab_stats(f, &bottom_chunks, stats, settings),
-        Tab::Help => build_tab_help(f, &bottom_chunks),
-    }
-
-    tui_info
+    tui_info.tab_area = Some(tab.render(f, &bottom_chunks, ctx));
 }
 
 /// Draw vertical and horizontal lines
@@ -826,6 +1253,7 @@ fn draw_lines(ctx: &mut ratatui::widgets::canvas::Context<'_>) {
 
 /// Draw locations on the map
 pub fn draw_locations(ctx: &mut ratatui::widgets::canvas::Context<'_>, settings: &Settings) {
+    draw_runways(ctx, settings);
     for location in &settings.opts.locations {
         let (x, y) = settings.to_xy(location.lat, location.long);
 
@@ -848,6 +1276,28 @@ pub fn draw_locations(ctx: &mut ratatui::widgets::canvas::Context<'_>, settings:
     }
 }
 
+/// Draw the runways of `--runways` over their extended centerlines
+fn draw_runways(ctx: &mut ratatui::widgets::canvas::Context<'_>, settings: &Settings) {
+    let line = |(lat1, long1), (lat2, long2), color| {
+        let (x1, y1) = settings.to_xy(lat1, long1);
+        let (x2, y2) = settings.to_xy(lat2, long2);
+        Line { x1, y1, x2, y2, color }
+    };
+    let centerline_length = settings.opts.centerline_length;
+    if centerline_length > 0.0 {
+        for runway in &settings.runways {
+            for (threshold, end) in runway.centerlines(centerline_length).into_iter().flatten() {
+                ctx.draw(&line(threshold, end, Color::DarkGray));
+            }
+        }
+    }
+    for runway in &settings.runways {
+        if let Some((le, he)) = runway.ends() {
+            ctx.draw(&line(le, he, Color::White));
+        }
+    }
+}
+
 /// function ran within a thread for updating `gps_lat_long` when the gpsd shows a new `lat_long`
 /// position.
 fn gpsd_thread(gpsd_ip: &str, gps_lat_long: Arc<Mutex<Option<(f64, f64)>>>) {
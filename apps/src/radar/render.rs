@@ -0,0 +1,427 @@
+//! Off-screen rendering of `--render`, for e-paper and framebuffer displays
+//!
+//! The tabs are drawn with the same layout and projection code as the terminal, through the
+//! ratatui [`Backend`] trait, into an [`ImageBackend`] keeping the cells in a [`Buffer`] instead
+//! of writing them to a terminal. Each cell is then rasterized into [`CELL_WIDTH`] x
+//! [`CELL_HEIGHT`] pixels: a 5x7 font for ASCII, the dots of the Braille characters of the Map
+//! canvas, and the lines of the borders. The image is written as a binary PPM file, replaced
+//! atomically, such as converted for an e-paper display with `convert radar.ppm -monochrome`.
+
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, Context, Result};
+use ratatui::backend::{Backend, WindowSize};
+use ratatui::buffer::{Buffer, Cell};
+use ratatui::layout::{Position, Rect, Size};
+use ratatui::style::{Color, Modifier};
+use ratatui::Terminal;
+
+/// Pixels of a cell, the 5x7 glyphs with a column and a row of spacing
+pub const CELL_WIDTH: u16 = 6;
+pub const CELL_HEIGHT: u16 = 8;
+
+/// Size in pixels of the image from `--render-size`, such as `800x480`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RenderSize {
+    pub width: u16,
+    pub height: u16,
+}
+
+impl FromStr for RenderSize {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (width, height) = s.split_once('x').ok_or_else(|| anyhow!("expected WIDTHxHEIGHT"))?;
+        let size = Self { width: width.parse()?, height: height.parse()? };
+        // the tab bar and the borders need a few cells
+        if size.width < 20 * CELL_WIDTH || size.height < 10 * CELL_HEIGHT {
+            return Err(anyhow!("under {}x{}", 20 * CELL_WIDTH, 10 * CELL_HEIGHT));
+        }
+        Ok(size)
+    }
+}
+
+/// Rendered pixels, see [`ImageBackend::image`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Image {
+    pub width: usize,
+    pub height: usize,
+    /// RGB, by rows
+    pub pixels: Vec<[u8; 3]>,
+}
+
+impl Image {
+    fn new(width: usize, height: usize) -> Self {
+        Self { width, height, pixels: vec![[0; 3]; width * height] }
+    }
+
+    fn fill(&mut self, x: usize, y: usize, width: usize, height: usize, rgb: [u8; 3]) {
+        for row in y..(y + height).min(self.height) {
+            for column in x..(x + width).min(self.width) {
+                self.pixels[row * self.width + column] = rgb;
+            }
+        }
+    }
+
+    /// Write as a binary (P6) PPM
+    pub fn write_ppm(&self, mut writer: impl Write) -> io::Result<()> {
+        write!(writer, "P6\n{} {}\n255\n", self.width, self.height)?;
+        writer.write_all(&self.pixels.concat())
+    }
+}
+
+/// Off-screen ratatui [`Backend`], see [`self`]
+#[derive(Debug)]
+pub struct ImageBackend {
+    buffer: Buffer,
+    cursor: Position,
+    size: RenderSize,
+}
+
+impl ImageBackend {
+    /// Backend of the cells fitting in `size`
+    #[must_use]
+    pub fn new(size: RenderSize) -> Self {
+        let area = Rect::new(0, 0, size.width / CELL_WIDTH, size.height / CELL_HEIGHT);
+        Self { buffer: Buffer::empty(area), cursor: Position::ORIGIN, size }
+    }
+
+    /// Rasterize the cells, the pixels past the last cell are left black
+    #[must_use]
+    pub fn image(&self) -> Image {
+        let mut image = Image::new(self.size.width.into(), self.size.height.into());
+        for position in self.buffer.area.positions() {
+            let x = usize::from(position.x * CELL_WIDTH);
+            let y = usize::from(position.y * CELL_HEIGHT);
+            draw_cell(&mut image, x, y, &self.buffer[position]);
+        }
+        image
+    }
+}
+
+impl Backend for ImageBackend {
+    fn draw<'a, I>(&mut self, content: I) -> io::Result<()>
+    where
+        I: Iterator<Item = (u16, u16, &'a Cell)>,
+    {
+        for (x, y, cell) in content {
+            self.buffer[(x, y)] = cell.clone();
+        }
+        Ok(())
+    }
+
+    fn hide_cursor(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn show_cursor(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn get_cursor_position(&mut self) -> io::Result<Position> {
+        Ok(self.cursor)
+    }
+
+    fn set_cursor_position<P: Into<Position>>(&mut self, position: P) -> io::Result<()> {
+        self.cursor = position.into();
+        Ok(())
+    }
+
+    fn clear(&mut self) -> io::Result<()> {
+        self.buffer.reset();
+        Ok(())
+    }
+
+    fn size(&self) -> io::Result<Size> {
+        Ok(self.buffer.area.as_size())
+    }
+
+    fn window_size(&mut self) -> io::Result<WindowSize> {
+        Ok(WindowSize {
+            columns_rows: self.buffer.area.as_size(),
+            pixels: Size::new(self.size.width, self.size.height),
+        })
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Image file of `--render`, drawn every `--render-interval`
+#[derive(Debug)]
+pub struct Renderer {
+    pub terminal: Terminal<ImageBackend>,
+    path: PathBuf,
+    interval: Duration,
+    last: Option<Instant>,
+}
+
+impl Renderer {
+    pub fn new(path: impl Into<PathBuf>, size: RenderSize, interval: Duration) -> Result<Self> {
+        let terminal = Terminal::new(ImageBackend::new(size))?;
+        Ok(Self { terminal, path: path.into(), interval, last: None })
+    }
+
+    /// The image wasn't written within the interval
+    #[must_use]
+    pub fn is_due(&self, now: Instant) -> bool {
+        self.last.map_or(true, |last| now.duration_since(last) >= self.interval)
+    }
+
+    /// Write the image of the last draw of [`Self::terminal`], through a temporary file such
+    /// that readers never see a partial image
+    pub fn save(&mut self, now: Instant) -> Result<()> {
+        self.last = Some(now);
+        let image = self.terminal.backend().image();
+        let mut tmp = self.path.clone().into_os_string();
+        tmp.push(".tmp");
+        let mut writer = BufWriter::new(
+            File::create(&tmp).with_context(|| format!("unable to create: {tmp:?}"))?,
+        );
+        image.write_ppm(&mut writer)?;
+        writer.into_inner().map_err(io::IntoInnerError::into_error)?.sync_all()?;
+        std::fs::rename(&tmp, &self.path)
+            .with_context(|| format!("unable to write: {}", self.path.display()))
+    }
+}
+
+fn draw_cell(image: &mut Image, x: usize, y: usize, cell: &Cell) {
+    let (mut fg, mut bg) = (rgb(cell.fg, [229; 3]), rgb(cell.bg, [0; 3]));
+    if cell.modifier.contains(Modifier::REVERSED) {
+        (fg, bg) = (bg, fg);
+    }
+    let (width, height) = (usize::from(CELL_WIDTH), usize::from(CELL_HEIGHT));
+    image.fill(x, y, width, height, bg);
+
+    let Some(c) = cell.symbol().chars().next() else {
+        return;
+    };
+    if let Some(dots) = braille(c) {
+        // 2x4 dots, each a 3x2 block such that the lines of the canvas stay connected
+        for (bit, (column, row)) in BRAILLE_DOTS.iter().enumerate() {
+            if dots & (1 << bit) != 0 {
+                image.fill(x + column * 3, y + row * 2, 3, 2, fg);
+            }
+        }
+    } else if let Some((up, down, left, right)) = box_lines(c) {
+        let (center_x, center_y) = (x + 2, y + 3);
+        if up {
+            image.fill(center_x, y, 1, 4, fg);
+        }
+        if down {
+            image.fill(center_x, center_y, 1, height - 3, fg);
+        }
+        if left {
+            image.fill(x, center_y, 3, 1, fg);
+        }
+        if right {
+            image.fill(center_x, center_y, width - 2, 1, fg);
+        }
+    } else if c == '█' {
+        image.fill(x, y, width, height, fg);
+    } else {
+        // unknown characters are drawn as a hollow box
+        let columns = glyph(c).unwrap_or([0x7f, 0x41, 0x41, 0x41, 0x7f]);
+        for (column, bits) in columns.iter().enumerate() {
+            for row in 0..7 {
+                if bits & (1 << row) != 0 {
+                    image.fill(x + column, y + row, 1, 1, fg);
+                }
+            }
+        }
+    }
+}
+
+/// Bits of U+2800 to U+28FF, from the Braille dots 1 to 8
+const BRAILLE_DOTS: [(usize, usize); 8] =
+    [(0, 0), (0, 1), (0, 2), (1, 0), (1, 1), (1, 2), (0, 3), (1, 3)];
+
+fn braille(c: char) -> Option<u8> {
+    u32::from(c).checked_sub(0x2800).and_then(|dots| u8::try_from(dots).ok())
+}
+
+/// Lines (up, down, left, right) of the box drawing characters of the borders
+fn box_lines(c: char) -> Option<(bool, bool, bool, bool)> {
+    Some(match c {
+        '─' | '━' | '═' => (false, false, true, true),
+        '│' | '┃' | '║' => (true, true, false, false),
+        '┌' | '╭' | '╔' => (false, true, false, true),
+        '┐' | '╮' | '╗' => (false, true, true, false),
+        '└' | '╰' | '╚' => (true, false, false, true),
+        '┘' | '╯' | '╝' => (true, false, true, false),
+        '├' => (true, true, false, true),
+        '┤' => (true, true, true, false),
+        '┬' => (false, true, true, true),
+        '┴' => (true, false, true, true),
+        '┼' => (true, true, true, true),
+        _ => return None,
+    })
+}
+
+/// 5x7 glyph, by columns with the top row in the lowest bit
+fn glyph(c: char) -> Option<[u8; 5]> {
+    Some(match c {
+        ' '..='~' => FONT[usize::from(c as u8 - b' ')],
+        '↑' => [0x04, 0x02, 0x7f, 0x02, 0x04],
+        '↓' => [0x10, 0x20, 0x7f, 0x20, 0x10],
+        '→' => [0x08, 0x08, 0x2a, 0x1c, 0x08],
+        '←' => [0x08, 0x1c, 0x2a, 0x08, 0x08],
+        '↗' => [0x40, 0x20, 0x14, 0x0c, 0x1c],
+        '↘' => [0x01, 0x02, 0x14, 0x18, 0x1c],
+        '↙' => [0x1c, 0x18, 0x14, 0x02, 0x01],
+        '↖' => [0x1c, 0x0c, 0x14, 0x20, 0x40],
+        '■' => [0x3e, 0x3e, 0x3e, 0x3e, 0x3e],
+        '▲' => [0x60, 0x78, 0x7e, 0x78, 0x60],
+        '○' => [0x1c, 0x22, 0x22, 0x22, 0x1c],
+        '•' => [0x00, 0x1c, 0x1c, 0x1c, 0x00],
+        '·' => [0x00, 0x00, 0x08, 0x00, 0x00],
+        '°' => [0x06, 0x09, 0x09, 0x06, 0x00],
+        _ => return None,
+    })
+}
+
+/// Printable ASCII, from `' '` to `'~'`
+#[rustfmt::skip]
+const FONT: [[u8; 5]; 95] = [
+    [0x00, 0x00, 0x00, 0x00, 0x00], [0x00, 0x00, 0x5f, 0x00, 0x00], [0x00, 0x07, 0x00, 0x07, 0x00],
+    [0x14, 0x7f, 0x14, 0x7f, 0x14], [0x24, 0x2a, 0x7f, 0x2a, 0x12], [0x23, 0x13, 0x08, 0x64, 0x62],
+    [0x36, 0x49, 0x55, 0x22, 0x50], [0x00, 0x05, 0x03, 0x00, 0x00], [0x00, 0x1c, 0x22, 0x41, 0x00],
+    [0x00, 0x41, 0x22, 0x1c, 0x00], [0x08, 0x2a, 0x1c, 0x2a, 0x08], [0x08, 0x08, 0x3e, 0x08, 0x08],
+    [0x00, 0x50, 0x30, 0x00, 0x00], [0x08, 0x08, 0x08, 0x08, 0x08], [0x00, 0x60, 0x60, 0x00, 0x00],
+    [0x20, 0x10, 0x08, 0x04, 0x02], [0x3e, 0x51, 0x49, 0x45, 0x3e], [0x00, 0x42, 0x7f, 0x40, 0x00],
+    [0x42, 0x61, 0x51, 0x49, 0x46], [0x21, 0x41, 0x45, 0x4b, 0x31], [0x18, 0x14, 0x12, 0x7f, 0x10],
+    [0x27, 0x45, 0x45, 0x45, 0x39], [0x3c, 0x4a, 0x49, 0x49, 0x30], [0x01, 0x71, 0x09, 0x05, 0x03],
+    [0x36, 0x49, 0x49, 0x49, 0x36], [0x06, 0x49, 0x49, 0x29, 0x1e], [0x00, 0x36, 0x36, 0x00, 0x00],
+    [0x00, 0x56, 0x36, 0x00, 0x00], [0x08, 0x14, 0x22, 0x41, 0x00], [0x14, 0x14, 0x14, 0x14, 0x14],
+    [0x00, 0x41, 0x22, 0x14, 0x08], [0x02, 0x01, 0x51, 0x09, 0x06], [0x32, 0x49, 0x79, 0x41, 0x3e],
+    [0x7e, 0x11, 0x11, 0x11, 0x7e], [0x7f, 0x49, 0x49, 0x49, 0x36], [0x3e, 0x41, 0x41, 0x41, 0x22],
+    [0x7f, 0x41, 0x41, 0x22, 0x1c], [0x7f, 0x49, 0x49, 0x49, 0x41], [0x7f, 0x09, 0x09, 0x09, 0x01],
+    [0x3e, 0x41, 0x49, 0x49, 0x7a], [0x7f, 0x08, 0x08, 0x08, 0x7f], [0x00, 0x41, 0x7f, 0x41, 0x00],
+    [0x20, 0x40, 0x41, 0x3f, 0x01], [0x7f, 0x08, 0x14, 0x22, 0x41], [0x7f, 0x40, 0x40, 0x40, 0x40],
+    [0x7f, 0x02, 0x0c, 0x02, 0x7f], [0x7f, 0x04, 0x08, 0x10, 0x7f], [0x3e, 0x41, 0x41, 0x41, 0x3e],
+    [0x7f, 0x09, 0x09, 0x09, 0x06], [0x3e, 0x41, 0x51, 0x21, 0x5e], [0x7f, 0x09, 0x19, 0x29, 0x46],
+    [0x46, 0x49, 0x49, 0x49, 0x31], [0x01, 0x01, 0x7f, 0x01, 0x01], [0x3f, 0x40, 0x40, 0x40, 0x3f],
+    [0x1f, 0x20, 0x40, 0x20, 0x1f], [0x3f, 0x40, 0x38, 0x40, 0x3f], [0x63, 0x14, 0x08, 0x14, 0x63],
+    [0x07, 0x08, 0x70, 0x08, 0x07], [0x61, 0x51, 0x49, 0x45, 0x43], [0x00, 0x7f, 0x41, 0x41, 0x00],
+    [0x02, 0x04, 0x08, 0x10, 0x20], [0x00, 0x41, 0x41, 0x7f, 0x00], [0x04, 0x02, 0x01, 0x02, 0x04],
+    [0x40, 0x40, 0x40, 0x40, 0x40], [0x00, 0x01, 0x02, 0x04, 0x00], [0x20, 0x54, 0x54, 0x54, 0x78],
+    [0x7f, 0x48, 0x44, 0x44, 0x38], [0x38, 0x44, 0x44, 0x44, 0x20], [0x38, 0x44, 0x44, 0x48, 0x7f],
+    [0x38, 0x54, 0x54, 0x54, 0x18], [0x08, 0x7e, 0x09, 0x01, 0x02], [0x0c, 0x52, 0x52, 0x52, 0x3e],
+    [0x7f, 0x08, 0x04, 0x04, 0x78], [0x00, 0x44, 0x7d, 0x40, 0x00], [0x20, 0x40, 0x44, 0x3d, 0x00],
+    [0x7f, 0x10, 0x28, 0x44, 0x00], [0x00, 0x41, 0x7f, 0x40, 0x00], [0x7c, 0x04, 0x18, 0x04, 0x78],
+    [0x7c, 0x08, 0x04, 0x04, 0x78], [0x38, 0x44, 0x44, 0x44, 0x38], [0x7c, 0x14, 0x14, 0x14, 0x08],
+    [0x08, 0x14, 0x14, 0x18, 0x7c], [0x7c, 0x08, 0x04, 0x04, 0x08], [0x48, 0x54, 0x54, 0x54, 0x20],
+    [0x04, 0x3f, 0x44, 0x40, 0x20], [0x3c, 0x40, 0x40, 0x20, 0x7c], [0x1c, 0x20, 0x40, 0x20, 0x1c],
+    [0x3c, 0x40, 0x30, 0x40, 0x3c], [0x44, 0x28, 0x10, 0x28, 0x44], [0x0c, 0x50, 0x50, 0x50, 0x3c],
+    [0x44, 0x64, 0x54, 0x4c, 0x44], [0x00, 0x08, 0x36, 0x41, 0x00], [0x00, 0x00, 0x7f, 0x00, 0x00],
+    [0x00, 0x41, 0x36, 0x08, 0x00], [0x08, 0x04, 0x08, 0x10, 0x08],
+];
+
+/// Colors of a dark xterm, `reset` being the default foreground or background
+fn rgb(color: Color, reset: [u8; 3]) -> [u8; 3] {
+    match color {
+        Color::Reset => reset,
+        Color::Black => [0, 0, 0],
+        Color::Red => [205, 0, 0],
+        Color::Green => [0, 205, 0],
+        Color::Yellow => [205, 205, 0],
+        Color::Blue => [0, 0, 238],
+        Color::Magenta => [205, 0, 205],
+        Color::Cyan => [0, 205, 205],
+        Color::Gray => [229, 229, 229],
+        Color::DarkGray => [127, 127, 127],
+        Color::LightRed => [255, 0, 0],
+        Color::LightGreen => [0, 255, 0],
+        Color::LightYellow => [255, 255, 0],
+        Color::LightBlue => [92, 92, 255],
+        Color::LightMagenta => [255, 0, 255],
+        Color::LightCyan => [0, 255, 255],
+        Color::White => [255, 255, 255],
+        Color::Rgb(r, g, b) => [r, g, b],
+        Color::Indexed(index) => match index {
+            0..=15 => rgb(ANSI[usize::from(index)], reset),
+            // 6x6x6 cube
+            16..=231 => {
+                let level = |n: u8| if n == 0 { 0 } else { 55 + n * 40 };
+                let n = index - 16;
+                [level(n / 36), level(n / 6 % 6), level(n % 6)]
+            }
+            _ => [8 + (index - 232) * 10; 3],
+        },
+    }
+}
+
+const ANSI: [Color; 16] = [
+    Color::Black,
+    Color::Red,
+    Color::Green,
+    Color::Yellow,
+    Color::Blue,
+    Color::Magenta,
+    Color::Cyan,
+    Color::Gray,
+    Color::DarkGray,
+    Color::LightRed,
+    Color::LightGreen,
+    Color::LightYellow,
+    Color::LightBlue,
+    Color::LightMagenta,
+    Color::LightCyan,
+    Color::White,
+];
+
+#[cfg(test)]
+mod tests {
+    use ratatui::style::Style;
+    use ratatui::widgets::{Block, Paragraph};
+
+    use super::*;
+
+    #[test]
+    fn render_size() {
+        assert_eq!(
+            "800x480".parse::<RenderSize>().unwrap(),
+            RenderSize { width: 800, height: 480 }
+        );
+        assert!("800".parse::<RenderSize>().is_err());
+        assert!("800x40".parse::<RenderSize>().is_err());
+    }
+
+    #[test]
+    fn rasterize() {
+        let size = RenderSize { width: 125, height: 80 };
+        let mut terminal = Terminal::new(ImageBackend::new(size)).unwrap();
+        assert_eq!(terminal.size().unwrap(), Size::new(20, 10));
+        terminal
+            .draw(|f| {
+                let paragraph = Paragraph::new("I\u{28ff}").style(Style::default().fg(Color::Red));
+                f.render_widget(paragraph.block(Block::bordered()), f.area());
+            })
+            .unwrap();
+        let image = terminal.backend().image();
+        assert_eq!((image.width, image.height), (125, 80));
+        let pixel = |x: usize, y: usize| image.pixels[y * image.width + x];
+
+        // the border of the top left cell
+        assert_eq!(pixel(0, 0), [0; 3]);
+        assert_eq!(pixel(2, 7), [205, 0, 0]);
+        assert_eq!(pixel(5, 3), [205, 0, 0]);
+        // the stem of "I", at the cell (1, 1)
+        assert_eq!(pixel(6 + 2, 8 + 3), [205, 0, 0]);
+        assert_eq!(pixel(6 + 1, 8 + 3), [0; 3]);
+        // all the dots of the Braille character
+        assert!((12..18).all(|x| (8..16).all(|y| pixel(x, y) == [205, 0, 0])));
+        // past the last cell
+        assert_eq!(pixel(124, 79), [0; 3]);
+
+        let mut ppm = vec![];
+        image.write_ppm(&mut ppm).unwrap();
+        assert!(ppm.starts_with(b"P6\n125 80\n255\n"));
+        assert_eq!(ppm.len(), 14 + 125 * 80 * 3);
+    }
+}
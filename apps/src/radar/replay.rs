@@ -0,0 +1,225 @@
+//! Playback of a recorded AVR file from `--replay`
+//!
+//! Lines with a `@` MLAT timestamp are played at the pace they were received, scaled by the
+//! playback speed. Lines without a timestamp are played as fast as they are read.
+
+use std::fmt;
+use std::io::{self, BufRead};
+use std::time::{Duration, Instant};
+
+use adsb_deku::avr;
+
+/// Frequency of the `@` MLAT timestamp clock
+const MLAT_CLOCK_HZ: f64 = 12_000_000.0;
+
+/// Slowest playback speed
+const MIN_SPEED: f64 = 1.0 / 16.0;
+
+/// Fastest playback speed
+const MAX_SPEED: f64 = 64.0;
+
+/// Longest time spent playing lines that are due without drawing the tui
+const MAX_DRAW_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Recorded file played back in the tui, which can be paused, stepped and sped up
+#[derive(Debug)]
+pub struct Replay<R> {
+    reader: R,
+    /// Line read from `reader`, not yet played
+    next: Option<String>,
+    /// `reader` reached the end of the file
+    finished: bool,
+    paused: bool,
+    /// Play the next frame while paused
+    step: bool,
+    speed: f64,
+    /// Timestamp of a played line and when it was played, for pacing the following lines
+    clock: Option<(u64, Instant)>,
+    /// Last time the tui was drawn
+    drawn: Instant,
+}
+
+impl<R: BufRead> Replay<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            next: None,
+            finished: false,
+            paused: false,
+            step: false,
+            speed: 1.0,
+            clock: None,
+            drawn: Instant::now(),
+        }
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Pause or resume playback
+    pub fn toggle_pause(&mut self) {
+        self.paused ^= true;
+        self.step = false;
+        self.clock = None;
+    }
+
+    /// Pause and play the next frame
+    pub fn step(&mut self) {
+        self.paused = true;
+        self.step = true;
+    }
+
+    /// Double the playback speed
+    pub fn faster(&mut self) {
+        self.set_speed(self.speed * 2.0);
+    }
+
+    /// Halve the playback speed
+    pub fn slower(&mut self) {
+        self.set_speed(self.speed / 2.0);
+    }
+
+    fn set_speed(&mut self, speed: f64) {
+        self.speed = speed.clamp(MIN_SPEED, MAX_SPEED);
+        self.clock = None;
+    }
+
+    /// Read the next line, if it is due at `now`
+    ///
+    /// Returns:
+    ///   `Ok(Some(line))`: line to play, including the newline
+    ///   `Ok(None)`:       paused, finished, or the next line isn't due yet
+    ///   `Err(e)`:         error reading the file
+    pub fn next_line(&mut self, now: Instant) -> io::Result<Option<String>> {
+        if !self.is_due(now)? {
+            return Ok(None);
+        }
+        let line = self.next.take();
+        if let Some(line) = &line {
+            if let Ok(Some(frame)) = avr::parse_line(line) {
+                // start pacing from this frame, or restart if the receiver clock was reset
+                if let Some(timestamp) = frame.timestamp {
+                    if self.clock.map_or(true, |(start, _)| timestamp < start) {
+                        self.clock = Some((timestamp, now));
+                    }
+                }
+                self.step = false;
+            }
+        }
+        Ok(line)
+    }
+
+    /// Return true if the tui should be drawn before playing more lines
+    ///
+    /// Lines that are already due are played without drawing, for at most [`MAX_DRAW_INTERVAL`].
+    pub fn should_draw(&mut self, now: Instant) -> bool {
+        let draw = !self.is_due(now).unwrap_or(false)
+            || now.saturating_duration_since(self.drawn) >= MAX_DRAW_INTERVAL;
+        if draw {
+            self.drawn = now;
+        }
+        draw
+    }
+
+    /// Return true if the next line can be played at `now`
+    fn is_due(&mut self, now: Instant) -> io::Result<bool> {
+        if self.paused && !self.step {
+            return Ok(false);
+        }
+        if self.next.is_none() && !self.finished {
+            let mut line = String::new();
+            if self.reader.read_line(&mut line)? == 0 {
+                self.finished = true;
+            } else {
+                self.next = Some(line);
+            }
+        }
+        let Some(line) = &self.next else {
+            return Ok(false);
+        };
+        if self.step {
+            return Ok(true);
+        }
+
+        let timestamp = avr::parse_line(line).ok().flatten().and_then(|frame| frame.timestamp);
+        match (timestamp, self.clock) {
+            (Some(timestamp), Some((start, played))) if timestamp >= start => {
+                let offset = (timestamp - start) as f64 / MLAT_CLOCK_HZ / self.speed;
+                Ok(now >= played + Duration::from_secs_f64(offset))
+            }
+            _ => Ok(true),
+        }
+    }
+}
+
+impl<R> fmt::Display for Replay<R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "replay {}x", self.speed)?;
+        if self.finished && self.next.is_none() {
+            write!(f, " (END)")
+        } else if self.paused {
+            write!(f, " (PAUSED)")
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+
+    /// Three frames, one second apart
+    const FILE: &str = "@000000B71B008D40621D58C382D690C8AC2863A7;
+# comment
+@0000016E36008D40621D58C386435CC412692AD6;
+@00000225510002E197B00179C3;
+";
+
+    #[test]
+    fn paced_by_timestamp() {
+        let start = Instant::now();
+        let mut replay = Replay::new(Cursor::new(FILE));
+
+        assert!(replay.next_line(start).unwrap().unwrap().starts_with("@000000B71B00"));
+        assert_eq!(replay.next_line(start).unwrap().unwrap(), "# comment\n");
+        assert!(replay.next_line(start).unwrap().is_none());
+        assert!(replay.should_draw(start + Duration::from_millis(999)));
+        assert!(replay.next_line(start + Duration::from_secs(1)).unwrap().is_some());
+
+        // twice as fast
+        replay.faster();
+        let now = start + Duration::from_secs(2);
+        assert!(replay.next_line(now).unwrap().is_some());
+        assert!(replay.next_line(now).unwrap().is_none());
+        assert_eq!(replay.to_string(), "replay 2x (END)");
+    }
+
+    #[test]
+    fn pause_and_step() {
+        let start = Instant::now();
+        let mut replay = Replay::new(Cursor::new(FILE));
+
+        replay.toggle_pause();
+        assert!(replay.is_paused());
+        assert!(replay.next_line(start).unwrap().is_none());
+        assert!(replay.should_draw(start));
+        assert_eq!(replay.to_string(), "replay 1x (PAUSED)");
+
+        // a step plays a single frame, including the lines before it
+        replay.step();
+        assert!(replay.next_line(start).unwrap().is_some());
+        assert!(replay.next_line(start).unwrap().is_none());
+        replay.step();
+        assert_eq!(replay.next_line(start).unwrap().unwrap(), "# comment\n");
+        assert!(replay.next_line(start).unwrap().unwrap().starts_with("@0000016E3600"));
+        assert!(replay.next_line(start + Duration::from_secs(10)).unwrap().is_none());
+
+        // resume from the next frame, without waiting for the time spent paused
+        replay.toggle_pause();
+        assert!(replay.next_line(start + Duration::from_secs(10)).unwrap().is_some());
+    }
+}
@@ -0,0 +1,126 @@
+//! Health of each input of radar, for the "Sources" section of the Stats tab
+//!
+//! A [`SourceHealth`] follows the [`IngestStats`] of the [`Ingest`] reading a source, across the
+//! new [`Ingest`] of each reconnection, such that a dead feed shows as no frames/s and a growing
+//! last data age.
+//!
+//! [`Ingest`]: rsadsb_apps::ingest::Ingest
+
+use std::time::{Duration, Instant};
+
+use rsadsb_apps::ingest::IngestStats;
+
+/// Interval of the frames/s measurement
+const RATE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Frames, CRC failures, last data and reconnections of a source, see [`self`]
+#[derive(Debug, Clone)]
+pub struct SourceHealth {
+    /// Such as `localhost:30002`
+    pub name: String,
+    /// Frames read, including the frames dropped by `--input-queue`
+    pub frames: u64,
+    /// Frames read per second, over the last [`RATE_INTERVAL`]
+    pub rate: f32,
+    pub crc_checked: u64,
+    pub crc_failures: u64,
+    /// Monotonic time of the last frame read
+    pub last_data: Option<Instant>,
+    pub reconnects: u32,
+    /// Stats of the current [`Ingest`](rsadsb_apps::ingest::Ingest), for the difference
+    ingest: IngestStats,
+    /// Start and `frames` of the current rate measurement
+    rate_start: (Instant, u64),
+}
+
+impl SourceHealth {
+    pub fn new(name: String, now: Instant) -> Self {
+        Self {
+            name,
+            frames: 0,
+            rate: 0.0,
+            crc_checked: 0,
+            crc_failures: 0,
+            last_data: None,
+            reconnects: 0,
+            ingest: IngestStats::default(),
+            rate_start: (now, 0),
+        }
+    }
+
+    /// Update from the stats of the current `Ingest` of the source
+    pub fn update(&mut self, ingest: IngestStats, now: Instant) {
+        let frames = (ingest.received + ingest.dropped)
+            .saturating_sub(self.ingest.received + self.ingest.dropped);
+        if frames > 0 {
+            self.frames += frames;
+            self.last_data = Some(now);
+        }
+        self.crc_checked += ingest.crc_checked.saturating_sub(self.ingest.crc_checked);
+        self.crc_failures += ingest.crc_failures.saturating_sub(self.ingest.crc_failures);
+        self.ingest = ingest;
+
+        let (start, start_frames) = self.rate_start;
+        let elapsed = now.duration_since(start);
+        if elapsed >= RATE_INTERVAL {
+            self.rate = (self.frames - start_frames) as f32 / elapsed.as_secs_f32();
+            self.rate_start = (now, self.frames);
+        }
+    }
+
+    /// The source was reconnected, with a new `Ingest` starting from zero
+    pub fn reconnected(&mut self) {
+        self.reconnects += 1;
+        self.ingest = IngestStats::default();
+    }
+
+    /// Ratio of the CRC-checked frames with an invalid CRC, `None` without any checked frame
+    #[must_use]
+    pub fn crc_failure_rate(&self) -> Option<f32> {
+        (self.crc_checked != 0).then(|| self.crc_failures as f32 / self.crc_checked as f32)
+    }
+
+    /// Time since the last frame read, `None` if none were read
+    #[must_use]
+    pub fn last_data_age(&self, now: Instant) -> Option<Duration> {
+        self.last_data.map(|last_data| now.duration_since(last_data))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stats(received: u64, crc_checked: u64, crc_failures: u64) -> IngestStats {
+        IngestStats { received, crc_checked, crc_failures, ..IngestStats::default() }
+    }
+
+    #[test]
+    fn health() {
+        let start = Instant::now();
+        let mut health = SourceHealth::new("localhost:30002".to_string(), start);
+        assert_eq!(health.crc_failure_rate(), None);
+        assert_eq!(health.last_data_age(start), None);
+
+        health.update(stats(100, 80, 4), start + Duration::from_secs(2));
+        assert_eq!(health.frames, 100);
+        assert_eq!(health.rate, 50.0);
+        assert_eq!(health.crc_failure_rate(), Some(0.05));
+
+        // the new Ingest of a reconnection counts from zero
+        health.reconnected();
+        health.update(stats(20, 20, 0), start + Duration::from_secs(3));
+        assert_eq!(health.frames, 120);
+        assert_eq!(health.rate, 20.0);
+        assert_eq!(health.crc_failures, 4);
+        assert_eq!(health.reconnects, 1);
+
+        // no data
+        health.update(stats(20, 20, 0), start + Duration::from_secs(5));
+        assert_eq!(health.rate, 0.0);
+        assert_eq!(
+            health.last_data_age(start + Duration::from_secs(5)),
+            Some(Duration::from_secs(2))
+        );
+    }
+}
@@ -1,23 +1,106 @@
-use std::time::SystemTime;
+use std::time::{Instant, SystemTime};
 
+use adsb_deku::demod::DemodMeta;
 use adsb_deku::ICAO;
-use ratatui::layout::{Constraint, Rect};
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
 use ratatui::style::{Color, Style};
 use ratatui::widgets::{Block, Row, Table};
-use rsadsb_common::{Added, AirplaneCoor, Airplanes};
+use rsadsb_apps::ingest::IngestStats;
+use rsadsb_common::anomaly::Anomalies;
+use rsadsb_common::dedup::Dedup;
+use rsadsb_common::demod::DemodStats;
+use rsadsb_common::equipage::FleetEquipage;
+use rsadsb_common::governor::Governor;
+use rsadsb_common::interference::InterferenceDetector;
+use rsadsb_common::interrogation::Interrogation;
+use rsadsb_common::locate::{self, ReceiverEstimate, ReceiverLocator};
+use rsadsb_common::qnh::QnhConsensus;
+use rsadsb_common::timestamp;
+use rsadsb_common::{ActionOutcome, Added, AirplaneCoor, Airplanes, FirstFixStats, RejectReason};
 use tracing::info;
 
+use crate::ledger::Ledger;
+use crate::sources::SourceHealth;
+use crate::tab::{Tab, TabContext};
 use crate::{Settings, DEFAULT_PRECISION};
 
+/// Stats tab
+pub struct StatsTab;
+
+impl Tab for StatsTab {
+    fn title(&self, _: &Airplanes) -> String {
+        "Stats".to_string()
+    }
+
+    fn render(&self, f: &mut ratatui::Frame, chunks: &[Rect], ctx: &TabContext<'_>) -> Rect {
+        build_tab_stats(f, chunks, ctx.stats, ctx.settings);
+        chunks[1]
+    }
+}
+
 #[derive(Debug, Default)]
 pub struct Stats {
     most_distance: Option<(SystemTime, ICAO, AirplaneCoor)>,
     most_airplanes: Option<(SystemTime, u32)>,
     total_airplanes: u32,
+    /// positions dropped by (out of range, distance jump, stale pair)
+    rejected_positions: (u64, u64, u64),
+    /// tracked aircraft with the most positions rejected by the range checks, with (out of
+    /// range, distance jump)
+    most_rejected_fixes: Option<(ICAO, u64, u64)>,
+    anomalies: Anomalies,
+    first_fix: FirstFixStats,
+    equipage: FleetEquipage,
+    interrogation: Interrogation,
+    /// fleet-wide NIC/NACp drops
+    interference: InterferenceDetector,
+    /// (passed, suppressed) frames of `--dedup-window`
+    dedup: Option<(u64, u64)>,
+    /// (passed, dropped) frames of `--max-rate`
+    governor: Option<(u64, u64)>,
+    /// measurements of the demodulator attached to the frames, `None` without any
+    demod: Option<DemodStats>,
+    /// frames read from the ADS-B server, see `Ingest::stats`
+    ingest: Option<IngestStats>,
+    /// last QNH of the aircraft below `--transition-altitude`
+    qnh: Option<(SystemTime, QnhConsensus)>,
+    /// (samples, estimate) of `--auto-position`
+    auto_position: Option<(usize, Option<ReceiverEstimate>)>,
+    /// daily statistics of `--stats-ledger`
+    pub ledger: Option<Ledger>,
+    /// health of each input, empty with `--replay`
+    pub sources: Vec<SourceHealth>,
 }
 
 impl Stats {
-    pub fn update(&mut self, airplanes: &Airplanes, airplane_added: Added) {
+    /// Update from a decoded frame
+    pub fn update(&mut self, airplanes: &Airplanes, outcome: &ActionOutcome) {
+        // Update most airplanes
+        let current_len = airplanes.len();
+        let most_airplanes = self.most_airplanes.map_or(0, |most_airplanes| most_airplanes.1);
+
+        if most_airplanes < current_len as u32 {
+            info!("new most airplanes: {}", current_len);
+            self.most_airplanes = Some((SystemTime::now(), current_len as u32));
+        }
+
+        // Update total airplanes
+        if outcome.added == Added::Yes {
+            self.total_airplanes += 1;
+        }
+
+        // Update rejected positions
+        match outcome.rejected_reason {
+            Some(RejectReason::OutOfRange) => self.rejected_positions.0 += 1,
+            Some(RejectReason::DistanceJump) => self.rejected_positions.1 += 1,
+            Some(RejectReason::StalePair) => self.rejected_positions.2 += 1,
+            None => (),
+        }
+    }
+
+    /// Update the values computed from all airplanes, once before drawing instead of for each
+    /// frame
+    pub fn refresh(&mut self, airplanes: &Airplanes) {
         // Update most_distance
         let current_distance = self.most_distance.map_or(0.0, |most_distance| {
             most_distance.2.kilo_distance.map_or(0.0, |kilo_distance| kilo_distance)
@@ -31,20 +114,69 @@ impl Stats {
             }
         }
 
-        // Update most airplanes
-        let current_len = airplanes.len();
-        let most_airplanes = self.most_airplanes.map_or(0, |most_airplanes| most_airplanes.1);
+        // Update the tracked airplane with the most rejected positions
+        self.most_rejected_fixes = airplanes
+            .iter()
+            .filter(|(_, state)| state.rejected_fixes.total() > 0)
+            .max_by_key(|(_, state)| state.rejected_fixes.total())
+            .map(|(key, state)| {
+                let rejected = &state.rejected_fixes;
+                (*key, rejected.out_of_range, rejected.distance_jump)
+            });
 
-        if most_airplanes < current_len as u32 {
-            info!("new most airplanes: {}", current_len);
-            self.most_airplanes = Some((SystemTime::now(), current_len as u32));
-        }
+        // Update anomalies of currently tracked airplanes
+        self.anomalies = airplanes.anomalies();
 
-        // Update total airplanes
-        if airplane_added == Added::Yes {
-            self.total_airplanes += 1;
+        // Update time to first position of all airplanes
+        self.first_fix = airplanes.first_fix_stats();
+
+        // Update transponder level and ADS-B version of tracked airplanes
+        self.equipage = airplanes.fleet_equipage();
+
+        // Update downlink requests and interrogators of tracked airplanes
+        self.interrogation = airplanes.interrogation();
+
+        // Start or end GNSS interference events of tracked airplanes
+        self.interference.update(airplanes, Instant::now());
+    }
+}
+
+impl Stats {
+    pub fn update_dedup(&mut self, dedup: &Dedup) {
+        self.dedup = Some((dedup.passed, dedup.suppressed));
+    }
+
+    pub fn update_governor(&mut self, governor: &Governor) {
+        self.governor = Some((governor.passed, governor.dropped));
+    }
+
+    pub fn update_demod(&mut self, meta: &DemodMeta) {
+        self.demod.get_or_insert_with(DemodStats::default).add(meta);
+    }
+
+    /// Update the QNH, kept when no aircraft below `transition_altitude` (ft) sends its QNH
+    pub fn update_qnh(&mut self, airplanes: &Airplanes, transition_altitude: i32) {
+        if let Some(consensus) = QnhConsensus::from_airplanes(airplanes, transition_altitude) {
+            self.qnh = Some((SystemTime::now(), consensus));
         }
     }
+
+    /// Last QNH (hPa), see [`Self::update_qnh`]
+    pub fn qnh(&self) -> Option<f32> {
+        self.qnh.map(|(_, consensus)| consensus.qnh)
+    }
+
+    pub fn update_ingest(&mut self, ingest: IngestStats) {
+        self.ingest = Some(ingest);
+    }
+
+    pub fn update_auto_position(
+        &mut self,
+        locator: &ReceiverLocator,
+        estimate: Option<ReceiverEstimate>,
+    ) {
+        self.auto_position = Some((locator.samples(), estimate));
+    }
 }
 
 /// Render Help tab for tui display
@@ -64,9 +196,8 @@ pub fn build_tab_stats(
         let distance = format!("{:.DEFAULT_PRECISION$}", value.kilo_distance.unwrap());
 
         // display time
-        let datetime = time::OffsetDateTime::from(time);
         (
-            datetime.to_offset(settings.utc_offset).format(&format).unwrap(),
+            timestamp::local(time, settings.utc_offset).format(&format).unwrap(),
             format!("[{key}]: {distance}km {lat},{lon}"),
         )
     } else {
@@ -77,9 +208,8 @@ pub fn build_tab_stats(
     // Most airplanes tracked at one time
     let (time, value) = if let Some((time, most_airplanes)) = stats.most_airplanes {
         // display time
-        let datetime = time::OffsetDateTime::from(time);
         (
-            datetime.to_offset(settings.utc_offset).format(&format).unwrap(),
+            timestamp::local(time, settings.utc_offset).format(&format).unwrap(),
             most_airplanes.to_string(),
         )
     } else {
@@ -91,12 +221,237 @@ pub fn build_tab_stats(
     let total_airplanes_s = stats.total_airplanes.to_string();
     rows.push(Row::new(vec!["Total Airplanes", "All Time", &total_airplanes_s]));
 
-    // draw table
+    // Positions dropped while decoding
+    let (out_of_range, distance_jump, stale_pair) = stats.rejected_positions;
+    let rejected_s = format!(
+        "{} (out of range: {out_of_range}, distance jump: {distance_jump}, stale pair: {stale_pair})",
+        out_of_range + distance_jump + stale_pair,
+    );
+    rows.push(Row::new(vec!["Dropped Position", "All Time", &rejected_s]));
+
+    // Airplane with the most positions dropped by the range checks
+    let most_rejected_s = stats.most_rejected_fixes.map_or_else(
+        || "None".to_string(),
+        |(key, out_of_range, distance_jump)| {
+            format!("[{key}] (out of range: {out_of_range}, distance jump: {distance_jump})")
+        },
+    );
+    rows.push(Row::new(vec!["Most Dropped", "Tracked", &most_rejected_s]));
+
+    // Anomalies of currently tracked airplanes
+    let anomalies = stats.anomalies;
+    let anomalies_s = format!(
+        "{} (capability: {}, version: {}, NIC: {}, NACp: {}, callsign: {}, invalid callsign: {})",
+        anomalies.total(),
+        anomalies.capability_change,
+        anomalies.version_change,
+        anomalies.nic_change,
+        anomalies.nac_change,
+        anomalies.callsign_alternation,
+        anomalies.invalid_callsign,
+    );
+    rows.push(Row::new(vec!["Anomalies", "Tracked", &anomalies_s]));
+
+    // Messages and time from detection to first position
+    let first_fix = stats.first_fix;
+    let first_fix_s = match (first_fix.mean_messages(), first_fix.mean_elapsed()) {
+        (Some(messages), Some(elapsed)) => format!(
+            "{messages:.1} messages, {:.1}s (max: {} messages, {:.1}s, aircraft: {})",
+            elapsed.as_secs_f32(),
+            first_fix.max_messages,
+            first_fix.max_elapsed.as_secs_f32(),
+            first_fix.aircraft,
+        ),
+        _ => "None".to_string(),
+    };
+    rows.push(Row::new(vec!["First Position", "All Time", &first_fix_s]));
+
+    // Transponder level and ADS-B version of currently tracked airplanes
+    let equipage = stats.equipage;
+    let equipage_s = format!(
+        "Level 1: {}, Level 2+: {}, ADS-B v0: {}, v1: {}, v2: {}, unknown: {}",
+        equipage.level_1,
+        equipage.level_2,
+        equipage.adsb_version[0],
+        equipage.adsb_version[1],
+        equipage.adsb_version[2],
+        equipage.unknown_version,
+    );
+    rows.push(Row::new(vec!["Equipage", "Tracked", &equipage_s]));
+
+    // Comm-B requests and interrogators (IIS) of currently tracked airplanes
+    let interrogation = &stats.interrogation;
+    let interrogators: Vec<String> =
+        interrogation.interrogators().map(|(iis, amount)| format!("{iis}: {amount}")).collect();
+    let interrogation_s = format!(
+        "Comm-B broadcast: {}, Comm-B request: {}, IIS: [{}]",
+        interrogation.broadcast,
+        interrogation.comm_b_request,
+        interrogators.join(", "),
+    );
+    rows.push(Row::new(vec!["Interrogation", "Tracked", &interrogation_s]));
+
+    // Fleet-wide drops of the NIC/NACp of currently tracked airplanes
+    let interference = &stats.interference;
+    let fleet = interference.fleet;
+    let state_s = match interference.started() {
+        Some(started) => format!("ACTIVE for {:.0}s", started.elapsed().as_secs_f32()),
+        None => "None".to_string(),
+    };
+    let interference_s = format!(
+        "{state_s} (degraded: {}/{}, events: {})",
+        fleet.degraded, fleet.aircraft, interference.events
+    );
+    let style =
+        if interference.is_active() { Style::default().fg(Color::Red) } else { Style::default() };
+    rows.push(Row::new(vec!["GNSS Jamming", "Tracked", &interference_s]).style(style));
+
+    // QNH of the airplanes below --transition-altitude
+    let (time, value) = if let Some((time, consensus)) = stats.qnh {
+        (
+            timestamp::local(time, settings.utc_offset).format(&format).unwrap(),
+            format!(
+                "{:.1} hPa (aircraft: {}, spread: {:.1} hPa)",
+                consensus.qnh, consensus.samples, consensus.spread
+            ),
+        )
+    } else {
+        ("None".to_string(), "".to_string())
+    };
+    rows.push(Row::new(vec!["QNH".to_string(), time, value]));
+
+    // Duplicate frames suppressed by --dedup-window
+    if let Some((passed, suppressed)) = stats.dedup {
+        let dedup_s = format!("{suppressed} (passed: {passed})");
+        rows.push(Row::new(vec!["Duplicates".to_string(), "All Time".to_string(), dedup_s]));
+    }
+
+    // Frames dropped by --max-rate
+    if let Some((passed, dropped)) = stats.governor {
+        let governor_s = format!("{dropped} (passed: {passed})");
+        rows.push(Row::new(vec!["Throttled".to_string(), "All Time".to_string(), governor_s]));
+    }
+
+    // Measurements of the demodulator, only from the frontends attaching them to the frames
+    if let Some(demod) = &stats.demod {
+        let score_s = demod
+            .mean_preamble_score()
+            .map_or_else(|| "None".to_string(), |score| format!("{score:.2}"));
+        let signal_s = demod
+            .mean_signal_level()
+            .map_or_else(|| "None".to_string(), |signal_level| format!("{signal_level:.0}"));
+        let demod_s = format!(
+            "{} frames, preamble score: {score_s}, signal level: {signal_s}, corrected: {} frames ({} bits), phases: {:?}",
+            demod.frames, demod.corrected_frames, demod.corrected_bits, demod.phases
+        );
+        rows.push(Row::new(vec!["Demodulator".to_string(), "All Time".to_string(), demod_s]));
+    }
+
+    // Partial or joined lines, and frames dropped by --input-queue from the ADS-B server
+    if let Some(ingest) = stats.ingest {
+        rows.push(Row::new(vec![
+            "Input Resyncs".to_string(),
+            "Connection".to_string(),
+            ingest.resyncs.to_string(),
+        ]));
+        let dropped_s = format!("{} (received: {})", ingest.dropped, ingest.received);
+        rows.push(Row::new(vec!["Input Dropped".to_string(), "Connection".to_string(), dropped_s]));
+    }
+
+    // Frame rate, CRC failures, last data and reconnections of each input
+    let now = Instant::now();
+    for source in &stats.sources {
+        let crc_s = source
+            .crc_failure_rate()
+            .map_or_else(|| "None".to_string(), |rate| format!("{:.1}%", rate * 100.0));
+        let last_data_s = source
+            .last_data_age(now)
+            .map_or_else(|| "None".to_string(), |age| format!("{:.1}s ago", age.as_secs_f32()));
+        let source_s = format!(
+            "{}: {:.1} frames/s, CRC failures: {crc_s}, last data: {last_data_s}, reconnects: {}",
+            source.name, source.rate, source.reconnects,
+        );
+        rows.push(Row::new(vec!["Source".to_string(), "Now".to_string(), source_s]));
+    }
+
+    // Receiver location estimated by --auto-position
+    if let Some((samples, estimate)) = stats.auto_position {
+        let auto_position_s = match estimate {
+            Some(estimate) => format!(
+                "{:.DEFAULT_PRECISION$}, {:.DEFAULT_PRECISION$} (samples: {samples}, range: {:.0} km)",
+                estimate.position.0, estimate.position.1, estimate.range
+            ),
+            None => format!("waiting (samples: {samples}/{})", locate::MIN_SAMPLES),
+        };
+        rows.push(Row::new(vec![
+            "Auto Position".to_string(),
+            "All Time".to_string(),
+            auto_position_s,
+        ]));
+    }
+
+    // draw table, with the daily history of --stats-ledger below
+    let rows_len = rows.len() as u16;
     let widths = &[Constraint::Length(16), Constraint::Length(15), Constraint::Length(200)];
     let table = Table::new(rows, widths)
         .style(Style::default().fg(Color::White))
         .header(Row::new(vec!["Type", "DateTime", "Value"]).bottom_margin(1))
         .block(Block::bordered().title("Stats"))
         .column_spacing(1);
-    f.render_widget(table, chunks[1]);
+    let Some(ledger) = &stats.ledger else {
+        f.render_widget(table, chunks[1]);
+        return;
+    };
+    let vertical_chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(rows_len + 4), Constraint::Min(0)])
+        .split(chunks[1]);
+    f.render_widget(table, vertical_chunks[0]);
+    build_ledger(f, vertical_chunks[1], ledger);
+}
+
+/// Render the daily history of `--stats-ledger`, newest first
+fn build_ledger(f: &mut ratatui::Frame, area: Rect, ledger: &Ledger) {
+    let range = |range: Option<f64>| {
+        range.map_or_else(String::new, |range| format!("{range:.DEFAULT_PRECISION$}km"))
+    };
+
+    let (aircraft, messages, max_range) = ledger.records();
+    let mut rows = vec![Row::new(vec![
+        "Record".to_string(),
+        aircraft.to_string(),
+        messages.to_string(),
+        range(max_range),
+        String::new(),
+    ])
+    .style(Style::default().fg(Color::Green))];
+    for day in ledger.days().rev() {
+        let busiest_hour = day
+            .busiest_hour()
+            .map_or_else(String::new, |(hour, messages)| format!("{hour:02}:00 ({messages})"));
+        rows.push(Row::new(vec![
+            day.date.clone(),
+            day.unique_aircraft.to_string(),
+            day.messages.to_string(),
+            range(day.max_range),
+            busiest_hour,
+        ]));
+    }
+
+    let widths = &[
+        Constraint::Length(10),
+        Constraint::Length(8),
+        Constraint::Length(10),
+        Constraint::Length(12),
+        Constraint::Length(20),
+    ];
+    let table = Table::new(rows, widths)
+        .style(Style::default().fg(Color::White))
+        .header(
+            Row::new(vec!["Date", "Aircraft", "Messages", "Max Range", "Busiest Hour"])
+                .bottom_margin(1),
+        )
+        .block(Block::bordered().title("Daily History"))
+        .column_spacing(1);
+    f.render_widget(table, area);
 }
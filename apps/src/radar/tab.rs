@@ -0,0 +1,187 @@
+//! Tabs of the top bar, see [`Tab`]
+//!
+//! The keys and mouse events of all tabs, such as switching tabs and quitting, are handled in the
+//! main loop. Everything else is handled by the selected tab, so a new tab only implements
+//! [`Tab`] and is added to [`TabBar::new`]. Keys are looked up in [`crate::keys::Keymap`], so the
+//! tabs handle an [`Action`].
+
+use crossterm::event::{MouseButton, MouseEvent, MouseEventKind};
+use ratatui::layout::Rect;
+use rsadsb_common::Airplanes;
+
+use crate::airplanes::AirplanesTab;
+use crate::coverage::{Coverage, CoverageTab};
+use crate::help::HelpTab;
+use crate::keys::Action;
+use crate::map::MapTab;
+use crate::polar::Polar;
+use crate::stats::{Stats, StatsTab};
+use crate::Settings;
+
+/// Index of the Map tab in [`TabBar::new`], selected from the Airplanes tab
+pub const MAP_TAB: usize = 0;
+
+/// State of the main loop drawn by the tabs
+pub struct TabContext<'a> {
+    pub settings: &'a Settings,
+    pub adsb_airplanes: &'a Airplanes,
+    pub coverage: &'a Coverage,
+    pub stats: &'a Stats,
+    pub polar: &'a Polar,
+}
+
+/// Tab of the top bar
+pub trait Tab {
+    /// Title in the top bar
+    fn title(&self, adsb_airplanes: &Airplanes) -> String;
+
+    /// Draw the tab in `chunks[1]`, with the `--touchscreen` controls in `chunks[0]`
+    ///
+    /// Returns the area receiving the mouse events of [`Self::handle_mouse`].
+    fn render(&self, f: &mut ratatui::Frame, chunks: &[Rect], ctx: &TabContext<'_>) -> Rect;
+
+    /// Handle the action of a key that isn't handled by all tabs
+    fn handle_key(&mut self, _action: Action, _settings: &mut Settings, _airplanes: &Airplanes) {}
+
+    /// Handle a mouse event below the top bar, in the `area` returned by [`Self::render`]
+    fn handle_mouse(
+        &mut self,
+        _mouse: MouseEvent,
+        _settings: &mut Settings,
+        _airplanes: &Airplanes,
+        _area: Rect,
+    ) {
+    }
+
+    /// Show the zoom and reset controls of `--touchscreen`
+    fn touchscreen(&self) -> bool {
+        false
+    }
+}
+
+/// Tabs of the top bar, in order
+pub struct TabBar {
+    tabs: Vec<Box<dyn Tab>>,
+}
+
+impl TabBar {
+    pub fn new() -> Self {
+        Self {
+            tabs: vec![
+                Box::new(MapTab),
+                Box::new(CoverageTab),
+                Box::new(AirplanesTab),
+                Box::new(StatsTab),
+                Box::new(HelpTab),
+            ],
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.tabs.len()
+    }
+
+    pub fn get(&self, index: usize) -> &dyn Tab {
+        self.tabs[index].as_ref()
+    }
+
+    pub fn get_mut(&mut self, index: usize) -> &mut dyn Tab {
+        self.tabs[index].as_mut()
+    }
+
+    pub fn titles(&self, adsb_airplanes: &Airplanes) -> Vec<String> {
+        self.tabs.iter().map(|tab| tab.title(adsb_airplanes)).collect()
+    }
+}
+
+/// Index of the title in `titles` of the top bar at `column`, including the padding around it
+pub fn clicked_tab(titles: &[String], column: u16) -> Option<usize> {
+    // after the margin and border
+    let mut start = 2;
+    for (index, title) in titles.iter().enumerate() {
+        // padding on both sides
+        let end = start + title.chars().count() as u16 + 1;
+        if (start..=end).contains(&column) {
+            return Some(index);
+        }
+        // divider
+        start = end + 2;
+    }
+    None
+}
+
+/// Handle the keys moving and zooming the Map and Coverage tabs
+pub fn handle_map_key(action: Action, settings: &mut Settings) {
+    match action {
+        Action::ZoomOut => settings.scale_increase(),
+        Action::ZoomIn => settings.scale_decrease(),
+        Action::Up => settings.lat_increase(),
+        Action::Down => settings.lat_decrease(),
+        Action::Left => settings.long_increase(),
+        Action::Right => settings.long_decrease(),
+        Action::Enter => settings.reset(),
+        _ => (),
+    }
+}
+
+/// Handle the keys changing the altitude band of the Map and Airplanes tabs
+pub fn handle_altitude_key(action: Action, settings: &mut Settings) {
+    match action {
+        Action::FloorUp => settings.floor_increase(),
+        Action::FloorDown => settings.floor_decrease(),
+        Action::CeilingUp => settings.ceiling_increase(),
+        Action::CeilingDown => settings.ceiling_decrease(),
+        _ => (),
+    }
+}
+
+/// Move the Map and Coverage tabs drawn in `area` by dragging with the left button
+pub fn drag_map(mouse: MouseEvent, settings: &mut Settings, area: Rect) {
+    if mouse.kind != MouseEventKind::Drag(MouseButton::Left) {
+        return;
+    }
+
+    // check bounds, ignoring touchscreen controls and the Airplanes table of the split layout
+    if mouse.column < area.x || mouse.column >= area.right() {
+        return;
+    }
+
+    // if we have a previous mouse drag without a mouse lift, change the current position
+    if let Some((column, row)) = &settings.last_mouse_dragging {
+        let up = f64::from(i32::from(mouse.row).wrapping_sub(i32::from(*row))) * 0.020;
+        if let Some(lat) = &mut settings.custom_lat {
+            *lat += up;
+        } else {
+            settings.custom_lat = Some(settings.lat + up);
+        }
+
+        let left = f64::from(i32::from(mouse.column).wrapping_sub(i32::from(*column))) * 0.020;
+        if let Some(long) = &mut settings.custom_long {
+            *long -= left;
+        } else {
+            settings.custom_long = Some(settings.long - left);
+        }
+    }
+    settings.last_mouse_dragging = Some((mouse.column, mouse.row));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clicked_title() {
+        let titles: Vec<_> =
+            ["Map", "Coverage", "Airplanes(12)"].into_iter().map(String::from).collect();
+        assert_eq!(clicked_tab(&titles, 1), None);
+        assert_eq!(clicked_tab(&titles, 2), Some(0));
+        assert_eq!(clicked_tab(&titles, 6), Some(0));
+        // divider
+        assert_eq!(clicked_tab(&titles, 7), None);
+        assert_eq!(clicked_tab(&titles, 8), Some(1));
+        assert_eq!(clicked_tab(&titles, 17), Some(1));
+        assert_eq!(clicked_tab(&titles, 19), Some(2));
+        assert_eq!(clicked_tab(&titles, 33), Some(2));
+        assert_eq!(clicked_tab(&titles, 34), None);
+    }
+}
@@ -0,0 +1,154 @@
+//! Running the apps as a systemd service
+//!
+//! A [`Service`] notifies systemd with `sd_notify` when the app is ready (for `Type=notify`
+//! units), keeps the `WatchdogSec=` watchdog alive, and turns `SIGTERM` into a graceful
+//! shutdown checked by the main loop, such that files are saved before quitting. A second
+//! `SIGTERM` terminates immediately.
+//!
+//! Without systemd (no `NOTIFY_SOCKET`), the notifications are ignored.
+//!
+//! ```ini
+//! [Service]
+//! Type=notify
+//! WatchdogSec=30
+//! ExecStart=/usr/bin/1090 --host 127.0.0.1
+//! ```
+
+use std::io;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use tracing::{debug, info};
+
+/// Notifications sent by [`Service`]
+#[derive(Debug, Clone, Copy)]
+enum State {
+    Ready,
+    Watchdog,
+    Stopping,
+}
+
+/// Notifications and shutdown of the service, see [`self`]
+#[derive(Debug)]
+pub struct Service {
+    terminate: Arc<AtomicBool>,
+    /// Interval of the watchdog notifications, half of `WATCHDOG_USEC`
+    watchdog: Option<Duration>,
+    last_watchdog: Instant,
+}
+
+impl Service {
+    /// Catch `SIGTERM`, and read the watchdog interval
+    pub fn new() -> io::Result<Self> {
+        let terminate = Arc::new(AtomicBool::new(false));
+        #[allow(unused_mut)]
+        let mut watchdog = None;
+        #[cfg(unix)]
+        {
+            use signal_hook::consts::SIGTERM;
+            // the second SIGTERM, while the first one is being handled, terminates
+            signal_hook::flag::register_conditional_default(SIGTERM, Arc::clone(&terminate))?;
+            signal_hook::flag::register(SIGTERM, Arc::clone(&terminate))?;
+
+            let mut usec = 0;
+            if sd_notify::watchdog_enabled(false, &mut usec) {
+                watchdog = Some(Duration::from_micros(usec / 2));
+            }
+        }
+        debug!("service watchdog: {watchdog:?}");
+        Ok(Self { terminate, watchdog, last_watchdog: Instant::now() })
+    }
+
+    /// Notify that the app started, such as after connecting to the ADS-B server
+    pub fn ready(&self) {
+        info!("service ready");
+        self.notify(State::Ready);
+    }
+
+    /// Notify the watchdog, if due. Called from the main loop.
+    pub fn keep_alive(&mut self) {
+        if let Some(watchdog) = self.watchdog {
+            if self.last_watchdog.elapsed() >= watchdog {
+                self.last_watchdog = Instant::now();
+                self.notify(State::Watchdog);
+            }
+        }
+    }
+
+    /// A `SIGTERM` was received, the app should save its files and quit
+    pub fn terminated(&self) -> bool {
+        self.terminate.load(Ordering::Relaxed)
+    }
+
+    /// Notify that the app is quitting
+    pub fn stopping(&self) {
+        info!("service stopping");
+        self.notify(State::Stopping);
+    }
+
+    #[cfg(unix)]
+    fn notify(&self, state: State) {
+        use sd_notify::NotifyState;
+
+        let state = match state {
+            State::Ready => NotifyState::Ready,
+            State::Watchdog => NotifyState::Watchdog,
+            State::Stopping => NotifyState::Stopping,
+        };
+        if let Err(e) = sd_notify::notify(false, &[state]) {
+            debug!("sd_notify: {e}");
+        }
+    }
+
+    #[cfg(not(unix))]
+    fn notify(&self, _state: State) {}
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use std::os::unix::net::UnixDatagram;
+    use std::process::Command;
+
+    use super::*;
+
+    /// Set for the test process spawned by [`notify_and_terminate`]
+    const CHILD: &str = "RSADSB_SERVICE_CHILD";
+
+    /// Run [`service_child`] in its own process, such that `NOTIFY_SOCKET` and `SIGTERM` don't
+    /// reach the other tests
+    #[test]
+    fn notify_and_terminate() {
+        let path = std::env::temp_dir().join(format!("rsadsb-notify-{}.sock", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let systemd = UnixDatagram::bind(&path).unwrap();
+
+        let status = Command::new(std::env::current_exe().unwrap())
+            .args(["--exact", "service::tests::service_child", "--nocapture"])
+            .env(CHILD, "1")
+            .env("NOTIFY_SOCKET", &path)
+            .status()
+            .unwrap();
+        assert!(status.success());
+
+        let mut buf = [0; 64];
+        let len = systemd.recv(&mut buf).unwrap();
+        assert_eq!(&buf[..len], b"READY=1\n");
+        let len = systemd.recv(&mut buf).unwrap();
+        assert_eq!(&buf[..len], b"STOPPING=1\n");
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn service_child() {
+        if std::env::var_os(CHILD).is_none() {
+            return;
+        }
+        let service = Service::new().unwrap();
+        service.ready();
+        assert!(!service.terminated());
+        signal_hook::low_level::raise(signal_hook::consts::SIGTERM).unwrap();
+        assert!(service.terminated());
+        service.stopping();
+    }
+}
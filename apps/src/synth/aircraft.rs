@@ -0,0 +1,247 @@
+//! Simulated aircraft, flying great-circle routes between random points around the receiver
+
+use std::ops::Range;
+
+use adsb_deku::cpr::Position;
+use adsb_deku::{CPRFormat, ICAO};
+use rsadsb_common::geo;
+
+use crate::encode;
+
+/// Kilometers per hour in a knot
+const KT_TO_KMH: f64 = 1.852;
+
+/// Seconds between airborne positions, alternating even and odd (DO-260B 2.2.3.3.1.1)
+const POSITION_INTERVAL: f64 = 0.5;
+
+/// Seconds between airborne velocities
+const VELOCITY_INTERVAL: f64 = 0.5;
+
+/// Seconds between identifications
+const IDENTIFICATION_INTERVAL: f64 = 5.0;
+
+/// Destinations are reached within this distance (km)
+const ARRIVAL_DISTANCE: f64 = 2.0;
+
+/// Climb and descent rate (ft/min)
+const CLIMB_RATE: f64 = 1500.0;
+
+/// Max turn rate (degrees/second), a standard rate turn
+const TURN_RATE: f64 = 3.0;
+
+const AIRLINES: [&str; 10] = ["AAL", "DAL", "UAL", "SWA", "JBU", "KLM", "BAW", "DLH", "AFR", "ACA"];
+
+/// xorshift64* pseudo-random numbers, for repeatable traffic with `--seed`
+#[derive(Debug, Clone)]
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        // the state must not be 0
+        Self(seed | 1)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    /// Uniform in `range`
+    pub fn range(&mut self, range: Range<f64>) -> f64 {
+        let unit = (self.next_u64() >> 11) as f64 / (1_u64 << 53) as f64;
+        range.start + unit * (range.end - range.start)
+    }
+
+    /// Uniform point within `radius` (km) of `center`
+    fn point(&mut self, center: (f64, f64), radius: f64) -> (f64, f64) {
+        let bearing = self.range(0.0..360.0);
+        let distance = radius * self.range(0.0..1.0).sqrt();
+        geo::destination(center, bearing, distance)
+    }
+}
+
+/// Area of the simulated traffic
+#[derive(Debug, Clone, Copy)]
+pub struct Area {
+    /// (latitude, longitude)
+    pub center: (f64, f64),
+    /// km
+    pub radius: f64,
+}
+
+#[derive(Debug, Clone)]
+pub struct SimAircraft {
+    pub icao: ICAO,
+    pub callsign: String,
+    /// emitter category of set A
+    pub category: u8,
+    /// (latitude, longitude)
+    pub position: (f64, f64),
+    /// ft
+    pub altitude: f64,
+    /// cruise altitude (ft) until the destination
+    pub target_altitude: f64,
+    /// ground speed (kt)
+    pub speed: f64,
+    /// degrees
+    pub heading: f64,
+    pub destination: (f64, f64),
+    /// format of the next airborne position
+    format: CPRFormat,
+    /// seconds until the next airborne position, velocity and identification
+    next: [f64; 3],
+}
+
+impl SimAircraft {
+    /// Aircraft at a random point of `area`, flying to another
+    pub fn random(rng: &mut Rng, area: Area) -> Self {
+        let icao = ICAO::from(rng.next_u64() as u32);
+        let airline = AIRLINES[rng.next_u64() as usize % AIRLINES.len()];
+        let callsign = format!("{airline}{}", rng.next_u64() % 9999 + 1);
+        let position = rng.point(area.center, area.radius);
+        let destination = rng.point(area.center, area.radius);
+        let altitude = (rng.range(3000.0..39000.0) / 100.0).round() * 100.0;
+        Self {
+            icao,
+            callsign,
+            // small, large or heavy aircraft
+            category: [1, 3, 5][rng.next_u64() as usize % 3],
+            position,
+            altitude,
+            target_altitude: altitude,
+            speed: rng.range(250.0..480.0).round(),
+            heading: geo::bearing(position, destination),
+            destination,
+            format: CPRFormat::Even,
+            // spread the frames of all aircraft in time
+            next: [
+                rng.range(0.0..POSITION_INTERVAL),
+                rng.range(0.0..VELOCITY_INTERVAL),
+                rng.range(0.0..IDENTIFICATION_INTERVAL),
+            ],
+        }
+    }
+
+    /// Vertical rate (ft/min)
+    pub fn vert_rate(&self) -> i32 {
+        let diff = self.target_altitude - self.altitude;
+        if diff.abs() < 1.0 {
+            0
+        } else {
+            CLIMB_RATE.copysign(diff) as i32
+        }
+    }
+
+    /// Fly for `dt` seconds, turning and climbing towards the destination
+    pub fn step(&mut self, dt: f64, rng: &mut Rng, area: Area) {
+        if geo::distance(self.position, self.destination) < ARRIVAL_DISTANCE {
+            self.destination = rng.point(area.center, area.radius);
+            self.target_altitude = (rng.range(3000.0..39000.0) / 100.0).round() * 100.0;
+        }
+
+        // shortest turn towards the great-circle bearing
+        let bearing = geo::bearing(self.position, self.destination);
+        let turn = (bearing - self.heading + 540.0) % 360.0 - 180.0;
+        let max_turn = TURN_RATE * dt;
+        self.heading = (self.heading + turn.clamp(-max_turn, max_turn)).rem_euclid(360.0);
+
+        let climb = f64::from(self.vert_rate()) / 60.0 * dt;
+        self.altitude += climb;
+        if (self.target_altitude - self.altitude) * climb.signum() < 0.0 {
+            self.altitude = self.target_altitude;
+        }
+
+        let distance = self.speed * KT_TO_KMH / 3600.0 * dt;
+        self.position = geo::destination(self.position, self.heading, distance);
+    }
+
+    /// Frames due in the next `dt` seconds, with each interval divided by `rate`
+    pub fn frames(&mut self, dt: f64, rate: f64) -> Vec<Vec<u8>> {
+        let intervals = [POSITION_INTERVAL, VELOCITY_INTERVAL, IDENTIFICATION_INTERVAL];
+        let mut frames = vec![];
+        for (kind, interval) in intervals.into_iter().enumerate() {
+            self.next[kind] -= dt;
+            while self.next[kind] <= 0.0 {
+                self.next[kind] += interval / rate;
+                frames.push(self.frame(kind));
+            }
+        }
+        frames
+    }
+
+    fn frame(&mut self, kind: usize) -> Vec<u8> {
+        match kind {
+            0 => {
+                let (latitude, longitude) = self.position;
+                let position = Position { latitude, longitude };
+                let format = self.format;
+                self.format = match format {
+                    CPRFormat::Even => CPRFormat::Odd,
+                    CPRFormat::Odd => CPRFormat::Even,
+                };
+                encode::airborne_position(self.icao, position, self.altitude as i32, format)
+            }
+            1 => encode::airborne_velocity(self.icao, self.heading, self.speed, self.vert_rate()),
+            _ => encode::identification(self.icao, &self.callsign, self.category),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use adsb_deku::Frame;
+    use rsadsb_common::Airplanes;
+
+    use super::*;
+
+    const AREA: Area = Area { center: (35.0, -80.0), radius: 100.0 };
+
+    #[test]
+    fn tracked() {
+        let mut rng = Rng::new(1);
+        let mut aircraft: Vec<_> = (0..5).map(|_| SimAircraft::random(&mut rng, AREA)).collect();
+        let mut airplanes = Airplanes::builder().position(AREA.center).build();
+
+        for _ in 0..100 {
+            for a in &mut aircraft {
+                a.step(0.1, &mut rng, AREA);
+                for bytes in a.frames(0.1, 1.0) {
+                    airplanes.action(Frame::from_bytes(&bytes).unwrap());
+                }
+            }
+        }
+
+        assert_eq!(airplanes.len(), 5);
+        for a in &aircraft {
+            let state = airplanes.get(a.icao).unwrap();
+            assert_eq!(state.callsign.as_deref(), Some(a.callsign.as_str()));
+            // 2 positions and velocities per second, and an identification every 5 seconds
+            assert!(state.num_messages >= 41, "{}", state.num_messages);
+            let position = state.coords.position.unwrap();
+            let error = geo::distance((position.latitude, position.longitude), a.position);
+            // moved since the last position
+            assert!(error < 1.0, "{error}");
+            let details = airplanes.aircraft_details(a.icao).unwrap();
+            assert!((f64::from(details.altitude) - a.altitude).abs() <= 25.0);
+        }
+    }
+
+    #[test]
+    fn flies_to_destination() {
+        let mut rng = Rng::new(2);
+        let mut a = SimAircraft::random(&mut rng, AREA);
+        a.target_altitude = a.altitude + 3000.0;
+        a.destination = geo::destination(a.position, 0.0, 50.0);
+        a.heading = 0.0;
+        let start = geo::distance(a.position, a.destination);
+        for _ in 0..60 {
+            a.step(1.0, &mut rng, AREA);
+        }
+        assert!(geo::distance(a.position, a.destination) < start);
+        assert!(geo::distance(AREA.center, a.position) < AREA.radius + 10.0);
+        assert_eq!(a.vert_rate(), 1500);
+        assert!((a.target_altitude - a.altitude - 1500.0).abs() < 1.0);
+    }
+}
@@ -0,0 +1,68 @@
+//! Playback of a recorded AVR file at a fixed rate, for load testing with real traffic
+
+use std::fs;
+
+use adsb_deku::avr::{self, AvrFrame};
+use anyhow::{bail, Context};
+
+/// Frames of a recorded file, sent in a loop
+#[derive(Debug)]
+pub struct Capture {
+    frames: Vec<AvrFrame>,
+    /// index of the next frame to send
+    next: usize,
+    /// frames owed since the last call to [`Self::frames`], below 1
+    owed: f64,
+}
+
+impl Capture {
+    /// Read the frames of the AVR file at `path`, skipping the lines that aren't frames
+    pub fn open(path: &str) -> anyhow::Result<Self> {
+        let text = fs::read_to_string(path).with_context(|| format!("unable to read: {path}"))?;
+        Self::parse(&text).with_context(|| format!("no AVR frames in: {path}"))
+    }
+
+    fn parse(text: &str) -> anyhow::Result<Self> {
+        let frames: Vec<_> = text.lines().filter_map(|line| avr::parse_line(line).ok()?).collect();
+        if frames.is_empty() {
+            bail!("empty capture");
+        }
+        Ok(Self { frames, next: 0, owed: 0.0 })
+    }
+
+    /// Frames due in the next `dt` seconds at `rate` frames/s, from the start again after the
+    /// last frame
+    pub fn frames(&mut self, dt: f64, rate: f64) -> Vec<AvrFrame> {
+        self.owed += dt * rate;
+        let due = self.owed as usize;
+        self.owed -= due as f64;
+        (0..due)
+            .map(|_| {
+                let frame = self.frames[self.next];
+                self.next = (self.next + 1) % self.frames.len();
+                frame
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rate() {
+        let text = "# comment\n*8D40621D58C382D690C8AC2863A7;\ngarbage\n*5dab3d17d4ba29;\n";
+        let mut capture = Capture::parse(text).unwrap();
+        assert_eq!(capture.frames.len(), 2);
+
+        // 2.5 frames in each call
+        let frames: Vec<_> = (0..4).flat_map(|_| capture.frames(0.05, 50.0)).collect();
+        assert_eq!(frames.len(), 10);
+        assert_eq!(frames[0].bytes().len(), 14);
+        assert_eq!(frames[1].bytes().len(), 7);
+        assert_eq!(frames[2], frames[0]);
+
+        assert!(Capture::parse("garbage\n").is_err());
+    }
+}
@@ -0,0 +1,180 @@
+//! Encoding of DF17 extended squitters with [`Frame::to_bytes`], and of the AVR and Beast output
+//! formats
+
+use adsb_deku::adsb::{
+    AirborneVelocity, AirborneVelocitySubType, GroundSpeedDecoding, Identification, TypeCoding,
+    VerticalRateSource, ADSB, ME,
+};
+use adsb_deku::beast::ESCAPE;
+use adsb_deku::cpr::{self, Position};
+use adsb_deku::{
+    encode_callsign, Altitude, CPRFormat, Capability, Frame, Sign, SurveillanceStatus, DF, ICAO,
+};
+
+/// Length (bytes) of a Mode S long frame
+pub const LONG_FRAME_BYTES: usize = 14;
+
+/// Signal level of Beast messages
+const SIGNAL_LEVEL: u8 = 0x80;
+
+/// DF17 frame of `icao` with `me`, and the parity
+fn extended_squitter(icao: ICAO, me: ME) -> Vec<u8> {
+    // level 2 transponder, airborne
+    let adsb = ADSB { capability: Capability::AG_AIRBORNE, icao, me, pi: ICAO([0; 3]) };
+    // PANIC: the fields are in range, such as the altitude in 25 ft increments
+    Frame::new(DF::ADSB(adsb), 0).to_bytes().unwrap()
+}
+
+/// Sign bit of a west, south or descending velocity if `negative`
+fn sign(negative: bool) -> Sign {
+    if negative {
+        Sign::Negative
+    } else {
+        Sign::Positive
+    }
+}
+
+/// `ME::AircraftIdentification`, with the emitter `category` of set A
+///
+/// The `callsign` is made of `A-Z`, `0-9` and spaces, in any case.
+pub fn identification(icao: ICAO, callsign: &str, category: u8) -> Vec<u8> {
+    let callsign = callsign.to_ascii_uppercase();
+    // PANIC: the callsigns of the simulated aircraft are valid
+    let raw = encode_callsign(&callsign).unwrap();
+    let identification = Identification { tc: TypeCoding::A, ca: category, raw, cn: callsign };
+    extended_squitter(icao, ME::AircraftIdentification(identification))
+}
+
+/// `ME::AirbornePositionBaroAltitude`, with the `altitude` (ft) in 25 ft increments
+pub fn airborne_position(
+    icao: ICAO,
+    position: Position,
+    altitude: i32,
+    format: CPRFormat,
+) -> Vec<u8> {
+    let n = ((altitude + 1000) / 25).clamp(0, 0x7ff);
+    let (lat_cpr, lon_cpr) = cpr::encode(position, format);
+    let altitude = Altitude {
+        tc: 11,
+        ss: SurveillanceStatus::NoCondition,
+        saf_or_imf: 0,
+        alt: Some(n * 25 - 1000),
+        t: false,
+        odd_flag: format,
+        lat_cpr,
+        lon_cpr,
+    };
+    extended_squitter(icao, ME::AirbornePositionBaroAltitude(altitude))
+}
+
+/// `ME::AirborneVelocity` ground speed subtype, from the `heading` (degrees), ground `speed`
+/// (kt) and barometric `vert_rate` (ft/min)
+pub fn airborne_velocity(icao: ICAO, heading: f64, speed: f64, vert_rate: i32) -> Vec<u8> {
+    let v_ew = speed * heading.to_radians().sin();
+    let v_ns = speed * heading.to_radians().cos();
+    let ground_speed = GroundSpeedDecoding {
+        ew_sign: sign(v_ew < 0.0),
+        ew_vel: (v_ew.abs().round() as u16 + 1).min(1023),
+        ns_sign: sign(v_ns < 0.0),
+        ns_vel: (v_ns.abs().round() as u16 + 1).min(1023),
+    };
+    let velocity = AirborneVelocity {
+        // subsonic ground speed
+        st: 1,
+        // intent change, IFR capability, NACv 1
+        nac_v: 1,
+        sub_type: AirborneVelocitySubType::GroundSpeedDecoding(ground_speed),
+        vrate_src: VerticalRateSource::BarometricPressureAltitude,
+        vrate_sign: sign(vert_rate < 0),
+        vrate_value: (vert_rate.unsigned_abs() / 64 + 1).min(511) as u16,
+        reverved: 0,
+        gnss_sign: Sign::Positive,
+        // unknown GNSS/baro altitude difference
+        gnss_baro_diff: 0,
+    };
+    extended_squitter(icao, ME::AirborneVelocity(velocity))
+}
+
+/// AVR line of `bytes`, as sent by dump1090 on port 30002
+pub fn avr(bytes: &[u8]) -> String {
+    format!("*{};\n", hex::encode_upper(bytes))
+}
+
+/// Beast message of the short or long frame `bytes`, as sent by dump1090 on port 30005
+///
+/// `timestamp` is a 48 bit counter of a 12 MHz clock.
+pub fn beast(bytes: &[u8], timestamp: u64) -> Vec<u8> {
+    let kind = if bytes.len() == LONG_FRAME_BYTES { b'3' } else { b'2' };
+    let mut message = vec![ESCAPE, kind];
+    let timestamp = timestamp.to_be_bytes();
+    let data = timestamp[2..].iter().chain(&[SIGNAL_LEVEL]).chain(bytes);
+    for byte in data {
+        message.push(*byte);
+        if *byte == ESCAPE {
+            message.push(ESCAPE);
+        }
+    }
+    message
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ICAO_TEST: ICAO = ICAO([0x40, 0x62, 0x1d]);
+
+    fn me(bytes: &[u8]) -> ME {
+        let frame = Frame::from_bytes(bytes).unwrap();
+        assert_eq!(frame.crc, 0);
+        let DF::ADSB(adsb) = frame.df else { panic!("{frame:?}") };
+        assert_eq!(adsb.icao, ICAO_TEST);
+        adsb.me
+    }
+
+    #[test]
+    fn known_frames() {
+        // from the adsb_deku tests and pyModeS
+        let position = Position { latitude: 52.257_202_148_437_5, longitude: 3.919_372_558_593_75 };
+        let bytes = airborne_position(ICAO_TEST, position, 38000, CPRFormat::Even);
+        assert_eq!(hex::encode(bytes), "8d40621d58c382d690c8ac2863a7");
+
+        let bytes = identification(ICAO([0x48, 0x40, 0xd6]), "KLM1023", 0);
+        assert_eq!(hex::encode(bytes), "8d4840d6202cc371c32ce0576098");
+    }
+
+    #[test]
+    fn decode() {
+        let ME::AircraftIdentification(identification) = me(&identification(ICAO_TEST, "dal12", 3))
+        else {
+            panic!()
+        };
+        assert_eq!(identification.cn, "DAL12");
+        assert_eq!(identification.ca, 3);
+
+        let ME::AirborneVelocity(velocity) = me(&airborne_velocity(ICAO_TEST, 225.0, 400.0, -1280))
+        else {
+            panic!()
+        };
+        let (heading, speed, vert_rate) = velocity.calculate().unwrap();
+        assert!((heading - 225.0).abs() < 0.5, "{heading}");
+        assert!((speed - 400.0).abs() < 1.0, "{speed}");
+        assert_eq!(vert_rate, -1280);
+    }
+
+    #[test]
+    fn output_formats() {
+        let bytes = identification(ICAO([0x48, 0x40, 0xd6]), "KLM1023", 0);
+        assert_eq!(avr(&bytes), "*8D4840D6202CC371C32CE0576098;\n");
+
+        let message = beast(&bytes, 0x1a);
+        assert_eq!(message[..2], [ESCAPE, b'3']);
+        // escaped timestamp
+        assert_eq!(message[2..10], [0, 0, 0, 0, 0, ESCAPE, ESCAPE, SIGNAL_LEVEL]);
+        assert_eq!(message[10..], bytes);
+
+        let short = [0x5d, 0xab, 0x3d, 0x17, 0xd4, 0xba, 0x29];
+        let message = beast(&short, 0);
+        assert_eq!(message[..2], [ESCAPE, b'2']);
+        assert_eq!(message[9..], short);
+    }
+}
@@ -0,0 +1,193 @@
+mod aircraft;
+mod capture;
+mod encode;
+
+use std::io::Write;
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use anyhow::Context;
+use clap::Parser;
+use rsadsb_apps::service::Service;
+
+use crate::aircraft::{Area, Rng, SimAircraft};
+use crate::capture::Capture;
+
+/// Time between each update of the aircraft
+const TICK: Duration = Duration::from_millis(50);
+
+/// Longest time a client can block the sending of frames
+const WRITE_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Time between each print of the frames sent
+const REPORT_INTERVAL: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Parser)]
+#[command(
+    name = "synth",
+    version,
+    author = "wcampbell0x2a",
+    about = "Serve synthetic ADS-B traffic for demos and load testing"
+)]
+struct Options {
+    /// ip address to listen on
+    #[arg(long, default_value = "127.0.0.1")]
+    host: String,
+    /// port of the AVR server, same as dump1090
+    #[arg(long, default_value = "30002")]
+    avr_port: u16,
+    /// port of the Beast binary server, same as dump1090
+    #[arg(long, default_value = "30005")]
+    beast_port: u16,
+    /// Latitude of the center of the traffic, such as the `--lat` of radar
+    #[arg(long, required_unless_present = "replay")]
+    lat: Option<f64>,
+    /// Longitude of the center of the traffic, such as the `--long` of radar
+    #[arg(long, required_unless_present = "replay")]
+    long: Option<f64>,
+    /// Amount of simulated aircraft
+    #[arg(long, default_value = "20")]
+    aircraft: usize,
+    /// Radius (km) of the traffic around the center
+    #[arg(long, default_value = "150")]
+    radius: f64,
+    /// Multiply the messages sent by each aircraft, for load testing
+    #[arg(long, default_value = "1")]
+    rate: f64,
+    /// Seed of the traffic, for the same aircraft and routes in each run
+    #[arg(long)]
+    seed: Option<u64>,
+    /// Send the frames of a recorded AVR file in a loop instead of simulated aircraft
+    #[arg(long)]
+    replay: Option<String>,
+    /// Frames sent per second with `--replay`
+    #[arg(long, default_value = "3000")]
+    replay_rate: f64,
+}
+
+/// Source of the frames sent
+enum Traffic {
+    Simulated { rng: Rng, area: Area, aircraft: Vec<SimAircraft>, rate: f64 },
+    Replay { capture: Capture, rate: f64 },
+}
+
+impl Traffic {
+    fn new(options: &Options) -> anyhow::Result<Self> {
+        if let Some(replay) = &options.replay {
+            let capture = Capture::open(replay)?;
+            return Ok(Self::Replay { capture, rate: options.replay_rate });
+        }
+        // required by clap without --replay
+        let (Some(lat), Some(long)) = (options.lat, options.long) else {
+            unreachable!();
+        };
+        let area = Area { center: (lat, long), radius: options.radius };
+        let seed = options.seed.unwrap_or_else(|| {
+            SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_nanos() as u64
+        });
+        let mut rng = Rng::new(seed);
+        let aircraft = (0..options.aircraft).map(|_| SimAircraft::random(&mut rng, area)).collect();
+        Ok(Self::Simulated { rng, area, aircraft, rate: options.rate })
+    }
+
+    /// Frames of the next `dt` seconds
+    fn frames(&mut self, dt: f64) -> Vec<Vec<u8>> {
+        match self {
+            Self::Simulated { rng, area, aircraft, rate } => {
+                let mut frames = vec![];
+                for a in aircraft {
+                    a.step(dt, rng, *area);
+                    frames.extend(a.frames(dt, *rate));
+                }
+                frames
+            }
+            Self::Replay { capture, rate } => {
+                capture.frames(dt, *rate).iter().map(|avr| avr.bytes().to_vec()).collect()
+            }
+        }
+    }
+}
+
+/// Connected clients of a server
+type Clients = Arc<Mutex<Vec<TcpStream>>>;
+
+/// Accept clients on `port` from a new thread
+fn listen(host: &str, port: u16) -> anyhow::Result<Clients> {
+    let listener = TcpListener::bind((host, port))
+        .with_context(|| format!("unable to listen on {host}:{port}"))?;
+    println!("listening on {}", listener.local_addr()?);
+    let clients = Clients::default();
+    let accepted = Arc::clone(&clients);
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            if let Ok(address) = stream.peer_addr() {
+                println!("{address} connected");
+            }
+            if stream.set_write_timeout(Some(WRITE_TIMEOUT)).is_ok() {
+                accepted.lock().unwrap().push(stream);
+            }
+        }
+    });
+    Ok(clients)
+}
+
+/// Send `data` to all `clients`, dropping the disconnected ones
+fn send(clients: &Clients, data: &[u8]) {
+    if data.is_empty() {
+        return;
+    }
+    clients.lock().unwrap().retain_mut(|stream| {
+        let sent = stream.write_all(data).is_ok();
+        if !sent {
+            if let Ok(address) = stream.peer_addr() {
+                println!("{address} disconnected");
+            }
+        }
+        sent
+    });
+}
+
+fn main() -> anyhow::Result<()> {
+    let options = Options::parse();
+    let mut traffic = Traffic::new(&options)?;
+
+    let avr_clients = listen(&options.host, options.avr_port)?;
+    let beast_clients = listen(&options.host, options.beast_port)?;
+    let mut service = Service::new()?;
+    service.ready();
+
+    let start = Instant::now();
+    let mut last = start;
+    let mut report = (start, 0);
+    while !service.terminated() {
+        service.keep_alive();
+        thread::sleep(TICK);
+        let now = Instant::now();
+        let dt = now.duration_since(last).as_secs_f64();
+        last = now;
+        // 12 MHz clock of the Beast timestamp
+        let timestamp = (now.duration_since(start).as_nanos() * 12 / 1000) as u64;
+
+        let mut avr = String::new();
+        let mut beast = vec![];
+        let frames = traffic.frames(dt);
+        for bytes in &frames {
+            avr.push_str(&encode::avr(bytes));
+            beast.extend(encode::beast(bytes, timestamp));
+        }
+        send(&avr_clients, avr.as_bytes());
+        send(&beast_clients, &beast);
+
+        report.1 += frames.len();
+        let elapsed = now.duration_since(report.0);
+        if elapsed >= REPORT_INTERVAL {
+            println!("sent {:.0} frames/s", report.1 as f64 / elapsed.as_secs_f64());
+            report = (now, 0);
+        }
+    }
+
+    service.stopping();
+    Ok(())
+}
@@ -1,4 +1,4 @@
-use adsb_deku::cpr::get_position;
+use adsb_deku::cpr::{get_position, nl};
 use adsb_deku::{Altitude, CPRFormat, Frame};
 use criterion::{criterion_group, criterion_main, Criterion};
 
@@ -50,9 +50,38 @@ fn lax_message() {
     }
 }
 
+/// NL(lat) from the formula in ICAO 9871 (D.2.4.7.2), for comparison against the table in `nl`
+fn nl_formula(lat: f64) -> u64 {
+    if lat == 0.0 {
+        return 59;
+    }
+    if lat.abs() == 87.0 {
+        return 2;
+    }
+    if lat.abs() > 87.0 {
+        return 1;
+    }
+    let nz = 15.0;
+    let a = 1.0 - (std::f64::consts::PI / (2.0 * nz)).cos();
+    let b = (std::f64::consts::PI / 180.0 * lat.abs()).cos().powi(2);
+    (2.0 * std::f64::consts::PI / (1.0 - a / b).acos()).floor() as u64
+}
+
+fn b_nl(f: fn(f64) -> u64) -> u64 {
+    let mut sum = 0;
+    let mut lat = -90.0;
+    while lat <= 90.0 {
+        sum += f(criterion::black_box(lat));
+        lat += 0.5;
+    }
+    sum
+}
+
 fn criterion_benchmark(c: &mut Criterion) {
     c.bench_function("lax_messsages", |b| b.iter(lax_message));
     c.bench_function("get_position", |b| b.iter(b_get_position));
+    c.bench_function("nl_table", |b| b.iter(|| b_nl(nl)));
+    c.bench_function("nl_formula", |b| b.iter(|| b_nl(nl_formula)));
 }
 
 criterion_group!(benches, criterion_benchmark);
@@ -0,0 +1,18 @@
+#![no_main]
+use std::mem::discriminant;
+
+use adsb_deku::deku::no_std_io::Cursor;
+use adsb_deku::Frame;
+use libfuzzer_sys::fuzz_target;
+
+// Frame::from_slice and Frame::from_reader must agree on every input
+fuzz_target!(|data: &[u8]| {
+    match (Frame::from_slice(data), Frame::from_reader(Cursor::new(data))) {
+        (Ok(slice), Ok(reader)) => {
+            assert_eq!(slice, reader);
+            assert_eq!(slice.raw(), reader.raw());
+        }
+        (Err(slice), Err(reader)) => assert_eq!(discriminant(&slice), discriminant(&reader)),
+        (slice, reader) => panic!("{slice:?} != {reader:?}"),
+    }
+});
@@ -1,10 +1,9 @@
 #![no_main]
 use libfuzzer_sys::fuzz_target;
-use adsb_deku::{Frame, DF};
-use adsb_deku::deku::{DekuContainerRead};
+use adsb_deku::Frame;
 
 fuzz_target!(|data: &[u8]| {
-    if let Ok((_, frame)) = Frame::from_bytes((&data, 0)) {
+    if let Ok(frame) = Frame::from_bytes(data) {
         println!("{}", frame);
         println!("{:?}", frame);
     }
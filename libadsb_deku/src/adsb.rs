@@ -4,7 +4,7 @@
 //! [`DF::TisB`]: crate::DF::TisB
 
 #[cfg(feature = "alloc")]
-use alloc::{fmt, format, string::String};
+use alloc::{fmt, format, string::String, vec::Vec};
 #[cfg(feature = "alloc")]
 use core::{
     clone::Clone,
@@ -23,15 +23,19 @@ use core::{
 #[cfg(not(feature = "alloc"))]
 use std::{fmt, i64};
 
-use deku::no_std_io::{Read, Seek};
+use deku::ctx::{BitSize, Endian};
+use deku::no_std_io::{self, Read, Seek};
 use deku::prelude::*;
 
-use crate::mode_ac::decode_id13_field;
-use crate::{aircraft_identification_read, Altitude, CPRFormat, Capability, Sign, ICAO};
+use crate::mode_ac::{decode_id13_field, encode_id13_field};
+use crate::{
+    baro_altitude, decode_callsign, Altitude, CPRFormat, Capability, Sign, SurveillanceStatus, ICAO,
+};
 
 /// [`crate::DF::ADSB`] || [`crate::DF::TisB`]
-#[derive(Debug, PartialEq, DekuRead, Clone)]
+#[derive(Debug, PartialEq, DekuRead, DekuWrite, Clone)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct ADSB {
     /// Transponder Capability
     pub capability: Capability,
@@ -45,18 +49,21 @@ pub struct ADSB {
 
 impl ADSB {
     /// `to_string` with DF.id() input
-    pub(crate) fn to_string(&self, address_type: &str) -> result::Result<String, Error> {
-        let mut f = String::new();
-        write!(f, "{}", self.me.to_string(self.icao, address_type, self.capability, true)?)?;
-        Ok(f)
+    pub(crate) fn to_string(
+        &self,
+        address_type: &str,
+        transition_altitude: Option<i32>,
+    ) -> result::Result<String, Error> {
+        self.me.to_string(self.icao, address_type, self.capability, true, transition_altitude)
     }
 }
 
 /// ADS-B Message, 5 first bits are known as Type Code (TC)
 ///
 /// reference: ICAO 9871 (A.2.3.1)
-#[derive(Debug, PartialEq, DekuRead, Clone)]
+#[derive(Debug, PartialEq, DekuRead, DekuWrite, Clone)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 #[deku(id_type = "u8", bits = "5")]
 pub enum ME {
     #[deku(id_pat = "9..=18")]
@@ -65,8 +72,9 @@ pub enum ME {
     #[deku(id = "19")]
     AirborneVelocity(AirborneVelocity),
 
-    #[deku(id = "0")]
-    NoPosition([u8; 6]),
+    /// Barometric altitude without a position, with the fields of an airborne position
+    #[deku(id_pat = "0")]
+    NoPosition(Altitude),
 
     #[deku(id_pat = "1..=4")]
     AircraftIdentification(Identification),
@@ -77,14 +85,17 @@ pub enum ME {
     #[deku(id_pat = "20..=22")]
     AirbornePositionGNSSAltitude(Altitude),
 
-    #[deku(id = "23")]
-    Reserved0([u8; 6]),
+    /// The 7 bytes of the message, including the type code
+    #[deku(id_pat = "23")]
+    Reserved0([u8; 7]),
 
+    /// The 7 bytes of the message, including the type code
     #[deku(id_pat = "24")]
-    SurfaceSystemStatus([u8; 6]),
+    SurfaceSystemStatus([u8; 7]),
 
+    /// The 7 bytes of the message, including the type code
     #[deku(id_pat = "25..=27")]
-    Reserved1([u8; 6]),
+    Reserved1([u8; 7]),
 
     #[deku(id = "28")]
     AircraftStatus(AircraftStatus),
@@ -92,8 +103,9 @@ pub enum ME {
     #[deku(id = "29")]
     TargetStateAndStatusInformation(TargetStateAndStatusInformation),
 
-    #[deku(id = "30")]
-    AircraftOperationalCoordination([u8; 6]),
+    /// The 7 bytes of the message, including the type code
+    #[deku(id_pat = "30")]
+    AircraftOperationalCoordination([u8; 7]),
 
     #[deku(id = "31")]
     AircraftOperationStatus(OperationStatus),
@@ -107,6 +119,7 @@ impl ME {
         address_type: &str,
         capability: Capability,
         is_transponder: bool,
+        transition_altitude: Option<i32>,
     ) -> result::Result<String, Error> {
         let transponder = match is_transponder {
             true => " ",
@@ -114,12 +127,22 @@ impl ME {
         };
         let mut f = String::new();
         match self {
-            ME::NoPosition(_) => {
+            ME::NoPosition(altitude) => {
                 writeln!(f, " Extended Squitter{transponder}No position information")?;
                 writeln!(f, "  Address:       {icao} {address_type}")?;
                 writeln!(f, "  Air/Ground:    {capability}")?;
+                if let Some(alt) = altitude.alt {
+                    writeln!(
+                        f,
+                        "  Altitude:      {} barometric",
+                        baro_altitude(alt, transition_altitude)
+                    )?;
+                }
+                if altitude.ss != SurveillanceStatus::NoCondition {
+                    writeln!(f, "  Surveillance:  {}", altitude.ss)?;
+                }
             }
-            ME::AircraftIdentification(Identification { tc, ca, cn }) => {
+            ME::AircraftIdentification(Identification { tc, ca, cn, .. }) => {
                 writeln!(f, " Extended Squitter{transponder}Aircraft identification and category")?;
                 writeln!(f, "  Address:       {icao} {address_type}")?;
                 writeln!(f, "  Air/Ground:    {capability}")?;
@@ -137,7 +160,7 @@ impl ME {
                 )?;
                 writeln!(f, "  Address:       {icao} {address_type}")?;
                 writeln!(f, "  Air/Ground:    {capability}")?;
-                write!(f, "{altitude}")?;
+                altitude.write_fields(&mut f, transition_altitude)?;
             }
             ME::AirborneVelocity(airborne_velocity) => match &airborne_velocity.sub_type {
                 AirborneVelocitySubType::GroundSpeedDecoding(_) => {
@@ -283,8 +306,9 @@ impl ME {
 }
 
 /// [`ME::AirborneVelocity`] && [`AirborneVelocitySubType::GroundSpeedDecoding`]
-#[derive(Debug, PartialEq, Eq, DekuRead, Copy, Clone)]
+#[derive(Debug, PartialEq, Eq, DekuRead, DekuWrite, Copy, Clone)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct GroundSpeedDecoding {
     pub ew_sign: Sign,
     #[deku(endian = "big", bits = "10")]
@@ -295,8 +319,9 @@ pub struct GroundSpeedDecoding {
 }
 
 /// [`ME::AirborneVelocity`] && [`AirborneVelocitySubType::AirspeedDecoding`]
-#[derive(Debug, PartialEq, Eq, DekuRead, Clone)]
+#[derive(Debug, PartialEq, Eq, DekuRead, DekuWrite, Clone)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct AirspeedDecoding {
     #[deku(bits = "1")]
     pub status_heading: u8,
@@ -307,31 +332,69 @@ pub struct AirspeedDecoding {
     #[deku(
         endian = "big",
         bits = "10",
-        map = "|airspeed: u16| -> result::Result<_, DekuError> {Ok(if airspeed > 0 { airspeed - 1 } else { 0 })}"
+        map = "|airspeed: u16| -> result::Result<_, DekuError> {Ok(if airspeed > 0 { airspeed - 1 } else { 0 })}",
+        writer = "(airspeed + 1).to_writer(deku::writer, (Endian::Big, BitSize(10)))"
     )]
     pub airspeed: u16,
 }
 
 /// Aircraft Operational Status Subtype
-#[derive(Debug, PartialEq, Eq, DekuRead, Copy, Clone)]
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
-#[deku(id_type = "u8", bits = "3")]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum OperationStatus {
-    #[deku(id = "0")]
     Airborne(OperationStatusAirborne),
 
-    #[deku(id = "1")]
     Surface(OperationStatusSurface),
 
-    #[deku(id_pat = "2..=7")]
-    Reserved(#[deku(bits = "5")] u8, [u8; 5]),
+    /// 2-7, with the value of the sub type and the rest of the message
+    Reserved(u8, [u8; 6]),
+}
+
+// Not derived, see `Capability`: the sub type starts at bit 5 of the message
+impl<'a> DekuReader<'a, ()> for OperationStatus {
+    fn from_reader_with_ctx<R: Read + Seek>(
+        reader: &mut Reader<R>,
+        _: (),
+    ) -> Result<Self, DekuError> {
+        let sub_type = u8::from_reader_with_ctx(reader, BitSize(3))?;
+        Ok(match sub_type {
+            0 => Self::Airborne(OperationStatusAirborne::from_reader_with_ctx(reader, ())?),
+            1 => Self::Surface(OperationStatusSurface::from_reader_with_ctx(reader, ())?),
+            sub_type => Self::Reserved(sub_type, <[u8; 6]>::from_reader_with_ctx(reader, ())?),
+        })
+    }
+}
+
+impl DekuWriter<()> for OperationStatus {
+    fn to_writer<W: no_std_io::Write + Seek>(
+        &self,
+        writer: &mut Writer<W>,
+        _: (),
+    ) -> Result<(), DekuError> {
+        match self {
+            Self::Airborne(airborne) => {
+                0_u8.to_writer(writer, BitSize(3))?;
+                airborne.to_writer(writer, ())
+            }
+            Self::Surface(surface) => {
+                1_u8.to_writer(writer, BitSize(3))?;
+                surface.to_writer(writer, ())
+            }
+            Self::Reserved(sub_type, rest) => {
+                sub_type.to_writer(writer, BitSize(3))?;
+                rest.to_writer(writer, ())
+            }
+        }
+    }
 }
 
 /// [`ME::AircraftOperationStatus`] && [`OperationStatus`] == 0
 ///
 /// Version 2 support only
-#[derive(Debug, PartialEq, Eq, DekuRead, Copy, Clone)]
+#[derive(Debug, PartialEq, Eq, DekuRead, DekuWrite, Copy, Clone)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct OperationStatusAirborne {
     /// CC (16 bits)
     pub capability_class: CapabilityClassAirborne,
@@ -385,8 +448,9 @@ impl fmt::Display for OperationStatusAirborne {
 }
 
 /// [`ME::AircraftOperationStatus`]
-#[derive(Debug, PartialEq, Eq, DekuRead, Copy, Clone)]
+#[derive(Debug, PartialEq, Eq, DekuRead, DekuWrite, Copy, Clone)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct CapabilityClassAirborne {
     #[deku(bits = "2", assert_eq = "0")]
     pub reserved0: u8,
@@ -437,8 +501,9 @@ impl fmt::Display for CapabilityClassAirborne {
 /// [`ME::AircraftOperationStatus`] && [`OperationStatus`] == 1
 ///
 /// Version 2 support only
-#[derive(Debug, PartialEq, Eq, DekuRead, Copy, Clone)]
+#[derive(Debug, PartialEq, Eq, DekuRead, DekuWrite, Copy, Clone)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct OperationStatusSurface {
     /// CC (14 bits)
     pub capability_class: CapabilityClassSurface,
@@ -451,10 +516,7 @@ pub struct OperationStatusSurface {
     pub operational_mode: OperationalMode,
 
     /// OM last 8 bits (diff for airborne/surface)
-    // TODO: parse:
-    // http://www.anteni.net/adsb/Doc/1090-WP30-18-DRAFT_DO-260B-V42.pdf
-    // 2.2.3.2.7.2.4.7 “GPS Antenna Offset” OM Code Subfield in Aircraft Operational Status Messages
-    pub gps_antenna_offset: u8,
+    pub gps_antenna_offset: GpsAntennaOffset,
 
     pub version_number: ADSBVersion,
 
@@ -492,6 +554,9 @@ impl fmt::Display for OperationStatusSurface {
         }
         write!(f, "   Operational modes: {}", self.operational_mode)?;
         writeln!(f)?;
+        if !self.gps_antenna_offset.is_no_data() {
+            writeln!(f, "   Antenna offset:     {}", self.gps_antenna_offset)?;
+        }
         writeln!(f, "   NACp:               {}", self.navigational_accuracy_category)?;
         writeln!(f, "   SIL:                {} (per hour)", self.source_integrity_level)?;
         writeln!(f, "   NICbaro:            {}", self.barometric_altitude_integrity)?;
@@ -504,9 +569,83 @@ impl fmt::Display for OperationStatusSurface {
     }
 }
 
+/// GPS Antenna Offset from the OM field of [`OperationStatusSurface`]
+///
+/// reference: DO-260B 2.2.3.2.7.2.4.7 "GPS Antenna Offset" OM Code Subfield in Aircraft
+/// Operational Status Messages
+#[derive(Debug, PartialEq, Eq, DekuRead, DekuWrite, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct GpsAntennaOffset {
+    /// Direction (left = 0, right = 1) and encoded distance from the longitudinal axis
+    #[deku(bits = "3")]
+    pub lateral: u8,
+
+    /// Encoded distance aft of the nose of the aircraft
+    #[deku(bits = "5")]
+    pub longitudinal: u8,
+}
+
+impl GpsAntennaOffset {
+    /// Lateral offset from the longitudinal axis of the aircraft in meters, positive to the right
+    ///
+    /// Returns `None` if no data
+    #[must_use]
+    pub fn lateral_meters(&self) -> Option<i8> {
+        let meters = ((self.lateral & 0b011) * 2) as i8;
+        match (self.lateral & 0b100 != 0, meters) {
+            (false, 0) => None,
+            (false, meters) => Some(-meters),
+            (true, meters) => Some(meters),
+        }
+    }
+
+    /// Longitudinal offset aft of the nose of the aircraft in meters
+    ///
+    /// Returns `None` if no data, or if the offset was applied by the sensor
+    #[must_use]
+    pub fn longitudinal_meters(&self) -> Option<u8> {
+        match self.longitudinal {
+            0 | 1 => None,
+            n => Some((n - 1) * 2),
+        }
+    }
+
+    /// Position was already corrected to the ADS-B position reference point by the sensor
+    #[must_use]
+    pub fn applied_by_sensor(&self) -> bool {
+        self.longitudinal == 1
+    }
+
+    /// Neither offset has data
+    #[must_use]
+    pub fn is_no_data(&self) -> bool {
+        self.lateral == 0 && self.longitudinal == 0
+    }
+}
+
+impl fmt::Display for GpsAntennaOffset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.lateral_meters() {
+            Some(0) => write!(f, "0 m")?,
+            Some(meters) if meters < 0 => write!(f, "{} m left", -meters)?,
+            Some(meters) => write!(f, "{meters} m right")?,
+            None => write!(f, "lateral N/A")?,
+        }
+        if let Some(meters) = self.longitudinal_meters() {
+            write!(f, ", {meters} m aft of nose")
+        } else if self.applied_by_sensor() {
+            write!(f, ", applied by sensor")
+        } else {
+            write!(f, ", longitudinal N/A")
+        }
+    }
+}
+
 /// [`ME::AircraftOperationStatus`]
-#[derive(Debug, PartialEq, Eq, DekuRead, Copy, Clone)]
+#[derive(Debug, PartialEq, Eq, DekuRead, DekuWrite, Copy, Clone)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct CapabilityClassSurface {
     /// 0, 0 in current version, reserved as id for later versions
     #[deku(bits = "2", assert_eq = "0")]
@@ -547,8 +686,9 @@ impl fmt::Display for CapabilityClassSurface {
 }
 
 /// `OperationMode` field not including the last 8 bits that are different for Surface/Airborne
-#[derive(Debug, PartialEq, Eq, DekuRead, Copy, Clone)]
+#[derive(Debug, PartialEq, Eq, DekuRead, DekuWrite, Copy, Clone)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct OperationalMode {
     /// (0, 0) in Version 2, reserved for other values
     #[deku(bits = "2", assert_eq = "0")]
@@ -594,8 +734,9 @@ impl fmt::Display for OperationalMode {
 /// ADS-B Defined from different ICAO documents
 ///
 /// reference: ICAO 9871 (5.3.2.3)
-#[derive(Debug, PartialEq, Eq, DekuRead, Copy, Clone)]
+#[derive(Debug, PartialEq, Eq, DekuRead, DekuWrite, Copy, Clone)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 #[deku(id_type = "u8", bits = "3")]
 pub enum ADSBVersion {
     #[deku(id = "0")]
@@ -615,8 +756,9 @@ impl fmt::Display for ADSBVersion {
 /// Control Field (B.3) for [`crate::DF::TisB`]
 ///
 /// reference: ICAO 9871
-#[derive(Debug, PartialEq, DekuRead, Clone)]
+#[derive(Debug, PartialEq, DekuRead, DekuWrite, Clone)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct ControlField {
     t: ControlFieldType,
     /// AA: Address, Announced
@@ -627,16 +769,31 @@ pub struct ControlField {
 
 impl fmt::Display for ControlField {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(
-            f,
-            "{}",
-            self.me.to_string(self.aa, &format!("{}", self.t), Capability::AG_UNCERTAIN3, false,)?
+        write!(f, "{}", self.to_string(None)?)
+    }
+}
+
+impl ControlField {
+    /// `to_string` with the barometric altitudes at or above `transition_altitude` as flight
+    /// levels
+    pub(crate) fn to_string(
+        &self,
+        transition_altitude: Option<i32>,
+    ) -> result::Result<String, Error> {
+        let address_type = format!("{}", self.t);
+        self.me.to_string(
+            self.aa,
+            &address_type,
+            Capability::AG_UNCERTAIN3,
+            false,
+            transition_altitude,
         )
     }
 }
 
-#[derive(Debug, PartialEq, Eq, DekuRead, Clone)]
+#[derive(Debug, PartialEq, Eq, DekuRead, DekuWrite, Clone)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 #[deku(id_type = "u8", bits = "3")]
 #[allow(non_camel_case_types)]
 pub enum ControlFieldType {
@@ -688,35 +845,70 @@ impl fmt::Display for ControlFieldType {
 }
 
 /// Table: A-2-97
-#[derive(Debug, PartialEq, Eq, DekuRead, Copy, Clone)]
+#[derive(Debug, PartialEq, Eq, DekuRead, DekuWrite, Copy, Clone)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct AircraftStatus {
     pub sub_type: AircraftStatusType,
     pub emergency_state: EmergencyState,
     #[deku(
         bits = "13",
         endian = "big",
-        map = "|squawk: u32| -> Result<_, DekuError> {Ok(decode_id13_field(squawk))}"
+        map = "|squawk: u32| -> Result<_, DekuError> {Ok(decode_id13_field(squawk))}",
+        writer = "encode_id13_field(*squawk).to_writer(deku::writer, (Endian::Big, BitSize(13)))"
     )]
     pub squawk: u32,
+    /// The rest of the message, reserved for sub type 1
+    #[deku(bits = "32", endian = "big")]
+    pub reserved: u32,
 }
 
-#[derive(Debug, PartialEq, Eq, DekuRead, Copy, Clone)]
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
-#[deku(id_type = "u8", bits = "3")]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum AircraftStatusType {
-    #[deku(id = "0")]
     NoInformation,
-    #[deku(id = "1")]
     EmergencyPriorityStatus,
-    #[deku(id = "2")]
     ACASRaBroadcast,
-    #[deku(id_pat = "_")]
-    Reserved,
+    /// 3-7, with the value of the sub type
+    Reserved(u8),
+}
+
+// Not derived, see `Capability`: the sub type starts at bit 5 of the message
+impl<'a> DekuReader<'a, ()> for AircraftStatusType {
+    fn from_reader_with_ctx<R: Read + Seek>(
+        reader: &mut Reader<R>,
+        _: (),
+    ) -> Result<Self, DekuError> {
+        let sub_type = u8::from_reader_with_ctx(reader, BitSize(3))?;
+        Ok(match sub_type {
+            0 => Self::NoInformation,
+            1 => Self::EmergencyPriorityStatus,
+            2 => Self::ACASRaBroadcast,
+            sub_type => Self::Reserved(sub_type),
+        })
+    }
+}
+
+impl DekuWriter<()> for AircraftStatusType {
+    fn to_writer<W: no_std_io::Write + Seek>(
+        &self,
+        writer: &mut Writer<W>,
+        _: (),
+    ) -> Result<(), DekuError> {
+        let sub_type: u8 = match self {
+            Self::NoInformation => 0,
+            Self::EmergencyPriorityStatus => 1,
+            Self::ACASRaBroadcast => 2,
+            Self::Reserved(sub_type) => *sub_type,
+        };
+        sub_type.to_writer(writer, BitSize(3))
+    }
 }
 
-#[derive(Debug, PartialEq, Eq, DekuRead, Copy, Clone)]
+#[derive(Debug, PartialEq, Eq, DekuRead, DekuWrite, Copy, Clone)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 #[deku(id_type = "u8", bits = "3")]
 pub enum EmergencyState {
     None = 0,
@@ -746,8 +938,9 @@ impl fmt::Display for EmergencyState {
     }
 }
 
-#[derive(Debug, PartialEq, Eq, DekuRead, Copy, Clone)]
+#[derive(Debug, PartialEq, Eq, DekuRead, DekuWrite, Copy, Clone)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct OperationCodeSurface {
     #[deku(bits = "1")]
     pub poe: u8,
@@ -760,21 +953,65 @@ pub struct OperationCodeSurface {
     pub lw: u8,
 }
 
-#[derive(Debug, PartialEq, Eq, DekuRead, Clone)]
+#[derive(Debug, PartialEq, Eq, DekuRead, DekuWrite, Clone)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct Identification {
     pub tc: TypeCoding,
 
     #[deku(bits = "3")]
     pub ca: u8,
 
-    /// N-Number / Tail Number
-    #[deku(reader = "aircraft_identification_read(deku::reader)")]
+    /// The 8 characters of 6 bits, as received, see [`crate::decode_callsign`]
+    ///
+    /// This is written instead of [`Self::cn`], see [`crate::encode_callsign`]
+    #[deku(bits = "48", endian = "big")]
+    pub raw: u64,
+
+    /// N-Number / Tail Number, without the trailing spaces
+    #[deku(skip, default = "decode_callsign(*raw)")]
     pub cn: String,
 }
 
-#[derive(Debug, PartialEq, Eq, DekuRead, Copy, Clone)]
+impl Identification {
+    #[must_use]
+    pub fn category(&self) -> EmitterCategory {
+        EmitterCategory { tc: self.tc, ca: self.ca }
+    }
+}
+
+/// Emitter category of [`Identification`], such as `A3` for a large aircraft
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct EmitterCategory {
+    pub tc: TypeCoding,
+    pub ca: u8,
+}
+
+impl EmitterCategory {
+    /// Surface vehicle, `C1` (emergency) or `C3` (service)
+    #[must_use]
+    pub fn is_surface_vehicle(&self) -> bool {
+        self.tc == TypeCoding::C && matches!(self.ca, 1 | 3)
+    }
+
+    /// Fixed ground or tethered obstruction, `C4` to `C7`
+    #[must_use]
+    pub fn is_obstruction(&self) -> bool {
+        self.tc == TypeCoding::C && self.ca >= 4
+    }
+}
+
+impl fmt::Display for EmitterCategory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", self.tc, self.ca)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, DekuRead, DekuWrite, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 #[deku(id_type = "u8", bits = "5")]
 pub enum TypeCoding {
     D = 1,
@@ -799,8 +1036,9 @@ impl fmt::Display for TypeCoding {
 }
 
 /// Target State and Status (§2.2.3.2.7.1)
-#[derive(Copy, Clone, Debug, PartialEq, DekuRead)]
+#[derive(Copy, Clone, Debug, PartialEq, DekuRead, DekuWrite)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct TargetStateAndStatusInformation {
     // TODO Support Target State and Status defined in DO-260A, ADS-B Version=1
     // TODO Support reserved 2..=3
@@ -811,13 +1049,15 @@ pub struct TargetStateAndStatusInformation {
     #[deku(
         bits = "12",
         endian = "big",
-        map = "|altitude: u32| -> Result<_, DekuError> {Ok(if altitude > 1 {(altitude - 1) * 32} else {0} )}"
+        map = "|altitude: u32| -> Result<_, DekuError> {Ok(if altitude > 1 {(altitude - 1) * 32} else {0} )}",
+        writer = "(if *altitude == 0 { 0 } else { altitude / 32 + 1 }).to_writer(deku::writer, (Endian::Big, BitSize(12)))"
     )]
     pub altitude: u32,
     #[deku(
         bits = "9",
         endian = "big",
-        map = "|qnh: u32| -> Result<_, DekuError> {if qnh == 0 { Ok(0.0) } else { Ok(800.0 + ((qnh - 1) as f32) * 0.8)}}"
+        map = "|qnh: u32| -> Result<_, DekuError> {if qnh == 0 { Ok(0.0) } else { Ok(800.0 + ((qnh - 1) as f32) * 0.8)}}",
+        writer = "(if *qnh == 0.0 { 0 } else { libm::roundf((qnh - 800.0) / 0.8) as u32 + 1 }).to_writer(deku::writer, (Endian::Big, BitSize(9)))"
     )]
     pub qnh: f32,
     #[deku(bits = "1")]
@@ -825,7 +1065,8 @@ pub struct TargetStateAndStatusInformation {
     #[deku(
         bits = "9",
         endian = "big",
-        map = "|heading: u16| -> Result<_, DekuError> {Ok(heading as f32 * 180.0 / 256.0)}"
+        map = "|heading: u16| -> Result<_, DekuError> {Ok(heading as f32 * 180.0 / 256.0)}",
+        writer = "(libm::roundf(heading * 256.0 / 180.0) as u16).to_writer(deku::writer, (Endian::Big, BitSize(9)))"
     )]
     pub heading: f32,
     #[deku(bits = "4")]
@@ -854,8 +1095,9 @@ pub struct TargetStateAndStatusInformation {
 }
 
 /// [`ME::AirborneVelocity`]
-#[derive(Debug, PartialEq, Eq, DekuRead, Clone)]
+#[derive(Debug, PartialEq, Eq, DekuRead, DekuWrite, Clone)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct AirborneVelocity {
     #[deku(bits = "3")]
     pub st: u8,
@@ -872,7 +1114,8 @@ pub struct AirborneVelocity {
     pub gnss_sign: Sign,
     #[deku(
         bits = "7",
-        map = "|gnss_baro_diff: u16| -> Result<_, DekuError> {Ok(if gnss_baro_diff > 1 {(gnss_baro_diff - 1)* 25} else { 0 })}"
+        map = "|gnss_baro_diff: u16| -> Result<_, DekuError> {Ok(if gnss_baro_diff > 1 {(gnss_baro_diff - 1)* 25} else { 0 })}",
+        writer = "(if *gnss_baro_diff == 0 { 0 } else { gnss_baro_diff / 25 + 1 }).to_writer(deku::writer, BitSize(7))"
     )]
     pub gnss_baro_diff: u16,
 }
@@ -902,8 +1145,9 @@ impl AirborneVelocity {
 }
 
 /// Airborne Velocity Message “Subtype” Code Field Encoding
-#[derive(Debug, PartialEq, Eq, DekuRead, Clone)]
+#[derive(Debug, PartialEq, Eq, DekuRead, DekuWrite, Clone)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 #[deku(ctx = "st: u8", id = "st")]
 pub enum AirborneVelocitySubType {
     #[deku(id = "0")]
@@ -919,23 +1163,31 @@ pub enum AirborneVelocitySubType {
     Reserved1(#[deku(bits = "22")] u32),
 }
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq, DekuRead)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, DekuRead, DekuWrite)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 #[deku(id_type = "u8", bits = "3")]
 pub enum AirborneVelocityType {
     Subsonic = 1,
     Supersonic = 3,
 }
 
-#[derive(Debug, PartialEq, Eq, DekuRead, Copy, Clone)]
+#[derive(Debug, PartialEq, Eq, DekuRead, DekuWrite, Copy, Clone)]
 #[deku(ctx = "t: AirborneVelocityType")]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct AirborneVelocitySubFields {
     pub dew: DirectionEW,
-    #[deku(reader = "Self::read_v(deku::reader, t)")]
+    #[deku(
+        reader = "Self::read_v(deku::reader, t)",
+        writer = "Self::write_v(deku::writer, *vew, t)"
+    )]
     pub vew: u16,
     pub dns: DirectionNS,
-    #[deku(reader = "Self::read_v(deku::reader, t)")]
+    #[deku(
+        reader = "Self::read_v(deku::reader, t)",
+        writer = "Self::write_v(deku::writer, *vns, t)"
+    )]
     pub vns: u16,
 }
 
@@ -955,50 +1207,69 @@ impl AirborneVelocitySubFields {
             }
         }
     }
+
+    /// Reverse of [`Self::read_v`]
+    fn write_v<W: no_std_io::Write + Seek>(
+        writer: &mut Writer<W>,
+        value: u16,
+        t: AirborneVelocityType,
+    ) -> result::Result<(), DekuError> {
+        let value = match t {
+            AirborneVelocityType::Subsonic => value + 1,
+            AirborneVelocityType::Supersonic => value / 4 + 1,
+        };
+        value.to_writer(writer, (Endian::Big, BitSize(10)))
+    }
 }
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq, DekuRead)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, DekuRead, DekuWrite)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 #[deku(id_type = "u8", bits = "1")]
 pub enum DirectionEW {
     WestToEast = 0,
     EastToWest = 1,
 }
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq, DekuRead)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, DekuRead, DekuWrite)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 #[deku(id_type = "u8", bits = "1")]
 pub enum DirectionNS {
     SouthToNorth = 0,
     NorthToSouth = 1,
 }
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq, DekuRead)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, DekuRead, DekuWrite)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 #[deku(id_type = "u8", bits = "1")]
 pub enum SourceBitVerticalRate {
     GNSS = 0,
     Barometer = 1,
 }
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq, DekuRead)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, DekuRead, DekuWrite)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 #[deku(id_type = "u8", bits = "1")]
 pub enum SignBitVerticalRate {
     Up = 0,
     Down = 1,
 }
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq, DekuRead)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, DekuRead, DekuWrite)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 #[deku(id_type = "u8", bits = "1")]
 pub enum SignBitGNSSBaroAltitudesDiff {
     Above = 0,
     Below = 1,
 }
 
-#[derive(Debug, PartialEq, Eq, DekuRead, Copy, Clone)]
+#[derive(Debug, PartialEq, Eq, DekuRead, DekuWrite, Copy, Clone)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 #[deku(id_type = "u8", bits = "1")]
 pub enum VerticalRateSource {
     BarometricPressureAltitude = 0,
@@ -1018,9 +1289,12 @@ impl fmt::Display for VerticalRateSource {
     }
 }
 
-#[derive(Debug, PartialEq, Eq, DekuRead, Copy, Clone)]
+#[derive(Debug, PartialEq, Eq, DekuRead, DekuWrite, Copy, Clone)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct SurfacePosition {
+    #[deku(bits = "5")]
+    pub tc: u8,
     #[deku(bits = "7")]
     pub mov: u8,
     pub s: StatusForGroundTrack,
@@ -1035,8 +1309,9 @@ pub struct SurfacePosition {
     pub lon_cpr: u32,
 }
 
-#[derive(Debug, PartialEq, Eq, DekuRead, Copy, Clone)]
+#[derive(Debug, PartialEq, Eq, DekuRead, DekuWrite, Copy, Clone)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 #[deku(id_type = "u8", bits = "1")]
 pub enum StatusForGroundTrack {
     Invalid = 0,
@@ -0,0 +1,176 @@
+//! AVR text format, as output by dump1090 on port 30002
+//!
+//! Each line contains a single frame in hex, in one of the following forms:
+//! - `*8D40621D58C382D690C8AC2863A7;`
+//! - `@0000C3E3A1B28D40621D58C382D690C8AC2863A7;`, with a 48 bit MLAT timestamp (12 MHz clock)
+//!
+//! Blank lines and comments starting with `#` are ignored.
+//!
+//! ```rust
+//! use adsb_deku::avr;
+//! use adsb_deku::Frame;
+//!
+//! let avr = avr::parse_line("@0000C3E3A1B28D40621D58C382D690C8AC2863A7;\n").unwrap().unwrap();
+//! assert_eq!(avr.timestamp, Some(0xC3E3A1B2));
+//! let frame = Frame::from_bytes(avr.bytes()).unwrap();
+//! ```
+
+#[cfg(feature = "alloc")]
+use core::{
+    clone::Clone,
+    cmp::{Eq, PartialEq},
+    fmt::Debug,
+    iter::Iterator,
+    marker::Copy,
+    option::Option::{self, None, Some},
+    prelude::rust_2021::derive,
+    result::Result::{self, Err, Ok},
+    write,
+};
+
+use crate::demod::DemodMeta;
+
+/// Length (bytes) of a Mode S short frame
+const SHORT_FRAME_BYTES: usize = 7;
+
+/// Length (bytes) of a Mode S long frame
+const LONG_FRAME_BYTES: usize = 14;
+
+/// Length (hex characters) of the `@` timestamp
+const TIMESTAMP_LEN: usize = 12;
+
+/// Error from [`parse_line`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum AvrError {
+    /// Line doesn't start with `*` or `@`
+    UnknownPrefix(char),
+    /// Line contains a non hex character
+    InvalidHex,
+    /// `@` line is too short to contain the timestamp
+    MissingTimestamp,
+    /// Frame is not a Mode S short (7 bytes) or long (14 bytes) frame, contains the amount of
+    /// hex characters
+    InvalidLength(usize),
+}
+
+impl core::fmt::Display for AvrError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::UnknownPrefix(c) => write!(f, "unknown avr prefix: {c:?}"),
+            Self::InvalidHex => write!(f, "invalid hex"),
+            Self::MissingTimestamp => write!(f, "missing timestamp"),
+            Self::InvalidLength(len) => write!(f, "invalid frame length: {len} hex characters"),
+        }
+    }
+}
+
+/// Frame bytes and optional timestamp of a single AVR line
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct AvrFrame {
+    /// MLAT timestamp from a `@` line, 12 MHz clock
+    pub timestamp: Option<u64>,
+    bytes: [u8; LONG_FRAME_BYTES],
+    len: usize,
+    /// Measurements of the demodulator, never in the AVR lines, see [`crate::demod`]
+    pub meta: Option<DemodMeta>,
+}
+
+impl AvrFrame {
+    /// Frame of `bytes` from a demodulator, without an AVR line
+    ///
+    /// Returns [`AvrError::InvalidLength`] (in hex characters) if `bytes` is not a Mode S short
+    /// or long frame.
+    pub fn new(bytes: &[u8], timestamp: Option<u64>) -> Result<Self, AvrError> {
+        let len = bytes.len();
+        if !(len == SHORT_FRAME_BYTES || len == LONG_FRAME_BYTES) {
+            return Err(AvrError::InvalidLength(len * 2));
+        }
+        let mut frame = Self { timestamp, bytes: [0; LONG_FRAME_BYTES], len, meta: None };
+        frame.bytes[..len].copy_from_slice(bytes);
+        Ok(frame)
+    }
+
+    /// Attach the measurements of the demodulator
+    #[must_use]
+    pub fn with_meta(mut self, meta: DemodMeta) -> Self {
+        self.meta = Some(meta);
+        self
+    }
+
+    /// Repair the CRC of a DF17/18 frame by flipping at most `max_bits` of its least confident
+    /// bits, from [`DemodMeta::bit_confidence`]
+    ///
+    /// Returns the amount of flipped bits, also added to [`DemodMeta::corrected_bits`], and `None`
+    /// without a confidence or a repair, see [`crate::crc::soft_repair`].
+    pub fn soft_repair(&mut self, max_bits: usize) -> Option<u8> {
+        let meta = self.meta.as_mut()?;
+        let confidence = meta.bit_confidence?;
+        let flipped = crate::crc::soft_repair(&mut self.bytes[..self.len], &confidence, max_bits)?;
+        if flipped > 0 {
+            meta.corrected_bits = Some(meta.corrected_bits.unwrap_or(0).saturating_add(flipped));
+        }
+        Some(flipped)
+    }
+
+    /// Frame bytes, to be used with [`crate::Frame::from_bytes`]
+    #[must_use]
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes[..self.len]
+    }
+}
+
+/// Parse a single AVR line
+///
+/// Surrounding whitespace (such as `\r\n`) and the `;` terminator are optional.
+///
+/// Returns:
+///   `Ok(Some(frame))`: `*` or `@` line
+///   `Ok(None)`:        blank or comment line
+///   `Err(e)`:          garbage line
+pub fn parse_line(line: &str) -> Result<Option<AvrFrame>, AvrError> {
+    let line = line.trim();
+    let line = line.strip_suffix(';').unwrap_or(line);
+
+    let mut chars = line.chars();
+    let (timestamp, hex) = match chars.next() {
+        None | Some('#') => return Ok(None),
+        Some('*') => (None, chars.as_str()),
+        Some('@') => {
+            let rest = chars.as_str();
+            if rest.len() < TIMESTAMP_LEN || !rest.is_char_boundary(TIMESTAMP_LEN) {
+                return Err(AvrError::MissingTimestamp);
+            }
+            let (timestamp, hex) = rest.split_at(TIMESTAMP_LEN);
+            let timestamp = timestamp
+                .bytes()
+                .try_fold(0_u64, |acc, c| Ok((acc << 4) | u64::from(hex_value(c)?)))?;
+            (Some(timestamp), hex)
+        }
+        Some(c) => return Err(AvrError::UnknownPrefix(c)),
+    };
+
+    let len = hex.len() / 2;
+    if hex.len() % 2 != 0 || !(len == SHORT_FRAME_BYTES || len == LONG_FRAME_BYTES) {
+        return Err(AvrError::InvalidLength(hex.len()));
+    }
+
+    let mut bytes = [0; LONG_FRAME_BYTES];
+    for (byte, pair) in bytes.iter_mut().zip(hex.as_bytes().chunks_exact(2)) {
+        *byte = (hex_value(pair[0])? << 4) | hex_value(pair[1])?;
+    }
+
+    Ok(Some(AvrFrame { timestamp, bytes, len, meta: None }))
+}
+
+fn hex_value(c: u8) -> Result<u8, AvrError> {
+    match c {
+        b'0'..=b'9' => Ok(c - b'0'),
+        b'a'..=b'f' => Ok(c - b'a' + 10),
+        b'A'..=b'F' => Ok(c - b'A' + 10),
+        _ => Err(AvrError::InvalidHex),
+    }
+}
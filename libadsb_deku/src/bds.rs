@@ -1,35 +1,232 @@
 //! B-Definition Subfield for Comm-B Messages
+//!
+//! `BDS` 1,0 and 2,0 contain their own identifier in the first byte of the MB field. The
+//! Enhanced Surveillance (EHS) registers `BDS` 4,0, 5,0 and 6,0 don't contain an identifier,
+//! so they are inferred by checking that only one of them decodes to plausible values. Otherwise
+//! the reply is a [`BDS::Unknown`], with a [`BdsDecodeReport`] of why each register was rejected.
+//!
+//! Writing a `BDS` encodes the values of its register into the MB field, with the status bits of
+//! the missing values cleared. An inferred register can be written with values that are decoded
+//! as another register, or as ambiguous.
+//!
+//! The [`Display`](fmt::Display) of a `BDS` matches the verbose output of dump1090, with the same
+//! names, units and precision of each value, such that the output of both decoders can be diffed.
 
 use alloc::format;
 use alloc::string::String;
+use alloc::vec::Vec;
 #[cfg(feature = "alloc")]
 use core::{
-    clone::Clone, cmp::PartialEq, fmt, fmt::Debug, prelude::rust_2021::derive, result::Result::Ok,
-    writeln,
+    clone::Clone,
+    cmp::PartialEq,
+    fmt,
+    fmt::Debug,
+    iter::Iterator,
+    marker::Copy,
+    option::Option::{self, None, Some},
+    prelude::rust_2021::derive,
+    result::Result::{self, Err, Ok},
+    write, writeln,
 };
 
+use deku::no_std_io::{Cursor, Read, Seek, Write};
 use deku::prelude::*;
 
-use crate::aircraft_identification_read;
+use crate::{aircraft_identification_read, encode_callsign};
 
-#[derive(Debug, PartialEq, Eq, DekuRead, Clone)]
-#[deku(id_type = "u8")]
+/// Mach number of the least significant bit in `BDS` 6,0
+const MACH_LSB: f32 = 2.048 / 512.0;
+
+#[derive(Debug, PartialEq, Clone)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum BDS {
     /// (1, 0) Table A-2-16
-    #[deku(id = "0x00")]
     Empty([u8; 6]),
 
     /// (1, 0) Table A-2-16
-    #[deku(id = "0x10")]
     DataLinkCapability(DataLinkCapability),
 
     /// (2, 0) Table A-2-32
-    #[deku(id = "0x20")]
-    AircraftIdentification(#[deku(reader = "aircraft_identification_read(deku::reader)")] String),
+    AircraftIdentification(String),
+
+    /// (4, 0) Table A-2-64, inferred
+    SelectedVerticalIntention(SelectedVerticalIntention),
+
+    /// (5, 0) Table A-2-80, inferred
+    TrackAndTurnReport(TrackAndTurnReport),
+
+    /// (6, 0) Table A-2-96, inferred
+    HeadingAndSpeedReport(HeadingAndSpeedReport),
+
+    /// No register, or more than one of the inferred registers, decoded to plausible values
+    Unknown(BdsDecodeReport),
+}
+
+impl<'a> DekuReader<'a, ()> for BDS {
+    fn from_reader_with_ctx<R: Read + Seek>(
+        reader: &mut Reader<R>,
+        _: (),
+    ) -> Result<Self, DekuError> {
+        let mb = <[u8; 7]>::from_reader_with_ctx(reader, ())?;
+        Self::from_mb(mb)
+    }
+}
 
-    #[deku(id_pat = "_")]
-    Unknown((u8, [u8; 6])),
+impl DekuWriter<()> for BDS {
+    fn to_writer<W: Write + Seek>(&self, writer: &mut Writer<W>, _: ()) -> Result<(), DekuError> {
+        self.to_mb()?.to_writer(writer, ())
+    }
+}
+
+impl DekuContainerWrite for BDS {}
+
+impl BDS {
+    /// Encode the 56 bit MB field of a Comm-B reply, the reverse of [`Self::from_mb`]
+    fn to_mb(&self) -> Result<[u8; 7], DekuError> {
+        let (id, rest) = match self {
+            Self::Empty(rest) => (0x00, *rest),
+            Self::DataLinkCapability(capability) => {
+                let mut rest = [0; 6];
+                capability.to_slice(&mut rest)?;
+                (0x10, rest)
+            }
+            Self::AircraftIdentification(callsign) => {
+                let raw = encode_callsign(callsign)
+                    .ok_or(DekuError::InvalidParam("invalid callsign".into()))?;
+                let mut rest = [0; 6];
+                rest.copy_from_slice(&raw.to_be_bytes()[2..]);
+                (0x20, rest)
+            }
+            Self::SelectedVerticalIntention(bds) => return Ok(mb_bytes(bds.to_mb())),
+            Self::TrackAndTurnReport(bds) => return Ok(mb_bytes(bds.to_mb())),
+            Self::HeadingAndSpeedReport(bds) => return Ok(mb_bytes(bds.to_mb())),
+            Self::Unknown(report) => return Ok(report.mb),
+        };
+        let mut bytes = [id; 7];
+        bytes[1..].copy_from_slice(&rest);
+        Ok(bytes)
+    }
+
+    /// Decode the 56 bit MB field of a Comm-B reply
+    fn from_mb(bytes: [u8; 7]) -> Result<Self, DekuError> {
+        let id = bytes[0];
+        let mut rest = [0; 6];
+        rest.copy_from_slice(&bytes[1..]);
+        let mut attempts = Vec::new();
+
+        match id {
+            0x00 if rest == [0; 6] => return Ok(Self::Empty(rest)),
+            0x10 => {
+                let (_, capability) = DataLinkCapability::from_bytes((&rest, 0))?;
+                return Ok(Self::DataLinkCapability(capability));
+            }
+            0x20 => {
+                let mut cursor = Cursor::new(&rest);
+                let mut reader = Reader::new(&mut cursor);
+                let callsign = aircraft_identification_read(&mut reader)?;
+                if !callsign.contains('#') {
+                    return Ok(Self::AircraftIdentification(callsign));
+                }
+                attempts.push(BdsAttempt {
+                    register: (2, 0),
+                    rejection: Some(BdsRejection::InvalidCallsign),
+                });
+            }
+            _ => (),
+        }
+
+        let mb = bytes.iter().fold(0_u64, |acc, b| (acc << 8) | u64::from(*b));
+        let bds40 = SelectedVerticalIntention::from_mb(mb);
+        let bds50 = TrackAndTurnReport::from_mb(mb);
+        let bds60 = HeadingAndSpeedReport::from_mb(mb);
+        let bds = match (bds40, bds50, bds60) {
+            (Ok(bds40), Err(_), Err(_)) => Self::SelectedVerticalIntention(bds40),
+            (Err(_), Ok(bds50), Err(_)) => Self::TrackAndTurnReport(bds50),
+            (Err(_), Err(_), Ok(bds60)) => Self::HeadingAndSpeedReport(bds60),
+            // no match, or ambiguous
+            _ if id == 0x00 => Self::Empty(rest),
+            (bds40, bds50, bds60) => {
+                attempts.extend([
+                    BdsAttempt { register: (4, 0), rejection: bds40.err() },
+                    BdsAttempt { register: (5, 0), rejection: bds50.err() },
+                    BdsAttempt { register: (6, 0), rejection: bds60.err() },
+                ]);
+                Self::Unknown(BdsDecodeReport { mb: bytes, attempts })
+            }
+        };
+        Ok(bds)
+    }
+}
+
+/// Registers attempted for a [`BDS::Unknown`] MB field, for comparing decoders
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct BdsDecodeReport {
+    /// The 56 bit MB field
+    pub mb: [u8; 7],
+    /// BDS 2,0 if the MB field starts with its identifier, then the inferred BDS 4,0, 5,0 and 6,0
+    pub attempts: Vec<BdsAttempt>,
+}
+
+impl BdsDecodeReport {
+    /// Inferred registers with plausible values, more than one for an ambiguous MB field
+    pub fn plausible(&self) -> impl Iterator<Item = (u8, u8)> + '_ {
+        self.attempts
+            .iter()
+            .filter(|attempt| attempt.rejection.is_none())
+            .map(|attempt| attempt.register)
+    }
+}
+
+/// Decoding of a single register, see [`BdsDecodeReport`]
+#[derive(Debug, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct BdsAttempt {
+    /// BDS number, such as (5, 0)
+    pub register: (u8, u8),
+    /// Why the register was rejected, `None` if the values are plausible
+    pub rejection: Option<BdsRejection>,
+}
+
+/// Failed plausibility check of a register, see [`BdsDecodeReport`]
+///
+/// Bits are 1-indexed in the MB field, as in ICAO 9871.
+#[derive(Debug, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum BdsRejection {
+    /// The status bit at `bit` is not set, but the value following it isn't zero
+    Status { bit: u32 },
+    /// The `len` reserved bits starting at `bit` aren't zero
+    Reserved { bit: u32, len: u32 },
+    /// The status bits of the main values are not set
+    NoValue,
+    /// The value with the status bit at `bit` is outside of `min..=max`
+    Range { bit: u32, value: f32, min: f32, max: f32 },
+    /// Ground speed and true airspeed (kt) differ by more than 200 kt
+    SpeedDifference { ground_speed: u16, true_airspeed: u16 },
+    /// The BDS 2,0 callsign contains invalid characters
+    InvalidCallsign,
+}
+
+impl fmt::Display for BdsRejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Status { bit } => write!(f, "status bit {bit} not set, with a value"),
+            Self::Reserved { bit, len } => write!(f, "reserved bits {bit}..{} set", bit + len),
+            Self::NoValue => write!(f, "no value"),
+            Self::Range { bit, value, min, max } => {
+                write!(f, "value of status bit {bit}: {value} outside of {min}..={max}")
+            }
+            Self::SpeedDifference { ground_speed, true_airspeed } => {
+                write!(f, "ground speed {ground_speed} kt and TAS {true_airspeed} kt differ")
+            }
+            Self::InvalidCallsign => write!(f, "invalid callsign"),
+        }
+    }
 }
 
 impl fmt::Display for BDS {
@@ -42,9 +239,59 @@ impl fmt::Display for BDS {
                 writeln!(f, "Comm-B format: BDS2,0 Aircraft identification")?;
                 writeln!(f, "  Ident:         {s}")?;
             }
+            // dump1090 doesn't display the capabilities
             Self::DataLinkCapability(_) => {
                 writeln!(f, "Comm-B format: BDS1,0 Datalink capabilities")?;
             }
+            Self::SelectedVerticalIntention(bds) => {
+                writeln!(f, "Comm-B format: BDS4,0 Vertical intent")?;
+                write_value(
+                    f,
+                    "MCP selected altitude:",
+                    NAV_WIDTH,
+                    bds.mcp_selected_altitude,
+                    "ft",
+                )?;
+                write_value(
+                    f,
+                    "FMS selected altitude:",
+                    NAV_WIDTH,
+                    bds.fms_selected_altitude,
+                    "ft",
+                )?;
+                write_value(
+                    f,
+                    "QNH:",
+                    NAV_WIDTH,
+                    fmt_f32(bds.barometric_pressure_setting, 1),
+                    "millibars",
+                )?;
+            }
+            Self::TrackAndTurnReport(bds) => {
+                writeln!(f, "Comm-B format: BDS5,0 Track and turn report")?;
+                let ground_speed = bds.ground_speed.map(f32::from);
+                write_value(f, "Groundspeed:", WIDTH, fmt_f32(ground_speed, 1), "kt")?;
+                write_value(f, "TAS:", WIDTH, bds.true_airspeed, "kt")?;
+                write_value(f, "Ground track:", WIDTH, fmt_f32(bds.true_track, 1), "")?;
+                let track_rate = bds
+                    .track_angle_rate
+                    .map(|rate| format!("{rate:.2} deg/sec {}", side(rate, 0.0)));
+                write_value(f, "Track rate:", WIDTH, track_rate, "")?;
+                let roll =
+                    bds.roll_angle.map(|roll| format!("{roll:.1} degrees {}", side(roll, 0.05)));
+                write_value(f, "Roll:", WIDTH, roll, "")?;
+            }
+            Self::HeadingAndSpeedReport(bds) => {
+                writeln!(f, "Comm-B format: BDS6,0 Heading and speed report")?;
+                write_value(f, "IAS:", WIDTH, bds.indicated_airspeed, "kt")?;
+                write_value(f, "Mach number:", WIDTH, fmt_f32(bds.mach, 3), "")?;
+                write_value(f, "Mag heading:", WIDTH, fmt_f32(bds.magnetic_heading, 1), "")?;
+                write_value(f, "Baro rate:", WIDTH, bds.barometric_altitude_rate, "ft/min")?;
+                write_value(f, "Geom rate:", WIDTH, bds.inertial_vertical_velocity, "ft/min")?;
+            }
+            Self::Unknown(report) if report.plausible().count() > 1 => {
+                writeln!(f, "Comm-B format: ambiguous format")?;
+            }
             Self::Unknown(_) => {
                 writeln!(f, "Comm-B format: unknown format")?;
             }
@@ -53,9 +300,48 @@ impl fmt::Display for BDS {
     }
 }
 
+/// Width of the names of the values of the `BDS` registers, as in dump1090
+const WIDTH: usize = 15;
+
+/// Width of the names of the `BDS` 4,0 values, as in dump1090
+const NAV_WIDTH: usize = 23;
+
+/// Format `value` with `precision` digits after the decimal point
+fn fmt_f32(value: Option<f32>, precision: usize) -> Option<String> {
+    value.map(|value| format!("{value:.precision$}"))
+}
+
+/// `left` or `right` for a negative or positive `value` outside of `-dead_band..=dead_band`
+fn side(value: f32, dead_band: f32) -> &'static str {
+    if value < -dead_band {
+        "left"
+    } else if value > dead_band {
+        "right"
+    } else {
+        ""
+    }
+}
+
+/// Write `name` padded to `width` and `value` if available, used for the display of `BDS`
+/// registers
+fn write_value<T: fmt::Display>(
+    f: &mut fmt::Formatter<'_>,
+    name: &str,
+    width: usize,
+    value: Option<T>,
+    unit: &str,
+) -> fmt::Result {
+    if let Some(value) = value {
+        let value = format!("{value} {unit}");
+        writeln!(f, "  {name:<width$}{}", value.trim_end())?;
+    }
+    Ok(())
+}
+
 /// To report the data link capability of the Mode S transponder/data link installation
-#[derive(Debug, PartialEq, Eq, DekuRead, Clone)]
+#[derive(Debug, PartialEq, Eq, DekuRead, DekuWrite, Clone)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct DataLinkCapability {
     #[deku(bits = "1")]
     #[deku(pad_bits_after = "5")] // reserved
@@ -86,3 +372,261 @@ pub struct DataLinkCapability {
     pub reserved_acas: u8,
     pub bit_array: u16,
 }
+
+/// Selected vertical intention, `BDS` 4,0
+#[derive(Debug, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SelectedVerticalIntention {
+    /// MCP/FCU selected altitude (ft)
+    pub mcp_selected_altitude: Option<u16>,
+    /// FMS selected altitude (ft)
+    pub fms_selected_altitude: Option<u16>,
+    /// Barometric pressure setting (millibars)
+    pub barometric_pressure_setting: Option<f32>,
+}
+
+impl SelectedVerticalIntention {
+    fn from_mb(mb: u64) -> Result<Self, BdsRejection> {
+        reserved(mb, 40, 8)?;
+        reserved(mb, 52, 2)?;
+        let mcp = status_field(mb, 1, 12)?.map(|v| v as u16 * 16);
+        let fms = status_field(mb, 14, 12)?.map(|v| v as u16 * 16);
+        let baro = status_field(mb, 27, 12)?.map(|v| v as f32 * 0.1 + 800.0);
+        status_field(mb, 48, 3)?;
+        status_field(mb, 54, 2)?;
+
+        if mcp.is_none() && fms.is_none() && baro.is_none() {
+            return Err(BdsRejection::NoValue);
+        }
+        check_range(1, mcp.map(f32::from), 0.0, 50_000.0)?;
+        check_range(14, fms.map(f32::from), 0.0, 50_000.0)?;
+        check_range(27, baro, 900.0, 1100.0)?;
+
+        Ok(Self {
+            mcp_selected_altitude: mcp,
+            fms_selected_altitude: fms,
+            barometric_pressure_setting: baro,
+        })
+    }
+
+    fn to_mb(self) -> u64 {
+        let mut mb = 0;
+        set_status_field(&mut mb, 1, 12, self.mcp_selected_altitude.map(|v| u64::from(v / 16)));
+        set_status_field(&mut mb, 14, 12, self.fms_selected_altitude.map(|v| u64::from(v / 16)));
+        let baro =
+            self.barometric_pressure_setting.map(|v| libm::roundf((v - 800.0) * 10.0) as u64);
+        set_status_field(&mut mb, 27, 12, baro);
+        mb
+    }
+}
+
+/// Track and turn report, `BDS` 5,0
+#[derive(Debug, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct TrackAndTurnReport {
+    /// Roll angle (degrees), negative is left wing down
+    pub roll_angle: Option<f32>,
+    /// True track angle (degrees), 0 to 360
+    pub true_track: Option<f32>,
+    /// Ground speed (kt)
+    pub ground_speed: Option<u16>,
+    /// Track angle rate (degrees/second)
+    pub track_angle_rate: Option<f32>,
+    /// True airspeed (kt)
+    pub true_airspeed: Option<u16>,
+}
+
+impl TrackAndTurnReport {
+    fn from_mb(mb: u64) -> Result<Self, BdsRejection> {
+        let roll = signed_status_field(mb, 1, 9)?.map(|v| v as f32 * 45.0 / 256.0);
+        let track = signed_status_field(mb, 12, 10)?.map(|v| angle(v as f32 * 90.0 / 512.0));
+        let gs = status_field(mb, 24, 10)?.map(|v| v as u16 * 2);
+        let rate = signed_status_field(mb, 35, 9)?.map(|v| v as f32 * 8.0 / 256.0);
+        let tas = status_field(mb, 46, 10)?.map(|v| v as u16 * 2);
+
+        if roll.is_none() && track.is_none() && gs.is_none() && tas.is_none() {
+            return Err(BdsRejection::NoValue);
+        }
+        check_range(1, roll, -50.0, 50.0)?;
+        check_range(24, gs.map(f32::from), 0.0, 600.0)?;
+        check_range(46, tas.map(f32::from), 0.0, 500.0)?;
+        if let (Some(gs), Some(tas)) = (gs, tas) {
+            if (i32::from(gs) - i32::from(tas)).abs() > 200 {
+                return Err(BdsRejection::SpeedDifference { ground_speed: gs, true_airspeed: tas });
+            }
+        }
+
+        Ok(Self {
+            roll_angle: roll,
+            true_track: track,
+            ground_speed: gs,
+            track_angle_rate: rate,
+            true_airspeed: tas,
+        })
+    }
+
+    fn to_mb(self) -> u64 {
+        let mut mb = 0;
+        let roll = self.roll_angle.map(|v| libm::roundf(v * 256.0 / 45.0) as i64);
+        set_signed_status_field(&mut mb, 1, 9, roll);
+        let track = self.true_track.map(|v| libm::roundf(signed_angle(v) * 512.0 / 90.0) as i64);
+        set_signed_status_field(&mut mb, 12, 10, track);
+        set_status_field(&mut mb, 24, 10, self.ground_speed.map(|v| u64::from(v / 2)));
+        let rate = self.track_angle_rate.map(|v| libm::roundf(v * 256.0 / 8.0) as i64);
+        set_signed_status_field(&mut mb, 35, 9, rate);
+        set_status_field(&mut mb, 46, 10, self.true_airspeed.map(|v| u64::from(v / 2)));
+        mb
+    }
+}
+
+/// Heading and speed report, `BDS` 6,0
+#[derive(Debug, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct HeadingAndSpeedReport {
+    /// Magnetic heading (degrees), 0 to 360
+    pub magnetic_heading: Option<f32>,
+    /// Indicated airspeed (kt)
+    pub indicated_airspeed: Option<u16>,
+    /// Mach number
+    pub mach: Option<f32>,
+    /// Barometric altitude rate (ft/min)
+    pub barometric_altitude_rate: Option<i16>,
+    /// Inertial vertical velocity (ft/min)
+    pub inertial_vertical_velocity: Option<i16>,
+}
+
+impl HeadingAndSpeedReport {
+    fn from_mb(mb: u64) -> Result<Self, BdsRejection> {
+        let heading = signed_status_field(mb, 1, 10)?.map(|v| angle(v as f32 * 90.0 / 512.0));
+        let ias = status_field(mb, 13, 10)?.map(|v| v as u16);
+        let mach = status_field(mb, 24, 10)?.map(|v| v as f32 * MACH_LSB);
+        let baro_rate = signed_status_field(mb, 35, 9)?.map(|v| v as i16 * 32);
+        let inertial_rate = signed_status_field(mb, 46, 9)?.map(|v| v as i16 * 32);
+
+        if heading.is_none() && ias.is_none() && mach.is_none() {
+            return Err(BdsRejection::NoValue);
+        }
+        check_range(13, ias.map(f32::from), 1.0, 500.0)?;
+        check_range(24, mach, MACH_LSB, 1.0)?;
+        check_range(35, baro_rate.map(f32::from), -6000.0, 6000.0)?;
+        check_range(46, inertial_rate.map(f32::from), -6000.0, 6000.0)?;
+
+        Ok(Self {
+            magnetic_heading: heading,
+            indicated_airspeed: ias,
+            mach,
+            barometric_altitude_rate: baro_rate,
+            inertial_vertical_velocity: inertial_rate,
+        })
+    }
+
+    fn to_mb(self) -> u64 {
+        let mut mb = 0;
+        let heading =
+            self.magnetic_heading.map(|v| libm::roundf(signed_angle(v) * 512.0 / 90.0) as i64);
+        set_signed_status_field(&mut mb, 1, 10, heading);
+        set_status_field(&mut mb, 13, 10, self.indicated_airspeed.map(u64::from));
+        set_status_field(&mut mb, 24, 10, self.mach.map(|v| libm::roundf(v / MACH_LSB) as u64));
+        let baro_rate = self.barometric_altitude_rate.map(|v| i64::from(v / 32));
+        set_signed_status_field(&mut mb, 35, 9, baro_rate);
+        let inertial_rate = self.inertial_vertical_velocity.map(|v| i64::from(v / 32));
+        set_signed_status_field(&mut mb, 46, 9, inertial_rate);
+        mb
+    }
+}
+
+/// Bytes of the 56 bit MB field
+fn mb_bytes(mb: u64) -> [u8; 7] {
+    let mut bytes = [0; 7];
+    bytes.copy_from_slice(&mb.to_be_bytes()[1..]);
+    bytes
+}
+
+/// Read `len` bits of the 56 bit MB field, starting at the 1-indexed bit `start`
+fn bits(mb: u64, start: u32, len: u32) -> u64 {
+    (mb >> (56 - (start - 1) - len)) & ((1 << len) - 1)
+}
+
+/// Check that the `len` reserved bits starting at `start` are zero
+fn reserved(mb: u64, start: u32, len: u32) -> Result<(), BdsRejection> {
+    if bits(mb, start, len) != 0 {
+        return Err(BdsRejection::Reserved { bit: start, len });
+    }
+    Ok(())
+}
+
+/// Check that the `value` with the status bit at `status` is within `min..=max`
+fn check_range(status: u32, value: Option<f32>, min: f32, max: f32) -> Result<(), BdsRejection> {
+    match value {
+        Some(value) if !(min..=max).contains(&value) => {
+            Err(BdsRejection::Range { bit: status, value, min, max })
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Read the status bit at `status`, followed by a `len` bit value
+///
+/// Return an error if the field is inconsistent: the status bit is not set, but the value is
+/// non-zero. Return `Ok(None)` if the value is not available.
+fn status_field(mb: u64, status: u32, len: u32) -> Result<Option<u64>, BdsRejection> {
+    let value = bits(mb, status + 1, len);
+    match (bits(mb, status, 1), value) {
+        (0, 0) => Ok(None),
+        (0, _) => Err(BdsRejection::Status { bit: status }),
+        _ => Ok(Some(value)),
+    }
+}
+
+/// Read the status bit at `status`, followed by a sign bit and a `len` bit value
+///
+/// See [`status_field`]
+fn signed_status_field(mb: u64, status: u32, len: u32) -> Result<Option<i64>, BdsRejection> {
+    let value = status_field(mb, status, len + 1)?;
+    Ok(value.map(|value| {
+        let value = value as i64;
+        if value & (1 << len) != 0 {
+            value - (1 << (len + 1))
+        } else {
+            value
+        }
+    }))
+}
+
+/// Write the status bit at `status`, followed by the `len` bit `value`, the reverse of
+/// [`status_field`]
+///
+/// The status bit is cleared for a value that is not available.
+fn set_status_field(mb: &mut u64, status: u32, len: u32, value: Option<u64>) {
+    if let Some(value) = value {
+        *mb |= ((1 << len) | (value & ((1 << len) - 1))) << (56 - status - len);
+    }
+}
+
+/// Write the status bit at `status`, followed by a sign bit and the `len` bit `value`
+///
+/// See [`set_status_field`]
+fn set_signed_status_field(mb: &mut u64, status: u32, len: u32, value: Option<i64>) {
+    set_status_field(mb, status, len + 1, value.map(|value| value as u64));
+}
+
+/// Convert an angle from -180..180 to 0..360
+fn angle(angle: f32) -> f32 {
+    if angle < 0.0 {
+        angle + 360.0
+    } else {
+        angle
+    }
+}
+
+/// Convert an angle from 0..360 to -180..180, the reverse of [`angle`]
+fn signed_angle(angle: f32) -> f32 {
+    if angle >= 180.0 {
+        angle - 360.0
+    } else {
+        angle
+    }
+}
@@ -0,0 +1,254 @@
+//! Beast binary format, as output by dump1090 and readsb on port 30005
+//!
+//! Each message is `<esc> <type> <6 byte timestamp> <1 byte signal level> <frame>`, where `<esc>`
+//! is `0x1a` and any `0x1a` byte after the type is doubled. The type is one of:
+//! - `'1'`: Mode A/C reply, 2 bytes
+//! - `'2'`: Mode S short frame, 7 bytes
+//! - `'3'`: Mode S long frame, 14 bytes
+//!
+//! The timestamp is a 48 bit MLAT counter (12 MHz clock), as the `@` lines of [`crate::avr`].
+//! A [`BeastDecoder`] is fed the bytes of a stream as they are received, and realigns on the next
+//! message after joining a stream in the middle of a message or a truncated message.
+//!
+//! ```rust
+//! use adsb_deku::beast::BeastDecoder;
+//! use adsb_deku::Frame;
+//!
+//! let bytes = [
+//!     0x1a, b'3', 0x00, 0x00, 0xc3, 0xe3, 0xa1, 0xb2, 0x80, 0x8d, 0x40, 0x62, 0x1d, 0x58, 0xc3,
+//!     0x82, 0xd6, 0x90, 0xc8, 0xac, 0x28, 0x63, 0xa7,
+//! ];
+//! let mut decoder = BeastDecoder::default();
+//! let beast = decoder.decode(&bytes).next().unwrap().unwrap();
+//! assert_eq!(beast.timestamp, 0xc3e3_a1b2);
+//! assert_eq!(beast.signal_level, 0x80);
+//! let avr = beast.to_avr().unwrap();
+//! let frame = Frame::from_bytes(avr.bytes()).unwrap();
+//! ```
+
+#[cfg(feature = "alloc")]
+use core::{
+    clone::Clone,
+    cmp::{Eq, PartialEq},
+    default::Default,
+    fmt::Debug,
+    iter::Iterator,
+    marker::Copy,
+    option::Option::{self, None, Some},
+    prelude::rust_2021::derive,
+    result::Result::{self, Err, Ok},
+    write,
+};
+
+use crate::avr::AvrFrame;
+use crate::demod::DemodMeta;
+
+/// Start of a Beast message, doubled in the message
+pub const ESCAPE: u8 = 0x1a;
+
+/// Length (bytes) of the timestamp and signal level before the frame
+const HEADER_BYTES: usize = 6 + 1;
+
+/// Length (bytes) of the longest frame, a Mode S long frame
+const MAX_FRAME_BYTES: usize = 14;
+
+/// Type of a Beast message, after the first `<esc>`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum MessageType {
+    /// `'1'`, Mode A/C reply
+    ModeAC,
+    /// `'2'`, Mode S short frame
+    ModeSShort,
+    /// `'3'`, Mode S long frame
+    ModeSLong,
+}
+
+impl MessageType {
+    /// Type of the `byte` after the `<esc>`, `None` if unknown
+    #[must_use]
+    pub const fn from_byte(byte: u8) -> Option<Self> {
+        match byte {
+            b'1' => Some(Self::ModeAC),
+            b'2' => Some(Self::ModeSShort),
+            b'3' => Some(Self::ModeSLong),
+            _ => None,
+        }
+    }
+
+    /// Length (bytes) of the frame
+    #[must_use]
+    pub const fn frame_len(self) -> usize {
+        match self {
+            Self::ModeAC => 2,
+            Self::ModeSShort => 7,
+            Self::ModeSLong => 14,
+        }
+    }
+}
+
+/// Error from [`BeastDecoder::push`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum BeastError {
+    /// Message with an unknown type, such as the status messages of some receivers. The message
+    /// is skipped
+    UnknownType(u8),
+    /// Message interrupted by the `<esc>` of the next message, before the end of its frame
+    Truncated,
+}
+
+impl core::fmt::Display for BeastError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::UnknownType(t) => write!(f, "unknown beast message type: {t:#04x}"),
+            Self::Truncated => write!(f, "truncated beast message"),
+        }
+    }
+}
+
+/// Single Beast message
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct BeastFrame {
+    pub message_type: MessageType,
+    /// MLAT timestamp, 12 MHz clock
+    pub timestamp: u64,
+    /// Signal level of the frame, from 0 to 255
+    pub signal_level: u8,
+    bytes: [u8; MAX_FRAME_BYTES],
+}
+
+impl BeastFrame {
+    /// Frame bytes, the Mode A/C code of a [`MessageType::ModeAC`]
+    #[must_use]
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes[..self.message_type.frame_len()]
+    }
+
+    /// Mode S frame with the timestamp, and the signal level in [`DemodMeta::signal_level`],
+    /// `None` for a [`MessageType::ModeAC`]
+    #[must_use]
+    pub fn to_avr(&self) -> Option<AvrFrame> {
+        let meta = DemodMeta::default().with_signal_level(self.signal_level);
+        AvrFrame::new(self.bytes(), Some(self.timestamp)).ok().map(|avr| avr.with_meta(meta))
+    }
+}
+
+/// Position of a [`BeastDecoder`] in the stream
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+enum State {
+    /// Waiting for the `<esc>` of the next message
+    #[default]
+    Sync,
+    /// After the `<esc>`, waiting for the type
+    Type,
+    /// Reading the escaped data of a message, `escaped` after a single `<esc>`
+    Data { message_type: MessageType, escaped: bool },
+}
+
+/// Decoder of a stream of Beast messages, see [`self`]
+#[derive(Debug, Default, Clone)]
+pub struct BeastDecoder {
+    state: State,
+    data: [u8; HEADER_BYTES + MAX_FRAME_BYTES],
+    len: usize,
+    /// bytes were skipped since the last message
+    skipping: bool,
+    resyncs: u64,
+}
+
+impl BeastDecoder {
+    /// Decode the next byte of the stream
+    ///
+    /// Returns:
+    ///   `Some(Ok(frame))`: last byte of a message
+    ///   `Some(Err(e))`:    unknown or truncated message
+    ///   `None`:            more bytes needed
+    pub fn push(&mut self, byte: u8) -> Option<Result<BeastFrame, BeastError>> {
+        match self.state {
+            State::Sync => {
+                if byte == ESCAPE {
+                    self.state = State::Type;
+                } else if !self.skipping {
+                    self.skipping = true;
+                    self.resyncs += 1;
+                }
+                None
+            }
+            State::Type => self.start(byte),
+            State::Data { message_type, escaped } => {
+                if escaped && byte != ESCAPE {
+                    // the single <esc> started the next message, of type `byte`
+                    let _ = self.start(byte);
+                    return Some(Err(BeastError::Truncated));
+                }
+                if !escaped && byte == ESCAPE {
+                    self.state = State::Data { message_type, escaped: true };
+                    return None;
+                }
+                self.data[self.len] = byte;
+                self.len += 1;
+                self.state = State::Data { message_type, escaped: false };
+                if self.len < HEADER_BYTES + message_type.frame_len() {
+                    return None;
+                }
+                self.state = State::Sync;
+                self.skipping = false;
+                Some(Ok(self.frame(message_type)))
+            }
+        }
+    }
+
+    /// Decode the messages of `bytes`, continuing the message of the previous bytes
+    pub fn decode<'a>(
+        &'a mut self,
+        bytes: &'a [u8],
+    ) -> impl Iterator<Item = Result<BeastFrame, BeastError>> + 'a {
+        bytes.iter().filter_map(|byte| self.push(*byte))
+    }
+
+    /// Amount of times the stream was realigned on a message
+    ///
+    /// This counts the bytes skipped outside of a message (such as joining a stream in the middle
+    /// of a message), and the escaped `<esc>` seen instead of a message type.
+    #[must_use]
+    pub fn resyncs(&self) -> u64 {
+        self.resyncs
+    }
+
+    /// Start a message of type `byte`, after its `<esc>`
+    fn start(&mut self, byte: u8) -> Option<Result<BeastFrame, BeastError>> {
+        self.len = 0;
+        if byte == ESCAPE {
+            // escaped data byte of a message joined in the middle
+            self.state = State::Sync;
+            self.skipping = true;
+            self.resyncs += 1;
+            return None;
+        }
+        match MessageType::from_byte(byte) {
+            Some(message_type) => {
+                self.state = State::Data { message_type, escaped: false };
+                None
+            }
+            None => {
+                self.state = State::Sync;
+                // the data of the unknown message isn't counted as a resync
+                self.skipping = true;
+                Some(Err(BeastError::UnknownType(byte)))
+            }
+        }
+    }
+
+    fn frame(&self, message_type: MessageType) -> BeastFrame {
+        let timestamp = self.data[..6].iter().fold(0_u64, |acc, b| (acc << 8) | u64::from(*b));
+        let mut bytes = [0; MAX_FRAME_BYTES];
+        let len = message_type.frame_len();
+        bytes[..len].copy_from_slice(&self.data[HEADER_BYTES..HEADER_BYTES + len]);
+        BeastFrame { message_type, timestamp, signal_level: self.data[6], bytes }
+    }
+}
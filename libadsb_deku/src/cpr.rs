@@ -30,14 +30,19 @@ const CPR_MAX: f64 = 131_072.0;
 /// Post-processing of CPR into Latitude/Longitude
 #[derive(Debug, PartialEq, Clone, Copy)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct Position {
     pub latitude: f64,
     pub longitude: f64,
 }
 
-/// The NL function uses the precomputed table from 1090-WP-9-14
+/// Number of longitude zones for a latitude, `NL(lat)`
+///
+/// The NL function uses the precomputed table from 1090-WP-9-14 instead of evaluating the
+/// transcendental formula from ICAO 9871 (D.2.4.7.2) on every call.
 /// This code is translated from <https://github.com/wiedehopf/readsb/blob/dev/cpr.c>
-pub(crate) fn cpr_nl(lat: f64) -> u64 {
+#[must_use]
+pub fn nl(lat: f64) -> u64 {
     let mut lat = lat;
     if lat < 0.0 {
         // Table is symmetric about the equator
@@ -268,6 +273,65 @@ pub fn get_position(cpr_frames: (&Altitude, &Altitude)) -> Option<Position> {
     Some(Position { latitude: lat, longitude: lon })
 }
 
+/// Calculate Locally unambiguous position decoding
+///
+/// Using a single `Altitude` and a reference position (such as the receiver location),
+/// calculate the latitude/longitude. The reference must be within 180 NM of the aircraft for
+/// the returned position to be correct.
+///
+/// reference: ICAO 9871 (D.2.4.7.5)
+#[must_use]
+pub fn get_position_from_reference(frame: &Altitude, reference: (f64, f64)) -> Option<Position> {
+    let (lat_ref, lon_ref) = reference;
+    let (i, d_lat) = match frame.odd_flag {
+        CPRFormat::Even => (0, D_LAT_EVEN),
+        CPRFormat::Odd => (1, D_LAT_ODD),
+    };
+
+    let cpr_lat = f64::from(frame.lat_cpr) / CPR_MAX;
+    let cpr_lon = f64::from(frame.lon_cpr) / CPR_MAX;
+
+    let j = libm::floor(lat_ref / d_lat)
+        + libm::floor(0.5 + positive_mod(lat_ref, d_lat) / d_lat - cpr_lat);
+    let lat = d_lat * (j + cpr_lat);
+    if !(-90.0..=90.0).contains(&lat) {
+        return None;
+    }
+
+    let ni = cmp::max(nl(lat).saturating_sub(i), 1) as f64;
+    let d_lon = 360.0 / ni;
+    let m = libm::floor(lon_ref / d_lon)
+        + libm::floor(0.5 + positive_mod(lon_ref, d_lon) / d_lon - cpr_lon);
+    let mut lon = d_lon * (m + cpr_lon);
+    if lon >= 180.0 {
+        lon -= 360.0;
+    } else if lon < -180.0 {
+        lon += 360.0;
+    }
+
+    Some(Position { latitude: lat, longitude: lon })
+}
+
+/// Encode `position` into the 17 bit airborne (latitude, longitude) of an `Altitude`
+///
+/// reference: ICAO 9871 (D.2.4.7.3)
+#[must_use]
+pub fn encode(position: Position, format: CPRFormat) -> (u32, u32) {
+    let (i, d_lat) = match format {
+        CPRFormat::Even => (0, D_LAT_EVEN),
+        CPRFormat::Odd => (1, D_LAT_ODD),
+    };
+    let yz = libm::floor(CPR_MAX * positive_mod(position.latitude, d_lat) / d_lat + 0.5);
+    let r_lat = d_lat * (yz / CPR_MAX + libm::floor(position.latitude / d_lat));
+
+    let ni = cmp::max(nl(r_lat).saturating_sub(i), 1) as f64;
+    let d_lon = 360.0 / ni;
+    let xz = libm::floor(CPR_MAX * positive_mod(position.longitude, d_lon) / d_lon + 0.5);
+
+    let mask = CPR_MAX as u32 - 1;
+    (yz as u32 & mask, xz as u32 & mask)
+}
+
 fn positive_mod(a: f64, b: f64) -> f64 {
     let mut ret = a % b;
     if ret < 0.0 {
@@ -283,10 +347,8 @@ fn get_lat_lon(
     cpr_format: &CPRFormat,
 ) -> (f64, f64) {
     let (p, c) = if cpr_format == &CPRFormat::Even { (0, cpr_lon_even) } else { (1, cpr_lon_odd) };
-    let ni = cmp::max(cpr_nl(lat) - p, 1) as f64;
-    let m = libm::floor(
-        cpr_lon_even * (cpr_nl(lat) - 1) as f64 - cpr_lon_odd * cpr_nl(lat) as f64 + 0.5,
-    );
+    let ni = cmp::max(nl(lat) - p, 1) as f64;
+    let m = libm::floor(cpr_lon_even * (nl(lat) - 1) as f64 - cpr_lon_odd * nl(lat) as f64 + 0.5);
 
     // rem_euclid
     let r = positive_mod(m, ni);
@@ -304,10 +366,74 @@ mod tests {
 
     #[test]
     fn cpr_nl_high_low_lat() {
-        assert_eq!(cpr_nl(89.9), 1);
-        assert_eq!(cpr_nl(-89.9), 1);
-        assert_eq!(cpr_nl(86.9), 2);
-        assert_eq!(cpr_nl(-86.9), 2);
+        assert_eq!(nl(89.9), 1);
+        assert_eq!(nl(-89.9), 1);
+        assert_eq!(nl(86.9), 2);
+        assert_eq!(nl(-86.9), 2);
+    }
+
+    /// NL(lat) from the formula in ICAO 9871 (D.2.4.7.2)
+    fn nl_formula(lat: f64) -> u64 {
+        if lat == 0.0 {
+            return 59;
+        }
+        if lat.abs() == 87.0 {
+            return 2;
+        }
+        if lat.abs() > 87.0 {
+            return 1;
+        }
+        let a = 1.0 - libm::cos(core::f64::consts::PI / (2.0 * NZ));
+        let b = libm::pow(libm::cos(core::f64::consts::PI / 180.0 * lat.abs()), 2.0);
+        libm::floor(2.0 * core::f64::consts::PI / libm::acos(1.0 - a / b)) as u64
+    }
+
+    #[test]
+    fn cpr_nl_band_boundaries() {
+        // (boundary latitude, NL just below the boundary)
+        let boundaries = [
+            (10.470_471_30, 59),
+            (29.911_356_86, 52),
+            (44.194_549_51, 43),
+            (59.954_592_77, 30),
+            (74.438_934_16, 16),
+            (86.535_369_98, 3),
+            (87.0, 2),
+        ];
+        for (lat, nl_below) in boundaries {
+            assert_eq!(nl(lat - 1e-6), nl_below);
+            assert_eq!(nl(lat + 1e-6), nl_below - 1);
+            assert_eq!(nl(-lat + 1e-6), nl_below);
+            assert_eq!(nl(-lat - 1e-6), nl_below - 1);
+        }
+    }
+
+    #[test]
+    fn cpr_nl_matches_formula() {
+        let mut lat = -90.0;
+        while lat <= 90.0 {
+            assert_eq!(nl(lat), nl_formula(lat), "lat: {lat}");
+            lat += 0.001;
+        }
+    }
+
+    #[test]
+    fn cpr_encode() {
+        let position = Position { latitude: 52.257_202_148_437_5, longitude: 3.919_372_558_593_75 };
+        assert_eq!(encode(position, CPRFormat::Even), (93000, 51372));
+
+        for (latitude, longitude) in
+            [(52.2572, 3.9193), (-33.9, 151.2), (35.0, -80.0), (80.5, -170.3)]
+        {
+            let position = Position { latitude, longitude };
+            let frame = |odd_flag| {
+                let (lat_cpr, lon_cpr) = encode(position, odd_flag);
+                Altitude { odd_flag, lat_cpr, lon_cpr, ..Altitude::default() }
+            };
+            let decoded = get_position((&frame(CPRFormat::Odd), &frame(CPRFormat::Even))).unwrap();
+            assert!((decoded.latitude - latitude).abs() < 0.0001, "{decoded:?}");
+            assert!((decoded.longitude - longitude).abs() < 0.0001, "{decoded:?}");
+        }
     }
 
     #[test]
@@ -377,4 +503,29 @@ mod tests {
         );
         assert_eq!((position.longitude - 150.283_852_435_172_9).abs(), 0.0);
     }
+
+    #[test]
+    fn cpr_calculate_position_from_reference() {
+        let even = Altitude {
+            odd_flag: CPRFormat::Even,
+            lat_cpr: 93000,
+            lon_cpr: 51372,
+            ..Altitude::default()
+        };
+        let odd = Altitude {
+            odd_flag: CPRFormat::Odd,
+            lat_cpr: 74158,
+            lon_cpr: 50194,
+            ..Altitude::default()
+        };
+        let reference = (52.258, 3.918);
+
+        let position = get_position_from_reference(&even, reference).unwrap();
+        assert!((position.latitude - 52.257_202_148_437_5).abs() < f64::EPSILON);
+        assert!((position.longitude - 3.919_372_558_593_75).abs() < 1e-9);
+
+        let position = get_position_from_reference(&odd, reference).unwrap();
+        assert!((position.latitude - 52.265_780_174_126_06).abs() < 1e-9);
+        assert!((position.longitude - 3.938_912_527_901_786).abs() < 1e-9);
+    }
 }
@@ -1,7 +1,48 @@
+/*!
+Mode S parity, the CRC-24 of the frame XORed with the last 24 bits
+
+For DF11/17/18, the checksum of a frame without errors is 0. Encoders compute the parity of a
+frame with the last 24 bits set to 0:
+
+```rust
+use adsb_deku::crc::modes_checksum;
+
+let mut bytes = [0x8d, 0x40, 0x62, 0x1d, 0x58, 0xc3, 0x82, 0xd6, 0x90, 0xc8, 0xac, 0, 0, 0];
+let parity = modes_checksum(&bytes, 112).unwrap();
+bytes[11..].copy_from_slice(&parity.to_be_bytes()[1..]);
+assert_eq!(bytes[11..], [0x28, 0x63, 0xa7]);
+assert_eq!(modes_checksum(&bytes, 112).unwrap(), 0);
+```
+
+# Soft-decision repair
+
+At long range, the frames with a few wrong bits fail the checksum. Brute-force correction of 1 or
+2 bits over the whole frame quickly accepts wrong frames, but a demodulator knows which bits were
+the hardest to decide. [`soft_repair`] only tries flipping the [`SOFT_REPAIR_CANDIDATES`] least
+confident bits, such that up to [`MAX_SOFT_REPAIR_BITS`] bits are repaired:
+
+```rust
+use adsb_deku::crc::{modes_checksum, soft_repair};
+
+let valid = [0x8d, 0x40, 0x62, 0x1d, 0x58, 0xc3, 0x82, 0xd6, 0x90, 0xc8, 0xac, 0x28, 0x63, 0xa7];
+let mut bytes = valid;
+bytes[5] ^= 0b0000_0101;
+bytes[9] ^= 0b1000_0000;
+let mut confidence = [255; 112];
+for bit in [45, 47, 72] {
+    confidence[bit] = 10;
+}
+assert_eq!(soft_repair(&mut bytes, &confidence, 3), Some(3));
+assert_eq!(bytes, valid);
+```
+!*/
+
 //This module includes functionality translated from mode_s.c
 #[cfg(feature = "alloc")]
 use core::{
     convert::From,
+    iter::Iterator,
+    option::Option::{self, None, Some},
     result,
     result::Result::{Err, Ok},
 };
@@ -267,6 +308,9 @@ pub const CRC_TABLE: [u32; 256] = [
     0x00fa_0480,
 ];
 
+/// Checksum of the first `bits` of `message`, 56 for a short and 112 for a long frame
+///
+/// Returns an error if `message` is shorter than `bits`.
 pub fn modes_checksum(message: &[u8], bits: usize) -> result::Result<u32, DekuError> {
     let mut rem: u32 = 0;
     let n = bits / 8;
@@ -290,3 +334,75 @@ pub fn modes_checksum(message: &[u8], bits: usize) -> result::Result<u32, DekuEr
 
     Ok(rem)
 }
+
+/// Most bits flipped by [`soft_repair`]
+pub const MAX_SOFT_REPAIR_BITS: usize = 4;
+
+/// Least confident bits tried by [`soft_repair`]
+pub const SOFT_REPAIR_CANDIDATES: usize = 12;
+
+/// Bits of a long frame
+const LONG_FRAME_BITS: usize = 112;
+
+/// Bits of the DF field, never flipped such that a repaired frame stays a DF17/18
+const DF_BITS: usize = 5;
+
+/// Flip at most `max_bits` (and [`MAX_SOFT_REPAIR_BITS`]) of the least confident bits of a DF17/18
+/// `message` for a checksum of 0, see [`self`]
+///
+/// `confidence` of each bit of `message` is from 0 for the least confident. The fewest flipped
+/// bits are tried first, and `message` is only changed by a repair.
+///
+/// Returns the amount of flipped bits, `Some(0)` for a valid `message`, and `None` for a short
+/// frame, another DF, or without a repair.
+pub fn soft_repair(message: &mut [u8], confidence: &[u8], max_bits: usize) -> Option<u8> {
+    if message.len() != LONG_FRAME_BITS / 8
+        || confidence.len() < LONG_FRAME_BITS
+        || !matches!(message[0] >> 3, 17 | 18)
+    {
+        return None;
+    }
+    let checksum = modes_checksum(message, LONG_FRAME_BITS).ok()?;
+    if checksum == 0 {
+        return Some(0);
+    }
+
+    let mut bits: [usize; LONG_FRAME_BITS - DF_BITS] = core::array::from_fn(|i| i + DF_BITS);
+    bits.sort_unstable_by_key(|bit| (confidence[*bit], *bit));
+    let candidates = &bits[..SOFT_REPAIR_CANDIDATES];
+    let syndromes: [u32; SOFT_REPAIR_CANDIDATES] =
+        core::array::from_fn(|i| bit_syndrome(candidates[i]));
+
+    let mut flips = [0; MAX_SOFT_REPAIR_BITS];
+    for n in 1..=max_bits.min(MAX_SOFT_REPAIR_BITS) {
+        if find_flips(&syndromes, checksum, 0, &mut flips[..n]) {
+            for i in &flips[..n] {
+                let bit = candidates[*i];
+                message[bit / 8] ^= 0x80 >> (bit % 8);
+            }
+            return Some(n as u8);
+        }
+    }
+    None
+}
+
+/// Checksum of a long frame with only `bit` set, the change of the checksum when flipping `bit`
+fn bit_syndrome(bit: usize) -> u32 {
+    let mut message = [0; LONG_FRAME_BITS / 8];
+    message[bit / 8] = 0x80 >> (bit % 8);
+    modes_checksum(&message, LONG_FRAME_BITS).unwrap_or(0)
+}
+
+/// Fill `flips` with increasing indices of `syndromes` from `start`, XORing to `checksum`
+fn find_flips(syndromes: &[u32], checksum: u32, start: usize, flips: &mut [usize]) -> bool {
+    let Some((flip, rest)) = flips.split_first_mut() else {
+        return checksum == 0;
+    };
+    for (i, syndrome) in syndromes.iter().enumerate().skip(start) {
+        *flip = i;
+        if find_flips(syndromes, checksum ^ syndrome, i + 1, rest) {
+            return true;
+        }
+    }
+    false
+}
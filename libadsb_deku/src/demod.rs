@@ -0,0 +1,88 @@
+//! Pulse-level metadata from the demodulator, attached to the frames it outputs
+//!
+//! The frame bytes don't tell how well the pulses were received. A demodulator frontend (such as
+//! one decoding the IQ samples of an SDR in the same process) attaches a [`DemodMeta`] to each
+//! [`AvrFrame`], which flows with the frame to the statistics of the application, and to the
+//! soft-decision repair of [`AvrFrame::soft_repair`] with the confidence of each bit. All of its
+//! values are optional, as each demodulator has different measurements, and more are added
+//! without breaking the frontends.
+//!
+//! ```rust
+//! use adsb_deku::avr::AvrFrame;
+//! use adsb_deku::demod::DemodMeta;
+//! use adsb_deku::Frame;
+//!
+//! let bytes = [0x8d, 0x40, 0x62, 0x1d, 0x58, 0xc3, 0x82, 0xd6, 0x90, 0xc8, 0xac, 0x28, 0x63, 0xa7];
+//! let meta = DemodMeta::default().with_preamble_score(0.92).with_corrected_bits(1);
+//! let avr = AvrFrame::new(&bytes, None).unwrap().with_meta(meta);
+//! assert_eq!(avr.meta.unwrap().corrected_bits, Some(1));
+//! let frame = Frame::from_bytes(avr.bytes()).unwrap();
+//! ```
+//!
+//! [`AvrFrame`]: crate::avr::AvrFrame
+//! [`AvrFrame::soft_repair`]: crate::avr::AvrFrame::soft_repair
+
+#[cfg(feature = "alloc")]
+use core::{
+    clone::Clone,
+    cmp::PartialEq,
+    default::Default,
+    fmt::Debug,
+    marker::Copy,
+    option::Option::{self, Some},
+    prelude::rust_2021::derive,
+};
+
+/// Measurements of the demodulator for a single frame, see [`self`]
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[non_exhaustive]
+pub struct DemodMeta {
+    /// Correlation of the received preamble with the ideal pulses, from 0.0 to 1.0
+    pub preamble_score: Option<f32>,
+    /// Bits corrected by the demodulator with the CRC, 0 for a frame received without error
+    pub corrected_bits: Option<u8>,
+    /// Sampling phase of the decoded bits, in the units of the demodulator (such as the 5
+    /// phases of dump1090 at 2.4 MHz)
+    pub phase: Option<u8>,
+    /// Confidence of each bit of a long frame, from 0 for the least confident (such as the
+    /// difference of the pulse amplitudes of the two halves of the bit), see [`crate::crc::soft_repair`]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub bit_confidence: Option<[u8; 112]>,
+    /// Signal level of the frame, from 0 to 255, such as in the Beast messages, see
+    /// [`crate::beast`]
+    pub signal_level: Option<u8>,
+}
+
+impl DemodMeta {
+    #[must_use]
+    pub fn with_preamble_score(mut self, preamble_score: f32) -> Self {
+        self.preamble_score = Some(preamble_score);
+        self
+    }
+
+    #[must_use]
+    pub fn with_corrected_bits(mut self, corrected_bits: u8) -> Self {
+        self.corrected_bits = Some(corrected_bits);
+        self
+    }
+
+    #[must_use]
+    pub fn with_phase(mut self, phase: u8) -> Self {
+        self.phase = Some(phase);
+        self
+    }
+
+    #[must_use]
+    pub fn with_bit_confidence(mut self, bit_confidence: [u8; 112]) -> Self {
+        self.bit_confidence = Some(bit_confidence);
+        self
+    }
+
+    #[must_use]
+    pub fn with_signal_level(mut self, signal_level: u8) -> Self {
+        self.signal_level = Some(signal_level);
+        self
+    }
+}
@@ -21,6 +21,9 @@ See [`rsadsb.github.io`] for more details.
 | 21       | [`Comm-B Identity Reply`]           | 3.1.2.6.8   |
 | 24..=31  | [`ModeS Extended Squitter`]          | 3.1.2.7.3   |
 
+The unassigned formats 1..=3, 6..=10 and 12..=15 decode as [`Reserved Short`], 22 and 23 as
+[`Reserved Long`], keeping the bits instead of failing.
+
 # [`Comm-B Altitude Reply`] and [`Comm-B Identity Reply`] Comm-B Support
 
 |  [`BDS`]  |  Name                                   |  Table      |
@@ -89,6 +92,8 @@ different `adsb_deku` uses. See the [`README.md`] for examples of use.
 [`Comm-B Altitude Reply`]: crate::DF::CommBAltitudeReply
 [`Comm-B Identity Reply`]: crate::DF::CommBIdentityReply
 [`ModeS Extended Squitter`]: crate::DF::ModeSExtendedSquitter
+[`Reserved Short`]: crate::DF::ReservedShort
+[`Reserved Long`]: crate::DF::ReservedLong
 
 [`Empty`]: crate::bds::BDS::Empty
 [`Data Link Capability`]: crate::bds::BDS::DataLinkCapability
@@ -121,7 +126,7 @@ different `adsb_deku` uses. See the [`README.md`] for examples of use.
 extern crate alloc;
 
 #[cfg(feature = "alloc")]
-use alloc::{fmt, format, string::String, string::ToString, vec, vec::Vec};
+use alloc::{fmt, format, string::String, vec, vec::Vec};
 #[cfg(feature = "alloc")]
 use core::{
     clone::Clone,
@@ -130,7 +135,6 @@ use core::{
     default::Default,
     fmt::Debug,
     hash::Hash,
-    iter::IntoIterator,
     marker::Copy,
     prelude::rust_2021::derive,
     result,
@@ -139,189 +143,644 @@ use core::{
 };
 
 pub mod adsb;
+pub mod avr;
 pub mod bds;
+pub mod beast;
 pub mod cpr;
-mod crc;
-mod mode_ac;
+pub mod crc;
+pub mod demod;
+pub mod mode_ac;
+
+/// The `deku` version used by this crate
+///
+/// Decoding errors are a [`DekuError`], and the types of this crate implement the `deku` traits
+/// such as `DekuContainerRead`. Use this re-export instead of depending on `deku`, so the versions
+/// always match.
+pub use deku;
+pub use deku::DekuError;
 
 #[doc = include_str!("../README.md")]
 mod readme_test {}
 
-use adsb::{ControlField, ADSB};
+use adsb::{ControlField, ADSB, ME};
 use bds::BDS;
 use deku::ctx::{BitSize, Endian};
-use deku::no_std_io::{Cursor, Read, Seek};
+use deku::no_std_io::{Cursor, Read, Seek, SeekFrom, Write};
 use deku::prelude::*;
 
 /// Every read to this struct will be saved into an internal cache. This is to keep the cache
 /// around for the crc without reading from the buffer twice!
+///
+/// Only the bytes actually read are cached, so readers returning less bytes than requested
+/// (such as non-blocking sockets) are supported.
 struct ReaderCrc<R: Read + Seek> {
     reader: R,
     cache: Vec<u8>,
-    just_seeked: bool,
+    /// Amount of bytes, already in the cache, that will be read again after seeking backwards
+    skip: usize,
+}
+
+impl<R: Read + Seek> ReaderCrc<R> {
+    fn new(reader: R) -> Self {
+        Self { reader, cache: vec![], skip: 0 }
+    }
 }
 
 impl<R: Read + Seek> Read for ReaderCrc<R> {
     fn read(&mut self, buf: &mut [u8]) -> deku::no_std_io::Result<usize> {
-        let n = self.reader.read(buf);
-        if !self.just_seeked {
-            if let Ok(n) = n {
-                self.cache.extend_from_slice(&buf[..n]);
-            }
-        }
-        self.just_seeked = false;
-        n
+        let n = self.reader.read(buf)?;
+        let skipped = n.min(self.skip);
+        self.skip -= skipped;
+        self.cache.extend_from_slice(&buf[skipped..n]);
+        Ok(n)
     }
 }
 
 impl<R: Read + Seek> Seek for ReaderCrc<R> {
-    fn seek(&mut self, pos: deku::no_std_io::SeekFrom) -> deku::no_std_io::Result<u64> {
-        self.just_seeked = true;
-        self.reader.seek(pos)
+    fn seek(&mut self, pos: SeekFrom) -> deku::no_std_io::Result<u64> {
+        let n = self.reader.seek(pos)?;
+        if let SeekFrom::Current(offset) = pos {
+            if offset < 0 {
+                self.skip += offset.unsigned_abs() as usize;
+            }
+        }
+        Ok(n)
     }
 }
 
+/// Length (bytes) of a Mode S long frame
+const MODES_LONG_MSG_BYTES: usize = 14;
+
+/// Length (bytes) of a Mode S short frame
+const MODES_SHORT_MSG_BYTES: usize = 7;
+
 /// Downlink ADS-B Packet
-#[derive(Debug)]
+///
+/// Frames are equal if their [`Self::df`] and [`Self::crc`] are, whatever the bytes of
+/// [`Self::raw`].
+#[derive(Debug, Clone)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct Frame {
     /// Starting with 5 bit identifier, decode packet
     pub df: DF,
     /// Calculated from all bits, used as ICAO for Response packets
     pub crc: u32,
+    /// Bytes of the frame, as read, see [`Self::raw`]
+    raw: [u8; MODES_LONG_MSG_BYTES],
+    raw_len: u8,
+}
+
+impl PartialEq for Frame {
+    fn eq(&self, other: &Self) -> bool {
+        self.df == other.df && self.crc == other.crc
+    }
 }
 
 impl Frame {
+    /// New frame of `df` with the parity of `crc`, without any [`Self::raw`] bytes
+    ///
+    /// Such as to be encoded with [`Self::to_bytes`].
+    #[must_use]
+    pub fn new(df: DF, crc: u32) -> Self {
+        Self { df, crc, raw: [0; MODES_LONG_MSG_BYTES], raw_len: 0 }
+    }
+
+    /// Frame of `df` and `crc`, with the `raw` bytes it was decoded from
+    fn with_raw(df: DF, crc: u32, raw: &[u8]) -> Self {
+        let mut frame = Self::new(df, crc);
+        let len = raw.len().min(MODES_LONG_MSG_BYTES);
+        frame.raw[..len].copy_from_slice(&raw[..len]);
+        frame.raw_len = len as u8;
+        frame
+    }
+
     pub fn from_bytes(buf: &[u8]) -> Result<Frame, DekuError> {
         let cursor = Cursor::new(buf);
         Self::from_reader(cursor)
     }
 
     pub fn from_reader<R: Read + Seek>(r: R) -> Result<Frame, DekuError> {
-        let mut reader_crc = ReaderCrc { reader: r, cache: vec![], just_seeked: false };
+        let mut reader_crc = ReaderCrc::new(r);
         let mut reader = Reader::new(&mut reader_crc);
         let df = DF::from_reader_with_ctx(&mut reader, ())?;
 
         let crc = Self::read_crc(&df, &mut reader_crc)?;
 
-        Ok(Self { df, crc })
+        Ok(Self::with_raw(df, crc, &reader_crc.cache))
+    }
+
+    /// Decode a frame directly from `buf`, without the buffering of [`Self::from_reader`]
+    ///
+    /// This returns the same `Frame`, or the same kind of error, as [`Self::from_bytes`]. Bytes
+    /// after the frame are ignored.
+    pub fn from_slice(buf: &[u8]) -> Result<Frame, DekuError> {
+        // not `DF::from_bytes`, which panics instead of returning an error when padding goes
+        // past the end of a truncated frame
+        let mut cursor = Cursor::new(buf);
+        let mut reader = Reader::new(&mut cursor);
+        let df = DF::from_reader_with_ctx(&mut reader, ())?;
+        let byte_len = Self::byte_len(&df);
+        let raw = &buf[..byte_len.min(buf.len())];
+        let crc = crc::modes_checksum(raw, byte_len * 8)?;
+
+        Ok(Self::with_raw(df, crc, raw))
+    }
+
+    /// Bytes of the frame, as read, empty for a frame of [`Self::new`]
+    #[must_use]
+    pub fn raw(&self) -> &[u8] {
+        &self.raw[..usize::from(self.raw_len)]
+    }
+
+    /// [`Self::raw`] in lowercase hex, such as for an AVR line or comparing with other decoders
+    ///
+    /// ```rust
+    /// use adsb_deku::Frame;
+    ///
+    /// let bytes = [0x8d, 0x40, 0x62, 0x1d, 0x58, 0xc3, 0x82, 0xd6, 0x90, 0xc8, 0xac, 0x28, 0x63, 0xa7];
+    /// let frame = Frame::from_bytes(&bytes).unwrap();
+    /// assert_eq!(frame.to_hex(), "8d40621d58c382d690c8ac2863a7");
+    /// ```
+    #[must_use]
+    pub fn to_hex(&self) -> String {
+        use core::fmt::Write;
+
+        let mut hex = String::with_capacity(self.raw().len() * 2);
+        for byte in self.raw() {
+            let _ = write!(hex, "{byte:02x}");
+        }
+        hex
+    }
+
+    /// Encode [`Self::df`] into the bytes of a short or long frame, with the parity of [`Self::crc`]
+    ///
+    /// The parity field is overlaid such that the frame decodes with the same [`Self::crc`]: zero
+    /// for a valid DF17/18 frame, or the aircraft address of a DF0/4/5/16/20/21 frame, such as
+    /// of a new frame of [`Self::new`].
+    ///
+    /// Values with more than one encoding, such as a gillham coded altitude that is also a
+    /// multiple of 25 ft, are written in a single encoding and the reserved or undecoded bits are
+    /// zero, so the bytes may differ from [`Self::raw`] while decoding to the same values.
+    ///
+    /// ```rust
+    /// use adsb_deku::Frame;
+    ///
+    /// let bytes = [0x8d, 0x40, 0x62, 0x1d, 0x58, 0xc3, 0x82, 0xd6, 0x90, 0xc8, 0xac, 0x28, 0x63, 0xa7];
+    /// let frame = Frame::from_bytes(&bytes).unwrap();
+    /// assert_eq!(frame.to_bytes().unwrap(), bytes);
+    /// ```
+    pub fn to_bytes(&self) -> Result<Vec<u8>, DekuError> {
+        // not `DF::to_bytes`, the `Vec` writer of `no_std_io` doesn't grow without `std`. DF19
+        // only contains the AF field, the rest of the frame stays zero
+        let byte_len = Self::byte_len(&self.df);
+        let mut bytes = vec![0; byte_len];
+        self.df.to_slice(&mut bytes)?;
+
+        let parity = crc::modes_checksum(&bytes, byte_len * 8)? ^ self.crc;
+        for (byte, parity) in bytes[byte_len - 3..].iter_mut().zip(&parity.to_be_bytes()[1..]) {
+            *byte ^= parity;
+        }
+        Ok(bytes)
+    }
+}
+
+impl Frame {
+    /// Calculate the airborne position of this single frame using locally unambiguous decoding
+    ///
+    /// `receiver` is the (latitude, longitude) of a reference position, such as the receiver,
+    /// which must be within 180 NM of the aircraft. This does not need any previous frames, and
+    /// returns `None` if this frame doesn't contain an airborne position.
+    ///
+    /// reference: ICAO 9871 (D.2.4.7.5)
+    #[must_use]
+    pub fn position_with_reference(&self, receiver: (f64, f64)) -> Option<cpr::Position> {
+        let me = match &self.df {
+            DF::ADSB(ADSB { me, .. }) | DF::TisB { cf: ControlField { me, .. }, .. } => me,
+            _ => return None,
+        };
+        match me {
+            ME::AirbornePositionBaroAltitude(altitude)
+            | ME::AirbornePositionGNSSAltitude(altitude) => {
+                cpr::get_position_from_reference(altitude, receiver)
+            }
+            _ => None,
+        }
+    }
+
+    /// Aircraft address of the AA field of DF11/17/18
+    ///
+    /// For DF18 (TIS-B/ADS-R and non-transponder ADS-B), this is the address announced in the
+    /// control field: unlike DF0/4/5/16/20/21, the parity isn't overlaid with the address. Returns
+    /// `None` for other DFs, see [`Self::icao_from_parity`].
+    ///
+    /// ```rust
+    /// use adsb_deku::{Frame, ICAO};
+    ///
+    /// let bytes = [0x90, 0x40, 0x62, 0x1d, 0x20, 0x2c, 0xc3, 0x71, 0xc3, 0x2c, 0xe0, 0x53, 0x0a, 0xa9];
+    /// let frame = Frame::from_bytes(&bytes).unwrap();
+    /// assert_eq!(frame.announced_icao(), Some(ICAO([0x40, 0x62, 0x1d])));
+    /// ```
+    #[must_use]
+    pub fn announced_icao(&self) -> Option<ICAO> {
+        match &self.df {
+            DF::AllCallReply { icao, .. } => Some(*icao),
+            DF::ADSB(adsb) => Some(adsb.icao),
+            DF::TisB { cf, .. } => Some(cf.aa),
+            _ => None,
+        }
+    }
+
+    /// Recover the aircraft address from the Address/Parity field
+    ///
+    /// For DF0/4/5/16/20/21, the parity is overlaid with the aircraft address, so the CRC
+    /// residual ([`Self::crc`]) is the address. Since a corrupted frame also results in an
+    /// address, this is only returned if `lookup` contains the address, such as the addresses of
+    /// recently seen DF11/17/18 frames.
+    ///
+    /// Returns `None` for other DFs, where the address is available in the frame itself.
+    #[must_use]
+    pub fn icao_from_parity(&self, lookup: &(impl IcaoLookup + ?Sized)) -> Option<ICAO> {
+        match self.df {
+            DF::ShortAirAirSurveillance { .. }
+            | DF::SurveillanceAltitudeReply { .. }
+            | DF::SurveillanceIdentityReply { .. }
+            | DF::LongAirAir { .. }
+            | DF::CommBAltitudeReply { .. }
+            | DF::CommBIdentityReply { .. } => {
+                let icao = ICAO::from(self.crc);
+                lookup.contains(icao).then_some(icao)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Lookup of known aircraft addresses, used to validate [`Frame::icao_from_parity`]
+pub trait IcaoLookup {
+    /// Return true if `icao` is a known aircraft address
+    fn contains(&self, icao: ICAO) -> bool;
+}
+
+impl IcaoLookup for [ICAO] {
+    fn contains(&self, icao: ICAO) -> bool {
+        <[ICAO]>::contains(self, &icao)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl IcaoLookup for alloc::collections::BTreeSet<ICAO> {
+    fn contains(&self, icao: ICAO) -> bool {
+        alloc::collections::BTreeSet::contains(self, &icao)
+    }
+}
+
+/// Set of accepted Downlink Formats, checked against the first byte of a frame before decoding
+///
+/// Parsed from a comma separated list of formats or inclusive ranges, such as `0,4,5,11,16-21`.
+/// Note that the first 5 bits of [`DF::ModeSExtendedSquitter`] frames are 24 to 31.
+///
+/// ```rust
+/// use adsb_deku::DfFilter;
+///
+/// let filter: DfFilter = "17,18".parse().unwrap();
+/// assert!(filter.accepts(&[0x8d, 0xa2, 0xc1, 0xbd]));
+/// assert!(!filter.accepts(&[0x5d, 0xab, 0x3d, 0x17]));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct DfFilter(u32);
+
+impl DfFilter {
+    /// Accept every Downlink Format
+    #[must_use]
+    pub const fn all() -> Self {
+        Self(u32::MAX)
+    }
+
+    /// Accept no Downlink Format
+    #[must_use]
+    pub const fn none() -> Self {
+        Self(0)
+    }
+
+    /// Also accept `df`, ignoring the bits above the 5 bit DF field
+    #[must_use]
+    pub const fn with(self, df: u8) -> Self {
+        Self(self.0 | (1 << (df & 0b1_1111)))
+    }
+
+    /// Return true if `df` is accepted
+    #[must_use]
+    pub const fn contains(&self, df: u8) -> bool {
+        df <= 0b1_1111 && self.0 & (1 << df) != 0
+    }
+
+    /// Return true if the Downlink Format of the frame `bytes` is accepted, false if empty
+    #[must_use]
+    pub fn accepts(&self, bytes: &[u8]) -> bool {
+        bytes.first().is_some_and(|first| self.contains(first >> 3))
+    }
+}
+
+impl Default for DfFilter {
+    fn default() -> Self {
+        Self::all()
+    }
+}
+
+/// Error from parsing a [`DfFilter`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DfFilterError {
+    /// Entry isn't a number or range of numbers
+    InvalidNumber,
+    /// Downlink Format larger than 31
+    OutOfRange(u32),
+    /// Range with a start after its end, such as `21-16`
+    ReversedRange(u8, u8),
+}
+
+impl fmt::Display for DfFilterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidNumber => {
+                write!(f, "expected downlink format or range, such as 17 or 16-21")
+            }
+            Self::OutOfRange(df) => write!(f, "downlink format out of range (0-31): {df}"),
+            Self::ReversedRange(start, end) => {
+                write!(f, "downlink format range start after its end: {start}-{end}")
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for DfFilterError {}
+
+impl core::str::FromStr for DfFilter {
+    type Err = DfFilterError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        fn df(s: &str) -> Result<u8, DfFilterError> {
+            // wider than the DF, such that 300 is out of range instead of an invalid number
+            let df: u32 = s.trim().parse().map_err(|_| DfFilterError::InvalidNumber)?;
+            match u8::try_from(df) {
+                Ok(df) if df <= 0b1_1111 => Ok(df),
+                _ => Err(DfFilterError::OutOfRange(df)),
+            }
+        }
+
+        let mut filter = Self::none();
+        for entry in s.split(',') {
+            let (start, end) = match entry.split_once('-') {
+                Some((start, end)) => (df(start)?, df(end)?),
+                None => (df(entry)?, df(entry)?),
+            };
+            if start > end {
+                return Err(DfFilterError::ReversedRange(start, end));
+            }
+            for df in start..=end {
+                filter = filter.with(df);
+            }
+        }
+        Ok(filter)
     }
 }
 
 impl Frame {
     /// Read rest as CRC bits
+    ///
+    /// Only the bytes of this frame are read, returning an error instead of panicking if the
+    /// reader fails
     fn read_crc<R: Read + Seek>(
         df: &DF,
         reader: &mut ReaderCrc<R>,
     ) -> result::Result<u32, DekuError> {
-        const MODES_LONG_MSG_BYTES: usize = 14;
-        const MODES_SHORT_MSG_BYTES: usize = 7;
-
-        let bit_len = if let Ok(id) = df.deku_id() {
-            if id & 0x10 != 0 {
-                MODES_LONG_MSG_BYTES * 8
-            } else {
-                MODES_SHORT_MSG_BYTES * 8
+        let byte_len = Self::byte_len(df);
+        let mut buf = [0; MODES_LONG_MSG_BYTES];
+        while reader.cache.len() < byte_len {
+            let remaining = byte_len - reader.cache.len();
+            match reader.read(&mut buf[..remaining]) {
+                // end of reader, checksum will return an error for the short frame
+                Ok(0) => break,
+                Ok(_) => (),
+                Err(e) => return Err(DekuError::Io(e.kind())),
             }
-        } else {
-            // In this case, it's the DF::CommD, which has multiple ids
-            MODES_LONG_MSG_BYTES * 8
-        };
-
-        if bit_len > reader.cache.len() * 8 {
-            let mut buf = vec![];
-            reader.read_to_end(&mut buf).unwrap();
-            reader.cache.append(&mut buf);
         }
 
-        let crc = crc::modes_checksum(&reader.cache, bit_len)?;
+        let crc = crc::modes_checksum(&reader.cache, byte_len * 8)?;
         Ok(crc)
     }
+
+    /// Length (bytes) of the frame of `df`, short or long
+    fn byte_len(df: &DF) -> usize {
+        match df {
+            DF::ReservedShort { .. } => MODES_SHORT_MSG_BYTES,
+            DF::ModeSExtendedSquitter { .. } | DF::ReservedLong { .. } => MODES_LONG_MSG_BYTES,
+            _ => match df.deku_id() {
+                Ok(id) if id & 0x10 == 0 => MODES_SHORT_MSG_BYTES,
+                _ => MODES_LONG_MSG_BYTES,
+            },
+        }
+    }
 }
 
-impl fmt::Display for Frame {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+impl Frame {
+    /// `Display` of the frame with the barometric altitudes at or above `transition_altitude`
+    /// (ft) as flight levels, see [`AltitudeDisplay`]
+    ///
+    /// ```rust
+    /// use adsb_deku::Frame;
+    ///
+    /// let bytes = [0x8d, 0x40, 0x62, 0x1d, 0x58, 0xc3, 0x82, 0xd6, 0x90, 0xc8, 0xac, 0x28, 0x63, 0xa7];
+    /// let frame = Frame::from_bytes(&bytes).unwrap();
+    /// assert!(frame.to_string().contains("  Altitude:      38000 ft barometric"));
+    /// let display = frame.display_flight_levels(18000).to_string();
+    /// assert!(display.contains("  Altitude:      FL380 barometric"));
+    /// ```
+    #[must_use]
+    pub fn display_flight_levels(&self, transition_altitude: i32) -> FlightLevelDisplay<'_> {
+        FlightLevelDisplay { frame: self, transition_altitude }
+    }
+
+    fn fmt_with(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+        transition_altitude: Option<i32>,
+    ) -> fmt::Result {
+        let baro = |altitude| baro_altitude(altitude, transition_altitude);
         let crc = self.crc;
         match &self.df {
-            DF::ShortAirAirSurveillance { altitude, .. } => {
+            DF::ShortAirAirSurveillance { altitude, ri, .. } => {
                 writeln!(f, " Short Air-Air Surveillance")?;
                 writeln!(f, "  ICAO Address:  {crc:06x} (Mode S / ADS-B)")?;
-                if altitude.0 > 0 {
-                    let altitude = altitude.0;
+                if let Some(altitude) = altitude.0 {
                     writeln!(f, "  Air/Ground:    airborne?")?;
-                    writeln!(f, "  Altitude:      {altitude} ft barometric")?;
+                    writeln!(f, "  Altitude:      {} barometric", baro(altitude))?;
                 } else {
                     writeln!(f, "  Air/Ground:    ground")?;
                 }
+                writeln!(f, "  Reply info:    {ri}")?;
             }
-            DF::SurveillanceAltitudeReply { fs, ac, .. } => {
+            DF::SurveillanceAltitudeReply { fs, dr, um, ac, .. } => {
                 writeln!(f, " Surveillance, Altitude Reply")?;
                 writeln!(f, "  ICAO Address:  {crc:06x} (Mode S / ADS-B)")?;
                 writeln!(f, "  Air/Ground:    {fs}")?;
-                if ac.0 > 0 {
-                    let altitude = ac.0;
-                    writeln!(f, "  Altitude:      {altitude} ft barometric")?;
+                if let Some(altitude) = ac.0 {
+                    writeln!(f, "  Altitude:      {} barometric", baro(altitude))?;
                 }
+                fmt_downlink(f, "  ", *dr, *um)?;
             }
-            DF::SurveillanceIdentityReply { fs, id, .. } => {
+            DF::SurveillanceIdentityReply { fs, dr, um, id, .. } => {
                 let identity = id.0;
                 writeln!(f, " Surveillance, Identity Reply")?;
                 writeln!(f, "  ICAO Address:  {crc:06x} (Mode S / ADS-B)")?;
                 writeln!(f, "  Air/Ground:    {fs}")?;
                 writeln!(f, "  Identity:      {identity:04x}")?;
+                fmt_downlink(f, "  ", *dr, *um)?;
             }
             DF::AllCallReply { capability, icao, .. } => {
                 writeln!(f, " All Call Reply")?;
                 writeln!(f, "  ICAO Address:  {icao} (Mode S / ADS-B)")?;
                 writeln!(f, "  Air/Ground:    {capability}")?;
             }
-            DF::LongAirAir { altitude, .. } => {
+            DF::LongAirAir { altitude, ri, .. } => {
                 writeln!(f, " Long Air-Air ACAS")?;
                 writeln!(f, "  ICAO Address:  {crc:06x} (Mode S / ADS-B)")?;
                 // TODO the airborne? should't be static
-                if altitude.0 > 0 {
-                    let altitude = altitude.0;
+                if let Some(altitude) = altitude.0 {
                     writeln!(f, "  Air/Ground:    airborne?")?;
-                    writeln!(f, "  Baro altitude: {altitude} ft")?;
+                    writeln!(f, "  Baro altitude: {}", baro(altitude))?;
                 } else {
                     writeln!(f, "  Air/Ground:    ground")?;
                 }
+                writeln!(f, "  Reply info:    {ri}")?;
             }
             DF::ADSB(adsb) => {
-                write!(f, "{}", adsb.to_string("(Mode S / ADS-B)")?)?;
+                write!(f, "{}", adsb.to_string("(Mode S / ADS-B)", transition_altitude)?)?;
             }
             DF::TisB { cf, .. } => {
-                write!(f, "{cf}")?;
+                write!(f, "{}", cf.to_string(transition_altitude)?)?;
             }
             // TODO
             DF::ExtendedQuitterMilitaryApplication { .. } => {}
-            DF::CommBAltitudeReply { bds, alt, .. } => {
+            DF::CommBAltitudeReply { bds, dr, um, alt, .. } => {
                 writeln!(f, " Comm-B, Altitude Reply")?;
                 writeln!(f, "  ICAO Address:  {crc:x?} (Mode S / ADS-B)")?;
-                let altitude = alt.0;
-                writeln!(f, "  Altitude:      {altitude} ft")?;
+                if let Some(altitude) = alt.0 {
+                    writeln!(f, "  Altitude:      {}", baro(altitude))?;
+                }
+                fmt_downlink(f, "  ", *dr, *um)?;
                 write!(f, "  {bds}")?;
             }
-            DF::CommBIdentityReply { id, bds, .. } => {
+            DF::CommBIdentityReply { id, dr, um, bds, .. } => {
                 writeln!(f, " Comm-B, Identity Reply")?;
                 writeln!(f, "    ICAO Address:  {crc:x?} (Mode S / ADS-B)")?;
                 writeln!(f, "    Squawk:        {id:x?}")?;
+                fmt_downlink(f, "    ", *dr, *um)?;
                 write!(f, "    {bds}")?;
             }
             DF::ModeSExtendedSquitter { .. } => {
                 writeln!(f, " Mode S Extended Squitter Message")?;
                 writeln!(f, "    ICAO Address:     {crc:x?} (Mode S / ADS-B)")?;
             }
+            DF::ReservedShort { df, .. } | DF::ReservedLong { df, .. } => {
+                writeln!(f, " Reserved Downlink Format {df}")?;
+                writeln!(f, "  ICAO Address:  {crc:06x} (Mode S / ADS-B)")?;
+            }
         }
         Ok(())
     }
 }
 
+impl fmt::Display for Frame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_with(f, None)
+    }
+}
+
+/// `Display` of a [`Frame`] with flight levels, see [`Frame::display_flight_levels`]
+#[derive(Debug, Clone, Copy)]
+pub struct FlightLevelDisplay<'a> {
+    frame: &'a Frame,
+    transition_altitude: i32,
+}
+
+impl fmt::Display for FlightLevelDisplay<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.frame.fmt_with(f, Some(self.transition_altitude))
+    }
+}
+
+/// Altitude (ft) displayed as a flight level at or above the transition altitude (ft), such as
+/// `FL350`, and in feet below, such as `3000 ft`
+///
+/// ```rust
+/// use adsb_deku::AltitudeDisplay;
+///
+/// assert_eq!(AltitudeDisplay::new(35000, 18000).to_string(), "FL350");
+/// assert_eq!(AltitudeDisplay::new(3000, 18000).to_string(), "3000 ft");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AltitudeDisplay {
+    pub altitude: i32,
+    pub transition_altitude: i32,
+}
+
+impl AltitudeDisplay {
+    #[must_use]
+    pub const fn new(altitude: i32, transition_altitude: i32) -> Self {
+        Self { altitude, transition_altitude }
+    }
+
+    /// Flight level (hundreds of feet), `None` below the transition altitude
+    #[must_use]
+    pub const fn flight_level(self) -> Option<i32> {
+        if self.altitude >= self.transition_altitude {
+            Some(self.altitude / 100)
+        } else {
+            None
+        }
+    }
+}
+
+impl fmt::Display for AltitudeDisplay {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.flight_level() {
+            Some(flight_level) => write!(f, "FL{flight_level:03}"),
+            None => write!(f, "{} ft", self.altitude),
+        }
+    }
+}
+
+/// Barometric `altitude` of the `Display` of [`Frame`], in feet without a `transition_altitude`
+pub(crate) fn baro_altitude(altitude: i32, transition_altitude: Option<i32>) -> AltitudeDisplay {
+    AltitudeDisplay::new(altitude, transition_altitude.unwrap_or(i32::MAX))
+}
+
+/// DR and UM lines of the `Display` of [`Frame`], only with a request or interrogator
+fn fmt_downlink(
+    f: &mut fmt::Formatter<'_>,
+    indent: &str,
+    dr: DownlinkRequest,
+    um: UtilityMessage,
+) -> fmt::Result {
+    if dr != DownlinkRequest::None {
+        writeln!(f, "{indent}Downlink req:  {dr}")?;
+    }
+    if um.iis != 0 || um.ids != UtilityMessageType::NoInformation {
+        writeln!(f, "{indent}Interrogator:  {um}")?;
+    }
+    Ok(())
+}
+
 /// Downlink Format (3.1.2.3.2.1.2)
 ///
 /// Starting with 5 bits, decode the rest of the message as the correct data packets
-#[derive(Debug, PartialEq, DekuRead, Clone)]
+#[derive(Debug, PartialEq, DekuRead, DekuWrite, Clone)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 #[deku(id_type = "u8", bits = "5")]
 pub enum DF {
     /// 17: Extended Squitter, Downlink Format 17 (3.1.2.8.6)
@@ -360,8 +819,12 @@ pub enum DF {
         #[deku(bits = "2")]
         unused1: u8,
         /// RI: Reply Information
-        #[deku(bits = "4")]
-        ri: u8,
+        #[deku(
+            bits = "4",
+            map = "|ri: u8| -> Result<_, DekuError> { Ok(ReplyInformation::from(ri)) }",
+            writer = "u8::from(*ri).to_writer(deku::writer, BitSize(4))"
+        )]
+        ri: ReplyInformation,
         /// Spare
         #[deku(bits = "2")]
         unused2: u8,
@@ -412,15 +875,19 @@ pub enum DF {
         sl: u8,
         #[deku(bits = "2")]
         spare2: u8,
-        #[deku(bits = "4")]
-        ri: u8,
+        /// RI: Reply Information
+        #[deku(
+            bits = "4",
+            map = "|ri: u8| -> Result<_, DekuError> { Ok(ReplyInformation::from(ri)) }",
+            writer = "u8::from(*ri).to_writer(deku::writer, BitSize(4))"
+        )]
+        ri: ReplyInformation,
         #[deku(bits = "2")]
         spare3: u8,
         /// AC: altitude code
         altitude: AC13Field,
         /// MV: message, acas
-        #[deku(count = "7")]
-        mv: Vec<u8>,
+        mv: [u8; 7],
         /// AP: address, parity
         parity: ICAO,
     },
@@ -458,6 +925,8 @@ pub enum DF {
         alt: AC13Field,
         /// MB Message, Comm-B
         bds: BDS,
+        /// AP address/parity
+        parity: ICAO,
     },
 
     /// 21: COMM-B Reply, Downlink Format 21 (3.1.2.6.8)
@@ -473,7 +942,8 @@ pub enum DF {
         #[deku(
             bits = "13",
             endian = "big",
-            map = "|squawk: u32| -> Result<_, DekuError> {Ok(mode_ac::decode_id13_field(squawk))}"
+            map = "|squawk: u32| -> Result<_, DekuError> {Ok(mode_ac::decode_id13_field(squawk))}",
+            writer = "mode_ac::encode_id13_field(*id).to_writer(deku::writer, (Endian::Big, BitSize(13)))"
         )]
         id: u32,
         /// MB Message, Comm-B
@@ -509,19 +979,45 @@ pub enum DF {
 
         parity: ICAO,
     },
+
+    /// 1-3, 6-10, 12-15: Short frames of the Downlink Formats not assigned by ICAO 9871, instead of
+    /// a decoding error
+    #[deku(id_pat = "1..=3 | 6..=10 | 12..=15")]
+    ReservedShort {
+        #[deku(bits = 5)]
+        df: u8,
+        #[deku(bits = 27, endian = "big")]
+        data: u32,
+        /// AP: address, parity
+        parity: ICAO,
+    },
+
+    /// 22-23: Long frames of the Downlink Formats not assigned by ICAO 9871 (22 is for military
+    /// use), instead of a decoding error
+    #[deku(id_pat = "22..=23")]
+    ReservedLong {
+        #[deku(bits = 5)]
+        df: u8,
+        #[deku(bits = 83, endian = "big")]
+        data: u128,
+        /// AP: address, parity
+        parity: ICAO,
+    },
 }
 
 /// Latitude, Longitude and Altitude information
-#[derive(Debug, PartialEq, Eq, DekuRead, Default, Copy, Clone)]
+#[derive(Debug, PartialEq, Eq, DekuRead, DekuWrite, Default, Copy, Clone)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct Altitude {
     #[deku(bits = "5")]
     pub tc: u8,
     pub ss: SurveillanceStatus,
     #[deku(bits = "1")]
     pub saf_or_imf: u8,
-    #[deku(reader = "Self::read(deku::reader)")]
-    pub alt: Option<u16>,
+    /// Barometric altitude in feet, `None` if the altitude is invalid or not available
+    #[deku(reader = "Self::read(deku::reader)", writer = "Self::write(deku::writer, *alt)")]
+    pub alt: Option<i32>,
     /// UTC sync or not
     #[deku(bits = "1")]
     pub t: bool,
@@ -535,10 +1031,26 @@ pub struct Altitude {
 
 impl fmt::Display for Altitude {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let altitude = self
-            .alt
-            .map_or_else(|| "None".to_string(), |altitude| format!("{altitude} ft barometric"));
-        writeln!(f, "  Altitude:      {altitude}")?;
+        self.write_fields(f, None)
+    }
+}
+
+impl Altitude {
+    /// Lines of the `Display`, with the barometric altitude as a flight level at or above
+    /// `transition_altitude`, see [`Frame::display_flight_levels`]
+    pub(crate) fn write_fields(
+        &self,
+        f: &mut impl fmt::Write,
+        transition_altitude: Option<i32>,
+    ) -> fmt::Result {
+        match self.alt {
+            Some(altitude) => writeln!(
+                f,
+                "  Altitude:      {} barometric",
+                baro_altitude(altitude, transition_altitude)
+            )?,
+            None => writeln!(f, "  Altitude:      None")?,
+        }
         writeln!(f, "  CPR type:      Airborne")?;
         writeln!(f, "  CPR odd flag:  {}", self.odd_flag)?;
         writeln!(f, "  CPR latitude:  ({})", self.lat_cpr)?;
@@ -548,64 +1060,121 @@ impl fmt::Display for Altitude {
 }
 
 impl Altitude {
+    /// Navigation Integrity Category of the type code, `None` for a type code without a position
+    ///
+    /// The NIC supplements select between two values for TC 11 (8 or 9) and TC 16 (2 or 3), the
+    /// lower value is returned (DO-260B 2.2.3.2.7.2.6).
+    #[must_use]
+    pub const fn nic(&self) -> Option<u8> {
+        match self.tc {
+            9 | 20 => Some(11),
+            10 | 21 => Some(10),
+            11 => Some(8),
+            12 => Some(7),
+            13 => Some(6),
+            14 => Some(5),
+            15 => Some(4),
+            16 => Some(2),
+            17 => Some(1),
+            18 | 22 => Some(0),
+            _ => None,
+        }
+    }
+
     /// `decodeAC12Field`
-    fn read<R: Read + Seek>(reader: &mut Reader<R>) -> Result<Option<u16>, DekuError> {
+    fn read<R: Read + Seek>(reader: &mut Reader<R>) -> Result<Option<i32>, DekuError> {
         let num = u32::from_reader_with_ctx(reader, (Endian::Big, BitSize(12)))?;
+
+        // altitude information unavailable
+        if num == 0 {
+            return Ok(None);
+        }
+
         let q = num & 0x10;
 
         if q > 0 {
+            // 25 ft increments, starting at -1000 ft
             let n = ((num & 0x0fe0) >> 1) | (num & 0x000f);
-            let n = n * 25;
-            if n > 1000 {
-                // TODO: maybe replace with Result->Option
-                Ok(u16::try_from(n - 1000).ok())
-            } else {
-                Ok(None)
-            }
+            Ok(Some(n as i32 * 25 - 1000))
         } else {
             let mut n = ((num & 0x0fc0) << 1) | (num & 0x003f);
             n = mode_ac::decode_id13_field(n);
-            if let Ok(n) = mode_ac::mode_a_to_mode_c(n) {
-                Ok(u16::try_from(n * 100).ok())
-            } else {
-                Ok(None)
-            }
+            Ok(mode_ac::mode_a_to_mode_c(n).ok().map(|n| n * 100))
         }
     }
+
+    /// Reverse of [`Self::read`], in 25 ft increments if possible
+    fn write<W: Write + Seek>(writer: &mut Writer<W>, alt: Option<i32>) -> Result<(), DekuError> {
+        let num = match alt {
+            None => 0,
+            Some(alt) => match altitude_q(alt) {
+                Some(n) => ((n & 0x07f0) << 1) | 0x10 | (n & 0x000f),
+                None => {
+                    let n = altitude_gillham(alt)?;
+                    ((n & 0x1f80) >> 1) | (n & 0x003f)
+                }
+            },
+        };
+        num.to_writer(writer, (Endian::Big, BitSize(12)))
+    }
+}
+
+/// 11 bit value of `altitude` (ft) in 25 ft increments, starting at -1000 ft, as used with the Q
+/// bit of an AC12 or AC13 field
+fn altitude_q(altitude: i32) -> Option<u32> {
+    let n = altitude.checked_add(1000)?;
+    (n % 25 == 0 && (0..=0x7ff * 25).contains(&n)).then_some((n / 25) as u32)
+}
+
+/// 13 bit ID/AC field of the gillham code of `altitude` (ft)
+fn altitude_gillham(altitude: i32) -> Result<u32, DekuError> {
+    if altitude % 100 != 0 {
+        return Err(DekuError::InvalidParam("altitude isn't a multiple of 100 ft".into()));
+    }
+    mode_ac::mode_c_to_mode_a(altitude / 100)
+        .map(mode_ac::encode_id13_field)
+        .map_err(|e| DekuError::InvalidParam(e.into()))
 }
 
 /// SPI Condition
-#[derive(Debug, PartialEq, Eq, DekuRead, Copy, Clone)]
+#[derive(Debug, PartialEq, Eq, DekuRead, DekuWrite, Copy, Clone, Default)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 #[deku(id_type = "u8", bits = "2")]
 pub enum SurveillanceStatus {
+    #[default]
     NoCondition = 0,
     PermanentAlert = 1,
     TemporaryAlert = 2,
     SPICondition = 3,
 }
 
-impl Default for SurveillanceStatus {
-    fn default() -> Self {
-        Self::NoCondition
+impl fmt::Display for SurveillanceStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::NoCondition => "no condition",
+                Self::PermanentAlert => "permanent alert",
+                Self::TemporaryAlert => "temporary alert",
+                Self::SPICondition => "SPI condition",
+            }
+        )
     }
 }
 
 /// Even / Odd
-#[derive(Debug, PartialEq, Eq, DekuRead, Copy, Clone)]
+#[derive(Debug, PartialEq, Eq, DekuRead, DekuWrite, Copy, Clone, Default)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 #[deku(id_type = "u8", bits = "1")]
 pub enum CPRFormat {
+    #[default]
     Even = 0,
     Odd = 1,
 }
 
-impl Default for CPRFormat {
-    fn default() -> Self {
-        Self::Even
-    }
-}
-
 impl fmt::Display for CPRFormat {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
@@ -620,8 +1189,9 @@ impl fmt::Display for CPRFormat {
 }
 
 /// Positive / Negative
-#[derive(Debug, PartialEq, Eq, DekuRead, Copy, Clone)]
+#[derive(Debug, PartialEq, Eq, DekuRead, DekuWrite, Copy, Clone)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 #[deku(id_type = "u8", bits = "1")]
 pub enum Sign {
     Positive = 0,
@@ -652,9 +1222,13 @@ impl fmt::Display for Sign {
 }
 
 /// 13 bit identity code
-#[derive(Debug, PartialEq, Eq, DekuRead, Copy, Clone)]
+#[derive(Debug, PartialEq, Eq, DekuRead, DekuWrite, Copy, Clone)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
-pub struct IdentityCode(#[deku(reader = "Self::read(deku::reader)")] pub u16);
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct IdentityCode(
+    #[deku(reader = "Self::read(deku::reader)", writer = "Self::write(deku::writer, *field_0)")]
+    pub u16,
+);
 
 impl IdentityCode {
     fn read<R: Read + Seek>(reader: &mut Reader<R>) -> result::Result<u16, DekuError> {
@@ -681,10 +1255,16 @@ impl IdentityCode {
         let num: u16 = (a << 12 | b << 8 | c << 4 | d) as u16;
         Ok(num)
     }
+
+    /// Reverse of [`Self::read`]
+    fn write<W: Write + Seek>(writer: &mut Writer<W>, num: u16) -> result::Result<(), DekuError> {
+        let num = mode_ac::encode_id13_field(u32::from(num));
+        num.to_writer(writer, (Endian::Big, BitSize(13)))
+    }
 }
 
 /// ICAO Address; Mode S transponder code
-#[derive(Debug, PartialEq, Eq, PartialOrd, DekuRead, Hash, Copy, Clone, Ord)]
+#[derive(Debug, PartialEq, Eq, PartialOrd, DekuRead, DekuWrite, Hash, Copy, Clone, Ord)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ICAO(pub [u8; 3]);
 
@@ -697,6 +1277,22 @@ impl fmt::Display for ICAO {
     }
 }
 
+/// Same hex address as [`fmt::Display`]
+#[cfg(feature = "defmt")]
+impl defmt::Format for ICAO {
+    fn format(&self, f: defmt::Formatter<'_>) {
+        defmt::write!(f, "{=u8:02x}{=u8:02x}{=u8:02x}", self.0[0], self.0[1], self.0[2]);
+    }
+}
+
+impl From<u32> for ICAO {
+    /// Lower 24 bits of `value`, such as the address recovered from [`Frame::crc`]
+    fn from(value: u32) -> Self {
+        let bytes = value.to_be_bytes();
+        Self([bytes[1], bytes[2], bytes[3]])
+    }
+}
+
 impl core::str::FromStr for ICAO {
     type Err = core::num::ParseIntError;
 
@@ -709,8 +1305,9 @@ impl core::str::FromStr for ICAO {
 }
 
 /// Type of `DownlinkRequest`
-#[derive(Debug, PartialEq, Eq, DekuRead, Copy, Clone)]
+#[derive(Debug, PartialEq, Eq, DekuRead, DekuWrite, Copy, Clone)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 #[deku(id_type = "u8", bits = "5")]
 pub enum DownlinkRequest {
     #[deku(id = 0b00000)]
@@ -729,26 +1326,58 @@ pub enum DownlinkRequest {
     Unknown(#[deku(bits = 5)] u8),
 }
 
+impl DownlinkRequest {
+    /// Announces a Comm-B broadcast message, to be read by all interrogators
+    #[must_use]
+    pub const fn is_broadcast(&self) -> bool {
+        matches!(self, Self::CommBBroadcastMsg1 | Self::CommBBroadcastMsg2)
+    }
+}
+
+impl fmt::Display for DownlinkRequest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::None => write!(f, "none"),
+            Self::RequestSendCommB => write!(f, "Comm-B request"),
+            Self::CommBBroadcastMsg1 => write!(f, "Comm-B broadcast 1"),
+            Self::CommBBroadcastMsg2 => write!(f, "Comm-B broadcast 2"),
+            Self::Unknown(dr) => write!(f, "unknown ({dr})"),
+        }
+    }
+}
+
 /// Uplink / Downlink
-#[derive(Debug, PartialEq, Eq, DekuRead, Copy, Clone)]
+#[derive(Debug, PartialEq, Eq, DekuRead, DekuWrite, Copy, Clone)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 #[deku(id_type = "u8", bits = "1")]
 pub enum KE {
     DownlinkELMTx = 0,
     UplinkELMAck = 1,
 }
 
-#[derive(Debug, PartialEq, Eq, DekuRead, Copy, Clone)]
+/// UM: Utility Message, the interrogator that reserved the transponder
+#[derive(Debug, PartialEq, Eq, DekuRead, DekuWrite, Copy, Clone)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct UtilityMessage {
+    /// IIS: Interrogator Identifier of the last interrogation, 0 without one
     #[deku(bits = "4")]
     pub iis: u8,
+    /// IDS: type of the reservation
     pub ids: UtilityMessageType,
 }
 
+impl fmt::Display for UtilityMessage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "IIS {}, {}", self.iis, self.ids)
+    }
+}
+
 /// Message Type
-#[derive(Debug, PartialEq, Eq, DekuRead, Copy, Clone)]
+#[derive(Debug, PartialEq, Eq, DekuRead, DekuWrite, Copy, Clone)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 #[deku(id_type = "u8", bits = "2")]
 pub enum UtilityMessageType {
     NoInformation = 0b00,
@@ -757,9 +1386,21 @@ pub enum UtilityMessageType {
     CommD = 0b11,
 }
 
+impl fmt::Display for UtilityMessageType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoInformation => write!(f, "no reservation"),
+            Self::CommB => write!(f, "Comm-B reservation"),
+            Self::CommC => write!(f, "Comm-C reservation"),
+            Self::CommD => write!(f, "Comm-D reservation"),
+        }
+    }
+}
+
 /// Airborne / Ground and SPI
-#[derive(Debug, PartialEq, Eq, DekuRead, Copy, Clone)]
+#[derive(Debug, PartialEq, Eq, DekuRead, DekuWrite, Copy, Clone)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 #[deku(id_type = "u8", bits = "3")]
 pub enum FlightStatus {
     NoAlertNoSPIAirborne = 0b000,
@@ -790,78 +1431,221 @@ impl fmt::Display for FlightStatus {
     }
 }
 
+/// RI: Reply Information of the air-air replies (DF0, DF16)
+///
+/// The ACAS capability of the aircraft, or without ACAS the category of its maximum cruising true
+/// airspeed, which is the only speed some targets ever transmit.
+///
+/// reference: ICAO Annex 10 Vol IV (3.1.2.8.2.2)
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ReplyInformation {
+    /// No operating ACAS
+    NoAcas,
+    /// ACAS with resolution capability inhibited
+    AcasResolutionInhibited,
+    /// ACAS with vertical-only resolution capability
+    AcasVerticalOnly,
+    /// ACAS with vertical and horizontal resolution capability
+    AcasVerticalAndHorizontal,
+    /// No ACAS, maximum airspeed not available
+    NoMaxAirspeed,
+    /// No ACAS, maximum airspeed category (9 to 14), see [`Self::max_airspeed`]
+    MaxAirspeed(u8),
+    /// Not assigned (1, 5 to 7, 15), contains the value
+    NotAssigned(u8),
+}
+
+impl From<u8> for ReplyInformation {
+    fn from(ri: u8) -> Self {
+        match ri {
+            0 => Self::NoAcas,
+            2 => Self::AcasResolutionInhibited,
+            3 => Self::AcasVerticalOnly,
+            4 => Self::AcasVerticalAndHorizontal,
+            8 => Self::NoMaxAirspeed,
+            9..=14 => Self::MaxAirspeed(ri),
+            _ => Self::NotAssigned(ri),
+        }
+    }
+}
+
+impl From<ReplyInformation> for u8 {
+    fn from(ri: ReplyInformation) -> Self {
+        match ri {
+            ReplyInformation::NoAcas => 0,
+            ReplyInformation::AcasResolutionInhibited => 2,
+            ReplyInformation::AcasVerticalOnly => 3,
+            ReplyInformation::AcasVerticalAndHorizontal => 4,
+            ReplyInformation::NoMaxAirspeed => 8,
+            ReplyInformation::MaxAirspeed(ri) | ReplyInformation::NotAssigned(ri) => ri,
+        }
+    }
+}
+
+impl ReplyInformation {
+    /// Whether the aircraft has an operating ACAS, `None` if not assigned
+    #[must_use]
+    pub const fn has_acas(&self) -> Option<bool> {
+        match self {
+            Self::AcasResolutionInhibited
+            | Self::AcasVerticalOnly
+            | Self::AcasVerticalAndHorizontal => Some(true),
+            Self::NoAcas | Self::NoMaxAirspeed | Self::MaxAirspeed(_) => Some(false),
+            Self::NotAssigned(_) => None,
+        }
+    }
+
+    /// Maximum cruising true airspeed (kt), as (exclusive lower bound, inclusive upper bound)
+    ///
+    /// The last category is over 1200 kt, without an upper bound.
+    #[must_use]
+    pub const fn max_airspeed(&self) -> Option<(u16, Option<u16>)> {
+        match self {
+            Self::MaxAirspeed(9) => Some((0, Some(75))),
+            Self::MaxAirspeed(10) => Some((75, Some(150))),
+            Self::MaxAirspeed(11) => Some((150, Some(300))),
+            Self::MaxAirspeed(12) => Some((300, Some(600))),
+            Self::MaxAirspeed(13) => Some((600, Some(1200))),
+            Self::MaxAirspeed(14) => Some((1200, None)),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ReplyInformation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoAcas => write!(f, "no ACAS"),
+            Self::AcasResolutionInhibited => write!(f, "ACAS, resolution inhibited"),
+            Self::AcasVerticalOnly => write!(f, "ACAS, vertical-only resolution"),
+            Self::AcasVerticalAndHorizontal => {
+                write!(f, "ACAS, vertical and horizontal resolution")
+            }
+            Self::NoMaxAirspeed => write!(f, "no ACAS, max airspeed not available"),
+            Self::NotAssigned(ri) => write!(f, "not assigned ({ri})"),
+            Self::MaxAirspeed(_) => match self.max_airspeed() {
+                Some((lower, Some(upper))) => {
+                    write!(f, "no ACAS, max airspeed {lower}-{upper} kt")
+                }
+                Some((lower, None)) => write!(f, "no ACAS, max airspeed over {lower} kt"),
+                None => write!(f, "no ACAS"),
+            },
+        }
+    }
+}
+
 /// 13 bit encoded altitude
-#[derive(Debug, PartialEq, Eq, DekuRead, Copy, Clone)]
+///
+/// Barometric altitude in feet, `None` if the altitude is invalid or not available
+#[derive(Debug, PartialEq, Eq, DekuRead, DekuWrite, Copy, Clone)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
-pub struct AC13Field(#[deku(reader = "Self::read(deku::reader)")] pub u16);
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct AC13Field(
+    #[deku(reader = "Self::read(deku::reader)", writer = "Self::write(deku::writer, *field_0)")]
+    pub Option<i32>,
+);
 
 impl AC13Field {
-    // TODO Add unit
-    fn read<R: Read + Seek>(reader: &mut Reader<R>) -> result::Result<u16, DekuError> {
+    fn read<R: Read + Seek>(reader: &mut Reader<R>) -> result::Result<Option<i32>, DekuError> {
         let num = u16::from_reader_with_ctx(reader, (Endian::Big, BitSize(13)))?;
 
         // Handle invalid or special codes
-        if num == 0 || num == 0b1111111111111 {
-            return Ok(0);
+        // TODO: read altitude when meter is selected
+        if !mode_ac::is_valid_altitude_code(num) {
+            return Ok(None);
         }
 
-        let m_bit = num & 0x0040;
         let q_bit = num & 0x0010;
 
-        if m_bit != 0 {
-            // TODO: read altitude when meter is selected
-            Ok(0)
-        } else if q_bit != 0 {
+        if q_bit != 0 {
+            // 25 ft increments, starting at -1000 ft
             let n = ((num & 0x1f80) >> 2) | ((num & 0x0020) >> 1) | (num & 0x000f);
-            let n = n * 25;
-            if n > 1000 {
-                Ok(n - 1000)
-            } else {
-                // TODO: add error
-                Ok(0)
-            }
+            Ok(Some(i32::from(n) * 25 - 1000))
         } else {
-            // TODO 11 bit gillham coded altitude
-            if let Ok(n) = mode_ac::mode_a_to_mode_c(mode_ac::decode_id13_field(num as u32)) {
-                Ok((100 * n) as u16)
-            } else {
-                Ok(0)
-            }
+            // 100 ft increments, gillham coded
+            Ok(mode_ac::mode_a_to_mode_c(mode_ac::decode_id13_field(u32::from(num)))
+                .ok()
+                .map(|n| n * 100))
         }
     }
+
+    /// Reverse of [`Self::read`], in 25 ft increments if possible
+    fn write<W: Write + Seek>(
+        writer: &mut Writer<W>,
+        altitude: Option<i32>,
+    ) -> result::Result<(), DekuError> {
+        let num = match altitude {
+            None => 0,
+            Some(altitude) => match altitude_q(altitude) {
+                Some(n) => ((n & 0x07e0) << 2) | ((n & 0x0010) << 1) | 0x10 | (n & 0x000f),
+                None => altitude_gillham(altitude)?,
+            },
+        };
+        num.to_writer(writer, (Endian::Big, BitSize(13)))
+    }
 }
 
 /// Transponder level and additional information (3.1.2.5.2.2.1)
-#[derive(Debug, PartialEq, Eq, DekuRead, Copy, Clone)]
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
-#[deku(id_type = "u8", bits = "3")]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 #[allow(non_camel_case_types)]
 pub enum Capability {
     /// Level 1 transponder (surveillance only), and either airborne or on the ground
-    #[deku(id = 0x00)]
     AG_UNCERTAIN,
 
-    #[deku(id_pat = "0x01..=0x03")]
-    Reserved(#[deku(bits = 3)] u8),
+    /// 1-3, with the value of the CA field
+    Reserved(u8),
 
     /// Level 2 or above transponder, on ground
-    #[deku(id = 0x04)]
     AG_GROUND,
 
     /// Level 2 or above transponder, airborne
-    #[deku(id = 0x05)]
     AG_AIRBORNE,
 
     /// Level 2 or above transponder, either airborne or on ground
-    #[deku(id = 0x06)]
     AG_UNCERTAIN2,
 
     /// DR field is not equal to 0, or fs field equal 2, 3, 4, or 5, and either airborne or on
     /// ground
-    #[deku(id = 0x07)]
     AG_UNCERTAIN3,
 }
 
+// Not derived: the field of an `id_pat` variant is read by seeking back over the id, which only
+// restores whole bytes, and the CA field starts at bit 5.
+impl<'a> DekuReader<'a, ()> for Capability {
+    fn from_reader_with_ctx<R: Read + Seek>(
+        reader: &mut Reader<R>,
+        _: (),
+    ) -> Result<Self, DekuError> {
+        let ca = u8::from_reader_with_ctx(reader, BitSize(3))?;
+        Ok(match ca {
+            0x00 => Self::AG_UNCERTAIN,
+            0x04 => Self::AG_GROUND,
+            0x05 => Self::AG_AIRBORNE,
+            0x06 => Self::AG_UNCERTAIN2,
+            0x07 => Self::AG_UNCERTAIN3,
+            ca => Self::Reserved(ca),
+        })
+    }
+}
+
+impl DekuWriter<()> for Capability {
+    fn to_writer<W: Write + Seek>(&self, writer: &mut Writer<W>, _: ()) -> Result<(), DekuError> {
+        let ca: u8 = match self {
+            Self::AG_UNCERTAIN => 0x00,
+            Self::Reserved(ca) => *ca,
+            Self::AG_GROUND => 0x04,
+            Self::AG_AIRBORNE => 0x05,
+            Self::AG_UNCERTAIN2 => 0x06,
+            Self::AG_UNCERTAIN3 => 0x07,
+        };
+        ca.to_writer(writer, BitSize(3))
+    }
+}
+
 impl fmt::Display for Capability {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
@@ -884,14 +1668,49 @@ const CHAR_LOOKUP: &[u8; 64] = b"#ABCDEFGHIJKLMNOPQRSTUVWXYZ##### ##############
 pub(crate) fn aircraft_identification_read<R: Read + Seek>(
     reader: &mut Reader<R>,
 ) -> Result<String, DekuError> {
-    let mut chars = vec![];
-    for _ in 0..=6 {
-        let c = <u8>::from_reader_with_ctx(reader, BitSize(6))?;
-        if c != 32 {
-            chars.push(c);
-        }
-    }
-    let encoded = chars.into_iter().map(|b| CHAR_LOOKUP[b as usize] as char).collect::<String>();
+    let raw = u64::from_reader_with_ctx(reader, (Endian::Big, BitSize(48)))?;
+    Ok(decode_callsign(raw))
+}
 
-    Ok(encoded)
+/// The 8 characters of the 48 bits of an identification, without the trailing spaces
+///
+/// Undefined character codes are decoded as `#`.
+///
+/// ```rust
+/// use adsb_deku::decode_callsign;
+///
+/// // "KLM1023 "
+/// assert_eq!(decode_callsign(0x2cc3_71c3_2ce0), "KLM1023");
+/// ```
+#[must_use]
+pub fn decode_callsign(raw: u64) -> String {
+    let encoded = (0..8)
+        .map(|i| CHAR_LOOKUP[((raw >> (42 - i * 6)) & 0x3f) as usize] as char)
+        .collect::<String>();
+    String::from(encoded.trim_end_matches(' '))
+}
+
+/// The 48 bits of an identification of `callsign`, padded with spaces, the reverse of
+/// [`decode_callsign`]
+///
+/// Returns `None` if `callsign` is longer than 8 characters, or contains characters other than
+/// `A-Z`, `0-9` and space.
+///
+/// ```rust
+/// use adsb_deku::encode_callsign;
+///
+/// assert_eq!(encode_callsign("KLM1023"), Some(0x2cc3_71c3_2ce0));
+/// assert_eq!(encode_callsign("klm1023"), None);
+/// ```
+#[must_use]
+pub fn encode_callsign(callsign: &str) -> Option<u64> {
+    if callsign.len() > 8 {
+        return None;
+    }
+    let mut raw = 0;
+    for c in callsign.bytes().chain(core::iter::repeat(b' ')).take(8) {
+        let code = CHAR_LOOKUP.iter().position(|lookup| *lookup == c && c != b'#')?;
+        raw = (raw << 6) | code as u64;
+    }
+    Some(raw)
 }
@@ -1,4 +1,6 @@
 //! Mode AC Conversion methods
+//!
+//! reference: ICAO Annex 10 Vol IV (3.1.1.7.12.2.3)
 
 #[cfg(feature = "alloc")]
 use core::{
@@ -6,7 +8,12 @@ use core::{
     result::Result::{Err, Ok},
 };
 
-pub(crate) fn decode_id13_field(id13_field: u32) -> u32 {
+/// Convert a 13 bit ID/AC field into the Mode A (gillham) bit ordering
+///
+/// The returned value is laid out as `A4 A2 A1 _ B4 B2 B1 _ C4 C2 C1 _ D4 D2 D1` from bit 14 to
+/// bit 0. The M bit (bit 6) of the field is ignored.
+#[must_use]
+pub fn decode_id13_field(id13_field: u32) -> u32 {
     let mut hex_gillham: u32 = 0;
 
     if id13_field & 0x1000 != 0 {
@@ -50,7 +57,38 @@ pub(crate) fn decode_id13_field(id13_field: u32) -> u32 {
     hex_gillham
 }
 
-pub(crate) fn mode_a_to_mode_c(mode_a: u32) -> result::Result<u32, &'static str> {
+/// Bits of the Mode A (gillham) ordering and of the 13 bit ID/AC field, without the M bit
+const GILLHAM_ID13: [(u32, u32); 12] = [
+    (0x0010, 0x1000), // C1
+    (0x1000, 0x0800), // A1
+    (0x0020, 0x0400), // C2
+    (0x2000, 0x0200), // A2
+    (0x0040, 0x0100), // C4
+    (0x4000, 0x0080), // A4
+    (0x0100, 0x0020), // B1
+    (0x0001, 0x0010), // D1 or Q
+    (0x0200, 0x0008), // B2
+    (0x0002, 0x0004), // D2
+    (0x0400, 0x0002), // B4
+    (0x0004, 0x0001), // D4
+];
+
+/// Convert a Mode A (gillham) code into a 13 bit ID/AC field, the reverse of
+/// [`decode_id13_field`]
+///
+/// The M bit (bit 6) of the field is cleared.
+#[must_use]
+pub fn encode_id13_field(mode_a: u32) -> u32 {
+    GILLHAM_ID13
+        .iter()
+        .filter(|(gillham, _)| mode_a & gillham != 0)
+        .fold(0, |id13_field, (_, id13)| id13_field | id13)
+}
+
+/// Convert a Mode A (gillham) code from [`decode_id13_field`] into the altitude in 100 ft units
+///
+/// Gillham coded altitudes range from -1200 ft to 126700 ft.
+pub fn mode_a_to_mode_c(mode_a: u32) -> result::Result<i32, &'static str> {
     let mut five_hundreds: u32 = 0;
     let mut one_hundreds: u32 = 0;
 
@@ -112,10 +150,61 @@ pub(crate) fn mode_a_to_mode_c(mode_a: u32) -> result::Result<u32, &'static str>
         one_hundreds = 6 - one_hundreds;
     }
 
+    // Gillham coded altitudes start at -1200 ft
     let n = (five_hundreds * 5) + one_hundreds;
-    if n >= 13 {
-        Ok(n - 13)
+    if n >= 1 {
+        Ok(n as i32 - 13)
     } else {
         Err("Invalid altitude")
     }
 }
+
+/// Convert an altitude in 100 ft units into a Mode A (gillham) code, the reverse of
+/// [`mode_a_to_mode_c`]
+///
+/// Gillham coded altitudes range from -1200 ft to 126700 ft.
+pub fn mode_c_to_mode_a(altitude: i32) -> result::Result<u32, &'static str> {
+    if !(-12..=1267).contains(&altitude) {
+        return Err("Invalid altitude");
+    }
+    let n = (altitude + 12) as u32;
+    let five_hundreds = n / 5;
+    let mut one_hundreds = n % 5;
+
+    // 500 ft increments are gray coded in D2 D4 A1 A2 A4 B1 B2 B4
+    let gray = five_hundreds ^ (five_hundreds >> 1);
+    let mut mode_a = 0;
+    for (bit, pulse) in
+        [0x0002, 0x0004, 0x1000, 0x2000, 0x4000, 0x0100, 0x0200, 0x0400].iter().enumerate()
+    {
+        if gray & (1 << (7 - bit)) != 0 {
+            mode_a |= pulse;
+        }
+    }
+
+    // 100 ft increments (C1 C2 C4) are reflected every other 500 ft increment
+    if five_hundreds % 2 == 1 {
+        one_hundreds = 4 - one_hundreds;
+    }
+    mode_a |= [0x0040, 0x0060, 0x0020, 0x0030, 0x0010][one_hundreds as usize];
+
+    Ok(mode_a)
+}
+
+/// Check if a 13 bit AC field contains an altitude that can be decoded
+///
+/// The field is invalid if all bits are zero or set, if the altitude is reported in meters
+/// (M bit), or if the gillham code is not a valid altitude.
+#[must_use]
+pub fn is_valid_altitude_code(ac13_field: u16) -> bool {
+    const M_BIT: u16 = 0x0040;
+    const Q_BIT: u16 = 0x0010;
+
+    if ac13_field == 0 || ac13_field >= 0b1_1111_1111_1111 || ac13_field & M_BIT != 0 {
+        return false;
+    }
+    if ac13_field & Q_BIT != 0 {
+        return true;
+    }
+    mode_a_to_mode_c(decode_id13_field(u32::from(ac13_field))).is_ok()
+}
@@ -0,0 +1,111 @@
+use adsb_deku::avr::{parse_line, AvrError, AvrFrame};
+use adsb_deku::demod::DemodMeta;
+use adsb_deku::{Frame, DF};
+use hexlit::hex;
+
+#[test]
+fn avr_star() {
+    let avr = parse_line("*8D40621D58C382D690C8AC2863A7;\n").unwrap().unwrap();
+    assert_eq!(avr.timestamp, None);
+    assert_eq!(avr.bytes(), hex!("8D40621D58C382D690C8AC2863A7"));
+    let frame = Frame::from_bytes(avr.bytes()).unwrap();
+    assert!(matches!(frame.df, DF::ADSB(_)));
+
+    // short frame, lowercase, without terminator
+    let avr = parse_line("*5dab3d17d4ba29").unwrap().unwrap();
+    assert_eq!(avr.bytes(), hex!("5dab3d17d4ba29"));
+}
+
+#[test]
+fn avr_timestamp() {
+    let avr = parse_line("@0000C3E3A1B28D40621D58C382D690C8AC2863A7;\r\n").unwrap().unwrap();
+    assert_eq!(avr.timestamp, Some(0xC3E3_A1B2));
+    assert_eq!(avr.bytes(), hex!("8D40621D58C382D690C8AC2863A7"));
+
+    let avr = parse_line("@FFFFFFFFFFFF5dab3d17d4ba29;").unwrap().unwrap();
+    assert_eq!(avr.timestamp, Some(0xFFFF_FFFF_FFFF));
+    assert_eq!(avr.bytes(), hex!("5dab3d17d4ba29"));
+}
+
+#[test]
+fn avr_ignored() {
+    assert_eq!(parse_line(""), Ok(None));
+    assert_eq!(parse_line("  \r\n"), Ok(None));
+    assert_eq!(parse_line("# comment"), Ok(None));
+}
+
+#[test]
+fn avr_garbage() {
+    assert_eq!(parse_line("8D40621D58C382D690C8AC2863A7;"), Err(AvrError::UnknownPrefix('8')));
+    assert_eq!(parse_line("*8D40621D58C382D690C8AC2863AZ;"), Err(AvrError::InvalidHex));
+    assert_eq!(parse_line("*8D40621D58;"), Err(AvrError::InvalidLength(10)));
+    assert_eq!(parse_line("*8D40621D58C382D690C8AC2863A;"), Err(AvrError::InvalidLength(27)));
+    assert_eq!(parse_line("@0000C3E3;"), Err(AvrError::MissingTimestamp));
+    assert_eq!(parse_line("@0000C3E3A1B2;"), Err(AvrError::InvalidLength(0)));
+    assert_eq!(parse_line("@+000C3E3A1B25dab3d17d4ba29;"), Err(AvrError::InvalidHex));
+    assert_eq!(parse_line("*5dab3d17d4ba2é"), Err(AvrError::InvalidLength(15)));
+}
+
+#[test]
+fn avr_demod_meta() {
+    let bytes = hex!("8D40621D58C382D690C8AC2863A7");
+    let meta = DemodMeta::default().with_preamble_score(0.8).with_corrected_bits(2).with_phase(3);
+    let avr = AvrFrame::new(&bytes, Some(0xC3E3_A1B2)).unwrap().with_meta(meta);
+    assert_eq!(avr.bytes(), bytes);
+    assert_eq!(avr.timestamp, Some(0xC3E3_A1B2));
+    let meta = avr.meta.unwrap();
+    assert_eq!(
+        (meta.preamble_score, meta.corrected_bits, meta.phase),
+        (Some(0.8), Some(2), Some(3))
+    );
+
+    // the same frame as its AVR line, without the measurements
+    let line = parse_line("@0000C3E3A1B28D40621D58C382D690C8AC2863A7;").unwrap().unwrap();
+    assert_eq!(line.meta, None);
+    assert_eq!(line, AvrFrame::new(&bytes, Some(0xC3E3_A1B2)).unwrap());
+
+    assert_eq!(AvrFrame::new(&bytes[..10], None), Err(AvrError::InvalidLength(20)));
+}
+
+#[test]
+fn avr_soft_repair() {
+    let valid = hex!("8D40621D58C382D690C8AC2863A7");
+    let mut bytes = valid;
+    // 2 bits in the ME and 1 in the parity
+    bytes[6] ^= 0b0100_0000;
+    bytes[10] ^= 0b0000_0001;
+    bytes[12] ^= 0b0001_0000;
+    let mut confidence = [200; 112];
+    for bit in [49, 87, 99] {
+        confidence[bit] = 3;
+    }
+    // more confident decoys
+    for bit in [8, 20, 60, 100] {
+        confidence[bit] = 50;
+    }
+
+    let mut avr = AvrFrame::new(&bytes, None).unwrap();
+    assert_eq!(avr.soft_repair(4), None);
+
+    let meta = DemodMeta::default().with_corrected_bits(0).with_bit_confidence(confidence);
+    let mut avr = avr.with_meta(meta);
+    assert_eq!(avr.soft_repair(2), None);
+    assert_eq!(avr.bytes(), bytes);
+    assert_eq!(avr.soft_repair(4), Some(3));
+    assert_eq!(avr.bytes(), valid);
+    assert_eq!(avr.meta.unwrap().corrected_bits, Some(3));
+    assert!(matches!(Frame::from_bytes(avr.bytes()).unwrap().df, DF::ADSB(_)));
+    assert_eq!(avr.soft_repair(4), Some(0));
+
+    // errors in confident bits are not repaired
+    let mut bytes = valid;
+    bytes[3] ^= 0b1000_0000;
+    let mut avr = AvrFrame::new(&bytes, None).unwrap().with_meta(meta);
+    assert_eq!(avr.soft_repair(4), None);
+    assert_eq!(avr.bytes(), bytes);
+
+    // only DF17/18
+    let short = hex!("5D4CA2D4000000");
+    let mut avr = AvrFrame::new(&short, None).unwrap().with_meta(meta);
+    assert_eq!(avr.soft_repair(4), None);
+}
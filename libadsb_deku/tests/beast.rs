@@ -0,0 +1,115 @@
+use adsb_deku::beast::{BeastDecoder, BeastError, MessageType, ESCAPE};
+use adsb_deku::{Frame, DF};
+use hexlit::hex;
+use proptest::prelude::*;
+
+/// Beast message of `bytes`, escaping the `<esc>` bytes
+fn message(kind: u8, timestamp: u64, signal_level: u8, bytes: &[u8]) -> Vec<u8> {
+    let mut message = vec![ESCAPE, kind];
+    let timestamp = timestamp.to_be_bytes();
+    for byte in timestamp[2..].iter().chain(&[signal_level]).chain(bytes) {
+        message.push(*byte);
+        if *byte == ESCAPE {
+            message.push(ESCAPE);
+        }
+    }
+    message
+}
+
+#[test]
+fn beast_frames() {
+    let long = hex!("8D40621D58C382D690C8AC2863A7");
+    let short = hex!("5dab3d17d4ba29");
+    let mut input = message(b'3', 0x1a, 0x80, &long);
+    input.extend(message(b'2', 0xffff_ffff_ffff, 0x1a, &short));
+    input.extend(message(b'1', 0, 0x10, &hex!("0a1a")));
+
+    let mut decoder = BeastDecoder::default();
+    let frames: Vec<_> = decoder.decode(&input).map(Result::unwrap).collect();
+    assert_eq!(frames.len(), 3);
+
+    assert_eq!(frames[0].message_type, MessageType::ModeSLong);
+    assert_eq!(frames[0].timestamp, 0x1a);
+    assert_eq!(frames[0].signal_level, 0x80);
+    assert_eq!(frames[0].bytes(), long);
+    let avr = frames[0].to_avr().unwrap();
+    assert_eq!(avr.timestamp, Some(0x1a));
+    assert_eq!(avr.meta.unwrap().signal_level, Some(0x80));
+    let frame = Frame::from_bytes(avr.bytes()).unwrap();
+    assert!(matches!(frame.df, DF::ADSB(_)));
+
+    assert_eq!(frames[1].message_type, MessageType::ModeSShort);
+    assert_eq!(frames[1].timestamp, 0xffff_ffff_ffff);
+    assert_eq!(frames[1].signal_level, 0x1a);
+    assert_eq!(frames[1].bytes(), short);
+
+    assert_eq!(frames[2].message_type, MessageType::ModeAC);
+    assert_eq!(frames[2].bytes(), hex!("0a1a"));
+    assert_eq!(frames[2].to_avr(), None);
+    assert_eq!(decoder.resyncs(), 0);
+}
+
+#[test]
+fn beast_split() {
+    let input = message(b'3', 0x1a1a, 0x1a, &hex!("8D40621D58C382D690C8AC2863A7"));
+    let mut decoder = BeastDecoder::default();
+    for chunk in input[..input.len() - 1].chunks(3) {
+        assert_eq!(decoder.decode(chunk).next(), None);
+    }
+    let beast = decoder.decode(&input[input.len() - 1..]).next().unwrap().unwrap();
+    assert_eq!(beast.timestamp, 0x1a1a);
+}
+
+#[test]
+fn beast_resync() {
+    let short = hex!("5dab3d17d4ba29");
+    let full = message(b'2', 0x1a, 0x80, &short);
+
+    // joined in the middle of a message, including its escaped <esc>
+    let mut input = full[4..].to_vec();
+    input.extend(&full);
+    let mut decoder = BeastDecoder::default();
+    let frames: Vec<_> = decoder.decode(&input).collect();
+    assert_eq!(frames.len(), 1);
+    assert_eq!(frames[0].unwrap().bytes(), short);
+    assert_eq!(decoder.resyncs(), 2);
+
+    // truncated by the next message
+    let mut input = full[..6].to_vec();
+    input.extend(&full);
+    let mut decoder = BeastDecoder::default();
+    let frames: Vec<_> = decoder.decode(&input).collect();
+    assert_eq!(frames[0], Err(BeastError::Truncated));
+    assert_eq!(frames[1].unwrap().bytes(), short);
+    assert_eq!(frames.len(), 2);
+
+    // unknown type, skipped
+    let mut input = message(b'4', 0, 0, &[0x00, 0x01]);
+    input.extend(&full);
+    let mut decoder = BeastDecoder::default();
+    let frames: Vec<_> = decoder.decode(&input).collect();
+    assert_eq!(frames[0], Err(BeastError::UnknownType(b'4')));
+    assert_eq!(frames[1].unwrap().bytes(), short);
+    assert_eq!(frames.len(), 2);
+    assert_eq!(decoder.resyncs(), 0);
+}
+
+proptest! {
+    #[test]
+    fn beast_roundtrip(
+        long in any::<bool>(),
+        timestamp in 0..(1_u64 << 48),
+        signal_level in any::<u8>(),
+        bytes in prop::array::uniform14(prop_oneof![Just(ESCAPE), any::<u8>()]),
+    ) {
+        let (kind, len) = if long { (b'3', 14) } else { (b'2', 7) };
+        let input = message(kind, timestamp, signal_level, &bytes[..len]);
+        let mut decoder = BeastDecoder::default();
+        let frames: Vec<_> = decoder.decode(&input).collect();
+        prop_assert_eq!(frames.len(), 1);
+        let beast = frames[0].unwrap();
+        prop_assert_eq!(beast.timestamp, timestamp);
+        prop_assert_eq!(beast.signal_level, signal_level);
+        prop_assert_eq!(beast.bytes(), &bytes[..len]);
+    }
+}
@@ -0,0 +1,70 @@
+//! The decoded types implement `defmt::Format`, encoded by a logger collecting the bytes, as there
+//! is no defmt logger on the host
+//!
+//! Only built with `--features defmt`, such as by `cargo xtask feature-matrix`.
+
+#![cfg(feature = "defmt")]
+
+use std::sync::Mutex;
+
+use adsb_deku::adsb::{AirborneVelocity, Identification, ME};
+use adsb_deku::bds::BDS;
+use adsb_deku::{Altitude, Frame, DF, ICAO};
+use hexlit::hex;
+
+/// Bytes written by [`Logger`]
+static BYTES: Mutex<Vec<u8>> = Mutex::new(Vec::new());
+
+#[defmt::global_logger]
+struct Logger;
+
+unsafe impl defmt::Logger for Logger {
+    fn acquire() {}
+
+    unsafe fn flush() {}
+
+    unsafe fn release() {}
+
+    unsafe fn write(bytes: &[u8]) {
+        BYTES.lock().unwrap().extend_from_slice(bytes);
+    }
+}
+
+defmt::timestamp!("{=u32}", 0);
+
+fn assert_format<T: defmt::Format>() {}
+
+/// Bytes of `value` encoded by defmt
+fn encode(value: impl defmt::Format) -> Vec<u8> {
+    BYTES.lock().unwrap().clear();
+    defmt::println!("{}", value);
+    BYTES.lock().unwrap().clone()
+}
+
+#[test]
+fn format() {
+    assert_format::<Frame>();
+    assert_format::<DF>();
+    assert_format::<ME>();
+    assert_format::<ICAO>();
+    assert_format::<Altitude>();
+    assert_format::<AirborneVelocity>();
+    assert_format::<Identification>();
+    assert_format::<BDS>();
+
+    // a single test, as the frames share the logger
+    let position = Frame::from_bytes(&hex!("8D40621D58C382D690C8AC2863A7")).unwrap();
+    let identification = Frame::from_bytes(&hex!("8D40621D202CC371C32CE0576098")).unwrap();
+    let comm_b = Frame::from_bytes(&hex!("a0001910204d7075d35820c25c0c")).unwrap();
+    let encoded = [encode(&position), encode(&identification), encode(&comm_b)];
+    for bytes in &encoded {
+        assert!(bytes.len() > 8, "{bytes:?}");
+    }
+    assert_ne!(encoded[0], encoded[1]);
+    assert_ne!(encoded[1], encoded[2]);
+    assert_eq!(encode(&position), encoded[0]);
+
+    let DF::ADSB(adsb) = &identification.df else { unreachable!() };
+    assert!(!encode(adsb.icao).is_empty());
+    assert!(!encode(&adsb.me).is_empty());
+}
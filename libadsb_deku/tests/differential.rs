@@ -0,0 +1,72 @@
+//! `Frame::from_slice` and `Frame::from_reader` must decode the same `Frame`, or fail with the
+//! same kind of error, for any input
+
+use std::mem::discriminant;
+
+use adsb_deku::deku::no_std_io::{Cursor, Read, Result, Seek, SeekFrom};
+use adsb_deku::{DekuError, Frame};
+use proptest::prelude::*;
+
+const TEST_STR: &str = include_str!("../tests/lax-messages.txt");
+
+/// Reader returning at most `chunk` bytes for every read, such as a non-blocking socket
+struct ChunkedReader {
+    cursor: Cursor<Vec<u8>>,
+    chunk: usize,
+}
+
+impl Read for ChunkedReader {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        let len = buf.len().min(self.chunk);
+        self.cursor.read(&mut buf[..len])
+    }
+}
+
+impl Seek for ChunkedReader {
+    fn seek(&mut self, pos: SeekFrom) -> Result<u64> {
+        self.cursor.seek(pos)
+    }
+}
+
+fn assert_same(bytes: &[u8], chunk: usize) {
+    let reader = ChunkedReader { cursor: Cursor::new(bytes.to_vec()), chunk };
+    match (Frame::from_slice(bytes), Frame::from_reader(reader)) {
+        (Ok(slice), Ok(reader)) => {
+            assert_eq!(slice, reader, "{bytes:02x?}, chunk: {chunk}");
+            assert_eq!(slice.raw(), reader.raw(), "{bytes:02x?}, chunk: {chunk}");
+        }
+        (Err(slice), Err(reader)) => assert_eq!(
+            discriminant::<DekuError>(&slice),
+            discriminant(&reader),
+            "{bytes:02x?}, chunk: {chunk}: {slice} != {reader}"
+        ),
+        (slice, reader) => panic!("{bytes:02x?}, chunk: {chunk}: {slice:?} != {reader:?}"),
+    }
+}
+
+#[test]
+fn recorded() {
+    // a sample of the recorded frames, decoding all of them twice is too slow for debug builds
+    for line in TEST_STR.lines().step_by(50) {
+        let bytes = hex::decode(&line[1..line.len() - 1]).unwrap();
+        assert_same(&bytes, bytes.len());
+        // truncated frame
+        assert_same(&bytes[..bytes.len() - 1], bytes.len());
+    }
+}
+
+proptest! {
+    #[test]
+    fn random(bytes in prop::collection::vec(any::<u8>(), 0..=16), chunk in 1..=16_usize) {
+        assert_same(&bytes, chunk);
+    }
+
+    #[test]
+    fn random_df(df in 0..32_u8, rest in prop::collection::vec(any::<u8>(), 13), len in 1..=14_usize) {
+        // every downlink format, short or long
+        let mut bytes = vec![df << 3 | rest[0] >> 5];
+        bytes.extend_from_slice(&rest[1..]);
+        bytes.truncate(len);
+        assert_same(&bytes, 1);
+    }
+}
@@ -0,0 +1,121 @@
+//! Every 5-bit Downlink Format and ADS-B Type Code decodes to the variant of its `id_pat` range,
+//! without a decoding error for the unassigned values
+
+use adsb_deku::adsb::ME;
+use adsb_deku::{Frame, DF, ICAO};
+
+/// Variant name of `df`
+fn df_variant(df: &DF) -> &'static str {
+    match df {
+        DF::ShortAirAirSurveillance { .. } => "ShortAirAirSurveillance",
+        DF::SurveillanceAltitudeReply { .. } => "SurveillanceAltitudeReply",
+        DF::SurveillanceIdentityReply { .. } => "SurveillanceIdentityReply",
+        DF::AllCallReply { .. } => "AllCallReply",
+        DF::LongAirAir { .. } => "LongAirAir",
+        DF::ADSB(_) => "ADSB",
+        DF::TisB { .. } => "TisB",
+        DF::ExtendedQuitterMilitaryApplication { .. } => "ExtendedQuitterMilitaryApplication",
+        DF::CommBAltitudeReply { .. } => "CommBAltitudeReply",
+        DF::CommBIdentityReply { .. } => "CommBIdentityReply",
+        DF::ModeSExtendedSquitter { .. } => "ModeSExtendedSquitter",
+        DF::ReservedShort { .. } => "ReservedShort",
+        DF::ReservedLong { .. } => "ReservedLong",
+    }
+}
+
+/// Variant name of `me`
+fn me_variant(me: &ME) -> &'static str {
+    match me {
+        ME::AirbornePositionBaroAltitude(_) => "AirbornePositionBaroAltitude",
+        ME::AirborneVelocity(_) => "AirborneVelocity",
+        ME::NoPosition(_) => "NoPosition",
+        ME::AircraftIdentification(_) => "AircraftIdentification",
+        ME::SurfacePosition(_) => "SurfacePosition",
+        ME::AirbornePositionGNSSAltitude(_) => "AirbornePositionGNSSAltitude",
+        ME::Reserved0(_) => "Reserved0",
+        ME::SurfaceSystemStatus(_) => "SurfaceSystemStatus",
+        ME::Reserved1(_) => "Reserved1",
+        ME::AircraftStatus(_) => "AircraftStatus",
+        ME::TargetStateAndStatusInformation(_) => "TargetStateAndStatusInformation",
+        ME::AircraftOperationalCoordination(_) => "AircraftOperationalCoordination",
+        ME::AircraftOperationStatus(_) => "AircraftOperationStatus",
+    }
+}
+
+#[test]
+fn downlink_formats() {
+    for df in 0..32_u8 {
+        let expected = match df {
+            0 => "ShortAirAirSurveillance",
+            4 => "SurveillanceAltitudeReply",
+            5 => "SurveillanceIdentityReply",
+            11 => "AllCallReply",
+            16 => "LongAirAir",
+            17 => "ADSB",
+            18 => "TisB",
+            19 => "ExtendedQuitterMilitaryApplication",
+            20 => "CommBAltitudeReply",
+            21 => "CommBIdentityReply",
+            24..=31 => "ModeSExtendedSquitter",
+            1..=3 | 6..=10 | 12..=15 => "ReservedShort",
+            22 | 23 => "ReservedLong",
+            32.. => unreachable!(),
+        };
+        let len = if df & 0x10 == 0 { 7 } else { 14 };
+        let mut bytes = [0; 14];
+        bytes[0] = df << 3;
+        let frame = Frame::from_bytes(&bytes[..len]).unwrap_or_else(|e| panic!("DF{df}: {e}"));
+        assert_eq!(df_variant(&frame.df), expected, "DF{df}");
+        // the length of the frame from its DF, ignoring the bytes after it
+        assert_eq!(Frame::from_slice(&bytes).unwrap(), frame, "DF{df}");
+    }
+}
+
+#[test]
+fn reserved_downlink_formats() {
+    let frame = Frame::from_bytes(&[0x0a, 0x12, 0x34, 0x56, 0xab, 0xcd, 0xef]).unwrap();
+    assert_eq!(
+        frame.df,
+        DF::ReservedShort { df: 1, data: 0x0212_3456, parity: ICAO([0xab, 0xcd, 0xef]) }
+    );
+    assert!(frame.to_string().contains("Reserved Downlink Format 1"));
+
+    let mut bytes = [0xff; 14];
+    bytes[0] = 22 << 3 | 0x07;
+    let frame = Frame::from_bytes(&bytes).unwrap();
+    assert_eq!(df_variant(&frame.df), "ReservedLong");
+    let DF::ReservedLong { df, data, .. } = frame.df else { unreachable!() };
+    assert_eq!(df, 22);
+    assert_eq!(data, (1 << 83) - 1);
+}
+
+#[test]
+fn type_codes() {
+    for tc in 0..32_u8 {
+        let expected = match tc {
+            0 => "NoPosition",
+            1..=4 => "AircraftIdentification",
+            5..=8 => "SurfacePosition",
+            9..=18 => "AirbornePositionBaroAltitude",
+            19 => "AirborneVelocity",
+            20..=22 => "AirbornePositionGNSSAltitude",
+            23 => "Reserved0",
+            24 => "SurfaceSystemStatus",
+            25..=27 => "Reserved1",
+            28 => "AircraftStatus",
+            29 => "TargetStateAndStatusInformation",
+            30 => "AircraftOperationalCoordination",
+            31 => "AircraftOperationStatus",
+            32.. => unreachable!(),
+        };
+        let mut bytes = [0; 14];
+        bytes[..4].copy_from_slice(&[0x8d, 0xab, 0xcd, 0xef]);
+        bytes[4] = tc << 3;
+        bytes[11..].copy_from_slice(&[0x11, 0x22, 0x33]);
+        let frame = Frame::from_bytes(&bytes).unwrap_or_else(|e| panic!("TC{tc}: {e}"));
+        let DF::ADSB(adsb) = &frame.df else { panic!("TC{tc}: {:?}", frame.df) };
+        assert_eq!(me_variant(&adsb.me), expected, "TC{tc}");
+        // the message is read to its end
+        assert_eq!(adsb.pi, ICAO([0x11, 0x22, 0x33]), "TC{tc}");
+    }
+}
@@ -0,0 +1,137 @@
+use std::collections::BTreeMap;
+
+use adsb_deku::mode_ac::{
+    self, decode_id13_field, is_valid_altitude_code, mode_a_to_mode_c, mode_c_to_mode_a,
+};
+use adsb_deku::{Frame, DF};
+use proptest::prelude::*;
+
+/// Mode A bit for each pulse, in gillham order
+const A1: u32 = 0x1000;
+const A2: u32 = 0x2000;
+const A4: u32 = 0x4000;
+const B1: u32 = 0x0100;
+const B2: u32 = 0x0200;
+const B4: u32 = 0x0400;
+const C1: u32 = 0x0010;
+const C2: u32 = 0x0020;
+const C4: u32 = 0x0040;
+const D2: u32 = 0x0002;
+const D4: u32 = 0x0004;
+
+/// Published gillham codes of ICAO Annex 10 Volume IV, as in the Mode C tables of dump1090,
+/// as (altitude in 100 ft units, Mode A pulses)
+///
+/// The 500 ft band edges, the 100 ft steps reflected in the odd 500 ft bands, and the limits.
+const GILLHAM_CODES: [(i32, u32); 13] = [
+    (-12, C4),
+    (-11, C2 | C4),
+    (-10, C2),
+    (-8, C1),
+    (-7, B4 | C1),
+    (0, B2 | B4 | C2),
+    (1, B2 | B4 | C1 | C2),
+    (5, B2 | C2),
+    (6, B2 | C2 | C4),
+    (10, B1 | B2 | C2),
+    (100, A2 | A4 | B1 | B4 | C2),
+    (350, A1 | A4 | B1 | C2 | D4),
+    (1267, C4 | D2),
+];
+
+/// Reverse of `decode_id13_field`, with the M bit cleared
+fn encode_id13_field(mode_a: u32) -> u32 {
+    let order = [C1, A1, C2, A2, C4, A4, 0, B1, 0x0001, B2, D2, B4, D4];
+    let mut id13 = 0;
+    for (i, pulse) in order.iter().enumerate() {
+        if *pulse != 0 && mode_a & pulse != 0 {
+            id13 |= 1 << (12 - i);
+        }
+    }
+    id13
+}
+
+/// All Mode A codes, from 0000 to 7777 (octal)
+fn all_mode_a_codes() -> impl Iterator<Item = u32> {
+    (0..4096_u32).map(|code| {
+        let a = (code >> 9) & 0x7;
+        let b = (code >> 6) & 0x7;
+        let c = (code >> 3) & 0x7;
+        let d = code & 0x7;
+        (a << 12) | (b << 8) | (c << 4) | d
+    })
+}
+
+#[test]
+fn gillham_published_codes() {
+    for (altitude, mode_a) in GILLHAM_CODES {
+        assert_eq!(mode_a_to_mode_c(mode_a), Ok(altitude), "{altitude}");
+        assert_eq!(mode_c_to_mode_a(altitude), Ok(mode_a), "{altitude}");
+    }
+}
+
+#[test]
+fn mode_c_to_mode_a_table() {
+    // the Mode A code of each altitude, from decoding all Mode A codes
+    let mut table = BTreeMap::new();
+    for mode_a in all_mode_a_codes() {
+        if let Ok(altitude) = mode_a_to_mode_c(mode_a) {
+            assert_eq!(table.insert(altitude, mode_a), None, "{altitude}");
+        }
+    }
+    assert_eq!(table.len(), 1280);
+    for altitude in -12..=1267 {
+        assert_eq!(mode_c_to_mode_a(altitude).ok(), table.get(&altitude).copied(), "{altitude}");
+    }
+}
+
+#[test]
+fn gillham_known_values() {
+    assert_eq!(mode_a_to_mode_c(C4), Ok(-12));
+    assert_eq!(mode_a_to_mode_c(C2 | C4), Ok(-11));
+    assert_eq!(mode_a_to_mode_c(C1 | B4), Ok(-7));
+    // D1 is never used for altitude
+    assert!(mode_a_to_mode_c(0x0001 | C1).is_err());
+    // C1, C2, C4 can't all be zero
+    assert!(mode_a_to_mode_c(A1).is_err());
+    assert_eq!(mode_c_to_mode_a(-12), Ok(C4));
+    assert_eq!(mode_c_to_mode_a(-7), Ok(C1 | B4));
+    assert!(mode_c_to_mode_a(-13).is_err());
+    assert!(mode_c_to_mode_a(1268).is_err());
+}
+
+proptest! {
+    #[test]
+    fn id13_field_round_trip(id13 in 0..0x2000_u32) {
+        let id13 = id13 & !0x0040;
+        prop_assert_eq!(encode_id13_field(decode_id13_field(id13)), id13);
+    }
+
+    #[test]
+    fn id13_field_ignores_m_bit(id13 in 0..0x2000_u32) {
+        prop_assert_eq!(decode_id13_field(id13 | 0x0040), decode_id13_field(id13 & !0x0040));
+    }
+
+    #[test]
+    fn encode_id13_field_matches_reference(mode_a in 0..0x8000_u32) {
+        prop_assert_eq!(mode_ac::encode_id13_field(mode_a), encode_id13_field(mode_a));
+    }
+
+    #[test]
+    fn gillham_round_trip(altitude in -12..=1267_i32) {
+        let id13 = encode_id13_field(mode_c_to_mode_a(altitude).unwrap());
+        prop_assert!(is_valid_altitude_code(id13 as u16));
+        prop_assert_eq!(mode_a_to_mode_c(decode_id13_field(id13)), Ok(altitude));
+    }
+
+    #[test]
+    fn valid_altitude_code_matches_ac13_decoding(ac13 in 0..0x2000_u32) {
+        // DF4, Surveillance Altitude Reply
+        let bytes = ((4 << 27) | ac13).to_be_bytes();
+        let frame = Frame::from_bytes(&[bytes[0], bytes[1], bytes[2], bytes[3], 0, 0, 0]).unwrap();
+        let DF::SurveillanceAltitudeReply { ac, .. } = frame.df else {
+            unreachable!()
+        };
+        prop_assert_eq!(ac.0.is_some(), is_valid_altitude_code(ac13 as u16));
+    }
+}
@@ -0,0 +1,81 @@
+use adsb_deku::Frame;
+use deku::no_std_io::{Cursor, Read, Result, Seek, SeekFrom};
+use hexlit::hex;
+
+/// Reader returning at most `chunk` bytes for every read, such as a non-blocking socket
+struct ChunkedReader {
+    cursor: Cursor<Vec<u8>>,
+    chunk: usize,
+}
+
+impl ChunkedReader {
+    fn new(bytes: &[u8], chunk: usize) -> Self {
+        Self { cursor: Cursor::new(bytes.to_vec()), chunk }
+    }
+}
+
+impl Read for ChunkedReader {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        let len = buf.len().min(self.chunk);
+        self.cursor.read(&mut buf[..len])
+    }
+}
+
+impl Seek for ChunkedReader {
+    fn seek(&mut self, pos: SeekFrom) -> Result<u64> {
+        self.cursor.seek(pos)
+    }
+}
+
+const FRAMES: [&[u8]; 8] = [
+    // DF17 airborne position
+    &hex!("8D40621D58C382D690C8AC2863A7"),
+    // DF17 airborne velocity
+    &hex!("8da3d42599250129780484712c50"),
+    // DF17 operation status
+    &hex!("8dacc040f8210002004ab8569c35"),
+    // DF11 all call reply
+    &hex!("5dab3d17d4ba29"),
+    // DF0 short air-air surveillance
+    &hex!("02e19cb02512c3"),
+    // DF4 surveillance altitude reply
+    &hex!("200012b0d96e39"),
+    // DF20 Comm-B altitude reply
+    &hex!("A000029C85E42F313000007047D3"),
+    // DF21 Comm-B identity reply
+    &hex!("A800161110010080E6000055C6F1"),
+];
+
+#[test]
+fn chunked_reader() {
+    for bytes in FRAMES {
+        let expected = Frame::from_bytes(bytes).unwrap();
+        for chunk in 1..=bytes.len() {
+            let frame = Frame::from_reader(ChunkedReader::new(bytes, chunk)).unwrap();
+            assert_eq!(frame.crc, expected.crc, "{bytes:02x?}, chunk: {chunk}");
+            assert_eq!(format!("{frame}"), format!("{expected}"), "{bytes:02x?}, chunk: {chunk}");
+            assert_eq!(frame.raw(), bytes, "chunk: {chunk}");
+        }
+    }
+}
+
+#[test]
+fn one_byte_reader_stream() {
+    // frames read back to back from the same stream
+    let stream: Vec<u8> = FRAMES.concat();
+    let mut reader = ChunkedReader::new(&stream, 1);
+    for bytes in FRAMES {
+        let expected = Frame::from_bytes(bytes).unwrap();
+        let frame = Frame::from_reader(&mut reader).unwrap();
+        assert_eq!(frame.crc, expected.crc, "{bytes:02x?}");
+        assert_eq!(format!("{frame}"), format!("{expected}"), "{bytes:02x?}");
+        assert_eq!(frame.raw(), bytes);
+    }
+}
+
+#[test]
+fn short_reader() {
+    // missing the last byte of the crc
+    let bytes = hex!("8D40621D58C382D690C8AC2863");
+    assert!(Frame::from_reader(ChunkedReader::new(&bytes, 1)).is_err());
+}
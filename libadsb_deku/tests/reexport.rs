@@ -0,0 +1,13 @@
+use adsb_deku::deku::prelude::*;
+use adsb_deku::{DekuError, Frame, DF};
+use hexlit::hex;
+
+#[test]
+fn deku_traits() {
+    let bytes = hex!("8da2c1bd587ba2adb31799cb802b");
+    let (_, df) = DF::from_bytes((&bytes, 0)).unwrap();
+    assert!(matches!(df, DF::ADSB(_)));
+
+    let error: DekuError = Frame::from_bytes(&bytes[..3]).unwrap_err();
+    assert!(matches!(error, DekuError::Incomplete(_)));
+}
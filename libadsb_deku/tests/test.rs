@@ -1,5 +1,14 @@
-use adsb_deku::adsb::{VerticalRateSource, ME};
-use adsb_deku::{CPRFormat, Capability, Frame, DF};
+use adsb_deku::adsb::{
+    AircraftStatusType, ControlField, EmergencyState, EmitterCategory, GpsAntennaOffset,
+    OperationStatus, TypeCoding, VerticalRateSource, ADSB, ME,
+};
+use adsb_deku::bds::{
+    BdsAttempt, BdsDecodeReport, BdsRejection, SelectedVerticalIntention, TrackAndTurnReport, BDS,
+};
+use adsb_deku::{
+    AltitudeDisplay, CPRFormat, Capability, DfFilter, DfFilterError, Frame, ReplyInformation,
+    SurveillanceStatus, DF, ICAO,
+};
 use assert_hex::assert_eq_hex;
 use hexlit::hex;
 use test_log::test;
@@ -15,12 +24,28 @@ fn testing01() {
             assert_eq!(me.lat_cpr, 93000);
             assert_eq!(me.lon_cpr, 51372);
             assert_eq!(me.odd_flag, CPRFormat::Even);
+            assert_eq!(me.nic(), Some(8));
             return;
         }
     }
     unreachable!();
 }
 
+#[test]
+fn testing01_position_with_reference() {
+    // from adsb-rs
+    let bytes = hex!("8D40621D58C382D690C8AC2863A7");
+    let frame = Frame::from_bytes(&bytes).unwrap();
+    let position = frame.position_with_reference((52.258, 3.918)).unwrap();
+    assert!((position.latitude - 52.257_202_148_437_5).abs() < f64::EPSILON);
+    assert!((position.longitude - 3.919_372_558_593_75).abs() < 1e-9);
+
+    // velocity frames don't contain a position
+    let bytes = hex!("8da3d42599250129780484712c50");
+    let frame = Frame::from_bytes(&bytes).unwrap();
+    assert_eq!(frame.position_with_reference((52.258, 3.918)), None);
+}
+
 #[test]
 fn testing02() {
     // from adsb-rs
@@ -191,6 +216,69 @@ fn testing08() {
     unreachable!();
 }
 
+#[test]
+fn testing_identification_8_characters() {
+    let frame = Frame::from_bytes(&hex!("8d4ca4ed2015a671cb3d010c353d")).unwrap();
+    assert_eq!(frame.crc, 0);
+    let DF::ADSB(adsb) = &frame.df else { unreachable!() };
+    let ME::AircraftIdentification(identification) = &adsb.me else { unreachable!() };
+    assert_eq!(identification.cn, "EZY1234A");
+    assert_eq!(identification.raw, 0x15a6_71cb_3d01);
+    assert_eq!(adsb.pi, ICAO(hex!("0c353d")));
+
+    // undefined character codes, and the trailing spaces
+    assert_eq!(adsb_deku::decode_callsign(0), "########");
+    assert_eq!(adsb_deku::decode_callsign(0x0428_200a_0820), "AB  B");
+}
+
+#[test]
+fn testing_reserved_values() {
+    // CA=2 and an aircraft status of sub type 5, both reserved
+    let frame = Frame::from_bytes(&hex!("8aabcdefe5000000000000000000")).unwrap();
+    let DF::ADSB(adsb) = &frame.df else { unreachable!() };
+    assert_eq!(adsb.capability, Capability::Reserved(2));
+    let ME::AircraftStatus(status) = adsb.me else { unreachable!() };
+    assert_eq!(status.sub_type, AircraftStatusType::Reserved(5));
+    assert_eq!(status.emergency_state, EmergencyState::None);
+
+    // DF11 with CA=3
+    let frame = Frame::from_bytes(&hex!("5babcdef000000")).unwrap();
+    let DF::AllCallReply { capability, icao, .. } = frame.df else { unreachable!() };
+    assert_eq!(capability, Capability::Reserved(3));
+    assert_eq!(icao, ICAO(hex!("abcdef")));
+
+    // operational status of sub type 2
+    let frame = Frame::from_bytes(&hex!("8dabcdeffa010203040506112233")).unwrap();
+    let DF::ADSB(adsb) = &frame.df else { unreachable!() };
+    assert_eq!(
+        adsb.me,
+        ME::AircraftOperationStatus(OperationStatus::Reserved(2, hex!("010203040506")))
+    );
+    assert_eq!(adsb.pi, ICAO(hex!("112233")));
+
+    // reserved type code 25, with the whole message
+    let frame = Frame::from_bytes(&hex!("8dabcdefcf010203040506112233")).unwrap();
+    let DF::ADSB(adsb) = &frame.df else { unreachable!() };
+    assert_eq!(adsb.me, ME::Reserved1(hex!("cf010203040506")));
+}
+
+#[test]
+fn testing_no_position() {
+    // TC=0: barometric altitude, without a position
+    let bytes = hex!("8d4840d602c3800000000056fd92");
+    let frame = Frame::from_bytes(&bytes).unwrap();
+    assert_eq!(frame.crc, 0);
+    let DF::ADSB(adsb) = &frame.df else { unreachable!() };
+    let ME::NoPosition(altitude) = adsb.me else { unreachable!() };
+    assert_eq!(altitude.tc, 0);
+    assert_eq!(altitude.ss, SurveillanceStatus::PermanentAlert);
+    assert_eq!(altitude.alt, Some(38000));
+    assert_eq!(altitude.nic(), None);
+    let display = frame.to_string();
+    assert!(display.contains("  Altitude:      38000 ft barometric"), "{display}");
+    assert!(display.contains("  Surveillance:  permanent alert"), "{display}");
+}
+
 //*02e19cb02512c3;
 //CRC: 0d097e
 //RSSI: -8.1 dBFS
@@ -211,9 +299,19 @@ fn testing_df_shortairairsurveillance() {
   ICAO Address:  0d097e (Mode S / ADS-B)
   Air/Ground:    airborne?
   Altitude:      45000 ft barometric
+  Reply info:    ACAS, vertical-only resolution
 "#,
         resulting_string
     );
+
+    // RI:11, without ACAS
+    let bytes = hex!("02e59cb02512c3");
+    let frame = Frame::from_bytes(&bytes).unwrap();
+    let DF::ShortAirAirSurveillance { ri, .. } = frame.df else { unreachable!() };
+    assert_eq!(ri, ReplyInformation::MaxAirspeed(11));
+    assert_eq!(ri.has_acas(), Some(false));
+    assert_eq!(ri.max_airspeed(), Some((150, Some(300))));
+    assert_eq!(ri.to_string(), "no ACAS, max airspeed 150-300 kt");
 }
 
 // -----new-----
@@ -387,6 +485,7 @@ fn testing_surveillanceidentityreply() {
   ICAO Address:  510af9 (Mode S / ADS-B)
   Air/Ground:    airborne
   Identity:      0356
+  Interrogator:  IIS 0, Comm-C reservation
 "#,
         resulting_string
     );
@@ -496,6 +595,7 @@ fn testing_issue_03() {
   ICAO Address:  ac049e (Mode S / ADS-B)
   Air/Ground:    airborne?
   Baro altitude: 35000 ft
+  Reply info:    ACAS, vertical-only resolution
 "#,
         resulting_string
     );
@@ -510,6 +610,7 @@ fn testing_issue_04() {
         r#" Short Air-Air Surveillance
   ICAO Address:  a33325 (Mode S / ADS-B)
   Air/Ground:    ground
+  Reply info:    ACAS, resolution inhibited
 "#,
         resulting_string
     );
@@ -524,6 +625,8 @@ fn testing_df_21() {
         r#" Comm-B, Identity Reply
     ICAO Address:  a95fdc (Mode S / ADS-B)
     Squawk:        6246
+    Downlink req:  Comm-B broadcast 1
+    Interrogator:  IIS 8, Comm-B reservation
     Comm-B format: unknown format
 "#,
         resulting_string
@@ -723,6 +826,7 @@ fn issue_11_12() {
         r#" Extended Squitter (Non-Transponder) No position information
   Address:       ef92b3 (TIS-B)
   Air/Ground:    airborne?
+  Altitude:      3100 ft barometric
 "#,
         resulting_string
     );
@@ -797,6 +901,7 @@ fn fix_issue_13() {
   ICAO Address:  ab92a2 (Mode S / ADS-B)
   Air/Ground:    airborne?
   Altitude:      10600 ft barometric
+  Reply info:    no ACAS
 "#,
         resulting_string
     );
@@ -895,6 +1000,8 @@ fn test_issue_16() {
         r#" Comm-B, Altitude Reply
   ICAO Address:  abef98 (Mode S / ADS-B)
   Altitude:      20300 ft
+  Downlink req:  Comm-B broadcast 1
+  Interrogator:  IIS 15, Comm-D reservation
   Comm-B format: unknown format
 "#,
         resulting_string
@@ -961,3 +1068,365 @@ fn test_df17_error() {
         resulting_string
     );
 }
+
+/// Build a DF17 airborne position (TC 11) frame with the 12 bit altitude field
+fn df17_airborne_position(alt_code: u64) -> [u8; 14] {
+    let me: u64 = (11 << 51) | (alt_code << 36) | (93000 << 17) | 51372;
+    let mut bytes = [0; 14];
+    bytes[..4].copy_from_slice(&[0x8d, 0x40, 0x62, 0x1d]);
+    bytes[4..11].copy_from_slice(&me.to_be_bytes()[1..]);
+    bytes
+}
+
+/// Build a DF4 surveillance altitude reply with the 13 bit altitude field
+fn df4_altitude_reply(ac_code: u32) -> [u8; 7] {
+    let bits: u32 = (4 << 27) | ac_code;
+    let mut bytes = [0; 7];
+    bytes[..4].copy_from_slice(&bits.to_be_bytes());
+    bytes
+}
+
+#[test]
+fn test_low_altitude_q_bit() {
+    // (n, altitude in feet) with altitude = n * 25 - 1000
+    let vectors = [(0, -1000), (32, -200), (40, 0), (41, 25), (60, 500), (80, 1000), (81, 1025)];
+    for (n, expected) in vectors {
+        let alt_code = ((n & 0x7f0) << 1) | 0x10 | (n & 0x0f);
+        let frame = Frame::from_bytes(&df17_airborne_position(alt_code)).unwrap();
+        let DF::ADSB(adsb) = frame.df else { unreachable!() };
+        let ME::AirbornePositionBaroAltitude(altitude) = adsb.me else { unreachable!() };
+        assert_eq!(altitude.alt, Some(expected), "n: {n}");
+
+        let ac_code =
+            ((n as u32 & 0x7e0) << 2) | ((n as u32 & 0x10) << 1) | 0x10 | (n as u32 & 0x0f);
+        let frame = Frame::from_bytes(&df4_altitude_reply(ac_code)).unwrap();
+        let DF::SurveillanceAltitudeReply { ac, .. } = frame.df else { unreachable!() };
+        assert_eq!(ac.0, Some(expected), "n: {n}");
+    }
+}
+
+#[test]
+fn test_altitude_unavailable() {
+    let frame = Frame::from_bytes(&df17_airborne_position(0)).unwrap();
+    let DF::ADSB(adsb) = frame.df else { unreachable!() };
+    let ME::AirbornePositionBaroAltitude(altitude) = adsb.me else { unreachable!() };
+    assert_eq!(altitude.alt, None);
+
+    let frame = Frame::from_bytes(&df4_altitude_reply(0)).unwrap();
+    let DF::SurveillanceAltitudeReply { ac, .. } = frame.df else { unreachable!() };
+    assert_eq!(ac.0, None);
+}
+
+#[test]
+fn test_comm_b_bds40() {
+    // from pyModeS
+    let bytes = hex!("A000029C85E42F313000007047D3");
+    let frame = Frame::from_bytes(&bytes).unwrap();
+    let resulting_string = format!("{frame}");
+    assert_eq!(
+        r#" Comm-B, Altitude Reply
+  ICAO Address:  4243d0 (Mode S / ADS-B)
+  Altitude:      3300 ft
+  Comm-B format: BDS4,0 Vertical intent
+  MCP selected altitude: 3008 ft
+  FMS selected altitude: 3008 ft
+  QNH:                   1020.0 millibars
+"#,
+        resulting_string
+    );
+}
+
+#[test]
+fn test_comm_b_bds50() {
+    // from pyModeS
+    let bytes = hex!("A000139381951536E024D4CCF6B5");
+    let frame = Frame::from_bytes(&bytes).unwrap();
+    let DF::CommBAltitudeReply { bds: BDS::TrackAndTurnReport(bds), .. } = &frame.df else {
+        unreachable!();
+    };
+    assert_eq!(bds.roll_angle, Some(2.109_375));
+    assert_eq!(bds.ground_speed, Some(438));
+    assert_eq!(bds.true_airspeed, Some(424));
+    let resulting_string = format!("{frame}");
+    assert_eq!(
+        r#" Comm-B, Altitude Reply
+  ICAO Address:  3c4dd2 (Mode S / ADS-B)
+  Altitude:      30275 ft
+  Comm-B format: BDS5,0 Track and turn report
+  Groundspeed:   438.0 kt
+  TAS:           424 kt
+  Ground track:  114.3
+  Track rate:    0.12 deg/sec right
+  Roll:          2.1 degrees right
+"#,
+        resulting_string
+    );
+}
+
+#[test]
+fn test_comm_b_bds60() {
+    // from pyModeS
+    let bytes = hex!("A00004128F39F91A7E27C46ADC21");
+    let frame = Frame::from_bytes(&bytes).unwrap();
+    let DF::CommBAltitudeReply { bds: BDS::HeadingAndSpeedReport(bds), .. } = &frame.df else {
+        unreachable!();
+    };
+    assert_eq!(bds.indicated_airspeed, Some(252));
+    assert_eq!(bds.barometric_altitude_rate, Some(-1920));
+    let resulting_string = format!("{frame}");
+    assert_eq!(
+        r#" Comm-B, Altitude Reply
+  ICAO Address:  48507f (Mode S / ADS-B)
+  Altitude:      5450 ft
+  Comm-B format: BDS6,0 Heading and speed report
+  IAS:           252 kt
+  Mach number:   0.420
+  Mag heading:   42.7
+  Baro rate:     -1920 ft/min
+  Geom rate:     -1920 ft/min
+"#,
+        resulting_string
+    );
+}
+
+#[test]
+fn test_comm_b_dump1090_display() {
+    // values and formats of dump1090 --interactive off, as golden output
+    let bds = BDS::TrackAndTurnReport(TrackAndTurnReport {
+        roll_angle: Some(-12.3),
+        true_track: None,
+        ground_speed: Some(250),
+        track_angle_rate: Some(-0.5),
+        true_airspeed: None,
+    });
+    assert_eq!(
+        r#"Comm-B format: BDS5,0 Track and turn report
+  Groundspeed:   250.0 kt
+  Track rate:    -0.50 deg/sec left
+  Roll:          -12.3 degrees left
+"#,
+        bds.to_string()
+    );
+
+    let bds = BDS::TrackAndTurnReport(TrackAndTurnReport {
+        roll_angle: Some(0.0),
+        true_track: Some(0.0),
+        ground_speed: None,
+        track_angle_rate: None,
+        true_airspeed: Some(300),
+    });
+    assert_eq!(
+        r#"Comm-B format: BDS5,0 Track and turn report
+  TAS:           300 kt
+  Ground track:  0.0
+  Roll:          0.0 degrees
+"#,
+        bds.to_string()
+    );
+
+    let bds = BDS::SelectedVerticalIntention(SelectedVerticalIntention {
+        mcp_selected_altitude: None,
+        fms_selected_altitude: Some(35008),
+        barometric_pressure_setting: None,
+    });
+    assert_eq!(
+        "Comm-B format: BDS4,0 Vertical intent\n  FMS selected altitude: 35008 ft\n",
+        bds.to_string()
+    );
+
+    let plausible = |register| BdsAttempt { register, rejection: None };
+    let bds = BDS::Unknown(BdsDecodeReport {
+        mb: [0; 7],
+        attempts: vec![plausible((4, 0)), plausible((5, 0))],
+    });
+    assert_eq!("Comm-B format: ambiguous format\n", bds.to_string());
+    let rejected = BdsAttempt { register: (4, 0), rejection: Some(BdsRejection::NoValue) };
+    let bds = BDS::Unknown(BdsDecodeReport { mb: [0; 7], attempts: vec![rejected] });
+    assert_eq!("Comm-B format: unknown format\n", bds.to_string());
+}
+
+#[test]
+fn test_icao_from_parity() {
+    // DF20, from pyModeS
+    let bytes = hex!("A000029C85E42F313000007047D3");
+    let frame = Frame::from_bytes(&bytes).unwrap();
+    let icao = ICAO([0x42, 0x43, 0xd0]);
+    assert_eq!(frame.icao_from_parity(&[icao][..]), Some(icao));
+    assert_eq!(frame.icao_from_parity(&[ICAO([0xab, 0xcd, 0xef])][..]), None);
+
+    // DF17 contains the address
+    let bytes = hex!("8D40621D58C382D690C8AC2863A7");
+    let frame = Frame::from_bytes(&bytes).unwrap();
+    assert_eq!(frame.icao_from_parity(&[ICAO([0x40, 0x62, 0x1d])][..]), None);
+}
+
+#[test]
+fn test_df_filter() {
+    let filter: DfFilter = "0,4, 5,16-21".parse().unwrap();
+    for df in [0, 4, 5, 16, 17, 18, 19, 20, 21] {
+        assert!(filter.contains(df), "{df}");
+    }
+    for df in [1, 11, 15, 22, 24, 31, 32] {
+        assert!(!filter.contains(df), "{df}");
+    }
+    assert_eq!(
+        filter,
+        DfFilter::none()
+            .with(0)
+            .with(4)
+            .with(5)
+            .with(16)
+            .with(17)
+            .with(18)
+            .with(19)
+            .with(20)
+            .with(21)
+    );
+
+    // first byte of DF17 and DF11 frames
+    let filter: DfFilter = "17".parse().unwrap();
+    assert!(filter.accepts(&hex!("8D40621D58C382D690C8AC2863A7")));
+    assert!(!filter.accepts(&hex!("5dab3d17d4ba29")));
+    assert!(!filter.accepts(&[]));
+    assert!(DfFilter::default().accepts(&hex!("5dab3d17d4ba29")));
+
+    assert_eq!("".parse::<DfFilter>(), Err(DfFilterError::InvalidNumber));
+    assert_eq!("17,a".parse::<DfFilter>(), Err(DfFilterError::InvalidNumber));
+    assert_eq!("0-32".parse::<DfFilter>(), Err(DfFilterError::OutOfRange(32)));
+    assert_eq!("300".parse::<DfFilter>(), Err(DfFilterError::OutOfRange(300)));
+    assert_eq!("21-16".parse::<DfFilter>(), Err(DfFilterError::ReversedRange(21, 16)));
+}
+
+#[test]
+fn test_gps_antenna_offset() {
+    // github issue #8 frame with GPS antenna offset 0b101_00110
+    let bytes = hex!("96A4D01FF9002106A6493075E234");
+    let frame = Frame::from_bytes(&bytes).unwrap();
+    let DF::TisB { cf: ControlField { me, .. }, .. } = &frame.df else { unreachable!() };
+    let ME::AircraftOperationStatus(OperationStatus::Surface(status)) = me else { unreachable!() };
+    let offset = status.gps_antenna_offset;
+    assert_eq!(offset, GpsAntennaOffset { lateral: 0b101, longitudinal: 0b00110 });
+    assert_eq!(offset.lateral_meters(), Some(2));
+    assert_eq!(offset.longitudinal_meters(), Some(10));
+    assert!(frame.to_string().contains("   Antenna offset:     2 m right, 10 m aft of nose\n"));
+
+    let offset = GpsAntennaOffset { lateral: 0b011, longitudinal: 0b11111 };
+    assert_eq!(offset.lateral_meters(), Some(-6));
+    assert_eq!(offset.longitudinal_meters(), Some(60));
+    assert_eq!(offset.to_string(), "6 m left, 60 m aft of nose");
+
+    let offset = GpsAntennaOffset { lateral: 0b100, longitudinal: 1 };
+    assert_eq!(offset.lateral_meters(), Some(0));
+    assert_eq!(offset.longitudinal_meters(), None);
+    assert!(offset.applied_by_sensor());
+    assert_eq!(offset.to_string(), "0 m, applied by sensor");
+
+    let offset = GpsAntennaOffset { lateral: 0, longitudinal: 0 };
+    assert!(offset.is_no_data());
+    assert_eq!(offset.to_string(), "lateral N/A, longitudinal N/A");
+}
+
+#[test]
+fn test_emitter_category() {
+    let bytes = hex!("8da08f94233d1775e7e9e0000000");
+    let frame = Frame::from_bytes(&bytes).unwrap();
+    let DF::ADSB(ADSB { me: ME::AircraftIdentification(identification), .. }) = &frame.df else {
+        unreachable!()
+    };
+    let category = identification.category();
+    assert_eq!(category.to_string(), "A3");
+    assert!(!category.is_surface_vehicle());
+    assert!(!category.is_obstruction());
+
+    for (ca, surface_vehicle, obstruction) in
+        [(0, false, false), (1, true, false), (2, false, false), (3, true, false), (4, false, true)]
+    {
+        let category = EmitterCategory { tc: TypeCoding::C, ca };
+        assert_eq!(category.is_surface_vehicle(), surface_vehicle, "C{ca}");
+        assert_eq!(category.is_obstruction(), obstruction, "C{ca}");
+    }
+}
+
+#[test]
+fn test_comm_b_unknown_report() {
+    // test_issue_16
+    let bytes = hex!("a227ed3417826515bebd01707629");
+    let frame = Frame::from_bytes(&bytes).unwrap();
+    let DF::CommBAltitudeReply { bds: BDS::Unknown(report), .. } = &frame.df else {
+        unreachable!();
+    };
+    assert_eq!(report.mb, hex!("17826515bebd01"));
+    assert_eq!(
+        report.attempts,
+        [
+            BdsAttempt {
+                register: (4, 0),
+                rejection: Some(BdsRejection::Reserved { bit: 40, len: 8 })
+            },
+            BdsAttempt { register: (5, 0), rejection: Some(BdsRejection::Status { bit: 1 }) },
+            BdsAttempt { register: (6, 0), rejection: Some(BdsRejection::Status { bit: 1 }) },
+        ]
+    );
+    assert_eq!(report.plausible().count(), 0);
+
+    // test_comm_b_bds60 with an IAS of 600 kt
+    let bytes = hex!("a00004128f3cb11a7e27c46adc21");
+    let frame = Frame::from_bytes(&bytes).unwrap();
+    let DF::CommBAltitudeReply { bds: BDS::Unknown(report), .. } = &frame.df else {
+        unreachable!();
+    };
+    let rejection = report.attempts[2].rejection.unwrap();
+    assert_eq!(rejection, BdsRejection::Range { bit: 13, value: 600.0, min: 1.0, max: 500.0 });
+    assert_eq!(rejection.to_string(), "value of status bit 13: 600 outside of 1..=500");
+}
+
+#[test]
+fn altitude_display() {
+    let display = |altitude| AltitudeDisplay::new(altitude, 6000);
+    assert_eq!(display(38050).flight_level(), Some(380));
+    assert_eq!(display(6000).flight_level(), Some(60));
+    assert_eq!(display(5975).flight_level(), None);
+    assert_eq!(display(38050).to_string(), "FL380");
+    assert_eq!(display(6500).to_string(), "FL065");
+    assert_eq!(display(5975).to_string(), "5975 ft");
+    assert_eq!(display(-200).to_string(), "-200 ft");
+}
+
+#[test]
+fn display_flight_levels() {
+    for (bytes, feet, flight_level) in [
+        // DF17 airborne position
+        (
+            &hex!("8d40621d58c382d690c8ac2863a7")[..],
+            "  Altitude:      38000 ft barometric\n",
+            "  Altitude:      FL380 barometric\n",
+        ),
+        // DF16
+        (
+            &hex!("80e1969058b5025b9850641d2974")[..],
+            "  Baro altitude: 35000 ft\n",
+            "  Baro altitude: FL350\n",
+        ),
+        // DF20
+        (
+            &hex!("a0001910204d7075d35820c25c0c")[..],
+            "  Altitude:      39000 ft\n",
+            "  Altitude:      FL390\n",
+        ),
+    ] {
+        let frame = Frame::from_bytes(bytes).unwrap();
+        let display = frame.to_string();
+        assert!(display.contains(feet), "{display}");
+        let flight_levels = frame.display_flight_levels(18000).to_string();
+        assert_eq!(flight_levels, display.replace(feet, flight_level));
+        // below the transition altitude
+        assert_eq!(frame.display_flight_levels(40000).to_string(), display);
+    }
+
+    // DF18 (TIS-B) airborne position at 700 ft
+    let frame = Frame::from_bytes(&hex!("95298FCA680946499671468C7ACA")).unwrap();
+    let display = frame.display_flight_levels(18000).to_string();
+    assert!(display.contains("  Altitude:      700 ft barometric\n"), "{display}");
+    let display = frame.display_flight_levels(500).to_string();
+    assert!(display.contains("  Altitude:      FL007 barometric\n"), "{display}");
+}
@@ -0,0 +1,140 @@
+//! A `Frame` encoded with `Frame::to_bytes` must decode to the same values and CRC, and the bytes
+//! of the `deku` writers must match the received frames
+
+use adsb_deku::adsb::{Identification, TypeCoding, ADSB, ME};
+use adsb_deku::bds::{TrackAndTurnReport, BDS};
+use adsb_deku::deku::{DekuContainerRead, DekuContainerWrite};
+use adsb_deku::{encode_callsign, AC13Field, Altitude, Capability, DekuError, Frame, DF, ICAO};
+use hexlit::hex;
+
+const TEST_STR: &str = include_str!("../tests/lax-messages.txt");
+
+#[test]
+fn recorded() {
+    // a sample of the recorded frames, encoding all of them is too slow for debug builds
+    for line in TEST_STR.lines().step_by(50) {
+        let bytes = hex::decode(&line[1..line.len() - 1]).unwrap();
+        let frame = Frame::from_bytes(&bytes).unwrap();
+        let encoded = frame.to_bytes().unwrap();
+        let decoded = Frame::from_bytes(&encoded).unwrap();
+        assert_eq!(decoded.to_string(), frame.to_string(), "{line}");
+        assert_eq!(decoded.crc, frame.crc, "{line}");
+        assert_eq!(decoded.to_bytes().unwrap(), encoded, "{line}");
+    }
+}
+
+#[test]
+fn same_bytes() {
+    for bytes in [
+        // DF17 airborne position, velocity, target state and operational status
+        &hex!("8d40621d58c382d690c8ac2863a7")[..],
+        &hex!("8da3d42599250129780484712c50"),
+        &hex!("8da08f94ea1b785e8f3c088ab467"),
+        &hex!("8dacc040f8210002004ab8569c35"),
+        // DF18 TIS-B
+        &hex!("96a082fb213b1cf2113820d6eddf"),
+        // DF11 all call reply
+        &hex!("5dab3d17d4ba29"),
+        // DF5 surveillance identity reply
+        &hex!("2a000818af8bf5"),
+        // DF20 Comm-B, aircraft identification, track and turn report, heading and speed report
+        &hex!("a0001910204d7075d35820c25c0c"),
+        &hex!("a000139381951536e024d4ccf6b5"),
+        &hex!("a00004128f39f91a7e27c46adc21"),
+        // DF21 Comm-B
+        &hex!("ae24238d15ee315463718b1af755"),
+    ] {
+        let frame = Frame::from_bytes(bytes).unwrap();
+        assert_eq!(frame.to_bytes().unwrap(), bytes, "{frame:?}");
+    }
+}
+
+#[test]
+fn gillham_altitude() {
+    // DF0 with a gillham coded altitude of 5300 ft, written in 25 ft increments
+    let bytes = hex!("0000108a46f754");
+    let frame = Frame::from_bytes(&bytes).unwrap();
+    let DF::ShortAirAirSurveillance { altitude, .. } = frame.df else { unreachable!() };
+    assert_eq!(altitude, AC13Field(Some(5300)));
+
+    let encoded = frame.to_bytes().unwrap();
+    assert_eq!(encoded[..4], hex!("000003bc"));
+    let decoded = Frame::from_bytes(&encoded).unwrap();
+    let DF::ShortAirAirSurveillance { altitude, .. } = decoded.df else { unreachable!() };
+    assert_eq!(altitude, AC13Field(Some(5300)));
+    // same aircraft address from the overlaid parity
+    assert_eq!(decoded.crc, frame.crc);
+
+    // above the 25 ft increments, only gillham coded
+    let altitude = Altitude { alt: Some(60_000), ..Altitude::default() };
+    let mut bytes = [0; 7];
+    altitude.to_slice(&mut bytes).unwrap();
+    let (_, decoded) = Altitude::from_bytes((&bytes, 0)).unwrap();
+    assert_eq!(decoded.alt, Some(60_000));
+
+    // not a multiple of 25 ft
+    let altitude = Altitude { alt: Some(10_010), ..Altitude::default() };
+    assert!(matches!(altitude.to_slice(&mut bytes), Err(DekuError::InvalidParam(_))));
+    // above the 25 ft increments, not a multiple of 100 ft
+    let altitude = Altitude { alt: Some(60_050), ..Altitude::default() };
+    assert!(matches!(
+        altitude.to_slice(&mut bytes),
+        Err(DekuError::InvalidParam(e)) if e.contains("100 ft")
+    ));
+}
+
+#[test]
+fn new_frame() {
+    // new identification with the parity of a valid frame
+    let me = ME::AircraftIdentification(Identification {
+        tc: TypeCoding::A,
+        ca: 0,
+        raw: encode_callsign("KLM1023").unwrap(),
+        cn: String::new(),
+    });
+    let df = DF::ADSB(ADSB {
+        capability: Capability::AG_AIRBORNE,
+        icao: ICAO([0x48, 0x40, 0xd6]),
+        me,
+        pi: ICAO([0; 3]),
+    });
+    let frame = Frame::new(df, 0);
+    let bytes = frame.to_bytes().unwrap();
+    assert_eq!(bytes, hex!("8d4840d6202cc371c32ce0576098"));
+
+    let decoded = Frame::from_bytes(&bytes).unwrap();
+    assert_eq!(decoded.crc, 0);
+    // the raw bytes aren't compared
+    assert_eq!(Frame::new(decoded.df.clone(), decoded.crc), decoded);
+    assert_eq!(decoded.raw(), bytes);
+    assert_eq!(frame.raw(), []);
+    let DF::ADSB(ADSB { me: ME::AircraftIdentification(identification), .. }) = decoded.df else {
+        unreachable!()
+    };
+    assert_eq!(identification.cn, "KLM1023");
+}
+
+#[test]
+fn bds() {
+    let bds = BDS::TrackAndTurnReport(TrackAndTurnReport {
+        roll_angle: Some(-2.109_375),
+        true_track: Some(245.917_97),
+        ground_speed: Some(432),
+        track_angle_rate: None,
+        true_airspeed: Some(424),
+    });
+    let mut bytes = [0; 7];
+    assert_eq!(bds.to_slice(&mut bytes).unwrap(), 7);
+    // DF20 of the MB field, decoded as the same register
+    let mut frame = vec![0xa0, 0x00, 0x18, 0x38];
+    frame.extend_from_slice(&bytes);
+    frame.extend_from_slice(&[0; 3]);
+    let DF::CommBAltitudeReply { bds: decoded, .. } = Frame::from_bytes(&frame).unwrap().df else {
+        unreachable!()
+    };
+    assert_eq!(decoded, bds);
+
+    BDS::AircraftIdentification("KLM1023".to_string()).to_slice(&mut bytes).unwrap();
+    assert_eq!(bytes, hex!("202cc371c32ce0"));
+    assert!(BDS::AircraftIdentification("klm".to_string()).to_slice(&mut bytes).is_err());
+}
@@ -0,0 +1,119 @@
+//! Barometric and GNSS altitudes of an aircraft, and the [`AltitudeSource`] used by each consumer
+//!
+//! The airborne positions contain either the barometric altitude (TC 9-18, the pressure altitude
+//! referenced to 1013.25 hPa, as flown and shown by the air traffic control) or the GNSS altitude
+//! (TC 20-22, above the WGS84 ellipsoid). Most aircraft only send the barometric altitude, with
+//! the difference to the GNSS altitude in their airborne velocities. Each consumer of the
+//! altitude, such as the map labels or an export, chooses its [`AltitudeSource`] and gets the
+//! altitude with [`AirplaneState::altitude`], falling back to any known altitude.
+//!
+//! ```rust
+//! use adsb_deku::Frame;
+//! use rsadsb_common::altitude::AltitudeSource;
+//! use rsadsb_common::Airplanes;
+//!
+//! let mut airplanes = Airplanes::default();
+//! // barometric altitude of 38000 ft
+//! let bytes = [0x8d, 0x40, 0x62, 0x1d, 0x58, 0xc3, 0x82, 0xd6, 0x90, 0xc8, 0xac, 0x28, 0x63, 0xa7];
+//! airplanes.action(Frame::from_bytes(&bytes).unwrap());
+//! // airborne velocity, with the GNSS altitude 550 ft above the barometric altitude
+//! let bytes = [0x8d, 0x40, 0x62, 0x1d, 0x99, 0x44, 0x09, 0x94, 0x08, 0x38, 0x17, 0x5b, 0x28, 0x4f];
+//! airplanes.action(Frame::from_bytes(&bytes).unwrap());
+//!
+//! let (_, state) = airplanes.iter().next().unwrap();
+//! assert_eq!(state.gnss_baro_diff, Some(550));
+//! assert_eq!(state.altitude(AltitudeSource::Baro), Some(38000));
+//! assert_eq!(state.altitude(AltitudeSource::GnssCorrected), Some(38550));
+//! // without a GNSS altitude
+//! assert_eq!(state.altitude(AltitudeSource::Gnss), Some(38000));
+//! ```
+//!
+//! The altitudes are displayed as flight levels at or above the transition altitude, and in feet
+//! below, with [`AltitudeDisplay`] of `adsb_deku`, also used by
+//! [`adsb_deku::Frame::display_flight_levels`]:
+//!
+//! ```rust
+//! use rsadsb_common::altitude::AltitudeDisplay;
+//!
+//! assert_eq!(AltitudeDisplay::new(35000, 18000).to_string(), "FL350");
+//! assert_eq!(AltitudeDisplay::new(3000, 18000).to_string(), "3000 ft");
+//! ```
+//!
+//! [`AirplaneState::altitude`]: crate::AirplaneState::altitude
+
+#[cfg(feature = "alloc")]
+use core::{
+    clone::Clone,
+    cmp::{Eq, PartialEq},
+    default::Default,
+    fmt::Debug,
+    marker::Copy,
+    prelude::rust_2021::derive,
+    result::Result::{self, Err, Ok},
+    write,
+};
+
+pub use adsb_deku::AltitudeDisplay;
+
+/// Altitude used by a consumer, see [`self`]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AltitudeSource {
+    /// Barometric altitude, also from the altitude replies and SBS messages
+    #[default]
+    Baro,
+    /// GNSS altitude of the TC 20-22 positions
+    Gnss,
+    /// GNSS altitude, else the barometric altitude corrected by the difference to the GNSS
+    /// altitude of the airborne velocities
+    GnssCorrected,
+}
+
+impl AltitudeSource {
+    /// Name used by [`core::str::FromStr`] and [`core::fmt::Display`]
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Baro => "baro",
+            Self::Gnss => "gnss",
+            Self::GnssCorrected => "gnss-corrected",
+        }
+    }
+}
+
+impl core::fmt::Display for AltitudeSource {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl core::str::FromStr for AltitudeSource {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "baro" => Ok(Self::Baro),
+            "gnss" => Ok(Self::Gnss),
+            "gnss-corrected" => Ok(Self::GnssCorrected),
+            _ => Err("expected baro, gnss or gnss-corrected"),
+        }
+    }
+}
+
+/// GNSS type codes of the airborne positions
+pub(crate) const fn is_gnss(tc: u8) -> bool {
+    matches!(tc, 20..=22)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        for source in [AltitudeSource::Baro, AltitudeSource::Gnss, AltitudeSource::GnssCorrected] {
+            assert_eq!(source.name().parse(), Ok(source));
+        }
+        assert!("geometric".parse::<AltitudeSource>().is_err());
+    }
+}
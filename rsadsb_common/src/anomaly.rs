@@ -0,0 +1,195 @@
+//! Detection of broadcast changes that usually indicate decode errors or spoofing
+//!
+//! The transponder capability, ADS-B version and NIC/NAC values of an aircraft shouldn't change
+//! mid-flight, and an aircraft shouldn't alternate between two callsigns. Each detected change
+//! is logged as a `tracing` event and counted in [`Anomalies`].
+
+#[cfg(feature = "alloc")]
+use alloc::string::String;
+#[cfg(feature = "alloc")]
+use core::{
+    clone::Clone,
+    cmp::{Eq, PartialEq},
+    default::Default,
+    fmt::Debug,
+    marker::Copy,
+    option::Option::{self, Some},
+    prelude::rust_2021::derive,
+};
+
+use adsb_deku::adsb::ADSBVersion;
+use adsb_deku::{Capability, ICAO};
+use tracing::warn;
+
+use crate::equipage::TransponderLevel;
+
+/// Counters of detected anomalies
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Anomalies {
+    /// Transponder level changed, see [`Capability`]
+    pub capability_change: u32,
+    /// ADS-B version changed
+    pub version_change: u32,
+    /// NIC supplement-A changed
+    pub nic_change: u32,
+    /// NACp changed
+    pub nac_change: u32,
+    /// Callsign changed back to the previous callsign
+    pub callsign_alternation: u32,
+    /// Callsign rejected, see [`crate::ident::is_valid_callsign`]
+    pub invalid_callsign: u32,
+}
+
+impl Anomalies {
+    /// Sum of all counters
+    #[must_use]
+    pub const fn total(&self) -> u32 {
+        self.capability_change
+            + self.version_change
+            + self.nic_change
+            + self.nac_change
+            + self.callsign_alternation
+            + self.invalid_callsign
+    }
+}
+
+impl core::ops::AddAssign for Anomalies {
+    fn add_assign(&mut self, other: Self) {
+        self.capability_change += other.capability_change;
+        self.version_change += other.version_change;
+        self.nic_change += other.nic_change;
+        self.nac_change += other.nac_change;
+        self.callsign_alternation += other.callsign_alternation;
+        self.invalid_callsign += other.invalid_callsign;
+    }
+}
+
+/// Last broadcast values of an aircraft, used for detecting [`Anomalies`]
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BroadcastHistory {
+    /// from `adsb::ADSB::capability` and the capability of `DF::AllCallReply`
+    pub capability: Option<Capability>,
+    /// from `adsb::OperationStatus`
+    pub version: Option<ADSBVersion>,
+    /// from `adsb::OperationStatus`
+    pub nic_supplement_a: Option<u8>,
+    /// from `adsb::OperationStatus`
+    pub nacp: Option<u8>,
+    /// Callsign before the current callsign
+    pub previous_callsign: Option<String>,
+    pub anomalies: Anomalies,
+}
+
+impl BroadcastHistory {
+    /// update from `adsb::ADSB::capability` and the capability of `DF::AllCallReply`
+    ///
+    /// Changing between on ground and airborne is expected, only a change of the transponder
+    /// level is counted.
+    pub(crate) fn update_capability(&mut self, icao: ICAO, capability: Capability) {
+        if let Some(previous) = self.capability {
+            if TransponderLevel::from_capability(previous)
+                != TransponderLevel::from_capability(capability)
+            {
+                warn!("[{icao}] capability changed: {previous} -> {capability}");
+                self.anomalies.capability_change += 1;
+            }
+        }
+        self.capability = Some(capability);
+    }
+
+    /// update from `adsb::OperationStatus::{Airborne, Surface}`
+    pub(crate) fn update_operation_status(
+        &mut self,
+        icao: ICAO,
+        version: ADSBVersion,
+        nic_supplement_a: u8,
+        nacp: u8,
+    ) {
+        if let Some(previous) = self.version {
+            if previous != version {
+                warn!("[{icao}] ADS-B version changed: {previous} -> {version}");
+                self.anomalies.version_change += 1;
+            }
+        }
+        if let Some(previous) = self.nic_supplement_a {
+            if previous != nic_supplement_a {
+                warn!("[{icao}] NIC supplement-A changed: {previous} -> {nic_supplement_a}");
+                self.anomalies.nic_change += 1;
+            }
+        }
+        if let Some(previous) = self.nacp {
+            if previous != nacp {
+                warn!("[{icao}] NACp changed: {previous} -> {nacp}");
+                self.anomalies.nac_change += 1;
+            }
+        }
+        self.version = Some(version);
+        self.nic_supplement_a = Some(nic_supplement_a);
+        self.nacp = Some(nacp);
+    }
+
+    /// update before `current` callsign is replaced by `callsign`
+    pub(crate) fn update_callsign(&mut self, icao: ICAO, current: Option<&str>, callsign: &str) {
+        let Some(current) = current else {
+            return;
+        };
+        if current == callsign {
+            return;
+        }
+        if self.previous_callsign.as_deref() == Some(callsign) {
+            warn!("[{icao}] callsign alternating: {current} -> {callsign}");
+            self.anomalies.callsign_alternation += 1;
+        }
+        self.previous_callsign = Some(String::from(current));
+    }
+
+    /// `callsign` was rejected, see [`crate::ident::is_valid_callsign`]
+    pub(crate) fn reject_callsign(&mut self, icao: ICAO, callsign: &str) {
+        warn!("[{icao}] invalid callsign: {callsign:?}");
+        self.anomalies.invalid_callsign += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn capability_change() {
+        let icao = ICAO([0xab, 0xcd, 0xef]);
+        let mut history = BroadcastHistory::default();
+        history.update_capability(icao, Capability::AG_GROUND);
+        history.update_capability(icao, Capability::AG_AIRBORNE);
+        assert_eq!(history.anomalies.capability_change, 0);
+        history.update_capability(icao, Capability::AG_UNCERTAIN);
+        assert_eq!(history.anomalies.capability_change, 1);
+    }
+
+    #[test]
+    fn operation_status_change() {
+        let icao = ICAO([0xab, 0xcd, 0xef]);
+        let mut history = BroadcastHistory::default();
+        history.update_operation_status(icao, ADSBVersion::DOC9871AppendixC, 0, 9);
+        history.update_operation_status(icao, ADSBVersion::DOC9871AppendixC, 0, 9);
+        assert_eq!(history.anomalies, Anomalies::default());
+        history.update_operation_status(icao, ADSBVersion::DOC9871AppendixB, 1, 8);
+        assert_eq!(history.anomalies.version_change, 1);
+        assert_eq!(history.anomalies.nic_change, 1);
+        assert_eq!(history.anomalies.nac_change, 1);
+        assert_eq!(history.anomalies.total(), 3);
+    }
+
+    #[test]
+    fn callsign_alternation() {
+        let icao = ICAO([0xab, 0xcd, 0xef]);
+        let mut history = BroadcastHistory::default();
+        history.update_callsign(icao, None, "ABC123");
+        history.update_callsign(icao, Some("ABC123"), "ABC123");
+        history.update_callsign(icao, Some("ABC123"), "XYZ789");
+        assert_eq!(history.anomalies.callsign_alternation, 0);
+        history.update_callsign(icao, Some("XYZ789"), "ABC123");
+        assert_eq!(history.anomalies.callsign_alternation, 1);
+    }
+}
@@ -0,0 +1,234 @@
+//! Fixed-size records of the closest aircraft, for microcontroller-driven displays
+//!
+//! [`Airplanes::compact_snapshot`] returns the aircraft with a position sorted by distance from
+//! the receiver, as [`CompactAircraft`] of [`CompactAircraft::SIZE`] bytes without any
+//! allocation on the receiving side. Each record is sent over UART or SPI with
+//! [`CompactAircraft::to_le_bytes`].
+//!
+//! ```rust
+//! use adsb_deku::Frame;
+//! use rsadsb_common::compact::{CompactAircraft, FLAG_ALTITUDE};
+//! use rsadsb_common::Airplanes;
+//!
+//! let mut airplanes = Airplanes::builder().position((52.0, 4.0)).build();
+//! // even and odd airborne positions at 38000 ft
+//! for bytes in [
+//!     [0x8d, 0x40, 0x62, 0x1d, 0x58, 0xc3, 0x82, 0xd6, 0x90, 0xc8, 0xac, 0x28, 0x63, 0xa7],
+//!     [0x8d, 0x40, 0x62, 0x1d, 0x58, 0xc3, 0x86, 0x43, 0x5c, 0xc4, 0x12, 0x69, 0x2a, 0xd6],
+//! ] {
+//!     airplanes.action(Frame::from_bytes(&bytes).unwrap());
+//! }
+//! let snapshot = airplanes.compact_snapshot::<8>(8);
+//! assert_eq!(snapshot.len(), 1);
+//! assert_eq!(snapshot[0].icao, 0x40621d);
+//! assert_eq!(snapshot[0].altitude(), Some(38000));
+//! assert_ne!(snapshot[0].flags & FLAG_ALTITUDE, 0);
+//! assert_eq!(snapshot[0].to_le_bytes().len(), CompactAircraft::SIZE);
+//! ```
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+#[cfg(feature = "alloc")]
+use core::{
+    clone::Clone,
+    cmp::{Ord, PartialEq},
+    fmt::Debug,
+    iter::Iterator,
+    marker::Copy,
+    option::Option::{self, Some},
+    prelude::rust_2021::derive,
+};
+
+use crate::{squawk, AirplaneState, Airplanes};
+
+/// `alt` is known
+pub const FLAG_ALTITUDE: u8 = 1 << 0;
+
+/// `heading` is known
+pub const FLAG_HEADING: u8 = 1 << 1;
+
+/// Reported on the ground
+pub const FLAG_ON_GROUND: u8 = 1 << 2;
+
+/// Squawking 7500, 7600 or 7700
+pub const FLAG_EMERGENCY: u8 = 1 << 3;
+
+/// Feet of each unit of [`CompactAircraft::alt`], the resolution of the Mode S altitude
+pub const ALT_UNIT: i32 = 25;
+
+/// Aircraft with a position, in the memory layout of a C struct without padding
+///
+/// ```c
+/// struct compact_aircraft {
+///     uint32_t icao;
+///     float lat;
+///     float lon;
+///     int16_t alt;
+///     uint8_t heading;
+///     uint8_t flags;
+/// };
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[repr(C)]
+pub struct CompactAircraft {
+    /// 24-bit ICAO address
+    pub icao: u32,
+    pub lat: f32,
+    pub lon: f32,
+    /// Altitude in [`ALT_UNIT`] (ft), 0 without [`FLAG_ALTITUDE`]
+    pub alt: i16,
+    /// Heading in 1/256 of a turn (1.40625°), 0 = North, 0 without [`FLAG_HEADING`]
+    pub heading: u8,
+    /// `FLAG_*` bits
+    pub flags: u8,
+}
+
+impl CompactAircraft {
+    /// Bytes of [`Self::to_le_bytes`], also the size of the struct
+    pub const SIZE: usize = 16;
+
+    /// Record of `state`, `None` without a position
+    #[must_use]
+    pub fn new(icao: u32, state: &AirplaneState) -> Option<Self> {
+        let position = state.coords.position?;
+        let mut flags = 0;
+        let alt = state.coords.altitude().map_or(0, |altitude| {
+            flags |= FLAG_ALTITUDE;
+            (altitude / ALT_UNIT).clamp(i32::from(i16::MIN), i32::from(i16::MAX)) as i16
+        });
+        let heading = state.heading.map_or(0, |heading| {
+            flags |= FLAG_HEADING;
+            binary_angle(heading)
+        });
+        if state.is_on_ground() == Some(true) {
+            flags |= FLAG_ON_GROUND;
+        }
+        if state.squawk.is_some_and(squawk::is_emergency) {
+            flags |= FLAG_EMERGENCY;
+        }
+        Some(Self {
+            icao,
+            lat: position.latitude as f32,
+            lon: position.longitude as f32,
+            alt,
+            heading,
+            flags,
+        })
+    }
+
+    /// Altitude (ft), `None` without [`FLAG_ALTITUDE`]
+    #[must_use]
+    pub fn altitude(&self) -> Option<i32> {
+        (self.flags & FLAG_ALTITUDE != 0).then(|| i32::from(self.alt) * ALT_UNIT)
+    }
+
+    /// Heading (degrees), `None` without [`FLAG_HEADING`]
+    #[must_use]
+    pub fn heading_deg(&self) -> Option<f32> {
+        (self.flags & FLAG_HEADING != 0).then(|| f32::from(self.heading) * 360.0 / 256.0)
+    }
+
+    /// Fields in order and little-endian, independently of the target
+    #[must_use]
+    pub fn to_le_bytes(&self) -> [u8; Self::SIZE] {
+        let mut bytes = [0; Self::SIZE];
+        bytes[0..4].copy_from_slice(&self.icao.to_le_bytes());
+        bytes[4..8].copy_from_slice(&self.lat.to_le_bytes());
+        bytes[8..12].copy_from_slice(&self.lon.to_le_bytes());
+        bytes[12..14].copy_from_slice(&self.alt.to_le_bytes());
+        bytes[14] = self.heading;
+        bytes[15] = self.flags;
+        bytes
+    }
+}
+
+/// `heading` (degrees) in 1/256 of a turn
+fn binary_angle(heading: f32) -> u8 {
+    let turn = libm::roundf(heading / 360.0 * 256.0) as i32;
+    turn.rem_euclid(256) as u8
+}
+
+impl Airplanes {
+    /// At most `max_n` (and `N`) aircraft with a position, the closest to the receiver first
+    ///
+    /// Aircraft without a distance, such as without a receiver position, are last.
+    #[must_use]
+    pub fn compact_snapshot<const N: usize>(
+        &self,
+        max_n: usize,
+    ) -> heapless::Vec<CompactAircraft, N> {
+        let mut aircraft: Vec<_> = self
+            .iter()
+            .filter_map(|(icao, state)| {
+                let icao = u32::from_be_bytes([0, icao.0[0], icao.0[1], icao.0[2]]);
+                Some((state.coords.kilo_distance, CompactAircraft::new(icao, state)?))
+            })
+            .collect();
+        aircraft.sort_by(|(a, _), (b, _)| match (a, b) {
+            (Some(a), Some(b)) => a.total_cmp(b),
+            (a, b) => b.is_some().cmp(&a.is_some()),
+        });
+        aircraft.into_iter().take(max_n).map(|(_, aircraft)| aircraft).take(N).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use adsb_deku::ICAO;
+
+    use super::*;
+
+    #[test]
+    fn layout() {
+        assert_eq!(core::mem::size_of::<CompactAircraft>(), CompactAircraft::SIZE);
+        let aircraft = CompactAircraft {
+            icao: 0x00ab_cdef,
+            lat: 1.0,
+            lon: -1.0,
+            alt: -2,
+            heading: 64,
+            flags: FLAG_ALTITUDE | FLAG_HEADING,
+        };
+        assert_eq!(
+            aircraft.to_le_bytes(),
+            [0xef, 0xcd, 0xab, 0x00, 0, 0, 0x80, 0x3f, 0, 0, 0x80, 0xbf, 0xfe, 0xff, 64, 0b11]
+        );
+        assert_eq!(aircraft.altitude(), Some(-50));
+        assert_eq!(aircraft.heading_deg(), Some(90.0));
+    }
+
+    #[test]
+    fn binary_angles() {
+        assert_eq!(binary_angle(0.0), 0);
+        assert_eq!(binary_angle(180.0), 128);
+        assert_eq!(binary_angle(359.9), 0);
+        assert_eq!(binary_angle(-90.0), 192);
+    }
+
+    #[test]
+    fn closest_first() {
+        let mut airplanes = Airplanes::new();
+        for (i, kilo_distance) in [Some(30.0), None, Some(10.0), Some(20.0)].into_iter().enumerate()
+        {
+            let state = airplanes.airplanes.entry(ICAO([0, 0, i as u8])).or_default();
+            state.coords.position =
+                Some(adsb_deku::cpr::Position { latitude: 52.0, longitude: 4.0 });
+            state.coords.kilo_distance = kilo_distance;
+            state.coords.sbs_altitude = Some(45000);
+        }
+        airplanes.airplanes.entry(ICAO([0, 0, 9])).or_default();
+        // DF5 of 000002 squawking 7700
+        let bytes = [0x28, 0x00, 0x0a, 0xaa, 0x4a, 0xa4, 0xcb];
+        airplanes.action(adsb_deku::Frame::from_bytes(&bytes).unwrap());
+        assert_eq!(airplanes.get(ICAO([0, 0, 2])).unwrap().squawk, Some(squawk::EMERGENCY));
+
+        let snapshot = airplanes.compact_snapshot::<8>(8);
+        let icaos: Vec<_> = snapshot.iter().map(|aircraft| aircraft.icao).collect();
+        assert_eq!(icaos, [2, 3, 0, 1]);
+        assert_eq!(snapshot[0].altitude(), Some(45000));
+        assert_eq!(snapshot[0].flags, FLAG_ALTITUDE | FLAG_EMERGENCY);
+
+        assert_eq!(airplanes.compact_snapshot::<8>(2).len(), 2);
+        assert_eq!(airplanes.compact_snapshot::<3>(8).len(), 3);
+    }
+}
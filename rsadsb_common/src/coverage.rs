@@ -0,0 +1,292 @@
+//! Max range of the receiver by bearing and altitude band, such as for publishing the antenna
+//! performance
+//!
+//! With the `svg` feature, [`RangeCoverage::to_svg`] renders the ranges as a polar diagram.
+//!
+//! ```rust
+//! use rsadsb_common::coverage::RangeCoverage;
+//!
+//! let mut coverage = RangeCoverage::new();
+//! coverage.add(90.0, 250.0, 36000);
+//! coverage.add(92.0, 300.0, 38000);
+//! coverage.add(90.0, 80.0, 3000);
+//! assert_eq!(coverage.max_range(), 300.0);
+//! assert_eq!(coverage.range(3, 90.0), 300.0);
+//! assert_eq!(coverage.range(0, 90.0), 80.0);
+//! ```
+
+#[cfg(feature = "alloc")]
+use alloc::{format, string::String};
+#[cfg(feature = "alloc")]
+use alloc::{vec, vec::Vec};
+#[cfg(feature = "svg")]
+use core::fmt::Write;
+#[cfg(feature = "alloc")]
+use core::{
+    clone::Clone, cmp::PartialEq, default::Default, fmt::Debug, iter::Iterator,
+    option::Option::Some, prelude::rust_2021::derive,
+};
+
+use adsb_deku::ICAO;
+
+use crate::Airplanes;
+
+/// Width of each bearing bin (degrees)
+pub const BEARING_STEP: f64 = 5.0;
+
+/// Amount of bearing bins, covering 0 to 360 degrees
+pub const BEARING_BINS: usize = 72;
+
+/// Lowest altitude (ft) of each band, lower altitudes are in the first band
+pub const ALTITUDE_BANDS: [i32; 4] = [0, 10_000, 20_000, 30_000];
+
+/// Fill and stroke of each band in the SVG, from red for low altitudes to blue for high altitudes
+#[cfg(feature = "svg")]
+const BAND_COLORS: [&str; ALTITUDE_BANDS.len()] = ["#d62728", "#ff7f0e", "#2ca02c", "#1f77b4"];
+
+/// Width and height of the SVG
+#[cfg(feature = "svg")]
+const SVG_SIZE: f64 = 640.0;
+
+/// Radius of the outer range ring in the SVG
+#[cfg(feature = "svg")]
+const SVG_RADIUS: f64 = 260.0;
+
+/// Max range (km) of each [`ALTITUDE_BANDS`] by bearing from the receiver, see [`self`]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RangeCoverage {
+    /// [bearing bin][band], 0.0 without a position
+    ranges: Vec<[f64; ALTITUDE_BANDS.len()]>,
+}
+
+impl Default for RangeCoverage {
+    fn default() -> Self {
+        Self { ranges: vec![[0.0; ALTITUDE_BANDS.len()]; BEARING_BINS] }
+    }
+}
+
+impl RangeCoverage {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a position at `bearing` (degrees) and `kilo_distance` (km) from the receiver, of an
+    /// aircraft at `altitude` (ft)
+    pub fn add(&mut self, bearing: f64, kilo_distance: f64, altitude: i32) {
+        let range = &mut self.ranges[bin(bearing)][altitude_band(altitude)];
+        *range = range.max(kilo_distance);
+    }
+
+    /// Add the current position of `icao`, if any
+    pub fn update(&mut self, airplanes: &Airplanes, icao: ICAO) {
+        // not `Airplanes::aircraft_details`, which clones the track, for every frame received
+        let Some(coords) = airplanes.get(icao).map(|state| &state.coords) else {
+            return;
+        };
+        if let (Some(_), Some(altitude), Some(kilo_distance)) =
+            (coords.position, coords.altitude(), coords.kilo_distance)
+        {
+            self.add(coords.bearing.unwrap_or_default(), kilo_distance, altitude);
+        }
+    }
+
+    /// Max range (km) of the altitude `band` at `bearing` (degrees)
+    #[must_use]
+    pub fn range(&self, band: usize, bearing: f64) -> f64 {
+        self.ranges[bin(bearing)][band]
+    }
+
+    /// Max range (km) of all bearings and bands
+    #[must_use]
+    pub fn max_range(&self) -> f64 {
+        self.ranges.iter().flatten().copied().fold(0.0, f64::max)
+    }
+
+    /// Polar diagram of the ranges with `title`, with North up and a polygon for each band
+    #[cfg(feature = "svg")]
+    #[must_use]
+    pub fn to_svg(&self, title: &str) -> String {
+        let center = SVG_SIZE / 2.0;
+        let ring_step = ring_step(self.max_range());
+        let outer = ring_step * 4.0;
+        let scale = SVG_RADIUS / outer;
+        let point = |bearing: f64, kilo_distance: f64| {
+            let bearing = bearing.to_radians();
+            let radius = kilo_distance * scale;
+            (center + radius * libm::sin(bearing), center - radius * libm::cos(bearing))
+        };
+
+        let mut svg = String::new();
+        // writing to a String can't fail
+        let _ = writeln!(
+            svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{SVG_SIZE}" height="{SVG_SIZE}" viewBox="0 0 {SVG_SIZE} {SVG_SIZE}" font-family="sans-serif" font-size="12">"#
+        );
+        let _ = writeln!(svg, r#"<rect width="100%" height="100%" fill="white"/>"#);
+        let _ = writeln!(
+            svg,
+            r#"<text x="{center}" y="24" font-size="16" text-anchor="middle">{}</text>"#,
+            escape(title)
+        );
+
+        // range rings and labels
+        for ring in 1..=4 {
+            let kilo_distance = ring_step * f64::from(ring);
+            let _ = writeln!(
+                svg,
+                r##"<circle cx="{center}" cy="{center}" r="{:.1}" fill="none" stroke="#bbbbbb"/>"##,
+                kilo_distance * scale
+            );
+            let (x, y) = point(45.0, kilo_distance);
+            let _ = writeln!(
+                svg,
+                r##"<text x="{x:.1}" y="{y:.1}" fill="#777777">{kilo_distance} km</text>"##
+            );
+        }
+
+        // bearing lines and cardinal directions
+        for bearing in (0..360).step_by(30) {
+            let (x, y) = point(f64::from(bearing), outer);
+            let _ = writeln!(
+                svg,
+                r##"<line x1="{center}" y1="{center}" x2="{x:.1}" y2="{y:.1}" stroke="#dddddd"/>"##
+            );
+        }
+        for (bearing, name) in [(0.0, "N"), (90.0, "E"), (180.0, "S"), (270.0, "W")] {
+            let (x, y) = point(bearing, outer + 14.0 / scale);
+            let _ = writeln!(
+                svg,
+                r#"<text x="{x:.1}" y="{y:.1}" text-anchor="middle" dominant-baseline="middle">{name}</text>"#
+            );
+        }
+
+        // highest band first, so the lower bands are drawn on top
+        for band in (0..ALTITUDE_BANDS.len()).rev() {
+            let points: Vec<_> = self
+                .ranges
+                .iter()
+                .enumerate()
+                .map(|(bin, ranges)| {
+                    let bearing = (bin as f64 + 0.5) * BEARING_STEP;
+                    let (x, y) = point(bearing, ranges[band]);
+                    format!("{x:.1},{y:.1}")
+                })
+                .collect();
+            let color = BAND_COLORS[band];
+            let _ = writeln!(
+                svg,
+                r#"<polygon points="{}" fill="{color}" fill-opacity="0.3" stroke="{color}"/>"#,
+                points.join(" ")
+            );
+        }
+
+        // legend
+        for (band, color) in BAND_COLORS.iter().enumerate() {
+            let y = SVG_SIZE - 20.0 - 18.0 * (ALTITUDE_BANDS.len() - 1 - band) as f64;
+            let label = band_label(band);
+            let _ = writeln!(
+                svg,
+                r#"<rect x="12" y="{:.1}" width="12" height="12" fill="{color}" fill-opacity="0.6"/><text x="30" y="{y:.1}">{label}</text>"#,
+                y - 10.0
+            );
+        }
+        let _ = writeln!(svg, "</svg>");
+        svg
+    }
+}
+
+/// Index in [`ALTITUDE_BANDS`] of `altitude` (ft)
+#[must_use]
+pub fn altitude_band(altitude: i32) -> usize {
+    ALTITUDE_BANDS.iter().rposition(|lowest| altitude >= *lowest).unwrap_or(0)
+}
+
+/// Altitudes of `band`, such as "10000-20000 ft"
+#[must_use]
+pub fn band_label(band: usize) -> String {
+    let lowest = ALTITUDE_BANDS[band];
+    match ALTITUDE_BANDS.get(band + 1) {
+        Some(highest) => format!("{lowest}-{highest} ft"),
+        None => format!("{lowest}+ ft"),
+    }
+}
+
+/// Bearing bin of `bearing` (degrees)
+fn bin(bearing: f64) -> usize {
+    let bearing = bearing % 360.0;
+    let bearing = if bearing < 0.0 { bearing + 360.0 } else { bearing };
+    (libm::floor(bearing / BEARING_STEP) as usize).min(BEARING_BINS - 1)
+}
+
+/// Distance (km) between the 4 range rings, a round number containing `max_range`
+#[cfg(feature = "svg")]
+fn ring_step(max_range: f64) -> f64 {
+    [10.0, 25.0, 50.0, 75.0, 100.0, 125.0, 150.0]
+        .into_iter()
+        .find(|step| step * 4.0 >= max_range)
+        .unwrap_or_else(|| libm::ceil(max_range / 4.0 / 50.0) * 50.0)
+}
+
+/// Escape the XML special characters of `text`
+#[cfg(feature = "svg")]
+fn escape(text: &str) -> String {
+    let mut escaped = String::new();
+    for c in text.chars() {
+        match c {
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '&' => escaped.push_str("&amp;"),
+            '"' => escaped.push_str("&quot;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bands() {
+        let mut coverage = RangeCoverage::new();
+        coverage.add(359.9, 100.0, -200);
+        coverage.add(0.0, 150.0, 9999);
+        coverage.add(360.0, 120.0, 10_000);
+        assert_eq!(coverage.range(0, 359.0), 100.0);
+        assert_eq!(coverage.range(0, 2.0), 150.0);
+        assert_eq!(coverage.range(1, 0.0), 120.0);
+        assert_eq!(coverage.range(2, 0.0), 0.0);
+        assert_eq!(coverage.max_range(), 150.0);
+
+        assert_eq!(altitude_band(-200), 0);
+        assert_eq!(altitude_band(45000), 3);
+        assert_eq!(band_label(0), "0-10000 ft");
+        assert_eq!(band_label(3), "30000+ ft");
+    }
+
+    #[cfg(feature = "svg")]
+    #[test]
+    fn svg() {
+        let mut coverage = RangeCoverage::new();
+        for bearing in 0..360 {
+            coverage.add(f64::from(bearing), 320.0, 35000);
+            coverage.add(f64::from(bearing), 90.0, 4000);
+        }
+        let svg = coverage.to_svg("KCLT <rx1>");
+        assert!(svg.starts_with("<svg "));
+        assert!(svg.trim_end().ends_with("</svg>"));
+        assert!(svg.contains("KCLT &lt;rx1&gt;"));
+        assert_eq!(svg.matches("<polygon").count(), 4);
+        assert_eq!(svg.matches("<circle").count(), 4);
+        // rings of 100 km for ranges up to 400 km
+        assert!(svg.contains(">400 km<"));
+        assert!(svg.contains(">30000+ ft<"));
+
+        assert_eq!(ring_step(0.0), 10.0);
+        assert_eq!(ring_step(580.0), 150.0);
+        assert_eq!(ring_step(700.0), 200.0);
+    }
+}
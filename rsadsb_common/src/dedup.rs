@@ -0,0 +1,100 @@
+//! Suppression of duplicate frames
+//!
+//! Receivers with multiple antennas, or re-broadcasting feeds, can emit the same squitter more
+//! than once. [`Dedup`] drops frames with the same raw bytes that were already seen within a
+//! small time window, before they are decoded and given to [`crate::Airplanes::action`].
+
+use std::collections::{BTreeMap, VecDeque};
+use std::time::{Duration, Instant};
+
+/// Duplicate frame filter, keyed on the raw frame bytes
+#[derive(Debug, Clone)]
+pub struct Dedup {
+    window: Duration,
+    /// Last time each frame was seen
+    seen: BTreeMap<Vec<u8>, Instant>,
+    /// Frames in the order they were seen, for expiring `seen`
+    expiry: VecDeque<(Instant, Vec<u8>)>,
+    /// Amount of frames passed through the filter
+    pub passed: u64,
+    /// Amount of frames suppressed as duplicates
+    pub suppressed: u64,
+}
+
+impl Dedup {
+    /// Suppress frames with the same bytes seen within `window`
+    #[must_use]
+    pub fn new(window: Duration) -> Self {
+        Self { window, seen: BTreeMap::new(), expiry: VecDeque::new(), passed: 0, suppressed: 0 }
+    }
+
+    /// Return true if `bytes` were already seen within the window, updating the counters
+    pub fn is_duplicate(&mut self, bytes: &[u8]) -> bool {
+        self.is_duplicate_at(bytes, Instant::now())
+    }
+
+    /// Same as [`Self::is_duplicate`], with `now` as the time `bytes` were received
+    pub fn is_duplicate_at(&mut self, bytes: &[u8], now: Instant) -> bool {
+        self.expire(now);
+
+        if self.seen.contains_key(bytes) {
+            self.suppressed += 1;
+            return true;
+        }
+
+        self.seen.insert(bytes.to_vec(), now);
+        self.expiry.push_back((now, bytes.to_vec()));
+        self.passed += 1;
+        false
+    }
+
+    /// Remove frames older than the window
+    fn expire(&mut self, now: Instant) {
+        while let Some((time, _)) = self.expiry.front() {
+            if now.saturating_duration_since(*time) < self.window {
+                break;
+            }
+            if let Some((time, bytes)) = self.expiry.pop_front() {
+                if self.seen.get(&bytes) == Some(&time) {
+                    self.seen.remove(&bytes);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FRAME: [u8; 7] = [0x5d, 0xab, 0x3d, 0x17, 0xd4, 0xba, 0x29];
+    const OTHER: [u8; 7] = [0x5d, 0xa0, 0x39, 0xb4, 0x6d, 0x7d, 0x81];
+
+    #[test]
+    fn duplicate_within_window() {
+        let start = Instant::now();
+        let mut dedup = Dedup::new(Duration::from_millis(100));
+
+        assert!(!dedup.is_duplicate_at(&FRAME, start));
+        assert!(!dedup.is_duplicate_at(&OTHER, start));
+        assert!(dedup.is_duplicate_at(&FRAME, start + Duration::from_millis(50)));
+        assert!(dedup.is_duplicate_at(&OTHER, start + Duration::from_millis(99)));
+        assert_eq!(dedup.passed, 2);
+        assert_eq!(dedup.suppressed, 2);
+    }
+
+    #[test]
+    fn expired_after_window() {
+        let start = Instant::now();
+        let mut dedup = Dedup::new(Duration::from_millis(100));
+
+        assert!(!dedup.is_duplicate_at(&FRAME, start));
+        assert!(!dedup.is_duplicate_at(&FRAME, start + Duration::from_millis(100)));
+        assert!(dedup.is_duplicate_at(&FRAME, start + Duration::from_millis(150)));
+        assert!(!dedup.is_duplicate_at(&FRAME, start + Duration::from_millis(200)));
+        assert_eq!(dedup.passed, 3);
+        assert_eq!(dedup.suppressed, 1);
+        assert_eq!(dedup.seen.len(), 1);
+        assert_eq!(dedup.expiry.len(), 1);
+    }
+}
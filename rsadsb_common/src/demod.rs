@@ -0,0 +1,107 @@
+//! Statistics of the pulse-level metadata of the received frames
+//!
+//! A demodulator frontend attaches a [`DemodMeta`] to the frames it outputs, see
+//! [`adsb_deku::demod`]. A [`DemodStats`] sums them, such as for showing the reception quality
+//! of the receiver.
+//!
+//! ```rust
+//! use adsb_deku::demod::DemodMeta;
+//! use rsadsb_common::demod::DemodStats;
+//!
+//! let mut stats = DemodStats::default();
+//! stats.add(&DemodMeta::default().with_preamble_score(0.9).with_corrected_bits(0));
+//! stats.add(&DemodMeta::default().with_preamble_score(0.7).with_corrected_bits(2));
+//! assert_eq!(stats.frames, 2);
+//! assert_eq!(stats.corrected_frames, 1);
+//! assert!((stats.mean_preamble_score().unwrap() - 0.8).abs() < 1e-6);
+//! ```
+
+#[cfg(feature = "alloc")]
+use core::{
+    clone::Clone,
+    cmp::PartialEq,
+    default::Default,
+    fmt::Debug,
+    option::Option::{self, Some},
+    prelude::rust_2021::derive,
+};
+
+use adsb_deku::demod::DemodMeta;
+
+/// Sampling phases counted in [`DemodStats::phases`], the higher phases are in the last
+pub const PHASES: usize = 8;
+
+/// Sums of the [`DemodMeta`] of the received frames, see [`self`]
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DemodStats {
+    /// Frames with a [`DemodMeta`]
+    pub frames: u64,
+    preamble_score_sum: f64,
+    preamble_scores: u64,
+    signal_level_sum: u64,
+    signal_levels: u64,
+    /// Frames with at least a corrected bit
+    pub corrected_frames: u64,
+    /// Bits corrected in all frames
+    pub corrected_bits: u64,
+    /// Frames by [`DemodMeta::phase`]
+    pub phases: [u64; PHASES],
+}
+
+impl DemodStats {
+    pub fn add(&mut self, meta: &DemodMeta) {
+        self.frames += 1;
+        if let Some(score) = meta.preamble_score {
+            self.preamble_score_sum += f64::from(score);
+            self.preamble_scores += 1;
+        }
+        if let Some(bits) = meta.corrected_bits.filter(|bits| *bits > 0) {
+            self.corrected_frames += 1;
+            self.corrected_bits += u64::from(bits);
+        }
+        if let Some(phase) = meta.phase {
+            self.phases[usize::from(phase).min(PHASES - 1)] += 1;
+        }
+        if let Some(signal_level) = meta.signal_level {
+            self.signal_level_sum += u64::from(signal_level);
+            self.signal_levels += 1;
+        }
+    }
+
+    /// Mean [`DemodMeta::preamble_score`], `None` without any score
+    #[must_use]
+    pub fn mean_preamble_score(&self) -> Option<f64> {
+        (self.preamble_scores > 0).then(|| self.preamble_score_sum / self.preamble_scores as f64)
+    }
+
+    /// Mean [`DemodMeta::signal_level`], `None` without any signal level
+    #[must_use]
+    pub fn mean_signal_level(&self) -> Option<f64> {
+        (self.signal_levels > 0).then(|| self.signal_level_sum as f64 / self.signal_levels as f64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn demod_stats() {
+        let mut stats = DemodStats::default();
+        assert_eq!(stats.mean_preamble_score(), None);
+
+        stats.add(&DemodMeta::default());
+        stats.add(&DemodMeta::default().with_corrected_bits(1).with_phase(2));
+        stats.add(&DemodMeta::default().with_corrected_bits(2).with_phase(12));
+        assert_eq!(stats.frames, 3);
+        assert_eq!(stats.mean_preamble_score(), None);
+        assert_eq!((stats.corrected_frames, stats.corrected_bits), (2, 3));
+        assert_eq!(stats.phases, [0, 0, 1, 0, 0, 0, 0, 1]);
+        assert_eq!(stats.mean_signal_level(), None);
+
+        stats.add(&DemodMeta::default().with_signal_level(0x80));
+        stats.add(&DemodMeta::default().with_signal_level(0x40));
+        assert_eq!(stats.mean_signal_level(), Some(96.0));
+    }
+}
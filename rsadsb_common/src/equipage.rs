@@ -0,0 +1,84 @@
+//! Transponder level and ADS-B version of aircraft
+//!
+//! The transponder level is from the capability of DF11 all-call replies and DF17 extended
+//! squitters, the ADS-B version from `ME::AircraftOperationStatus`. Together they classify the
+//! equipage of the aircraft seen by a receiver, see [`crate::Airplanes::fleet_equipage`].
+
+#[cfg(feature = "alloc")]
+use core::{
+    clone::Clone,
+    cmp::{Eq, PartialEq},
+    default::Default,
+    fmt::Debug,
+    marker::Copy,
+    option::Option::{self, None, Some},
+    prelude::rust_2021::derive,
+};
+
+use adsb_deku::adsb::ADSBVersion;
+use adsb_deku::Capability;
+
+/// Transponder level, from [`Capability`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TransponderLevel {
+    /// Surveillance only
+    Level1,
+    /// Level 2 or above, with Comm-B data link
+    Level2,
+}
+
+impl TransponderLevel {
+    /// Level of `capability`, `None` if reserved
+    #[must_use]
+    pub const fn from_capability(capability: Capability) -> Option<Self> {
+        match capability {
+            Capability::AG_UNCERTAIN => Some(Self::Level1),
+            Capability::Reserved(_) => None,
+            _ => Some(Self::Level2),
+        }
+    }
+}
+
+/// Equipage of an aircraft, see [`crate::AirplaneState::equipage`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Equipage {
+    /// from the last capability received, `None` if not yet received or reserved
+    pub transponder_level: Option<TransponderLevel>,
+    /// from the last `ME::AircraftOperationStatus`
+    pub adsb_version: Option<ADSBVersion>,
+}
+
+/// Amount of aircraft by equipage, see [`crate::Airplanes::fleet_equipage`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FleetEquipage {
+    pub aircraft: u32,
+    /// [`TransponderLevel::Level1`]
+    pub level_1: u32,
+    /// [`TransponderLevel::Level2`]
+    pub level_2: u32,
+    /// ADS-B version 0, 1 and 2
+    pub adsb_version: [u32; 3],
+    /// Aircraft without a received ADS-B version
+    pub unknown_version: u32,
+}
+
+impl FleetEquipage {
+    /// Count an aircraft with `equipage`
+    pub fn add(&mut self, equipage: Equipage) {
+        self.aircraft += 1;
+        match equipage.transponder_level {
+            Some(TransponderLevel::Level1) => self.level_1 += 1,
+            Some(TransponderLevel::Level2) => self.level_2 += 1,
+            None => (),
+        }
+        match equipage.adsb_version {
+            Some(ADSBVersion::DOC9871AppendixA) => self.adsb_version[0] += 1,
+            Some(ADSBVersion::DOC9871AppendixB) => self.adsb_version[1] += 1,
+            Some(ADSBVersion::DOC9871AppendixC) => self.adsb_version[2] += 1,
+            None => self.unknown_version += 1,
+        }
+    }
+}
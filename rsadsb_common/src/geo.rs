@@ -0,0 +1,151 @@
+//! Receiver-centric geometry, such as the bearing and elevation of an aircraft
+
+/// Mean radius of the earth (km)
+pub(crate) const EARTH_RADIUS: f64 = 6371.00;
+
+/// Feet to kilometers
+const FT_TO_KM: f64 = 0.000_304_8;
+
+/// Initial bearing (degrees) from `from` to `to`, both (latitude, longitude)
+///
+/// 0 = North
+/// 90 = East, and so on
+#[must_use]
+pub fn bearing(from: (f64, f64), to: (f64, f64)) -> f64 {
+    let lat1 = from.0.to_radians();
+    let lat2 = to.0.to_radians();
+    let delta_long = (to.1 - from.1).to_radians();
+
+    let y = libm::sin(delta_long) * libm::cos(lat2);
+    let x = libm::cos(lat1) * libm::sin(lat2)
+        - libm::sin(lat1) * libm::cos(lat2) * libm::cos(delta_long);
+
+    let bearing = libm::atan2(y, x).to_degrees();
+    if bearing < 0.0 {
+        bearing + 360.0
+    } else {
+        bearing
+    }
+}
+
+/// Great-circle distance (km) between `from` and `to`, both (latitude, longitude)
+///
+/// <https://en.wikipedia.org/wiki/Haversine_formula>
+#[must_use]
+pub fn distance(from: (f64, f64), to: (f64, f64)) -> f64 {
+    let lat1 = from.0.to_radians();
+    let lat2 = to.0.to_radians();
+    let x_lat = libm::sin((lat2 - lat1) / 2.0);
+    let x_long = libm::sin((to.1 - from.1).to_radians() / 2.0);
+
+    // this clippy lint will dis-allow mul_add, this isn't available for `no_std`
+    #[allow(clippy::suboptimal_flops)]
+    let a = x_lat * x_lat + libm::cos(lat1) * libm::cos(lat2) * x_long * x_long;
+
+    2.0 * EARTH_RADIUS * libm::atan2(libm::sqrt(a), libm::sqrt(1.0 - a))
+}
+
+/// Point (latitude, longitude) at `kilo_distance` (km) from `from` along the great circle
+/// starting at `bearing` (degrees)
+#[must_use]
+pub fn destination(from: (f64, f64), bearing: f64, kilo_distance: f64) -> (f64, f64) {
+    let lat1 = from.0.to_radians();
+    let long1 = from.1.to_radians();
+    let bearing = bearing.to_radians();
+    let angle = kilo_distance / EARTH_RADIUS;
+
+    let lat2 = libm::asin(
+        libm::sin(lat1) * libm::cos(angle)
+            + libm::cos(lat1) * libm::sin(angle) * libm::cos(bearing),
+    );
+    let long2 = long1
+        + libm::atan2(
+            libm::sin(bearing) * libm::sin(angle) * libm::cos(lat1),
+            libm::cos(angle) - libm::sin(lat1) * libm::sin(lat2),
+        );
+
+    // normalize to -180..180
+    let long2 = (long2.to_degrees() + 540.0) % 360.0 - 180.0;
+    (lat2.to_degrees(), long2)
+}
+
+/// Elevation angle (degrees) from a receiver at sea level to an aircraft at `altitude` (ft)
+/// that is `kilo_distance` (km) away along the ground
+///
+/// This includes the curvature of the earth, so far away aircraft at low altitudes have a
+/// negative elevation.
+#[must_use]
+pub fn elevation(kilo_distance: f64, altitude: f64) -> f64 {
+    let angle = kilo_distance / EARTH_RADIUS;
+    let aircraft_radius = EARTH_RADIUS + altitude * FT_TO_KM;
+
+    let up = aircraft_radius * libm::cos(angle) - EARTH_RADIUS;
+    let across = aircraft_radius * libm::sin(angle);
+    libm::atan2(up, across).to_degrees()
+}
+
+/// Radio horizon (km) of an aircraft at `altitude` (ft) for a receiver at sea level
+///
+/// With the standard 4/3 earth radius for the refraction of the atmosphere, aircraft further
+/// away are below the horizon and can't be received.
+#[must_use]
+pub fn radio_horizon(altitude: f64) -> f64 {
+    let height = altitude.max(0.0) * FT_TO_KM;
+    libm::sqrt(2.0 * EARTH_RADIUS * 4.0 / 3.0 * height)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bearing_cardinal() {
+        assert!((bearing((0.0, 0.0), (1.0, 0.0)) - 0.0).abs() < 1e-9);
+        assert!((bearing((0.0, 0.0), (0.0, 1.0)) - 90.0).abs() < 1e-9);
+        assert!((bearing((0.0, 0.0), (-1.0, 0.0)) - 180.0).abs() < 1e-9);
+        assert!((bearing((0.0, 0.0), (0.0, -1.0)) - 270.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn great_circle_distance() {
+        assert_eq!(distance((35.0, -80.0), (35.0, -80.0)), 0.0);
+        // one degree of latitude
+        assert!((distance((0.0, 0.0), (1.0, 0.0)) - 111.195).abs() < 1e-3);
+        // Charlotte to Atlanta
+        let kilo_distance = distance((35.214, -80.943), (33.640, -84.427));
+        assert!((kilo_distance - 364.3).abs() < 0.1);
+        assert_eq!(kilo_distance, distance((33.640, -84.427), (35.214, -80.943)));
+    }
+
+    #[test]
+    fn horizon() {
+        assert_eq!(radio_horizon(0.0), 0.0);
+        // 4.12 km * sqrt(meters)
+        assert!((radio_horizon(35000.0) - 4.12 * libm::sqrt(35000.0 * 0.3048)).abs() < 1.0);
+        assert!(radio_horizon(10000.0) < radio_horizon(35000.0));
+    }
+
+    #[test]
+    fn great_circle_destination() {
+        let (lat, long) = destination((0.0, 0.0), 90.0, 111.195);
+        assert!(lat.abs() < 1e-9 && (long - 1.0).abs() < 1e-4);
+        // back to Atlanta
+        let from = (35.214, -80.943);
+        let to = destination(from, bearing(from, (33.640, -84.427)), 364.3);
+        assert!(distance(to, (33.640, -84.427)) < 0.1);
+        // across the antimeridian
+        let (_, long) = destination((0.0, 179.5), 90.0, 111.195);
+        assert!((long + 179.5).abs() < 1e-3);
+    }
+
+    #[test]
+    fn elevation_angle() {
+        // overhead
+        assert!((elevation(0.0, 10_000.0) - 90.0).abs() < 1e-9);
+        // 10 km away at 10 km altitude, slightly less than 45 degrees from curvature
+        let angle = elevation(10.0, 10.0 / FT_TO_KM);
+        assert!(angle < 45.0 && angle > 44.9);
+        // far away and low, below the horizon
+        assert!(elevation(400.0, 1000.0) < 0.0);
+    }
+}
@@ -0,0 +1,114 @@
+//! Throttling of incoming frames on constrained devices
+//!
+//! At busy sites a small device such as a Raspberry Pi Zero can't decode every frame and still
+//! keep the display responsive. [`Governor`] limits the amount of frames per second given to
+//! [`crate::Airplanes::action`], while always passing the frames that move airplanes on screen.
+//!
+//! The check is done on the raw bytes, before the frame is decoded.
+
+use std::time::{Duration, Instant};
+
+/// Length of the window `max_rate` is counted over
+const WINDOW: Duration = Duration::from_secs(1);
+
+/// Frame rate limiter prioritizing position and velocity frames
+#[derive(Debug, Clone)]
+pub struct Governor {
+    /// Amount of frames per second
+    max_rate: u32,
+    /// Start of the current window
+    window_start: Option<Instant>,
+    /// Amount of frames passed in the current window
+    window_count: u32,
+    /// Amount of frames passed through the governor
+    pub passed: u64,
+    /// Amount of frames dropped
+    pub dropped: u64,
+}
+
+impl Governor {
+    /// Limit to `max_rate` frames per second
+    #[must_use]
+    pub fn new(max_rate: u32) -> Self {
+        Self { max_rate, window_start: None, window_count: 0, passed: 0, dropped: 0 }
+    }
+
+    /// Return true if the frame with `bytes` should be decoded, updating the counters
+    pub fn allow(&mut self, bytes: &[u8]) -> bool {
+        self.allow_at(bytes, Instant::now())
+    }
+
+    /// Same as [`Self::allow`], with `now` as the time `bytes` were received
+    ///
+    /// Priority frames (see [`is_priority`]) are always allowed but still count towards the rate,
+    /// other frames are dropped once `max_rate` frames were allowed in the current second.
+    pub fn allow_at(&mut self, bytes: &[u8], now: Instant) -> bool {
+        match self.window_start {
+            Some(start) if now.saturating_duration_since(start) < WINDOW => (),
+            _ => {
+                self.window_start = Some(now);
+                self.window_count = 0;
+            }
+        }
+
+        if self.window_count < self.max_rate || is_priority(bytes) {
+            self.window_count = self.window_count.saturating_add(1);
+            self.passed += 1;
+            true
+        } else {
+            self.dropped += 1;
+            false
+        }
+    }
+}
+
+/// Return true if `bytes` is an ADS-B (DF17) or TIS-B (DF18) position or velocity frame
+#[must_use]
+pub fn is_priority(bytes: &[u8]) -> bool {
+    let (Some(first), Some(me)) = (bytes.first(), bytes.get(4)) else {
+        return false;
+    };
+    let df = first >> 3;
+    let tc = me >> 3;
+    // 5..=8: surface position, 9..=18/20..=22: airborne position, 19: airborne velocity
+    matches!(df, 17 | 18) && matches!(tc, 5..=22)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// DF17 airborne position
+    const POSITION: [u8; 14] =
+        [0x8d, 0xa2, 0xc1, 0xbd, 0x58, 0x7b, 0xa2, 0xad, 0xb3, 0x17, 0x99, 0xcb, 0x80, 0x2b];
+    /// DF17 aircraft identification
+    const IDENTIFICATION: [u8; 14] =
+        [0x8d, 0xa0, 0x8f, 0x94, 0x23, 0x3d, 0x17, 0x75, 0xe7, 0xe9, 0xe0, 0x00, 0x00, 0x00];
+    /// DF4 surveillance altitude reply
+    const SURVEILLANCE: [u8; 7] = [0x20, 0x00, 0x18, 0x38, 0xca, 0x3e, 0x51];
+
+    #[test]
+    fn priority() {
+        assert!(is_priority(&POSITION));
+        assert!(!is_priority(&IDENTIFICATION));
+        assert!(!is_priority(&SURVEILLANCE));
+        assert!(!is_priority(&[]));
+    }
+
+    #[test]
+    fn drop_over_rate() {
+        let start = Instant::now();
+        let mut governor = Governor::new(2);
+
+        assert!(governor.allow_at(&SURVEILLANCE, start));
+        assert!(governor.allow_at(&IDENTIFICATION, start));
+        assert!(!governor.allow_at(&SURVEILLANCE, start));
+        assert!(!governor.allow_at(&IDENTIFICATION, start + Duration::from_millis(999)));
+        // always pass position
+        assert!(governor.allow_at(&POSITION, start + Duration::from_millis(999)));
+        // next window
+        assert!(governor.allow_at(&SURVEILLANCE, start + Duration::from_secs(1)));
+        assert_eq!(governor.passed, 4);
+        assert_eq!(governor.dropped, 2);
+    }
+}
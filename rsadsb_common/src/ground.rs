@@ -0,0 +1,82 @@
+//! Movement of the aircraft on the ground: parked, taxiing or rolling for a takeoff
+//!
+//! The positions of a parked aircraft jitter by a few meters, which adds noise to its track. An
+//! aircraft on the ground is [`GroundState::Parked`] while its ground speed is under
+//! [`MAX_PARKED_SPEED`] and its position stays within [`MAX_PARKED_JITTER`] of the position where
+//! it was first seen parked, or the last position of its track, and these positions aren't added
+//! to the track.
+//!
+//! ```rust
+//! use rsadsb_common::ground::{ground_state, GroundState};
+//!
+//! assert_eq!(ground_state(Some(true), Some(0.0), Some(0.01)), Some(GroundState::Parked));
+//! assert_eq!(ground_state(Some(true), Some(15.0), None), Some(GroundState::Taxiing));
+//! assert_eq!(ground_state(Some(false), Some(0.0), None), None);
+//! ```
+
+#[cfg(feature = "alloc")]
+use core::{
+    clone::Clone,
+    cmp::{Eq, PartialEq},
+    fmt::Debug,
+    marker::Copy,
+    option::Option::{self, None, Some},
+    prelude::rust_2021::derive,
+};
+
+/// Highest ground speed (kt) of a parked aircraft
+pub const MAX_PARKED_SPEED: f32 = 3.0;
+
+/// Lowest ground speed (kt) of a takeoff roll, faster than any taxiing
+pub const MIN_TAKEOFF_SPEED: f32 = 40.0;
+
+/// Distance (km) of the jitter of the position of a parked aircraft
+pub const MAX_PARKED_JITTER: f64 = 0.05;
+
+/// Movement of an aircraft on the ground, see [`self`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum GroundState {
+    /// Not moving, other than the jitter of its position
+    Parked,
+    Taxiing,
+    /// Over [`MIN_TAKEOFF_SPEED`], also while rolling out after a landing
+    TakeoffRoll,
+}
+
+/// [`GroundState`] of an aircraft `on_ground`, `None` if airborne or unknown
+///
+/// `speed` is the ground speed (kt), and `moved` the distance (km) from the position where it was
+/// first seen parked, or the last position of its track.
+#[must_use]
+pub fn ground_state(
+    on_ground: Option<bool>,
+    speed: Option<f32>,
+    moved: Option<f64>,
+) -> Option<GroundState> {
+    if on_ground != Some(true) {
+        return None;
+    }
+    let state = match speed {
+        Some(speed) if speed >= MIN_TAKEOFF_SPEED => GroundState::TakeoffRoll,
+        Some(speed) if speed > MAX_PARKED_SPEED => GroundState::Taxiing,
+        // no speed, or slowly creeping further than the jitter
+        _ if moved.is_some_and(|moved| moved > MAX_PARKED_JITTER) => GroundState::Taxiing,
+        _ => GroundState::Parked,
+    };
+    Some(state)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn states() {
+        assert_eq!(ground_state(None, Some(0.0), None), None);
+        assert_eq!(ground_state(Some(true), None, None), Some(GroundState::Parked));
+        assert_eq!(ground_state(Some(true), Some(2.0), Some(0.2)), Some(GroundState::Taxiing));
+        assert_eq!(ground_state(Some(true), Some(20.0), None), Some(GroundState::Taxiing));
+        assert_eq!(ground_state(Some(true), Some(120.0), None), Some(GroundState::TakeoffRoll));
+    }
+}
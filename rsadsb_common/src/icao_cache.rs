@@ -0,0 +1,139 @@
+//! Recently seen aircraft addresses from CRC-verified DF11/17 frames
+//!
+//! The address recovered from the parity of a DF0/4/5/16/20/21 frame is only trusted if it was
+//! recently seen in a frame with a verified CRC, since any bit error results in a different
+//! address. This is the same technique used by dump1090 to avoid phantom aircraft.
+
+#[cfg(feature = "alloc")]
+use alloc::collections::BTreeMap;
+use core::time::Duration;
+#[cfg(feature = "alloc")]
+use core::{clone::Clone, default::Default, fmt::Debug, prelude::rust_2021::derive};
+#[cfg(feature = "std")]
+use std::time::Instant;
+
+use adsb_deku::{IcaoLookup, ICAO};
+
+/// Default time an address is kept after it was last seen, same as dump1090
+pub const DEFAULT_TTL: Duration = Duration::from_secs(60);
+
+/// Time-bounded set of aircraft addresses
+///
+/// Time is measured as a [`Duration`] from the creation of the cache. With feature `std`, this is
+/// the current time. Without, the time of the latest [`Self::insert_at`] is used as the current
+/// time for [`IcaoLookup::contains`].
+#[derive(Debug, Clone)]
+pub struct IcaoCache {
+    /// Amount of time an address is kept after it was last seen
+    ttl: Duration,
+    /// Last time each address was seen
+    seen: BTreeMap<ICAO, Duration>,
+    /// Latest time given to [`Self::insert_at`]
+    latest: Duration,
+    #[cfg(feature = "std")]
+    start: Instant,
+}
+
+impl Default for IcaoCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_TTL)
+    }
+}
+
+impl IcaoCache {
+    #[must_use]
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            seen: BTreeMap::new(),
+            latest: Duration::ZERO,
+            #[cfg(feature = "std")]
+            start: Instant::now(),
+        }
+    }
+
+    /// Current time, see [`IcaoCache`]
+    fn now(&self) -> Duration {
+        #[cfg(feature = "std")]
+        {
+            self.start.elapsed()
+        }
+        #[cfg(not(feature = "std"))]
+        {
+            self.latest
+        }
+    }
+
+    /// Add `icao` from a CRC-verified frame received now
+    pub fn insert(&mut self, icao: ICAO) {
+        self.insert_at(icao, self.now());
+    }
+
+    /// Add `icao` from a CRC-verified frame received at `now`
+    pub fn insert_at(&mut self, icao: ICAO, now: Duration) {
+        self.latest = self.latest.max(now);
+        self.seen.insert(icao, now);
+    }
+
+    /// Return true if `icao` was seen within the ttl before `now`
+    #[must_use]
+    pub fn contains_at(&self, icao: ICAO, now: Duration) -> bool {
+        self.seen.get(&icao).is_some_and(|seen| now.saturating_sub(*seen) < self.ttl)
+    }
+
+    /// Remove addresses not seen within the ttl
+    pub fn prune(&mut self) {
+        let now = self.now();
+        let ttl = self.ttl;
+        self.seen.retain(|_, seen| now.saturating_sub(*seen) < ttl);
+    }
+
+    /// Amount of addresses, including addresses not yet removed by [`Self::prune`]
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.seen.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.seen.is_empty()
+    }
+}
+
+impl IcaoLookup for IcaoCache {
+    fn contains(&self, icao: ICAO) -> bool {
+        self.contains_at(icao, self.now())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expire_after_ttl() {
+        let icao = ICAO([0xab, 0xcd, 0xef]);
+        let mut cache = IcaoCache::new(Duration::from_secs(60));
+        assert!(!cache.contains_at(icao, Duration::ZERO));
+
+        cache.insert_at(icao, Duration::from_secs(10));
+        assert!(cache.contains_at(icao, Duration::from_secs(10)));
+        assert!(cache.contains_at(icao, Duration::from_secs(69)));
+        assert!(!cache.contains_at(icao, Duration::from_secs(70)));
+
+        // seen again
+        cache.insert_at(icao, Duration::from_secs(65));
+        assert!(cache.contains_at(icao, Duration::from_secs(124)));
+        assert!(!cache.contains_at(ICAO([0, 0, 1]), Duration::from_secs(65)));
+    }
+
+    #[test]
+    fn insert_now() {
+        let icao = ICAO([0xab, 0xcd, 0xef]);
+        let mut cache = IcaoCache::default();
+        cache.insert(icao);
+        assert!(cache.contains(icao));
+        cache.prune();
+        assert_eq!(cache.len(), 1);
+    }
+}
@@ -0,0 +1,121 @@
+//! Changes of the callsign of an aircraft between its sources
+//!
+//! The callsign of an aircraft is broadcast in the ADS-B `ME::AircraftIdentification`, and also
+//! read by the interrogators from the Comm-B `BDS` 2,0 register (DF20/21). The newer value is
+//! kept in [`AirplaneState::callsign`] with its source in [`AirplaneState::callsign_source`], and
+//! an [`IdentChange`] is queued when a source disagrees with the callsign from another source,
+//! such as a crew updating the flight number in only one of the systems.
+//!
+//! ```rust
+//! use adsb_deku::Frame;
+//! use rsadsb_common::{Airplanes, DataSource};
+//!
+//! let mut airplanes = Airplanes::default();
+//! // ADS-B identification "KLM1023 "
+//! let bytes = [0x8d, 0x48, 0x40, 0xd6, 0x20, 0x2c, 0xc3, 0x71, 0xc3, 0x2c, 0xe0, 0x57, 0x60, 0x98];
+//! airplanes.action(Frame::from_bytes(&bytes).unwrap());
+//! let (_, state) = airplanes.iter().next().unwrap();
+//! assert_eq!(state.callsign_source, Some(DataSource::Adsb));
+//! assert_eq!(airplanes.drain_ident_changes().count(), 0);
+//! ```
+//!
+//! [`AirplaneState::callsign`]: crate::AirplaneState::callsign
+//! [`AirplaneState::callsign_source`]: crate::AirplaneState::callsign_source
+
+#[cfg(feature = "alloc")]
+use alloc::{collections::VecDeque, string::String};
+#[cfg(feature = "alloc")]
+use core::{clone::Clone, default::Default, fmt::Debug, prelude::rust_2021::derive};
+
+use adsb_deku::ICAO;
+
+use crate::DataSource;
+
+/// Changes kept until drained, the oldest are dropped
+const MAX_CHANGES: usize = 256;
+
+/// Not empty, with only `A-Z`, `0-9` and spaces
+///
+/// Garbage identifications decode to `#` for the undefined character codes, and SBS sources can
+/// send any character.
+///
+/// ```rust
+/// use rsadsb_common::ident::is_valid_callsign;
+///
+/// assert!(is_valid_callsign("KLM1023"));
+/// assert!(!is_valid_callsign("########"));
+/// assert!(!is_valid_callsign("KLM\u{7}"));
+/// assert!(!is_valid_callsign(""));
+/// ```
+#[must_use]
+pub fn is_valid_callsign(callsign: &str) -> bool {
+    !callsign.is_empty()
+        && callsign.chars().all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == ' ')
+}
+
+/// The callsign of `icao` from `source` differs from the `previous` callsign from another source
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct IdentChange {
+    pub icao: ICAO,
+    pub previous: String,
+    pub previous_source: DataSource,
+    pub callsign: String,
+    pub source: DataSource,
+}
+
+/// Queue of [`IdentChange`], see [`crate::Airplanes::drain_ident_changes`]
+#[derive(Debug, Default, Clone)]
+pub struct IdentChanges {
+    changes: VecDeque<IdentChange>,
+    /// Changes dropped over the max length of the queue
+    pub dropped: u64,
+}
+
+impl IdentChanges {
+    pub(crate) fn push(&mut self, change: IdentChange) {
+        if self.changes.len() == MAX_CHANGES {
+            self.changes.pop_front();
+            self.dropped += 1;
+        }
+        self.changes.push_back(change);
+    }
+
+    pub(crate) fn drain(&mut self) -> alloc::collections::vec_deque::Drain<'_, IdentChange> {
+        self.changes.drain(..)
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.changes.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn max_changes() {
+        let mut changes = IdentChanges::default();
+        let change = IdentChange {
+            icao: ICAO([0xab, 0xcd, 0xef]),
+            previous: String::from("KLM1023"),
+            previous_source: DataSource::Adsb,
+            callsign: String::from("KLM1024"),
+            source: DataSource::CommB,
+        };
+        for _ in 0..MAX_CHANGES + 1 {
+            changes.push(change.clone());
+        }
+        assert_eq!(changes.len(), MAX_CHANGES);
+        assert_eq!(changes.dropped, 1);
+        assert_eq!(changes.drain().count(), MAX_CHANGES);
+        assert!(changes.is_empty());
+    }
+}
@@ -0,0 +1,240 @@
+//! Detection of GNSS interference, from fleet-wide drops of the NIC and NACp of the aircraft
+//!
+//! Jamming or spoofing of GNSS degrades the position of all aircraft near the interference at
+//! the same time: their NIC (from the type code of the positions) and NACp (from
+//! `ME::AircraftOperationStatus`) drop, often to 0. A single aircraft with a bad navigation
+//! source doesn't, so an [`InterferenceDetector`] only starts an event when enough aircraft drop
+//! from a good [`Integrity`] within a [`InterferenceConfig::window`].
+//!
+//! ```rust
+//! use std::time::Instant;
+//!
+//! use rsadsb_common::interference::InterferenceDetector;
+//! use rsadsb_common::Airplanes;
+//!
+//! let airplanes = Airplanes::new();
+//! let mut detector = InterferenceDetector::default();
+//! assert_eq!(detector.update(&airplanes, Instant::now()), None);
+//! assert!(!detector.is_active());
+//! ```
+
+use std::time::{Duration, Instant};
+
+use adsb_deku::ICAO;
+use tracing::{debug, info, warn};
+
+use crate::Airplanes;
+
+/// Lowest NIC of a good position, a containment radius under 0.2 NM
+pub const MIN_NIC: u8 = 7;
+
+/// Lowest NACp of a good position, an accuracy under 0.1 NM
+pub const MIN_NACP: u8 = 7;
+
+/// Last NIC and NACp of an aircraft, see [`crate::AirplaneState::integrity`]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Integrity {
+    /// from the type code of the last airborne position
+    pub nic: Option<u8>,
+    /// from the last `ME::AircraftOperationStatus`
+    pub nacp: Option<u8>,
+    /// Monotonic time of the drop from a good to a degraded integrity, `None` while good or if
+    /// it was never good
+    pub dropped: Option<Instant>,
+}
+
+impl Integrity {
+    /// NIC or NACp under [`MIN_NIC`] or [`MIN_NACP`], `None` if neither is known
+    #[must_use]
+    pub fn is_degraded(&self) -> Option<bool> {
+        match (self.nic, self.nacp) {
+            (None, None) => None,
+            (nic, nacp) => {
+                Some(nic.is_some_and(|nic| nic < MIN_NIC) || nacp.is_some_and(|n| n < MIN_NACP))
+            }
+        }
+    }
+
+    pub(crate) fn update_nic(&mut self, icao: ICAO, nic: u8, now: Instant) {
+        let previous = self.is_degraded();
+        self.nic = Some(nic);
+        self.update(icao, previous, now);
+    }
+
+    pub(crate) fn update_nacp(&mut self, icao: ICAO, nacp: u8, now: Instant) {
+        let previous = self.is_degraded();
+        self.nacp = Some(nacp);
+        self.update(icao, previous, now);
+    }
+
+    fn update(&mut self, icao: ICAO, previous: Option<bool>, now: Instant) {
+        match (previous, self.is_degraded()) {
+            (Some(false), Some(true)) => {
+                debug!("[{icao}] integrity dropped: NIC {:?}, NACp {:?}", self.nic, self.nacp);
+                self.dropped = Some(now);
+            }
+            (_, Some(false)) => self.dropped = None,
+            _ => (),
+        }
+    }
+}
+
+/// Amount of aircraft by [`Integrity`], see [`Airplanes::fleet_integrity`]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct FleetIntegrity {
+    /// Aircraft with a known NIC or NACp
+    pub aircraft: u32,
+    /// Aircraft still degraded after a drop
+    pub degraded: u32,
+    /// `degraded` aircraft that dropped within the window
+    pub recent: u32,
+}
+
+/// Thresholds of an [`InterferenceDetector`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct InterferenceConfig {
+    /// Time in which the drops must happen
+    pub window: Duration,
+    /// Aircraft that must drop
+    pub min_aircraft: u32,
+    /// Ratio of the aircraft with a known integrity that must drop
+    pub min_ratio: f32,
+}
+
+impl Default for InterferenceConfig {
+    fn default() -> Self {
+        Self { window: Duration::from_secs(60), min_aircraft: 3, min_ratio: 0.5 }
+    }
+}
+
+/// Start or end of a GNSS interference, from [`InterferenceDetector::update`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InterferenceEvent {
+    /// Enough aircraft dropped within the window
+    Started(FleetIntegrity),
+    /// Less than [`InterferenceConfig::min_aircraft`] are still degraded, after the duration of
+    /// the event
+    Ended(Duration),
+}
+
+/// Events of GNSS interference over the tracked aircraft, see [`self`]
+#[derive(Debug, Default, Clone)]
+pub struct InterferenceDetector {
+    pub config: InterferenceConfig,
+    /// Monotonic time of the start of the current event
+    started: Option<Instant>,
+    /// Events started
+    pub events: u32,
+    /// From the last [`Self::update`]
+    pub fleet: FleetIntegrity,
+}
+
+impl InterferenceDetector {
+    #[must_use]
+    pub fn new(config: InterferenceConfig) -> Self {
+        Self { config, ..Self::default() }
+    }
+
+    /// Check the aircraft for the start or end of an event, logging it
+    pub fn update(&mut self, airplanes: &Airplanes, now: Instant) -> Option<InterferenceEvent> {
+        let fleet = airplanes.fleet_integrity(self.config.window, now);
+        self.fleet = fleet;
+        let ratio = |amount: u32| amount as f32 / fleet.aircraft.max(1) as f32;
+        match self.started {
+            None => {
+                if fleet.recent >= self.config.min_aircraft
+                    && ratio(fleet.recent) >= self.config.min_ratio
+                {
+                    warn!(
+                        "GNSS interference: {}/{} aircraft with a degraded NIC or NACp",
+                        fleet.recent, fleet.aircraft
+                    );
+                    self.started = Some(now);
+                    self.events += 1;
+                    return Some(InterferenceEvent::Started(fleet));
+                }
+            }
+            Some(started) => {
+                if fleet.degraded < self.config.min_aircraft {
+                    let duration = now.duration_since(started);
+                    info!("GNSS interference ended after {:.0}s", duration.as_secs_f32());
+                    self.started = None;
+                    return Some(InterferenceEvent::Ended(duration));
+                }
+            }
+        }
+        None
+    }
+
+    /// An event started and didn't end
+    #[must_use]
+    pub fn is_active(&self) -> bool {
+        self.started.is_some()
+    }
+
+    /// Monotonic time of the start of the current event
+    #[must_use]
+    pub fn started(&self) -> Option<Instant> {
+        self.started
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn integrity() {
+        let icao = ICAO([0xab, 0xcd, 0xef]);
+        let now = Instant::now();
+        let mut integrity = Integrity::default();
+        assert_eq!(integrity.is_degraded(), None);
+
+        // never good, such as without a GNSS navigation source
+        integrity.update_nic(icao, 0, now);
+        assert_eq!(integrity.is_degraded(), Some(true));
+        assert_eq!(integrity.dropped, None);
+
+        integrity.update_nic(icao, 8, now);
+        integrity.update_nacp(icao, 9, now);
+        assert_eq!(integrity.is_degraded(), Some(false));
+        integrity.update_nacp(icao, 0, now);
+        assert_eq!(integrity.dropped, Some(now));
+        integrity.update_nacp(icao, 9, now);
+        assert_eq!(integrity.dropped, None);
+    }
+
+    #[test]
+    fn detector() {
+        let start = Instant::now();
+        let mut airplanes = Airplanes::new();
+        let icaos: Vec<ICAO> = (0..4).map(|i| ICAO([0xab, 0xcd, i])).collect();
+        for icao in &icaos {
+            airplanes.entry_or_insert(*icao).0.integrity.update_nic(*icao, 8, start);
+        }
+        let mut detector = InterferenceDetector::default();
+        assert_eq!(detector.update(&airplanes, start), None);
+
+        // 3 of 4 aircraft drop to NIC 0
+        let now = start + Duration::from_secs(10);
+        for icao in &icaos[..3] {
+            airplanes.entry_or_insert(*icao).0.integrity.update_nic(*icao, 0, now);
+        }
+        let fleet = FleetIntegrity { aircraft: 4, degraded: 3, recent: 3 };
+        assert_eq!(detector.update(&airplanes, now), Some(InterferenceEvent::Started(fleet)));
+        assert!(detector.is_active());
+        assert_eq!(detector.update(&airplanes, now), None);
+
+        // still degraded after the window
+        let now = start + Duration::from_secs(100);
+        assert_eq!(detector.update(&airplanes, now), None);
+        assert_eq!(detector.fleet.recent, 0);
+
+        airplanes.entry_or_insert(icaos[0]).0.integrity.update_nic(icaos[0], 8, now);
+        assert_eq!(
+            detector.update(&airplanes, now),
+            Some(InterferenceEvent::Ended(Duration::from_secs(90)))
+        );
+        assert_eq!(detector.events, 1);
+    }
+}
@@ -0,0 +1,95 @@
+//! Downlink requests and interrogators of the Mode S surveillance and Comm-B replies
+//!
+//! The DR field of DF4/5/20/21 announces a Comm-B message waiting to be read, such as a broadcast
+//! for all interrogators, and the UM field contains the IIS of the interrogator that last
+//! interrogated the aircraft. Together they show the local SSR interrogation patterns, see
+//! [`crate::Airplanes::interrogation`].
+
+#[cfg(feature = "alloc")]
+use core::{
+    clone::Clone,
+    cmp::{Eq, PartialEq},
+    default::Default,
+    fmt::Debug,
+    iter::Iterator,
+    marker::Copy,
+    option::Option::{self, Some},
+    prelude::rust_2021::derive,
+};
+
+use adsb_deku::{DownlinkRequest, UtilityMessage};
+
+/// Last DR and UM of an aircraft, see [`crate::AirplaneState::downlink`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Downlink {
+    pub request: DownlinkRequest,
+    /// IIS of the last interrogator, `None` for 0 (no interrogator identifier)
+    pub interrogator: Option<u8>,
+}
+
+impl Downlink {
+    #[must_use]
+    pub fn new(request: DownlinkRequest, um: UtilityMessage) -> Self {
+        Self { request, interrogator: (um.iis != 0).then_some(um.iis) }
+    }
+}
+
+/// Amount of aircraft by downlink request and interrogator, see
+/// [`crate::Airplanes::interrogation`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Interrogation {
+    /// Aircraft with a received DR and UM
+    pub aircraft: u32,
+    /// Aircraft announcing a Comm-B broadcast message
+    pub broadcast: u32,
+    /// Aircraft requesting to send a Comm-B message
+    pub comm_b_request: u32,
+    /// Aircraft by the IIS (1 to 15) of their last interrogator, index 0 is unused
+    pub interrogators: [u32; 16],
+}
+
+impl Interrogation {
+    /// Count an aircraft with `downlink`
+    pub fn add(&mut self, downlink: Downlink) {
+        self.aircraft += 1;
+        if downlink.request.is_broadcast() {
+            self.broadcast += 1;
+        } else if downlink.request == DownlinkRequest::RequestSendCommB {
+            self.comm_b_request += 1;
+        }
+        if let Some(iis) = downlink.interrogator {
+            self.interrogators[usize::from(iis)] += 1;
+        }
+    }
+
+    /// IIS and amount of aircraft, of the interrogators seen
+    pub fn interrogators(&self) -> impl Iterator<Item = (u8, u32)> + '_ {
+        (1..).zip(self.interrogators[1..].iter().copied()).filter(|(_, amount)| *amount != 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use adsb_deku::UtilityMessageType;
+
+    use super::*;
+
+    #[test]
+    fn interrogation() {
+        let um = |iis| UtilityMessage { iis, ids: UtilityMessageType::NoInformation };
+        let mut interrogation = Interrogation::default();
+        interrogation.add(Downlink::new(DownlinkRequest::CommBBroadcastMsg1, um(15)));
+        interrogation.add(Downlink::new(DownlinkRequest::RequestSendCommB, um(3)));
+        interrogation.add(Downlink::new(DownlinkRequest::None, um(3)));
+        interrogation.add(Downlink::new(DownlinkRequest::None, um(0)));
+
+        assert_eq!(interrogation.aircraft, 4);
+        assert_eq!(interrogation.broadcast, 1);
+        assert_eq!(interrogation.comm_b_request, 1);
+        assert_eq!(interrogation.interrogators().collect::<Vec<_>>(), [(3, 2), (15, 1)]);
+    }
+}
@@ -13,18 +13,101 @@ use core::{
     result::Result::Ok, writeln,
 };
 #[cfg(feature = "std")]
-use std::time::SystemTime;
+use std::time::{Duration, Instant, SystemTime};
 
-use adsb_deku::adsb::{AirborneVelocity, Identification, ME};
-use adsb_deku::{cpr, Altitude, CPRFormat, Frame, DF, ICAO};
+use adsb_deku::adsb::{
+    AirborneVelocity, EmitterCategory, Identification, OperationStatus,
+    TargetStateAndStatusInformation, ME,
+};
+use adsb_deku::bds::{
+    DataLinkCapability, HeadingAndSpeedReport, SelectedVerticalIntention, TrackAndTurnReport, BDS,
+};
+use adsb_deku::{
+    cpr, Altitude, CPRFormat, DownlinkRequest, Frame, IcaoLookup, ReplyInformation, UtilityMessage,
+    DF, ICAO,
+};
 use tracing::{debug, info, warn};
 
+pub mod altitude;
+pub mod anomaly;
+#[cfg(feature = "compact")]
+pub mod compact;
+pub mod coverage;
+#[cfg(feature = "std")]
+pub mod dedup;
+pub mod demod;
+pub mod equipage;
+pub mod geo;
+#[cfg(feature = "std")]
+pub mod governor;
+pub mod ground;
+pub mod icao_cache;
+pub mod ident;
+#[cfg(feature = "std")]
+pub mod interference;
+pub mod interrogation;
+pub mod locate;
+pub mod privacy;
+pub mod qnh;
+pub mod quality;
+#[cfg(feature = "std")]
+pub mod readsb;
+pub mod rejected;
+pub mod sbs;
+#[cfg(feature = "std")]
+pub mod shared;
+#[cfg(feature = "std")]
+pub mod snapshot;
+pub mod sort;
+pub mod squawk;
+#[cfg(feature = "time")]
+pub mod timestamp;
+pub mod track;
+pub mod wind;
+
+use altitude::AltitudeSource;
+use anomaly::{Anomalies, BroadcastHistory};
+use equipage::{Equipage, FleetEquipage, TransponderLevel};
+use ground::GroundState;
+use icao_cache::IcaoCache;
+use ident::{IdentChange, IdentChanges};
+#[cfg(feature = "std")]
+use interference::{FleetIntegrity, Integrity};
+use interrogation::{Downlink, Interrogation};
+use quality::{QualityHistory, QualityScore};
+use rejected::{RejectedFix, RejectedFixes};
+use sbs::SbsMessage;
+use wind::Wind;
+
 // Max absurd distance an aircraft travelled between messages
 const MAX_AIRCRAFT_DISTANCE: f64 = 100.0;
 
-#[derive(Debug, PartialEq, Eq)]
+/// Longest time between an even and odd frame for a global CPR decode (ICAO 9871 D.2.4.7.7)
+#[cfg(feature = "std")]
+const CPR_GLOBAL_WINDOW: Duration = Duration::from_secs(10);
+
+/// Longest time between two headings for computing the turn rate, see
+/// [`AirplaneState::turn_rate`]
+#[cfg(feature = "std")]
+const TURN_RATE_WINDOW: Duration = Duration::from_secs(30);
+
+/// Longest time a heading is extrapolated, see [`AirplaneState::estimated_heading`]
+#[cfg(feature = "std")]
+const MAX_HEADING_EXTRAPOLATION: Duration = Duration::from_secs(15);
+
+/// Time since the first message before the message rate is used, see
+/// [`AirplaneState::message_rate`]
+#[cfg(feature = "std")]
+const MIN_RATE_DURATION: Duration = Duration::from_secs(10);
+
+/// Weight of the newest vertical rate in [`AirplaneState::smoothed_vert_speed`]
+const VERT_SPEED_SMOOTHING: f32 = 0.3;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Added {
     /// Airplane was not added
+    #[default]
     No,
     /// Airplane was added
     Yes,
@@ -39,6 +122,39 @@ impl From<bool> for Added {
     }
 }
 
+/// What a frame changed in [`Airplanes`], returned by [`Airplanes::action`]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ActionOutcome {
+    /// The aircraft of the frame is newly tracked
+    pub added: Added,
+    /// The position of the aircraft was decoded and changed
+    pub position_updated: bool,
+    /// The callsign of the aircraft was set or changed
+    pub callsign_updated: bool,
+    /// Why the position of the frame wasn't used
+    pub rejected_reason: Option<RejectReason>,
+}
+
+impl From<Added> for ActionOutcome {
+    fn from(added: Added) -> Self {
+        Self { added, ..Self::default() }
+    }
+}
+
+/// Reason of a rejected position, see [`ActionOutcome::rejected_reason`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum RejectReason {
+    /// Farther than the max range from the receiver, the position of the aircraft is cleared
+    OutOfRange,
+    /// Farther than possible from the previous position, the position of the aircraft is cleared
+    DistanceJump,
+    /// Even and odd frames too far apart for a global decode, without a previous position for a
+    /// local decode
+    StalePair,
+}
+
 /// `BTreeMap` of of all currently tracked `ICAO` and `AirplaneState`.
 ///
 /// Currently tracked means that within calling [`Self::action`], an aircraft is added to this data
@@ -46,17 +162,124 @@ impl From<bool> for Added {
 #[cfg_attr(feature = "serde", serde_with::serde_as)]
 #[derive(Debug, Default, Clone)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
-pub struct Airplanes(
+pub struct Airplanes {
     #[cfg_attr(
         feature = "serde",
         serde(with = "serde_with::As::<Vec<(serde_with::DisplayFromStr, serde_with::Same)>>")
     )]
-    BTreeMap<ICAO, AirplaneState>,
-);
+    airplanes: BTreeMap<ICAO, AirplaneState>,
+    /// Addresses from CRC-verified DF11/17 frames, see [`icao_cache`]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    icao_cache: IcaoCache,
+    /// (latitude, longitude) of the receiver
+    receiver_position: Option<(f64, f64)>,
+    /// Max range of the receiver in kilometers
+    max_range: Option<f64>,
+    track_config: TrackConfig,
+    first_fix_stats: FirstFixStats,
+    /// Callsign changes between sources, see [`ident`]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    ident_changes: IdentChanges,
+    /// Positions rejected by the range checks of all aircraft, see [`rejected`]
+    rejected_fixes: RejectedFixes,
+    /// see [`AirplanesBuilder::keep_invalid_callsigns`]
+    keep_invalid_callsigns: bool,
+}
+
+/// Recording of previous positions into [`AirplaneState::track`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TrackConfig {
+    /// Record previous positions
+    pub enabled: bool,
+    /// Keep at most this amount of previous positions, dropping the oldest
+    ///
+    /// `None` keeps all positions until the aircraft is pruned
+    pub max_len: Option<usize>,
+}
+
+impl Default for TrackConfig {
+    fn default() -> Self {
+        Self { enabled: true, max_len: None }
+    }
+}
+
+/// Builder for [`Airplanes`], created with [`Airplanes::builder`]
+///
+/// ```rust
+/// use rsadsb_common::{Airplanes, TrackConfig};
+///
+/// let airplanes = Airplanes::builder()
+///     .max_range(300.0)
+///     .position((35.0, -80.0))
+///     .track_config(TrackConfig { enabled: true, max_len: Some(100) })
+///     .build();
+/// assert_eq!(airplanes.receiver_position(), Some((35.0, -80.0)));
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct AirplanesBuilder {
+    receiver_position: Option<(f64, f64)>,
+    max_range: Option<f64>,
+    track_config: TrackConfig,
+    keep_invalid_callsigns: bool,
+}
+
+impl AirplanesBuilder {
+    /// (latitude, longitude) of the receiver
+    ///
+    /// Without a receiver position, the distance and bearing of aircraft are unknown and
+    /// [`Airplanes::aircraft_details`] always returns `None`.
+    #[must_use]
+    pub fn position(mut self, lat_long: (f64, f64)) -> Self {
+        self.receiver_position = Some(lat_long);
+        self
+    }
+
+    /// Max range of the receiver in kilometers, positions further away are ignored
+    ///
+    /// Unlimited if not set.
+    #[must_use]
+    pub fn max_range(mut self, max_range: f64) -> Self {
+        self.max_range = Some(max_range);
+        self
+    }
+
+    /// Recording of previous positions, defaults to [`TrackConfig::default`]
+    #[must_use]
+    pub fn track_config(mut self, track_config: TrackConfig) -> Self {
+        self.track_config = track_config;
+        self
+    }
+
+    /// Keep the callsigns with other characters than `A-Z`, `0-9` and spaces, such as the `#` of
+    /// undefined character codes, instead of rejecting them, see [`ident::is_valid_callsign`]
+    ///
+    /// The rejected callsigns are counted in [`Anomalies::invalid_callsign`].
+    #[must_use]
+    pub fn keep_invalid_callsigns(mut self, keep_invalid_callsigns: bool) -> Self {
+        self.keep_invalid_callsigns = keep_invalid_callsigns;
+        self
+    }
+
+    #[must_use]
+    pub fn build(self) -> Airplanes {
+        Airplanes {
+            airplanes: BTreeMap::new(),
+            icao_cache: IcaoCache::default(),
+            receiver_position: self.receiver_position,
+            max_range: self.max_range,
+            track_config: self.track_config,
+            first_fix_stats: FirstFixStats::default(),
+            ident_changes: IdentChanges::default(),
+            rejected_fixes: RejectedFixes::default(),
+            keep_invalid_callsigns: self.keep_invalid_callsigns,
+        }
+    }
+}
 
 impl fmt::Display for Airplanes {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        for key in self.0.keys() {
+        for key in self.airplanes.keys() {
             let value = self.aircraft_details(*key);
             if let Some(value) = value {
                 writeln!(f, "{key}: {value:?}")?;
@@ -66,25 +289,81 @@ impl fmt::Display for Airplanes {
     }
 }
 
+/// Currently tracked aircraft and recently seen addresses from CRC-verified DF11/17 frames, for
+/// validating [`Frame::icao_from_parity`]
+impl IcaoLookup for Airplanes {
+    fn contains(&self, icao: ICAO) -> bool {
+        self.airplanes.contains_key(&icao) || self.icao_cache.contains(icao)
+    }
+}
+
 // public
 impl Airplanes {
+    /// Without a receiver position or max range, see [`Self::builder`]
     #[must_use]
     pub fn new() -> Self {
-        Self(BTreeMap::new())
+        Self::builder().build()
+    }
+
+    #[must_use]
+    pub fn builder() -> AirplanesBuilder {
+        AirplanesBuilder::default()
+    }
+
+    /// (latitude, longitude) of the receiver, see [`AirplanesBuilder::position`]
+    #[must_use]
+    pub fn receiver_position(&self) -> Option<(f64, f64)> {
+        self.receiver_position
+    }
+
+    /// Update the (latitude, longitude) of the receiver, such as from gpsd
+    ///
+    /// The distance and bearing of all aircraft are recomputed, and the position of aircraft now
+    /// out of the max range is cleared.
+    pub fn set_receiver_position(&mut self, lat_long: (f64, f64)) {
+        self.receiver_position = Some(lat_long);
+        self.update_distances();
+    }
+
+    /// Max range of the receiver in kilometers, see [`AirplanesBuilder::max_range`]
+    #[must_use]
+    pub fn max_range(&self) -> Option<f64> {
+        self.max_range
+    }
+
+    /// Update the max range of the receiver, clearing the position of aircraft now out of range
+    pub fn set_max_range(&mut self, max_range: Option<f64>) {
+        self.max_range = max_range;
+        self.update_distances();
+    }
+
+    #[must_use]
+    pub fn track_config(&self) -> TrackConfig {
+        self.track_config
+    }
+
+    pub fn set_track_config(&mut self, track_config: TrackConfig) {
+        self.track_config = track_config;
+    }
+
+    /// Addresses from CRC-verified DF11/17 frames, see [`icao_cache`]
+    #[must_use]
+    pub fn icao_cache(&self) -> &IcaoCache {
+        &self.icao_cache
     }
 
     /// Tuple `iter()` of all `(ICAO, AirplanesState)`
     ///
     /// equivalent [`BTreeMap::iter`]
     pub fn iter(&self) -> alloc::collections::btree_map::Iter<'_, ICAO, AirplaneState> {
-        self.0.iter()
+        self.airplanes.iter()
     }
 
     /// Get all `ICAO` keys
     ///
     /// equivalent [`BTreeMap::keys`]
     pub fn keys(&self) -> alloc::collections::btree_map::Keys<'_, ICAO, AirplaneState> {
-        self.0.keys()
+        self.airplanes.keys()
     }
 
     /// From `ICAO`, get `AirplaneState`
@@ -92,7 +371,7 @@ impl Airplanes {
     /// equivalent [`BTreeMap::get`]
     #[must_use]
     pub fn get(&self, key: ICAO) -> Option<&AirplaneState> {
-        self.0.get(&key)
+        self.airplanes.get(&key)
     }
 
     /// Amount of currently tracked airplanes
@@ -100,13 +379,13 @@ impl Airplanes {
     /// equivalent [`BTreeMap::len`]
     #[must_use]
     pub fn len(&self) -> usize {
-        self.0.len()
+        self.airplanes.len()
     }
 
     /// equivalent [`BTreeMap::is_empty`]
     #[must_use]
     pub fn is_empty(&self) -> bool {
-        self.0.is_empty()
+        self.airplanes.is_empty()
     }
 
     /// Update `Airplanes` with new `Frame`
@@ -115,59 +394,217 @@ impl Airplanes {
     /// updates the field that the `ME` value equates to within [`Self`]. This also adds
     /// airplanes (`ICAO` and `AirplaneState`) when a new aircraft is detected.
     ///
-    /// `lat_long`: (latitude, longitude) of current receiver location
+    /// `DF::TisB` (DF18) frames update the aircraft of their AA field, since their parity isn't
+    /// overlaid with the address.
     ///
-    /// `max_range`: max range of the receiver
+    /// The capability of `DF::AllCallReply` updates already tracked aircraft, see
+    /// [`AirplaneState::equipage`].
     ///
-    /// Return true if entry was added into `Airplanes`
-    pub fn action(&mut self, frame: Frame, lat_long: (f64, f64), max_rang: f64) -> Added {
-        let mut airplane_added = Added::No;
+    /// Comm-B replies (`DF::CommBAltitudeReply`, `DF::CommBIdentityReply`) containing
+    /// `BDS::AircraftIdentification` or `BDS::DataLinkCapability` also update the aircraft, using
+    /// the ICAO address recovered from [`Frame::icao_from_parity`]. This address, also used for
+    /// counting DF0/4/5/16 messages and the [`ReplyInformation`] of DF0/16, must be already
+    /// tracked or recently seen in a CRC-verified DF11/17 frame. The DR and UM of DF4/5/20/21
+    /// update the [`AirplaneState::downlink`] of tracked aircraft.
+    ///
+    /// Positions are checked against the receiver position and max range given to
+    /// [`Self::builder`].
+    ///
+    /// Return what was changed, see [`ActionOutcome`]
+    pub fn action(&mut self, frame: Frame) -> ActionOutcome {
+        let mut outcome = ActionOutcome::default();
         match frame.df {
             DF::ADSB(ref adsb) => {
-                airplane_added = match &adsb.me {
+                if frame.crc == 0 {
+                    self.icao_cache.insert(adsb.icao);
+                }
+                outcome = match &adsb.me {
                     ME::AircraftIdentification(identification) => {
                         self.add_identification(adsb.icao, identification)
                     }
-                    ME::AirborneVelocity(vel) => self.add_airborne_velocity(adsb.icao, vel),
+                    ME::AirborneVelocity(vel) => self.add_airborne_velocity(adsb.icao, vel).into(),
                     ME::AirbornePositionGNSSAltitude(altitude)
                     | ME::AirbornePositionBaroAltitude(altitude) => {
-                        self.update_position(adsb.icao, altitude, lat_long, max_rang)
+                        self.update_position(adsb.icao, altitude)
+                    }
+                    ME::NoPosition(altitude) => self.add_no_position(adsb.icao, altitude).into(),
+                    ME::AircraftOperationStatus(status) => {
+                        self.add_operation_status(adsb.icao, status).into()
+                    }
+                    ME::TargetStateAndStatusInformation(target) => {
+                        self.add_target_state(adsb.icao, target).into()
                     }
-                    _ => Added::No,
+                    _ => ActionOutcome::default(),
                 };
-                let incr_airplane_added = self.incr_messages(adsb.icao);
-                airplane_added =
-                    if incr_airplane_added == Added::Yes || airplane_added == Added::Yes {
-                        Added::Yes
-                    } else {
-                        Added::No
-                    };
+                if self.incr_messages(adsb.icao) == Added::Yes {
+                    outcome.added = Added::Yes;
+                }
+                self.add_capability(adsb.icao, adsb.capability);
             }
             DF::TisB { cf, pi } => {
                 info!("TISB: {cf:?}, {pi:?}");
-                airplane_added = match cf.me {
+                // the AA field, the parity isn't overlaid with the address of DF18
+                let icao = cf.aa;
+                outcome = match cf.me {
                     ME::AircraftIdentification(identification) => {
-                        self.add_identification(pi, &identification)
+                        self.add_identification(icao, &identification)
                     }
-                    ME::AirborneVelocity(vel) => self.add_airborne_velocity(pi, &vel),
+                    ME::AirborneVelocity(vel) => self.add_airborne_velocity(icao, &vel).into(),
                     ME::AirbornePositionGNSSAltitude(altitude)
                     | ME::AirbornePositionBaroAltitude(altitude) => {
-                        self.update_position(pi, &altitude, lat_long, max_rang)
+                        self.update_position(icao, &altitude)
+                    }
+                    ME::NoPosition(altitude) => self.add_no_position(icao, &altitude).into(),
+                    ME::AircraftOperationStatus(status) => {
+                        self.add_operation_status(icao, &status).into()
                     }
-                    _ => Added::No,
+                    ME::TargetStateAndStatusInformation(target) => {
+                        self.add_target_state(icao, &target).into()
+                    }
+                    _ => ActionOutcome::default(),
                 };
-                let incr_airplane_added = self.incr_messages(pi);
-                airplane_added =
-                    if incr_airplane_added == Added::Yes || airplane_added == Added::Yes {
-                        Added::Yes
-                    } else {
-                        Added::No
-                    };
+                if self.incr_messages(icao) == Added::Yes {
+                    outcome.added = Added::Yes;
+                }
+            }
+            // the crc is the interrogator code overlaid on the parity: the II or SI of the
+            // interrogator and its code label in the low 7 bits, 0 for acquisition squitters. Other
+            // bits are errors, the address can't be trusted.
+            DF::AllCallReply { icao, capability, .. } if frame.crc < 0x80 => {
+                self.icao_cache.insert(icao);
+                self.add_capability(icao, capability);
+            }
+            DF::CommBAltitudeReply { ref bds, dr, um, .. } => {
+                if let Some(icao) = frame.icao_from_parity(self) {
+                    let added = self.incr_messages(icao);
+                    outcome = ActionOutcome { added, ..self.add_comm_b(icao, bds) };
+                    self.add_downlink(icao, dr, um);
+                }
+            }
+            DF::CommBIdentityReply { ref bds, dr, um, id, .. } => {
+                if let Some(icao) = frame.icao_from_parity(self) {
+                    let added = self.incr_messages(icao);
+                    outcome = ActionOutcome { added, ..self.add_comm_b(icao, bds) };
+                    self.add_squawk(icao, id);
+                    self.add_downlink(icao, dr, um);
+                }
+            }
+            DF::SurveillanceIdentityReply { dr, um, id, .. } => {
+                if let Some(icao) = frame.icao_from_parity(self) {
+                    outcome = self.incr_messages(icao).into();
+                    self.add_squawk(icao, u32::from(id.0));
+                    self.add_downlink(icao, dr, um);
+                }
+            }
+            DF::ShortAirAirSurveillance { ri, .. } | DF::LongAirAir { ri, .. } => {
+                if let Some(icao) = frame.icao_from_parity(self) {
+                    outcome = self.incr_messages(icao).into();
+                    self.add_reply_information(icao, ri);
+                }
+            }
+            DF::SurveillanceAltitudeReply { dr, um, .. } => {
+                if let Some(icao) = frame.icao_from_parity(self) {
+                    outcome = self.incr_messages(icao).into();
+                    self.add_downlink(icao, dr, um);
+                }
             }
             _ => (),
         }
 
-        airplane_added
+        outcome
+    }
+
+    /// Update `Airplanes` with a SBS (BaseStation) `message`, for sources without the frames
+    ///
+    /// The values of the message update the aircraft like the frames containing them, see
+    /// [`sbs`]. Positions are checked against the receiver position and max range given to
+    /// [`Self::builder`], like decoded CPR positions.
+    ///
+    /// Return what the message changed, like [`Self::action`]
+    pub fn apply_sbs(&mut self, message: &SbsMessage) -> ActionOutcome {
+        let icao = message.icao;
+        let (receiver_position, max_range) = (self.receiver_position, self.max_range);
+        let track_config = self.track_config;
+        let keep_invalid = self.keep_invalid_callsigns;
+        let (state, airplane_added) = self.entry_or_insert(icao);
+        debug!("[{icao}] with sbs: {message:?}");
+        let previous_callsign = state.callsign.clone();
+        let previous_position = state.coords.position;
+        let ident_change = message.callsign.as_ref().and_then(|callsign| {
+            state.update_callsign(icao, callsign, DataSource::Sbs, keep_invalid)
+        });
+        if let Some(track) = message.track {
+            state.update_heading(track, DataSource::Sbs);
+        }
+        if let Some(ground_speed) = message.ground_speed {
+            state.speed = Some(ground_speed);
+            state.speed_source = Some(DataSource::Sbs);
+        }
+        if let Some(vertical_rate) = message.vertical_rate {
+            state.vert_speed = Some(vertical_rate);
+            state.update_smoothed_vert_speed(f32::from(vertical_rate));
+        }
+        if let Some(decimal) = message.squawk {
+            state.squawk = Some(squawk::from_decimal(decimal));
+        }
+        if message.on_ground.is_some() {
+            state.on_ground = message.on_ground;
+        }
+        if message.altitude.is_some() {
+            state.coords.sbs_altitude = message.altitude;
+        }
+        let mut rejected = None;
+        if let Some((latitude, longitude)) = message.position {
+            let mut coords = state.coords;
+            let position = cpr::Position { latitude, longitude };
+            match coords.check_position(Some(position), None, receiver_position, max_range) {
+                Ok(()) => {
+                    state.quality_history.add_position(true);
+                    state.update_coords(coords, message.ground_speed, track_config);
+                }
+                Err(fix) => {
+                    state.quality_history.add_position(false);
+                    state.rejected_fixes.add(fix);
+                    rejected = Some(fix);
+                }
+            }
+        }
+        let position_updated =
+            state.coords.position.is_some() && state.coords.position != previous_position;
+        let callsign_updated = state.callsign != previous_callsign;
+        if let Some(fix) = rejected {
+            self.rejected_fixes.add(fix);
+        }
+        self.push_ident_change(ident_change);
+        self.incr_messages(icao);
+        let rejected_reason = rejected.map(|fix| fix.reason);
+
+        ActionOutcome { added: airplane_added, position_updated, callsign_updated, rejected_reason }
+    }
+
+    /// Set the [`AirplaneState::signal_level`] of `icao` from the [`adsb_deku::demod::DemodMeta`]
+    /// of its frame, such as from the Beast messages. Untracked aircraft are ignored.
+    pub fn add_signal_level(&mut self, icao: ICAO, signal_level: u8) {
+        if let Some(state) = self.airplanes.get_mut(&icao) {
+            state.signal_level = Some(signal_level);
+        }
+    }
+
+    /// Take the callsign changes between sources since the last call, see [`ident`]
+    pub fn drain_ident_changes(&mut self) -> alloc::collections::vec_deque::Drain<'_, IdentChange> {
+        self.ident_changes.drain()
+    }
+
+    /// Callsign changes not yet drained, and dropped over the max length, see [`ident`]
+    #[must_use]
+    pub fn ident_changes(&self) -> &IdentChanges {
+        &self.ident_changes
+    }
+
+    fn push_ident_change(&mut self, change: Option<IdentChange>) {
+        if let Some(change) = change {
+            self.ident_changes.push(change);
+        }
     }
 
     /// from `ICAO` return details on that airplane
@@ -188,8 +625,21 @@ impl Airplanes {
                         position: *position,
                         altitude,
                         kilo_distance,
+                        bearing_deg: coor.bearing.unwrap_or_default(),
+                        elevation_deg: geo::elevation(kilo_distance, f64::from(altitude)),
                         heading: airplane_state.heading,
                         track: track.clone(),
+                        #[cfg(feature = "std")]
+                        seen: airplane_state.seen().as_secs_f64(),
+                        #[cfg(feature = "std")]
+                        seen_pos: airplane_state.seen_pos().map(|seen| seen.as_secs_f64()),
+                        #[cfg(feature = "std")]
+                        odd_age: coor.cpr_age(CPRFormat::Odd).map(|age| age.as_secs_f64()),
+                        #[cfg(feature = "std")]
+                        even_age: coor.cpr_age(CPRFormat::Even).map(|age| age.as_secs_f64()),
+                        decode: coor.decode,
+                        quality: airplane_state.quality(),
+                        ground_state: airplane_state.ground_state(),
                     })
                 } else {
                     None
@@ -199,6 +649,111 @@ impl Airplanes {
         }
     }
 
+    /// Aggregate the estimated wind of all aircraft into `band_height` ft altitude bands
+    #[must_use]
+    pub fn wind_model(&self, band_height: u32) -> wind::WindModel {
+        wind::WindModel::from_airplanes(self, band_height)
+    }
+
+    /// Aggregate of the [`FirstFix`] of all aircraft since creation, including pruned aircraft
+    ///
+    /// This quantifies how quickly CPR even/odd pairs are decoded into positions.
+    #[must_use]
+    pub fn first_fix_stats(&self) -> FirstFixStats {
+        self.first_fix_stats
+    }
+
+    /// Positions rejected by the range checks of all aircraft since the start, see [`rejected`]
+    #[must_use]
+    pub fn rejected_fixes(&self) -> &RejectedFixes {
+        &self.rejected_fixes
+    }
+
+    /// Sum of the [`Anomalies`] of all currently tracked aircraft
+    #[must_use]
+    pub fn anomalies(&self) -> Anomalies {
+        let mut anomalies = Anomalies::default();
+        for state in self.airplanes.values() {
+            anomalies += state.broadcast.anomalies;
+        }
+        anomalies
+    }
+
+    /// Amount of currently tracked aircraft by [`Equipage`]
+    #[must_use]
+    pub fn fleet_equipage(&self) -> FleetEquipage {
+        let mut fleet = FleetEquipage::default();
+        for state in self.airplanes.values() {
+            fleet.add(state.equipage());
+        }
+        fleet
+    }
+
+    /// Amount of aircraft by [`AirplaneState::downlink`], see [`interrogation`]
+    #[must_use]
+    pub fn interrogation(&self) -> Interrogation {
+        let mut interrogation = Interrogation::default();
+        for downlink in self.airplanes.values().filter_map(|state| state.downlink) {
+            interrogation.add(downlink);
+        }
+        interrogation
+    }
+
+    /// Amount of currently tracked aircraft by [`AirplaneState::integrity`], with a drop within
+    /// `window` of `now` counted as recent, see [`interference`]
+    #[cfg(feature = "std")]
+    #[must_use]
+    pub fn fleet_integrity(&self, window: Duration, now: Instant) -> FleetIntegrity {
+        let mut fleet = FleetIntegrity::default();
+        for integrity in self.airplanes.values().map(|state| state.integrity) {
+            if integrity.is_degraded().is_none() {
+                continue;
+            }
+            fleet.aircraft += 1;
+            if let Some(dropped) = integrity.dropped {
+                fleet.degraded += 1;
+                if now.saturating_duration_since(dropped) <= window {
+                    fleet.recent += 1;
+                }
+            }
+        }
+        fleet
+    }
+
+    /// Airborne aircraft with the smallest [`AirplaneCoor::kilo_distance`] from the receiver
+    #[must_use]
+    pub fn closest(&self) -> Option<(ICAO, &AirplaneState)> {
+        self.airborne()
+            .filter_map(|(icao, state)| Some((icao, state, state.coords.kilo_distance?)))
+            .min_by(|(.., a), (.., b)| a.total_cmp(b))
+            .map(|(icao, state, _)| (icao, state))
+    }
+
+    /// Aircraft with the highest [`AirplaneCoor::altitude`]
+    #[must_use]
+    pub fn highest(&self) -> Option<(ICAO, &AirplaneState)> {
+        self.airborne()
+            .filter(|(_, state)| state.coords.altitude().is_some())
+            .max_by_key(|(_, state)| state.coords.altitude())
+    }
+
+    /// Airborne aircraft with the highest ground [`AirplaneState::speed`]
+    #[must_use]
+    pub fn fastest(&self) -> Option<(ICAO, &AirplaneState)> {
+        self.airborne()
+            .filter_map(|(icao, state)| Some((icao, state, state.speed?)))
+            .max_by(|(.., a), (.., b)| a.total_cmp(b))
+            .map(|(icao, state, _)| (icao, state))
+    }
+
+    /// Aircraft not reported on the ground
+    fn airborne(&self) -> impl Iterator<Item = (ICAO, &AirplaneState)> {
+        self.airplanes
+            .iter()
+            .filter(|(_, state)| state.on_ground != Some(true))
+            .map(|(icao, state)| (*icao, state))
+    }
+
     /// Return all aircraft that currently have a [`cpr::Position`]
     #[must_use]
     pub fn all_position(&self) -> Vec<(ICAO, cpr::Position)> {
@@ -214,18 +769,18 @@ impl Airplanes {
     }
 
     /// Remove airplanes that have not been seen since `filter_time` seconds
+    ///
+    /// This uses the monotonic [`AirplaneState::last_seen`], so adjustments of the system clock
+    /// (such as NTP at boot) don't remove aircraft. This also removes expired addresses from the
+    /// [`IcaoCache`]
     #[cfg(feature = "std")]
     pub fn prune(&mut self, filter_time: u64) {
-        self.0.retain(|k, v| {
-            if let Ok(time) = v.last_time.elapsed() {
-                if time < std::time::Duration::from_secs(filter_time) {
-                    true
-                } else {
-                    info!("[{k}] non-active, removing");
-                    false
-                }
+        self.icao_cache.prune();
+        self.airplanes.retain(|k, v| {
+            if v.last_seen.elapsed() < Duration::from_secs(filter_time) {
+                true
             } else {
-                info!("[{k}] non-active(time error), removing");
+                info!("[{k}] non-active, removing");
                 false
             }
         });
@@ -234,9 +789,23 @@ impl Airplanes {
 
 // private
 impl Airplanes {
+    /// Recompute the distance and bearing of all aircraft from the receiver position, clearing the
+    /// position of aircraft out of the max range
+    fn update_distances(&mut self) {
+        let Some(lat_long) = self.receiver_position else {
+            return;
+        };
+        for (icao, state) in &mut self.airplanes {
+            if !state.coords.update_distance(lat_long, self.max_range) {
+                warn!("[{icao}] out of range from receiver: {lat_long:?}");
+                state.coords = AirplaneCoor::default();
+            }
+        }
+    }
+
     // Return (matching state from icao, true if airplane added)
     fn entry_or_insert(&mut self, icao: ICAO) -> (&mut AirplaneState, Added) {
-        let entry = self.0.entry(icao);
+        let entry = self.airplanes.entry(icao);
         let airplane_added =
             Added::from(matches!(entry, alloc::collections::btree_map::Entry::Vacant(_)));
         if Added::Yes == airplane_added {
@@ -245,7 +814,8 @@ impl Airplanes {
         (entry.or_default(), airplane_added)
     }
 
-    /// Increment message count of `ICAO`. If feature: `std`, set `last_time` to current time.
+    /// Increment message count of `ICAO`. If feature: `std`, set `last_time` and `last_seen` to
+    /// current time.
     ///
     /// Return true if entry was added into `Airplanes`
     pub fn incr_messages(&mut self, icao: ICAO) -> Added {
@@ -253,23 +823,179 @@ impl Airplanes {
         state.num_messages += 1;
         #[cfg(feature = "std")]
         {
-            state.last_time = std::time::SystemTime::now();
+            state.last_time = SystemTime::now();
+            state.last_seen = Instant::now();
         }
 
         airplane_added
     }
 
     /// update from `ME::AircraftIdentification`
+    fn add_identification(&mut self, icao: ICAO, identification: &Identification) -> ActionOutcome {
+        let keep_invalid = self.keep_invalid_callsigns;
+        let (state, airplane_added) = self.entry_or_insert(icao);
+        let previous = state.callsign.clone();
+        let change =
+            state.update_callsign(icao, &identification.cn, DataSource::Adsb, keep_invalid);
+        let callsign_updated = state.callsign != previous;
+        state.category = Some(identification.category());
+        info!("[{icao}] with identification: {}", identification.cn);
+        self.push_ident_change(change);
+
+        ActionOutcome { added: airplane_added, callsign_updated, ..ActionOutcome::default() }
+    }
+
+    /// update from the `BDS` of a Comm-B reply
+    ///
+    /// The `icao` recovered from the parity of a corrupted reply is a random address, so this is
+    /// only called with the addresses returned by [`Frame::icao_from_parity`]: already tracked, or
+    /// recently seen in a CRC-verified DF11/17 frame. The reply is counted by [`Self::action`]
+    /// whatever its register, like the other replies with the address in the parity.
+    ///
+    /// Return what was changed, see [`ActionOutcome`]
+    fn add_comm_b(&mut self, icao: ICAO, bds: &BDS) -> ActionOutcome {
+        match bds {
+            // an invalid callsign is another register, inferred as `BDS` 2,0
+            BDS::AircraftIdentification(callsign) if ident::is_valid_callsign(callsign) => {
+                let (state, airplane_added) = self.entry_or_insert(icao);
+                let callsign_updated = state.callsign.as_deref() != Some(callsign.as_str());
+                let change = state.update_callsign(icao, callsign, DataSource::CommB, false);
+                info!("[{icao}] with comm-b identification: {callsign}");
+                self.push_ident_change(change);
+                ActionOutcome {
+                    added: airplane_added,
+                    callsign_updated,
+                    ..ActionOutcome::default()
+                }
+            }
+            BDS::DataLinkCapability(capability) => {
+                let (state, airplane_added) = self.entry_or_insert(icao);
+                state.datalink_capability = Some(capability.clone());
+                info!("[{icao}] with comm-b datalink capability");
+                airplane_added.into()
+            }
+            BDS::SelectedVerticalIntention(report) => {
+                if let Some(state) = self.airplanes.get_mut(&icao) {
+                    state.update_selected_vertical_intention(report);
+                    info!("[{icao}] with comm-b selected vertical intention");
+                }
+                ActionOutcome::default()
+            }
+            BDS::TrackAndTurnReport(report) => {
+                if let Some(state) = self.airplanes.get_mut(&icao) {
+                    state.update_track_and_turn(report);
+                    info!("[{icao}] with comm-b track and turn report");
+                }
+                ActionOutcome::default()
+            }
+            BDS::HeadingAndSpeedReport(report) => {
+                if let Some(state) = self.airplanes.get_mut(&icao) {
+                    state.update_heading_and_speed(report);
+                    info!("[{icao}] with comm-b heading and speed report");
+                }
+                ActionOutcome::default()
+            }
+            _ => ActionOutcome::default(),
+        }
+    }
+
+    /// update from `ME::AircraftOperationStatus`
     ///
     /// Return true if entry was added into `Airplanes`
-    fn add_identification(&mut self, icao: ICAO, identification: &Identification) -> Added {
+    fn add_operation_status(&mut self, icao: ICAO, status: &OperationStatus) -> Added {
         let (state, airplane_added) = self.entry_or_insert(icao);
-        state.callsign = Some(identification.cn.clone());
-        info!("[{icao}] with identification: {}", identification.cn);
+        match status {
+            OperationStatus::Airborne(status) => {
+                state.broadcast.update_operation_status(
+                    icao,
+                    status.version_number,
+                    status.nic_supplement_a,
+                    status.navigational_accuracy_category,
+                );
+                state.quality_history.update_operation_status(
+                    status.source_integrity_level,
+                    status.barometric_altitude_integrity,
+                );
+                #[cfg(feature = "std")]
+                state.integrity.update_nacp(
+                    icao,
+                    status.navigational_accuracy_category,
+                    Instant::now(),
+                );
+            }
+            OperationStatus::Surface(status) => {
+                state.broadcast.update_operation_status(
+                    icao,
+                    status.version_number,
+                    status.nic_supplement_a,
+                    status.navigational_accuracy_category,
+                );
+                state.quality_history.update_operation_status(
+                    status.source_integrity_level,
+                    status.barometric_altitude_integrity,
+                );
+                #[cfg(feature = "std")]
+                state.integrity.update_nacp(
+                    icao,
+                    status.navigational_accuracy_category,
+                    Instant::now(),
+                );
+            }
+            OperationStatus::Reserved(..) => (),
+        }
+
+        airplane_added
+    }
+
+    /// update from `ME::TargetStateAndStatusInformation`
+    ///
+    /// Return true if entry was added into `Airplanes`
+    fn add_target_state(&mut self, icao: ICAO, target: &TargetStateAndStatusInformation) -> Added {
+        let (state, airplane_added) = self.entry_or_insert(icao);
+        // 0 is sent without a QNH
+        if target.qnh != 0.0 {
+            info!("[{icao}] with target state: QNH {} hPa", target.qnh);
+            state.qnh = Some(target.qnh);
+        }
 
         airplane_added
     }
 
+    /// update from the capability of `DF::AllCallReply` and `DF::ADSB`, only for tracked aircraft
+    fn add_capability(&mut self, icao: ICAO, capability: adsb_deku::Capability) {
+        if let Some(state) = self.airplanes.get_mut(&icao) {
+            state.broadcast.update_capability(icao, capability);
+        }
+    }
+
+    /// update from the identity code of `DF::SurveillanceIdentityReply` and
+    /// `DF::CommBIdentityReply`, only for tracked aircraft
+    fn add_squawk(&mut self, icao: ICAO, squawk: u32) {
+        if let Some(state) = self.airplanes.get_mut(&icao) {
+            state.squawk = Some(squawk);
+        }
+    }
+
+    /// update from the RI of `DF::ShortAirAirSurveillance` and `DF::LongAirAir`
+    fn add_reply_information(&mut self, icao: ICAO, ri: ReplyInformation) {
+        if let Some(state) = self.airplanes.get_mut(&icao) {
+            state.reply_information = Some(ri);
+        }
+    }
+
+    /// update from the DR and UM of DF4/5/20/21, logging the start and end of Comm-B broadcasts
+    fn add_downlink(&mut self, icao: ICAO, dr: DownlinkRequest, um: UtilityMessage) {
+        if let Some(state) = self.airplanes.get_mut(&icao) {
+            let was_broadcast = state.downlink.is_some_and(|d| d.request.is_broadcast());
+            match (was_broadcast, dr.is_broadcast()) {
+                (false, true) => info!("[{icao}] Comm-B broadcast: {dr}"),
+                (true, false) => info!("[{icao}] Comm-B broadcast ended: {dr}"),
+                _ => (),
+            }
+            state.downlink = Some(Downlink::new(dr, um));
+        }
+    }
+
     /// update from `ME::AirborneVelocity`
     ///
     /// Return true if entry was added into `Airplanes`
@@ -277,58 +1003,157 @@ impl Airplanes {
         let (state, airplane_added) = self.entry_or_insert(icao);
         if let Some((heading, ground_speed, vert_speed)) = vel.calculate() {
             info!("[{icao}] with airborne velocity: heading: {heading}, speed: {ground_speed}, vertical speed: {vert_speed}");
-            state.heading = Some(heading);
+            state.update_heading(heading, DataSource::Adsb);
             state.speed = Some(ground_speed as f32);
+            state.speed_source = Some(DataSource::Adsb);
             state.vert_speed = Some(vert_speed);
+            state.update_smoothed_vert_speed(f32::from(vert_speed));
+        }
+        // 0 is also sent without the difference
+        if vel.gnss_baro_diff != 0 {
+            state.gnss_baro_diff =
+                Some(i32::from(vel.gnss_sign.value()) * i32::from(vel.gnss_baro_diff));
         }
 
         airplane_added
     }
 
-    /// update from `ME::AirbornePosition{GNSSAltitude, BaroAltitude}`
+    /// update from `ME::NoPosition`, the barometric altitude of an aircraft without a position
     ///
     /// Return true if entry was added into `Airplanes`
-    fn update_position(
-        &mut self,
-        icao: ICAO,
-        altitude: &Altitude,
-        lat_long: (f64, f64),
-        max_range: f64,
-    ) -> Added {
+    fn add_no_position(&mut self, icao: ICAO, altitude: &Altitude) -> Added {
+        let (state, airplane_added) = self.entry_or_insert(icao);
+        info!("[{icao}] with no position: {:?}, {:?}", altitude.alt, altitude.ss);
+        if altitude.alt.is_some() {
+            state.coords.no_position_altitude = altitude.alt;
+        }
+
+        airplane_added
+    }
+
+    /// update from `ME::AirbornePosition{GNSSAltitude, BaroAltitude}`
+    fn update_position(&mut self, icao: ICAO, altitude: &Altitude) -> ActionOutcome {
+        let (receiver_position, max_range) = (self.receiver_position, self.max_range);
+        let track_config = self.track_config;
         let (state, airplane_added) = self.entry_or_insert(icao);
         info!(
             "[{icao}] with: {:?}, cpr lat: {}, cpr long: {}",
             altitude.alt, altitude.lat_cpr, altitude.lon_cpr
         );
-        let mut temp_coords = match altitude.odd_flag {
-            CPRFormat::Odd => AirplaneCoor {
-                altitudes: [state.coords.altitudes[0], Some(*altitude)],
-                ..state.coords
-            },
-            CPRFormat::Even => AirplaneCoor {
-                altitudes: [Some(*altitude), state.coords.altitudes[1]],
-                ..state.coords
-            },
+        let latest = match altitude.odd_flag {
+            CPRFormat::Odd => 1,
+            CPRFormat::Even => 0,
         };
+        let mut temp_coords = state.coords;
+        temp_coords.altitudes[latest] = Some(*altitude);
+        // newer than the altitude without a position
+        temp_coords.no_position_altitude = None;
+        #[cfg(feature = "std")]
+        {
+            temp_coords.cpr_seen[latest] = Some(Instant::now());
+            if let Some(nic) = altitude.nic() {
+                state.integrity.update_nic(icao, nic, Instant::now());
+            }
+        }
         // update the position from the new even/odd message if it's a good new position
-        if temp_coords.update_position(lat_long, max_range) {
-            // don't bother updating if it's the same coords
-            if state.coords != temp_coords {
-                // update track
-                if let Some(track) = &mut state.track {
-                    track.push(state.coords);
-                } else {
-                    state.track = Some(vec![state.coords]);
+        let previous = state.coords.position;
+        let mut rejected_fix = None;
+        let rejected_reason =
+            match temp_coords.update_position(receiver_position, max_range, latest) {
+                Ok(()) => {
+                    if temp_coords.position.is_some() && state.coords != temp_coords {
+                        state.quality_history.add_position(true);
+                    }
+                    state.update_coords(temp_coords, None, track_config);
+                    None
                 }
-                // update new position
-                state.coords = temp_coords;
-            }
-        } else {
-            // clear record
-            state.coords = AirplaneCoor::default();
+                Err(Rejected::StalePair) => {
+                    state.update_coords(temp_coords, None, track_config);
+                    Some(RejectReason::StalePair)
+                }
+                Err(Rejected::Fix(fix)) => {
+                    state.quality_history.add_position(false);
+                    state.rejected_fixes.add(fix);
+                    rejected_fix = Some(fix);
+                    // clear record
+                    state.coords = AirplaneCoor::default();
+                    Some(fix.reason)
+                }
+            };
+        let position_updated = state.coords.position.is_some() && state.coords.position != previous;
+
+        if state.first_fix.is_none() && state.coords.position.is_some() {
+            // num_messages is incremented after this message
+            let first_fix = FirstFix {
+                messages: state.num_messages + 1,
+                #[cfg(feature = "std")]
+                elapsed: state.first_seen.elapsed(),
+            };
+            info!("[{icao}] first position fix: {first_fix:?}");
+            state.first_fix = Some(first_fix);
+            self.first_fix_stats.add(first_fix);
+        }
+        if let Some(fix) = rejected_fix {
+            self.rejected_fixes.add(fix);
         }
 
-        airplane_added
+        ActionOutcome {
+            added: airplane_added,
+            position_updated,
+            callsign_updated: false,
+            rejected_reason,
+        }
+    }
+}
+
+/// Messages and time from the first detection of an aircraft to its first position fix
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FirstFix {
+    /// Messages received, including the message completing the position
+    pub messages: u32,
+    /// Time since [`AirplaneState::first_seen`]
+    #[cfg(feature = "std")]
+    pub elapsed: Duration,
+}
+
+/// Aggregate of the [`FirstFix`] of all aircraft, see [`Airplanes::first_fix_stats`]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FirstFixStats {
+    /// Amount of aircraft with a position fix
+    pub aircraft: u32,
+    pub total_messages: u64,
+    pub max_messages: u32,
+    #[cfg(feature = "std")]
+    pub total_elapsed: Duration,
+    #[cfg(feature = "std")]
+    pub max_elapsed: Duration,
+}
+
+impl FirstFixStats {
+    fn add(&mut self, first_fix: FirstFix) {
+        self.aircraft += 1;
+        self.total_messages += u64::from(first_fix.messages);
+        self.max_messages = self.max_messages.max(first_fix.messages);
+        #[cfg(feature = "std")]
+        {
+            self.total_elapsed += first_fix.elapsed;
+            self.max_elapsed = self.max_elapsed.max(first_fix.elapsed);
+        }
+    }
+
+    /// Mean amount of messages to the first position fix
+    #[must_use]
+    pub fn mean_messages(&self) -> Option<f64> {
+        (self.aircraft != 0).then(|| self.total_messages as f64 / f64::from(self.aircraft))
+    }
+
+    /// Mean time to the first position fix
+    #[cfg(feature = "std")]
+    #[must_use]
+    pub fn mean_elapsed(&self) -> Option<Duration> {
+        (self.aircraft != 0).then(|| self.total_elapsed / self.aircraft)
     }
 }
 
@@ -337,10 +1162,98 @@ impl Airplanes {
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct AirplaneDetails {
     pub position: cpr::Position,
-    pub altitude: u16,
+    pub altitude: i32,
     pub kilo_distance: f64,
+    /// bearing from receiver lat/long, see [`geo::bearing`]
+    pub bearing_deg: f64,
+    /// elevation from receiver, see [`geo::elevation`]
+    pub elevation_deg: f64,
     pub heading: Option<f32>,
     pub track: Option<Vec<AirplaneCoor>>,
+    /// seconds since the last message, see [`AirplaneState::seen`]
+    #[cfg(feature = "std")]
+    pub seen: f64,
+    /// seconds since the last position, see [`AirplaneState::seen_pos`]
+    #[cfg(feature = "std")]
+    pub seen_pos: Option<f64>,
+    /// seconds since the stored odd CPR frame, see [`AirplaneCoor::cpr_age`]
+    #[cfg(feature = "std")]
+    pub odd_age: Option<f64>,
+    /// seconds since the stored even CPR frame, see [`AirplaneCoor::cpr_age`]
+    #[cfg(feature = "std")]
+    pub even_age: Option<f64>,
+    /// decoding of the current position
+    pub decode: Option<CprDecode>,
+    /// see [`AirplaneState::quality`]
+    pub quality: Option<QualityScore>,
+    /// see [`AirplaneState::ground_state`]
+    pub ground_state: Option<GroundState>,
+}
+
+/// CPR decoding of a position, see [`AirplaneCoor::decode`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CprDecode {
+    /// From an even and odd frame received within 10 seconds
+    Global,
+    /// From a single frame, relative to the previous position
+    Local,
+}
+
+/// Vertical trend of an aircraft, see [`AirplaneState::trend`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Trend {
+    Climb,
+    Level,
+    Descent,
+}
+
+/// Source of a fused value in [`AirplaneState`]
+///
+/// Ordered by priority, a value from a higher priority source is never overwritten by a lower
+/// priority source.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DataSource {
+    /// Comm-B replies, the Enhanced Surveillance `BDS` 5,0 and 6,0, or the identification of
+    /// `BDS` 2,0
+    CommB,
+    /// ADS-B `ME::AirborneVelocity`
+    Adsb,
+    /// SBS (BaseStation) message, see [`Airplanes::apply_sbs`]
+    Sbs,
+}
+
+/// Enhanced Surveillance (EHS) values from Comm-B `BDS` 4,0, 5,0 and 6,0
+///
+/// Each report replaces all the values of its register, a value not available in the latest
+/// report is `None` instead of the value of an older report.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EnhancedSurveillance {
+    /// MCP/FCU selected altitude (ft)
+    pub mcp_selected_altitude: Option<u16>,
+    /// FMS selected altitude (ft)
+    pub fms_selected_altitude: Option<u16>,
+    /// Barometric pressure setting (millibars)
+    pub barometric_pressure_setting: Option<f32>,
+    /// Roll angle (degrees), negative is left wing down
+    pub roll_angle: Option<f32>,
+    /// Track angle rate (degrees/second)
+    pub track_angle_rate: Option<f32>,
+    /// True track angle (degrees)
+    pub true_track: Option<f32>,
+    /// Ground speed (kt)
+    pub ground_speed: Option<u16>,
+    /// True airspeed (kt)
+    pub true_airspeed: Option<u16>,
+    /// Indicated airspeed (kt)
+    pub indicated_airspeed: Option<u16>,
+    /// Mach number
+    pub mach: Option<f32>,
+    /// Magnetic heading (degrees)
+    pub magnetic_heading: Option<f32>,
 }
 
 /// Value in `BTreeMap` of `Airplanes`
@@ -349,8 +1262,13 @@ pub struct AirplaneDetails {
 pub struct AirplaneState {
     // TODO: rename to coor
     pub coords: AirplaneCoor,
+    /// Mode A code as nibbles, such as `0x7700` for 7700, see [`squawk`]
     pub squawk: Option<u32>,
     pub callsign: Option<String>,
+    /// Source of `callsign`, the newest callsign is kept whatever its source
+    pub callsign_source: Option<DataSource>,
+    /// from `ME::AircraftIdentification`
+    pub category: Option<EmitterCategory>,
     /// heading from `adsb::AirborneVelocity::calculate()`
     ///
     /// 0 = Straight up
@@ -360,13 +1278,64 @@ pub struct AirplaneState {
     ///
     /// Stored as a f64 in that library but we store as f32 for size reasons in this library
     pub speed: Option<f32>,
+    /// Source of `heading`
+    pub heading_source: Option<DataSource>,
+    /// Monotonic time of the last `heading`
+    #[cfg(feature = "std")]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub heading_seen: Option<Instant>,
+    /// Turn rate (degrees/second) from the last two `heading`, see [`Self::turn_rate`]
+    #[cfg(feature = "std")]
+    pub heading_rate: Option<f32>,
+    /// Source of `speed`
+    pub speed_source: Option<DataSource>,
     /// vert_speed from `adsb::AirborneVelocity::calculate()`
     pub vert_speed: Option<i16>,
+    /// GNSS altitude above the barometric altitude (ft), from `adsb::AirborneVelocity`, see
+    /// [`Self::altitude`]
+    pub gnss_baro_diff: Option<i32>,
+    /// Altimeter setting (hPa) from `adsb::TargetStateAndStatusInformation`, see [`qnh`]
+    pub qnh: Option<f32>,
+    /// exponential moving average of `vert_speed`, see [`Self::trend`]
+    pub smoothed_vert_speed: Option<f32>,
+    /// from Comm-B `BDS` 4,0, 5,0 and 6,0
+    pub ehs: EnhancedSurveillance,
     pub on_ground: Option<bool>,
+    /// from Comm-B `BDS::DataLinkCapability`
+    pub datalink_capability: Option<DataLinkCapability>,
+    /// ACAS capability or maximum airspeed, from the RI of the air-air replies (DF0/DF16)
+    pub reply_information: Option<ReplyInformation>,
+    /// Downlink request and interrogator, from the DR and UM of DF4/5/20/21
+    pub downlink: Option<Downlink>,
+    /// capability, version and NIC/NAC values, for detecting [`Anomalies`]
+    pub broadcast: BroadcastHistory,
+    /// SIL, NICbaro and position checks, for [`Self::quality`]
+    pub quality_history: QualityHistory,
+    /// Positions rejected by the range checks, see [`rejected`]
+    pub rejected_fixes: RejectedFixes,
+    /// NIC and NACp, for detecting GNSS interference
+    #[cfg(feature = "std")]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub integrity: Integrity,
     pub num_messages: u32,
+    /// Signal level (0 to 255) of the last frame with one, see [`Airplanes::add_signal_level`]
+    pub signal_level: Option<u8>,
+    /// Messages and time to the first position fix, `None` until a position is decoded
+    pub first_fix: Option<FirstFix>,
+    /// Monotonic time of the first message
+    #[cfg(feature = "std")]
+    #[cfg_attr(feature = "serde", serde(skip, default = "Instant::now"))]
+    pub first_seen: Instant,
+    /// Wall clock time of the last message, for display only
     #[cfg(feature = "std")]
     pub last_time: SystemTime,
+    /// Monotonic time of the last message, used by [`Airplanes::prune`]
+    #[cfg(feature = "std")]
+    #[cfg_attr(feature = "serde", serde(skip, default = "Instant::now"))]
+    pub last_seen: Instant,
     pub track: Option<Vec<AirplaneCoor>>,
+    /// Position where the aircraft was first seen [`GroundState::Parked`], see [`ground`]
+    pub parked_position: Option<cpr::Position>,
 }
 
 impl Default for AirplaneState {
@@ -375,15 +1344,360 @@ impl Default for AirplaneState {
             coords: AirplaneCoor::default(),
             squawk: None,
             callsign: None,
+            callsign_source: None,
+            category: None,
             heading: None,
             speed: None,
+            heading_source: None,
+            #[cfg(feature = "std")]
+            heading_seen: None,
+            #[cfg(feature = "std")]
+            heading_rate: None,
+            speed_source: None,
             vert_speed: None,
+            gnss_baro_diff: None,
+            qnh: None,
+            smoothed_vert_speed: None,
+            ehs: EnhancedSurveillance::default(),
             on_ground: None,
+            datalink_capability: None,
+            reply_information: None,
+            downlink: None,
+            broadcast: BroadcastHistory::default(),
+            quality_history: QualityHistory::default(),
+            rejected_fixes: RejectedFixes::default(),
+            #[cfg(feature = "std")]
+            integrity: Integrity::default(),
             num_messages: 0,
+            signal_level: None,
+            first_fix: None,
+            #[cfg(feature = "std")]
+            first_seen: Instant::now(),
             #[cfg(feature = "std")]
             last_time: SystemTime::now(),
+            #[cfg(feature = "std")]
+            last_seen: Instant::now(),
             track: None,
+            parked_position: None,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl AirplaneState {
+    /// Time since the last message
+    #[must_use]
+    pub fn seen(&self) -> Duration {
+        self.last_seen.elapsed()
+    }
+
+    /// Time since the last position, `None` if the position isn't known
+    #[must_use]
+    pub fn seen_pos(&self) -> Option<Duration> {
+        self.coords.position.and(self.coords.last_seen).map(|last_seen| last_seen.elapsed())
+    }
+
+    /// Messages per second since the first message, `None` for the first 10 seconds
+    #[must_use]
+    pub fn message_rate(&self) -> Option<f64> {
+        let elapsed = self.first_seen.elapsed();
+        (elapsed >= MIN_RATE_DURATION).then(|| f64::from(self.num_messages) / elapsed.as_secs_f64())
+    }
+
+    /// Turn rate (degrees/second), positive to the right
+    ///
+    /// From the EHS track angle rate, else from the EHS roll angle for a coordinated turn at the
+    /// true airspeed (or ground speed), else from the last two headings.
+    #[must_use]
+    pub fn turn_rate(&self) -> Option<f32> {
+        if let Some(track_angle_rate) = self.ehs.track_angle_rate {
+            return Some(track_angle_rate);
+        }
+        let speed = self.ehs.true_airspeed.map(f32::from).or(self.speed);
+        if let (Some(roll_angle), Some(speed)) = (self.ehs.roll_angle, speed) {
+            if speed > 0.0 {
+                return Some(1091.0 * libm::tanf(roll_angle.to_radians()) / speed);
+            }
+        }
+        self.heading_rate
+    }
+
+    /// `heading` extrapolated to `at` with the [`Self::turn_rate`], for at most 15 seconds
+    #[must_use]
+    pub fn estimated_heading(&self, at: Instant) -> Option<f32> {
+        let heading = self.heading?;
+        let (Some(seen), Some(turn_rate)) = (self.heading_seen, self.turn_rate()) else {
+            return Some(heading);
+        };
+        let elapsed = at.saturating_duration_since(seen).min(MAX_HEADING_EXTRAPOLATION);
+        Some((heading + turn_rate * elapsed.as_secs_f32()).rem_euclid(360.0))
+    }
+}
+
+#[cfg(feature = "time")]
+impl AirplaneState {
+    /// [`Self::last_time`] in UTC
+    #[must_use]
+    pub fn last_seen_utc(&self) -> time::OffsetDateTime {
+        timestamp::utc(self.last_time)
+    }
+
+    /// [`Self::last_time`] in the local time of `offset`
+    #[must_use]
+    pub fn last_seen_local(&self, offset: time::UtcOffset) -> time::OffsetDateTime {
+        timestamp::local(self.last_time, offset)
+    }
+
+    /// [`Self::last_time`] as an ISO 8601 UTC string, see [`timestamp::iso8601`]
+    #[must_use]
+    pub fn last_seen_iso8601(&self) -> String {
+        timestamp::iso8601(self.last_time)
+    }
+}
+
+impl AirplaneState {
+    /// Transponder level and ADS-B version, from the capability and version in [`Self::broadcast`]
+    #[must_use]
+    pub fn equipage(&self) -> Equipage {
+        Equipage {
+            transponder_level: self
+                .broadcast
+                .capability
+                .and_then(TransponderLevel::from_capability),
+            adsb_version: self.broadcast.version,
+        }
+    }
+
+    /// Quality of the broadcast, from the NACp in [`Self::broadcast`], [`Self::quality_history`]
+    /// and the message rate, see [`crate::quality`]
+    #[must_use]
+    pub fn quality(&self) -> Option<QualityScore> {
+        #[cfg(feature = "std")]
+        let message_rate = self.message_rate();
+        #[cfg(not(feature = "std"))]
+        let message_rate = None;
+        QualityScore::new(self.broadcast.nacp, &self.quality_history, message_rate)
+    }
+
+    /// Altitude (ft) from `source`, else [`AirplaneCoor::altitude`], see [`altitude`]
+    #[must_use]
+    pub fn altitude(&self, source: AltitudeSource) -> Option<i32> {
+        let altitude = match source {
+            AltitudeSource::Baro => self.coords.baro_altitude(),
+            AltitudeSource::Gnss => self.coords.gnss_altitude(),
+            AltitudeSource::GnssCorrected => self.coords.gnss_altitude().or_else(|| {
+                let (baro, diff) = (self.coords.baro_altitude(), self.gnss_baro_diff);
+                baro.zip(diff).map(|(baro, diff)| baro + diff)
+            }),
+        };
+        altitude.or_else(|| self.coords.altitude())
+    }
+
+    /// `on_ground`, or the air/ground state of the last capability, `None` if unknown
+    #[must_use]
+    pub fn is_on_ground(&self) -> Option<bool> {
+        self.on_ground.or(match self.broadcast.capability {
+            Some(adsb_deku::Capability::AG_GROUND) => Some(true),
+            Some(adsb_deku::Capability::AG_AIRBORNE) => Some(false),
+            _ => None,
+        })
+    }
+
+    /// Parked, taxiing or rolling for a takeoff, `None` if not on the ground, see [`ground`]
+    #[must_use]
+    pub fn ground_state(&self) -> Option<GroundState> {
+        self.ground_state_at(&self.coords, self.speed)
+    }
+
+    /// [`Self::ground_state`] at `coords` with the ground `speed` of that fix, moved from the
+    /// position where the aircraft was first seen parked, or else the last position of the track
+    fn ground_state_at(&self, coords: &AirplaneCoor, speed: Option<f32>) -> Option<GroundState> {
+        let moved = match (self.anchor_position(), coords.position) {
+            (Some(last), Some(position)) => Some(geo::distance(
+                (last.latitude, last.longitude),
+                (position.latitude, position.longitude),
+            )),
+            _ => None,
+        };
+        ground::ground_state(self.is_on_ground(), speed, moved)
+    }
+
+    /// `parked_position`, or the position of the last coords of the track
+    fn anchor_position(&self) -> Option<cpr::Position> {
+        let last = self.track.as_ref().and_then(|track| track.last()).unwrap_or(&self.coords);
+        self.parked_position.or(last.position)
+    }
+
+    /// Climb or descent if [`Self::smoothed_vert_speed`] is over `threshold` (ft/min), otherwise
+    /// level
+    #[must_use]
+    pub fn trend(&self, threshold: f32) -> Option<Trend> {
+        self.smoothed_vert_speed.map(|vert_speed| {
+            if vert_speed >= threshold {
+                Trend::Climb
+            } else if vert_speed <= -threshold {
+                Trend::Descent
+            } else {
+                Trend::Level
+            }
+        })
+    }
+
+    fn update_heading(&mut self, heading: f32, source: DataSource) {
+        #[cfg(feature = "std")]
+        {
+            let now = Instant::now();
+            self.heading_rate = match (self.heading, self.heading_seen) {
+                (Some(previous), Some(seen)) if now.duration_since(seen) <= TURN_RATE_WINDOW => {
+                    let elapsed = now.duration_since(seen).as_secs_f32();
+                    // shortest turn, within -180..180
+                    let change = (heading - previous + 180.0).rem_euclid(360.0) - 180.0;
+                    (elapsed > 0.0).then(|| change / elapsed).or(self.heading_rate)
+                }
+                _ => None,
+            };
+            self.heading_seen = Some(now);
         }
+        self.heading = Some(heading);
+        self.heading_source = Some(source);
+    }
+
+    fn update_smoothed_vert_speed(&mut self, vert_speed: f32) {
+        let smoothed = self.smoothed_vert_speed.map_or(vert_speed, |smoothed| {
+            smoothed + VERT_SPEED_SMOOTHING * (vert_speed - smoothed)
+        });
+        self.smoothed_vert_speed = Some(smoothed);
+    }
+
+    /// Estimate the wind from the ground vector and the EHS air vector
+    ///
+    /// The ground vector is `heading` and `speed`, and the air vector is the EHS magnetic heading
+    /// and true airspeed. See [`Wind::from_vectors`].
+    #[must_use]
+    pub fn estimate_wind(&self) -> Option<Wind> {
+        let (Some(track), Some(ground_speed)) = (self.heading, self.speed) else {
+            return None;
+        };
+        let (Some(heading), Some(airspeed)) = (self.ehs.magnetic_heading, self.ehs.true_airspeed)
+        else {
+            return None;
+        };
+        Some(Wind::from_vectors(track, ground_speed, heading, f32::from(airspeed)))
+    }
+
+    /// Estimate the static air temperature (celsius) from the EHS true airspeed and mach
+    #[must_use]
+    pub fn estimate_temperature(&self) -> Option<f32> {
+        wind::temperature(f32::from(self.ehs.true_airspeed?), self.ehs.mach?)
+    }
+
+    /// Replace `coords` with a new position, adding the previous position to the track
+    ///
+    /// `speed` is the ground speed of the new position, if the fix has one.
+    fn update_coords(
+        &mut self,
+        coords: AirplaneCoor,
+        speed: Option<f32>,
+        track_config: TrackConfig,
+    ) {
+        // don't bother updating if it's the same coords
+        if self.coords == coords {
+            return;
+        }
+        // the jitter of a parked aircraft isn't added to its track, nor does it move the position
+        // where it was parked, so that creeping along is eventually taxiing
+        let parked = self.ground_state_at(&coords, speed) == Some(GroundState::Parked);
+        self.parked_position = if parked { self.anchor_position() } else { None };
+        if track_config.enabled && !parked {
+            let track = self.track.get_or_insert_with(Vec::new);
+            track.push(self.coords);
+            if let Some(max_len) = track_config.max_len {
+                if track.len() > max_len {
+                    track.drain(..track.len() - max_len);
+                }
+            }
+        }
+        self.coords = coords;
+    }
+
+    /// Set `callsign` from `source`, checking for [`Anomalies::callsign_alternation`]
+    ///
+    /// Invalid callsigns are rejected unless `keep_invalid`, see
+    /// [`AirplanesBuilder::keep_invalid_callsigns`]. Returns the change from the callsign of
+    /// another source, see [`ident`]
+    fn update_callsign(
+        &mut self,
+        icao: ICAO,
+        callsign: &str,
+        source: DataSource,
+        keep_invalid: bool,
+    ) -> Option<IdentChange> {
+        if !keep_invalid && !ident::is_valid_callsign(callsign) {
+            self.broadcast.reject_callsign(icao, callsign);
+            return None;
+        }
+        self.broadcast.update_callsign(icao, self.callsign.as_deref(), callsign);
+        let change = match (&self.callsign, self.callsign_source) {
+            (Some(previous), Some(previous_source))
+                if previous != callsign && previous_source != source =>
+            {
+                warn!(
+                    "[{icao}] callsign from {source:?}: {callsign}, from {previous_source:?}: \
+                     {previous}"
+                );
+                Some(IdentChange {
+                    icao,
+                    previous: previous.clone(),
+                    previous_source,
+                    callsign: String::from(callsign),
+                    source,
+                })
+            }
+            _ => None,
+        };
+        self.callsign = Some(String::from(callsign));
+        self.callsign_source = Some(source);
+        change
+    }
+
+    /// update from Comm-B `BDS::SelectedVerticalIntention`
+    fn update_selected_vertical_intention(&mut self, report: &SelectedVerticalIntention) {
+        let ehs = &mut self.ehs;
+        ehs.mcp_selected_altitude = report.mcp_selected_altitude;
+        ehs.fms_selected_altitude = report.fms_selected_altitude;
+        ehs.barometric_pressure_setting = report.barometric_pressure_setting;
+    }
+
+    /// update from Comm-B `BDS::TrackAndTurnReport`
+    ///
+    /// `heading` and `speed` are only updated if they weren't set from ADS-B
+    fn update_track_and_turn(&mut self, report: &TrackAndTurnReport) {
+        let ehs = &mut self.ehs;
+        ehs.roll_angle = report.roll_angle;
+        ehs.track_angle_rate = report.track_angle_rate;
+        ehs.true_track = report.true_track;
+        ehs.ground_speed = report.ground_speed;
+        ehs.true_airspeed = report.true_airspeed;
+
+        if self.heading_source <= Some(DataSource::CommB) {
+            if let Some(track) = report.true_track {
+                self.update_heading(track, DataSource::CommB);
+            }
+        }
+        if self.speed_source <= Some(DataSource::CommB) {
+            if let Some(ground_speed) = report.ground_speed {
+                self.speed = Some(f32::from(ground_speed));
+                self.speed_source = Some(DataSource::CommB);
+            }
+        }
+    }
+
+    /// update from Comm-B `BDS::HeadingAndSpeedReport`
+    fn update_heading_and_speed(&mut self, report: &HeadingAndSpeedReport) {
+        let ehs = &mut self.ehs;
+        ehs.magnetic_heading = report.magnetic_heading;
+        ehs.indicated_airspeed = report.indicated_airspeed;
+        ehs.mach = report.mach;
     }
 }
 
@@ -397,66 +1711,202 @@ pub struct AirplaneCoor {
     /// last good time
     #[cfg(feature = "std")]
     pub last_time: Option<SystemTime>,
+    /// last good time, monotonic
+    #[cfg(feature = "std")]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub last_seen: Option<Instant>,
     /// distance from receiver lat/long
     pub kilo_distance: Option<f64>,
+    /// bearing from receiver lat/long
+    pub bearing: Option<f64>,
+    /// time each of `altitudes` was received
+    #[cfg(feature = "std")]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub cpr_seen: [Option<Instant>; 2],
+    /// decoding of `position`, `None` for the positions of SBS messages
+    pub decode: Option<CprDecode>,
+    /// altitude (ft) of SBS messages, used without an altitude from the CPR frames, see
+    /// [`Airplanes::apply_sbs`]
+    pub sbs_altitude: Option<i32>,
+    /// altitude (ft) of `ME::NoPosition`, used until the next position, see [`Self::altitude`]
+    pub no_position_altitude: Option<i32>,
+}
+
+/// Position of [`AirplaneCoor::update_position`] not used
+enum Rejected {
+    /// see [`RejectReason::StalePair`]
+    StalePair,
+    Fix(RejectedFix),
 }
 
 impl AirplaneCoor {
     /// After checking the range of the new lat / long, new position from last position, update the
     /// position of an aircraft
-    fn update_position(&mut self, lat_long: (f64, f64), max_range: f64) -> bool {
-        if let [Some(odd), Some(even)] = self.altitudes {
-            let test_position = cpr::get_position((&odd, &even));
-
-            // Check kilometer range from receiver
-            if let Some(test_position) = test_position {
-                let kilo_distance = Self::haversine_distance(
-                    lat_long,
-                    (test_position.latitude, test_position.longitude),
-                );
-                if kilo_distance > max_range {
-                    warn!("range: {kilo_distance} -  old: {lat_long:?} new: {test_position:?}");
-                    return false;
-                }
-                self.kilo_distance = Some(kilo_distance);
-                debug!("range: {kilo_distance}");
+    ///
+    /// The position is decoded globally from both `altitudes`, or locally from the `latest` of
+    /// `altitudes` relative to the previous position if the other frame is too old. Without a
+    /// receiver position, the range isn't checked.
+    fn update_position(
+        &mut self,
+        lat_long: Option<(f64, f64)>,
+        max_range: Option<f64>,
+        latest: usize,
+    ) -> Result<(), Rejected> {
+        let (test_position, decode) = match (self.altitudes, self.position) {
+            ([Some(odd), Some(even)], _) if self.is_global_pair() => {
+                (cpr::get_position((&odd, &even)), CprDecode::Global)
             }
+            (altitudes, Some(position)) => {
+                let Some(frame) = altitudes[latest] else {
+                    return Ok(());
+                };
+                let reference = (position.latitude, position.longitude);
+                (cpr::get_position_from_reference(&frame, reference), CprDecode::Local)
+            }
+            ([Some(_), Some(_)], None) => return Err(Rejected::StalePair),
+            _ => return Ok(()),
+        };
+        self.check_position(test_position, Some(decode), lat_long, max_range).map_err(Rejected::Fix)
+    }
 
-            // if previous position, check against for range. This is a non-great way of doing
-            // this, but maybe in the future we can check against the speed of the aircraft
-            if let (Some(current_position), Some(test_position)) = (self.position, test_position) {
-                let distance = Self::haversine_distance_position(current_position, test_position);
-                if distance > MAX_AIRCRAFT_DISTANCE {
-                    warn!("distance: {distance} old: {current_position:?}, invalid: {test_position:?}");
-                    return false;
-                }
-                debug!("distance: {distance}");
+    /// After checking the range of `test_position` from the receiver `lat_long` and the distance
+    /// from the last position, update the position of an aircraft
+    fn check_position(
+        &mut self,
+        test_position: Option<cpr::Position>,
+        decode: Option<CprDecode>,
+        lat_long: Option<(f64, f64)>,
+        max_range: Option<f64>,
+    ) -> Result<(), RejectedFix> {
+        // Check kilometer range from receiver
+        if let (Some(test_position), Some(lat_long)) = (test_position, lat_long) {
+            let kilo_distance = Self::haversine_distance(
+                lat_long,
+                (test_position.latitude, test_position.longitude),
+            );
+            if max_range.is_some_and(|max_range| kilo_distance > max_range) {
+                warn!("range: {kilo_distance} -  old: {lat_long:?} new: {test_position:?}");
+                let reason = RejectReason::OutOfRange;
+                return Err(RejectedFix { position: test_position, reason, kilo_distance });
             }
+            self.kilo_distance = Some(kilo_distance);
+            self.bearing =
+                Some(geo::bearing(lat_long, (test_position.latitude, test_position.longitude)));
+            debug!("range: {kilo_distance}");
+        }
 
-            // Good new position!
-            self.position = test_position;
-            debug!("update_position: odd: (lat: {}, long: {}), even: (lat: {}, long: {}), position: {:?}",
-                odd.lat_cpr,
-                odd.lon_cpr,
-                even.lat_cpr,
-                even.lat_cpr,
-                self.position);
-            #[cfg(feature = "std")]
-            {
-                self.last_time = Some(SystemTime::now());
+        // if previous position, check against for range. This is a non-great way of doing
+        // this, but maybe in the future we can check against the speed of the aircraft
+        if let (Some(current_position), Some(test_position)) = (self.position, test_position) {
+            let distance = Self::haversine_distance_position(current_position, test_position);
+            if distance > MAX_AIRCRAFT_DISTANCE {
+                warn!("distance: {distance} old: {current_position:?}, invalid: {test_position:?}");
+                let reason = RejectReason::DistanceJump;
+                return Err(RejectedFix {
+                    position: test_position,
+                    reason,
+                    kilo_distance: distance,
+                });
             }
+            debug!("distance: {distance}");
+        }
+
+        // Good new position!
+        self.position = test_position;
+        self.decode = test_position.and(decode);
+        debug!("update_position: {decode:?} {:?}, position: {:?}", self.altitudes, self.position);
+        #[cfg(feature = "std")]
+        {
+            self.last_time = Some(SystemTime::now());
+            self.last_seen = Some(Instant::now());
+        }
+        Ok(())
+    }
+
+    /// Return true if both `altitudes` were received within [`CPR_GLOBAL_WINDOW`]
+    #[cfg(feature = "std")]
+    fn is_global_pair(&self) -> bool {
+        match self.cpr_seen {
+            [Some(a), Some(b)] => a.max(b).duration_since(a.min(b)) <= CPR_GLOBAL_WINDOW,
+            _ => false,
+        }
+    }
+
+    /// Without timestamps, any pair of frames is decoded globally
+    #[cfg(not(feature = "std"))]
+    fn is_global_pair(&self) -> bool {
+        true
+    }
+
+    /// Time since the stored CPR frame with the `format`
+    #[cfg(feature = "std")]
+    #[must_use]
+    pub fn cpr_age(&self, format: CPRFormat) -> Option<Duration> {
+        self.altitudes
+            .iter()
+            .zip(self.cpr_seen)
+            .find_map(|(altitude, seen)| match (altitude, seen) {
+                (Some(altitude), Some(seen)) if altitude.odd_flag == format => Some(seen),
+                _ => None,
+            })
+            .map(|seen| seen.elapsed())
+    }
+
+    /// Recompute the distance and bearing of the current position from the receiver `lat_long`
+    ///
+    /// Return false if the position is out of `max_range`
+    fn update_distance(&mut self, lat_long: (f64, f64), max_range: Option<f64>) -> bool {
+        let Some(position) = self.position else {
+            return true;
+        };
+        let position = (position.latitude, position.longitude);
+        let kilo_distance = Self::haversine_distance(lat_long, position);
+        if max_range.is_some_and(|max_range| kilo_distance > max_range) {
+            return false;
         }
+        self.kilo_distance = Some(kilo_distance);
+        self.bearing = Some(geo::bearing(lat_long, position));
         true
     }
 
-    /// Return altitude from Odd Altitude
-    fn altitude(&self) -> Option<u16> {
+    /// Return altitude from `ME::NoPosition` received since the last position, Odd Altitude, or
+    /// from SBS messages
+    #[must_use]
+    pub fn altitude(&self) -> Option<i32> {
+        if self.no_position_altitude.is_some() {
+            return self.no_position_altitude;
+        }
         if let Some(odd) = self.altitudes[0] {
             if let Some(alt) = odd.alt {
                 return Some(alt);
             }
         }
-        None
+        self.sbs_altitude
+    }
+
+    /// Barometric altitude, from `ME::NoPosition` received since the last position, the TC 9-18
+    /// positions, or from SBS messages
+    #[must_use]
+    pub fn baro_altitude(&self) -> Option<i32> {
+        if self.no_position_altitude.is_some() {
+            return self.no_position_altitude;
+        }
+        self.altitudes
+            .iter()
+            .flatten()
+            .find(|altitude| !altitude::is_gnss(altitude.tc))
+            .and_then(|altitude| altitude.alt)
+            .or(self.sbs_altitude)
+    }
+
+    /// GNSS altitude, from the TC 20-22 positions
+    #[must_use]
+    pub fn gnss_altitude(&self) -> Option<i32> {
+        self.altitudes
+            .iter()
+            .flatten()
+            .find(|altitude| altitude::is_gnss(altitude.tc))
+            .and_then(|altitude| altitude.alt)
     }
 
     /// Calculate the kilometers between two lat/long points
@@ -468,27 +1918,713 @@ impl AirplaneCoor {
         Self::haversine_distance((lat1, long1), (lat2, long2))
     }
 
-    // https://en.wikipedia.org/wiki/Haversine_formula
+    /// Calculate the kilometers between two lat/long points, see [`geo::distance`]
     fn haversine_distance(s: (f64, f64), other: (f64, f64)) -> f64 {
-        // kilometers
-        let lat1_rad = s.0.to_radians();
-        let lat2_rad = other.0.to_radians();
-        let long1_rad = s.1.to_radians();
-        let long2_rad = other.1.to_radians();
-
-        let x_lat = libm::sin((lat2_rad - lat1_rad) / 2.00);
-        let x_long = libm::sin((long2_rad - long1_rad) / 2.00);
-
-        // this clippy lint will dis-allow mul_add, this isn't available for `no_std`
-        #[allow(clippy::suboptimal_flops)]
-        let a = x_lat * x_lat
-            + libm::cos(lat1_rad)
-                * libm::cos(lat2_rad)
-                * f64::from(libm::powf(libm::sin(x_long) as f32, 2.0));
-
-        let c = 2.0 * libm::atan2(libm::sqrt(a), libm::sqrt(1.0 - a));
-
-        let r = 6371.00;
-        r * c
+        geo::distance(s, other)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+
+    use adsb_deku::adsb::ADSBVersion;
+    use adsb_deku::Capability;
+
+    use super::*;
+
+    const ICAO_40621D: ICAO = ICAO([0x40, 0x62, 0x1d]);
+
+    /// even and odd airborne position of 40621d, at (52.2572, 3.9193)
+    fn add_position(airplanes: &mut Airplanes) {
+        for bytes in [
+            [0x8d, 0x40, 0x62, 0x1d, 0x58, 0xc3, 0x82, 0xd6, 0x90, 0xc8, 0xac, 0x28, 0x63, 0xa7],
+            [0x8d, 0x40, 0x62, 0x1d, 0x58, 0xc3, 0x86, 0x43, 0x5c, 0xc4, 0x12, 0x69, 0x2a, 0xd6],
+        ] {
+            airplanes.action(Frame::from_bytes(&bytes).unwrap());
+        }
+    }
+
+    #[test]
+    fn haversine_distance() {
+        // a quarter of the equator, the sine of the half longitude difference was taken twice,
+        // giving about 9010 km
+        let kilo_distance = AirplaneCoor::haversine_distance((0.0, 0.0), (0.0, 90.0));
+        assert!((kilo_distance - 10_007.543).abs() < 1e-3, "{kilo_distance}");
+        let kilo_distance = AirplaneCoor::haversine_distance((35.214, -80.943), (33.640, -84.427));
+        assert!((kilo_distance - 364.3).abs() < 0.1, "{kilo_distance}");
+    }
+
+    #[test]
+    fn set_receiver_position() {
+        let mut airplanes = Airplanes::builder().position((52.0, 4.0)).max_range(100.0).build();
+        add_position(&mut airplanes);
+        let distance = airplanes.get(ICAO_40621D).unwrap().coords.kilo_distance.unwrap();
+        assert!((28.0..30.0).contains(&distance), "{distance}");
+
+        // closer, still in range
+        airplanes.set_receiver_position((52.2, 3.9));
+        let coords = airplanes.get(ICAO_40621D).unwrap().coords;
+        assert!(coords.kilo_distance.unwrap() < 10.0);
+        assert!(coords.position.is_some());
+
+        // out of range
+        airplanes.set_receiver_position((50.0, 4.0));
+        let coords = airplanes.get(ICAO_40621D).unwrap().coords;
+        assert_eq!(coords.position, None);
+        assert_eq!(coords.kilo_distance, None);
+    }
+
+    #[test]
+    fn ground_state() {
+        let mut airplanes = Airplanes::builder().position((52.0, 4.0)).build();
+        let message = |line| sbs::SbsMessage::parse_line(line).unwrap().unwrap();
+        airplanes.apply_sbs(&message("MSG,2,1,1,40621D,1,,,,,,0,0,,52.3080,4.7640,,,,,,-1"));
+        // jitter of ~10 m
+        airplanes.apply_sbs(&message("MSG,2,1,1,40621D,1,,,,,,0,1,,52.3081,4.7640,,,,,,-1"));
+        airplanes.apply_sbs(&message("MSG,2,1,1,40621D,1,,,,,,0,0,,52.3080,4.7641,,,,,,-1"));
+        let state = airplanes.get(ICAO_40621D).unwrap();
+        assert_eq!(state.ground_state(), Some(GroundState::Parked));
+        assert_eq!(state.track.as_ref().map_or(0, Vec::len), 0);
+
+        airplanes.apply_sbs(&message("MSG,2,1,1,40621D,1,,,,,,0,15,,52.3090,4.7640,,,,,,-1"));
+        airplanes.apply_sbs(&message("MSG,2,1,1,40621D,1,,,,,,0,15,,52.3100,4.7640,,,,,,-1"));
+        let details = airplanes.aircraft_details(ICAO_40621D).unwrap();
+        assert_eq!(details.ground_state, Some(GroundState::Taxiing));
+        assert_eq!(details.track.map(|track| track.len()), Some(2));
+
+        airplanes.apply_sbs(&message("MSG,2,1,1,40621D,1,,,,,,0,90,,52.3200,4.7640,,,,,,-1"));
+        let state = airplanes.get(ICAO_40621D).unwrap();
+        assert_eq!(state.ground_state(), Some(GroundState::TakeoffRoll));
+
+        airplanes.apply_sbs(&message("MSG,3,1,1,40621D,1,,,,,,500,,,52.3300,4.7640,,,,,,0"));
+        let state = airplanes.get(ICAO_40621D).unwrap();
+        assert_eq!(state.ground_state(), None);
+    }
+
+    #[test]
+    fn creeping_ground_state() {
+        let mut airplanes = Airplanes::builder().position((52.0, 4.0)).build();
+        let message = |line| sbs::SbsMessage::parse_line(line).unwrap().unwrap();
+        airplanes.apply_sbs(&message("MSG,2,1,1,40621D,1,,,,,,0,0,,52.3080,4.7640,,,,,,-1"));
+        // ~22 m per fix without a speed, within the jitter of the previous position
+        for line in [
+            "MSG,2,1,1,40621D,1,,,,,,,,,52.3082,4.7640,,,,,,-1",
+            "MSG,2,1,1,40621D,1,,,,,,,,,52.3084,4.7640,,,,,,-1",
+            "MSG,2,1,1,40621D,1,,,,,,,,,52.3086,4.7640,,,,,,-1",
+        ] {
+            airplanes.apply_sbs(&message(line));
+        }
+        // taxiing once ~67 m from where it was parked
+        let state = airplanes.get(ICAO_40621D).unwrap();
+        assert_eq!(state.parked_position, None);
+        assert_eq!(state.track.as_ref().map_or(0, Vec::len), 1);
+
+        // stopped, the speed of the previous fixes is ignored
+        airplanes.apply_sbs(&message("MSG,2,1,1,40621D,1,,,,,,0,20,,52.3090,4.7640,,,,,,-1"));
+        airplanes.apply_sbs(&message("MSG,2,1,1,40621D,1,,,,,,,,,52.3090,4.7641,,,,,,-1"));
+        let state = airplanes.get(ICAO_40621D).unwrap();
+        assert_eq!(state.track.as_ref().map_or(0, Vec::len), 2);
+        assert!(state.parked_position.is_some());
+    }
+
+    #[test]
+    fn invalid_callsign() {
+        let message = |line| sbs::SbsMessage::parse_line(line).unwrap().unwrap();
+        let mut airplanes = Airplanes::default();
+        airplanes.apply_sbs(&message("MSG,1,1,1,40621D,1,,,,,KLM1023 ,,,,,,,,,,,"));
+        airplanes.apply_sbs(&message("MSG,1,1,1,40621D,1,,,,,KLM#023,,,,,,,,,,,"));
+        let state = airplanes.get(ICAO_40621D).unwrap();
+        assert_eq!(state.callsign.as_deref(), Some("KLM1023"));
+        assert_eq!(state.broadcast.anomalies.invalid_callsign, 1);
+
+        let mut airplanes = Airplanes::builder().keep_invalid_callsigns(true).build();
+        airplanes.apply_sbs(&message("MSG,1,1,1,40621D,1,,,,,KLM#023,,,,,,,,,,,"));
+        let state = airplanes.get(ICAO_40621D).unwrap();
+        assert_eq!(state.callsign.as_deref(), Some("KLM#023"));
+        assert_eq!(airplanes.anomalies().invalid_callsign, 0);
+    }
+
+    #[test]
+    fn apply_sbs() {
+        let mut airplanes = Airplanes::builder().position((52.0, 4.0)).max_range(100.0).build();
+        let message = |line| sbs::SbsMessage::parse_line(line).unwrap().unwrap();
+        let outcome = airplanes.apply_sbs(&message("MSG,1,1,1,40621D,1,,,,,KLM1023,,,,,,,,,,,"));
+        let added =
+            ActionOutcome { added: Added::Yes, callsign_updated: true, ..Default::default() };
+        assert_eq!(outcome, added);
+        let outcome = airplanes.apply_sbs(&message("MSG,1,1,1,40621D,1,,,,,KLM1023,,,,,,,,,,,"));
+        assert_eq!(outcome, ActionOutcome::default());
+        airplanes.apply_sbs(&message("MSG,4,1,1,40621D,1,,,,,,,420,90.0,,,-640,,,,,"));
+        let outcome =
+            airplanes.apply_sbs(&message("MSG,3,1,1,40621D,1,,,,,,38000,,,52.25,3.91,,,0,0,0,0"));
+        assert_eq!(outcome, ActionOutcome { position_updated: true, ..Default::default() });
+
+        let state = airplanes.get(ICAO_40621D).unwrap();
+        assert_eq!(state.callsign.as_deref(), Some("KLM1023"));
+        assert_eq!(state.speed, Some(420.0));
+        assert_eq!(state.heading_source, Some(DataSource::Sbs));
+        assert_eq!(state.vert_speed, Some(-640));
+        assert_eq!(state.num_messages, 4);
+        let details = airplanes.aircraft_details(ICAO_40621D).unwrap();
+        assert_eq!(details.altitude, 38000);
+        assert_eq!(details.decode, None);
+        assert!((28.0..30.0).contains(&details.kilo_distance), "{}", details.kilo_distance);
+
+        // out of range, the last position is kept
+        let outcome =
+            airplanes.apply_sbs(&message("MSG,3,1,1,40621D,1,,,,,,38000,,,50.0,10.0,,,0,0,0,0"));
+        assert_eq!(outcome.rejected_reason, Some(RejectReason::OutOfRange));
+        assert!(!outcome.position_updated);
+        let state = airplanes.get(ICAO_40621D).unwrap();
+        assert_eq!(state.coords.position.unwrap().latitude, 52.25);
+        assert_eq!(state.quality_history.rejected_positions, 1);
+
+        airplanes.apply_sbs(&message("MSG,3,1,1,40621D,1,,,,,,38100,,,52.26,3.92,,,0,0,0,0"));
+        let state = airplanes.get(ICAO_40621D).unwrap();
+        assert_eq!(state.coords.altitude(), Some(38100));
+        assert_eq!(state.track.as_ref().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn squawk_sources() {
+        let mut airplanes = Airplanes::new();
+        let message = |line| sbs::SbsMessage::parse_line(line).unwrap().unwrap();
+        airplanes.apply_sbs(&message("MSG,6,1,1,40621D,1,,,,,,,,,,,,7000,0,0,0,0"));
+        assert_eq!(airplanes.get(ICAO_40621D).unwrap().squawk, Some(0x7000));
+
+        // DF5 squawking 7700, same encoding as the SBS squawk
+        let bytes = [0x28, 0x00, 0x0a, 0xaa, 0x0a, 0xc6, 0xd4];
+        airplanes.action(Frame::from_bytes(&bytes).unwrap());
+        assert_eq!(airplanes.get(ICAO_40621D).unwrap().squawk, Some(squawk::EMERGENCY));
+
+        airplanes.apply_sbs(&message("MSG,6,1,1,40621D,1,,,,,,,,,,,,0356,0,0,0,0"));
+        let state = airplanes.get(ICAO_40621D).unwrap();
+        assert_eq!(state.squawk, Some(0x0356));
+    }
+
+    #[test]
+    fn set_max_range() {
+        let mut airplanes = Airplanes::builder().position((52.0, 4.0)).build();
+        add_position(&mut airplanes);
+        airplanes.set_max_range(Some(50.0));
+        assert!(airplanes.get(ICAO_40621D).unwrap().coords.position.is_some());
+        airplanes.set_max_range(Some(10.0));
+        assert!(airplanes.get(ICAO_40621D).unwrap().coords.position.is_none());
+    }
+
+    #[test]
+    fn action_outcome() {
+        const EVEN: [u8; 14] =
+            [0x8d, 0x40, 0x62, 0x1d, 0x58, 0xc3, 0x82, 0xd6, 0x90, 0xc8, 0xac, 0x28, 0x63, 0xa7];
+        const ODD: [u8; 14] =
+            [0x8d, 0x40, 0x62, 0x1d, 0x58, 0xc3, 0x86, 0x43, 0x5c, 0xc4, 0x12, 0x69, 0x2a, 0xd6];
+        let mut airplanes = Airplanes::builder().position((52.0, 4.0)).build();
+        let outcome = airplanes.action(Frame::from_bytes(&EVEN).unwrap());
+        assert_eq!(outcome, ActionOutcome::from(Added::Yes));
+        let outcome = airplanes.action(Frame::from_bytes(&ODD).unwrap());
+        assert_eq!(outcome, ActionOutcome { position_updated: true, ..ActionOutcome::default() });
+
+        // ADS-B identification "KLM1023 ", then the same callsign
+        let bytes =
+            [0x8d, 0x48, 0x40, 0xd6, 0x20, 0x2c, 0xc3, 0x71, 0xc3, 0x2c, 0xe0, 0x57, 0x60, 0x98];
+        let outcome = airplanes.action(Frame::from_bytes(&bytes).unwrap());
+        assert!(outcome.callsign_updated);
+        assert!(!airplanes.action(Frame::from_bytes(&bytes).unwrap()).callsign_updated);
+
+        // too far from the previous position
+        let state = airplanes.airplanes.get_mut(&ICAO_40621D).unwrap();
+        state.coords.position = Some(cpr::Position { latitude: 40.0, longitude: 4.0 });
+        let outcome = airplanes.action(Frame::from_bytes(&EVEN).unwrap());
+        assert_eq!(outcome.rejected_reason, Some(RejectReason::DistanceJump));
+        assert!(!outcome.position_updated);
+        assert_eq!(airplanes.get(ICAO_40621D).unwrap().coords.position, None);
+        let rejected = &airplanes.get(ICAO_40621D).unwrap().rejected_fixes;
+        assert_eq!((rejected.out_of_range, rejected.distance_jump), (0, 1));
+        assert!(rejected.recent[0].kilo_distance > MAX_AIRCRAFT_DISTANCE);
+        assert_eq!(airplanes.rejected_fixes(), rejected);
+
+        // out of the max range of the receiver
+        let mut airplanes = Airplanes::builder().position((52.0, 4.0)).max_range(10.0).build();
+        airplanes.action(Frame::from_bytes(&EVEN).unwrap());
+        let outcome = airplanes.action(Frame::from_bytes(&ODD).unwrap());
+        assert_eq!(outcome.rejected_reason, Some(RejectReason::OutOfRange));
+        let rejected = airplanes.rejected_fixes();
+        assert_eq!((rejected.out_of_range, rejected.distance_jump), (1, 0));
+        assert!((28.0..30.0).contains(&rejected.recent[0].kilo_distance));
+
+        // even frame too old for a global decode, without a previous position
+        #[cfg(feature = "std")]
+        {
+            let mut airplanes = Airplanes::builder().position((52.0, 4.0)).build();
+            airplanes.action(Frame::from_bytes(&EVEN).unwrap());
+            let stale = Instant::now() - Duration::from_secs(20);
+            airplanes.airplanes.get_mut(&ICAO_40621D).unwrap().coords.cpr_seen[0] = Some(stale);
+            let outcome = airplanes.action(Frame::from_bytes(&ODD).unwrap());
+            assert_eq!(outcome.rejected_reason, Some(RejectReason::StalePair));
+            assert!(airplanes.get(ICAO_40621D).unwrap().coords.altitudes[1].is_some());
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn cpr_decode() {
+        let mut airplanes = Airplanes::builder().position((52.0, 4.0)).build();
+        add_position(&mut airplanes);
+        let details = airplanes.aircraft_details(ICAO_40621D).unwrap();
+        assert_eq!(details.decode, Some(CprDecode::Global));
+        assert!(details.odd_age.unwrap() < 1.0);
+        assert!(details.even_age.unwrap() < 1.0);
+        let global = details.position;
+
+        // other frame too old for a global decode
+        let stale = Instant::now() - Duration::from_secs(20);
+        airplanes.airplanes.get_mut(&ICAO_40621D).unwrap().coords.cpr_seen = [Some(stale); 2];
+        let bytes =
+            [0x8d, 0x40, 0x62, 0x1d, 0x58, 0xc3, 0x86, 0x43, 0x5c, 0xc4, 0x12, 0x69, 0x2a, 0xd6];
+        airplanes.action(Frame::from_bytes(&bytes).unwrap());
+        let details = airplanes.aircraft_details(ICAO_40621D).unwrap();
+        assert_eq!(details.decode, Some(CprDecode::Local));
+        assert!((details.position.latitude - global.latitude).abs() < 1e-3);
+        assert!((details.position.longitude - global.longitude).abs() < 1e-3);
+        let (fresh, old) = match details.odd_age.unwrap() < details.even_age.unwrap() {
+            true => (details.odd_age, details.even_age),
+            false => (details.even_age, details.odd_age),
+        };
+        assert!(fresh.unwrap() < 1.0);
+        assert!(old.unwrap() >= 20.0);
+
+        // no local decode without a previous position
+        let coords = &mut airplanes.airplanes.get_mut(&ICAO_40621D).unwrap().coords;
+        coords.position = None;
+        coords.cpr_seen = [Some(stale); 2];
+        airplanes.action(Frame::from_bytes(&bytes).unwrap());
+        assert!(airplanes.get(ICAO_40621D).unwrap().coords.position.is_none());
+    }
+
+    #[test]
+    fn first_fix() {
+        let mut airplanes = Airplanes::builder().position((52.0, 4.0)).build();
+        add_position(&mut airplanes);
+        add_position(&mut airplanes);
+        let state = airplanes.get(ICAO_40621D).unwrap();
+        assert_eq!(state.first_fix.unwrap().messages, 2);
+        assert_eq!(state.num_messages, 4);
+
+        let stats = airplanes.first_fix_stats();
+        assert_eq!(stats.aircraft, 1);
+        assert_eq!(stats.max_messages, 2);
+        assert_eq!(stats.mean_messages(), Some(2.0));
+        #[cfg(feature = "std")]
+        assert!(stats.mean_elapsed().is_some());
+        assert_eq!(FirstFixStats::default().mean_messages(), None);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn seen() {
+        let mut airplanes = Airplanes::builder().position((52.0, 4.0)).build();
+        add_position(&mut airplanes);
+        let details = airplanes.aircraft_details(ICAO_40621D).unwrap();
+        assert!(details.seen < 1.0);
+        assert!(details.seen_pos.unwrap() < 1.0);
+
+        airplanes.set_max_range(Some(10.0));
+        let state = airplanes.get(ICAO_40621D).unwrap();
+        assert!(state.seen() < Duration::from_secs(1));
+        assert_eq!(state.seen_pos(), None);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn prune() {
+        let mut airplanes = Airplanes::default();
+        let now = Instant::now();
+        let (expired, kept) = (ICAO([0xab, 0xcd, 0x01]), ICAO([0xab, 0xcd, 0x02]));
+        for (icao, last_seen) in [(expired, 60), (kept, 59)] {
+            let (state, _) = airplanes.entry_or_insert(icao);
+            state.last_seen = now - Duration::from_secs(last_seen);
+            // the wall clock isn't used
+            state.last_time = SystemTime::UNIX_EPOCH;
+        }
+
+        // removed once seen at least `filter_time` ago
+        airplanes.prune(60);
+        assert!(airplanes.get(expired).is_none());
+        assert!(airplanes.get(kept).is_some());
+
+        airplanes.prune(0);
+        assert!(airplanes.is_empty());
+    }
+
+    #[test]
+    fn equipage() {
+        let mut airplanes = Airplanes::default();
+
+        // DF17 identification, airborne level 2 transponder
+        let bytes =
+            [0x8d, 0x40, 0x62, 0x1d, 0x20, 0x2c, 0xc3, 0x71, 0xc3, 0x2c, 0xe0, 0x57, 0x60, 0x98];
+        airplanes.action(Frame::from_bytes(&bytes).unwrap());
+        let state = airplanes.get(ICAO_40621D).unwrap();
+        assert_eq!(state.broadcast.capability, Some(Capability::AG_AIRBORNE));
+        assert_eq!(
+            state.equipage(),
+            Equipage { transponder_level: Some(TransponderLevel::Level2), adsb_version: None }
+        );
+
+        // DF11 all-call reply of the same aircraft
+        let bytes = [0x5e, 0x40, 0x62, 0x1d, 0x33, 0x93, 0x25];
+        airplanes.action(Frame::from_bytes(&bytes).unwrap());
+        let state = airplanes.get(ICAO_40621D).unwrap();
+        assert_eq!(state.broadcast.capability, Some(Capability::AG_UNCERTAIN2));
+
+        // DF11 all-call reply of an aircraft not tracked
+        let bytes = [0x5d, 0xab, 0x3d, 0x17, 0xd4, 0xba, 0x28];
+        airplanes.action(Frame::from_bytes(&bytes).unwrap());
+        assert!(airplanes.get(ICAO([0xab, 0x3d, 0x17])).is_none());
+
+        // DF17 operation status, ADS-B version 2
+        let bytes =
+            [0x8d, 0xac, 0xc0, 0x40, 0xf8, 0x21, 0x00, 0x02, 0x00, 0x4a, 0xb8, 0x56, 0x9c, 0x35];
+        airplanes.action(Frame::from_bytes(&bytes).unwrap());
+        let state = airplanes.get(ICAO([0xac, 0xc0, 0x40])).unwrap();
+        assert_eq!(state.equipage().adsb_version, Some(ADSBVersion::DOC9871AppendixC));
+
+        let fleet = airplanes.fleet_equipage();
+        assert_eq!(
+            fleet,
+            FleetEquipage {
+                aircraft: 2,
+                level_1: 0,
+                level_2: 2,
+                adsb_version: [0, 0, 1],
+                unknown_version: 1
+            }
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn estimated_heading() {
+        let mut state = AirplaneState::default();
+        let now = Instant::now();
+        assert_eq!(state.estimated_heading(now), None);
+
+        // turning right through north, from successive headings
+        state.update_heading(350.0, DataSource::Adsb);
+        state.heading_seen = Some(now - Duration::from_secs(2));
+        state.update_heading(4.0, DataSource::Adsb);
+        let turn_rate = state.turn_rate().unwrap();
+        assert!((turn_rate - 7.0).abs() < 0.1, "{turn_rate}");
+        let seen = state.heading_seen.unwrap();
+        let heading = state.estimated_heading(seen + Duration::from_secs(2)).unwrap();
+        assert!((heading - 18.0).abs() < 0.2, "{heading}");
+        // extrapolated for at most 15 seconds
+        let heading = state.estimated_heading(seen + Duration::from_secs(60)).unwrap();
+        assert!((heading - 109.0).abs() < 1.5, "{heading}");
+
+        // standard rate turn to the left from the roll angle, at 200 kt
+        state.ehs.roll_angle = Some(-28.8);
+        state.ehs.true_airspeed = Some(200);
+        let turn_rate = state.turn_rate().unwrap();
+        assert!((turn_rate + 3.0).abs() < 0.1, "{turn_rate}");
+
+        // headings too far apart
+        state.heading_seen = Some(now - Duration::from_secs(60));
+        state.update_heading(90.0, DataSource::Adsb);
+        assert_eq!(state.heading_rate, None);
+    }
+
+    #[test]
+    fn trend() {
+        let mut airplanes = Airplanes::default();
+        let bytes =
+            [0x8d, 0xa3, 0xd4, 0x25, 0x99, 0x25, 0x01, 0x29, 0x78, 0x04, 0x84, 0x71, 0x2c, 0x50];
+        airplanes.action(Frame::from_bytes(&bytes).unwrap());
+        let mut state = airplanes.get(ICAO([0xa3, 0xd4, 0x25])).unwrap().clone();
+        assert_eq!(state.smoothed_vert_speed, Some(0.0));
+        assert_eq!(state.trend(300.0), Some(Trend::Level));
+
+        // a single climbing rate doesn't change the trend
+        state.update_smoothed_vert_speed(800.0);
+        assert!((state.smoothed_vert_speed.unwrap() - 240.0).abs() < 0.01);
+        assert_eq!(state.trend(300.0), Some(Trend::Level));
+        state.update_smoothed_vert_speed(800.0);
+        assert_eq!(state.trend(300.0), Some(Trend::Climb));
+
+        state.smoothed_vert_speed = Some(-300.0);
+        assert_eq!(state.trend(300.0), Some(Trend::Descent));
+        assert_eq!(AirplaneState::default().trend(300.0), None);
+    }
+
+    #[test]
+    fn reply_information() {
+        let mut airplanes = Airplanes::default();
+        let icao = ICAO([0x0d, 0x09, 0x7e]);
+        // DF0 with RI:3, of an untracked aircraft
+        let bytes = [0x02, 0xe1, 0x9c, 0xb0, 0x25, 0x12, 0xc3];
+        airplanes.action(Frame::from_bytes(&bytes).unwrap());
+        assert!(airplanes.get(icao).is_none());
+
+        airplanes.incr_messages(icao);
+        airplanes.action(Frame::from_bytes(&bytes).unwrap());
+        let state = airplanes.get(icao).unwrap();
+        assert_eq!(state.reply_information, Some(ReplyInformation::AcasVerticalOnly));
+        assert_eq!(state.num_messages, 2);
+    }
+
+    #[test]
+    fn downlink() {
+        let mut airplanes = Airplanes::default();
+        let icao = ICAO([0xab, 0xef, 0x98]);
+        airplanes.incr_messages(icao);
+        // DF20 announcing a Comm-B broadcast, interrogated by IIS 15
+        let bytes =
+            [0xa2, 0x27, 0xed, 0x34, 0x17, 0x82, 0x65, 0x15, 0xbe, 0xbd, 0x01, 0x70, 0x76, 0x29];
+        airplanes.action(Frame::from_bytes(&bytes).unwrap());
+        let state = airplanes.get(icao).unwrap();
+        assert_eq!(
+            state.downlink,
+            Some(Downlink { request: DownlinkRequest::CommBBroadcastMsg1, interrogator: Some(15) })
+        );
+
+        let interrogation = airplanes.interrogation();
+        assert_eq!(interrogation.aircraft, 1);
+        assert_eq!(interrogation.broadcast, 1);
+        assert_eq!(interrogation.interrogators().collect::<Vec<_>>(), [(15, 1)]);
+    }
+
+    #[test]
+    fn comm_b_identification() {
+        let mut airplanes = Airplanes::default();
+        let icao = ICAO([0xaa, 0x6f, 0x80]);
+        // DF20 of aa6f80, BDS 2,0 "SWA545"
+        let bytes =
+            [0xa0, 0x00, 0x19, 0x10, 0x20, 0x4d, 0x70, 0x75, 0xd3, 0x58, 0x20, 0xc2, 0x5c, 0x0c];
+        // not seen in a CRC-verified frame yet
+        let outcome = airplanes.action(Frame::from_bytes(&bytes).unwrap());
+        assert_eq!(outcome.added, Added::No);
+        assert!(airplanes.is_empty());
+
+        // DF11 of aa6f80
+        let all_call = [0x5d, 0xaa, 0x6f, 0x80, 0xa1, 0x13, 0x47];
+        airplanes.action(Frame::from_bytes(&all_call).unwrap());
+        let outcome = airplanes.action(Frame::from_bytes(&bytes).unwrap());
+        assert_eq!(outcome.added, Added::Yes);
+        assert!(outcome.callsign_updated);
+        let state = airplanes.get(icao).unwrap();
+        assert_eq!(state.callsign.as_deref(), Some("SWA545"));
+        assert_eq!(state.callsign_source, Some(DataSource::CommB));
+        assert_eq!(state.num_messages, 1);
+
+        // other registers are counted, without changing the aircraft
+        let mut empty = [0xa0, 0x00, 0x19, 0x10, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+        let parity = adsb_deku::crc::modes_checksum(&empty, 112).unwrap() ^ 0x00aa_6f80;
+        empty[11..].copy_from_slice(&parity.to_be_bytes()[1..]);
+        let outcome = airplanes.action(Frame::from_bytes(&empty).unwrap());
+        assert_eq!(outcome, ActionOutcome::default());
+        let state = airplanes.get(icao).unwrap();
+        assert_eq!((state.callsign.as_deref(), state.num_messages), (Some("SWA545"), 2));
+
+        // corrupted reply, the parity gives another address
+        let mut corrupted = bytes;
+        corrupted[6] ^= 0x01;
+        let outcome = airplanes.action(Frame::from_bytes(&corrupted).unwrap());
+        assert_eq!(outcome.added, Added::No);
+        assert_eq!(airplanes.len(), 1);
+    }
+
+    #[test]
+    fn all_call_interrogator() {
+        let mut airplanes = Airplanes::default();
+        // DF11 of aa6f80 replying to the interrogator II 5
+        let mut bytes = [0x5d, 0xaa, 0x6f, 0x80, 0xa1, 0x13, 0x42];
+        let frame = Frame::from_bytes(&bytes).unwrap();
+        assert_eq!(frame.crc, 5);
+        airplanes.action(frame);
+        assert_eq!(airplanes.icao_cache().len(), 1);
+
+        // corrupted, outside of the interrogator code
+        let mut airplanes = Airplanes::default();
+        bytes[5] ^= 0x01;
+        airplanes.action(Frame::from_bytes(&bytes).unwrap());
+        assert!(airplanes.icao_cache().is_empty());
+    }
+
+    #[test]
+    fn selected_vertical_intention() {
+        let mut airplanes = Airplanes::default();
+        let icao = ICAO([0x42, 0x43, 0xd0]);
+        let report = SelectedVerticalIntention {
+            mcp_selected_altitude: Some(3008),
+            fms_selected_altitude: None,
+            barometric_pressure_setting: Some(1020.0),
+        };
+        // not tracked yet
+        airplanes.add_comm_b(icao, &BDS::SelectedVerticalIntention(report));
+        assert!(airplanes.get(icao).is_none());
+
+        airplanes.incr_messages(icao);
+        airplanes.add_comm_b(icao, &BDS::SelectedVerticalIntention(report));
+        // the latest report replaces all the values, a value not in the reply isn't kept
+        let report = SelectedVerticalIntention {
+            mcp_selected_altitude: Some(5008),
+            barometric_pressure_setting: None,
+            ..report
+        };
+        airplanes.add_comm_b(icao, &BDS::SelectedVerticalIntention(report));
+        let ehs = airplanes.get(icao).unwrap().ehs;
+        assert_eq!(ehs.mcp_selected_altitude, Some(5008));
+        assert_eq!(ehs.fms_selected_altitude, None);
+        assert_eq!(ehs.barometric_pressure_setting, None);
+    }
+
+    #[test]
+    fn ident_change() {
+        let mut airplanes = Airplanes::default();
+        let icao = ICAO([0x48, 0x40, 0xd6]);
+        // ADS-B identification "KLM1023 "
+        let bytes =
+            [0x8d, 0x48, 0x40, 0xd6, 0x20, 0x2c, 0xc3, 0x71, 0xc3, 0x2c, 0xe0, 0x57, 0x60, 0x98];
+        airplanes.action(Frame::from_bytes(&bytes).unwrap());
+        airplanes.add_comm_b(icao, &BDS::AircraftIdentification("KLM1023".to_string()));
+        assert_eq!(airplanes.ident_changes().len(), 0);
+
+        // the flight number was only changed in the transponder
+        airplanes.action(Frame::from_bytes(&bytes).unwrap());
+        airplanes.add_comm_b(icao, &BDS::AircraftIdentification("KLM1024".to_string()));
+        let state = airplanes.get(icao).unwrap();
+        assert_eq!(state.callsign.as_deref(), Some("KLM1024"));
+        assert_eq!(state.callsign_source, Some(DataSource::CommB));
+        assert_eq!(
+            airplanes.drain_ident_changes().collect::<Vec<_>>(),
+            [IdentChange {
+                icao,
+                previous: "KLM1023".to_string(),
+                previous_source: DataSource::Adsb,
+                callsign: "KLM1024".to_string(),
+                source: DataSource::CommB,
+            }]
+        );
+    }
+
+    #[test]
+    fn heading_speed_priority() {
+        let mut airplanes = Airplanes::default();
+        let icao = ICAO([0x48, 0x50, 0x20]);
+        let report = TrackAndTurnReport {
+            roll_angle: Some(-2.0),
+            true_track: Some(90.0),
+            ground_speed: Some(420),
+            track_angle_rate: None,
+            true_airspeed: Some(410),
+        };
+        airplanes.incr_messages(icao);
+        airplanes.add_comm_b(icao, &BDS::TrackAndTurnReport(report));
+        let state = airplanes.get(icao).unwrap();
+        assert_eq!((state.heading, state.speed), (Some(90.0), Some(420.0)));
+        assert_eq!(state.heading_source, Some(DataSource::CommB));
+        assert_eq!(state.speed_source, Some(DataSource::CommB));
+
+        // ADS-B velocity, heading 182.88 and 159 kt, replaces the Comm-B values
+        let velocity =
+            [0x8d, 0x48, 0x50, 0x20, 0x99, 0x44, 0x09, 0x94, 0x08, 0x38, 0x17, 0x5b, 0x28, 0x4f];
+        airplanes.action(Frame::from_bytes(&velocity).unwrap());
+        let state = airplanes.get(icao).unwrap();
+        let (heading, speed) = (state.heading.unwrap(), state.speed.unwrap());
+        assert!((heading - 182.88).abs() < 0.01, "{heading}");
+        assert_eq!(speed as u16, 159);
+        assert_eq!(state.heading_source, Some(DataSource::Adsb));
+        assert_eq!(state.speed_source, Some(DataSource::Adsb));
+
+        // and isn't replaced by a later Comm-B reply, only the EHS values are
+        airplanes.add_comm_b(icao, &BDS::TrackAndTurnReport(report));
+        let state = airplanes.get(icao).unwrap();
+        assert_eq!((state.heading, state.speed), (Some(heading), Some(speed)));
+        assert_eq!(state.heading_source, Some(DataSource::Adsb));
+        assert_eq!(state.speed_source, Some(DataSource::Adsb));
+        assert_eq!(state.ehs.true_track, Some(90.0));
+        assert_eq!(state.ehs.ground_speed, Some(420));
+
+        // without a roll angle in the latest report
+        let report = TrackAndTurnReport { roll_angle: None, ..report };
+        airplanes.add_comm_b(icao, &BDS::TrackAndTurnReport(report));
+        let state = airplanes.get(icao).unwrap();
+        assert_eq!(state.ehs.roll_angle, None);
+        assert_eq!(state.ehs.true_airspeed, Some(410));
+    }
+
+    #[test]
+    fn no_position_altitude() {
+        let mut airplanes = Airplanes::default();
+        let icao = ICAO([0x48, 0x40, 0xd6]);
+        // TC=0 at 38000 ft
+        let bytes =
+            [0x8d, 0x48, 0x40, 0xd6, 0x02, 0xc3, 0x80, 0x00, 0x00, 0x00, 0x00, 0x56, 0xfd, 0x92];
+        assert_eq!(airplanes.action(Frame::from_bytes(&bytes).unwrap()).added, Added::Yes);
+        let state = airplanes.get(icao).unwrap();
+        assert_eq!(state.coords.altitude(), Some(38000));
+        assert_eq!(state.coords.position, None);
+
+        // a position replaces the altitude without a position
+        let (state, _) = airplanes.entry_or_insert(icao);
+        state.coords.altitudes[0] = Some(Altitude { alt: Some(37000), ..Altitude::default() });
+        assert_eq!(state.coords.altitude(), Some(38000));
+        state.coords.no_position_altitude = None;
+        assert_eq!(state.coords.altitude(), Some(37000));
+    }
+
+    #[test]
+    fn target_state_qnh() {
+        let mut airplanes = Airplanes::default();
+        // QNH 1012.8 hPa
+        let bytes =
+            [0x8d, 0xa0, 0x8f, 0x94, 0xea, 0x1b, 0x78, 0x5e, 0x8f, 0x3c, 0x08, 0x8a, 0xb4, 0x67];
+        assert_eq!(airplanes.action(Frame::from_bytes(&bytes).unwrap()).added, Added::Yes);
+        let qnh = airplanes.get(ICAO([0xa0, 0x8f, 0x94])).unwrap().qnh.unwrap();
+        assert!((qnh - 1012.8).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn altitude_source() {
+        let mut airplanes = Airplanes::default();
+        let (state, _) = airplanes.entry_or_insert(ICAO([0xab, 0xcd, 0x01]));
+        assert_eq!(state.altitude(AltitudeSource::GnssCorrected), None);
+
+        state.coords.sbs_altitude = Some(36000);
+        state.gnss_baro_diff = Some(-200);
+        assert_eq!(state.altitude(AltitudeSource::Baro), Some(36000));
+        assert_eq!(state.altitude(AltitudeSource::Gnss), Some(36000));
+        assert_eq!(state.altitude(AltitudeSource::GnssCorrected), Some(35800));
+
+        // the last position has a GNSS altitude
+        state.coords.altitudes[0] =
+            Some(Altitude { tc: 20, alt: Some(37500), ..Altitude::default() });
+        state.coords.altitudes[1] =
+            Some(Altitude { tc: 11, alt: Some(37000), ..Altitude::default() });
+        assert_eq!(state.altitude(AltitudeSource::Baro), Some(37000));
+        assert_eq!(state.altitude(AltitudeSource::Gnss), Some(37500));
+        assert_eq!(state.altitude(AltitudeSource::GnssCorrected), Some(37500));
+    }
+
+    #[test]
+    fn closest_highest_fastest() {
+        let mut airplanes = Airplanes::default();
+        assert!(airplanes.closest().is_none());
+        let icaos = [ICAO([0xab, 0xcd, 0x01]), ICAO([0xab, 0xcd, 0x02]), ICAO([0xab, 0xcd, 0x03])];
+        for (i, (kilo_distance, altitude, speed)) in
+            [(20.0, 35000, 450.0), (5.0, 3000, 180.0), (1.0, 0, 20.0)].into_iter().enumerate()
+        {
+            let (state, _) = airplanes.entry_or_insert(icaos[i]);
+            state.coords.kilo_distance = Some(kilo_distance);
+            state.coords.sbs_altitude = Some(altitude);
+            state.speed = Some(speed);
+        }
+        // taxiing
+        airplanes.entry_or_insert(icaos[2]).0.on_ground = Some(true);
+
+        assert_eq!(airplanes.closest().map(|(icao, _)| icao), Some(icaos[1]));
+        assert_eq!(airplanes.highest().map(|(icao, _)| icao), Some(icaos[0]));
+        assert_eq!(airplanes.fastest().map(|(icao, _)| icao), Some(icaos[0]));
     }
 }
@@ -0,0 +1,235 @@
+//! Estimation of the receiver location from the positions of the received aircraft
+//!
+//! An aircraft at an altitude can only be received within its radio horizon, see
+//! [`geo::radio_horizon`], so each decoded position is a disk that contains the receiver: small
+//! for low aircraft, large for high ones. The estimate is the point with the smallest maximum
+//! distance to the positions, relative to their radio horizon. This is the receiver when the
+//! coverage is about the same in all directions; terrain blocking some directions moves the
+//! estimate away from them.
+//!
+//! ```rust
+//! use rsadsb_common::geo;
+//! use rsadsb_common::locate::ReceiverLocator;
+//!
+//! let receiver = (35.0, -80.0);
+//! let mut locator = ReceiverLocator::new();
+//! for bearing in 0..360 {
+//!     let altitude = 5000.0 + f64::from(bearing) * 100.0;
+//!     let kilo_distance = geo::radio_horizon(altitude) * 0.6;
+//!     let position = geo::destination(receiver, f64::from(bearing), kilo_distance);
+//!     locator.add(position, altitude as i32);
+//! }
+//! let estimate = locator.estimate().unwrap();
+//! assert!(geo::distance(estimate.position, receiver) < 25.0);
+//! ```
+
+#[cfg(feature = "alloc")]
+use alloc::{collections::BTreeMap, vec::Vec};
+#[cfg(feature = "alloc")]
+use core::{
+    clone::Clone,
+    cmp::PartialEq,
+    default::Default,
+    fmt::Debug,
+    iter::Iterator,
+    marker::Copy,
+    option::Option::{self, None, Some},
+    prelude::rust_2021::derive,
+};
+
+use adsb_deku::ICAO;
+
+use crate::{geo, Airplanes};
+
+/// Size (degrees) of the cells keeping the lowest position, bounding the memory of a day of
+/// positions
+const CELL_SIZE: f64 = 0.05;
+
+/// Cells needed for an estimate
+pub const MIN_SAMPLES: usize = 50;
+
+/// Lowest altitude (ft) of a sample, so the radio horizon is never 0
+const MIN_ALTITUDE: f64 = 100.0;
+
+/// Iterations of the minimax search
+const ITERATIONS: usize = 2000;
+
+/// Fraction of the samples furthest from the first estimate dropped as bad positions
+const OUTLIERS: f64 = 0.01;
+
+/// Kilometers in a degree of latitude
+const KM_PER_DEGREE: f64 = 111.195;
+
+/// Receiver location from [`ReceiverLocator::estimate`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ReceiverEstimate {
+    /// (latitude, longitude)
+    pub position: (f64, f64),
+    /// Cells with a position used for the estimate
+    pub samples: usize,
+    /// Distance (km) to the furthest position used
+    pub range: f64,
+}
+
+/// Lowest position received in a cell
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct Sample {
+    position: (f64, f64),
+    /// ft
+    altitude: f64,
+}
+
+/// Decoded positions kept for estimating the receiver location, see [`self`]
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ReceiverLocator {
+    cells: BTreeMap<(i32, i32), Sample>,
+}
+
+impl ReceiverLocator {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add the (latitude, longitude) `position` of an aircraft at `altitude` (ft)
+    pub fn add(&mut self, position: (f64, f64), altitude: i32) {
+        let sample = Sample { position, altitude: f64::from(altitude).max(MIN_ALTITUDE) };
+        let lowest = self.cells.entry(cell(position)).or_insert(sample);
+        if sample.altitude < lowest.altitude {
+            *lowest = sample;
+        }
+    }
+
+    /// Add the current position of `icao`, if any
+    pub fn update(&mut self, airplanes: &Airplanes, icao: ICAO) {
+        let Some(state) = airplanes.get(icao) else {
+            return;
+        };
+        if let (Some(position), Some(altitude)) = (state.coords.position, state.coords.altitude()) {
+            self.add((position.latitude, position.longitude), altitude);
+        }
+    }
+
+    /// Cells with a position
+    #[must_use]
+    pub fn samples(&self) -> usize {
+        self.cells.len()
+    }
+
+    /// Estimate the receiver location, `None` with less than [`MIN_SAMPLES`] cells
+    #[must_use]
+    pub fn estimate(&self) -> Option<ReceiverEstimate> {
+        if self.cells.len() < MIN_SAMPLES {
+            return None;
+        }
+
+        // flat (x, y) km around the mean position, precise enough within the radio horizon
+        let count = self.cells.len() as f64;
+        let origin = self.cells.values().fold((0.0, 0.0), |(lat, long), sample| {
+            (lat + sample.position.0 / count, long + sample.position.1 / count)
+        });
+        let scale = libm::cos(origin.0.to_radians()) * KM_PER_DEGREE;
+        let mut points: Vec<_> = self
+            .cells
+            .values()
+            .map(|sample| {
+                let long = (sample.position.1 - origin.1 + 540.0) % 360.0 - 180.0;
+                let x = long * scale;
+                let y = (sample.position.0 - origin.0) * KM_PER_DEGREE;
+                (x, y, geo::radio_horizon(sample.altitude))
+            })
+            .collect();
+
+        // drop the positions furthest from a first estimate, such as wrong CPR decodes
+        let first = minimax(&points);
+        points
+            .sort_by(|a, b| relative_distance(first, *a).total_cmp(&relative_distance(first, *b)));
+        let outliers = (count * OUTLIERS) as usize;
+        points.truncate(points.len() - outliers);
+        let (x, y) = minimax(&points);
+
+        let position = (origin.0 + y / KM_PER_DEGREE, origin.1 + x / scale);
+        let range =
+            points.iter().map(|point| libm::hypot(point.0 - x, point.1 - y)).fold(0.0, f64::max);
+        Some(ReceiverEstimate { position, samples: self.cells.len(), range })
+    }
+}
+
+/// Cell of `position`
+fn cell(position: (f64, f64)) -> (i32, i32) {
+    (libm::floor(position.0 / CELL_SIZE) as i32, libm::floor(position.1 / CELL_SIZE) as i32)
+}
+
+/// Distance from `(x, y)` to `point`, relative to the radio horizon of `point`
+fn relative_distance((x, y): (f64, f64), point: (f64, f64, f64)) -> f64 {
+    libm::hypot(point.0 - x, point.1 - y) / point.2
+}
+
+/// Point with the smallest maximum [`relative_distance`] to `points`
+///
+/// This moves towards the furthest point by a decreasing fraction of the distance, see
+/// <https://en.wikipedia.org/wiki/Bounding_sphere#Core-set_based_approximation>.
+fn minimax(points: &[(f64, f64, f64)]) -> (f64, f64) {
+    let mut center = (0.0, 0.0);
+    for i in 0..ITERATIONS {
+        let furthest = points
+            .iter()
+            .copied()
+            .max_by(|a, b| relative_distance(center, *a).total_cmp(&relative_distance(center, *b)));
+        let Some((x, y, _)) = furthest else {
+            break;
+        };
+        let step = 1.0 / (i + 2) as f64;
+        center = (center.0 + (x - center.0) * step, center.1 + (y - center.1) * step);
+    }
+    center
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RECEIVER: (f64, f64) = (50.0, 8.5);
+
+    /// positions around `RECEIVER`, at a fraction of the radio horizon
+    fn locator(bearings: impl Iterator<Item = u32>) -> ReceiverLocator {
+        let mut locator = ReceiverLocator::new();
+        // low aircraft are near the receiver, high ones are far
+        for (i, bearing) in bearings.enumerate() {
+            let altitude = [2000, 8000, 20000, 38000][i % 4];
+            let fraction = [0.9, 0.5, 0.7, 0.3][(i / 4) % 4];
+            let kilo_distance = geo::radio_horizon(f64::from(altitude)) * fraction;
+            let position = geo::destination(RECEIVER, f64::from(bearing), kilo_distance);
+            locator.add(position, altitude);
+        }
+        locator
+    }
+
+    #[test]
+    fn surrounding_traffic() {
+        let mut locator = locator((0..360).step_by(3));
+        // wrong CPR decode
+        locator.add((10.0, 8.0), 1000);
+        let estimate = locator.estimate().unwrap();
+        let error = geo::distance(estimate.position, RECEIVER);
+        assert!(error < 5.0, "{error}");
+        assert!(estimate.range < geo::radio_horizon(38000.0), "{}", estimate.range);
+        assert_eq!(estimate.samples, 121);
+    }
+
+    #[test]
+    fn minimum_samples() {
+        let mut locator = locator((0..360).step_by(10));
+        assert_eq!(locator.samples(), 36);
+        assert_eq!(locator.estimate(), None);
+        // the lowest position of a cell is kept
+        locator.add(RECEIVER, 30000);
+        locator.add((RECEIVER.0 + 0.001, RECEIVER.1), 3000);
+        locator.add(RECEIVER, 10000);
+        assert_eq!(locator.samples(), 37);
+        assert_eq!(locator.cells[&cell(RECEIVER)].altitude, 3000.0);
+    }
+}
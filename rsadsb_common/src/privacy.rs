@@ -0,0 +1,430 @@
+//! Filtering of aircraft addresses before they leave the receiver
+//!
+//! A [`PrivacyFilter`] drops or anonymizes configured addresses, such as the aircraft of the
+//! operator, before they reach exports, servers or recordings. The filter is applied to what is
+//! sent out, not to what is tracked: the same [`Airplanes`] can still show all aircraft locally.
+//!
+//! ```rust
+//! use adsb_deku::{Frame, ICAO};
+//! use rsadsb_common::privacy::{PrivacyAction, PrivacyFilter};
+//! use rsadsb_common::Airplanes;
+//!
+//! let mut filter = PrivacyFilter::new(PrivacyAction::Drop);
+//! filter.block(ICAO([0xa0, 0x8f, 0x94]));
+//!
+//! let bytes = [0x8d, 0xa0, 0x8f, 0x94, 0x23, 0x3d, 0x17, 0x75, 0xe7, 0xe9, 0xe0, 0x00, 0x00, 0x00];
+//! let frame = Frame::from_bytes(&bytes).unwrap();
+//! assert!(!filter.allows_frame(&frame));
+//!
+//! let mut airplanes = Airplanes::new();
+//! airplanes.action(frame);
+//! assert_eq!(airplanes.len(), 1);
+//! assert_eq!(filter.apply(&airplanes).len(), 0);
+//! ```
+//!
+//! Exports of the [`Airplanes`], such as the `aircraft.json` of readsb, are filtered by exporting
+//! the copy returned by [`PrivacyFilter::apply`], or the aircraft of [`PrivacyFilter::states`].
+
+#[cfg(feature = "alloc")]
+use alloc::borrow::Cow;
+#[cfg(feature = "alloc")]
+use alloc::collections::BTreeSet;
+#[cfg(feature = "alloc")]
+use core::{
+    clone::Clone,
+    cmp::{Eq, PartialEq},
+    fmt::Debug,
+    iter::Iterator,
+    marker::Copy,
+    option::Option::{self, None, Some},
+    prelude::rust_2021::derive,
+};
+
+use adsb_deku::{Frame, DF, ICAO};
+
+use crate::icao_cache::IcaoCache;
+use crate::ident::IdentChanges;
+use crate::sbs::SbsMessage;
+use crate::{AirplaneState, Airplanes};
+
+/// FNV-1a offset basis and prime, for [`PrivacyFilter::pseudonym`]
+const FNV_OFFSET: u32 = 0x811c_9dc5;
+const FNV_PRIME: u32 = 0x0100_0193;
+
+/// First address of the pseudonyms, up to `FFFFFF`
+///
+/// The block `F00000` to `FFFFFF` is reserved by ICAO, and the addresses from `F10000` aren't
+/// assigned to any aircraft, such that a pseudonym doesn't impersonate a real aircraft.
+const PSEUDONYM_START: u32 = 0x00f1_0000;
+
+/// What is done with a filtered address
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PrivacyAction {
+    /// Remove the aircraft
+    Drop,
+    /// Replace the address with [`PrivacyFilter::pseudonym`], and remove the callsign and squawk
+    Anonymize,
+}
+
+/// Blocklist or allowlist of aircraft addresses, see [`self`]
+///
+/// An address is filtered if it is blocked, or if an allowlist is set and it is not allowed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PrivacyFilter {
+    action: PrivacyAction,
+    blocked: BTreeSet<ICAO>,
+    /// only these addresses pass, if set
+    allowed: Option<BTreeSet<ICAO>>,
+    salt: u64,
+}
+
+impl PrivacyFilter {
+    /// Filter without any blocked address nor allowlist, passing all aircraft
+    ///
+    /// With `std`, the salt of [`Self::pseudonym`] is random, such that the pseudonyms change
+    /// on each run. Without, the salt is 0 and must be set with [`Self::with_salt`].
+    #[must_use]
+    pub fn new(action: PrivacyAction) -> Self {
+        Self { action, blocked: BTreeSet::new(), allowed: None, salt: random_salt() }
+    }
+
+    /// Secret salt of [`Self::pseudonym`], for pseudonyms that are stable between runs
+    ///
+    /// Since there are only 2^24 addresses, a pseudonym with a known salt can be reversed by
+    /// trying them all.
+    #[must_use]
+    pub fn with_salt(mut self, salt: u64) -> Self {
+        self.salt = salt;
+        self
+    }
+
+    #[must_use]
+    pub fn action(&self) -> PrivacyAction {
+        self.action
+    }
+
+    /// Filter `icao`
+    pub fn block(&mut self, icao: ICAO) {
+        self.blocked.insert(icao);
+    }
+
+    /// Only pass `icao` and the other allowed addresses
+    pub fn allow(&mut self, icao: ICAO) {
+        self.allowed.get_or_insert_with(BTreeSet::new).insert(icao);
+    }
+
+    /// Return true if all addresses pass
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.blocked.is_empty() && self.allowed.is_none()
+    }
+
+    /// Return true if `icao` is blocked or not allowed
+    #[must_use]
+    pub fn is_filtered(&self, icao: ICAO) -> bool {
+        self.blocked.contains(&icao) || self.allowed.as_ref().is_some_and(|a| !a.contains(&icao))
+    }
+
+    /// Address to send out for `icao`, `None` if dropped
+    #[must_use]
+    pub fn map_icao(&self, icao: ICAO) -> Option<ICAO> {
+        if !self.is_filtered(icao) {
+            return Some(icao);
+        }
+        match self.action {
+            PrivacyAction::Drop => None,
+            PrivacyAction::Anonymize => Some(self.pseudonym(icao)),
+        }
+    }
+
+    /// Stable replacement address of `icao`, from a hash of the salt and address
+    ///
+    /// The pseudonyms are in the unassigned addresses `F10000` to `FFFFFF`.
+    #[must_use]
+    pub fn pseudonym(&self, icao: ICAO) -> ICAO {
+        let hash = self
+            .salt
+            .to_be_bytes()
+            .iter()
+            .chain(&icao.0)
+            .fold(FNV_OFFSET, |hash, byte| (hash ^ u32::from(*byte)).wrapping_mul(FNV_PRIME));
+        ICAO::from(PSEUDONYM_START + hash % (0x0100_0000 - PSEUDONYM_START))
+    }
+
+    /// `message` to send out, with the pseudonym address and without callsign and squawk if
+    /// anonymized, `None` if dropped
+    #[must_use]
+    pub fn apply_sbs(&self, mut message: SbsMessage) -> Option<SbsMessage> {
+        let icao = self.map_icao(message.icao)?;
+        if icao != message.icao {
+            message.icao = icao;
+            message.callsign = None;
+            message.squawk = None;
+        }
+        Some(message)
+    }
+
+    /// Return true if `frame` can be sent out, such as to a recording or raw output
+    ///
+    /// Since the address can't be replaced in the bytes of a frame, frames of filtered aircraft
+    /// are never allowed, even with [`PrivacyAction::Anonymize`]. For DF0/4/5/16/20/21, the
+    /// address is the CRC residual, see [`Frame::icao_from_parity`]. With an allowlist, frames
+    /// without an address are not allowed.
+    #[must_use]
+    pub fn allows_frame(&self, frame: &Frame) -> bool {
+        match frame_icao(frame) {
+            Some(icao) => !self.is_filtered(icao),
+            None => self.allows_unknown(),
+        }
+    }
+
+    /// Return true if data without a known address can be sent out, such as frames that don't
+    /// decode
+    ///
+    /// This is only the case without an allowlist.
+    #[must_use]
+    pub fn allows_unknown(&self) -> bool {
+        self.allowed.is_none()
+    }
+
+    /// Copy of `airplanes` to send out, with filtered aircraft dropped or anonymized
+    ///
+    /// Two aircraft are never merged: an anonymized aircraft whose pseudonym is already used, by
+    /// a tracked address or the pseudonym of another aircraft, is dropped.
+    ///
+    /// The addresses of [`Airplanes::icao_cache`] and the callsigns of
+    /// [`Airplanes::ident_changes`] are not copied, since they may be of filtered aircraft.
+    #[must_use]
+    pub fn apply(&self, airplanes: &Airplanes) -> Airplanes {
+        let mut filtered = airplanes.clone();
+        if self.is_empty() {
+            return filtered;
+        }
+        filtered.icao_cache = IcaoCache::default();
+        filtered.ident_changes = IdentChanges::default();
+        filtered.airplanes =
+            self.states(airplanes).map(|(icao, state)| (icao, state.into_owned())).collect();
+        filtered
+    }
+
+    /// Aircraft of [`Self::apply`], without copying the aircraft that aren't anonymized
+    ///
+    /// The aircraft are in the order of their tracked address, not of the pseudonyms.
+    /// Such as for exports written often, where a copy of all the aircraft each time is too
+    /// slow.
+    pub fn states<'a>(
+        &'a self,
+        airplanes: &'a Airplanes,
+    ) -> impl Iterator<Item = (ICAO, Cow<'a, AirplaneState>)> + 'a {
+        let mut pseudonyms = BTreeSet::new();
+        airplanes.iter().filter_map(move |(icao, state)| {
+            let mapped = self.map_icao(*icao)?;
+            if mapped == *icao {
+                return Some((mapped, Cow::Borrowed(state)));
+            }
+            // the pseudonym of a tracked address sent out, or of another anonymized aircraft
+            let sent = airplanes.get(mapped).is_some() && self.map_icao(mapped) == Some(mapped);
+            if sent || !pseudonyms.insert(mapped) {
+                return None;
+            }
+            let mut state = state.clone();
+            state.callsign = None;
+            state.callsign_source = None;
+            state.broadcast.previous_callsign = None;
+            state.squawk = None;
+            Some((mapped, Cow::Owned(state)))
+        })
+    }
+}
+
+/// Salt of [`PrivacyFilter::new`], from the random keys of the `HashMap` of `std`
+#[cfg(feature = "std")]
+fn random_salt() -> u64 {
+    use std::hash::{BuildHasher, Hasher};
+
+    std::collections::hash_map::RandomState::new().build_hasher().finish()
+}
+
+#[cfg(not(feature = "std"))]
+fn random_salt() -> u64 {
+    0
+}
+
+/// Address of the aircraft sending `frame`, `None` if the frame has no address
+fn frame_icao(frame: &Frame) -> Option<ICAO> {
+    if let Some(icao) = frame.announced_icao() {
+        return Some(icao);
+    }
+    match &frame.df {
+        DF::ShortAirAirSurveillance { .. }
+        | DF::SurveillanceAltitudeReply { .. }
+        | DF::SurveillanceIdentityReply { .. }
+        | DF::LongAirAir { .. }
+        | DF::CommBAltitudeReply { .. }
+        | DF::CommBIdentityReply { .. } => Some(ICAO::from(frame.crc)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{format, vec, vec::Vec};
+
+    use super::*;
+
+    const ICAO_A: ICAO = ICAO([0x40, 0x62, 0x1d]);
+    const ICAO_B: ICAO = ICAO([0xa0, 0x8f, 0x94]);
+
+    /// identification of `ICAO_A`
+    const IDENTIFICATION: [u8; 14] =
+        [0x8d, 0x40, 0x62, 0x1d, 0x20, 0x2c, 0xc3, 0x71, 0xc3, 0x2c, 0xe0, 0x57, 0x60, 0x98];
+
+    fn airplanes() -> Airplanes {
+        let mut airplanes = Airplanes::new();
+        for bytes in [
+            IDENTIFICATION,
+            [0x8d, 0xa0, 0x8f, 0x94, 0x23, 0x3d, 0x17, 0x75, 0xe7, 0xe9, 0xe0, 0x00, 0x00, 0x00],
+        ] {
+            airplanes.action(Frame::from_bytes(&bytes).unwrap());
+        }
+        airplanes
+    }
+
+    #[test]
+    fn blocklist() {
+        let airplanes = airplanes();
+        let mut filter = PrivacyFilter::new(PrivacyAction::Drop);
+        assert!(filter.is_empty());
+        assert_eq!(filter.apply(&airplanes).len(), 2);
+
+        filter.block(ICAO_A);
+        let filtered = filter.apply(&airplanes);
+        assert_eq!(filtered.keys().copied().collect::<Vec<_>>(), vec![ICAO_B]);
+        // still tracked locally
+        assert!(airplanes.get(ICAO_A).is_some());
+
+        assert!(!filter.allows_frame(&Frame::from_bytes(&IDENTIFICATION).unwrap()));
+        // DF18 identification of `ICAO_A`, the address is in the AA field
+        let bytes =
+            [0x90, 0x40, 0x62, 0x1d, 0x20, 0x2c, 0xc3, 0x71, 0xc3, 0x2c, 0xe0, 0x53, 0x0a, 0xa9];
+        assert!(!filter.allows_frame(&Frame::from_bytes(&bytes).unwrap()));
+
+        // DF20 of 4243d0, the address is in the parity
+        let bytes =
+            [0xa0, 0x00, 0x02, 0x9c, 0x85, 0xe4, 0x2f, 0x31, 0x30, 0x00, 0x00, 0x70, 0x47, 0xd3];
+        let frame = Frame::from_bytes(&bytes).unwrap();
+        assert!(filter.allows_frame(&frame));
+        filter.block(ICAO([0x42, 0x43, 0xd0]));
+        assert!(!filter.allows_frame(&frame));
+        assert!(filter.allows_unknown());
+    }
+
+    #[test]
+    fn allowlist() {
+        let mut filter = PrivacyFilter::new(PrivacyAction::Drop);
+        filter.allow(ICAO_B);
+        assert!(filter.is_filtered(ICAO_A));
+        assert!(!filter.is_filtered(ICAO_B));
+        let filtered = filter.apply(&airplanes());
+        assert_eq!(filtered.keys().copied().collect::<Vec<_>>(), vec![ICAO_B]);
+        assert!(!filter.allows_unknown());
+    }
+
+    #[test]
+    fn anonymize() {
+        let airplanes = airplanes();
+        let mut filter = PrivacyFilter::new(PrivacyAction::Anonymize).with_salt(0x1234);
+        filter.block(ICAO_A);
+        let pseudonym = filter.pseudonym(ICAO_A);
+        assert_ne!(pseudonym, ICAO_A);
+        assert_eq!(filter.map_icao(ICAO_A), Some(pseudonym));
+        assert_eq!(filter.map_icao(ICAO_B), Some(ICAO_B));
+        // depends on the salt
+        let other = PrivacyFilter::new(PrivacyAction::Anonymize).with_salt(0x1235);
+        assert_ne!(other.pseudonym(ICAO_A), pseudonym);
+        // stable with the same salt
+        let same = PrivacyFilter::new(PrivacyAction::Anonymize).with_salt(0x1234);
+        assert_eq!(same.pseudonym(ICAO_A), pseudonym);
+
+        assert_eq!(airplanes.get(ICAO_A).unwrap().callsign.as_deref(), Some("KLM1023"));
+        let filtered = filter.apply(&airplanes);
+        assert_eq!(filtered.len(), 2);
+        assert!(filtered.get(ICAO_A).is_none());
+        assert_eq!(filtered.get(pseudonym).unwrap().callsign, None);
+        assert_eq!(filtered.get(pseudonym).unwrap().num_messages, 1);
+
+        // frames can't be anonymized
+        assert!(!filter.allows_frame(&Frame::from_bytes(&IDENTIFICATION).unwrap()));
+
+        let message = SbsMessage::from_frame(
+            &Frame::from_bytes(&IDENTIFICATION).unwrap(),
+            &airplanes,
+            crate::altitude::AltitudeSource::Baro,
+        )
+        .unwrap();
+        assert_eq!(message.callsign.as_deref(), Some("KLM1023"));
+        let message = filter.apply_sbs(message).unwrap();
+        assert_eq!((message.icao, message.callsign.as_deref()), (pseudonym, None));
+        assert_eq!(
+            PrivacyFilter::new(PrivacyAction::Drop).apply_sbs(message.clone()),
+            Some(message)
+        );
+    }
+
+    #[test]
+    fn anonymize_identity() {
+        let mut airplanes = airplanes();
+        // callsign change from another source, recorded in the history and ident changes
+        let line = "MSG,1,1,1,40621d,1,,,,,KLM1024,,,,,,,,,,,";
+        airplanes.apply_sbs(&SbsMessage::parse_line(line).unwrap().unwrap());
+        let state = airplanes.get(ICAO_A).unwrap();
+        assert_eq!(state.broadcast.previous_callsign.as_deref(), Some("KLM1023"));
+        assert_eq!(airplanes.ident_changes().len(), 1);
+
+        let mut filter = PrivacyFilter::new(PrivacyAction::Anonymize);
+        filter.block(ICAO_A);
+        let filtered = filter.apply(&airplanes);
+        assert!(filtered.icao_cache().is_empty());
+        assert!(filtered.ident_changes().is_empty());
+
+        let debug = format!("{filtered:?}");
+        assert!(!debug.contains(&format!("{:?}", ICAO_A)) && !debug.contains("KLM102"), "{debug}");
+        #[cfg(feature = "serde")]
+        {
+            let json = serde_json::to_string(&filtered).unwrap();
+            assert!(!json.contains("40621d") && !json.contains("KLM102"), "{json}");
+            assert!(json.contains("a08f94"), "{json}");
+        }
+    }
+
+    #[test]
+    fn pseudonym_range() {
+        for salt in 0..1000 {
+            let filter = PrivacyFilter::new(PrivacyAction::Anonymize).with_salt(salt);
+            let pseudonym = u32::from_be_bytes([0, 0xf1, 0, 0]);
+            assert!(filter.pseudonym(ICAO_A) >= ICAO::from(pseudonym));
+            assert!(filter.pseudonym(ICAO_B) >= ICAO::from(pseudonym));
+        }
+    }
+
+    #[test]
+    fn pseudonym_collision() {
+        let mut airplanes = airplanes();
+        let mut filter = PrivacyFilter::new(PrivacyAction::Anonymize);
+        filter.block(ICAO_A);
+        let pseudonym = filter.pseudonym(ICAO_A);
+
+        // tracked aircraft at the address of the pseudonym, such as from a corrupted frame
+        let line = format!("MSG,5,1,1,{pseudonym},1,,,,,,37000,,,,,,,,,,");
+        airplanes.apply_sbs(&SbsMessage::parse_line(&line).unwrap().unwrap());
+
+        let filtered = filter.apply(&airplanes);
+        assert_eq!(filtered.keys().copied().collect::<Vec<_>>(), vec![ICAO_B, pseudonym]);
+        // not merged with the anonymized aircraft
+        let state = filtered.get(pseudonym).unwrap();
+        assert_eq!(state.coords.baro_altitude(), Some(37000));
+        assert_eq!(state.num_messages, 1);
+    }
+}
@@ -0,0 +1,138 @@
+//! Local altimeter setting (QNH) from the Target State and Status messages of the aircraft
+//!
+//! Aircraft flying below the transition altitude have the local QNH set on their altimeter, which
+//! they broadcast in their Target State and Status messages, see [`AirplaneState::qnh`]. Above
+//! the transition altitude, they use the standard pressure of 1013.25 hPa. The median of the
+//! aircraft below the transition altitude gives a [`QnhConsensus`], used to convert their
+//! pressure altitudes to the true altitude with [`true_altitude`].
+//!
+//! ```rust
+//! use adsb_deku::Frame;
+//! use rsadsb_common::qnh::{self, QnhConsensus};
+//! use rsadsb_common::sbs::SbsMessage;
+//! use rsadsb_common::Airplanes;
+//!
+//! let mut airplanes = Airplanes::default();
+//! // target state and status with a QNH of 1012.8 hPa
+//! let bytes = [0x8d, 0xa0, 0x8f, 0x94, 0xea, 0x1b, 0x78, 0x5e, 0x8f, 0x3c, 0x08, 0x8a, 0xb4, 0x67];
+//! airplanes.action(Frame::from_bytes(&bytes).unwrap());
+//! let line = "MSG,5,1,1,A08F94,1,,,,,,5000,,,,,,,,,,";
+//! airplanes.apply_sbs(&SbsMessage::parse_line(line).unwrap().unwrap());
+//!
+//! let consensus = QnhConsensus::from_airplanes(&airplanes, 18000).unwrap();
+//! assert_eq!(consensus.samples, 1);
+//! assert!((consensus.qnh - 1012.8).abs() < 0.01);
+//! assert_eq!(qnh::true_altitude(5000, consensus.qnh), 4988);
+//! ```
+//!
+//! [`AirplaneState::qnh`]: crate::AirplaneState::qnh
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+#[cfg(feature = "alloc")]
+use core::{
+    clone::Clone,
+    cmp::PartialEq,
+    fmt::Debug,
+    iter::Iterator,
+    marker::Copy,
+    option::Option::{self, None, Some},
+    prelude::rust_2021::derive,
+};
+
+use crate::Airplanes;
+
+/// Standard pressure (hPa), used above the transition altitude
+pub const STANDARD_PRESSURE: f32 = 1013.25;
+
+/// QNH agreed on by the aircraft below the transition altitude
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct QnhConsensus {
+    /// Median QNH (hPa)
+    pub qnh: f32,
+    /// Amount of aircraft used for the median
+    pub samples: u32,
+    /// Difference (hPa) between the lowest and highest QNH
+    pub spread: f32,
+}
+
+impl QnhConsensus {
+    /// Consensus of the aircraft of `airplanes` with a QNH and a barometric altitude below
+    /// `transition_altitude` (ft), `None` without any
+    #[must_use]
+    pub fn from_airplanes(airplanes: &Airplanes, transition_altitude: i32) -> Option<Self> {
+        let mut values: Vec<f32> = airplanes
+            .iter()
+            .filter(|(_, state)| {
+                state.coords.baro_altitude().is_some_and(|alt| alt < transition_altitude)
+            })
+            .filter_map(|(_, state)| state.qnh)
+            .collect();
+        if values.is_empty() {
+            return None;
+        }
+        values.sort_by(f32::total_cmp);
+        let middle = values.len() / 2;
+        let qnh = if values.len() % 2 == 0 {
+            (values[middle - 1] + values[middle]) / 2.0
+        } else {
+            values[middle]
+        };
+        Some(Self {
+            qnh,
+            samples: values.len() as u32,
+            spread: values[values.len() - 1] - values[0],
+        })
+    }
+}
+
+/// Altitude (ft) of the `pressure` (hPa) in the International Standard Atmosphere
+fn pressure_altitude(pressure: f32) -> f64 {
+    145_366.45 * (1.0 - libm::pow(f64::from(pressure / STANDARD_PRESSURE), 0.190_284))
+}
+
+/// True altitude (ft) of the `pressure_altitude` (ft, referenced to [`STANDARD_PRESSURE`]) with
+/// the local `qnh` (hPa), about 27 ft for each hPa
+#[must_use]
+pub fn true_altitude(pressure_altitude: i32, qnh: f32) -> i32 {
+    pressure_altitude - libm::round(self::pressure_altitude(qnh)) as i32
+}
+
+#[cfg(test)]
+mod tests {
+    use adsb_deku::ICAO;
+
+    use super::*;
+
+    #[test]
+    fn consensus() {
+        let mut airplanes = Airplanes::default();
+        assert_eq!(QnhConsensus::from_airplanes(&airplanes, 18000), None);
+        // the last aircraft is above the transition altitude, with the standard pressure
+        for (i, (qnh, altitude)) in
+            [(1008.8, 3000), (1009.6, 8000), (1012.0, 5000), (1013.6, 37000)]
+                .into_iter()
+                .enumerate()
+        {
+            let (state, _) = airplanes.entry_or_insert(ICAO([0xab, 0xcd, i as u8]));
+            state.qnh = Some(qnh);
+            state.coords.sbs_altitude = Some(altitude);
+        }
+        let consensus = QnhConsensus::from_airplanes(&airplanes, 18000).unwrap();
+        assert_eq!(consensus.samples, 3);
+        assert_eq!(consensus.qnh, 1009.6);
+        assert!((consensus.spread - 3.2).abs() < 0.01);
+
+        let consensus = QnhConsensus::from_airplanes(&airplanes, 6000).unwrap();
+        assert_eq!(consensus.samples, 2);
+        assert!((consensus.qnh - 1010.4).abs() < 0.01);
+    }
+
+    #[test]
+    fn true_altitudes() {
+        assert_eq!(true_altitude(3000, STANDARD_PRESSURE), 3000);
+        assert_eq!(true_altitude(3000, 1023.25), 3272);
+        assert_eq!(true_altitude(3000, 1003.25), 2726);
+    }
+}
@@ -0,0 +1,180 @@
+//! Quality score of the ADS-B broadcast of an aircraft, for finding poorly performing
+//! transponders and decoding issues
+//!
+//! The score, from 0 to 100, is the weighted mean of the components that are known:
+//! - integrity: NACp, SIL and NICbaro from `ME::AircraftOperationStatus`
+//! - continuity: messages per second since the first message, up to [`EXPECTED_RATE`]
+//! - consistency: decoded positions accepted, versus rejected by the range and distance checks
+//!
+//! ```rust
+//! use rsadsb_common::quality::{QualityHistory, QualityScore};
+//!
+//! let mut history = QualityHistory::default();
+//! history.update_operation_status(3, 1);
+//! for _ in 0..9 {
+//!     history.add_position(true);
+//! }
+//! history.add_position(false);
+//! let quality = QualityScore::new(Some(9), &history, Some(4.0)).unwrap();
+//! assert_eq!(quality.consistency, Some(90));
+//! assert_eq!(quality.score, 93);
+//! ```
+
+#[cfg(feature = "alloc")]
+use core::{
+    clone::Clone,
+    cmp::{Eq, PartialEq},
+    default::Default,
+    fmt::Debug,
+    iter::Iterator,
+    marker::Copy,
+    option::Option::{self, None, Some},
+    prelude::rust_2021::derive,
+};
+
+/// Messages per second of an aircraft with a continuity of 100
+///
+/// An airborne aircraft broadcasts about 2 positions, 2 velocities and a few identification and
+/// status messages each second, and also replies to interrogations.
+pub const EXPECTED_RATE: f64 = 4.0;
+
+/// Highest NACp, an accuracy under 3 m (DO-260B 2.2.3.2.7.2.7)
+const MAX_NACP: u8 = 11;
+
+/// Highest SIL, a probability of exceeding the integrity containment radius under 1e-7 per hour
+const MAX_SIL: u8 = 3;
+
+/// Decoded positions needed for the consistency
+const MIN_POSITIONS: u32 = 4;
+
+/// Weight of the integrity, continuity and consistency in the score
+const WEIGHTS: [f64; 3] = [0.4, 0.3, 0.3];
+
+/// Values received for the [`QualityScore`] of an aircraft, see
+/// [`crate::AirplaneState::quality`]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct QualityHistory {
+    /// Source Integrity Level, from `adsb::OperationStatus`
+    pub sil: Option<u8>,
+    /// Barometric altitude integrity, from `adsb::OperationStatus`
+    pub nic_baro: Option<u8>,
+    /// Decoded positions accepted
+    pub positions: u32,
+    /// Decoded positions rejected by the range or distance checks
+    pub rejected_positions: u32,
+}
+
+impl QualityHistory {
+    /// update from `adsb::OperationStatus::{Airborne, Surface}`
+    pub fn update_operation_status(&mut self, sil: u8, nic_baro: u8) {
+        self.sil = Some(sil);
+        self.nic_baro = Some(nic_baro);
+    }
+
+    /// Count a decoded position, `accepted` or rejected
+    pub fn add_position(&mut self, accepted: bool) {
+        if accepted {
+            self.positions += 1;
+        } else {
+            self.rejected_positions += 1;
+        }
+    }
+}
+
+/// Quality of an aircraft from 0 (worst) to 100 (best), see [`self`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct QualityScore {
+    /// Weighted mean of the known components
+    pub score: u8,
+    /// From NACp, SIL and NICbaro, `None` until an operation status is received
+    pub integrity: Option<u8>,
+    /// From the message rate, `None` without a rate
+    pub continuity: Option<u8>,
+    /// From the accepted and rejected positions, `None` with less than 4 positions
+    pub consistency: Option<u8>,
+}
+
+impl QualityScore {
+    /// Score from the `nacp` and `history` of an aircraft, and its `message_rate` (messages/s)
+    ///
+    /// Returns `None` if no component is known.
+    #[must_use]
+    pub fn new(
+        nacp: Option<u8>,
+        history: &QualityHistory,
+        message_rate: Option<f64>,
+    ) -> Option<Self> {
+        let integrity = match (nacp, history.sil, history.nic_baro) {
+            (Some(nacp), Some(sil), Some(nic_baro)) => Some(
+                0.5 * f64::from(nacp.min(MAX_NACP)) / f64::from(MAX_NACP)
+                    + 0.3 * f64::from(sil.min(MAX_SIL)) / f64::from(MAX_SIL)
+                    + 0.2 * f64::from(nic_baro.min(1)),
+            ),
+            _ => None,
+        };
+        let continuity = message_rate.map(|rate| (rate / EXPECTED_RATE).clamp(0.0, 1.0));
+        let checked = history.positions + history.rejected_positions;
+        let consistency =
+            (checked >= MIN_POSITIONS).then(|| f64::from(history.positions) / f64::from(checked));
+
+        let components = [integrity, continuity, consistency];
+        let (sum, weights) = components
+            .iter()
+            .zip(WEIGHTS)
+            .filter_map(|(component, weight)| component.map(|component| (component, weight)))
+            .fold((0.0, 0.0), |(sum, weights), (component, weight)| {
+                (sum + component * weight, weights + weight)
+            });
+        if weights == 0.0 {
+            return None;
+        }
+        Some(Self {
+            score: percent(sum / weights),
+            integrity: integrity.map(percent),
+            continuity: continuity.map(percent),
+            consistency: consistency.map(percent),
+        })
+    }
+}
+
+/// `fraction` from 0.0 to 1.0 as 0 to 100
+fn percent(fraction: f64) -> u8 {
+    libm::round(fraction * 100.0) as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn components() {
+        let mut history = QualityHistory::default();
+        assert_eq!(QualityScore::new(None, &history, None), None);
+
+        // continuity only
+        let quality = QualityScore::new(None, &history, Some(1.0)).unwrap();
+        assert_eq!(
+            quality,
+            QualityScore { score: 25, integrity: None, continuity: Some(25), consistency: None }
+        );
+
+        // NACp alone isn't enough for the integrity
+        history.update_operation_status(3, 1);
+        assert_eq!(QualityScore::new(None, &history, Some(8.0)).unwrap().score, 100);
+        let quality = QualityScore::new(Some(11), &history, Some(8.0)).unwrap();
+        assert_eq!(quality.integrity, Some(100));
+        assert_eq!(quality.score, 100);
+
+        // poor integrity and bad positions
+        history.update_operation_status(0, 0);
+        for accepted in [true, false, false, true] {
+            history.add_position(accepted);
+        }
+        let quality = QualityScore::new(Some(0), &history, None).unwrap();
+        assert_eq!(quality.integrity, Some(0));
+        assert_eq!(quality.consistency, Some(50));
+        assert_eq!(quality.score, 21);
+    }
+}
@@ -0,0 +1,196 @@
+//! `aircraft.json` of readsb and dump1090-fa, such as for the tar1090 web interface
+//!
+//! [`Airplanes::to_aircraft_json`] renders the tracked aircraft in the schema of the
+//! `aircraft.json` that tar1090 reads from its `data` directory, with the `hex`, `flight`,
+//! `alt_baro`, `gs`, `track`, `lat`, `lon`, `seen_pos`, `seen`, `rssi` and `messages` of each
+//! aircraft. Values that aren't known are left out, as in readsb.
+//!
+//! The aircraft filtered by a [`PrivacyFilter`] are dropped or anonymized before rendering.
+//!
+//! ```rust
+//! use std::time::{Duration, UNIX_EPOCH};
+//!
+//! use adsb_deku::Frame;
+//! use rsadsb_common::Airplanes;
+//!
+//! let mut airplanes = Airplanes::builder().position((52.0, 4.0)).build();
+//! // even and odd airborne positions at 38000 ft, and identification
+//! for bytes in [
+//!     [0x8d, 0x40, 0x62, 0x1d, 0x58, 0xc3, 0x82, 0xd6, 0x90, 0xc8, 0xac, 0x28, 0x63, 0xa7],
+//!     [0x8d, 0x40, 0x62, 0x1d, 0x58, 0xc3, 0x86, 0x43, 0x5c, 0xc4, 0x12, 0x69, 0x2a, 0xd6],
+//!     [0x8d, 0x40, 0x62, 0x1d, 0x20, 0x2c, 0xc3, 0x71, 0xc3, 0x2c, 0xe0, 0x57, 0x60, 0x98],
+//! ] {
+//!     airplanes.action(Frame::from_bytes(&bytes).unwrap());
+//! }
+//!
+//! let json = airplanes.to_aircraft_json(UNIX_EPOCH + Duration::from_secs(1_700_000_000), None);
+//! assert!(json.starts_with(r#"{"now":1700000000.0,"messages":3,"aircraft":[{"hex":"40621d","#));
+//! assert!(json.contains(r#""flight":"KLM1023 ","alt_baro":38000,"lat":52.265780,"lon":3.938913,"#));
+//! ```
+//!
+//! With a [`PrivacyFilter`] dropping `40621d`:
+//!
+//! ```rust
+//! # use std::time::UNIX_EPOCH;
+//! # use adsb_deku::{Frame, ICAO};
+//! # use rsadsb_common::Airplanes;
+//! use rsadsb_common::privacy::{PrivacyAction, PrivacyFilter};
+//!
+//! # let mut airplanes = Airplanes::new();
+//! # let bytes = [0x8d, 0x40, 0x62, 0x1d, 0x20, 0x2c, 0xc3, 0x71, 0xc3, 0x2c, 0xe0, 0x57, 0x60, 0x98];
+//! # airplanes.action(Frame::from_bytes(&bytes).unwrap());
+//! let mut filter = PrivacyFilter::new(PrivacyAction::Drop);
+//! filter.block(ICAO([0x40, 0x62, 0x1d]));
+//! let json = airplanes.to_aircraft_json(UNIX_EPOCH, Some(&filter));
+//! assert_eq!(json, r#"{"now":0.0,"messages":0,"aircraft":[]}"#);
+//! ```
+//!
+//! [`PrivacyFilter`]: crate::privacy::PrivacyFilter
+
+use core::fmt::{self, Write};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use adsb_deku::ICAO;
+
+use crate::privacy::PrivacyFilter;
+use crate::{AirplaneState, Airplanes};
+
+/// Lowest `rssi` (dBFS), as in readsb
+const MIN_RSSI: f64 = -49.5;
+
+impl Airplanes {
+    /// `aircraft.json` of the tracked aircraft at `now`, see [`crate::readsb`]
+    ///
+    /// The aircraft are first filtered by `privacy`, see [`PrivacyFilter::apply`]. The `messages`
+    /// of the fleet are the sum of the messages of the rendered aircraft.
+    #[must_use]
+    pub fn to_aircraft_json(&self, now: SystemTime, privacy: Option<&PrivacyFilter>) -> String {
+        if let Some(privacy) = privacy {
+            return privacy.apply(self).to_aircraft_json(now, None);
+        }
+        let now = now.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs_f64();
+        let messages: u64 = self.iter().map(|(_, state)| u64::from(state.num_messages)).sum();
+        let aircraft: Vec<String> =
+            self.iter().map(|(icao, state)| aircraft_json(*icao, state)).collect();
+        format!(r#"{{"now":{now:.1},"messages":{messages},"aircraft":[{}]}}"#, aircraft.join(","))
+    }
+}
+
+/// Object of the aircraft `icao` in `aircraft.json`
+fn aircraft_json(icao: ICAO, state: &AirplaneState) -> String {
+    let mut json = format!(r#"{{"hex":"{icao}""#);
+    if let Some(callsign) = &state.callsign {
+        // padded to 8 characters, as in readsb
+        field(&mut json, "flight", format_args!(r#""{:<8}""#, escape(callsign)));
+    }
+    if state.on_ground == Some(true) {
+        field(&mut json, "alt_baro", r#""ground""#);
+    } else if let Some(altitude) = state.coords.baro_altitude() {
+        field(&mut json, "alt_baro", altitude);
+    }
+    if let Some(speed) = state.speed {
+        field(&mut json, "gs", format_args!("{speed:.1}"));
+    }
+    if let Some(heading) = state.heading {
+        field(&mut json, "track", format_args!("{heading:.1}"));
+    }
+    if let Some(position) = state.coords.position {
+        field(&mut json, "lat", format_args!("{:.6}", position.latitude));
+        field(&mut json, "lon", format_args!("{:.6}", position.longitude));
+    }
+    if let Some(seen_pos) = state.seen_pos() {
+        field(&mut json, "seen_pos", format_args!("{:.1}", seen_pos.as_secs_f64()));
+    }
+    field(&mut json, "seen", format_args!("{:.1}", state.seen().as_secs_f64()));
+    if let Some(signal_level) = state.signal_level {
+        field(&mut json, "rssi", format_args!("{:.1}", rssi(signal_level)));
+    }
+    field(&mut json, "messages", state.num_messages);
+    json.push('}');
+    json
+}
+
+/// Append `"name":value` to the object `json`
+fn field(json: &mut String, name: &str, value: impl fmt::Display) {
+    // writing to a String doesn't fail
+    let _ = write!(json, r#","{name}":{value}"#);
+}
+
+/// Signal power (dBFS) of the `signal_level` (0 to 255)
+fn rssi(signal_level: u8) -> f64 {
+    if signal_level == 0 {
+        return MIN_RSSI;
+    }
+    (20.0 * libm::log10(f64::from(signal_level) / 255.0)).max(MIN_RSSI)
+}
+
+/// Escape the JSON special characters of `text`
+fn escape(text: &str) -> String {
+    let mut escaped = String::new();
+    for c in text.chars() {
+        match c {
+            '"' => escaped.push_str(r#"\""#),
+            '\\' => escaped.push_str(r"\\"),
+            c if c.is_control() => {
+                let _ = write!(escaped, r"\u{:04x}", u32::from(c));
+            }
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::privacy::PrivacyAction;
+    use crate::sbs::SbsMessage;
+
+    use super::*;
+
+    #[test]
+    fn aircraft() {
+        let mut airplanes = Airplanes::new();
+        assert_eq!(
+            airplanes.to_aircraft_json(UNIX_EPOCH, None),
+            r#"{"now":0.0,"messages":0,"aircraft":[]}"#
+        );
+
+        for line in [
+            "MSG,1,1,1,A1B2C3,1,,,,,BAW1,,,,,,,,,,,",
+            "MSG,4,1,1,A1B2C3,1,,,,,,,450,270,,,0,,,,,",
+            "MSG,2,1,1,ABCDEF,1,,,,,,,,,,,,,,,,-1",
+        ] {
+            airplanes.apply_sbs(&SbsMessage::parse_line(line).unwrap().unwrap());
+        }
+        airplanes.add_signal_level(ICAO([0xa1, 0xb2, 0xc3]), 0x80);
+        // untracked
+        airplanes.add_signal_level(ICAO([0x12, 0x34, 0x56]), 0x80);
+
+        let json = airplanes.to_aircraft_json(UNIX_EPOCH, None);
+        assert!(json.starts_with(r#"{"now":0.0,"messages":3,"aircraft":[{"hex":"a1b2c3","#));
+        assert!(json.contains(r#""flight":"BAW1    ","gs":450.0,"track":270.0,"seen":"#), "{json}");
+        assert!(json.contains(r#","rssi":-6.0,"messages":2},{"hex":"abcdef","alt_baro":"ground","#));
+        assert!(json.ends_with(r#","messages":1}]}"#), "{json}");
+
+        // anonymized before rendering
+        let mut privacy = PrivacyFilter::new(PrivacyAction::Anonymize);
+        privacy.block(ICAO([0xa1, 0xb2, 0xc3]));
+        let json = airplanes.to_aircraft_json(UNIX_EPOCH, Some(&privacy));
+        assert!(!json.contains("a1b2c3") && !json.contains("BAW1"), "{json}");
+        assert!(json.contains(r#""messages":3,"aircraft":[{"hex":"abcdef","#), "{json}");
+    }
+
+    #[test]
+    fn escaped() {
+        assert_eq!(escape("KLM1023"), "KLM1023");
+        assert_eq!(escape("a\"b\\c\n"), r#"a\"b\\c\u000a"#);
+    }
+
+    #[test]
+    fn signal_power() {
+        assert_eq!(rssi(255), 0.0);
+        assert!((rssi(0x80) + 5.99).abs() < 0.01);
+        assert!((rssi(1) + 48.13).abs() < 0.01);
+        assert_eq!(rssi(0), MIN_RSSI);
+    }
+}
@@ -0,0 +1,109 @@
+//! Positions rejected by the range checks, for diagnosing a misconfigured receiver position or
+//! max range
+//!
+//! A decoded position farther than the max range from the receiver is rejected as
+//! [`RejectReason::OutOfRange`], and a position farther than possible from the previous position
+//! of the aircraft as [`RejectReason::DistanceJump`]. Both are counted for each aircraft in
+//! [`AirplaneState::rejected_fixes`], and for all aircraft since the start (including the pruned
+//! aircraft) in [`Airplanes::rejected_fixes`], keeping the last [`MAX_RECENT`] rejected positions.
+//! Many positions out of range at the same bearing usually come from a wrong receiver position.
+//!
+//! ```rust
+//! use adsb_deku::Frame;
+//! use rsadsb_common::{Airplanes, RejectReason};
+//!
+//! let mut airplanes = Airplanes::builder().position((52.0, 4.0)).max_range(10.0).build();
+//! // even and odd airborne position, about 29 km from the receiver
+//! for bytes in [
+//!     [0x8d, 0x40, 0x62, 0x1d, 0x58, 0xc3, 0x82, 0xd6, 0x90, 0xc8, 0xac, 0x28, 0x63, 0xa7],
+//!     [0x8d, 0x40, 0x62, 0x1d, 0x58, 0xc3, 0x86, 0x43, 0x5c, 0xc4, 0x12, 0x69, 0x2a, 0xd6],
+//! ] {
+//!     airplanes.action(Frame::from_bytes(&bytes).unwrap());
+//! }
+//! let rejected = airplanes.rejected_fixes();
+//! assert_eq!(rejected.out_of_range, 1);
+//! assert_eq!(rejected.recent[0].reason, RejectReason::OutOfRange);
+//! let (_, state) = airplanes.iter().next().unwrap();
+//! assert_eq!(state.rejected_fixes.total(), 1);
+//! ```
+//!
+//! [`AirplaneState::rejected_fixes`]: crate::AirplaneState::rejected_fixes
+//! [`Airplanes::rejected_fixes`]: crate::Airplanes::rejected_fixes
+
+#[cfg(feature = "alloc")]
+use alloc::collections::VecDeque;
+#[cfg(feature = "alloc")]
+use core::{
+    clone::Clone, cmp::PartialEq, default::Default, fmt::Debug, marker::Copy,
+    prelude::rust_2021::derive,
+};
+
+use adsb_deku::cpr;
+
+use crate::RejectReason;
+
+/// Rejected positions kept in [`RejectedFixes::recent`], the oldest are dropped
+pub const MAX_RECENT: usize = 8;
+
+/// A decoded position rejected by the range checks
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RejectedFix {
+    pub position: cpr::Position,
+    /// [`RejectReason::OutOfRange`] or [`RejectReason::DistanceJump`]
+    pub reason: RejectReason,
+    /// Kilometers from the receiver when out of range, else from the previous position
+    pub kilo_distance: f64,
+}
+
+/// Counts of [`RejectedFix`], with the last rejected positions
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RejectedFixes {
+    /// Positions farther than the max range from the receiver
+    pub out_of_range: u64,
+    /// Positions farther than possible from the previous position
+    pub distance_jump: u64,
+    /// Last [`MAX_RECENT`] rejected positions, oldest first
+    pub recent: VecDeque<RejectedFix>,
+}
+
+impl RejectedFixes {
+    pub(crate) fn add(&mut self, fix: RejectedFix) {
+        match fix.reason {
+            RejectReason::OutOfRange => self.out_of_range += 1,
+            RejectReason::DistanceJump => self.distance_jump += 1,
+            RejectReason::StalePair => (),
+        }
+        if self.recent.len() == MAX_RECENT {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(fix);
+    }
+
+    /// All rejected positions
+    #[must_use]
+    pub fn total(&self) -> u64 {
+        self.out_of_range + self.distance_jump
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recent() {
+        let mut rejected = RejectedFixes::default();
+        for i in 0..10 {
+            let reason =
+                if i % 2 == 0 { RejectReason::OutOfRange } else { RejectReason::DistanceJump };
+            let position = cpr::Position { latitude: f64::from(i), longitude: 0.0 };
+            rejected.add(RejectedFix { position, reason, kilo_distance: 600.0 });
+        }
+        assert_eq!((rejected.out_of_range, rejected.distance_jump), (5, 5));
+        assert_eq!(rejected.total(), 10);
+        assert_eq!(rejected.recent.len(), MAX_RECENT);
+        assert_eq!(rejected.recent[0].position.latitude, 2.0);
+    }
+}
@@ -0,0 +1,534 @@
+//! SBS-1 (BaseStation) text format, as output by dump1090 on port 30003
+//!
+//! Each `MSG` line contains some of the decoded values of a single frame, without the frame
+//! itself, such as for sources that only provide a BaseStation feed. The message updates the
+//! aircraft with [`crate::Airplanes::apply_sbs`].
+//!
+//! The other way, [`SbsMessage::from_frame`] converts a frame applied to the [`Airplanes`] into
+//! a message, written with [`SbsMessage::to_line`] for Virtual Radar Server and other consumers of
+//! a BaseStation feed.
+//!
+//! ```rust
+//! use rsadsb_common::sbs::SbsMessage;
+//! use rsadsb_common::Airplanes;
+//!
+//! let line = "MSG,3,1,1,4CA2D6,1,2024/01/01,12:00:00.000,2024/01/01,12:00:00.000,,37000,,,51.46,-0.45,,,0,0,0,0";
+//! let message = SbsMessage::parse_line(line).unwrap().unwrap();
+//! assert_eq!(message.altitude, Some(37000));
+//! assert_eq!(message.position, Some((51.46, -0.45)));
+//!
+//! let mut airplanes = Airplanes::new();
+//! let outcome = airplanes.apply_sbs(&message);
+//! assert!(outcome.position_updated);
+//! let state = airplanes.get(message.icao).unwrap();
+//! assert_eq!(state.coords.altitude(), Some(37000));
+//! assert_eq!(
+//!     message.to_line(Some(("2024/01/01", "12:00:00.000"))),
+//!     "MSG,3,1,1,4CA2D6,1,2024/01/01,12:00:00.000,2024/01/01,12:00:00.000,,37000,,,51.46000,-0.45000,,,,,,0"
+//! );
+//! ```
+
+#[cfg(feature = "alloc")]
+use alloc::string::{String, ToString};
+#[cfg(feature = "alloc")]
+use core::{
+    clone::Clone,
+    cmp::{Eq, PartialEq},
+    fmt::Debug,
+    iter::Iterator,
+    marker::Copy,
+    option::Option::{self, None, Some},
+    prelude::rust_2021::derive,
+    result::Result::{self, Err, Ok},
+    str::FromStr,
+    write,
+};
+
+use adsb_deku::adsb::{ControlField, ADSB, ME};
+use adsb_deku::{FlightStatus, Frame, DF, ICAO};
+
+use crate::altitude::{self, AltitudeSource};
+use crate::{squawk, Airplanes};
+
+/// Fields of a `MSG` line
+const FIELDS: usize = 22;
+
+/// Index of the fields of a `MSG` line
+mod field {
+    pub const TRANSMISSION_TYPE: usize = 1;
+    pub const HEX_IDENT: usize = 4;
+    pub const CALLSIGN: usize = 10;
+    pub const ALTITUDE: usize = 11;
+    pub const GROUND_SPEED: usize = 12;
+    pub const TRACK: usize = 13;
+    pub const LAT: usize = 14;
+    pub const LONG: usize = 15;
+    pub const VERTICAL_RATE: usize = 16;
+    pub const SQUAWK: usize = 17;
+    pub const IS_ON_GROUND: usize = 21;
+}
+
+/// Name of each field of a `MSG` line, for [`SbsError`]
+const FIELD_NAMES: [&str; FIELDS] = [
+    "message type",
+    "transmission type",
+    "session id",
+    "aircraft id",
+    "hex ident",
+    "flight id",
+    "date generated",
+    "time generated",
+    "date logged",
+    "time logged",
+    "callsign",
+    "altitude",
+    "ground speed",
+    "track",
+    "lat",
+    "long",
+    "vertical rate",
+    "squawk",
+    "alert",
+    "emergency",
+    "spi",
+    "is on ground",
+];
+
+/// Other message types of the BaseStation format, without aircraft values
+const OTHER_MESSAGES: [&str; 5] = ["SEL", "ID", "AIR", "STA", "CLK"];
+
+/// Error from [`SbsMessage::parse_line`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SbsError {
+    /// Line doesn't start with a BaseStation message type, such as `MSG`
+    UnknownMessage,
+    /// Line is too short to contain the field, contains the name of the field
+    MissingField(&'static str),
+    /// Field isn't a valid value, contains the name of the field
+    InvalidField(&'static str),
+}
+
+impl core::fmt::Display for SbsError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::UnknownMessage => write!(f, "unknown sbs message"),
+            Self::MissingField(name) => write!(f, "missing sbs field: {name}"),
+            Self::InvalidField(name) => write!(f, "invalid sbs field: {name}"),
+        }
+    }
+}
+
+/// Decoded values of a `MSG` line, `None` if not in the message, see [`self`]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SbsMessage {
+    /// 1 (identification) to 8 (all call reply)
+    pub transmission_type: u8,
+    pub icao: ICAO,
+    pub callsign: Option<String>,
+    /// Barometric altitude (ft)
+    pub altitude: Option<i32>,
+    /// Ground speed (kt)
+    pub ground_speed: Option<f32>,
+    /// Track (degrees)
+    pub track: Option<f32>,
+    /// (latitude, longitude)
+    pub position: Option<(f64, f64)>,
+    /// Vertical rate (ft/min)
+    pub vertical_rate: Option<i16>,
+    /// Mode A code as a decimal number, such as 7000, see [`crate::squawk`]
+    pub squawk: Option<u32>,
+    pub on_ground: Option<bool>,
+}
+
+impl SbsMessage {
+    /// Parse a single BaseStation line
+    ///
+    /// Surrounding whitespace (such as `\r\n`) is ignored, and fields missing at the end of the
+    /// line are `None`.
+    ///
+    /// Returns:
+    ///   `Ok(Some(message))`: `MSG` line
+    ///   `Ok(None)`:          blank line, or other message type without aircraft values
+    ///   `Err(e)`:            garbage line
+    pub fn parse_line(line: &str) -> Result<Option<Self>, SbsError> {
+        let line = line.trim();
+        let mut fields = [""; FIELDS];
+        let mut len = 0;
+        for (field, value) in fields.iter_mut().zip(line.split(',')) {
+            *field = value.trim();
+            len += 1;
+        }
+        match fields[0] {
+            "MSG" => (),
+            "" if line.is_empty() => return Ok(None),
+            message if OTHER_MESSAGES.contains(&message) => return Ok(None),
+            _ => return Err(SbsError::UnknownMessage),
+        }
+        if len <= field::HEX_IDENT {
+            return Err(SbsError::MissingField(FIELD_NAMES[field::HEX_IDENT]));
+        }
+
+        let transmission_type = fields[field::TRANSMISSION_TYPE]
+            .parse()
+            .ok()
+            .filter(|transmission_type| (1..=8).contains(transmission_type))
+            .ok_or(invalid(field::TRANSMISSION_TYPE))?;
+        let hex_ident = fields[field::HEX_IDENT];
+        if hex_ident.len() != 6 {
+            return Err(invalid(field::HEX_IDENT));
+        }
+        let icao = hex_ident.parse().map_err(|_| invalid(field::HEX_IDENT))?;
+
+        let callsign = fields[field::CALLSIGN].trim_end_matches(' ');
+        let callsign = (!callsign.is_empty()).then(|| callsign.to_string());
+        let latitude: Option<f64> = value(&fields, field::LAT)?;
+        let longitude: Option<f64> = value(&fields, field::LONG)?;
+        if latitude.is_some_and(|lat| !(-90.0..=90.0).contains(&lat)) {
+            return Err(invalid(field::LAT));
+        }
+        if longitude.is_some_and(|long| !(-180.0..=180.0).contains(&long)) {
+            return Err(invalid(field::LONG));
+        }
+        let squawk: Option<u32> = value(&fields, field::SQUAWK)?;
+        if squawk.is_some_and(|squawk| !squawk::is_valid_decimal(squawk)) {
+            return Err(invalid(field::SQUAWK));
+        }
+        let on_ground = match fields[field::IS_ON_GROUND] {
+            "" => None,
+            "0" => Some(false),
+            "-1" | "1" => Some(true),
+            _ => return Err(invalid(field::IS_ON_GROUND)),
+        };
+
+        Ok(Some(Self {
+            transmission_type,
+            icao,
+            callsign,
+            altitude: value(&fields, field::ALTITUDE)?,
+            ground_speed: value(&fields, field::GROUND_SPEED)?,
+            track: value(&fields, field::TRACK)?,
+            position: latitude.zip(longitude),
+            vertical_rate: value(&fields, field::VERTICAL_RATE)?,
+            squawk,
+            on_ground,
+        }))
+    }
+
+    /// Message of `frame`, after it was applied to `airplanes` with [`Airplanes::action`]
+    ///
+    /// The message contains the values of the frame, and the position decoded by `airplanes`
+    /// for an airborne position. Like dump1090, the transmission type is:
+    /// - 1: ADS-B identification
+    /// - 3: ADS-B airborne position
+    /// - 4: ADS-B airborne velocity
+    /// - 5: altitude reply, DF0/4/20
+    /// - 6: identity reply, DF5/21
+    /// - 7: air-air surveillance, DF16
+    /// - 8: all call reply, DF11
+    ///
+    /// The altitude is the barometric altitude of the frame, or the one of `altitude_source`
+    /// from [`crate::AirplaneState::altitude`]. Returns `None` for the other frames, and the
+    /// frames of aircraft not tracked by `airplanes`.
+    #[must_use]
+    pub fn from_frame(
+        frame: &Frame,
+        airplanes: &Airplanes,
+        altitude_source: AltitudeSource,
+    ) -> Option<Self> {
+        let icao = frame.announced_icao().or_else(|| frame.icao_from_parity(airplanes))?;
+        let state = airplanes.get(icao)?;
+        let altitude = |baro: Option<i32>| match altitude_source {
+            AltitudeSource::Baro => baro,
+            source => state.altitude(source),
+        };
+        let mut message = Self {
+            transmission_type: 0,
+            icao,
+            callsign: None,
+            altitude: None,
+            ground_speed: None,
+            track: None,
+            position: None,
+            vertical_rate: None,
+            squawk: None,
+            on_ground: None,
+        };
+        match &frame.df {
+            DF::ADSB(ADSB { me, .. }) | DF::TisB { cf: ControlField { me, .. }, .. } => match me {
+                ME::AircraftIdentification(identification) => {
+                    message.transmission_type = 1;
+                    message.callsign = Some(identification.cn.trim_end().to_string());
+                }
+                ME::AirbornePositionBaroAltitude(position)
+                | ME::AirbornePositionGNSSAltitude(position) => {
+                    message.transmission_type = 3;
+                    let baro = if altitude::is_gnss(position.tc) {
+                        state.coords.baro_altitude()
+                    } else {
+                        position.alt
+                    };
+                    message.altitude = altitude(baro);
+                    message.position = state
+                        .coords
+                        .position
+                        .map(|position| (position.latitude, position.longitude));
+                    message.on_ground = Some(false);
+                }
+                ME::AirborneVelocity(velocity) => {
+                    message.transmission_type = 4;
+                    let (track, ground_speed, vertical_rate) = velocity.calculate()?;
+                    message.track = Some(track);
+                    message.ground_speed = Some(ground_speed as f32);
+                    message.vertical_rate = Some(vertical_rate);
+                }
+                _ => return None,
+            },
+            DF::ShortAirAirSurveillance { altitude: ac, .. } => {
+                message.transmission_type = 5;
+                message.altitude = altitude(ac.0);
+            }
+            DF::SurveillanceAltitudeReply { fs, ac, .. }
+            | DF::CommBAltitudeReply { flight_status: fs, alt: ac, .. } => {
+                message.transmission_type = 5;
+                message.altitude = altitude(ac.0);
+                message.on_ground = on_ground(*fs);
+            }
+            DF::SurveillanceIdentityReply { fs, id, .. } => {
+                message.transmission_type = 6;
+                message.squawk = Some(squawk::to_decimal(u32::from(id.0)));
+                message.on_ground = on_ground(*fs);
+            }
+            DF::CommBIdentityReply { fs, id, .. } => {
+                message.transmission_type = 6;
+                message.squawk = Some(squawk::to_decimal(*id));
+                message.on_ground = on_ground(*fs);
+            }
+            DF::LongAirAir { altitude: ac, .. } => {
+                message.transmission_type = 7;
+                message.altitude = altitude(ac.0);
+            }
+            DF::AllCallReply { .. } => message.transmission_type = 8,
+            _ => return None,
+        }
+        Some(message)
+    }
+
+    /// `MSG` line of the message, without a line ending
+    ///
+    /// `date_time` is the date and time of both the generated and logged fields, such as
+    /// `("2024/01/01", "12:00:00.000")` from [`crate::timestamp::sbs`]. Without, these fields are
+    /// left empty, also written by [`core::fmt::Display`].
+    #[must_use]
+    pub fn to_line(&self, date_time: Option<(&str, &str)>) -> String {
+        let mut line = String::new();
+        let _ = self.write_line(&mut line, date_time.unwrap_or_default());
+        line
+    }
+
+    /// Write the `MSG` line, with the `date` and `time` fields
+    fn write_line(
+        &self,
+        f: &mut impl core::fmt::Write,
+        (date, time): (&str, &str),
+    ) -> core::fmt::Result {
+        let [a, b, c] = self.icao.0;
+        write!(
+            f,
+            "MSG,{},1,1,{a:02X}{b:02X}{c:02X},1,{date},{time},{date},{time},",
+            self.transmission_type
+        )?;
+        write!(f, "{},", self.callsign.as_deref().unwrap_or_default())?;
+        write_field(f, self.altitude)?;
+        write_field(f, self.ground_speed.map(libm::roundf))?;
+        write_field(f, self.track.map(libm::roundf))?;
+        match self.position {
+            Some((latitude, longitude)) => write!(f, "{latitude:.5},{longitude:.5},")?,
+            None => write!(f, ",,")?,
+        }
+        write_field(f, self.vertical_rate)?;
+        match self.squawk {
+            Some(squawk) => write!(f, "{squawk:04},")?,
+            None => write!(f, ",")?,
+        }
+        // alert, emergency and spi are unknown
+        write!(f, ",,,")?;
+        match self.on_ground {
+            Some(true) => write!(f, "-1"),
+            Some(false) => write!(f, "0"),
+            None => Ok(()),
+        }
+    }
+}
+
+impl core::fmt::Display for SbsMessage {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        self.write_line(f, ("", ""))
+    }
+}
+
+/// Field followed by its `,`, empty if `None`
+fn write_field(
+    f: &mut impl core::fmt::Write,
+    value: Option<impl core::fmt::Display>,
+) -> core::fmt::Result {
+    match value {
+        Some(value) => write!(f, "{value},"),
+        None => write!(f, ","),
+    }
+}
+
+/// On the ground from the flight status, `None` if unknown
+fn on_ground(fs: FlightStatus) -> Option<bool> {
+    match fs {
+        FlightStatus::NoAlertNoSPIAirborne | FlightStatus::AlertNoSPIAirborne => Some(false),
+        FlightStatus::NoAlertNoSPIOnGround | FlightStatus::AlertNoSPIOnGround => Some(true),
+        _ => None,
+    }
+}
+
+/// Value of the field at `index`, `None` if empty
+fn value<T: FromStr>(fields: &[&str; FIELDS], index: usize) -> Result<Option<T>, SbsError> {
+    match fields[index] {
+        "" => Ok(None),
+        value => value.parse().map(Some).map_err(|_| invalid(index)),
+    }
+}
+
+fn invalid(index: usize) -> SbsError {
+    SbsError::InvalidField(FIELD_NAMES[index])
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{format, vec, vec::Vec};
+
+    use super::*;
+
+    /// Lines of the frames applied to `airplanes`
+    fn lines(airplanes: &mut Airplanes, frames: &[&str], source: AltitudeSource) -> Vec<String> {
+        let mut lines = vec![];
+        for frame in frames {
+            let bytes: Vec<u8> = (0..frame.len())
+                .step_by(2)
+                .map(|i| u8::from_str_radix(&frame[i..i + 2], 16).unwrap())
+                .collect();
+            airplanes.action(Frame::from_bytes(&bytes).unwrap());
+            let frame = Frame::from_bytes(&bytes).unwrap();
+            let message = SbsMessage::from_frame(&frame, airplanes, source);
+            lines.extend(message.map(|message| message.to_string()));
+        }
+        lines
+    }
+
+    #[test]
+    fn from_frames() {
+        let mut airplanes = Airplanes::builder().position((52.0, 4.0)).build();
+        // identification, even and odd airborne position, velocity and target state
+        let frames = [
+            "8d40621d202cc371c32ce0576098",
+            "8d40621d58c382d690c8ac2863a7",
+            "8d40621d58c386435cc412692ad6",
+            "8d40621d994409940838175b284f",
+            "8d40621dea1b785e8f3c088ab467",
+        ];
+        assert_eq!(
+            lines(&mut airplanes, &frames, AltitudeSource::Baro),
+            [
+                "MSG,1,1,1,40621D,1,,,,,KLM1023,,,,,,,,,,,",
+                "MSG,3,1,1,40621D,1,,,,,,38000,,,,,,,,,,0",
+                "MSG,3,1,1,40621D,1,,,,,,38000,,,52.26578,3.93891,,,,,,0",
+                "MSG,4,1,1,40621D,1,,,,,,,159,183,,,-832,,,,,",
+            ]
+        );
+        let gnss_corrected = lines(&mut airplanes, &frames[2..3], AltitudeSource::GnssCorrected);
+        assert_eq!(gnss_corrected, ["MSG,3,1,1,40621D,1,,,,,,38550,,,52.26578,3.93891,,,,,,0"]);
+
+        // DF18 identification, of the address in the AA field
+        assert_eq!(
+            lines(&mut Airplanes::new(), &["9040621d202cc371c32ce0530aa9"], AltitudeSource::Baro),
+            ["MSG,1,1,1,40621D,1,,,,,KLM1023,,,,,,,,,,,"]
+        );
+
+        // surveillance replies of a tracked aircraft, from the parity
+        let mut airplanes = Airplanes::new();
+        for hex_ident in ["A3ECCE", "510AF9"] {
+            let line = format!("MSG,8,1,1,{hex_ident},1,,,,,,,,,,,,,,,,");
+            airplanes.apply_sbs(&SbsMessage::parse_line(&line).unwrap().unwrap());
+        }
+        assert_eq!(
+            lines(&mut airplanes, &["200012b0d96e39", "2a00516d492b80"], AltitudeSource::Baro),
+            ["MSG,5,1,1,A3ECCE,1,,,,,,29000,,,,,,,,,,0", "MSG,6,1,1,510AF9,1,,,,,,,,,,,,0356,,,,0"]
+        );
+        // not tracked
+        assert_eq!(
+            lines(&mut Airplanes::new(), &["200012b0d96e39"], AltitudeSource::Baro).len(),
+            0
+        );
+    }
+
+    #[test]
+    fn roundtrip() {
+        let line = "MSG,3,1,1,A1B2C3,1,2024/01/01,12:00:00.000,2024/01/01,12:00:00.000,UAL123,37000,451,271,35.12000,-80.40000,-1024,7000,,,,-1";
+        let message = SbsMessage::parse_line(line).unwrap().unwrap();
+        assert_eq!(message.to_line(Some(("2024/01/01", "12:00:00.000"))), line);
+        assert_eq!(SbsMessage::parse_line(&message.to_string()).unwrap().unwrap(), message);
+    }
+
+    #[test]
+    fn messages() {
+        let line = "MSG,1,1,1,A1B2C3,1,2024/01/01,12:00:00.000,2024/01/01,12:00:00.000,UAL123  ,,,,,,,,,,,\r\n";
+        let message = SbsMessage::parse_line(line).unwrap().unwrap();
+        assert_eq!(message.transmission_type, 1);
+        assert_eq!(message.icao, "a1b2c3".parse().unwrap());
+        assert_eq!(message.callsign.as_deref(), Some("UAL123"));
+        assert_eq!(message.altitude, None);
+
+        // trailing fields left out
+        let line = "MSG,4,1,1,A1B2C3,1,2024/01/01,12:00:00.000,2024/01/01,12:00:00.000,,,451,270.5,,,-1024";
+        let message = SbsMessage::parse_line(line).unwrap().unwrap();
+        assert_eq!(message.ground_speed, Some(451.0));
+        assert_eq!(message.track, Some(270.5));
+        assert_eq!(message.vertical_rate, Some(-1024));
+        assert_eq!(message.on_ground, None);
+
+        let line = "MSG,6,1,1,A1B2C3,1,,,,,,2500,,,,,,7000,0,0,0,-1";
+        let message = SbsMessage::parse_line(line).unwrap().unwrap();
+        assert_eq!(message.squawk, Some(7000));
+        assert_eq!(message.on_ground, Some(true));
+
+        assert_eq!(SbsMessage::parse_line("\r\n"), Ok(None));
+        assert_eq!(SbsMessage::parse_line("STA,,1,1,A1B2C3,1,,,,,RM"), Ok(None));
+    }
+
+    #[test]
+    fn invalid_messages() {
+        assert_eq!(
+            SbsMessage::parse_line("*8D40621D58C382D690C8AC2863A7;"),
+            Err(SbsError::UnknownMessage)
+        );
+        assert_eq!(SbsMessage::parse_line("MSG,3,1"), Err(SbsError::MissingField("hex ident")));
+        assert_eq!(
+            SbsMessage::parse_line("MSG,9,1,1,A1B2C3,1"),
+            Err(SbsError::InvalidField("transmission type"))
+        );
+        assert_eq!(
+            SbsMessage::parse_line("MSG,3,1,1,A1B2C3D,1"),
+            Err(SbsError::InvalidField("hex ident"))
+        );
+        assert_eq!(
+            SbsMessage::parse_line("MSG,3,1,1,A1B2C3,1,,,,,,35000,,,91.0,10.0"),
+            Err(SbsError::InvalidField("lat"))
+        );
+        assert_eq!(
+            SbsMessage::parse_line("MSG,3,1,1,A1B2C3,1,,,,,,FL350"),
+            Err(SbsError::InvalidField("altitude"))
+        );
+        // not an octal digit
+        assert_eq!(
+            SbsMessage::parse_line("MSG,6,1,1,A1B2C3,1,,,,,,,,,,,,7800,0,0,0,0"),
+            Err(SbsError::InvalidField("squawk"))
+        );
+    }
+}
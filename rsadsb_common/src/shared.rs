@@ -0,0 +1,145 @@
+//! [`Airplanes`] shared between threads
+//!
+//! Applications reading frames on a network thread, exporting on another thread and drawing a UI
+//! can all use a clone of the same [`SharedAirplanes`] handle.
+//!
+//! ```rust
+//! use adsb_deku::Frame;
+//! use rsadsb_common::shared::SharedAirplanes;
+//! use rsadsb_common::Airplanes;
+//!
+//! let airplanes = SharedAirplanes::new(Airplanes::builder().position((35.0, -80.0)).build());
+//!
+//! let network = airplanes.clone();
+//! std::thread::spawn(move || {
+//!     let bytes = [0x8d, 0xa0, 0x8f, 0x94, 0x23, 0x3d, 0x17, 0x75, 0xe7, 0xe9, 0xe0, 0x00, 0x00, 0x00];
+//!     network.action(Frame::from_bytes(&bytes).unwrap());
+//! })
+//! .join()
+//! .unwrap();
+//!
+//! assert_eq!(airplanes.snapshot().len(), 1);
+//! ```
+//!
+//! # Guarantees
+//! - Every method holds the lock only for its own duration, never across calls. A sequence of
+//!   calls, such as [`SharedAirplanes::get`] followed by [`SharedAirplanes::action`], can see
+//!   changes from other threads in between. Use [`SharedAirplanes::write`] for a sequence that
+//!   must be atomic.
+//! - [`SharedAirplanes::action`] and [`SharedAirplanes::prune`] are atomic, readers see either
+//!   the state before or after the frame.
+//! - [`SharedAirplanes::snapshot`] is a consistent copy that is not updated afterwards.
+//! - Many readers can hold the lock at the same time, writers have exclusive access.
+//! - A panic of another thread while holding the lock doesn't poison the handle, the state left by
+//!   that thread is used.
+
+use std::sync::{Arc, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+use adsb_deku::{Frame, ICAO};
+
+use crate::{ActionOutcome, AirplaneState, Airplanes};
+
+/// Cloneable thread-safe handle to [`Airplanes`], see [`self`]
+#[derive(Debug, Clone, Default)]
+pub struct SharedAirplanes(Arc<RwLock<Airplanes>>);
+
+impl From<Airplanes> for SharedAirplanes {
+    fn from(airplanes: Airplanes) -> Self {
+        Self::new(airplanes)
+    }
+}
+
+impl SharedAirplanes {
+    #[must_use]
+    pub fn new(airplanes: Airplanes) -> Self {
+        Self(Arc::new(RwLock::new(airplanes)))
+    }
+
+    /// [`Airplanes::action`]
+    pub fn action(&self, frame: Frame) -> ActionOutcome {
+        self.lock_write().action(frame)
+    }
+
+    /// [`Airplanes::prune`]
+    pub fn prune(&self, filter_time: u64) {
+        self.lock_write().prune(filter_time);
+    }
+
+    /// Copy of the current state of all aircraft
+    #[must_use]
+    pub fn snapshot(&self) -> Airplanes {
+        self.lock_read().clone()
+    }
+
+    /// Copy of the current state of `icao`
+    #[must_use]
+    pub fn get(&self, icao: ICAO) -> Option<AirplaneState> {
+        self.lock_read().get(icao).cloned()
+    }
+
+    /// Call `f` with shared access, without copying
+    pub fn read<T>(&self, f: impl FnOnce(&Airplanes) -> T) -> T {
+        f(&self.lock_read())
+    }
+
+    /// Call `f` with exclusive access, for a sequence of calls that must be atomic
+    pub fn write<T>(&self, f: impl FnOnce(&mut Airplanes) -> T) -> T {
+        f(&mut self.lock_write())
+    }
+
+    fn lock_read(&self) -> RwLockReadGuard<'_, Airplanes> {
+        self.0.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn lock_write(&self) -> RwLockWriteGuard<'_, Airplanes> {
+        self.0.write().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use super::*;
+
+    #[test]
+    fn concurrent_action() {
+        // aircraft identification, with different addresses
+        let frames = [
+            [0x8d, 0xa0, 0x8f, 0x94, 0x23, 0x3d, 0x17, 0x75, 0xe7, 0xe9, 0xe0, 0x00, 0x00, 0x00],
+            [0x8d, 0x40, 0x62, 0x1d, 0x20, 0x2c, 0xc3, 0x71, 0xc3, 0x2c, 0xe0, 0x57, 0x60, 0x98],
+        ];
+        let airplanes = SharedAirplanes::default();
+
+        let handles: Vec<_> = frames
+            .into_iter()
+            .map(|bytes| {
+                let airplanes = airplanes.clone();
+                thread::spawn(move || {
+                    for _ in 0..100 {
+                        airplanes.action(Frame::from_bytes(&bytes).unwrap());
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        let snapshot = airplanes.snapshot();
+        assert_eq!(snapshot.len(), 2);
+        let state = airplanes.get(ICAO([0x40, 0x62, 0x1d])).unwrap();
+        assert_eq!(state.num_messages, 100);
+        assert_eq!(state.callsign.as_deref(), Some("KLM1023"));
+        assert_eq!(airplanes.read(Airplanes::len), 2);
+    }
+
+    #[test]
+    fn not_poisoned() {
+        let airplanes = SharedAirplanes::default();
+        let cloned = airplanes.clone();
+        let result = thread::spawn(move || cloned.write(|_| panic!("panic while locked"))).join();
+        assert!(result.is_err());
+        assert!(airplanes.snapshot().is_empty());
+    }
+}
@@ -0,0 +1,231 @@
+//! Broadcast of [`Airplanes`] snapshots to many consumers
+//!
+//! A [`SnapshotPublisher`] sends immutable [`AirplanesSnapshot`] to any amount of
+//! [`SnapshotReceiver`], which always see the latest snapshot (like a watch channel). Consumers
+//! such as a UI, exporters and servers render from their own `Arc` without holding any lock.
+//!
+//! ```rust
+//! use std::time::Duration;
+//!
+//! use adsb_deku::Frame;
+//! use rsadsb_common::snapshot::SnapshotPublisher;
+//! use rsadsb_common::Airplanes;
+//!
+//! let mut airplanes = Airplanes::default();
+//! let publisher = SnapshotPublisher::new();
+//! let mut receiver = publisher.subscribe();
+//!
+//! let consumer = std::thread::spawn(move || {
+//!     let snapshot = receiver.wait_changed(Duration::from_secs(10)).unwrap();
+//!     snapshot.airplanes.len()
+//! });
+//!
+//! let bytes = [0x8d, 0xa0, 0x8f, 0x94, 0x23, 0x3d, 0x17, 0x75, 0xe7, 0xe9, 0xe0, 0x00, 0x00, 0x00];
+//! airplanes.action(Frame::from_bytes(&bytes).unwrap());
+//! publisher.publish(&airplanes);
+//!
+//! assert_eq!(consumer.join().unwrap(), 1);
+//! ```
+//!
+//! With a [`SharedAirplanes`], [`SharedAirplanes::publish_every`] publishes periodically from a
+//! thread instead.
+
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, PoisonError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::shared::SharedAirplanes;
+use crate::Airplanes;
+
+/// Immutable copy of [`Airplanes`] sent by a [`SnapshotPublisher`]
+#[derive(Debug, Clone)]
+pub struct AirplanesSnapshot {
+    /// Incremented by each [`SnapshotPublisher::publish`], 0 for the initial empty snapshot
+    pub version: u64,
+    /// Time of the snapshot
+    pub taken: Instant,
+    pub airplanes: Airplanes,
+}
+
+#[derive(Debug)]
+struct Channel {
+    latest: Mutex<Arc<AirplanesSnapshot>>,
+    changed: Condvar,
+}
+
+impl Channel {
+    fn lock(&self) -> MutexGuard<'_, Arc<AirplanesSnapshot>> {
+        self.latest.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Sending side of the snapshots, see [`self`]
+#[derive(Debug)]
+pub struct SnapshotPublisher {
+    channel: Arc<Channel>,
+}
+
+impl Default for SnapshotPublisher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SnapshotPublisher {
+    /// Publisher with an initial empty snapshot
+    #[must_use]
+    pub fn new() -> Self {
+        let snapshot = AirplanesSnapshot {
+            version: 0,
+            taken: Instant::now(),
+            airplanes: Airplanes::default(),
+        };
+        Self {
+            channel: Arc::new(Channel {
+                latest: Mutex::new(Arc::new(snapshot)),
+                changed: Condvar::new(),
+            }),
+        }
+    }
+
+    /// New receiver, which has seen the current snapshot
+    #[must_use]
+    pub fn subscribe(&self) -> SnapshotReceiver {
+        let seen = self.channel.lock().version;
+        SnapshotReceiver { channel: Arc::clone(&self.channel), seen }
+    }
+
+    /// Send a copy of `airplanes` to all receivers
+    pub fn publish(&self, airplanes: &Airplanes) {
+        let mut latest = self.channel.lock();
+        *latest = Arc::new(AirplanesSnapshot {
+            version: latest.version + 1,
+            taken: Instant::now(),
+            airplanes: airplanes.clone(),
+        });
+        drop(latest);
+        self.channel.changed.notify_all();
+    }
+
+    /// Amount of [`SnapshotReceiver`] not yet dropped
+    #[must_use]
+    pub fn receiver_count(&self) -> usize {
+        Arc::strong_count(&self.channel) - 1
+    }
+}
+
+/// Receiving side of the snapshots, see [`self`]
+///
+/// Clones have seen the same snapshots as the original.
+#[derive(Debug, Clone)]
+pub struct SnapshotReceiver {
+    channel: Arc<Channel>,
+    /// version of the last snapshot returned
+    seen: u64,
+}
+
+impl SnapshotReceiver {
+    /// Latest snapshot, marking it as seen
+    #[must_use]
+    pub fn latest(&mut self) -> Arc<AirplanesSnapshot> {
+        let latest = Arc::clone(&self.channel.lock());
+        self.seen = latest.version;
+        latest
+    }
+
+    /// Return true if a snapshot was published since the last one seen
+    #[must_use]
+    pub fn has_changed(&self) -> bool {
+        self.channel.lock().version != self.seen
+    }
+
+    /// Wait at most `timeout` for a snapshot that wasn't seen yet
+    ///
+    /// Returns `None` on timeout. Snapshots published in between are skipped, only the latest is
+    /// returned.
+    pub fn wait_changed(&mut self, timeout: Duration) -> Option<Arc<AirplanesSnapshot>> {
+        let seen = self.seen;
+        let (latest, _) = self
+            .channel
+            .changed
+            .wait_timeout_while(self.channel.lock(), timeout, |latest| latest.version == seen)
+            .unwrap_or_else(PoisonError::into_inner);
+        if latest.version == seen {
+            return None;
+        }
+        let latest = Arc::clone(&latest);
+        self.seen = latest.version;
+        Some(latest)
+    }
+}
+
+impl SharedAirplanes {
+    /// Publish a snapshot every `interval` from a new thread, until all receivers are dropped
+    #[must_use]
+    pub fn publish_every(&self, interval: Duration) -> SnapshotReceiver {
+        let publisher = SnapshotPublisher::new();
+        let receiver = publisher.subscribe();
+        let airplanes = self.clone();
+        thread::spawn(move || {
+            while publisher.receiver_count() > 0 {
+                airplanes.read(|airplanes| publisher.publish(airplanes));
+                thread::sleep(interval);
+            }
+        });
+        receiver
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use adsb_deku::Frame;
+
+    use super::*;
+
+    const FRAME: [u8; 14] =
+        [0x8d, 0xa0, 0x8f, 0x94, 0x23, 0x3d, 0x17, 0x75, 0xe7, 0xe9, 0xe0, 0x00, 0x00, 0x00];
+
+    #[test]
+    fn publish() {
+        let publisher = SnapshotPublisher::new();
+        let mut a = publisher.subscribe();
+        let mut b = a.clone();
+        assert_eq!(publisher.receiver_count(), 2);
+        assert!(!a.has_changed());
+        assert_eq!(a.wait_changed(Duration::from_millis(1)).map(|s| s.version), None);
+
+        let mut airplanes = Airplanes::default();
+        airplanes.action(Frame::from_bytes(&FRAME).unwrap());
+        publisher.publish(&airplanes);
+        publisher.publish(&airplanes);
+        assert!(a.has_changed());
+
+        // only the latest is received, by all receivers
+        let snapshot = a.wait_changed(Duration::from_secs(1)).unwrap();
+        assert_eq!(snapshot.version, 2);
+        assert_eq!(snapshot.airplanes.len(), 1);
+        assert!(!a.has_changed());
+        assert!(Arc::ptr_eq(&snapshot, &b.latest()));
+
+        // snapshots are not updated afterwards
+        let bytes =
+            [0x8d, 0x40, 0x62, 0x1d, 0x20, 0x2c, 0xc3, 0x71, 0xc3, 0x2c, 0xe0, 0x57, 0x60, 0x98];
+        airplanes.action(Frame::from_bytes(&bytes).unwrap());
+        assert_eq!(airplanes.len(), 2);
+        assert_eq!(snapshot.airplanes.len(), 1);
+
+        drop(b);
+        assert_eq!(publisher.receiver_count(), 1);
+    }
+
+    #[test]
+    fn publish_every() {
+        let airplanes = SharedAirplanes::default();
+        airplanes.action(Frame::from_bytes(&FRAME).unwrap());
+        let mut receiver = airplanes.publish_every(Duration::from_millis(10));
+        let snapshot = receiver.wait_changed(Duration::from_secs(10)).unwrap();
+        assert_eq!(snapshot.airplanes.len(), 1);
+        let next = receiver.wait_changed(Duration::from_secs(10)).unwrap();
+        assert!(next.version > snapshot.version);
+    }
+}
@@ -0,0 +1,206 @@
+//! Deterministic ordering and pagination of the aircraft, for tables and APIs
+//!
+//! [`Airplanes::sorted`] orders the aircraft by a [`SortKey`], with the aircraft without a value
+//! for the key always last and the ties in [`ICAO`] order, such that the same aircraft always
+//! give the same order. A [`Page`] then takes a part of them.
+//!
+//! ```rust
+//! use adsb_deku::Frame;
+//! use rsadsb_common::sort::{Page, SortKey, SortOrder};
+//! use rsadsb_common::Airplanes;
+//!
+//! let mut airplanes = Airplanes::default();
+//! let bytes = [0x8d, 0xa0, 0x8f, 0x94, 0x23, 0x3d, 0x17, 0x75, 0xe7, 0xe9, 0xe0, 0x00, 0x00, 0x00];
+//! airplanes.action(Frame::from_bytes(&bytes).unwrap());
+//!
+//! let page = Page { number: 0, size: 25 };
+//! let mut sorted = page.apply(airplanes.sorted(SortKey::Altitude, SortOrder::Descending));
+//! let (icao, _) = sorted.next().unwrap();
+//! assert_eq!(icao.to_string(), "a08f94");
+//! assert_eq!(page.count(airplanes.len()), 1);
+//! ```
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+#[cfg(feature = "alloc")]
+use core::{
+    clone::Clone,
+    cmp::{Eq, Ord, Ordering, PartialEq},
+    fmt::Debug,
+    iter::Iterator,
+    marker::Copy,
+    option::Option::{self, None, Some},
+    prelude::rust_2021::derive,
+};
+
+use adsb_deku::ICAO;
+
+use crate::{AirplaneState, Airplanes};
+
+/// Value ordering the aircraft in [`Airplanes::sorted`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SortKey {
+    Icao,
+    /// [`crate::AirplaneCoor::kilo_distance`] from the receiver
+    Distance,
+    /// [`crate::AirplaneCoor::altitude`]
+    Altitude,
+    /// Ground [`AirplaneState::speed`]
+    Speed,
+    /// Time since the last message, the most recently seen first in [`SortOrder::Ascending`]
+    #[cfg(feature = "std")]
+    LastSeen,
+    Callsign,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SortOrder {
+    Ascending,
+    Descending,
+}
+
+impl SortKey {
+    /// Order of `a` and `b` by this key, `Equal` for [`SortKey::Icao`]
+    fn cmp(self, a: &AirplaneState, b: &AirplaneState, order: SortOrder) -> Ordering {
+        match self {
+            Self::Icao => Ordering::Equal,
+            Self::Distance => {
+                cmp_known(a.coords.kilo_distance, b.coords.kilo_distance, order, f64::total_cmp)
+            }
+            Self::Altitude => cmp_known(a.coords.altitude(), b.coords.altitude(), order, Ord::cmp),
+            Self::Speed => cmp_known(a.speed, b.speed, order, f32::total_cmp),
+            #[cfg(feature = "std")]
+            Self::LastSeen => cmp_known(Some(a.seen()), Some(b.seen()), order, Ord::cmp),
+            Self::Callsign => {
+                cmp_known(a.callsign.as_ref(), b.callsign.as_ref(), order, |a, b| a.cmp(b))
+            }
+        }
+    }
+}
+
+/// Order of the known `a` and `b` with `cmp`, before the unknown values whatever the `order`
+fn cmp_known<T>(
+    a: Option<T>,
+    b: Option<T>,
+    order: SortOrder,
+    cmp: impl FnOnce(&T, &T) -> Ordering,
+) -> Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) => match order {
+            SortOrder::Ascending => cmp(&a, &b),
+            SortOrder::Descending => cmp(&b, &a),
+        },
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }
+}
+
+/// Page `number` (from 0) of `size` items
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Page {
+    pub number: usize,
+    pub size: usize,
+}
+
+impl Page {
+    /// Page of `size` items containing the item at `index`
+    #[must_use]
+    pub fn containing(index: usize, size: usize) -> Self {
+        Self { number: index / size.max(1), size }
+    }
+
+    /// Amount of pages of `total` items, at least 1
+    #[must_use]
+    pub fn count(&self, total: usize) -> usize {
+        total.div_ceil(self.size.max(1)).max(1)
+    }
+
+    /// Index of the first item of this page
+    #[must_use]
+    pub fn start(&self) -> usize {
+        self.number * self.size
+    }
+
+    /// Items of this page
+    pub fn apply<I: Iterator>(&self, items: I) -> core::iter::Take<core::iter::Skip<I>> {
+        items.skip(self.start()).take(self.size)
+    }
+}
+
+impl Airplanes {
+    /// Aircraft ordered `by` in `order`, see [`crate::sort`]
+    ///
+    /// The aircraft without a value for `by` are last, and the ties are in [`ICAO`] order.
+    pub fn sorted(
+        &self,
+        by: SortKey,
+        order: SortOrder,
+    ) -> impl Iterator<Item = (ICAO, &AirplaneState)> {
+        let mut airplanes: Vec<_> = self.iter().map(|(icao, state)| (*icao, state)).collect();
+        airplanes.sort_by(|(a_icao, a), (b_icao, b)| {
+            let icao = match (by, order) {
+                (SortKey::Icao, SortOrder::Descending) => b_icao.cmp(a_icao),
+                _ => a_icao.cmp(b_icao),
+            };
+            by.cmp(a, b, order).then(icao)
+        });
+        airplanes.into_iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+
+    use super::*;
+
+    fn airplanes() -> Airplanes {
+        let mut airplanes = Airplanes::new();
+        for (i, (altitude, callsign)) in [
+            (Some(5000), None),
+            (None, Some("KLM1")),
+            (Some(38000), Some("AAL2")),
+            (Some(5000), None),
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            let state = airplanes.airplanes.entry(ICAO([0, 0, i as u8])).or_default();
+            state.coords.sbs_altitude = altitude;
+            state.callsign = callsign.map(ToString::to_string);
+        }
+        airplanes
+    }
+
+    fn icaos(airplanes: &Airplanes, by: SortKey, order: SortOrder) -> Vec<u8> {
+        airplanes.sorted(by, order).map(|(icao, _)| icao.0[2]).collect()
+    }
+
+    #[test]
+    fn sorted() {
+        let airplanes = airplanes();
+        assert_eq!(icaos(&airplanes, SortKey::Icao, SortOrder::Ascending), [0, 1, 2, 3]);
+        assert_eq!(icaos(&airplanes, SortKey::Icao, SortOrder::Descending), [3, 2, 1, 0]);
+        // unknown last, ties by ICAO
+        assert_eq!(icaos(&airplanes, SortKey::Altitude, SortOrder::Ascending), [0, 3, 2, 1]);
+        assert_eq!(icaos(&airplanes, SortKey::Altitude, SortOrder::Descending), [2, 0, 3, 1]);
+        assert_eq!(icaos(&airplanes, SortKey::Callsign, SortOrder::Ascending), [2, 1, 0, 3]);
+        assert_eq!(icaos(&airplanes, SortKey::Speed, SortOrder::Descending), [0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn pages() {
+        let page = Page::containing(5, 2);
+        assert_eq!(page, Page { number: 2, size: 2 });
+        assert_eq!(page.start(), 4);
+        assert_eq!(page.apply(0..7).collect::<Vec<_>>(), [4, 5]);
+        assert_eq!(Page { number: 3, size: 2 }.apply(0..7).collect::<Vec<_>>(), [6]);
+        assert_eq!(page.count(7), 4);
+        assert_eq!(page.count(0), 1);
+        assert_eq!(Page::containing(5, 0).count(3), 3);
+    }
+}
@@ -0,0 +1,78 @@
+//! Squawk (Mode A identity code) of the aircraft
+//!
+//! The frames carry the 4 octal digits of the identity code, which [`adsb_deku::IdentityCode`]
+//! and [`AirplaneState::squawk`] hold as nibbles, such as `0x7700` for 7700, displayed with
+//! `{:04x}`. The SBS (BaseStation) messages use the decimal number instead, converted with
+//! [`to_decimal`] and [`from_decimal`].
+//!
+//! ```rust
+//! use rsadsb_common::squawk;
+//!
+//! assert_eq!(squawk::to_decimal(0x7700), 7700);
+//! assert_eq!(squawk::from_decimal(7700), 0x7700);
+//! assert!(squawk::is_emergency(0x7700));
+//! assert!(!squawk::is_emergency(7700));
+//! assert_eq!(format!("{:04x}", squawk::from_decimal(356)), "0356");
+//! ```
+//!
+//! [`AirplaneState::squawk`]: crate::AirplaneState::squawk
+
+#[cfg(feature = "alloc")]
+use core::iter::Iterator;
+
+/// Unlawful interference
+pub const HIJACK: u32 = 0x7500;
+
+/// Radio failure
+pub const RADIO_FAILURE: u32 = 0x7600;
+
+/// General emergency
+pub const EMERGENCY: u32 = 0x7700;
+
+/// The `squawk` (nibbles) is [`HIJACK`], [`RADIO_FAILURE`] or [`EMERGENCY`]
+#[must_use]
+pub const fn is_emergency(squawk: u32) -> bool {
+    matches!(squawk, HIJACK | RADIO_FAILURE | EMERGENCY)
+}
+
+/// Decimal number of the `squawk` (nibbles), such as 7000 for `0x7000`
+#[must_use]
+pub fn to_decimal(squawk: u32) -> u32 {
+    (0..4).rev().fold(0, |decimal, digit| decimal * 10 + ((squawk >> (digit * 4)) & 0xf))
+}
+
+/// The `decimal` number is a squawk: 4 octal digits, from 0 to 7777
+#[must_use]
+pub fn is_valid_decimal(decimal: u32) -> bool {
+    decimal <= 7777 && (0..4).all(|digit| decimal / 10_u32.pow(digit) % 10 < 8)
+}
+
+/// Squawk (nibbles) of the `decimal` number, such as `0x7000` for 7000
+///
+/// `decimal` must be valid, see [`is_valid_decimal`].
+#[must_use]
+pub fn from_decimal(decimal: u32) -> u32 {
+    (0..4).fold(0, |squawk, digit| squawk | ((decimal / 10_u32.pow(digit) % 10) << (digit * 4)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn roundtrip() {
+        for decimal in [0, 7, 356, 1200, 7000, 7500, 7777] {
+            assert_eq!(to_decimal(from_decimal(decimal)), decimal);
+        }
+        assert_eq!(from_decimal(1200), 0x1200);
+        assert_eq!(to_decimal(0x0356), 356);
+        assert!(is_emergency(from_decimal(7600)));
+        assert!(!is_emergency(0x7000));
+
+        assert!(is_valid_decimal(7777));
+        assert!(is_valid_decimal(356));
+        assert!(!is_valid_decimal(7800));
+        assert!(!is_valid_decimal(19));
+        assert!(!is_valid_decimal(10000));
+    }
+}
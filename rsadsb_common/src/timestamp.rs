@@ -0,0 +1,121 @@
+//! Human readable timestamps, with feature `time`
+//!
+//! Such as the [`AirplaneState::last_time`](crate::AirplaneState::last_time) of an aircraft:
+//! ```rust
+//! use std::time::{Duration, SystemTime};
+//!
+//! use rsadsb_common::timestamp;
+//!
+//! let time = SystemTime::UNIX_EPOCH + Duration::from_millis(1_725_278_400_123);
+//! assert_eq!(timestamp::iso8601(time), "2024-09-02T12:00:00.123Z");
+//! ```
+
+use std::time::SystemTime;
+
+use time::format_description::well_known::Rfc3339;
+use time::{format_description, OffsetDateTime, UtcOffset};
+
+/// `time` in UTC
+#[must_use]
+pub fn utc(time: SystemTime) -> OffsetDateTime {
+    OffsetDateTime::from(time)
+}
+
+/// `time` in the local time of `offset`, such as from [`UtcOffset::current_local_offset`]
+#[must_use]
+pub fn local(time: SystemTime, offset: UtcOffset) -> OffsetDateTime {
+    utc(time).to_offset(offset)
+}
+
+/// ISO 8601 (RFC 3339) UTC string of `time`, with millisecond precision
+#[must_use]
+pub fn iso8601(time: SystemTime) -> String {
+    let time = utc(time);
+    // trim to milliseconds, keeping the output short and stable
+    let time = time.replace_millisecond(time.millisecond()).unwrap_or(time);
+    time.format(&Rfc3339).unwrap_or_default()
+}
+
+/// (date, time) of `time` for [`crate::sbs::SbsMessage::to_line`], such as
+/// `("2024/09/02", "12:00:00.123")`
+#[must_use]
+pub fn sbs(time: OffsetDateTime) -> (String, String) {
+    let format = |description| {
+        let description = format_description::parse(description).unwrap_or_default();
+        time.format(&description).unwrap_or_default()
+    };
+    (format("[year]/[month]/[day]"), format("[hour]:[minute]:[second].[subsecond digits:3]"))
+}
+
+/// Serialize a [`SystemTime`] as an ISO 8601 string, with `#[serde(with = "...")]`
+///
+/// ```rust
+/// use std::time::SystemTime;
+///
+/// #[derive(serde::Serialize, serde::Deserialize)]
+/// struct Seen {
+///     #[serde(with = "rsadsb_common::timestamp::serde_iso8601")]
+///     last_time: SystemTime,
+/// }
+/// ```
+#[cfg(feature = "serde")]
+pub mod serde_iso8601 {
+    use std::time::SystemTime;
+
+    use serde::{de, Deserialize, Deserializer, Serializer};
+    use time::format_description::well_known::Rfc3339;
+    use time::OffsetDateTime;
+
+    pub fn serialize<S: Serializer>(time: &SystemTime, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&super::iso8601(*time))
+    }
+
+    /// Owned string, since readers such as `serde_json::from_reader` can't lend a `&str`
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<SystemTime, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        let time = OffsetDateTime::parse(&s, &Rfc3339).map_err(de::Error::custom)?;
+        Ok(time.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn local_offset() {
+        let time = SystemTime::UNIX_EPOCH + Duration::from_secs(1_725_278_400);
+        let local = local(time, UtcOffset::from_hms(-5, 0, 0).unwrap());
+        assert_eq!(local.hour(), 7);
+        assert_eq!(local.format(&Rfc3339).unwrap(), "2024-09-02T07:00:00-05:00");
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn iso8601_reader() {
+        #[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+        struct Seen {
+            #[serde(with = "serde_iso8601")]
+            last_time: SystemTime,
+        }
+
+        let seen = Seen { last_time: SystemTime::UNIX_EPOCH + Duration::from_secs(1_725_278_400) };
+        let json = serde_json::to_string(&seen).unwrap();
+        assert_eq!(json, r#"{"last_time":"2024-09-02T12:00:00Z"}"#);
+        let read: Seen = serde_json::from_reader(json.as_bytes()).unwrap();
+        assert_eq!(read, seen);
+        // escaped, such that the string can't be borrowed from the input
+        let read: Seen =
+            serde_json::from_str(r#"{"last_time":"2024-09-02T12:00:00\u005a"}"#).unwrap();
+        assert_eq!(read, seen);
+    }
+
+    #[test]
+    fn sbs_date_time() {
+        let time = SystemTime::UNIX_EPOCH + Duration::from_millis(1_725_278_400_123);
+        let (date, time) = sbs(utc(time));
+        assert_eq!((date.as_str(), time.as_str()), ("2024/09/02", "12:00:00.123"));
+    }
+}
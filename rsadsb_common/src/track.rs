@@ -0,0 +1,188 @@
+//! Simplification and smoothing of the tracks of previous positions, before rendering or export
+//!
+//! Long tracks are reduced with the Douglas-Peucker algorithm, keeping only the positions further
+//! than a tolerance from the simplified line, and the zig-zags of jittery CPR fixes are smoothed
+//! with Chaikin's corner cutting (converging to a quadratic B-spline). Both keep the first and
+//! last positions, such that the track still ends at the aircraft.
+//!
+//! ```rust
+//! use adsb_deku::cpr::Position;
+//! use rsadsb_common::track::TrackFilter;
+//!
+//! let track: Vec<Position> = (0..100)
+//!     .map(|i| Position { latitude: 35.0 + f64::from(i) * 0.001, longitude: -80.0 })
+//!     .collect();
+//! let filter = TrackFilter { tolerance: 0.05, smoothing: 0 };
+//! assert_eq!(filter.apply(&track).len(), 2);
+//! ```
+
+#[cfg(feature = "alloc")]
+use alloc::{vec, vec::Vec};
+#[cfg(feature = "alloc")]
+use core::{
+    clone::Clone,
+    default::Default,
+    fmt::Debug,
+    iter::{Extend, Iterator},
+    marker::Copy,
+    option::Option::Some,
+    prelude::rust_2021::derive,
+};
+
+use adsb_deku::cpr::Position;
+
+use crate::geo::EARTH_RADIUS;
+
+/// Simplification and smoothing of a track, see [`self`]
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TrackFilter {
+    /// Max distance (km) of the removed positions from the simplified track, 0 to keep all
+    pub tolerance: f64,
+    /// Iterations of Chaikin's corner cutting, 0 to keep the corners
+    pub smoothing: u8,
+}
+
+impl TrackFilter {
+    /// Neither simplifies nor smooths
+    #[must_use]
+    pub fn is_disabled(&self) -> bool {
+        self.tolerance <= 0.0 && self.smoothing == 0
+    }
+
+    /// [`simplify`] then [`smooth`] `track`
+    #[must_use]
+    pub fn apply(&self, track: &[Position]) -> Vec<Position> {
+        let simplified = simplify(track, self.tolerance);
+        smooth(&simplified, self.smoothing)
+    }
+}
+
+/// Douglas-Peucker simplification of `track`, removing the positions within `tolerance` (km) of
+/// the simplified track
+#[must_use]
+pub fn simplify(track: &[Position], tolerance: f64) -> Vec<Position> {
+    if track.len() <= 2 || tolerance <= 0.0 {
+        return track.to_vec();
+    }
+
+    // the distances are small, use an equirectangular projection around the first position
+    let cos_lat = libm::cos(track[0].latitude.to_radians());
+    let km_per_degree = EARTH_RADIUS.to_radians();
+    let points: Vec<(f64, f64)> = track
+        .iter()
+        .map(|p| (p.longitude * cos_lat * km_per_degree, p.latitude * km_per_degree))
+        .collect();
+
+    let mut keep = vec![false; track.len()];
+    keep[0] = true;
+    keep[track.len() - 1] = true;
+    // without recursion, for the tracks of aircraft seen for hours
+    let mut segments = vec![(0, track.len() - 1)];
+    while let Some((first, last)) = segments.pop() {
+        let mut furthest = (0.0, first);
+        for i in first + 1..last {
+            let distance = segment_distance(points[i], points[first], points[last]);
+            if distance > furthest.0 {
+                furthest = (distance, i);
+            }
+        }
+        let (distance, i) = furthest;
+        if distance > tolerance {
+            keep[i] = true;
+            segments.extend([(first, i), (i, last)]);
+        }
+    }
+    track.iter().zip(keep).filter(|(_, keep)| *keep).map(|(p, _)| *p).collect()
+}
+
+/// Distance from `p` to the segment from `a` to `b`
+fn segment_distance(p: (f64, f64), a: (f64, f64), b: (f64, f64)) -> f64 {
+    let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+    let length = dx * dx + dy * dy;
+    let t = if length == 0.0 {
+        0.0
+    } else {
+        (((p.0 - a.0) * dx + (p.1 - a.1) * dy) / length).clamp(0.0, 1.0)
+    };
+    let (x, y) = (a.0 + t * dx - p.0, a.1 + t * dy - p.1);
+    libm::sqrt(x * x + y * y)
+}
+
+/// Chaikin's corner cutting of `track`, `iterations` times
+///
+/// Each iteration replaces each segment by its points at 1/4 and 3/4, doubling the positions.
+#[must_use]
+pub fn smooth(track: &[Position], iterations: u8) -> Vec<Position> {
+    let mut track = track.to_vec();
+    if track.len() <= 2 {
+        return track;
+    }
+    let lerp = |a: &Position, b: &Position, t: f64| Position {
+        latitude: a.latitude + (b.latitude - a.latitude) * t,
+        longitude: a.longitude + (b.longitude - a.longitude) * t,
+    };
+    for _ in 0..iterations {
+        let mut smoothed = Vec::with_capacity(track.len() * 2);
+        smoothed.push(track[0]);
+        for pair in track.windows(2) {
+            smoothed.push(lerp(&pair[0], &pair[1], 0.25));
+            smoothed.push(lerp(&pair[0], &pair[1], 0.75));
+        }
+        smoothed.push(track[track.len() - 1]);
+        track = smoothed;
+    }
+    track
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn position(latitude: f64, longitude: f64) -> Position {
+        Position { latitude, longitude }
+    }
+
+    #[test]
+    fn simplify_tolerance() {
+        // a corner, with a 0.0001° (~11 m) zig-zag on each leg
+        let mut track = vec![];
+        for i in 0..=10 {
+            let jitter = if i % 2 == 0 { 0.0 } else { 0.0001 };
+            track.push(position(35.0 + f64::from(i) * 0.01, -80.0 + jitter));
+        }
+        for i in 1..=10 {
+            let jitter = if i % 2 == 0 { 0.0 } else { 0.0001 };
+            track.push(position(35.1 + jitter, -80.0 + f64::from(i) * 0.01));
+        }
+        assert_eq!(simplify(&track, 0.05), [track[0], track[10], track[20]]);
+        // under the jitter
+        assert_eq!(simplify(&track, 0.005).len(), track.len());
+        assert_eq!(simplify(&track, 0.0), track);
+        assert_eq!(simplify(&track[..2], 1.0), &track[..2]);
+    }
+
+    #[test]
+    fn smooth_corner() {
+        let track = [position(0.0, 0.0), position(1.0, 0.0), position(1.0, 1.0)];
+        let smoothed = smooth(&track, 1);
+        assert_eq!(
+            smoothed,
+            [
+                position(0.0, 0.0),
+                position(0.25, 0.0),
+                position(0.75, 0.0),
+                position(1.0, 0.25),
+                position(1.0, 0.75),
+                position(1.0, 1.0),
+            ]
+        );
+        assert_eq!(smooth(&track, 2).len(), 12);
+        assert_eq!(smooth(&track, 0), track);
+
+        let filter = TrackFilter { tolerance: 0.0, smoothing: 1 };
+        assert!(!filter.is_disabled());
+        assert_eq!(filter.apply(&track), smoothed);
+        assert!(TrackFilter::default().is_disabled());
+    }
+}
@@ -0,0 +1,174 @@
+//! Wind and temperature estimation from ADS-B and Comm-B Enhanced Surveillance (EHS) data
+//!
+//! The wind is the difference between the ground vector (track and ground speed) and the air
+//! vector (heading and true airspeed). The temperature is calculated from the speed of sound,
+//! using the true airspeed and mach number.
+
+#[cfg(feature = "alloc")]
+use alloc::collections::BTreeMap;
+#[cfg(feature = "alloc")]
+use core::{
+    clone::Clone,
+    default::Default,
+    fmt::Debug,
+    iter::Iterator,
+    marker::Copy,
+    option::Option::{self, None, Some},
+    prelude::rust_2021::derive,
+};
+
+use crate::Airplanes;
+
+/// Speed of sound (kt) at sea level in the International Standard Atmosphere
+const SPEED_OF_SOUND_ISA: f64 = 661.47;
+
+/// Temperature (kelvin) at sea level in the International Standard Atmosphere
+const TEMPERATURE_ISA: f64 = 288.15;
+
+/// Estimated wind
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Wind {
+    /// Direction the wind is blowing from (degrees), 0 to 360
+    pub direction: f32,
+    /// Wind speed (kt)
+    pub speed: f32,
+}
+
+impl Wind {
+    /// Calculate the wind from the ground vector and the air vector
+    ///
+    /// Angles are in degrees and speeds are in kt. Since EHS only reports the magnetic heading,
+    /// this is used as an approximation of the true heading.
+    #[must_use]
+    pub fn from_vectors(track: f32, ground_speed: f32, heading: f32, airspeed: f32) -> Self {
+        let (ground_x, ground_y) = to_vector(track, ground_speed);
+        let (air_x, air_y) = to_vector(heading, airspeed);
+        Self::from_components(ground_x - air_x, ground_y - air_y)
+    }
+
+    /// Wind from the (east, north) components of the wind vector
+    fn from_components(x: f64, y: f64) -> Self {
+        let speed = libm::sqrt(x * x + y * y);
+        // direction is where the wind is coming from, opposite of the vector
+        let mut direction = libm::atan2(-x, -y).to_degrees();
+        if direction < 0.0 {
+            direction += 360.0;
+        }
+        Self { direction: direction as f32, speed: speed as f32 }
+    }
+
+    /// (east, north) components of the wind vector
+    fn components(&self) -> (f64, f64) {
+        let (x, y) = to_vector(self.direction, self.speed);
+        (-x, -y)
+    }
+}
+
+/// (east, north) components of a vector
+fn to_vector(angle: f32, speed: f32) -> (f64, f64) {
+    let angle = f64::from(angle).to_radians();
+    let speed = f64::from(speed);
+    (speed * libm::sin(angle), speed * libm::cos(angle))
+}
+
+/// Calculate the static air temperature (celsius) from true airspeed (kt) and mach number
+#[must_use]
+pub fn temperature(true_airspeed: f32, mach: f32) -> Option<f32> {
+    if mach <= 0.0 || true_airspeed <= 0.0 {
+        return None;
+    }
+    let speed_of_sound = f64::from(true_airspeed) / f64::from(mach);
+    let ratio = speed_of_sound / SPEED_OF_SOUND_ISA;
+    let kelvin = TEMPERATURE_ISA * ratio * ratio;
+    Some((kelvin - 273.15) as f32)
+}
+
+/// Average wind of a single altitude band within [`WindModel`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct WindBand {
+    /// Vector average of all estimated winds within this band
+    pub wind: Wind,
+    /// Amount of aircraft used for the average
+    pub samples: u32,
+}
+
+/// Wind aggregated from all aircraft into altitude bands
+#[derive(Debug, Default, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct WindModel {
+    /// Height of each band (ft)
+    pub band_height: u32,
+    /// Key is the lowest altitude (ft) of the band
+    pub bands: BTreeMap<i32, WindBand>,
+}
+
+impl WindModel {
+    /// Aggregate the estimated wind of every aircraft in `airplanes` into `band_height` ft bands
+    #[must_use]
+    pub fn from_airplanes(airplanes: &Airplanes, band_height: u32) -> Self {
+        let band_height = band_height.max(1);
+        let mut sums: BTreeMap<i32, (f64, f64, u32)> = BTreeMap::new();
+        for (_, state) in airplanes.iter() {
+            let (Some(wind), Some(altitude)) = (state.estimate_wind(), state.coords.altitude())
+            else {
+                continue;
+            };
+            let band = altitude.div_euclid(band_height as i32) * band_height as i32;
+            let (x, y) = wind.components();
+            let sum = sums.entry(band).or_insert((0.0, 0.0, 0));
+            sum.0 += x;
+            sum.1 += y;
+            sum.2 += 1;
+        }
+
+        let bands = sums
+            .into_iter()
+            .map(|(band, (x, y, samples))| {
+                let n = f64::from(samples);
+                (band, WindBand { wind: Wind::from_components(x / n, y / n), samples })
+            })
+            .collect();
+
+        Self { band_height, bands }
+    }
+
+    /// Average wind of the band containing `altitude` (ft)
+    #[must_use]
+    pub fn get(&self, altitude: i32) -> Option<&WindBand> {
+        let band_height = self.band_height.max(1) as i32;
+        self.bands.get(&(altitude.div_euclid(band_height) * band_height))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wind_from_vectors() {
+        // heading north at 400 kt, blown east by a wind from the west
+        let wind = Wind::from_vectors(14.036_243, 412.310_56, 0.0, 400.0);
+        assert!((wind.direction - 270.0).abs() < 0.01);
+        assert!((wind.speed - 100.0).abs() < 0.01);
+
+        // headwind from the north
+        let wind = Wind::from_vectors(0.0, 350.0, 0.0, 400.0);
+        assert!(wind.direction.abs() < 0.01 || (wind.direction - 360.0).abs() < 0.01);
+        assert!((wind.speed - 50.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn temperature_from_mach() {
+        // standard atmosphere at sea level
+        let sea_level = temperature(661.47, 1.0).unwrap();
+        assert!((sea_level - 15.0).abs() < 0.01);
+
+        // FL350, ISA -54.3 celsius
+        let fl350 = temperature(461.0, 0.8).unwrap();
+        assert!((fl350 - -54.3).abs() < 1.0);
+
+        assert_eq!(temperature(458.0, 0.0), None);
+    }
+}
@@ -0,0 +1,120 @@
+//! Development tasks for the workspace, run with `cargo xtask <task>`
+//!
+//! `feature-matrix` builds and tests `adsb_deku` and `rsadsb_common` with each of the
+//! [`FEATURES`] combinations, then builds `ensure_no_std` with the nightly toolchain. The
+//! feature-gated code paths aren't compiled by `cargo test --workspace`, which only uses the
+//! default and unified features.
+
+use std::env;
+use std::path::PathBuf;
+use std::process::{Command, ExitCode};
+
+/// Feature combinations of each library, always with `--no-default-features`
+///
+/// `alloc` is required: `deku` doesn't build without it.
+const FEATURES: &[(&str, &[&str])] = &[
+    ("adsb_deku", &["std", "alloc", "std,serde", "alloc,serde", "std,defmt", "alloc,defmt"]),
+    (
+        "rsadsb_common",
+        &[
+            "std",
+            "alloc",
+            "std,serde",
+            "alloc,serde",
+            "time",
+            "alloc,svg",
+            "alloc,compact",
+            "std,serde,time,svg,compact",
+        ],
+    ),
+];
+
+const USAGE: &str = "\
+Usage: cargo xtask <task>
+
+Tasks:
+  feature-matrix [--no-test] [--skip-no-std]
+      Build and test adsb_deku and rsadsb_common with each feature combination, and build
+      ensure_no_std with the nightly toolchain";
+
+fn main() -> ExitCode {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let Some((task, options)) = args.split_first() else {
+        eprintln!("{USAGE}");
+        return ExitCode::FAILURE;
+    };
+    let result = match task.as_str() {
+        "feature-matrix" => feature_matrix(options),
+        _ => Err(format!("unknown task: {task}\n\n{USAGE}")),
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("xtask: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn feature_matrix(options: &[String]) -> Result<(), String> {
+    let mut test = true;
+    let mut no_std = true;
+    for option in options {
+        match option.as_str() {
+            "--no-test" => test = false,
+            "--skip-no-std" => no_std = false,
+            _ => return Err(format!("unknown option: {option}\n\n{USAGE}")),
+        }
+    }
+
+    let mut failures = vec![];
+    for (package, combinations) in FEATURES {
+        for features in *combinations {
+            let mut commands = vec!["build"];
+            if test {
+                commands.push("test");
+            }
+            for command in commands {
+                let args = [command, "--package", package, "--no-default-features", "--features"];
+                let mut cargo = cargo();
+                cargo.args(args).arg(features);
+                if !run(&mut cargo) {
+                    failures.push(format!("cargo {command} -p {package} --features {features}"));
+                }
+            }
+        }
+    }
+
+    if no_std {
+        let manifest = workspace_root().join("ensure_no_std/Cargo.toml");
+        let mut cargo = Command::new("cargo");
+        cargo.arg("+nightly").arg("build").arg("--manifest-path").arg(manifest);
+        if !run(&mut cargo) {
+            failures.push("cargo +nightly build (ensure_no_std)".to_string());
+        }
+    }
+
+    if failures.is_empty() {
+        Ok(())
+    } else {
+        Err(format!("{} failed:\n  {}", failures.len(), failures.join("\n  ")))
+    }
+}
+
+/// `cargo` running this xtask, in the workspace root
+fn cargo() -> Command {
+    let mut cargo = Command::new(env::var_os("CARGO").unwrap_or_else(|| "cargo".into()));
+    cargo.current_dir(workspace_root());
+    cargo
+}
+
+fn workspace_root() -> PathBuf {
+    let manifest_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    manifest_dir.parent().map(PathBuf::from).unwrap_or(manifest_dir)
+}
+
+/// Run `command`, returning whether it succeeded
+fn run(command: &mut Command) -> bool {
+    eprintln!("xtask: running {command:?}");
+    command.status().is_ok_and(|status| status.success())
+}